@@ -0,0 +1,75 @@
+/* A C API to load the `.llbc`/`.ullbc` files generated by Charon and traverse the translated
+ * crates.
+ *
+ * A crate is loaded with `charon_crate_load`, and its items are accessed by their index (from `0`
+ * to `charon_crate_num_items(krate) - 1`). The parts of the AST (bodies, signatures, etc.) are
+ * returned as JSON strings (with the same format as in the `.llbc` files) or pretty-printed.
+ *
+ * The strings returned by this API must be freed with `charon_string_free`. The functions which
+ * fail return `NULL` (or `-1`), and the error message can be retrieved with `charon_last_error`.
+ */
+#ifndef CHARON_H
+#define CHARON_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A loaded crate. */
+typedef struct CharonCrate CharonCrate;
+
+/* The kinds of items. */
+typedef enum CharonItemKind {
+  CHARON_ITEM_TYPE = 0,
+  CHARON_ITEM_FUN = 1,
+  CHARON_ITEM_GLOBAL = 2,
+  CHARON_ITEM_TRAIT_DECL = 3,
+  CHARON_ITEM_TRAIT_IMPL = 4,
+} CharonItemKind;
+
+/* Load a crate from a `.llbc` or `.ullbc` file. Returns `NULL` on failure. */
+CharonCrate *charon_crate_load(const char *path);
+
+/* Free a crate returned by `charon_crate_load`. */
+void charon_crate_free(CharonCrate *krate);
+
+/* Free a string returned by this API. */
+void charon_string_free(char *s);
+
+/* The message of the last error which happened on this thread, or `NULL`. The string is owned by
+ * the library and valid until the next call to this API. */
+const char *charon_last_error(void);
+
+/* The name of the crate. */
+char *charon_crate_name(const CharonCrate *krate);
+
+/* The number of items in the crate. */
+size_t charon_crate_num_items(const CharonCrate *krate);
+
+/* The kind of an item (a `CharonItemKind`). Returns `-1` if the index is invalid. */
+int32_t charon_item_kind(const CharonCrate *krate, size_t index);
+
+/* The fully-qualified name of an item. */
+char *charon_item_name(const CharonCrate *krate, size_t index);
+
+/* The pretty-printed item. */
+char *charon_item_to_string(const CharonCrate *krate, size_t index);
+
+/* The item, as JSON. This doesn't include the body of functions and globals (see
+ * `charon_item_body_to_json`). */
+char *charon_item_to_json(const CharonCrate *krate, size_t index);
+
+/* The body of a function or a global, as JSON. Returns `NULL` if the item doesn't have a body. */
+char *charon_item_body_to_json(const CharonCrate *krate, size_t index);
+
+/* The signature of a function, as JSON. Returns `NULL` if the item is not a function. */
+char *charon_item_signature_to_json(const CharonCrate *krate, size_t index);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CHARON_H */
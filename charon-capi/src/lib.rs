@@ -0,0 +1,297 @@
+//! A C API to load `.llbc`/`.ullbc` files and traverse the translated crates. See
+//! `include/charon.h` for the documentation of the functions.
+//!
+//! A loaded crate is accessed through an opaque `CharonCrate` handle, and its items through
+//! their index in the crate (from `0` to `charon_crate_num_items(krate) - 1`). The parts of the
+//! AST (bodies, types, signatures, etc.) are returned as JSON strings, with the same format as in
+//! the `.llbc` files, or pretty-printed.
+//!
+//! The strings returned by this API are owned by the caller, who must free them with
+//! `charon_string_free`. When a function fails, it returns `NULL` and the error can be retrieved
+//! with `charon_last_error`.
+#![allow(clippy::missing_safety_doc)]
+
+use charon_lib::ast::*;
+use charon_lib::formatter::IntoFormatter;
+use charon_lib::import::read_crate_data;
+use charon_lib::pretty::FmtWithCtx;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::path::Path;
+use std::ptr;
+
+/// A loaded crate.
+pub struct CharonCrate {
+    translated: TranslatedCrate,
+    /// The ids of the items, in the order in which they are exposed.
+    items: Vec<AnyTransId>,
+}
+
+/// The kinds of items. Must be kept in sync with `CharonItemKind` in `include/charon.h`.
+#[repr(C)]
+pub enum CharonItemKind {
+    Type = 0,
+    Fun = 1,
+    Global = 2,
+    TraitDecl = 3,
+    TraitImpl = 4,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(msg: impl ToString) {
+    let msg = CString::new(msg.to_string().replace('\0', "")).unwrap();
+    LAST_ERROR.with(|err| *err.borrow_mut() = Some(msg));
+}
+
+/// Convert a string into a C string owned by the caller.
+fn to_c_string(s: String) -> *mut c_char {
+    match CString::new(s) {
+        Ok(s) => s.into_raw(),
+        Err(err) => {
+            set_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+fn to_json(x: &impl serde::Serialize) -> *mut c_char {
+    match serde_json::to_string(x) {
+        Ok(s) => to_c_string(s),
+        Err(err) => {
+            set_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+impl CharonCrate {
+    fn item(&self, index: usize) -> Option<AnyTransItem<'_>> {
+        let item = self
+            .items
+            .get(index)
+            .and_then(|id| self.translated.get_item(*id));
+        if item.is_none() {
+            set_error(format!("invalid item index: {index}"));
+        }
+        item
+    }
+
+    /// The body of an item, if it is a function or a global with a body.
+    fn body(&self, index: usize) -> Option<&Body> {
+        let body_id = match self.item(index)? {
+            AnyTransItem::Fun(decl) => decl.body,
+            AnyTransItem::Global(decl) => decl.body,
+            _ => {
+                set_error(format!("item {index} is not a function or a global"));
+                return None;
+            }
+        };
+        let body = body_id.ok().and_then(|id| self.translated.bodies.get(id));
+        if body.is_none() {
+            set_error(format!("item {index} doesn't have a body"));
+        }
+        body
+    }
+}
+
+/// Load a crate from a `.llbc` or `.ullbc` file. Returns `NULL` on failure.
+#[no_mangle]
+pub unsafe extern "C" fn charon_crate_load(path: *const c_char) -> *mut CharonCrate {
+    let path = match CStr::from_ptr(path).to_str() {
+        Ok(path) => path,
+        Err(err) => {
+            set_error(err);
+            return ptr::null_mut();
+        }
+    };
+    let crate_data = match read_crate_data(Path::new(path)) {
+        Ok(crate_data) => crate_data,
+        Err(err) => {
+            set_error(format!("{err:#}"));
+            return ptr::null_mut();
+        }
+    };
+    let translated = crate_data.translated;
+    let items = translated.all_items_with_ids().map(|(id, _)| id).collect();
+    Box::into_raw(Box::new(CharonCrate { translated, items }))
+}
+
+/// Free a crate returned by `charon_crate_load`.
+#[no_mangle]
+pub unsafe extern "C" fn charon_crate_free(krate: *mut CharonCrate) {
+    if !krate.is_null() {
+        drop(Box::from_raw(krate));
+    }
+}
+
+/// Free a string returned by this API.
+#[no_mangle]
+pub unsafe extern "C" fn charon_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// The message of the last error which happened on this thread, or `NULL`. The string is owned
+/// by the library and valid until the next call to this API.
+#[no_mangle]
+pub extern "C" fn charon_last_error() -> *const c_char {
+    LAST_ERROR.with(|err| {
+        err.borrow()
+            .as_ref()
+            .map(|err| err.as_ptr())
+            .unwrap_or(ptr::null())
+    })
+}
+
+/// The name of the crate.
+#[no_mangle]
+pub unsafe extern "C" fn charon_crate_name(krate: *const CharonCrate) -> *mut c_char {
+    to_c_string((*krate).translated.crate_name.clone())
+}
+
+/// The number of items in the crate.
+#[no_mangle]
+pub unsafe extern "C" fn charon_crate_num_items(krate: *const CharonCrate) -> usize {
+    (*krate).items.len()
+}
+
+/// The kind of an item. Returns `-1` if the index is invalid.
+#[no_mangle]
+pub unsafe extern "C" fn charon_item_kind(krate: *const CharonCrate, index: usize) -> i32 {
+    match (*krate).item(index) {
+        Some(AnyTransItem::Type(_)) => CharonItemKind::Type as i32,
+        Some(AnyTransItem::Fun(_)) => CharonItemKind::Fun as i32,
+        Some(AnyTransItem::Global(_)) => CharonItemKind::Global as i32,
+        Some(AnyTransItem::TraitDecl(_)) => CharonItemKind::TraitDecl as i32,
+        Some(AnyTransItem::TraitImpl(_)) => CharonItemKind::TraitImpl as i32,
+        None => -1,
+    }
+}
+
+/// The fully-qualified name of an item.
+#[no_mangle]
+pub unsafe extern "C" fn charon_item_name(krate: *const CharonCrate, index: usize) -> *mut c_char {
+    let krate = &*krate;
+    let Some(item) = krate.item(index) else {
+        return ptr::null_mut();
+    };
+    let fmt_ctx = krate.translated.into_fmt();
+    to_c_string(item.item_meta().name.fmt_with_ctx(&fmt_ctx))
+}
+
+/// The pretty-printed item.
+#[no_mangle]
+pub unsafe extern "C" fn charon_item_to_string(
+    krate: *const CharonCrate,
+    index: usize,
+) -> *mut c_char {
+    let krate = &*krate;
+    if krate.item(index).is_none() {
+        return ptr::null_mut();
+    }
+    let fmt_ctx = krate.translated.into_fmt();
+    to_c_string(fmt_ctx.format_decl_id(krate.items[index]))
+}
+
+/// The item, as JSON. This doesn't include the body of functions and globals (see
+/// `charon_item_body_to_json`).
+#[no_mangle]
+pub unsafe extern "C" fn charon_item_to_json(
+    krate: *const CharonCrate,
+    index: usize,
+) -> *mut c_char {
+    match (*krate).item(index) {
+        Some(AnyTransItem::Type(d)) => to_json(d),
+        Some(AnyTransItem::Fun(d)) => to_json(d),
+        Some(AnyTransItem::Global(d)) => to_json(d),
+        Some(AnyTransItem::TraitDecl(d)) => to_json(d),
+        Some(AnyTransItem::TraitImpl(d)) => to_json(d),
+        None => ptr::null_mut(),
+    }
+}
+
+/// The body of a function or a global, as JSON. Returns `NULL` if the item doesn't have a body.
+#[no_mangle]
+pub unsafe extern "C" fn charon_item_body_to_json(
+    krate: *const CharonCrate,
+    index: usize,
+) -> *mut c_char {
+    match (*krate).body(index) {
+        Some(body) => to_json(body),
+        None => ptr::null_mut(),
+    }
+}
+
+/// The signature of a function, as JSON. Returns `NULL` if the item is not a function.
+#[no_mangle]
+pub unsafe extern "C" fn charon_item_signature_to_json(
+    krate: *const CharonCrate,
+    index: usize,
+) -> *mut c_char {
+    match (*krate).item(index) {
+        Some(AnyTransItem::Fun(d)) => to_json(&d.signature),
+        Some(_) => {
+            set_error(format!("item {index} is not a function"));
+            ptr::null_mut()
+        }
+        None => ptr::null_mut(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use charon_lib::export::{BuildInfo, CrateData};
+    use charon_lib::options::ExportProfile;
+
+    unsafe fn take_string(s: *mut c_char) -> String {
+        assert!(!s.is_null());
+        let string = CStr::from_ptr(s).to_str().unwrap().to_owned();
+        charon_string_free(s);
+        string
+    }
+
+    #[test]
+    fn load_and_read_name() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("test_crate.llbc");
+        let crate_data = CrateData {
+            charon_version: charon_lib::VERSION.to_owned(),
+            build_info: BuildInfo::current(),
+            translated: TranslatedCrate {
+                crate_name: "test_crate".to_owned(),
+                ..TranslatedCrate::default()
+            },
+            has_errors: false,
+        };
+        crate_data
+            .serialize_to_file(&path, ExportProfile::Default)
+            .unwrap();
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+        unsafe {
+            let krate = charon_crate_load(c_path.as_ptr());
+            assert!(!krate.is_null());
+            assert_eq!(take_string(charon_crate_name(krate)), "test_crate");
+            assert_eq!(charon_crate_num_items(krate), 0);
+            // Invalid indices are reported as errors.
+            assert!(charon_item_name(krate, 0).is_null());
+            let error = CStr::from_ptr(charon_last_error()).to_str().unwrap();
+            assert_eq!(error, "invalid item index: 0");
+            charon_crate_free(krate);
+        }
+    }
+
+    #[test]
+    fn load_missing_file() {
+        let c_path = CString::new("/nonexistent/test_crate.llbc").unwrap();
+        unsafe {
+            assert!(charon_crate_load(c_path.as_ptr()).is_null());
+            let error = CStr::from_ptr(charon_last_error()).to_str().unwrap();
+            assert!(error.contains("could not open"), "{error}");
+        }
+    }
+}
@@ -110,6 +110,21 @@ and unop =
 (** Nullary operation *)
 and nullop = SizeOf | AlignOf | OffsetOf of (int * field_id) list | UbChecks
 
+(** The layout of a type, as computed by rustc. *)
+and layout = {
+  size : int;  (** The size of the type, in bytes. *)
+  align : int;  (** The ABI alignment of the type, in bytes. *)
+  has_niche : bool;
+      (** Whether the type has a niche, i.e. invalid bit patterns that rustc may use to store the
+          discriminant of an enclosing enum.
+       *)
+}
+
+(** The layouts of the source and target types of a [CastKind::Transmute]. A layout is [None] when
+    it can't be computed, e.g. because the type depends on generic parameters.
+ *)
+and transmute_layouts = { src : layout option; tgt : layout option }
+
 (** For all the variants: the first type gives the source type, the second one gives
     the destination type.
  *)
@@ -119,6 +134,14 @@ and cast_kind =
           Remark: for now we don't support conversions with Char.
        *)
   | CastRawPtr of ty * ty
+  | CastExposeProvenance of ty * ty
+      (** Cast of a raw pointer to an integer which exposes the provenance of the pointer, so that it
+          can be recovered by a later [CastKind::WithExposedProvenance] (`ptr as usize`).
+       *)
+  | CastWithExposedProvenance of ty * ty
+      (** Cast of an integer to a raw pointer which picks up the provenance of a previously exposed
+          pointer (`addr as *const T`).
+       *)
   | CastFnPtr of ty * ty
   | CastUnsize of ty * ty
       (** [Unsize coercion](https://doc.rust-lang.org/std/ops/trait.CoerceUnsized.html). This is
@@ -127,9 +150,10 @@ and cast_kind =
 
           The special case of `&[T; N]` -> `&[T]` coercion is caught by `UnOp::ArrayToSlice`.
        *)
-  | CastTransmute of ty * ty
+  | CastTransmute of ty * ty * transmute_layouts
       (** Reinterprets the bits of a value of one type as another type, i.e. exactly what
-          [`std::mem::transmute`] does.
+          [`std::mem::transmute`] does. We record the layouts of the two types, which validity
+          checkers can use.
        *)
 
 (** Binary operations. *)
@@ -160,6 +184,12 @@ and binop =
   | Shl  (** Fails if the shift is bigger than the bit-size of the type. *)
   | Shr  (** Fails if the shift is bigger than the bit-size of the type. *)
 
+(** The types of the operands of a binary operation. They give the width of the operation, e.g. to
+    distinguish a `u128` addition from a `u32` one. The two types are the same, except for the
+    shifts whose right operand can have any integer type (e.g. `x << 3u32` with `x: u128`).
+ *)
+and binop_tys = { lhs_ty : ty; rhs_ty : ty }
+
 and operand =
   | Copy of place
   | Move of place
@@ -207,6 +237,8 @@ and assumed_fun_id =
           - `fn SliceSubSliceMut<T>(&mut [T], usize, usize) -> &mut [T]`
           - etc
        *)
+  | Async of async_builtin
+      (** An operation of the `async` runtime, recognized with `--async-builtins`. *)
 
 (** One of 8 built-in indexing operations. *)
 and builtin_index_op = {
@@ -222,6 +254,77 @@ and builtin_index_op = {
      *)
 }
 
+(** The operations of the `async` runtime which we treat as builtins (with `--async-builtins`), so
+    that the code which drives futures doesn't refer to opaque functions of the standard library.
+    `Ptr` is a pointer type and `T` the type it points to (`<Ptr as Deref>::Target`).
+ *)
+and async_builtin =
+  | FuturePoll
+      (** `core::future::Future::poll`:
+          `fn FuturePoll<F>[F: Future](Pin<&mut F>, &mut Context<'_>) -> Poll<F::Output>`.
+
+          Runs the future until it completes or has to wait. Unlike the other builtins, the generics
+          contain the trait reference which gives the implementation of `poll`.
+       *)
+  | PinNew
+      (** `core::pin::Pin::new`: `fn PinNew<Ptr>(Ptr) -> Pin<Ptr>`, where `T: Unpin`.
+
+          Wraps the pointer; this has no runtime effect.
+       *)
+  | PinNewUnchecked
+      (** `core::pin::Pin::new_unchecked`: `unsafe fn PinNewUnchecked<Ptr>(Ptr) -> Pin<Ptr>`.
+
+          Wraps the pointer; the caller promises that the pointee won't move until it is dropped.
+       *)
+  | PinAsRef  (** `core::pin::Pin::as_ref`: `fn PinAsRef<Ptr>(&Pin<Ptr>) -> Pin<&T>`. *)
+  | PinAsMut
+      (** `core::pin::Pin::as_mut`: `fn PinAsMut<Ptr>(&mut Pin<Ptr>) -> Pin<&mut T>`.
+
+          Reborrows the pinned pointer, typically to call [AsyncBuiltin::FuturePoll] several times.
+       *)
+  | PinGetRef  (** `core::pin::Pin::get_ref`: `fn PinGetRef<T>(Pin<&T>) -> &T`. *)
+  | PinGetMut
+      (** `core::pin::Pin::get_mut`: `fn PinGetMut<T>(Pin<&mut T>) -> &mut T`, where `T: Unpin`. *)
+  | PinGetUncheckedMut
+      (** `core::pin::Pin::get_unchecked_mut`: `unsafe fn PinGetUncheckedMut<T>(Pin<&mut T>) -> &mut T`.
+
+          The caller promises not to move the pointee out of the reference.
+       *)
+  | PinIntoInner
+      (** `core::pin::Pin::into_inner`: `fn PinIntoInner<Ptr>(Pin<Ptr>) -> Ptr`, where `T: Unpin`. *)
+  | PinMapUnchecked
+      (** `core::pin::Pin::map_unchecked`:
+          `unsafe fn PinMapUnchecked<T, U, F>[F: FnOnce(&T) -> &U](Pin<&T>, F) -> Pin<&U>`.
+
+          A pin projection: applies `F` to the pinned reference, typically to get a reference to a
+          field which is structurally pinned.
+       *)
+  | PinMapUncheckedMut
+      (** `core::pin::Pin::map_unchecked_mut`:
+          `unsafe fn PinMapUncheckedMut<T, U, F>[F: FnOnce(&mut T) -> &mut U](Pin<&mut T>, F) -> Pin<&mut U>`.
+
+          The mutable version of [AsyncBuiltin::PinMapUnchecked]; this is how a `poll` function
+          gets a pinned reference to the future stored in one of the fields of `Self`.
+       *)
+  | ContextFromWaker
+      (** `core::task::Context::from_waker`: `fn ContextFromWaker(&Waker) -> Context<'_>`.
+
+          Builds the context passed to [AsyncBuiltin::FuturePoll].
+       *)
+  | ContextWaker
+      (** `core::task::Context::waker`: `fn ContextWaker(&Context<'_>) -> &Waker`. *)
+  | WakerWake
+      (** `core::task::Waker::wake`: `fn WakerWake(Waker)`.
+
+          Signals the executor that the task which owns the waker can make progress again, and
+          consumes the waker.
+       *)
+  | WakerWakeByRef
+      (** `core::task::Waker::wake_by_ref`: `fn WakerWakeByRef(&Waker)`.
+
+          Like [AsyncBuiltin::WakerWake], without consuming the waker.
+       *)
+
 and fun_id_or_trait_method_ref =
   | FunId of fun_id
   | TraitMethod of trait_ref * trait_item_name * fun_decl_id
@@ -234,7 +337,9 @@ and fn_ptr = { func : fun_id_or_trait_method_ref; generics : generic_args }
 
 (** A constant expression.
 
-    Only the [Literal] and [Var] cases are left in the final LLBC.
+    By default, only the [Literal] and [Var] cases are left in the final LLBC. With
+    `--simplify-constants globals` or `--simplify-constants none`, the final LLBC may also contain
+    the [Adt], [Array], [Ref] and [MutPtr] cases (and [Global] with `none`).
 
     The other cases come from a straight translation from the MIR:
 
@@ -261,6 +366,41 @@ and fn_ptr = { func : fun_id_or_trait_method_ref; generics : generic_args }
  *)
 and raw_constant_expr =
   | CLiteral of literal
+  | CAdt of variant_id option * constant_expr list
+      (** 
+          In most situations:
+          Enumeration with one variant with no fields, structure with
+          no fields, unit (encoded as a 0-tuple).
+
+          Less frequently: arbitrary ADT values.
+
+          We eliminate this case in a micro-pass.
+       *)
+  | CArray of constant_expr list
+      (** A constant array, e.g. the value of a `const` of array type used in a pattern.
+
+          We eliminate this case in a micro-pass, unless `--simplify-constants none` is used.
+       *)
+  | CGlobal of global_decl_ref
+      (** The value is a top-level constant/static.
+
+          We eliminate this case in a micro-pass.
+
+          Remark: constants can actually have generic parameters.
+          ```text
+          struct V<const N: usize, T> {
+            x: [T; N],
+          }
+
+          impl<const N: usize, T> V<N, T> {
+            const LEN: usize = N; // This has generics <N, T>
+          }
+
+          fn use_v<const N: usize, T>(v: V<N, T>) {
+            let l = V::<N, T>::LEN; // We need to provided a substitution here
+          }
+          ```
+       *)
   | CTraitConst of trait_ref * trait_item_name
       (** 
           A trait constant.
@@ -275,10 +415,28 @@ and raw_constant_expr =
           Remark: trait constants can not be used in types, they are necessarily
           values.
        *)
+  | CRef of constant_expr
+      (** A shared reference to a constant value.
+
+          We eliminate this case in a micro-pass.
+       *)
+  | CMutPtr of constant_expr
+      (** A mutable pointer to a mutable static.
+
+          We eliminate this case in a micro-pass.
+       *)
   | CVar of const_generic_var_id  (** A const generic var *)
   | CFnPtr of fn_ptr  (** Function pointer *)
 
-and constant_expr = { value : raw_constant_expr; ty : ty }
+and constant_expr = {
+  value : raw_constant_expr;
+  ty : ty;
+  has_type_suffix : bool option;
+      (** For integer literals: whether the literal in the source code had an explicit type suffix
+        (like `1u32`) or had its type inferred (like `1`). This is `None` for the other constants,
+        and for the literals we couldn't find in the source code (e.g. those computed by rustc).
+     *)
+}
 
 (** TODO: we could factor out [Rvalue] and function calls (for LLBC, not ULLBC).
     We can also factor out the unops, binops with the function calls.
@@ -292,8 +450,10 @@ and rvalue =
       (** Takes a raw pointer with the given mutability to the given place. This is generated by
           pointer casts like `&v as *const _` or raw borrow expressions like `&raw const v.`
        *)
-  | BinaryOp of binop * operand * operand
-      (** Binary operations (note that we merge "checked" and "unchecked" binops) *)
+  | BinaryOp of binop * operand * operand * binop_tys
+      (** Binary operations (note that we merge "checked" and "unchecked" binops), with the types of
+          their operands.
+       *)
   | UnaryOp of unop * operand  (** Unary operation (e.g. not, neg) *)
   | NullaryOp of nullop * ty  (** Nullary operation (e.g. `size_of`) *)
   | Discriminant of place * type_decl_id
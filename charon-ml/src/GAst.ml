@@ -70,14 +70,50 @@ type fn_operand =
   | FnOpMove of place
       (** Use of a function pointer stored in a local variable *)
 
-and call = { func : fn_operand; args : operand list; dest : place }
+(** The signature of the function pointer called by a [FnOperand::Move] call, as given by the type
+    of the called place.
+ *)
+and fn_ptr_sig = {
+  is_unsafe : bool;
+  abi : string;
+      (** The ABI of the function pointer, as printed by rustc (e.g. `Rust`, `C { unwind: false }`). *)
+  regions : region_var list;
+      (** The regions bound by the function pointer type (e.g. `'a` in `for<'a> fn(&'a u32)`). *)
+  inputs : ty list;
+  output : ty;
+}
+
+and call = {
+  func : fn_operand;
+  args : operand list;
+  dest : place;
+  fn_ptr_sig : fn_ptr_sig option;
+      (** For calls to function pointers ([FnOperand::Move]), the signature of the called function
+          pointer. This is [None] for the other calls.
+       *)
+}
 
 (** Asserts are special constructs introduced by Rust to perform dynamic
     checks, to detect out-of-bounds accesses or divisions by zero for
     instance. We eliminate the assertions in [crate::remove_dynamic_checks],
     then introduce other dynamic checks in [crate::reconstruct_asserts].
  *)
-and assertion = { cond : operand; expected : bool }
+and assertion = {
+  cond : operand;
+  expected : bool;
+  message : operand list;
+      (** The constant operands of the panic message computed before failing, if any (e.g. the
+          string literal of `assert!(c, "message")`). See [crate::reconstruct_asserts].
+       *)
+}
+
+(** The kind of a [crate::llbc_ast::RawStatement::Retag], as in the Stacked Borrows memory model. *)
+and retag_kind =
+  | RetagFnEntry
+      (** The retag of the arguments of a function at the start of its body. *)
+  | RetagTwoPhase  (** The retag of a two-phase borrow when it is created. *)
+  | RetagRaw  (** The retag of a reference which is cast to a raw pointer. *)
+  | RetagDefault  (** Any other retag. *)
 [@@deriving
   show,
     ord,
@@ -159,6 +195,66 @@ and item_kind =
           item is a copy of the default item.
        *)
 
+(** The symbol-level information of a function or global, given by its `#[no_mangle]`,
+    `#[export_name]`, `#[link_name]` and `#[linkage]` attributes, and by the `extern` block it is
+    declared in.
+ *)
+and linkage = {
+  export_name : string option;
+      (** The name of the symbol, if the item is exported under an unmangled name: the name of the
+        item for `#[no_mangle]` items, and the given name for `#[export_name = "..."]` items.
+     *)
+  no_mangle : bool;  (** Whether the item has the `#[no_mangle]` attribute. *)
+  kind : string option;
+      (** The linkage given by the `#[linkage = "..."]` attribute, as named by rustc (e.g.
+        `WeakAny` for `#[linkage = "weak"]`).
+     *)
+  link_name : string option;
+      (** For items declared in an `extern { .. }` block: the name of the symbol they link to, i.e.
+        the name given by `#[link_name = "..."]` or else the name of the item.
+     *)
+  foreign_abi : string option;
+      (** For items declared in an `extern { .. }` block: the ABI of the block (e.g. `C`). *)
+}
+
+(** The items which implement the handlers used by the runtime. Crates which don't link the
+    standard library (`no_std`) provide their own; we only record the handlers defined in the
+    translated crate.
+ *)
+and runtime_handlers = {
+  global_allocator : global_decl_id option;
+      (** The static annotated with `#[global_allocator]`. *)
+  panic_handler : fun_decl_id option;
+      (** The function annotated with `#[panic_handler]`. *)
+  alloc_error_handler : fun_decl_id option;
+      (** The function annotated with `#[alloc_error_handler]`. *)
+}
+
+(** The outermost type constructor of a type. We use it to index the trait impls by their self
+    type (see [crate::ast::TraitImplIndex]).
+ *)
+and type_head =
+  | HeadAdt of type_id
+  | HeadLiteral of literal_type
+  | HeadRef of ref_kind
+  | HeadRawPtr of ref_kind
+  | HeadArrow
+  | HeadDynTrait
+  | HeadNever
+
+(** The trait impls of the crate, indexed by trait and by the head of their self type. This is
+    meant for consumers which resolve trait methods themselves.
+ *)
+and trait_impl_index = {
+  impls_of_trait : (trait_decl_id * trait_impl_id list) list;
+      (** The impls of each trait, sorted by trait id. *)
+  impls_by_self_head :
+    ((trait_decl_id * type_head option) * trait_impl_id list) list;
+      (** The impls of each trait for each head of the self type, sorted by key. The impls whose self
+        type is a type variable or an associated type (e.g. blanket impls) have `None` as head.
+     *)
+}
+
 (** A trait **declaration**.
 
     For instance:
@@ -226,6 +322,30 @@ and trait_decl = {
         The provided methods are the methods with a default implementation. The corresponding
         `FunDecl`s may have a body, according to the usual rules for extracting function bodies.
      *)
+  vtable_slots : (trait_item_name * int) list option;
+      (** The slots of the methods in the vtable of `dyn Trait`, following the layout used by rustc.
+        The first slots hold the drop glue, the size and the alignment of the type, and the
+        methods of the supertraits come before the methods of the trait. The methods which can't
+        be called on a trait object (e.g. because of a `Self: Sized` bound) don't have a slot.
+        This is `None` if the trait is not dyn-compatible.
+     *)
+  dyn_compatibility_violations : dyn_compatibility_violation list;
+      (** The reasons why the trait is not dyn-compatible (i.e. why it can't be used as `dyn Trait`),
+        as given by rustc. This is empty if the trait is dyn-compatible.
+     *)
+}
+
+(** A reason why a trait is not dyn-compatible (see [TraitDecl::dyn_compatibility_violations]). *)
+and dyn_compatibility_violation = {
+  item : trait_item_name option;
+      (** The method, associated constant or associated type which causes the violation, if any. It
+        may come from a supertrait. This is `None` for the violations which concern the trait as a
+        whole, e.g. a `Self: Sized` bound.
+     *)
+  reason : string;
+      (** The explanation given by rustc, e.g. "method `foo` references the `Self` type in its
+        return type".
+     *)
 }
 
 (** A trait **implementation**.
@@ -258,8 +378,44 @@ and trait_impl = {
       (** The implemented required methods *)
   provided_methods : (trait_item_name * fun_decl_id) list;
       (** The re-implemented provided methods *)
+  obligations : impl_obligation list;
+      (** The obligations that the impl must satisfy to be well-formed, with the proofs that they
+        hold. There is one obligation for each clause of the trait declaration: the parent clauses
+        and the clauses on the associated types.
+     *)
+}
+
+(** An obligation of a trait impl (see [TraitImpl::obligations]), e.g. for:
+    ```text
+    trait Foo: Clone {
+        type Item: Copy;
+    }
+
+    impl<T: Copy> Foo for Wrapper<T> {
+        type Item = T;
+    }
+    ```
+    the impl must prove `Wrapper<T>: Clone` and `T: Copy`.
+ *)
+and impl_obligation = {
+  origin : obligation_origin;
+      (** The clause of the trait declaration which gives rise to this obligation. *)
+  trait_ref : trait_ref;
+      (** The proof that the obligation holds. Its `trait_decl_ref` is the obligation itself, i.e.
+        the clause instantiated with the arguments of the impl.
+     *)
 }
 
+(** Where an [ImplObligation] comes from. *)
+and obligation_origin =
+  | ObligationParentClause of trait_clause_id
+      (** A parent clause of the trait declaration (a supertrait or a `where` clause). *)
+  | ObligationAssocTypeBound of trait_item_name * trait_clause_id
+      (** A clause on an associated type of the trait declaration (e.g. `type Item: Copy`). After
+        the `lift_associated_item_clauses` pass, these clauses become parent clauses of the trait
+        declaration and the id is the one of the corresponding parent clause.
+     *)
+
 (** We use this to store information about the parameters in parent blocks.
     This is necessary because in the definitions we store *all* the generics,
     including those coming from the outer impl block.
@@ -374,6 +530,18 @@ and declaration_group =
 and 'a0 g_declaration_group =
   | NonRecGroup of 'a0  (** A non-recursive declaration *)
   | RecGroup of 'a0 list  (** A (group of mutually) recursive declaration(s) *)
+
+(** The kind of a dependency between two declarations, given by the kind of the declaration which is
+    depended upon.
+ *)
+and dependency_kind =
+  | DepCall  (** A call to (or a reference to) a function. *)
+  | DepTypeUse  (** A use of a type. *)
+  | DepTraitRef  (** A reference to a trait declaration or to a trait impl. *)
+  | DepGlobalUse  (** A use of a global. *)
+
+(** An edge of the dependency graph of the declarations: `src` depends on `dst`. *)
+and dependency = { src : any_decl_id; dst : any_decl_id; kind : dependency_kind }
 [@@deriving show]
 
 (* Hand-written because they don't exist in rust *)
@@ -397,6 +565,7 @@ type 'body gfun_decl = {
   item_meta : item_meta;
   signature : fun_sig;
   kind : item_kind;
+  linkage : linkage;
   body : 'body gexpr_body option;
   is_global_decl_body : bool;
 }
@@ -408,7 +577,10 @@ type 'body gglobal_decl = {
   item_meta : item_meta;
   generics : generic_params;
   ty : ty;
+  is_mutable : bool;
+  has_interior_mutability : bool;
   kind : item_kind;
+  linkage : linkage;
   body : 'body;
 }
 [@@deriving show]
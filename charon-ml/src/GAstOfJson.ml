@@ -105,6 +105,25 @@ and nullop_of_json (js : json) : (nullop, string) result =
     | `String "UbChecks" -> Ok UbChecks
     | _ -> Error "")
 
+and layout_of_json (js : json) : (layout, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("size", size); ("align", align); ("has_niche", has_niche) ] ->
+        let* size = int_of_json size in
+        let* align = int_of_json align in
+        let* has_niche = bool_of_json has_niche in
+        Ok ({ size; align; has_niche } : layout)
+    | _ -> Error "")
+
+and transmute_layouts_of_json (js : json) : (transmute_layouts, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("src", src); ("tgt", tgt) ] ->
+        let* src = option_of_json layout_of_json src in
+        let* tgt = option_of_json layout_of_json tgt in
+        Ok ({ src; tgt } : transmute_layouts)
+    | _ -> Error "")
+
 and cast_kind_of_json (js : json) : (cast_kind, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
@@ -116,6 +135,14 @@ and cast_kind_of_json (js : json) : (cast_kind, string) result =
         let* x_0 = ty_of_json x_0 in
         let* x_1 = ty_of_json x_1 in
         Ok (CastRawPtr (x_0, x_1))
+    | `Assoc [ ("ExposeProvenance", `List [ x_0; x_1 ]) ] ->
+        let* x_0 = ty_of_json x_0 in
+        let* x_1 = ty_of_json x_1 in
+        Ok (CastExposeProvenance (x_0, x_1))
+    | `Assoc [ ("WithExposedProvenance", `List [ x_0; x_1 ]) ] ->
+        let* x_0 = ty_of_json x_0 in
+        let* x_1 = ty_of_json x_1 in
+        Ok (CastWithExposedProvenance (x_0, x_1))
     | `Assoc [ ("FnPtr", `List [ x_0; x_1 ]) ] ->
         let* x_0 = ty_of_json x_0 in
         let* x_1 = ty_of_json x_1 in
@@ -124,10 +151,11 @@ and cast_kind_of_json (js : json) : (cast_kind, string) result =
         let* x_0 = ty_of_json x_0 in
         let* x_1 = ty_of_json x_1 in
         Ok (CastUnsize (x_0, x_1))
-    | `Assoc [ ("Transmute", `List [ x_0; x_1 ]) ] ->
+    | `Assoc [ ("Transmute", `List [ x_0; x_1; x_2 ]) ] ->
         let* x_0 = ty_of_json x_0 in
         let* x_1 = ty_of_json x_1 in
-        Ok (CastTransmute (x_0, x_1))
+        let* x_2 = transmute_layouts_of_json x_2 in
+        Ok (CastTransmute (x_0, x_1, x_2))
     | _ -> Error "")
 
 and binop_of_json (js : json) : (binop, string) result =
@@ -154,6 +182,15 @@ and binop_of_json (js : json) : (binop, string) result =
     | `String "Shr" -> Ok Shr
     | _ -> Error "")
 
+and binop_tys_of_json (js : json) : (binop_tys, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("lhs_ty", lhs_ty); ("rhs_ty", rhs_ty) ] ->
+        let* lhs_ty = ty_of_json lhs_ty in
+        let* rhs_ty = ty_of_json rhs_ty in
+        Ok ({ lhs_ty; rhs_ty } : binop_tys)
+    | _ -> Error "")
+
 and operand_of_json (js : json) : (operand, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
@@ -189,6 +226,9 @@ and assumed_fun_id_of_json (js : json) : (assumed_fun_id, string) result =
     | `Assoc [ ("Index", index) ] ->
         let* index = builtin_index_op_of_json index in
         Ok (Index index)
+    | `Assoc [ ("Async", async) ] ->
+        let* async = async_builtin_of_json async in
+        Ok (Async async)
     | _ -> Error "")
 
 and builtin_index_op_of_json (js : json) : (builtin_index_op, string) result =
@@ -206,6 +246,26 @@ and builtin_index_op_of_json (js : json) : (builtin_index_op, string) result =
         Ok ({ is_array; mutability; is_range } : builtin_index_op)
     | _ -> Error "")
 
+and async_builtin_of_json (js : json) : (async_builtin, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "FuturePoll" -> Ok FuturePoll
+    | `String "PinNew" -> Ok PinNew
+    | `String "PinNewUnchecked" -> Ok PinNewUnchecked
+    | `String "PinAsRef" -> Ok PinAsRef
+    | `String "PinAsMut" -> Ok PinAsMut
+    | `String "PinGetRef" -> Ok PinGetRef
+    | `String "PinGetMut" -> Ok PinGetMut
+    | `String "PinGetUncheckedMut" -> Ok PinGetUncheckedMut
+    | `String "PinIntoInner" -> Ok PinIntoInner
+    | `String "PinMapUnchecked" -> Ok PinMapUnchecked
+    | `String "PinMapUncheckedMut" -> Ok PinMapUncheckedMut
+    | `String "ContextFromWaker" -> Ok ContextFromWaker
+    | `String "ContextWaker" -> Ok ContextWaker
+    | `String "WakerWake" -> Ok WakerWake
+    | `String "WakerWakeByRef" -> Ok WakerWakeByRef
+    | _ -> Error "")
+
 and fun_id_or_trait_method_ref_of_json (js : json) :
     (fun_id_or_trait_method_ref, string) result =
   combine_error_msgs js __FUNCTION__
@@ -235,10 +295,26 @@ and raw_constant_expr_of_json (js : json) : (raw_constant_expr, string) result =
     | `Assoc [ ("Literal", literal) ] ->
         let* literal = literal_of_json literal in
         Ok (CLiteral literal)
+    | `Assoc [ ("Adt", `List [ x_0; x_1 ]) ] ->
+        let* x_0 = option_of_json variant_id_of_json x_0 in
+        let* x_1 = list_of_json constant_expr_of_json x_1 in
+        Ok (CAdt (x_0, x_1))
+    | `Assoc [ ("Array", array) ] ->
+        let* array = list_of_json constant_expr_of_json array in
+        Ok (CArray array)
+    | `Assoc [ ("Global", global) ] ->
+        let* global = global_decl_ref_of_json global in
+        Ok (CGlobal global)
     | `Assoc [ ("TraitConst", `List [ x_0; x_1 ]) ] ->
         let* x_0 = trait_ref_of_json x_0 in
         let* x_1 = trait_item_name_of_json x_1 in
         Ok (CTraitConst (x_0, x_1))
+    | `Assoc [ ("Ref", ref) ] ->
+        let* ref = constant_expr_of_json ref in
+        Ok (CRef ref)
+    | `Assoc [ ("MutPtr", mut_ptr) ] ->
+        let* mut_ptr = constant_expr_of_json mut_ptr in
+        Ok (CMutPtr mut_ptr)
     | `Assoc [ ("Var", var) ] ->
         let* var = const_generic_var_id_of_json var in
         Ok (CVar var)
@@ -250,10 +326,13 @@ and raw_constant_expr_of_json (js : json) : (raw_constant_expr, string) result =
 and constant_expr_of_json (js : json) : (constant_expr, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("value", value); ("ty", ty) ] ->
+    | `Assoc
+        [ ("value", value); ("ty", ty); ("has_type_suffix", has_type_suffix) ]
+      ->
         let* value = raw_constant_expr_of_json value in
         let* ty = ty_of_json ty in
-        Ok ({ value; ty } : constant_expr)
+        let* has_type_suffix = option_of_json bool_of_json has_type_suffix in
+        Ok ({ value; ty; has_type_suffix } : constant_expr)
     | _ -> Error "")
 
 and rvalue_of_json (js : json) : (rvalue, string) result =
@@ -270,11 +349,12 @@ and rvalue_of_json (js : json) : (rvalue, string) result =
         let* x_0 = place_of_json x_0 in
         let* x_1 = ref_kind_of_json x_1 in
         Ok (RawPtr (x_0, x_1))
-    | `Assoc [ ("BinaryOp", `List [ x_0; x_1; x_2 ]) ] ->
+    | `Assoc [ ("BinaryOp", `List [ x_0; x_1; x_2; x_3 ]) ] ->
         let* x_0 = binop_of_json x_0 in
         let* x_1 = operand_of_json x_1 in
         let* x_2 = operand_of_json x_2 in
-        Ok (BinaryOp (x_0, x_1, x_2))
+        let* x_3 = binop_tys_of_json x_3 in
+        Ok (BinaryOp (x_0, x_1, x_2, x_3))
     | `Assoc [ ("UnaryOp", `List [ x_0; x_1 ]) ] ->
         let* x_0 = unop_of_json x_0 in
         let* x_1 = operand_of_json x_1 in
@@ -353,6 +433,7 @@ and gexpr_body_of_json :
           ("arg_count", arg_count);
           ("locals", locals);
           ("comments", _);
+          ("debug_info", _);
           ("body", body);
         ] ->
         let* span = span_of_json id_to_file span in
@@ -398,6 +479,92 @@ and item_kind_of_json (js : json) : (item_kind, string) result =
         Ok (TraitImplItem (impl_id, trait_id, item_name, reuses_default))
     | _ -> Error "")
 
+and linkage_of_json (js : json) : (linkage, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("export_name", export_name);
+          ("no_mangle", no_mangle);
+          ("kind", kind);
+          ("link_name", link_name);
+          ("foreign_abi", foreign_abi);
+        ] ->
+        let* export_name = option_of_json string_of_json export_name in
+        let* no_mangle = bool_of_json no_mangle in
+        let* kind = option_of_json string_of_json kind in
+        let* link_name = option_of_json string_of_json link_name in
+        let* foreign_abi = option_of_json string_of_json foreign_abi in
+        Ok
+          ({ export_name; no_mangle; kind; link_name; foreign_abi } : linkage)
+    | _ -> Error "")
+
+and runtime_handlers_of_json (js : json) : (runtime_handlers, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("global_allocator", global_allocator);
+          ("panic_handler", panic_handler);
+          ("alloc_error_handler", alloc_error_handler);
+        ] ->
+        let* global_allocator =
+          option_of_json global_decl_id_of_json global_allocator
+        in
+        let* panic_handler = option_of_json fun_decl_id_of_json panic_handler in
+        let* alloc_error_handler =
+          option_of_json fun_decl_id_of_json alloc_error_handler
+        in
+        Ok
+          ({ global_allocator; panic_handler; alloc_error_handler }
+            : runtime_handlers)
+    | _ -> Error "")
+
+and type_head_of_json (js : json) : (type_head, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Adt", adt) ] ->
+        let* adt = type_id_of_json adt in
+        Ok (HeadAdt adt)
+    | `Assoc [ ("Literal", literal) ] ->
+        let* literal = literal_type_of_json literal in
+        Ok (HeadLiteral literal)
+    | `Assoc [ ("Ref", ref_kind) ] ->
+        let* ref_kind = ref_kind_of_json ref_kind in
+        Ok (HeadRef ref_kind)
+    | `Assoc [ ("RawPtr", raw_ptr) ] ->
+        let* raw_ptr = ref_kind_of_json raw_ptr in
+        Ok (HeadRawPtr raw_ptr)
+    | `String "Arrow" -> Ok HeadArrow
+    | `String "DynTrait" -> Ok HeadDynTrait
+    | `String "Never" -> Ok HeadNever
+    | _ -> Error "")
+
+and trait_impl_index_of_json (js : json) : (trait_impl_index, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("impls_of_trait", impls_of_trait);
+          ("impls_by_self_head", impls_by_self_head);
+        ] ->
+        let* impls_of_trait =
+          list_of_json
+            (pair_of_json trait_decl_id_of_json
+               (list_of_json trait_impl_id_of_json))
+            impls_of_trait
+        in
+        let* impls_by_self_head =
+          list_of_json
+            (pair_of_json
+               (pair_of_json trait_decl_id_of_json
+                  (option_of_json type_head_of_json))
+               (list_of_json trait_impl_id_of_json))
+            impls_by_self_head
+        in
+        Ok ({ impls_of_trait; impls_by_self_head } : trait_impl_index)
+    | _ -> Error "")
+
 and global_decl_ref_of_json (js : json) : (global_decl_ref, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
@@ -428,6 +595,8 @@ and trait_decl_of_json (id_to_file : id_to_file_map) (js : json) :
           ("type_clauses", _);
           ("required_methods", required_methods);
           ("provided_methods", provided_methods);
+          ("vtable_slots", vtable_slots);
+          ("dyn_compatibility_violations", dyn_compatibility_violations);
         ] ->
         let* def_id = trait_decl_id_of_json def_id in
         let* item_meta = item_meta_of_json id_to_file item_meta in
@@ -451,6 +620,15 @@ and trait_decl_of_json (id_to_file : id_to_file_map) (js : json) :
             (pair_of_json trait_item_name_of_json fun_decl_id_of_json)
             provided_methods
         in
+        let* vtable_slots =
+          option_of_json
+            (list_of_json (pair_of_json trait_item_name_of_json int_of_json))
+            vtable_slots
+        in
+        let* dyn_compatibility_violations =
+          list_of_json dyn_compatibility_violation_of_json
+            dyn_compatibility_violations
+        in
         Ok
           ({
              def_id;
@@ -461,10 +639,22 @@ and trait_decl_of_json (id_to_file : id_to_file_map) (js : json) :
              types;
              required_methods;
              provided_methods;
+             vtable_slots;
+             dyn_compatibility_violations;
            }
             : trait_decl)
     | _ -> Error "")
 
+and dyn_compatibility_violation_of_json (js : json) :
+    (dyn_compatibility_violation, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("item", item); ("reason", reason) ] ->
+        let* item = option_of_json trait_item_name_of_json item in
+        let* reason = string_of_json reason in
+        Ok ({ item; reason } : dyn_compatibility_violation)
+    | _ -> Error "")
+
 and trait_impl_of_json (id_to_file : id_to_file_map) (js : json) :
     (trait_impl, string) result =
   combine_error_msgs js __FUNCTION__
@@ -481,6 +671,7 @@ and trait_impl_of_json (id_to_file : id_to_file_map) (js : json) :
           ("type_clauses", _);
           ("required_methods", required_methods);
           ("provided_methods", provided_methods);
+          ("obligations", obligations);
         ] ->
         let* def_id = trait_impl_id_of_json def_id in
         let* item_meta = item_meta_of_json id_to_file item_meta in
@@ -508,6 +699,7 @@ and trait_impl_of_json (id_to_file : id_to_file_map) (js : json) :
             (pair_of_json trait_item_name_of_json fun_decl_id_of_json)
             provided_methods
         in
+        let* obligations = list_of_json impl_obligation_of_json obligations in
         Ok
           ({
              def_id;
@@ -519,10 +711,32 @@ and trait_impl_of_json (id_to_file : id_to_file_map) (js : json) :
              types;
              required_methods;
              provided_methods;
+             obligations;
            }
             : trait_impl)
     | _ -> Error "")
 
+and impl_obligation_of_json (js : json) : (impl_obligation, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("origin", origin); ("trait_ref", trait_ref) ] ->
+        let* origin = obligation_origin_of_json origin in
+        let* trait_ref = trait_ref_of_json trait_ref in
+        Ok ({ origin; trait_ref } : impl_obligation)
+    | _ -> Error "")
+
+and obligation_origin_of_json (js : json) : (obligation_origin, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("ParentClause", parent_clause) ] ->
+        let* parent_clause = trait_clause_id_of_json parent_clause in
+        Ok (ObligationParentClause parent_clause)
+    | `Assoc [ ("AssocTypeBound", `List [ x_0; x_1 ]) ] ->
+        let* x_0 = trait_item_name_of_json x_0 in
+        let* x_1 = trait_clause_id_of_json x_1 in
+        Ok (ObligationAssocTypeBound (x_0, x_1))
+    | _ -> Error "")
+
 and fn_operand_of_json (js : json) : (fn_operand, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
@@ -534,14 +748,42 @@ and fn_operand_of_json (js : json) : (fn_operand, string) result =
         Ok (FnOpMove move)
     | _ -> Error "")
 
+and fn_ptr_sig_of_json (js : json) : (fn_ptr_sig, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("is_unsafe", is_unsafe);
+          ("abi", abi);
+          ("regions", regions);
+          ("inputs", inputs);
+          ("output", output);
+        ] ->
+        let* is_unsafe = bool_of_json is_unsafe in
+        let* abi = string_of_json abi in
+        let* regions =
+          vector_of_json region_id_of_json region_var_of_json regions
+        in
+        let* inputs = list_of_json ty_of_json inputs in
+        let* output = ty_of_json output in
+        Ok ({ is_unsafe; abi; regions; inputs; output } : fn_ptr_sig)
+    | _ -> Error "")
+
 and call_of_json (js : json) : (call, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("func", func); ("args", args); ("dest", dest) ] ->
+    | `Assoc
+        [
+          ("func", func);
+          ("args", args);
+          ("dest", dest);
+          ("fn_ptr_sig", fn_ptr_sig);
+        ] ->
         let* func = fn_operand_of_json func in
         let* args = list_of_json operand_of_json args in
         let* dest = place_of_json dest in
-        Ok ({ func; args; dest } : call)
+        let* fn_ptr_sig = option_of_json fn_ptr_sig_of_json fn_ptr_sig in
+        Ok ({ func; args; dest; fn_ptr_sig } : call)
     | _ -> Error "")
 
 and abort_kind_of_json (id_to_file : id_to_file_map) (js : json) :
@@ -557,10 +799,21 @@ and abort_kind_of_json (id_to_file : id_to_file_map) (js : json) :
 and assertion_of_json (js : json) : (assertion, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("cond", cond); ("expected", expected) ] ->
+    | `Assoc
+        [ ("cond", cond); ("expected", expected); ("message", message) ] ->
         let* cond = operand_of_json cond in
         let* expected = bool_of_json expected in
-        Ok ({ cond; expected } : assertion)
+        let* message = list_of_json operand_of_json message in
+        Ok ({ cond; expected; message } : assertion)
+    | _ -> Error "")
+
+and retag_kind_of_json (js : json) : (retag_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "FnEntry" -> Ok RetagFnEntry
+    | `String "TwoPhase" -> Ok RetagTwoPhase
+    | `String "Raw" -> Ok RetagRaw
+    | `String "Default" -> Ok RetagDefault
     | _ -> Error "")
 
 and any_decl_id_of_json (js : json) : (any_decl_id, string) result =
@@ -617,7 +870,31 @@ and span_of_json (id_to_file : id_to_file_map) (js : json) :
         let* generated_from_span =
           option_of_json (raw_span_of_json id_to_file) generated_from_span
         in
-        Ok ({ span; generated_from_span } : span)
+        Ok ({ span; generated_from_span; macro_origin = None } : span)
+    | `Assoc
+        [
+          ("span", span);
+          ("generated_from_span", generated_from_span);
+          ("macro_origin", macro_origin);
+        ] ->
+        let* span = raw_span_of_json id_to_file span in
+        let* generated_from_span =
+          option_of_json (raw_span_of_json id_to_file) generated_from_span
+        in
+        let* macro_origin = option_of_json macro_origin_of_json macro_origin in
+        Ok ({ span; generated_from_span; macro_origin } : span)
+    | _ -> Error "")
+
+and macro_origin_of_json (js : json) : (macro_origin, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Matches" -> Ok MacroMatches
+    | `String "Assert" -> Ok MacroAssert
+    | `String "AssertEq" -> Ok MacroAssertEq
+    | `String "AssertNe" -> Ok MacroAssertNe
+    | `String "DebugAssert" -> Ok MacroDebugAssert
+    | `String "DebugAssertEq" -> Ok MacroDebugAssertEq
+    | `String "DebugAssertNe" -> Ok MacroDebugAssertNe
     | _ -> Error "")
 
 and inline_attr_of_json (js : json) : (inline_attr, string) result =
@@ -632,6 +909,7 @@ and attribute_of_json (js : json) : (attribute, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
     | `String "Opaque" -> Ok AttrOpaque
+    | `String "Root" -> Ok AttrRoot
     | `Assoc [ ("Rename", rename) ] ->
         let* rename = string_of_json rename in
         Ok (AttrRename rename)
@@ -675,6 +953,61 @@ and attr_info_of_json (js : json) : (attr_info, string) result =
         Ok ({ attributes; inline; rename; public } : attr_info)
     | _ -> Error "")
 
+and visibility_of_json (js : json) : (visibility, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Public" -> Ok VisPublic
+    | `String "Crate" -> Ok VisCrate
+    | `String "Private" -> Ok VisPrivate
+    | _ -> Error "")
+
+and stability_of_json (js : json) : (stability, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("feature", feature); ("unstable", unstable) ] ->
+        let* feature = string_of_json feature in
+        let* unstable = bool_of_json unstable in
+        Ok ({ feature; unstable } : stability)
+    | _ -> Error "")
+
+and codegen_attrs_of_json (js : json) : (codegen_attrs, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("used", used);
+          ("link_section", link_section);
+          ("cold", cold);
+          ("track_caller", track_caller);
+          ("naked", naked);
+          ("target_features", target_features);
+        ] ->
+        let* used = bool_of_json used in
+        let* link_section = option_of_json string_of_json link_section in
+        let* cold = bool_of_json cold in
+        let* track_caller = bool_of_json track_caller in
+        let* naked = bool_of_json naked in
+        let* target_features = list_of_json string_of_json target_features in
+        Ok
+          ({ used; link_section; cold; track_caller; naked; target_features }
+            : codegen_attrs)
+    | _ -> Error "")
+
+and provenance_of_json (js : json) : (provenance, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("crate_name", crate_name);
+          ("crate_version", crate_version);
+          ("body_available", body_available);
+        ] ->
+        let* crate_name = string_of_json crate_name in
+        let* crate_version = option_of_json string_of_json crate_version in
+        let* body_available = bool_of_json body_available in
+        Ok ({ crate_name; crate_version; body_available } : provenance)
+    | _ -> Error "")
+
 and item_meta_of_json (id_to_file : id_to_file_map) (js : json) :
     (item_meta, string) result =
   combine_error_msgs js __FUNCTION__
@@ -686,6 +1019,13 @@ and item_meta_of_json (id_to_file : id_to_file_map) (js : json) :
           ("source_text", source_text);
           ("attr_info", attr_info);
           ("is_local", is_local);
+          ("visibility", visibility);
+          ("stability", stability);
+          ("is_harness", is_harness);
+          ("codegen_attrs", codegen_attrs);
+          ("provenance", provenance);
+          ("dedup_hash", dedup_hash);
+          ("annotations", annotations);
           ("opacity", _);
         ] ->
         let* name = name_of_json id_to_file name in
@@ -693,7 +1033,38 @@ and item_meta_of_json (id_to_file : id_to_file_map) (js : json) :
         let* source_text = option_of_json string_of_json source_text in
         let* attr_info = attr_info_of_json attr_info in
         let* is_local = bool_of_json is_local in
-        Ok ({ name; span; source_text; attr_info; is_local } : item_meta)
+        let* visibility = visibility_of_json visibility in
+        let* stability = option_of_json stability_of_json stability in
+        let* is_harness = bool_of_json is_harness in
+        let* codegen_attrs = codegen_attrs_of_json codegen_attrs in
+        let* provenance = provenance_of_json provenance in
+        let* dedup_hash = option_of_json string_of_json dedup_hash in
+        let* annotations = list_of_json annotation_of_json annotations in
+        Ok
+          ({
+             name;
+             span;
+             source_text;
+             attr_info;
+             is_local;
+             visibility;
+             stability;
+             is_harness;
+             codegen_attrs;
+             provenance;
+             dedup_hash;
+             annotations;
+           }
+            : item_meta)
+    | _ -> Error "")
+
+and annotation_of_json (js : json) : (annotation, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("key", key); ("value", value) ] ->
+        let* key = string_of_json key in
+        let* value = string_of_json value in
+        Ok ({ key; value } : annotation)
     | _ -> Error "")
 
 and file_name_of_json (js : json) : (file_name, string) result =
@@ -733,9 +1104,14 @@ and impl_elem_of_json (id_to_file : id_to_file_map) (js : json) :
         let* x_0 = generic_params_of_json id_to_file x_0 in
         let* x_1 = ty_of_json x_1 in
         Ok (ImplElemTy (x_0, x_1))
-    | `Assoc [ ("Trait", trait) ] ->
-        let* trait = trait_impl_id_of_json trait in
-        Ok (ImplElemTrait trait)
+    | `Assoc [ ("Trait", `List [ x_0; x_1; x_2 ]) ] ->
+        let* x_0 = trait_impl_id_of_json x_0 in
+        let* x_1 = generic_params_of_json id_to_file x_1 in
+        let* x_2 = trait_decl_ref_of_json x_2 in
+        Ok (ImplElemTrait (x_0, x_1, x_2))
+    | `Assoc [ ("Builtin", builtin) ] ->
+        let* builtin = trait_decl_ref_of_json builtin in
+        Ok (ImplElemBuiltin builtin)
     | _ -> Error "")
 
 and name_of_json (id_to_file : id_to_file_map) (js : json) :
@@ -1028,12 +1404,22 @@ and type_decl_of_json (id_to_file : id_to_file_map) (js : json) :
           ("item_meta", item_meta);
           ("generics", generics);
           ("kind", kind);
+          ("unpin", unpin);
         ] ->
         let* def_id = type_decl_id_of_json def_id in
         let* item_meta = item_meta_of_json id_to_file item_meta in
         let* generics = generic_params_of_json id_to_file generics in
         let* kind = type_decl_kind_of_json id_to_file kind in
-        Ok ({ def_id; item_meta; generics; kind } : type_decl)
+        let* unpin = option_of_json unpin_kind_of_json unpin in
+        Ok ({ def_id; item_meta; generics; kind; unpin } : type_decl)
+    | _ -> Error "")
+
+and unpin_kind_of_json (js : json) : (unpin_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Unpin" -> Ok Unpin
+    | `String "UnpinIfParamsUnpin" -> Ok UnpinIfParamsUnpin
+    | `String "NotUnpin" -> Ok NotUnpin
     | _ -> Error "")
 
 and type_decl_kind_of_json (id_to_file : id_to_file_map) (js : json) :
@@ -1091,13 +1477,26 @@ and field_of_json (id_to_file : id_to_file_map) (js : json) :
   combine_error_msgs js __FUNCTION__
     (match js with
     | `Assoc
-        [ ("span", span); ("attr_info", attr_info); ("name", name); ("ty", ty) ]
-      ->
+        [
+          ("span", span);
+          ("attr_info", attr_info);
+          ("name", name);
+          ("ty", ty);
+          ("recursion", recursion);
+        ] ->
         let* span = span_of_json id_to_file span in
         let* attr_info = attr_info_of_json attr_info in
         let* field_name = option_of_json string_of_json name in
         let* field_ty = ty_of_json ty in
-        Ok ({ span; attr_info; field_name; field_ty } : field)
+        let* field_recursion = option_of_json field_recursion_of_json recursion in
+        Ok ({ span; attr_info; field_name; field_ty; field_recursion } : field)
+    | _ -> Error "")
+
+and field_recursion_of_json (js : json) : (field_recursion, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Indirect" -> Ok RecIndirect
+    | `String "Direct" -> Ok RecDirect
     | _ -> Error "")
 
 and integer_type_of_json (js : json) : (integer_type, string) result =
@@ -1331,6 +1730,9 @@ and literal_of_json (js : json) : (literal, string) result =
     | `Assoc [ ("Str", str) ] ->
         let* str = string_of_json str in
         Ok (VStr str)
+    | `Assoc [ ("CStr", c_str) ] ->
+        let* c_str = list_of_json int_of_json c_str in
+        Ok (VCStr c_str)
     | _ -> Error "")
 
 and scalar_value_of_json (js : json) : (scalar_value, string) result =
@@ -1355,10 +1757,12 @@ and scalar_value_of_json (js : json) : (scalar_value, string) result =
 and float_value_of_json (js : json) : (float_value, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("value", value); ("ty", ty) ] ->
+    | `Assoc [ ("value", value); ("bits", bits); ("ty", ty) ] ->
         let* float_value = string_of_json value in
+        let* bits = string_of_json bits in
+        let float_bits = Z.of_string bits in
         let* float_ty = float_type_of_json ty in
-        Ok ({ float_value; float_ty } : float_value)
+        Ok ({ float_value; float_bits; float_ty } : float_value)
     | _ -> Error "")
 
 and vector_of_json :
@@ -1420,6 +1824,25 @@ and g_declaration_group_of_json :
         Ok (RecGroup rec_)
     | _ -> Error "")
 
+and dependency_kind_of_json (js : json) : (dependency_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Call" -> Ok DepCall
+    | `String "TypeUse" -> Ok DepTypeUse
+    | `String "TraitRef" -> Ok DepTraitRef
+    | `String "GlobalUse" -> Ok DepGlobalUse
+    | _ -> Error "")
+
+and dependency_of_json (js : json) : (dependency, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("src", src); ("dst", dst); ("kind", kind) ] ->
+        let* src = any_decl_id_of_json src in
+        let* dst = any_decl_id_of_json dst in
+        let* kind = dependency_kind_of_json kind in
+        Ok ({ src; dst; kind } : dependency)
+    | _ -> Error "")
+
 and maybe_opaque_body_of_json (bodies : 'body gexpr_body option list)
     (js : json) : ('body gexpr_body option, string) result =
   combine_error_msgs js __FUNCTION__
@@ -1443,12 +1866,14 @@ and gfun_decl_of_json (bodies : 'body gexpr_body option list)
           ("item_meta", item_meta);
           ("signature", signature);
           ("kind", kind);
+          ("linkage", linkage);
           ("body", body);
         ] ->
         let* def_id = FunDeclId.id_of_json def_id in
         let* item_meta = item_meta_of_json id_to_file item_meta in
         let* signature = fun_sig_of_json id_to_file signature in
         let* kind = item_kind_of_json kind in
+        let* linkage = linkage_of_json linkage in
         let* body = maybe_opaque_body_of_json bodies body in
         Ok
           {
@@ -1456,6 +1881,7 @@ and gfun_decl_of_json (bodies : 'body gexpr_body option list)
             item_meta;
             signature;
             kind;
+            linkage;
             body;
             is_global_decl_body = false;
           }
@@ -1473,17 +1899,33 @@ and gglobal_decl_of_json (bodies : 'body gexpr_body option list)
           ("item_meta", item_meta);
           ("generics", generics);
           ("ty", ty);
+          ("is_mutable", is_mutable);
+          ("has_interior_mutability", has_interior_mutability);
           ("kind", kind);
+          ("linkage", linkage);
           ("body", body);
         ] ->
         let* global_id = GlobalDeclId.id_of_json def_id in
         let* item_meta = item_meta_of_json id_to_file item_meta in
         let* generics = generic_params_of_json id_to_file generics in
         let* ty = ty_of_json ty in
+        let* is_mutable = bool_of_json is_mutable in
+        let* has_interior_mutability = bool_of_json has_interior_mutability in
         let* kind = item_kind_of_json kind in
+        let* linkage = linkage_of_json linkage in
         let* body = maybe_opaque_body_of_json bodies body in
         let global =
-          { def_id = global_id; item_meta; body; generics; ty; kind }
+          {
+            def_id = global_id;
+            item_meta;
+            body;
+            generics;
+            ty;
+            is_mutable;
+            has_interior_mutability;
+            kind;
+            linkage;
+          }
         in
         Ok global
     | _ -> Error "")
@@ -1523,6 +1965,11 @@ and gtranslated_crate_of_json
           ("file_id_to_content", file_id_to_content);
           ("all_ids", _);
           ("item_names", _);
+          ("reexports", _);
+          ("entry_points", _);
+          ("harnesses", _);
+          ("runtime_handlers", _);
+          ("mir_inlining", _);
           ("type_decls", types);
           ("fun_decls", functions);
           ("global_decls", globals);
@@ -1530,6 +1977,9 @@ and gtranslated_crate_of_json
           ("trait_decls", trait_decls);
           ("trait_impls", trait_impls);
           ("ordered_decls", declarations);
+          ("dependency_graph", _);
+          ("trait_impl_index", _);
+          ("failed_items", _);
         ] ->
         let* name = string_of_json name in
         let* id_to_file = id_to_file_of_json id_to_file in
@@ -1619,16 +2069,34 @@ and gcrate_of_json
     (body_of_json : id_to_file_map -> json -> ('body gexpr_body, string) result)
     (js : json) : (('body, 'body gexpr_body option) gcrate, string) result =
   match js with
-  | `Assoc [ ("charon_version", charon_version); ("translated", translated) ] ->
+  | `Assoc
+      [
+        ("charon_version", charon_version);
+        ("build_info", build_info);
+        ("translated", translated);
+      ] ->
       (* Ensure the version is the one we support. *)
       let* charon_version = string_of_json charon_version in
       if
         not (String.equal charon_version CharonVersion.supported_charon_version)
       then
+        (* The exact versions of charon and rustc which produced the file. *)
+        let build_info =
+          let or_unknown default = function
+            | `String s -> s
+            | _ -> default
+          in
+          match build_info with
+          | `Assoc [ ("charon_commit", commit); ("rustc_version", rustc) ] ->
+              "commit " ^ or_unknown "unknown" commit ^ ", "
+              ^ or_unknown "unknown rustc" rustc
+          | _ -> "unknown build"
+        in
         Error
           ("Incompatible version of charon: this program supports llbc emitted \
             by charon v" ^ CharonVersion.supported_charon_version
          ^ " but attempted to read a file emitted by charon v" ^ charon_version
-         ^ ".")
+         ^ " (" ^ build_info
+         ^ "). Regenerate the file with the same version of charon.")
       else gtranslated_crate_of_json body_of_json translated
   | _ -> combine_error_msgs js __FUNCTION__ (Error "")
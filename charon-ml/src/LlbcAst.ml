@@ -3,6 +3,26 @@ open Types
 open Values
 open Expressions
 open Meta
+open Identifiers
+module LabelId = IdGen ()
+
+(** We define this type to control the name of the visitor functions
+    (see e.g., {!LlbcAst.iter_statement_base} and {!raw_statement}).
+  *)
+type label_id = LabelId.id [@@deriving show, ord]
+
+(* Ancestors for the statement visitors *)
+class ['self] iter_statement_base =
+  object (self : 'self)
+    inherit [_] GAst.iter_statement_base
+    method visit_label_id : 'env -> label_id -> unit = fun _ _ -> ()
+  end
+
+class ['self] map_statement_base =
+  object (self : 'self)
+    inherit [_] GAst.map_statement_base
+    method visit_label_id : 'env -> label_id -> label_id = fun _ x -> x
+  end
 
 (** A raw statement: a statement without meta data. *)
 type raw_statement =
@@ -10,6 +30,10 @@ type raw_statement =
   | FakeRead of place
   | SetDiscriminant of place * variant_id
   | Drop of place
+  | Retag of retag_kind * place
+      (** A retag, for the Stacked Borrows memory model. These are only kept with the `--keep-retags`
+          option.
+       *)
   | Assert of assertion
   | Call of call
   (* FIXME: rename to `Abort` *)
@@ -27,7 +51,19 @@ type raw_statement =
   | Nop
   | Sequence of statement * statement
   | Switch of switch
+  | TryPropagate of try_propagate
+      (** The `?` operator: `dest := op?`. This is only introduced by the opt-in
+          [crate::transform::reconstruct_try] pass.
+       *)
   | Loop of statement
+  | Labeled of label_id * statement
+      (** A block which can be jumped to with {!Goto}. Those are only introduced in the
+          semi-structured mode (see the `--semi-structured` option), for the bodies whose
+          control-flow we failed to reconstruct. The body is then a sequence of labeled blocks, each
+          of which ends with a jump (a `goto`, a `return` or an `abort`).
+       *)
+  | Goto of label_id
+      (** Jump to the start of the labeled block with the given id. *)
   | Error of string
 
 and statement = {
@@ -38,6 +74,20 @@ and statement = {
 
 and block = statement
 
+(** The `?` operator. This calls `Try::branch` on `op`: if the result is `Continue(v)`, `v` is
+    stored in `dest`; if it is `Break(r)`, the function drops `residual_drops` and returns
+    `FromResidual::from_residual(r)`.
+ *)
+and try_propagate = {
+  op : operand;  (** The value the operator is applied to. *)
+  branch : fn_ptr;  (** The `Try::branch` method of the type of `op`. *)
+  from_residual : fn_ptr;
+      (** The `FromResidual::from_residual` method of the return type of the function. *)
+  residual_drops : place list;
+      (** The places which are dropped before returning the residual. *)
+  dest : place;  (** Where the output value is stored. *)
+}
+
 and switch =
   | If of operand * block * block
       (** Gives the `if` block and the `else` block. The `Operand` is the condition of the `if`, e.g. `if (y == 0)` could become
@@ -71,6 +121,41 @@ and switch =
           (whenever we find a discriminant read, we merge it with the subsequent
           switch into a match).
        *)
+  | NestedMatch of
+      place * (pattern * block) list * block option * match_kind
+      (** A multi-level match over an ADT, e.g.:
+          ```text
+          match x {
+            Some(Ok(_)) => ...,
+            Some(Err(_)) => ...,
+            None => ...,
+          }
+          ```
+
+          This is only introduced by the opt-in [crate::transform::reconstruct_matches] pass, which
+          merges the nested [Switch::Match]es on the fields of the scrutinee. The [MatchKind] records
+          the surface construct the match was recognized as.
+       *)
+
+(** The surface construct a [Switch::NestedMatch] was recognized as. *)
+and match_kind =
+  | KindMatch  (** A plain `match`. *)
+  | KindIfLet
+      (** `if let P = x { .. } else { .. }`: a single arm, and an `otherwise` branch. *)
+  | KindLetElse
+      (** `let P = x else { .. };`: a single arm, and an `otherwise` branch which diverges. *)
+  | KindWhileLet
+      (** `while let P = x { .. }`: an `if let` at the end of a loop body, whose `otherwise` branch
+          breaks out of the loop.
+       *)
+
+(** A pattern of a [Switch::NestedMatch]. *)
+and pattern =
+  | PatWildcard  (** Matches anything (`_`). *)
+  | PatVariant of variant_id list * (field_id * pattern) list
+      (** Matches the given variants, with sub-patterns for some of the fields (the other fields
+          are matched with `_`). The list of fields is only non-empty if there is a single variant.
+       *)
 [@@deriving
   show,
     ord,
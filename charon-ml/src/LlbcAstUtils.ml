@@ -50,11 +50,11 @@ let mk_sequence (st1 : statement) (st2 : statement) : statement =
 let rec chain_statements (st1 : statement) (st2 : statement) : statement =
   match st1.content with
   | SetDiscriminant _ | Assert _ | Call _ | Assign _ | FakeRead _ | Drop _
-  | Loop _ | Error _ ->
+  | Retag _ | TryPropagate _ | Loop _ | Labeled _ | Error _ ->
       (* Simply create a sequence *)
       mk_sequence st1 st2
   | Nop -> (* Ignore the nop *) st2
-  | Break _ | Continue _ | Panic | Return ->
+  | Break _ | Continue _ | Goto _ | Panic | Return ->
       (* Ignore the second statement, which won't be evaluated *) st1
   | Switch switch ->
       (* Insert inside the switch *)
@@ -85,6 +85,16 @@ and chain_statements_in_switch (switch : switch) (st : statement) : switch =
         | Some otherwise -> Some (chain_statements otherwise st)
       in
       Match (op, branches, otherwise)
+  | NestedMatch (op, branches, otherwise, kind) ->
+      let branches =
+        List.map (fun (pat, br) -> (pat, chain_statements br st)) branches
+      in
+      let otherwise =
+        match otherwise with
+        | None -> None
+        | Some otherwise -> Some (chain_statements otherwise st)
+      in
+      NestedMatch (op, branches, otherwise, kind)
 
 (** Compute a map from function declaration ids to declaration groups. *)
 let compute_fun_decl_groups_map (c : crate) : FunDeclId.Set.t FunDeclId.Map.t =
@@ -28,6 +28,10 @@ and raw_statement_of_json (id_to_file : id_to_file_map) (js : json) :
     | `Assoc [ ("Drop", place) ] ->
         let* place = place_of_json place in
         Ok (Drop place)
+    | `Assoc [ ("Retag", `List [ kind; place ]) ] ->
+        let* kind = retag_kind_of_json kind in
+        let* place = place_of_json place in
+        Ok (Retag (kind, place))
     | `Assoc [ ("Assert", assertion) ] ->
         let* assertion = assertion_of_json assertion in
         Ok (Assert assertion)
@@ -46,14 +50,43 @@ and raw_statement_of_json (id_to_file : id_to_file_map) (js : json) :
     | `Assoc [ ("Switch", tgt) ] ->
         let* switch = switch_of_json id_to_file tgt in
         Ok (Switch switch)
+    | `Assoc [ ("TryPropagate", try_propagate) ] ->
+        let* try_propagate = try_propagate_of_json try_propagate in
+        Ok (TryPropagate try_propagate)
     | `Assoc [ ("Loop", st) ] ->
         let* st = block_of_json id_to_file st in
         Ok (Loop st)
+    | `Assoc [ ("Labeled", `List [ label; st ]) ] ->
+        let* label = LabelId.id_of_json label in
+        let* st = block_of_json id_to_file st in
+        Ok (Labeled (label, st))
+    | `Assoc [ ("Goto", label) ] ->
+        let* label = LabelId.id_of_json label in
+        Ok (Goto label)
     | `Assoc [ ("Error", s) ] ->
         let* s = string_of_json s in
         Ok (Error s)
     | _ -> Error "")
 
+and try_propagate_of_json (js : json) : (try_propagate, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("op", op);
+          ("branch", branch);
+          ("from_residual", from_residual);
+          ("residual_drops", residual_drops);
+          ("dest", dest);
+        ] ->
+        let* op = operand_of_json op in
+        let* branch = fn_ptr_of_json branch in
+        let* from_residual = fn_ptr_of_json from_residual in
+        let* residual_drops = list_of_json place_of_json residual_drops in
+        let* dest = place_of_json dest in
+        Ok ({ op; branch; from_residual; residual_drops; dest } : try_propagate)
+    | _ -> Error "")
+
 and statement_of_json (id_to_file : id_to_file_map) (js : json) :
     (statement, string) result =
   combine_error_msgs js __FUNCTION__
@@ -63,6 +96,10 @@ and statement_of_json (id_to_file : id_to_file_map) (js : json) :
           ("span", span);
           ("content", content);
           ("comments_before", comments_before);
+          ("borrow_liveness", _);
+          ("move_info", _);
+          ("byte_range", _);
+          ("annotations", _);
         ] ->
         let* span = span_of_json id_to_file span in
         let* content = raw_statement_of_json id_to_file content in
@@ -128,6 +165,37 @@ and switch_of_json (id_to_file : id_to_file_map) (js : json) :
         in
         let* x_2 = option_of_json (block_of_json id_to_file) x_2 in
         Ok (Match (x_0, x_1, x_2))
+    | `Assoc [ ("NestedMatch", `List [ x_0; x_1; x_2; x_3 ]) ] ->
+        let* x_0 = place_of_json x_0 in
+        let* x_1 =
+          list_of_json
+            (pair_of_json pattern_of_json (block_of_json id_to_file))
+            x_1
+        in
+        let* x_2 = option_of_json (block_of_json id_to_file) x_2 in
+        let* x_3 = match_kind_of_json x_3 in
+        Ok (NestedMatch (x_0, x_1, x_2, x_3))
+    | _ -> Error "")
+
+and match_kind_of_json (js : json) : (match_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Match" -> Ok KindMatch
+    | `String "IfLet" -> Ok KindIfLet
+    | `String "LetElse" -> Ok KindLetElse
+    | `String "WhileLet" -> Ok KindWhileLet
+    | _ -> Error "")
+
+and pattern_of_json (js : json) : (pattern, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Wildcard" -> Ok PatWildcard
+    | `Assoc [ ("Variant", `List [ x_0; x_1 ]) ] ->
+        let* x_0 = list_of_json variant_id_of_json x_0 in
+        let* x_1 =
+          list_of_json (pair_of_json field_id_of_json pattern_of_json) x_1
+        in
+        Ok (PatVariant (x_0, x_1))
     | _ -> Error "")
 
 let expr_body_of_json (id_to_file : id_to_file_map) (js : json) :
@@ -158,7 +226,19 @@ let global_to_fun_id (conv : global_id_converter) (gid : GlobalDeclId.id) :
 let split_global (gid_conv : global_id_converter) global :
     global_decl * fun_decl =
   (* Deserialize the global declaration *)
-  let { def_id = global_id; item_meta; body; generics; ty; kind } = global in
+  let {
+    def_id = global_id;
+    item_meta;
+    body;
+    generics;
+    ty;
+    is_mutable;
+    has_interior_mutability;
+    kind;
+    linkage;
+  } =
+    global
+  in
   (* Decompose into a global and a function *)
   let fun_id = global_to_fun_id gid_conv global.def_id in
   let signature : fun_sig =
@@ -174,7 +254,17 @@ let split_global (gid_conv : global_id_converter) global :
     }
   in
   let global_decl : global_decl =
-    { def_id = global_id; item_meta; body = fun_id; generics; ty; kind }
+    {
+      def_id = global_id;
+      item_meta;
+      body = fun_id;
+      generics;
+      ty;
+      is_mutable;
+      has_interior_mutability;
+      kind;
+      linkage;
+    }
   in
   let fun_decl : fun_decl =
     {
@@ -182,6 +272,7 @@ let split_global (gid_conv : global_id_converter) global :
       item_meta;
       signature;
       kind = RegularItem;
+      linkage;
       body;
       is_global_decl_body = true;
     }
@@ -45,8 +45,25 @@ and span = {
      *)
   generated_from_span : raw_span option;
       (** Where the code actually comes from, in case of macro expansion/inlining/etc. *)
+  macro_origin : macro_origin option;
+      (** The standard assertion macro this code was expanded from, if any. This only applies to the
+        code generated by the macro (e.g. the comparison and the panic of an `assert_eq!`), not to
+        the expressions passed to it.
+     *)
 }
 
+(** The standard macros whose expansions we recognize. If some code comes from nested macros
+    (e.g. `debug_assert!` expands to `assert!`), we record the outermost one.
+ *)
+and macro_origin =
+  | MacroMatches  (** `matches!` *)
+  | MacroAssert  (** `assert!` *)
+  | MacroAssertEq  (** `assert_eq!` *)
+  | MacroAssertNe  (** `assert_ne!` *)
+  | MacroDebugAssert  (** `debug_assert!` *)
+  | MacroDebugAssertEq  (** `debug_assert_eq!` *)
+  | MacroDebugAssertNe  (** `debug_assert_ne!` *)
+
 (** `#[inline]` built-in attribute. *)
 and inline_attr =
   | Hint  (** `#[inline]` *)
@@ -59,6 +76,11 @@ and attribute =
       (** Do not translate the body of this item.
           Written `#[charon::opaque]`
        *)
+  | AttrRoot
+      (** Make this function an entry point of the crate, listed in
+          [crate::ast::TranslatedCrate::entry_points].
+          Written `#[charon::root]`
+       *)
   | AttrRename of string
       (** Provide a new name that consumers of the llbc can use.
           Written `#[charon::rename("new_name")]`
@@ -112,6 +134,67 @@ and attr_info = {
      *)
 }
 
+(** The visibility of an item, as declared in the source. *)
+and visibility =
+  | VisPublic  (** `pub`. *)
+  | VisCrate  (** `pub(crate)`. *)
+  | VisPrivate
+      (** Private, or restricted to a module (`pub(super)`, `pub(in path)`). Impl blocks and closures
+        don't have visibility modifiers; we consider them private.
+     *)
+
+(** A `#[stable(..)]` or `#[unstable(..)]` attribute. These are found in the standard library. *)
+and stability = {
+  feature : string;  (** The feature the item belongs to (`feature = "..."`). *)
+  unstable : bool;
+      (** Whether the item is unstable, i.e. whether using it requires enabling the feature. *)
+}
+
+(** The attributes which influence the code generated for an item, read from the codegen
+    information computed by rustc.
+ *)
+and codegen_attrs = {
+  used : bool;
+      (** Whether the item has the `#[used]` attribute (or `#[used(linker)]`), which means it must be
+        kept in the object file even if it looks unused.
+     *)
+  link_section : string option;
+      (** The section given by `#[link_section = "..."]`. *)
+  cold : bool;  (** Whether the function has the `#[cold]` attribute. *)
+  track_caller : bool;
+      (** Whether the function has the `#[track_caller]` attribute. *)
+  naked : bool;  (** Whether the function has the `#[naked]` attribute. *)
+  target_features : string list;
+      (** The target features enabled with `#[target_feature(enable = "...")]`. *)
+}
+
+(** Where an item comes from, so that the items of a merged extraction can be traced back to the
+    exact versions of the crates they come from.
+ *)
+and provenance = {
+  crate_name : string;  (** The name of the crate which defines the item. *)
+  crate_version : string option;
+      (** The version of this crate, when we know it: cargo gives the version of the crate being
+        extracted, and the version of a dependency appears in the path to its sources (e.g.
+        `.cargo/registry/src/<index>/smallvec-1.13.2/src/lib.rs`). This is `None` for the crates
+        of the standard library.
+     *)
+  body_available : bool;
+      (** Whether the MIR of the item was available, i.e. whether we could translate its body if it
+        wasn't opaque. This is `false` for the functions of other crates which are neither generic
+        nor inlinable, and for the items which have no body (types, traits and trait impls).
+     *)
+}
+
+(** A directive written in a comment, with `--comment-annotations`: `//@ requires: x > 0` gives
+    the annotation with key `requires` and value `x > 0`. This gives tools a lightweight way to
+    annotate the code without procedural macros.
+ *)
+and annotation = {
+  key : string;
+  value : string;  (** The rest of the line, which may be empty. *)
+}
+
 (** A filename. *)
 and file_name =
   | Virtual of path_buf  (** A remapped path (namely paths into stdlib) *)
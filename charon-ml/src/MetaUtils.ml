@@ -20,4 +20,7 @@ let combine_span (m0 : span) (m1 : span) : span =
       end_loc = loc_max m0.span.end_loc m1.span.end_loc;
     }
   in
-  { span; generated_from_span = None }
+  let macro_origin =
+    if m0.macro_origin = m1.macro_origin then m0.macro_origin else None
+  in
+  { span; generated_from_span = None; macro_origin }
@@ -409,6 +409,16 @@ let match_literal (pl : literal) (l : Values.literal) : bool =
   | LChar pv, VChar v -> pv = v
   | _ -> false
 
+(** Compare an identifier of a pattern with an identifier of a name. With
+    [--crate-versions-in-names], the crate roots look like [rand@0.8.5]: a
+    pattern without version matches all the versions. *)
+let match_ident (pid : string) (id : string) : bool =
+  pid = id
+  ||
+  match String.index_opt id '@' with
+  | Some i -> pid = String.sub id 0 i
+  | None -> false
+
 let rec match_name_with_generics (ctx : ctx) (c : match_config)
     ?(m : maps = mk_empty_maps ()) (p : pattern) (n : T.name)
     (g : T.generic_args) : bool =
@@ -425,7 +435,7 @@ let rec match_name_with_generics (ctx : ctx) (c : match_config)
          ^ "\n- id: " ^ id));
       (* We reached the end: match the generics.
          We have to generate an empty map. *)
-      pid = id && match_generic_args ctx c m pg g
+      match_ident pid id && match_generic_args ctx c m pg g
   | [ PImpl pty ], [ PeImpl (impl, _) ] -> (
       (* We can get there when matching a prefix of the name with a pattern *)
       (* We have to distinguish two cases:
@@ -436,12 +446,12 @@ let rec match_name_with_generics (ctx : ctx) (c : match_config)
       | ImplElemTy (_, ty) ->
           match_expr_with_ty ctx c (mk_empty_maps ()) pty ty
           && g = TypesUtils.empty_generic_args
-      | ImplElemTrait impl_id ->
-          match_expr_with_trait_impl_id ctx c pty impl_id
+      | ImplElemTrait (_, _, impl_trait) | ImplElemBuiltin impl_trait ->
+          match_expr_with_impl_trait ctx c pty impl_trait
           && g = TypesUtils.empty_generic_args)
   | PIdent (pid, pg) :: p, PeIdent (id, _) :: n ->
       (* This is not the end: check that the generics are empty *)
-      pid = id && pg = [] && match_name_with_generics ctx c p n g
+      match_ident pid id && pg = [] && match_name_with_generics ctx c p n g
   | PImpl pty :: p, PeImpl (impl, _) :: n -> (
       (* We have to distinguish two cases:
          - the impl is an inherent impl (linked to a type)
@@ -451,8 +461,8 @@ let rec match_name_with_generics (ctx : ctx) (c : match_config)
       | ImplElemTy (_, ty) ->
           match_expr_with_ty ctx c (mk_empty_maps ()) pty ty
           && match_name_with_generics ctx c p n g
-      | ImplElemTrait impl_id ->
-          match_expr_with_trait_impl_id ctx c pty impl_id
+      | ImplElemTrait (_, _, impl_trait) | ImplElemBuiltin impl_trait ->
+          match_expr_with_impl_trait ctx c pty impl_trait
           && match_name_with_generics ctx c p n g)
   | _ -> false
 
@@ -521,19 +531,15 @@ and match_expr_with_ty (ctx : ctx) (c : match_config) (m : maps) (pty : expr)
       match_expr_with_ty ctx c m pty ty
   | _ -> false
 
-and match_expr_with_trait_impl_id (ctx : ctx) (c : match_config) (ptr : expr)
-    (impl_id : T.TraitImplId.id) : bool =
-  (* Lookup the trait implementation *)
-  let impl = T.TraitImplId.Map.find impl_id ctx.trait_impls in
+and match_expr_with_impl_trait (ctx : ctx) (c : match_config) (ptr : expr)
+    (impl_trait : T.trait_decl_ref) : bool =
   (* Lookup the trait declaration *)
-  let d =
-    T.TraitDeclId.Map.find impl.impl_trait.trait_decl_id ctx.trait_decls
-  in
+  let d = T.TraitDeclId.Map.find impl_trait.trait_decl_id ctx.trait_decls in
   (* Match *)
   match ptr with
   | EComp pid ->
       match_name_with_generics ctx c pid d.item_meta.name
-        impl.impl_trait.decl_generics
+        impl_trait.decl_generics
   | EPrimAdt _ | ERef _ | EVar _ | EArrow _ | ERawPtr _ -> false
 
 and match_trait_ref (ctx : ctx) (c : match_config) (m : maps) (pid : pattern)
@@ -632,6 +638,7 @@ let assumed_fun_id_to_string (fid : E.assumed_fun_id) : string =
       let op = if is_range then "SubSlice" else "Index" in
       let mutability = PrintTypes.ref_kind_to_string mutability in
       ty ^ op ^ mutability
+  | Async op -> PrintExpressions.async_builtin_to_string op
 
 let match_fn_ptr (ctx : ctx) (c : match_config) (p : pattern) (func : E.fn_ptr)
     : bool =
@@ -811,7 +818,7 @@ let literal_to_pattern (_c : to_pat_config) (lit : Values.literal) : literal =
   | VScalar sv -> LInt sv.value
   | VBool v -> LBool v
   | VChar v -> LChar v
-  | VFloat _ | VStr _ | VByteStr _ ->
+  | VFloat _ | VStr _ | VByteStr _ | VCStr _ ->
       raise
         (Failure "Float, string and byte string literals are not valid in names")
 
@@ -840,9 +847,10 @@ and impl_elem_to_pattern (ctx : ctx) (c : to_pat_config) (impl : T.impl_elem) :
     pattern_elem =
   match impl with
   | ImplElemTy (generics, ty) -> PImpl (ty_to_pattern ctx c generics ty)
-  | ImplElemTrait impl_id ->
-      let impl = T.TraitImplId.Map.find impl_id ctx.trait_impls in
-      PImpl (trait_decl_ref_to_pattern ctx c impl.generics impl.impl_trait)
+  | ImplElemTrait (_, generics, impl_trait) ->
+      PImpl (trait_decl_ref_to_pattern ctx c generics impl_trait)
+  | ImplElemBuiltin impl_trait ->
+      PImpl (trait_decl_ref_to_pattern ctx c TypesUtils.empty_generic_params impl_trait)
 
 and trait_decl_ref_to_pattern (ctx : ctx) (c : to_pat_config)
     (params : T.generic_params) (tr : T.trait_decl_ref) : expr =
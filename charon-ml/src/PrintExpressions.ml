@@ -56,8 +56,14 @@ let cast_kind_to_string (env : ('a, 'b) fmt_env) (cast : cast_kind) : string =
   | CastScalar (src, tgt) ->
       "cast<" ^ literal_type_to_string src ^ "," ^ literal_type_to_string tgt
       ^ ">"
-  | CastFnPtr (src, tgt) | CastRawPtr (src, tgt) | CastTransmute (src, tgt) ->
+  | CastFnPtr (src, tgt) | CastRawPtr (src, tgt) | CastTransmute (src, tgt, _) ->
       "cast<" ^ ty_to_string env src ^ "," ^ ty_to_string env tgt ^ ">"
+  | CastExposeProvenance (src, tgt) ->
+      "expose_provenance<" ^ ty_to_string env src ^ "," ^ ty_to_string env tgt
+      ^ ">"
+  | CastWithExposedProvenance (src, tgt) ->
+      "with_exposed_provenance<" ^ ty_to_string env src ^ ","
+      ^ ty_to_string env tgt ^ ">"
   | CastUnsize (src, tgt) ->
       "unsize<" ^ ty_to_string env src ^ "," ^ ty_to_string env tgt ^ ">"
 
@@ -96,6 +102,24 @@ let binop_to_string (binop : binop) : string =
   | Shl -> "<<"
   | Shr -> ">>"
 
+let async_builtin_to_string (op : async_builtin) : string =
+  match op with
+  | FuturePoll -> "FuturePoll"
+  | PinNew -> "PinNew"
+  | PinNewUnchecked -> "PinNewUnchecked"
+  | PinAsRef -> "PinAsRef"
+  | PinAsMut -> "PinAsMut"
+  | PinGetRef -> "PinGetRef"
+  | PinGetMut -> "PinGetMut"
+  | PinGetUncheckedMut -> "PinGetUncheckedMut"
+  | PinIntoInner -> "PinIntoInner"
+  | PinMapUnchecked -> "PinMapUnchecked"
+  | PinMapUncheckedMut -> "PinMapUncheckedMut"
+  | ContextFromWaker -> "ContextFromWaker"
+  | ContextWaker -> "ContextWaker"
+  | WakerWake -> "WakerWake"
+  | WakerWakeByRef -> "WakerWakeByRef"
+
 let assumed_fun_id_to_string (aid : assumed_fun_id) : string =
   match aid with
   | BoxNew -> "alloc::boxed::Box::new"
@@ -107,6 +131,7 @@ let assumed_fun_id_to_string (aid : assumed_fun_id) : string =
       let op = if is_range then "SubSlice" else "Index" in
       let mutability = ref_kind_to_string mutability in
       "@" ^ ty ^ op ^ mutability
+  | Async async -> "@" ^ async_builtin_to_string async
 
 let fun_id_to_string (env : ('a, 'b) fmt_env) (fid : fun_id) : string =
   match fid with
@@ -124,11 +149,25 @@ let fn_ptr_to_string (env : ('a, 'b) fmt_env) (ptr : fn_ptr) : string =
   let generics = generic_args_to_string env ptr.generics in
   fun_id_or_trait_method_ref_to_string env ptr.func ^ generics
 
-let constant_expr_to_string (env : ('a, 'b) fmt_env) (cv : constant_expr) :
-    string =
+let rec constant_expr_to_string (env : ('a, 'b) fmt_env) (cv : constant_expr)
+    : string =
   match cv.value with
   | CLiteral lit ->
       "(" ^ literal_to_string lit ^ " : " ^ ty_to_string env cv.ty ^ ")"
+  | CAdt (variant_id, fields) ->
+      let variant_id =
+        match variant_id with
+        | Some id -> "Some(" ^ VariantId.to_string id ^ ")"
+        | None -> "None"
+      in
+      let fields = List.map (constant_expr_to_string env) fields in
+      "ConstAdt " ^ variant_id ^ " [" ^ String.concat ", " fields ^ "]"
+  | CArray elems ->
+      let elems = List.map (constant_expr_to_string env) elems in
+      "ConstArray [" ^ String.concat ", " elems ^ "]"
+  | CGlobal global -> global_decl_ref_to_string env global
+  | CRef cv -> "&" ^ constant_expr_to_string env cv
+  | CMutPtr cv -> "&raw mut " ^ constant_expr_to_string env cv
   | CVar vid -> const_generic_var_id_to_string env vid
   | CTraitConst (trait_ref, const_name) ->
       let trait_ref = trait_ref_to_string env trait_ref in
@@ -161,7 +200,7 @@ let rvalue_to_string (env : ('a, 'b) fmt_env) (rv : rvalue) : string =
       nullop_to_string env op ^ "<" ^ ty_to_string env ty ^ ">"
   | UnaryOp (unop, op) ->
       unop_to_string env unop ^ " " ^ operand_to_string env op
-  | BinaryOp (binop, op1, op2) ->
+  | BinaryOp (binop, op1, op2, _) ->
       operand_to_string env op1 ^ " " ^ binop_to_string binop ^ " "
       ^ operand_to_string env op2
   | Discriminant (p, _) -> "discriminant(" ^ place_to_string env p ^ ")"
@@ -31,8 +31,19 @@ let call_to_string (env : ('a, 'b) fmt_env) (indent : string) (call : call) :
 let assertion_to_string (env : ('a, 'b) fmt_env) (indent : string)
     (a : assertion) : string =
   let cond = operand_to_string env a.cond in
-  if a.expected then indent ^ "assert(" ^ cond ^ ")"
-  else indent ^ "assert(¬" ^ cond ^ ")"
+  let message =
+    String.concat ""
+      (List.map (fun op -> ", " ^ operand_to_string env op) a.message)
+  in
+  if a.expected then indent ^ "assert(" ^ cond ^ message ^ ")"
+  else indent ^ "assert(¬" ^ cond ^ message ^ ")"
+
+let retag_kind_to_string (kind : retag_kind) : string =
+  match kind with
+  | RetagFnEntry -> "fn_entry"
+  | RetagTwoPhase -> "two_phase"
+  | RetagRaw -> "raw"
+  | RetagDefault -> "default"
 
 (** Small helper *)
 let fun_sig_with_name_to_string (env : ('a, 'b) fmt_env) (indent : string)
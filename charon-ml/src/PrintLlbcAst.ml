@@ -12,6 +12,24 @@ type fmt_env = (statement, FunDeclId.id) PrintUtils.fmt_env
 module Ast = struct
   include PrintGAst
 
+  let rec pattern_to_string (pat : pattern) : string =
+    match pat with
+    | PatWildcard -> "_"
+    | PatVariant (variants, fields) -> (
+        let variants =
+          String.concat " | " (List.map VariantId.to_string variants)
+        in
+        match fields with
+        | [] -> variants
+        | _ ->
+            let fields =
+              List.map
+                (fun (fid, pat) ->
+                  FieldId.to_string fid ^ ": " ^ pattern_to_string pat)
+                fields
+            in
+            variants ^ " { " ^ String.concat ", " fields ^ " }")
+
   let rec statement_to_string (env : fmt_env) (indent : string)
       (indent_incr : string) (st : statement) : string =
     raw_statement_to_string env indent indent_incr st.content
@@ -28,6 +46,9 @@ module Ast = struct
         ^ VariantId.to_string variant_id
         ^ ")"
     | Drop p -> indent ^ "drop " ^ place_to_string env p
+    | Retag (kind, p) ->
+        indent ^ "retag[" ^ retag_kind_to_string kind ^ "] "
+        ^ place_to_string env p
     | Assert a -> assertion_to_string env indent a
     | Call call -> call_to_string env indent call
     | Panic -> indent ^ "panic"
@@ -102,11 +123,53 @@ module Ast = struct
                   ^ "\n" ^ indent1 ^ "}"
             in
             let branches = branches ^ otherwise in
-            indent ^ "match (" ^ p ^ ") {\n" ^ branches ^ "\n" ^ indent ^ "}")
+            indent ^ "match (" ^ p ^ ") {\n" ^ branches ^ "\n" ^ indent ^ "}"
+        | NestedMatch (p, branches, otherwise, kind) ->
+            let p = place_to_string env p in
+            let indent1 = indent ^ indent_incr in
+            let indent2 = indent1 ^ indent_incr in
+            let inner_to_string2 =
+              statement_to_string env indent2 indent_incr
+            in
+            let branches =
+              List.map
+                (fun (pat, be) ->
+                  indent ^ "| " ^ pattern_to_string pat ^ " => {\n"
+                  ^ inner_to_string2 be ^ "\n" ^ indent1 ^ "}")
+                branches
+            in
+            let branches = String.concat "\n" branches in
+            let otherwise =
+              match otherwise with
+              | None -> ""
+              | Some otherwise ->
+                  "\n" ^ indent1 ^ "_ => {\n" ^ inner_to_string2 otherwise
+                  ^ "\n" ^ indent1 ^ "}"
+            in
+            let branches = branches ^ otherwise in
+            let kind =
+              match kind with
+              | KindMatch -> "match"
+              | KindIfLet -> "if let match"
+              | KindLetElse -> "let else match"
+              | KindWhileLet -> "while let match"
+            in
+            indent ^ kind ^ " (" ^ p ^ ") {\n" ^ branches ^ "\n" ^ indent ^ "}")
+    | TryPropagate try_propagate ->
+        indent
+        ^ place_to_string env try_propagate.dest
+        ^ " := "
+        ^ operand_to_string env try_propagate.op
+        ^ "?"
     | Loop loop_st ->
         indent ^ "loop {\n"
         ^ statement_to_string env (indent ^ indent_incr) indent_incr loop_st
         ^ "\n" ^ indent ^ "}"
+    | Labeled (label, st) ->
+        indent ^ "'label" ^ LabelId.to_string label ^ ": {\n"
+        ^ statement_to_string env (indent ^ indent_incr) indent_incr st
+        ^ "\n" ^ indent ^ "}"
+    | Goto label -> indent ^ "goto 'label" ^ LabelId.to_string label
     | Error s -> indent ^ "ERROR(' " ^ s ^ "')"
 
   let fun_sig_to_string (env : fmt_env) (indent : string) (indent_incr : string)
@@ -271,22 +271,26 @@ and impl_elem_to_string (env : ('a, 'b) fmt_env) (elem : impl_elem) : string =
       (* Locally replace the generics and the predicates *)
       let env = fmt_env_update_generics_and_preds env generics in
       ty_to_string env ty
-  | ImplElemTrait impl_id -> begin
-      match TraitImplId.Map.find_opt impl_id env.trait_impls with
-      | None -> trait_impl_id_to_string env impl_id
-      | Some impl ->
-          (* Locally replace the generics and the predicates *)
-          let env = fmt_env_update_generics_and_preds env impl.generics in
-          (* Put the first type argument aside (it gives the type for which we
-             implement the trait) *)
-          let { trait_decl_id; decl_generics } = impl.impl_trait in
-          let ty, types = Collections.List.pop decl_generics.types in
-          let decl_generics = { decl_generics with types } in
-          let tr = { trait_decl_id; decl_generics } in
-          let ty = ty_to_string env ty in
-          let tr = trait_decl_ref_to_string env tr in
-          tr ^ " for " ^ ty
-    end
+  | ImplElemTrait (_, generics, impl_trait) ->
+      (* Locally replace the generics and the predicates *)
+      let env = fmt_env_update_generics_and_preds env generics in
+      (* Put the first type argument aside (it gives the type for which we
+         implement the trait) *)
+      let { trait_decl_id; decl_generics } = impl_trait in
+      let ty, types = Collections.List.pop decl_generics.types in
+      let decl_generics = { decl_generics with types } in
+      let tr = { trait_decl_id; decl_generics } in
+      let ty = ty_to_string env ty in
+      let tr = trait_decl_ref_to_string env tr in
+      tr ^ " for " ^ ty
+  | ImplElemBuiltin impl_trait ->
+      let { trait_decl_id; decl_generics } = impl_trait in
+      let ty, types = Collections.List.pop decl_generics.types in
+      let decl_generics = { decl_generics with types } in
+      let tr = { trait_decl_id; decl_generics } in
+      let ty = ty_to_string env ty in
+      let tr = trait_decl_ref_to_string env tr in
+      tr ^ " for " ^ ty
 
 and path_elem_to_string (env : ('a, 'b) fmt_env) (e : path_elem) : string =
   match e with
@@ -46,3 +46,4 @@ let literal_to_string (lit : literal) : string =
   | VChar c -> String.make 1 c
   | VStr s -> "\"" ^ s ^ "\""
   | VByteStr bs -> "[" ^ String.concat ", " (List.map string_of_int bs) ^ "]"
+  | VCStr bs -> "c[" ^ String.concat ", " (List.map string_of_int bs) ^ "]"
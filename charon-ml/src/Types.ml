@@ -630,6 +630,32 @@ type item_meta = {
   attr_info : attr_info;  (** Attributes and visibility. *)
   is_local : bool;
       (** `true` if the type decl is a local type decl, `false` if it comes from an external crate. *)
+  visibility : visibility;
+      (** The declared visibility of the item. Like [AttrInfo::public], this doesn't take
+        reachability into account.
+     *)
+  stability : stability option;  (** The stability attribute of the item, if any. *)
+  is_harness : bool;
+      (** Whether this item is a verification harness, i.e. a function annotated with one of the
+        harness attributes (`#[kani::proof]`, `#[quickcheck]`, or those given with
+        `--harness-attr`).
+     *)
+  codegen_attrs : codegen_attrs;
+      (** The codegen attributes of the item (`#[used]`, `#[link_section]`, etc.). *)
+  provenance : provenance;
+      (** The crate which defines the item, and whether its body was available. *)
+  dedup_hash : string option;
+      (** For the items which Charon generates and which may be generated identically when
+        extracting other crates (the specialized copies of blanket impls and their methods, and the
+        function pointer shims of closures): a hash of the contents of the item, which doesn't
+        depend on the ids of the crate. When merging several extractions, the items with the same
+        hash are duplicates of each other and only one of them needs to be kept, like weak symbols
+        at link time. This is `None` for the other items.
+     *)
+  annotations : annotation list;
+      (** The annotations found in the comments right before the item, if requested with
+        `--comment-annotations`. We only look for them in the local items.
+     *)
 }
 
 (** See the comments for [Name] *)
@@ -647,10 +673,22 @@ and path_elem =
       impl<T> PartialEq for List<T> { ...}
       ```
     We distinguish the two.
+
+    We record the self type (and the implemented trait) of the impl blocks in the names, so that
+    the names are self-describing, and stable across edits of unrelated impl blocks.
  *)
 and impl_elem =
   | ImplElemTy of generic_params * ty
-  | ImplElemTrait of trait_impl_id
+      (** An inherent impl block: its generics and its self type. *)
+  | ImplElemTrait of trait_impl_id * generic_params * trait_decl_ref
+      (** A trait impl block: its id, its generics and the implemented trait. The self type is the
+          first type argument of the trait reference. *)
+  | ImplElemBuiltin of trait_decl_ref
+      (** An implementation provided by the compiler (see [TraitRefKind::BuiltinOrAuto]): the
+          implemented trait. These have no declaration, hence this doesn't appear in the names of the
+          translated items; we use it to name the builtin impls (see
+          [TranslatedCrate::builtin_impl_name]).
+       *)
 
 (** An item name/path
 
@@ -709,8 +747,24 @@ and type_decl = {
   item_meta : item_meta;  (** Meta information associated with the item. *)
   generics : generic_params;
   kind : type_decl_kind;  (** The type kind: enum, struct, or opaque. *)
+  unpin : unpin_kind option;
+      (** Whether the type implements `Unpin`, i.e. whether its values may be moved after being
+        pinned. `None` for the types for which we don't compute it (e.g. `impl Trait` types).
+     *)
 }
 
+(** Whether a type implements `Unpin`. *)
+and unpin_kind =
+  | Unpin  (** The type always implements `Unpin`. *)
+  | UnpinIfParamsUnpin
+      (** The type implements `Unpin` when its type parameters do, like most generic types
+          (e.g. `Option<T>`).
+       *)
+  | NotUnpin
+      (** The type never implements `Unpin`, e.g. because it contains a `PhantomPinned` or has an
+          `impl !Unpin`. Pinned values of this type stay at the same address until they are dropped.
+       *)
+
 and type_decl_kind =
   | Struct of field list
   | Enum of variant list
@@ -745,7 +799,22 @@ and field = {
   attr_info : attr_info;
   field_name : string option;
   field_ty : ty;
+  field_recursion : field_recursion option;
+      (** If the type of this field mentions a type of the (mutually) recursive group of the type
+          declaration, how it does so. Only computed with `--annotate-recursive-fields`.
+       *)
 }
+
+(** How the type of a field refers to the recursive group of its type declaration. *)
+and field_recursion =
+  | RecIndirect
+      (** All the occurrences of the recursive group go through an indirection: `Box`, `Rc`, `Arc`,
+          `Vec`, a reference or a raw pointer.
+       *)
+  | RecDirect
+      (** Some occurrence of the recursive group doesn't go through a known indirection, e.g.
+          `Option<Self>` or `Foo<Self>` where `Foo` is a user-defined type.
+       *)
 [@@deriving show, ord]
 
 (* Hand-written because these don't exist on the rust side *)
@@ -36,6 +36,10 @@ type raw_statement =
   | Deinit of place
       (** We translate this to [crate::llbc_ast::RawStatement::Drop] in LLBC *)
   | Drop of place
+  | Retag of retag_kind * place
+      (** A retag, for the Stacked Borrows memory model. These are only kept with the `--keep-retags`
+          option.
+       *)
   | Assert of assertion
       (** A built-in assert, which corresponds to runtime checks that we remove, namely: bounds
           checks, over/underflow checks, div/rem by zero checks, pointer alignement check.
@@ -48,6 +48,10 @@ and raw_statement_of_json (js : json) : (raw_statement, string) result =
     | `Assoc [ ("Drop", drop) ] ->
         let* drop = place_of_json drop in
         Ok (Drop drop)
+    | `Assoc [ ("Retag", `List [ x_0; x_1 ]) ] ->
+        let* x_0 = retag_kind_of_json x_0 in
+        let* x_1 = place_of_json x_1 in
+        Ok (Retag (x_0, x_1))
     | `Assoc [ ("Assert", assert_) ] ->
         let* assert_ = assertion_of_json assert_ in
         Ok (Assert assert_)
@@ -58,7 +62,13 @@ and statement_of_json (id_to_file : id_to_file_map) (js : json) :
     (statement, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("span", span); ("content", content) ] ->
+    | `Assoc
+        [
+          ("span", span);
+          ("content", content);
+          ("borrow_liveness", _);
+          ("move_info", _);
+        ] ->
         let* span = span_of_json id_to_file span in
         let* content = raw_statement_of_json content in
         Ok ({ span; content } : statement)
@@ -102,7 +112,13 @@ and terminator_of_json (id_to_file : id_to_file_map) (js : json) :
     (terminator, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("span", span); ("content", content) ] ->
+    | `Assoc
+        [
+          ("span", span);
+          ("content", content);
+          ("borrow_liveness", _);
+          ("move_info", _);
+        ] ->
         let* span = span_of_json id_to_file span in
         let* content = raw_terminator_of_json id_to_file content in
         Ok ({ span; content } : terminator)
@@ -81,6 +81,8 @@ and literal =
   | VChar of char
   | VByteStr of int list
   | VStr of string
+  | VCStr of int list
+      (** A C string literal (`c"..."`), of type `&CStr`. The bytes include the trailing NUL. *)
 
 (** A scalar value. *)
 and scalar_value = {
@@ -92,10 +94,20 @@ and scalar_value = {
 }
 
 (** This is simlar to the Scalar value above. However, instead of storing
-    the float value itself, we store its String representation. This allows
+    the float value itself, we store its String representation and its bit pattern. This allows
     to derive the Eq and Ord traits, which are not implemented for floats
  *)
-and float_value = { float_value : string; float_ty : float_type }
+and float_value = {
+  float_value : string;
+      (** The value printed in decimal, e.g. `1.5`, `-0`, `inf` or `NaN`. This doesn't distinguish
+        the different NaNs.
+     *)
+  float_bits : big_int;
+      (** The bit pattern of the value, as given by `f32::to_bits` (and its equivalents for the other
+        float types). Unlike the string, this represents the value exactly.
+     *)
+  float_ty : float_type;
+}
 [@@deriving
   show,
     ord,
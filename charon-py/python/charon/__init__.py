@@ -0,0 +1,14 @@
+"""Load and inspect the `.llbc`/`.ullbc` files generated by Charon.
+
+Example:
+
+    import charon
+    krate = charon.load("my_crate.llbc")
+    for item in krate.items():
+        print(item.kind, item.name)
+        print(item)
+"""
+
+from ._charon import Crate, Item, load
+
+__all__ = ["Crate", "Item", "load"]
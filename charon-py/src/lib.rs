@@ -0,0 +1,153 @@
+//! Python bindings to load the `.llbc`/`.ullbc` files generated by Charon and inspect the
+//! translated crates. See `python/charon/__init__.py` for an example.
+//!
+//! The items are exposed through their kind, name, pretty-printed form and JSON (with the same
+//! format as in the `.llbc` files), which can be further inspected with the `json` module.
+use charon_lib::ast::*;
+use charon_lib::formatter::IntoFormatter;
+use charon_lib::import::read_crate_data;
+use charon_lib::pretty::FmtWithCtx;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::path::Path;
+
+/// A translated crate.
+#[pyclass(module = "charon")]
+struct Crate {
+    translated: TranslatedCrate,
+}
+
+/// An item of a translated crate.
+#[pyclass(module = "charon")]
+struct Item {
+    krate: Py<Crate>,
+    id: AnyTransId,
+}
+
+fn to_json(x: &impl serde::Serialize) -> PyResult<String> {
+    serde_json::to_string(x).map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+impl Crate {
+    fn item(&self, id: AnyTransId) -> AnyTransItem<'_> {
+        // The ids come from `all_items_with_ids`, hence the items exist.
+        self.translated.get_item(id).unwrap()
+    }
+
+    fn item_name(&self, id: AnyTransId) -> String {
+        let fmt_ctx = self.translated.into_fmt();
+        self.item(id).item_meta().name.fmt_with_ctx(&fmt_ctx)
+    }
+}
+
+#[pymethods]
+impl Crate {
+    /// The name of the crate.
+    #[getter]
+    fn name(&self) -> String {
+        self.translated.crate_name.clone()
+    }
+
+    /// The items of the crate, in the order in which they were translated.
+    fn items(slf: &Bound<'_, Self>) -> Vec<Item> {
+        slf.borrow()
+            .translated
+            .all_items_with_ids()
+            .map(|(id, _)| Item {
+                krate: slf.clone().unbind(),
+                id,
+            })
+            .collect()
+    }
+
+    /// The items with the given name (there can be several, e.g. for trait impls).
+    fn get(slf: &Bound<'_, Self>, name: &str) -> Vec<Item> {
+        Self::items(slf)
+            .into_iter()
+            .filter(|item| slf.borrow().item_name(item.id) == name)
+            .collect()
+    }
+
+    /// The whole crate, as JSON.
+    fn to_json(&self) -> PyResult<String> {
+        to_json(&self.translated)
+    }
+
+    fn __len__(&self) -> usize {
+        self.translated.all_items_with_ids().count()
+    }
+
+    fn __str__(&self) -> String {
+        self.translated.to_string()
+    }
+}
+
+#[pymethods]
+impl Item {
+    /// The kind of the item: one of `"Type"`, `"Fun"`, `"Global"`, `"TraitDecl"` and
+    /// `"TraitImpl"`.
+    #[getter]
+    fn kind(&self, py: Python<'_>) -> &'static str {
+        self.krate.borrow(py).item(self.id).variant_name()
+    }
+
+    /// The fully-qualified name of the item.
+    #[getter]
+    fn name(&self, py: Python<'_>) -> String {
+        self.krate.borrow(py).item_name(self.id)
+    }
+
+    /// The item, as JSON. This doesn't include the body of functions and globals (see
+    /// `body_to_json`).
+    fn to_json(&self, py: Python<'_>) -> PyResult<String> {
+        match self.krate.borrow(py).item(self.id) {
+            AnyTransItem::Type(d) => to_json(d),
+            AnyTransItem::Fun(d) => to_json(d),
+            AnyTransItem::Global(d) => to_json(d),
+            AnyTransItem::TraitDecl(d) => to_json(d),
+            AnyTransItem::TraitImpl(d) => to_json(d),
+        }
+    }
+
+    /// The body of a function or a global, as JSON, or `None` if the item doesn't have a body.
+    fn body_to_json(&self, py: Python<'_>) -> PyResult<Option<String>> {
+        let krate = self.krate.borrow(py);
+        let body_id = match krate.item(self.id) {
+            AnyTransItem::Fun(d) => d.body,
+            AnyTransItem::Global(d) => d.body,
+            _ => return Ok(None),
+        };
+        body_id
+            .ok()
+            .and_then(|id| krate.translated.bodies.get(id))
+            .map(to_json)
+            .transpose()
+    }
+
+    fn __str__(&self, py: Python<'_>) -> String {
+        let krate = self.krate.borrow(py);
+        krate.translated.into_fmt().format_decl_id(self.id)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> String {
+        format!("<charon.Item {} {}>", self.kind(py), self.name(py))
+    }
+}
+
+/// Load a crate from a `.llbc` or `.ullbc` file.
+#[pyfunction]
+fn load(path: &str) -> PyResult<Crate> {
+    let crate_data = read_crate_data(Path::new(path))
+        .map_err(|err| PyValueError::new_err(format!("{err:#}")))?;
+    Ok(Crate {
+        translated: crate_data.translated,
+    })
+}
+
+#[pymodule]
+fn _charon(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Crate>()?;
+    m.add_class::<Item>()?;
+    m.add_function(wrap_pyfunction!(load, m)?)?;
+    Ok(())
+}
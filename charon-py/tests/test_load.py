@@ -0,0 +1,48 @@
+"""Tests of the Python bindings. Run them with `pytest` after `maturin develop`. They need the
+`charon` executable: either set `CHARON`, or build it with `make` at the root of the repository,
+which puts it in `bin/`."""
+
+import os
+import pathlib
+import subprocess
+
+import pytest
+
+import charon
+
+CHARON = os.environ.get(
+    "CHARON", pathlib.Path(__file__).resolve().parents[2] / "bin" / "charon"
+)
+
+
+def translate(tmp_path, code):
+    """Translate a single-file crate named `test_crate` and return the path of the output."""
+    source = tmp_path / "test_crate.rs"
+    source.write_text(code)
+    dest = tmp_path / "test_crate.llbc"
+    subprocess.run(
+        [
+            CHARON,
+            "--no-cargo",
+            f"--input={source}",
+            "--crate=test_crate",
+            f"--dest-file={dest}",
+        ],
+        check=True,
+    )
+    return dest
+
+
+def test_load_and_read_name(tmp_path):
+    krate = charon.load(str(translate(tmp_path, "pub fn foo() -> u32 { 42 }\n")))
+    assert krate.name == "test_crate"
+    [foo] = krate.get("test_crate::foo")
+    assert foo.kind == "Fun"
+    assert foo.name == "test_crate::foo"
+    assert foo.body_to_json() is not None
+    assert "fn test_crate::foo()" in str(foo)
+
+
+def test_load_missing_file(tmp_path):
+    with pytest.raises(ValueError, match="could not open"):
+        charon.load(str(tmp_path / "missing.llbc"))
@@ -0,0 +1,27 @@
+//! Record the commit of charon and the version of rustc, which we embed in the files we produce
+//! (see `charon_lib::export::BuildInfo`).
+use std::process::Command;
+
+fn command_output(cmd: &mut Command) -> Option<String> {
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let output = String::from_utf8(output.stdout).ok()?;
+    Some(output.trim().to_owned())
+}
+
+fn main() {
+    // Not available when building from a source archive (e.g. with nix or from crates.io).
+    if let Some(commit) = command_output(Command::new("git").args(["rev-parse", "HEAD"])) {
+        println!("cargo:rustc-env=CHARON_COMMIT={commit}");
+    }
+    // The driver links to the rustc which builds charon (see `rust-toolchain`).
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    if let Some(version) = command_output(Command::new(rustc).arg("--version")) {
+        println!("cargo:rustc-env=CHARON_RUSTC_VERSION={version}");
+    }
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
+    println!("cargo:rerun-if-changed=rust-toolchain");
+}
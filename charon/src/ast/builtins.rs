@@ -24,6 +24,7 @@ pub static EXPLICIT_PANIC_NAME: &[&str] = &["core", "panicking", "panic_explicit
 pub enum BuiltinFun {
     Panic,
     BoxNew,
+    Async(ast::AsyncBuiltin),
 }
 
 impl BuiltinFun {
@@ -32,11 +33,89 @@ impl BuiltinFun {
     pub fn to_ullbc_builtin_fun(self) -> ast::BuiltinFunId {
         match self {
             BuiltinFun::BoxNew => ast::BuiltinFunId::BoxNew,
+            BuiltinFun::Async(op) => ast::BuiltinFunId::Async(op),
             BuiltinFun::Panic => panic!(),
         }
     }
 }
 
+const PIN_NAME: &[&str] = &["core", "pin", "Pin"];
+const CONTEXT_NAME: &[&str] = &["core", "task", "wake", "Context"];
+const WAKER_NAME: &[&str] = &["core", "task", "wake", "Waker"];
+
+/// The trait methods we recognize with `--async-builtins`: the path of the method.
+pub static ASYNC_TRAIT_BUILTINS: &[(&[&str], ast::AsyncBuiltin)] = &[(
+    &["core", "future", "future", "Future", "poll"],
+    ast::AsyncBuiltin::FuturePoll,
+)];
+
+/// The inherent methods we recognize with `--async-builtins`: the path of the `Self` type and the
+/// name of the method.
+pub static ASYNC_INHERENT_BUILTINS: &[(&[&str], &str, ast::AsyncBuiltin)] = &[
+    (PIN_NAME, "new", ast::AsyncBuiltin::PinNew),
+    (
+        PIN_NAME,
+        "new_unchecked",
+        ast::AsyncBuiltin::PinNewUnchecked,
+    ),
+    (PIN_NAME, "as_ref", ast::AsyncBuiltin::PinAsRef),
+    (PIN_NAME, "as_mut", ast::AsyncBuiltin::PinAsMut),
+    (PIN_NAME, "get_ref", ast::AsyncBuiltin::PinGetRef),
+    (PIN_NAME, "get_mut", ast::AsyncBuiltin::PinGetMut),
+    (
+        PIN_NAME,
+        "get_unchecked_mut",
+        ast::AsyncBuiltin::PinGetUncheckedMut,
+    ),
+    (PIN_NAME, "into_inner", ast::AsyncBuiltin::PinIntoInner),
+    (
+        PIN_NAME,
+        "map_unchecked",
+        ast::AsyncBuiltin::PinMapUnchecked,
+    ),
+    (
+        PIN_NAME,
+        "map_unchecked_mut",
+        ast::AsyncBuiltin::PinMapUncheckedMut,
+    ),
+    (
+        CONTEXT_NAME,
+        "from_waker",
+        ast::AsyncBuiltin::ContextFromWaker,
+    ),
+    (CONTEXT_NAME, "waker", ast::AsyncBuiltin::ContextWaker),
+    (WAKER_NAME, "wake", ast::AsyncBuiltin::WakerWake),
+    (WAKER_NAME, "wake_by_ref", ast::AsyncBuiltin::WakerWakeByRef),
+];
+
+/// Recognize the operations of the `async` runtime (see [ast::AsyncBuiltin]) by their name. The
+/// inherent methods are recognized by the type of their impl block, whose name must have been
+/// registered in `krate`.
+pub fn recognize_async_builtin(
+    krate: &ast::TranslatedCrate,
+    name: &Name,
+) -> Option<ast::AsyncBuiltin> {
+    if let Some((_, op)) = ASYNC_TRAIT_BUILTINS
+        .iter()
+        .find(|(path, _)| name.equals_ref_name(path))
+    {
+        return Some(*op);
+    }
+    let [.., PathElem::Impl(ImplElem::Ty(_, ty), _), PathElem::Ident(method, _)] =
+        name.name.as_slice()
+    else {
+        return None;
+    };
+    let TyKind::Adt(TypeId::Adt(type_id), _) = ty.kind() else {
+        return None;
+    };
+    let type_name = krate.item_name(*type_id)?;
+    ASYNC_INHERENT_BUILTINS
+        .iter()
+        .find(|(path, m, _)| *m == method.as_str() && type_name.equals_ref_name(path))
+        .map(|(_, _, op)| *op)
+}
+
 impl BuiltinTy {
     pub fn get_name(self) -> Name {
         let name: &[_] = match self {
@@ -170,6 +170,26 @@ pub enum NullOp {
     UbChecks,
 }
 
+/// The layout of a type, as computed by rustc.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Layout {
+    /// The size of the type, in bytes.
+    pub size: u64,
+    /// The ABI alignment of the type, in bytes.
+    pub align: u64,
+    /// Whether the type has a niche, i.e. invalid bit patterns that rustc may use to store the
+    /// discriminant of an enclosing enum.
+    pub has_niche: bool,
+}
+
+/// The layouts of the source and target types of a [CastKind::Transmute]. A layout is `None` when
+/// it can't be computed, e.g. because the type depends on generic parameters.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct TransmuteLayouts {
+    pub src: Option<Layout>,
+    pub tgt: Option<Layout>,
+}
+
 /// For all the variants: the first type gives the source type, the second one gives
 /// the destination type.
 #[derive(
@@ -181,6 +201,12 @@ pub enum CastKind {
     /// Remark: for now we don't support conversions with Char.
     Scalar(LiteralTy, LiteralTy),
     RawPtr(Ty, Ty),
+    /// Cast of a raw pointer to an integer which exposes the provenance of the pointer, so that it
+    /// can be recovered by a later [CastKind::WithExposedProvenance] (`ptr as usize`).
+    ExposeProvenance(Ty, Ty),
+    /// Cast of an integer to a raw pointer which picks up the provenance of a previously exposed
+    /// pointer (`addr as *const T`).
+    WithExposedProvenance(Ty, Ty),
     FnPtr(Ty, Ty),
     /// [Unsize coercion](https://doc.rust-lang.org/std/ops/trait.CoerceUnsized.html). This is
     /// either `[T; N]` -> `[T]` or `T: Trait` -> `dyn Trait` coercions, behind a pointer
@@ -189,8 +215,9 @@ pub enum CastKind {
     /// The special case of `&[T; N]` -> `&[T]` coercion is caught by `UnOp::ArrayToSlice`.
     Unsize(Ty, Ty),
     /// Reinterprets the bits of a value of one type as another type, i.e. exactly what
-    /// [`std::mem::transmute`] does.
-    Transmute(Ty, Ty),
+    /// [`std::mem::transmute`] does. We record the layouts of the two types, which validity
+    /// checkers can use.
+    Transmute(Ty, Ty, #[drive(skip)] TransmuteLayouts),
 }
 
 /// Binary operations.
@@ -233,6 +260,15 @@ pub enum BinOp {
     // No Offset binary operation: this is an operation on raw pointers
 }
 
+/// The types of the operands of a binary operation. They give the width of the operation, e.g. to
+/// distinguish a `u128` addition from a `u32` one. The two types are the same, except for the
+/// shifts whose right operand can have any integer type (e.g. `x << 3u32` with `x: u128`).
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, Drive, DriveMut)]
+pub struct BinOpTys {
+    pub lhs_ty: Ty,
+    pub rhs_ty: Ty,
+}
+
 #[derive(
     Debug,
     PartialEq,
@@ -321,6 +357,74 @@ pub enum BuiltinFunId {
     /// - `fn SliceSubSliceMut<T>(&mut [T], usize, usize) -> &mut [T]`
     /// - etc
     Index(BuiltinIndexOp),
+    /// An operation of the `async` runtime, recognized with `--async-builtins`.
+    Async(AsyncBuiltin),
+}
+
+/// The operations of the `async` runtime which we treat as builtins (with `--async-builtins`), so
+/// that the code which drives futures doesn't refer to opaque functions of the standard library.
+/// `Ptr` is a pointer type and `T` the type it points to (`<Ptr as Deref>::Target`).
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, VariantName, Serialize, Deserialize, Drive, DriveMut,
+)]
+pub enum AsyncBuiltin {
+    /// `core::future::Future::poll`:
+    /// `fn FuturePoll<F>[F: Future](Pin<&mut F>, &mut Context<'_>) -> Poll<F::Output>`.
+    ///
+    /// Runs the future until it completes or has to wait. Unlike the other builtins, the generics
+    /// contain the trait reference which gives the implementation of `poll`.
+    FuturePoll,
+    /// `core::pin::Pin::new`: `fn PinNew<Ptr>(Ptr) -> Pin<Ptr>`, where `T: Unpin`.
+    ///
+    /// Wraps the pointer; this has no runtime effect.
+    PinNew,
+    /// `core::pin::Pin::new_unchecked`: `unsafe fn PinNewUnchecked<Ptr>(Ptr) -> Pin<Ptr>`.
+    ///
+    /// Wraps the pointer; the caller promises that the pointee won't move until it is dropped.
+    PinNewUnchecked,
+    /// `core::pin::Pin::as_ref`: `fn PinAsRef<Ptr>(&Pin<Ptr>) -> Pin<&T>`.
+    PinAsRef,
+    /// `core::pin::Pin::as_mut`: `fn PinAsMut<Ptr>(&mut Pin<Ptr>) -> Pin<&mut T>`.
+    ///
+    /// Reborrows the pinned pointer, typically to call [AsyncBuiltin::FuturePoll] several times.
+    PinAsMut,
+    /// `core::pin::Pin::get_ref`: `fn PinGetRef<T>(Pin<&T>) -> &T`.
+    PinGetRef,
+    /// `core::pin::Pin::get_mut`: `fn PinGetMut<T>(Pin<&mut T>) -> &mut T`, where `T: Unpin`.
+    PinGetMut,
+    /// `core::pin::Pin::get_unchecked_mut`: `unsafe fn PinGetUncheckedMut<T>(Pin<&mut T>) -> &mut T`.
+    ///
+    /// The caller promises not to move the pointee out of the reference.
+    PinGetUncheckedMut,
+    /// `core::pin::Pin::into_inner`: `fn PinIntoInner<Ptr>(Pin<Ptr>) -> Ptr`, where `T: Unpin`.
+    PinIntoInner,
+    /// `core::pin::Pin::map_unchecked`:
+    /// `unsafe fn PinMapUnchecked<T, U, F>[F: FnOnce(&T) -> &U](Pin<&T>, F) -> Pin<&U>`.
+    ///
+    /// A pin projection: applies `F` to the pinned reference, typically to get a reference to a
+    /// field which is structurally pinned.
+    PinMapUnchecked,
+    /// `core::pin::Pin::map_unchecked_mut`:
+    /// `unsafe fn PinMapUncheckedMut<T, U, F>[F: FnOnce(&mut T) -> &mut U](Pin<&mut T>, F) -> Pin<&mut U>`.
+    ///
+    /// The mutable version of [AsyncBuiltin::PinMapUnchecked]; this is how a `poll` function
+    /// gets a pinned reference to the future stored in one of the fields of `Self`.
+    PinMapUncheckedMut,
+    /// `core::task::Context::from_waker`: `fn ContextFromWaker(&Waker) -> Context<'_>`.
+    ///
+    /// Builds the context passed to [AsyncBuiltin::FuturePoll].
+    ContextFromWaker,
+    /// `core::task::Context::waker`: `fn ContextWaker(&Context<'_>) -> &Waker`.
+    ContextWaker,
+    /// `core::task::Waker::wake`: `fn WakerWake(Waker)`.
+    ///
+    /// Signals the executor that the task which owns the waker can make progress again, and
+    /// consumes the waker.
+    WakerWake,
+    /// `core::task::Waker::wake_by_ref`: `fn WakerWakeByRef(&Waker)`.
+    ///
+    /// Like [AsyncBuiltin::WakerWake], without consuming the waker.
+    WakerWakeByRef,
 }
 
 /// One of 8 built-in indexing operations.
@@ -356,7 +460,9 @@ pub struct FnPtr {
 
 /// A constant expression.
 ///
-/// Only the [Literal] and [Var] cases are left in the final LLBC.
+/// By default, only the [Literal] and [Var] cases are left in the final LLBC. With
+/// `--simplify-constants globals` or `--simplify-constants none`, the final LLBC may also contain
+/// the [Adt], [Array], [Ref] and [MutPtr] cases (and [Global] with `none`).
 ///
 /// The other cases come from a straight translation from the MIR:
 ///
@@ -404,8 +510,11 @@ pub enum RawConstantExpr {
     /// Less frequently: arbitrary ADT values.
     ///
     /// We eliminate this case in a micro-pass.
-    #[charon::opaque]
     Adt(Option<VariantId>, Vec<ConstantExpr>),
+    /// A constant array, e.g. the value of a `const` of array type used in a pattern.
+    ///
+    /// We eliminate this case in a micro-pass, unless `--simplify-constants none` is used.
+    Array(Vec<ConstantExpr>),
     /// The value is a top-level constant/static.
     ///
     /// We eliminate this case in a micro-pass.
@@ -424,7 +533,6 @@ pub enum RawConstantExpr {
     ///   let l = V::<N, T>::LEN; // We need to provided a substitution here
     /// }
     /// ```
-    #[charon::opaque]
     Global(GlobalDeclRef),
     ///
     /// A trait constant.
@@ -442,12 +550,10 @@ pub enum RawConstantExpr {
     /// A shared reference to a constant value.
     ///
     /// We eliminate this case in a micro-pass.
-    #[charon::opaque]
     Ref(Box<ConstantExpr>),
     /// A mutable pointer to a mutable static.
     ///
     /// We eliminate this case in a micro-pass.
-    #[charon::opaque]
     MutPtr(Box<ConstantExpr>),
     /// A const generic var
     Var(ConstGenericVarId),
@@ -459,6 +565,10 @@ pub enum RawConstantExpr {
 pub struct ConstantExpr {
     pub value: RawConstantExpr,
     pub ty: Ty,
+    /// For integer literals: whether the literal in the source code had an explicit type suffix
+    /// (like `1u32`) or had its type inferred (like `1`). This is `None` for the other constants,
+    /// and for the literals we couldn't find in the source code (e.g. those computed by rustc).
+    pub has_type_suffix: Option<bool>,
 }
 
 /// TODO: we could factor out [Rvalue] and function calls (for LLBC, not ULLBC).
@@ -477,8 +587,9 @@ pub enum Rvalue {
     /// Takes a raw pointer with the given mutability to the given place. This is generated by
     /// pointer casts like `&v as *const _` or raw borrow expressions like `&raw const v.`
     RawPtr(Place, RefKind),
-    /// Binary operations (note that we merge "checked" and "unchecked" binops)
-    BinaryOp(BinOp, Operand, Operand),
+    /// Binary operations (note that we merge "checked" and "unchecked" binops), with the types of
+    /// their operands.
+    BinaryOp(BinOp, Operand, Operand, BinOpTys),
     /// Unary operation (e.g. not, neg)
     UnaryOp(UnOp, Operand),
     /// Nullary operation (e.g. `size_of`)
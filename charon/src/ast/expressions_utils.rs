@@ -10,6 +10,67 @@ impl Place {
     }
 }
 
+impl ProjectionElem {
+    /// Compute the type of the place obtained by applying this projection to a place of type
+    /// `ty`. Returns `Ok(None)` if the type can't be determined, e.g. because the type declaration
+    /// is missing, and an error if the projection doesn't apply to this type.
+    pub fn project_ty(&self, krate: &TranslatedCrate, ty: &Ty) -> Result<Option<Ty>, String> {
+        use ProjectionElem::*;
+        let ty = match (self, ty.kind()) {
+            (Deref, TyKind::Ref(_, ty, _) | TyKind::RawPtr(ty, _)) => ty.clone(),
+            (Deref, _) if let Some(ty) = ty.as_box() => ty.clone(),
+            (Field(FieldProjKind::Adt(id, variant), field_id), TyKind::Adt(type_id, args)) => {
+                if *type_id != TypeId::Adt(*id) {
+                    return Err(format!(
+                        "field projection on {id:?} applied to a {type_id:?}"
+                    ));
+                }
+                let Some(decl) = krate.type_decls.get(*id) else {
+                    return Ok(None);
+                };
+                let fields = match (&decl.kind, variant) {
+                    (TypeDeclKind::Struct(fields) | TypeDeclKind::Union(fields), None) => fields,
+                    (TypeDeclKind::Enum(variants), Some(variant)) => match variants.get(*variant) {
+                        Some(variant) => &variant.fields,
+                        None => return Err(format!("{id:?} has no variant {variant:?}")),
+                    },
+                    (TypeDeclKind::Opaque | TypeDeclKind::Error(_), _) => return Ok(None),
+                    _ => return Err(format!("invalid field projection on {id:?}")),
+                };
+                match fields.get(*field_id) {
+                    Some(field) => field.ty.substitute(args),
+                    None => return Err(format!("{id:?} has no field {field_id:?}")),
+                }
+            }
+            (Field(FieldProjKind::Tuple(arity), field_id), TyKind::Adt(TypeId::Tuple, args)) => {
+                if *arity != args.types.len() {
+                    return Err(format!(
+                        "projection on a tuple of arity {arity} applied to a tuple of arity {}",
+                        args.types.len()
+                    ));
+                }
+                match args.types.get(TypeVarId::new(field_id.index())) {
+                    Some(ty) => ty.clone(),
+                    None => return Err(format!("tuple has no field {field_id:?}")),
+                }
+            }
+            // The closure states are only known after `update_closure_signatures`.
+            (Field(FieldProjKind::ClosureState, _), _) => return Ok(None),
+            (
+                Index { .. },
+                TyKind::Adt(TypeId::Builtin(BuiltinTy::Array | BuiltinTy::Slice), args),
+            ) if let Some(elem_ty) = args.types.get(TypeVarId::ZERO) => elem_ty.clone(),
+            (Subslice { .. }, TyKind::Adt(TypeId::Builtin(BuiltinTy::Slice), _)) => ty.clone(),
+            // Taking a subslice of an array gives an array with a different length.
+            (Subslice { .. }, TyKind::Adt(TypeId::Builtin(BuiltinTy::Array), _)) => {
+                return Ok(None)
+            }
+            _ => return Err(format!("invalid projection {self:?} on type {ty:?}")),
+        };
+        Ok(Some(ty))
+    }
+}
+
 impl BorrowKind {
     pub fn mutable(x: bool) -> Self {
         if x {
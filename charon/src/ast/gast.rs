@@ -13,10 +13,12 @@ use derive_visitor::{Drive, DriveMut, Event, Visitor, VisitorMut};
 use macros::EnumIsA;
 use macros::EnumToGetters;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 generate_index_type!(FunDeclId, "Fun");
 generate_index_type!(BodyId, "Body");
+generate_index_type!(ScopeId, "Scope");
+generate_index_type!(BorrowId, "Borrow");
 
 /// A variable
 #[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
@@ -54,9 +56,86 @@ pub struct GExprBody<T> {
     /// are added to statements in the late `recover_body_comments` pass.
     #[charon::opaque]
     pub comments: Vec<(usize, Vec<String>)>,
+    /// The lexical scopes and the user variables of the body, as recorded in the MIR debug info,
+    /// if requested with `--debug-info`.
+    #[charon::opaque]
+    #[serde(default)]
+    pub debug_info: Option<BodyDebugInfo>,
     pub body: T,
 }
 
+/// The borrows created and used for the last time by a statement, computed by the opt-in
+/// [crate::transform::borrow_liveness] pass. The borrows (i.e. the [Rvalue::Ref]s) are numbered
+/// per body.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Drive, DriveMut)]
+pub struct BorrowLiveness {
+    /// The borrow created by this statement, if it is an assignment of an [Rvalue::Ref].
+    pub created: Option<BorrowId>,
+    /// The borrows used for the last time by this statement, i.e. the borrows which are not live
+    /// after it.
+    pub last_uses: Vec<BorrowId>,
+}
+
+/// The locals moved, copied and dropped by a statement, computed by the opt-in
+/// [crate::transform::compute_move_info] pass. The moves, copies and drops of the places which
+/// start with a local (e.g. `move x.0`) count as moves, copies and drops of the local.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Drive, DriveMut)]
+pub struct MoveInfo {
+    pub moved: Vec<VarId>,
+    pub copied: Vec<VarId>,
+    pub dropped: Vec<VarId>,
+}
+
+/// The debug info of a body: its lexical scopes and its user variables.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Drive, DriveMut)]
+pub struct BodyDebugInfo {
+    pub scopes: Vector<ScopeId, DebugScope>,
+    pub vars: Vec<VarDebugInfo>,
+}
+
+/// A lexical scope.
+#[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
+pub struct DebugScope {
+    pub span: Span,
+    /// The scope this scope is nested in. This is `None` for the outermost scope.
+    pub parent: Option<ScopeId>,
+}
+
+/// A user variable. Note that a user variable doesn't always correspond to a local: it may be
+/// a projection of a local (e.g. the variables bound by a pattern, or the variables captured by
+/// a closure), a constant, or it may be split over several places (see `fragment`).
+#[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
+pub struct VarDebugInfo {
+    pub name: String,
+    pub span: Span,
+    /// The scope in which the variable is declared.
+    pub scope: ScopeId,
+    /// If the variable is split over several places, the part of the variable this entry
+    /// describes (e.g. `[Field(1)]` for the second field of a tuple).
+    pub fragment: Option<Vec<DebugProjectionElem>>,
+    pub value: DebugValue,
+    /// If the variable is an argument of the function, its (1-based) index.
+    pub argument_index: Option<usize>,
+}
+
+/// The value of a user variable.
+#[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
+pub enum DebugValue {
+    /// The variable lives in a projection of a local.
+    Place(VarId, Vec<DebugProjectionElem>),
+    /// The variable was replaced by a constant.
+    Const,
+}
+
+/// A projection in a [DebugValue::Place]. Unlike [ProjectionElem], this doesn't carry type
+/// information.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Drive, DriveMut)]
+pub enum DebugProjectionElem {
+    Deref,
+    Field(FieldId),
+    Downcast(VariantId),
+}
+
 // The derive macro doesn't handle generics well.
 impl<T: Drive> Drive for GExprBody<T> {
     fn drive<V: Visitor>(&self, visitor: &mut V) {
@@ -64,6 +143,7 @@ impl<T: Drive> Drive for GExprBody<T> {
         self.span.drive(visitor);
         self.arg_count.drive(visitor);
         self.locals.drive(visitor);
+        self.debug_info.drive(visitor);
         self.body.drive(visitor);
         visitor.visit(self, Event::Exit);
     }
@@ -74,6 +154,7 @@ impl<T: DriveMut> DriveMut for GExprBody<T> {
         self.span.drive_mut(visitor);
         self.arg_count.drive_mut(visitor);
         self.locals.drive_mut(visitor);
+        self.debug_info.drive_mut(visitor);
         self.body.drive_mut(visitor);
         visitor.visit(self, Event::Exit);
     }
@@ -139,6 +220,26 @@ pub enum ItemKind {
     },
 }
 
+/// The symbol-level information of a function or global, given by its `#[no_mangle]`,
+/// `#[export_name]`, `#[link_name]` and `#[linkage]` attributes, and by the `extern` block it is
+/// declared in.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Drive, DriveMut, PartialEq, Eq)]
+pub struct Linkage {
+    /// The name of the symbol, if the item is exported under an unmangled name: the name of the
+    /// item for `#[no_mangle]` items, and the given name for `#[export_name = "..."]` items.
+    pub export_name: Option<String>,
+    /// Whether the item has the `#[no_mangle]` attribute.
+    pub no_mangle: bool,
+    /// The linkage given by the `#[linkage = "..."]` attribute, as named by rustc (e.g.
+    /// `WeakAny` for `#[linkage = "weak"]`).
+    pub kind: Option<String>,
+    /// For items declared in an `extern { .. }` block: the name of the symbol they link to, i.e.
+    /// the name given by `#[link_name = "..."]` or else the name of the item.
+    pub link_name: Option<String>,
+    /// For items declared in an `extern { .. }` block: the ABI of the block (e.g. `C`).
+    pub foreign_abi: Option<String>,
+}
+
 /// A function definition
 #[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
 pub struct FunDecl {
@@ -151,6 +252,8 @@ pub struct FunDecl {
     pub signature: FunSig,
     /// The function kind: "regular" function, trait method declaration, etc.
     pub kind: ItemKind,
+    /// The export name and linkage of the function.
+    pub linkage: Linkage,
     /// The function body, unless the function is opaque.
     /// Opaque functions are: external functions, or local functions tagged
     /// as opaque.
@@ -166,8 +269,16 @@ pub struct GlobalDecl {
     pub item_meta: ItemMeta,
     pub generics: GenericParams,
     pub ty: Ty,
+    /// Whether this is a `static mut`.
+    pub is_mutable: bool,
+    /// Whether the type of the global has interior mutability (i.e. doesn't implement `Freeze`),
+    /// like `AtomicU32` or `Mutex<T>`. A `static` whose type has interior mutability can be
+    /// modified even if it isn't `mut`.
+    pub has_interior_mutability: bool,
     /// The global kind: "regular" function, trait const declaration, etc.
     pub kind: ItemKind,
+    /// The export name and linkage of the global (only relevant for statics).
+    pub linkage: Linkage,
     pub body: Result<BodyId, Opaque>,
 }
 
@@ -241,12 +352,12 @@ pub struct TraitDecl {
     pub consts: Vec<(TraitItemName, Ty)>,
     /// Records associated constants that have a default value.
     #[charon::opaque]
-    pub const_defaults: HashMap<TraitItemName, GlobalDeclRef>,
+    pub const_defaults: BTreeMap<TraitItemName, GlobalDeclRef>,
     /// The associated types declared in the trait.
     pub types: Vec<TraitItemName>,
     /// Records associated types that have a default value.
     #[charon::opaque]
-    pub type_defaults: HashMap<TraitItemName, Ty>,
+    pub type_defaults: BTreeMap<TraitItemName, Ty>,
     /// List of trait clauses that apply to each associated type. This is used during translation,
     /// but the `lift_associated_item_clauses` pass moves them to be parent clauses later. Hence
     /// this is empty after that pass.
@@ -263,6 +374,29 @@ pub struct TraitDecl {
     /// The provided methods are the methods with a default implementation. The corresponding
     /// `FunDecl`s may have a body, according to the usual rules for extracting function bodies.
     pub provided_methods: Vec<(TraitItemName, FunDeclId)>,
+    /// The slots of the methods in the vtable of `dyn Trait`, following the layout used by rustc.
+    /// The first slots hold the drop glue, the size and the alignment of the type, and the
+    /// methods of the supertraits come before the methods of the trait. The methods which can't
+    /// be called on a trait object (e.g. because of a `Self: Sized` bound) don't have a slot.
+    /// This is `None` if the trait is not dyn-compatible.
+    #[drive(skip)]
+    pub vtable_slots: Option<Vec<(TraitItemName, usize)>>,
+    /// The reasons why the trait is not dyn-compatible (i.e. why it can't be used as `dyn Trait`),
+    /// as given by rustc. This is empty if the trait is dyn-compatible.
+    #[drive(skip)]
+    pub dyn_compatibility_violations: Vec<DynCompatibilityViolation>,
+}
+
+/// A reason why a trait is not dyn-compatible (see [TraitDecl::dyn_compatibility_violations]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynCompatibilityViolation {
+    /// The method, associated constant or associated type which causes the violation, if any. It
+    /// may come from a supertrait. This is `None` for the violations which concern the trait as a
+    /// whole, e.g. a `Self: Sized` bound.
+    pub item: Option<TraitItemName>,
+    /// The explanation given by rustc, e.g. "method `foo` references the `Self` type in its
+    /// return type".
+    pub reason: String,
 }
 
 /// A trait **implementation**.
@@ -299,6 +433,42 @@ pub struct TraitImpl {
     pub required_methods: Vec<(TraitItemName, FunDeclId)>,
     /// The re-implemented provided methods
     pub provided_methods: Vec<(TraitItemName, FunDeclId)>,
+    /// The obligations that the impl must satisfy to be well-formed, with the proofs that they
+    /// hold. There is one obligation for each clause of the trait declaration: the parent clauses
+    /// and the clauses on the associated types.
+    pub obligations: Vec<ImplObligation>,
+}
+
+/// An obligation of a trait impl (see [TraitImpl::obligations]), e.g. for:
+/// ```text
+/// trait Foo: Clone {
+///     type Item: Copy;
+/// }
+///
+/// impl<T: Copy> Foo for Wrapper<T> {
+///     type Item = T;
+/// }
+/// ```
+/// the impl must prove `Wrapper<T>: Clone` and `T: Copy`.
+#[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
+pub struct ImplObligation {
+    /// The clause of the trait declaration which gives rise to this obligation.
+    pub origin: ObligationOrigin,
+    /// The proof that the obligation holds. Its `trait_decl_ref` is the obligation itself, i.e.
+    /// the clause instantiated with the arguments of the impl.
+    pub trait_ref: TraitRef,
+}
+
+/// Where an [ImplObligation] comes from.
+#[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
+#[charon::variants_prefix("Obligation")]
+pub enum ObligationOrigin {
+    /// A parent clause of the trait declaration (a supertrait or a `where` clause).
+    ParentClause(#[drive(skip)] TraitClauseId),
+    /// A clause on an associated type of the trait declaration (e.g. `type Item: Copy`). After
+    /// the `lift_associated_item_clauses` pass, these clauses become parent clauses of the trait
+    /// declaration and the id is the one of the corresponding parent clause.
+    AssocTypeBound(#[drive(skip)] TraitItemName, #[drive(skip)] TraitClauseId),
 }
 
 /// A function operand is used in function calls.
@@ -313,11 +483,27 @@ pub enum FnOperand {
     Move(Place),
 }
 
+/// The signature of the function pointer called by a [FnOperand::Move] call, as given by the type
+/// of the called place.
+#[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
+pub struct FnPtrSig {
+    pub is_unsafe: bool,
+    /// The ABI of the function pointer, as printed by rustc (e.g. `Rust`, `C { unwind: false }`).
+    pub abi: String,
+    /// The regions bound by the function pointer type (e.g. `'a` in `for<'a> fn(&'a u32)`).
+    pub regions: Vector<RegionId, RegionVar>,
+    pub inputs: Vec<Ty>,
+    pub output: Ty,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
 pub struct Call {
     pub func: FnOperand,
     pub args: Vec<Operand>,
     pub dest: Place,
+    /// For calls to function pointers ([FnOperand::Move]), the signature of the called function
+    /// pointer. This is `None` for the other calls.
+    pub fn_ptr_sig: Option<FnPtrSig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
@@ -329,6 +515,20 @@ pub enum AbortKind {
     UndefinedBehavior,
 }
 
+/// The kind of a [crate::llbc_ast::RawStatement::Retag], as in the Stacked Borrows memory model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Drive, DriveMut)]
+#[charon::variants_prefix("Retag")]
+pub enum RetagKind {
+    /// The retag of the arguments of a function at the start of its body.
+    FnEntry,
+    /// The retag of a two-phase borrow when it is created.
+    TwoPhase,
+    /// The retag of a reference which is cast to a raw pointer.
+    Raw,
+    /// Any other retag.
+    Default,
+}
+
 /// Asserts are special constructs introduced by Rust to perform dynamic
 /// checks, to detect out-of-bounds accesses or divisions by zero for
 /// instance. We eliminate the assertions in [crate::remove_dynamic_checks],
@@ -338,4 +538,7 @@ pub enum AbortKind {
 pub struct Assert {
     pub cond: Operand,
     pub expected: bool,
+    /// The constant operands of the panic message computed before failing, if any (e.g. the
+    /// string literal of `assert!(c, "message")`). See [crate::reconstruct_asserts].
+    pub message: Vec<Operand>,
 }
@@ -1,14 +1,15 @@
 use crate::ast::*;
 use crate::formatter::{FmtCtx, Formatter, IntoFormatter};
 use crate::ids::Vector;
-use crate::reorder_decls::DeclarationsGroups;
+use crate::options::MirInlining;
+use crate::reorder_decls::{DeclarationsGroups, Dependency};
 use derive_visitor::{Drive, DriveMut};
 use hashlink::LinkedHashSet;
 use macros::{EnumAsGetters, EnumIsA, VariantIndexArity, VariantName};
 use serde::{Deserialize, Serialize};
-use serde_map_to_array::HashMapToArray;
+use serde_map_to_array::BTreeMapToArray;
 use std::cmp::{Ord, PartialOrd};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::ops::{Index, IndexMut};
 
@@ -78,6 +79,19 @@ pub enum AnyTransItem<'ctx> {
     TraitImpl(&'ctx TraitImpl),
 }
 
+/// The items which implement the handlers used by the runtime. Crates which don't link the
+/// standard library (`no_std`) provide their own; we only record the handlers defined in the
+/// translated crate.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RuntimeHandlers {
+    /// The static annotated with `#[global_allocator]`.
+    pub global_allocator: Option<GlobalDeclId>,
+    /// The function annotated with `#[panic_handler]`.
+    pub panic_handler: Option<FunDeclId>,
+    /// The function annotated with `#[alloc_error_handler]`.
+    pub alloc_error_handler: Option<FunDeclId>,
+}
+
 /// The data of a translated crate.
 #[derive(Default, Clone, Drive, DriveMut, Serialize, Deserialize)]
 pub struct TranslatedCrate {
@@ -97,18 +111,38 @@ pub struct TranslatedCrate {
 
     /// File id to content.
     ///
-    /// Note that some files may be missing, if they are not "real" files.
+    /// Note that some files may be missing, if they are not "real" files. This is a `BTreeMap` so
+    /// that the output doesn't depend on the iteration order of a hash map.
     #[drive(skip)]
-    #[serde(with = "HashMapToArray::<FileId, String>")]
-    pub file_id_to_content: HashMap<FileId, String>,
+    #[serde(with = "BTreeMapToArray::<FileId, String>")]
+    pub file_id_to_content: BTreeMap<FileId, String>,
 
     /// All the ids, in the order in which we encountered them
     #[drive(skip)]
     pub all_ids: LinkedHashSet<AnyTransId>,
     /// The names of all registered items. Available so we can know the names even of items that
     /// failed to translate.
-    #[serde(with = "HashMapToArray::<AnyTransId, Name>")]
-    pub item_names: HashMap<AnyTransId, Name>,
+    #[serde(with = "BTreeMapToArray::<AnyTransId, Name>")]
+    pub item_names: BTreeMap<AnyTransId, Name>,
+    /// The public paths under which items are re-exported (with `pub use`), mapped to the ids of
+    /// the re-exported items. E.g. if the `serde` crate contains `pub use ser::Serialize;`, this
+    /// maps `serde::Serialize` to the id of `serde::ser::Serialize`. This only contains the paths
+    /// which differ from the names of the items.
+    #[drive(skip)]
+    pub reexports: Vec<(Name, AnyTransId)>,
+    /// The functions from which the execution of the crate can start: the `main` or `#[start]`
+    /// function, followed by the functions designated with `--root` or `#[charon::root]`.
+    #[drive(skip)]
+    pub entry_points: Vec<FunDeclId>,
+    /// The functions marked as verification harnesses (see [ItemMeta::is_harness]).
+    #[drive(skip)]
+    pub harnesses: Vec<FunDeclId>,
+    /// The items of this crate which implement the runtime handlers, if any.
+    #[drive(skip)]
+    pub runtime_handlers: RuntimeHandlers,
+    /// The MIR inlining setting the crate was translated with (see `--mir-inlining`).
+    #[drive(skip)]
+    pub mir_inlining: MirInlining,
 
     /// The translated type definitions
     pub type_decls: Vector<TypeDeclId, TypeDecl>,
@@ -125,6 +159,39 @@ pub struct TranslatedCrate {
     /// The re-ordered groups of declarations, initialized as empty.
     #[drive(skip)]
     pub ordered_decls: Option<DeclarationsGroups>,
+    /// The dependency graph of the declarations, from which we computed `ordered_decls`. Like
+    /// `ordered_decls`, this is only computed for LLBC.
+    #[drive(skip)]
+    pub dependency_graph: Option<Vec<Dependency>>,
+    /// The trait impls indexed by trait and by self type, computed at the end of translation.
+    #[drive(skip)]
+    pub trait_impl_index: TraitImplIndex,
+    /// The items we failed to translate, in the order in which they failed. These items are
+    /// missing from the crate.
+    #[drive(skip)]
+    pub failed_items: Vec<ItemFailure>,
+}
+
+/// An item we failed to translate (see [TranslatedCrate::failed_items]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemFailure {
+    pub id: AnyTransId,
+    pub span: Span,
+    /// Whether the translation panicked, instead of reporting an error.
+    pub panicked: bool,
+    /// The error message, or the message of the panic.
+    pub message: String,
+}
+
+/// The trait impls of the crate, indexed by trait and by the head of their self type. This is
+/// meant for consumers which resolve trait methods themselves.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TraitImplIndex {
+    /// The impls of each trait, sorted by trait id.
+    pub impls_of_trait: Vec<(TraitDeclId, Vec<TraitImplId>)>,
+    /// The impls of each trait for each head of the self type, sorted by key. The impls whose self
+    /// type is a type variable or an associated type (e.g. blanket impls) have `None` as head.
+    pub impls_by_self_head: Vec<((TraitDeclId, Option<TypeHead>), Vec<TraitImplId>)>,
 }
 
 impl TranslatedCrate {
@@ -142,6 +209,34 @@ impl TranslatedCrate {
         self.item_names.get(&trans_id.into())
     }
 
+    /// The paths under which an item can be named: its name, followed by the public paths under
+    /// which it is re-exported.
+    pub fn item_paths(&self, trans_id: impl Into<AnyTransId>) -> impl Iterator<Item = &Name> {
+        let trans_id = trans_id.into();
+        self.item_name(trans_id).into_iter().chain(
+            self.reexports
+                .iter()
+                .filter(move |(_, id)| *id == trans_id)
+                .map(|(path, _)| path),
+        )
+    }
+
+    /// The canonical name of a builtin impl of the given trait (see [TraitRefKind::BuiltinOrAuto]),
+    /// e.g. `core::marker::{impl core::marker::Sized for [T]}`. Like the names of the trait impls,
+    /// it is placed in the module of the trait, and only depends on the implemented trait and its
+    /// arguments, so it can be used to identify the builtin impls and to match them with
+    /// patterns.
+    pub fn builtin_impl_name(&self, impl_trait: &TraitDeclRef) -> Option<Name> {
+        let trait_name = self.item_name(impl_trait.trait_id)?;
+        let mut name = trait_name.clone();
+        name.name.pop();
+        name.name.push(PathElem::Impl(
+            ImplElem::Builtin(impl_trait.clone()),
+            Disambiguator::ZERO,
+        ));
+        Some(name)
+    }
+
     pub fn all_items(&self) -> impl Iterator<Item = AnyTransItem<'_>> {
         self.all_items_with_ids().map(|(_, item)| item)
     }
@@ -0,0 +1,1063 @@
+//! Utilities to transform a whole [TranslatedCrate].
+use crate::ast::*;
+use crate::formatter::IntoFormatter;
+use crate::ids::Vector;
+use crate::pretty::FmtWithCtx;
+use crate::reorder_decls::{
+    compute_dependency_graph, compute_reordered_decls, DeclarationGroup, Dependency,
+    GDeclarationGroup, GroupKind,
+};
+use derive_visitor::{visitor_enter_fn, Drive, DriveMut, Visitor, VisitorMut};
+use hashlink::LinkedHashSet;
+use index_vec::Idx;
+use serde::Serialize;
+use siphasher::sip::SipHasher13;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// Maps the old ids of some kind to new, contiguous ones.
+struct IdRemap<I: Idx> {
+    map: HashMap<I, I>,
+    len: usize,
+    /// The new ids below that number are those of the retained items.
+    num_retained: usize,
+}
+
+impl<I: Idx + Hash> IdRemap<I> {
+    fn new() -> Self {
+        IdRemap {
+            map: HashMap::new(),
+            len: 0,
+            num_retained: 0,
+        }
+    }
+
+    /// Number the new ids after the elements of the vector.
+    fn after<T>(vector: &Vector<I, T>) -> Self {
+        IdRemap {
+            map: HashMap::new(),
+            len: vector.next_id().index(),
+            num_retained: 0,
+        }
+    }
+
+    /// Map the id to the next available new id, unless it is already mapped.
+    fn insert(&mut self, old: I) -> I {
+        *self.map.entry(old).or_insert_with(|| {
+            let new = I::from_usize(self.len);
+            self.len += 1;
+            new
+        })
+    }
+
+    /// Map the id to an existing new id.
+    fn insert_existing(&mut self, old: I, new: I) {
+        self.map.insert(old, new);
+    }
+
+    fn get(&self, old: I) -> Option<I> {
+        self.map.get(&old).copied()
+    }
+
+    /// Whether the id is mapped to the id of a retained item.
+    fn is_retained(&self, old: I) -> bool {
+        self.get(old)
+            .is_some_and(|new| new.index() < self.num_retained)
+    }
+
+    /// Record that the ids mapped so far are those of the retained items.
+    fn freeze_retained(&mut self) {
+        self.num_retained = self.len;
+    }
+
+    /// Move the retained elements to their new ids. The other new ids are empty slots.
+    fn compact<T>(&self, old: Vector<I, T>, mut set_id: impl FnMut(&mut T, I)) -> Vector<I, T> {
+        let mut slots: Vec<Option<T>> = (0..self.len).map(|_| None).collect();
+        for (old_id, mut x) in old.into_iter_indexed() {
+            if self.is_retained(old_id) {
+                let new_id = self.get(old_id).unwrap();
+                set_id(&mut x, new_id);
+                slots[new_id.index()] = Some(x);
+            }
+        }
+        let mut vector = Vector::new();
+        for slot in slots {
+            match slot {
+                Some(x) => {
+                    vector.push(x);
+                }
+                None => {
+                    vector.reserve_slot();
+                }
+            }
+        }
+        vector
+    }
+}
+
+struct Remaps {
+    types: IdRemap<TypeDeclId>,
+    funs: IdRemap<FunDeclId>,
+    globals: IdRemap<GlobalDeclId>,
+    trait_decls: IdRemap<TraitDeclId>,
+    trait_impls: IdRemap<TraitImplId>,
+    bodies: IdRemap<BodyId>,
+}
+
+impl Remaps {
+    fn new() -> Self {
+        Remaps {
+            types: IdRemap::new(),
+            funs: IdRemap::new(),
+            globals: IdRemap::new(),
+            trait_decls: IdRemap::new(),
+            trait_impls: IdRemap::new(),
+            bodies: IdRemap::new(),
+        }
+    }
+
+    /// Number the new ids after the items of the crate.
+    fn after(krate: &TranslatedCrate) -> Self {
+        Remaps {
+            types: IdRemap::after(&krate.type_decls),
+            funs: IdRemap::after(&krate.fun_decls),
+            globals: IdRemap::after(&krate.global_decls),
+            trait_decls: IdRemap::after(&krate.trait_decls),
+            trait_impls: IdRemap::after(&krate.trait_impls),
+            bodies: IdRemap::after(&krate.bodies),
+        }
+    }
+
+    fn insert(&mut self, id: AnyTransId) -> AnyTransId {
+        match id {
+            AnyTransId::Type(id) => self.types.insert(id).into(),
+            AnyTransId::Fun(id) => self.funs.insert(id).into(),
+            AnyTransId::Global(id) => self.globals.insert(id).into(),
+            AnyTransId::TraitDecl(id) => self.trait_decls.insert(id).into(),
+            AnyTransId::TraitImpl(id) => self.trait_impls.insert(id).into(),
+        }
+    }
+
+    fn insert_existing(&mut self, old: AnyTransId, new: AnyTransId) {
+        use AnyTransId::*;
+        match (old, new) {
+            (Type(old), Type(new)) => self.types.insert_existing(old, new),
+            (Fun(old), Fun(new)) => self.funs.insert_existing(old, new),
+            (Global(old), Global(new)) => self.globals.insert_existing(old, new),
+            (TraitDecl(old), TraitDecl(new)) => self.trait_decls.insert_existing(old, new),
+            (TraitImpl(old), TraitImpl(new)) => self.trait_impls.insert_existing(old, new),
+            _ => panic!("mapping ids of different kinds: {old:?} to {new:?}"),
+        }
+    }
+
+    fn get(&self, id: AnyTransId) -> Option<AnyTransId> {
+        match id {
+            AnyTransId::Type(id) => self.types.get(id).map(Into::into),
+            AnyTransId::Fun(id) => self.funs.get(id).map(Into::into),
+            AnyTransId::Global(id) => self.globals.get(id).map(Into::into),
+            AnyTransId::TraitDecl(id) => self.trait_decls.get(id).map(Into::into),
+            AnyTransId::TraitImpl(id) => self.trait_impls.get(id).map(Into::into),
+        }
+    }
+
+    fn is_retained(&self, id: AnyTransId) -> bool {
+        match id {
+            AnyTransId::Type(id) => self.types.is_retained(id),
+            AnyTransId::Fun(id) => self.funs.is_retained(id),
+            AnyTransId::Global(id) => self.globals.is_retained(id),
+            AnyTransId::TraitDecl(id) => self.trait_decls.is_retained(id),
+            AnyTransId::TraitImpl(id) => self.trait_impls.is_retained(id),
+        }
+    }
+
+    fn freeze_retained(&mut self) {
+        self.types.freeze_retained();
+        self.funs.freeze_retained();
+        self.globals.freeze_retained();
+        self.trait_decls.freeze_retained();
+        self.trait_impls.freeze_retained();
+        self.bodies.freeze_retained();
+    }
+
+    /// Remap the ids of a declaration group, dropping the ids of the removed items.
+    fn remap_group<Id: Copy + Into<AnyTransId> + TryFrom<AnyTransId>>(
+        &self,
+        group: GDeclarationGroup<Id>,
+    ) -> Option<GDeclarationGroup<Id>> {
+        let remap = |id: Id| -> Option<Id> {
+            let id = id.into();
+            if self.is_retained(id) {
+                self.get(id)?.try_into().ok()
+            } else {
+                None
+            }
+        };
+        match group {
+            GDeclarationGroup::NonRec(id) => Some(GDeclarationGroup::NonRec(remap(id)?)),
+            GDeclarationGroup::Rec(ids) => {
+                let ids: Vec<_> = ids.into_iter().filter_map(remap).collect();
+                (!ids.is_empty()).then_some(GDeclarationGroup::Rec(ids))
+            }
+        }
+    }
+
+    fn remap_decl_group(&self, group: DeclarationGroup) -> Option<DeclarationGroup> {
+        use DeclarationGroup::*;
+        Some(match group {
+            Type(gr) => Type(self.remap_group(gr)?),
+            Fun(gr) => Fun(self.remap_group(gr)?),
+            Global(gr) => Global(self.remap_group(gr)?),
+            TraitDecl(gr) => TraitDecl(self.remap_group(gr)?),
+            TraitImpl(gr) => TraitImpl(self.remap_group(gr)?),
+            Mixed(gr) => Mixed(self.remap_group(gr)?),
+        })
+    }
+}
+
+/// Collect the ids of the items referred to.
+#[derive(Default, Visitor)]
+#[visitor(
+    TypeDeclId(enter),
+    FunDeclId(enter),
+    GlobalDeclId(enter),
+    TraitDeclId(enter),
+    TraitImplId(enter),
+    Ty(enter)
+)]
+struct CollectIds {
+    ids: BTreeSet<AnyTransId>,
+}
+
+impl CollectIds {
+    fn enter_type_decl_id(&mut self, id: &TypeDeclId) {
+        self.ids.insert((*id).into());
+    }
+    fn enter_fun_decl_id(&mut self, id: &FunDeclId) {
+        self.ids.insert((*id).into());
+    }
+    fn enter_global_decl_id(&mut self, id: &GlobalDeclId) {
+        self.ids.insert((*id).into());
+    }
+    fn enter_trait_decl_id(&mut self, id: &TraitDeclId) {
+        self.ids.insert((*id).into());
+    }
+    fn enter_trait_impl_id(&mut self, id: &TraitImplId) {
+        self.ids.insert((*id).into());
+    }
+    fn enter_ty(&mut self, ty: &Ty) {
+        ty.drive_inner(self);
+    }
+}
+
+/// Replace the ids of the items with the new ones.
+#[derive(VisitorMut)]
+#[visitor(
+    TypeDeclId(enter),
+    FunDeclId(enter),
+    GlobalDeclId(enter),
+    TraitDeclId(enter),
+    TraitImplId(enter),
+    Ty(enter)
+)]
+struct RemapIds {
+    remaps: Remaps,
+}
+
+impl RemapIds {
+    fn enter_type_decl_id(&mut self, id: &mut TypeDeclId) {
+        *id = self.remaps.types.get(*id).unwrap();
+    }
+    fn enter_fun_decl_id(&mut self, id: &mut FunDeclId) {
+        *id = self.remaps.funs.get(*id).unwrap();
+    }
+    fn enter_global_decl_id(&mut self, id: &mut GlobalDeclId) {
+        *id = self.remaps.globals.get(*id).unwrap();
+    }
+    fn enter_trait_decl_id(&mut self, id: &mut TraitDeclId) {
+        *id = self.remaps.trait_decls.get(*id).unwrap();
+    }
+    fn enter_trait_impl_id(&mut self, id: &mut TraitImplId) {
+        *id = self.remaps.trait_impls.get(*id).unwrap();
+    }
+    fn enter_ty(&mut self, ty: &mut Ty) {
+        ty.drive_inner_mut(self);
+    }
+}
+
+/// Replace the file ids with the new ones.
+#[derive(VisitorMut)]
+#[visitor(FileId(enter))]
+struct RemapFileIds {
+    map: HashMap<FileId, FileId>,
+}
+
+impl RemapFileIds {
+    fn enter_file_id(&mut self, id: &mut FileId) {
+        *id = self.map[&*id];
+    }
+}
+
+/// Whether we have the contents of the item: the body of a function or global, the fields or
+/// variants of a type.
+fn has_contents(krate: &TranslatedCrate, item: AnyTransItem<'_>) -> bool {
+    match item {
+        AnyTransItem::Type(decl) => !matches!(decl.kind, TypeDeclKind::Opaque),
+        AnyTransItem::Fun(FunDecl { body, .. }) | AnyTransItem::Global(GlobalDecl { body, .. }) => {
+            body.as_ref()
+                .is_ok_and(|body| krate.bodies.get(*body).is_some())
+        }
+        AnyTransItem::TraitDecl(_) | AnyTransItem::TraitImpl(_) => true,
+    }
+}
+
+/// Erase the data which doesn't describe the contents of an item: the locations, which change
+/// when unrelated code moves around, the source text, and the hash of [ItemMeta::dedup_hash].
+#[derive(VisitorMut)]
+#[visitor(Span(enter), FileId(enter), ItemMeta(enter))]
+struct EraseUnstableData;
+
+impl EraseUnstableData {
+    fn enter_span(&mut self, span: &mut Span) {
+        *span = Span::dummy();
+    }
+    fn enter_file_id(&mut self, id: &mut FileId) {
+        *id = FileId::ZERO;
+    }
+    fn enter_item_meta(&mut self, item_meta: &mut ItemMeta) {
+        item_meta.source_text = None;
+        item_meta.dedup_hash = None;
+    }
+}
+
+/// Serialize the value after replacing its ids and erasing its unstable data.
+fn stable_repr<T: DriveMut + Serialize>(mut x: T, remap: &mut RemapIds) -> String {
+    x.drive_mut(remap);
+    x.drive_mut(&mut EraseUnstableData);
+    serde_json::to_string(&x).unwrap()
+}
+
+impl AnyTransItem<'_> {
+    /// A hash of the contents of the item (including its body) which doesn't depend on the ids of
+    /// the crate: the items it refers to are identified by their names, and the locations are
+    /// ignored. An item therefore keeps its hash across extractions as long as neither it nor the
+    /// names of the items it refers to change. This is meant for caching, deduplication and change
+    /// detection. `krate` must be the crate which contains the item.
+    ///
+    /// The hashing algorithm is fixed (SipHash-1-3 with zero keys), hence the hash doesn't depend on
+    /// the Rust toolchain or the platform. It may however change across Charon versions, when the
+    /// representation of the items changes.
+    pub fn stable_hash(&self, krate: &TranslatedCrate) -> u64 {
+        let fmt_ctx = krate.into_fmt();
+        // Number the items referred to (including this one) in the order of their names.
+        let mut ids = krate.item_references(self.id());
+        ids.insert(self.id());
+        let mut referred: Vec<(u32, String, AnyTransId)> = ids
+            .into_iter()
+            .map(|id| {
+                let name = krate
+                    .item_name(id)
+                    .map(|name| name.fmt_with_ctx(&fmt_ctx))
+                    .unwrap_or_default();
+                (id.variant_index_arity().0, name, id)
+            })
+            .collect();
+        referred.sort();
+        let mut remap = RemapIds {
+            remaps: Remaps::new(),
+        };
+        let mut hasher = SipHasher13::new();
+        for (_, name, id) in &referred {
+            remap.remaps.insert(*id);
+            name.hash(&mut hasher);
+        }
+
+        let repr = match *self {
+            AnyTransItem::Type(d) => stable_repr(
+                TypeDecl {
+                    def_id: TypeDeclId::ZERO,
+                    ..d.clone()
+                },
+                &mut remap,
+            ),
+            AnyTransItem::Fun(d) => stable_repr(
+                FunDecl {
+                    def_id: FunDeclId::ZERO,
+                    body: d.body.map(|_| BodyId::ZERO),
+                    ..d.clone()
+                },
+                &mut remap,
+            ),
+            AnyTransItem::Global(d) => stable_repr(
+                GlobalDecl {
+                    def_id: GlobalDeclId::ZERO,
+                    body: d.body.map(|_| BodyId::ZERO),
+                    ..d.clone()
+                },
+                &mut remap,
+            ),
+            AnyTransItem::TraitDecl(d) => stable_repr(
+                TraitDecl {
+                    def_id: TraitDeclId::ZERO,
+                    ..d.clone()
+                },
+                &mut remap,
+            ),
+            AnyTransItem::TraitImpl(d) => stable_repr(
+                TraitImpl {
+                    def_id: TraitImplId::ZERO,
+                    ..d.clone()
+                },
+                &mut remap,
+            ),
+        };
+        repr.hash(&mut hasher);
+
+        let body = match *self {
+            AnyTransItem::Fun(d) => d.body.ok(),
+            AnyTransItem::Global(d) => d.body.ok(),
+            _ => None,
+        };
+        if let Some(body) = body.and_then(|body| krate.bodies.get(body)) {
+            stable_repr(body.clone(), &mut remap).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+impl TranslatedCrate {
+    /// The items referred to by the given item, including from its body. This may contain the
+    /// item itself.
+    pub fn item_references(&self, id: AnyTransId) -> BTreeSet<AnyTransId> {
+        let mut collector = CollectIds::default();
+        if let Some(item) = self.get_item(id) {
+            item.drive(&mut collector);
+            let body = match item {
+                AnyTransItem::Fun(decl) => decl.body.ok(),
+                AnyTransItem::Global(decl) => decl.body.ok(),
+                _ => None,
+            };
+            if let Some(body) = body.and_then(|body| self.bodies.get(body)) {
+                body.drive(&mut collector);
+            }
+        }
+        collector.ids
+    }
+
+    /// Remove the items for which `keep` returns `false`, then compact the item vectors and
+    /// update all the ids in the crate accordingly (including in the bodies, the names and
+    /// `ordered_decls`). The bodies which are not used by the remaining items are removed too.
+    ///
+    /// The retained items keep their relative order. The references to removed items (and to
+    /// items which failed to translate) become references to empty slots, placed after the
+    /// retained items, like for the items which failed to translate; their names are kept. The
+    /// entries of `failed_items` are kept if their item is still referred to or if `keep` returns
+    /// `true` for it.
+    pub fn retain(&mut self, mut keep: impl FnMut(AnyTransId) -> bool) {
+        let mut remaps = Remaps::new();
+        let retained: Vec<AnyTransId> = (self.type_decls.iter_indices().map(AnyTransId::from))
+            .chain(self.fun_decls.iter_indices().map(AnyTransId::from))
+            .chain(self.global_decls.iter_indices().map(AnyTransId::from))
+            .chain(self.trait_decls.iter_indices().map(AnyTransId::from))
+            .chain(self.trait_impls.iter_indices().map(AnyTransId::from))
+            .filter(|id| keep(*id))
+            .collect();
+        for id in &retained {
+            remaps.insert(*id);
+            let body = match self.get_item(*id).unwrap() {
+                AnyTransItem::Fun(decl) => decl.body.ok(),
+                AnyTransItem::Global(decl) => decl.body.ok(),
+                _ => None,
+            };
+            if let Some(body) = body {
+                remaps.bodies.insert(body);
+            }
+        }
+        remaps.freeze_retained();
+
+        // Map the ids of the missing items which are still referred to.
+        let mut worklist = retained.clone();
+        for failure in &self.failed_items {
+            if remaps.get(failure.id).is_none() && keep(failure.id) {
+                remaps.insert(failure.id);
+                worklist.push(failure.id);
+            }
+        }
+        while let Some(id) = worklist.pop() {
+            let mut collector = CollectIds::default();
+            if remaps.is_retained(id) {
+                collector.ids = self.item_references(id);
+            }
+            if let Some(name) = self.item_names.get(&id) {
+                name.drive(&mut collector);
+            }
+            for referred in collector.ids {
+                if remaps.get(referred).is_none() {
+                    remaps.insert(referred);
+                    worklist.push(referred);
+                }
+            }
+        }
+
+        // Move the items and bodies to their new ids.
+        self.type_decls = remaps
+            .types
+            .compact(std::mem::take(&mut self.type_decls), |x, id| x.def_id = id);
+        self.fun_decls = remaps
+            .funs
+            .compact(std::mem::take(&mut self.fun_decls), |x, id| x.def_id = id);
+        self.global_decls = remaps
+            .globals
+            .compact(std::mem::take(&mut self.global_decls), |x, id| {
+                x.def_id = id
+            });
+        self.trait_decls = remaps
+            .trait_decls
+            .compact(std::mem::take(&mut self.trait_decls), |x, id| x.def_id = id);
+        self.trait_impls = remaps
+            .trait_impls
+            .compact(std::mem::take(&mut self.trait_impls), |x, id| x.def_id = id);
+        self.bodies = remaps
+            .bodies
+            .compact(std::mem::take(&mut self.bodies), |_, _| ());
+        for decl in self.fun_decls.iter_mut() {
+            if let Ok(body) = &mut decl.body {
+                *body = remaps.bodies.get(*body).unwrap();
+            }
+        }
+        for decl in self.global_decls.iter_mut() {
+            if let Ok(body) = &mut decl.body {
+                *body = remaps.bodies.get(*body).unwrap();
+            }
+        }
+
+        // Update the ids everywhere else.
+        let mut visitor = RemapIds { remaps };
+        self.type_decls.drive_mut(&mut visitor);
+        self.fun_decls.drive_mut(&mut visitor);
+        self.global_decls.drive_mut(&mut visitor);
+        self.trait_decls.drive_mut(&mut visitor);
+        self.trait_impls.drive_mut(&mut visitor);
+        self.bodies.drive_mut(&mut visitor);
+
+        self.item_names = std::mem::take(&mut self.item_names)
+            .into_iter()
+            .filter_map(|(id, mut name)| {
+                let id = visitor.remaps.get(id)?;
+                name.drive_mut(&mut visitor);
+                Some((id, name))
+            })
+            .collect();
+        let remaps = visitor.remaps;
+        self.all_ids = std::mem::take(&mut self.all_ids)
+            .into_iter()
+            .filter_map(|id| remaps.get(id))
+            .collect::<LinkedHashSet<_>>();
+        self.reexports = std::mem::take(&mut self.reexports)
+            .into_iter()
+            .filter(|(_, id)| remaps.is_retained(*id))
+            .map(|(path, id)| (path, remaps.get(id).unwrap()))
+            .collect();
+        self.entry_points = std::mem::take(&mut self.entry_points)
+            .into_iter()
+            .filter(|id| remaps.funs.is_retained(*id))
+            .map(|id| remaps.funs.get(id).unwrap())
+            .collect();
+        self.failed_items = std::mem::take(&mut self.failed_items)
+            .into_iter()
+            .filter_map(|failure| {
+                Some(ItemFailure {
+                    id: remaps.get(failure.id)?,
+                    ..failure
+                })
+            })
+            .collect();
+        self.harnesses = std::mem::take(&mut self.harnesses)
+            .into_iter()
+            .filter(|id| remaps.funs.is_retained(*id))
+            .map(|id| remaps.funs.get(id).unwrap())
+            .collect();
+        let handlers = &mut self.runtime_handlers;
+        handlers.global_allocator = handlers
+            .global_allocator
+            .filter(|id| remaps.globals.is_retained(*id))
+            .and_then(|id| remaps.globals.get(id));
+        handlers.panic_handler = handlers
+            .panic_handler
+            .filter(|id| remaps.funs.is_retained(*id))
+            .and_then(|id| remaps.funs.get(id));
+        handlers.alloc_error_handler = handlers
+            .alloc_error_handler
+            .filter(|id| remaps.funs.is_retained(*id))
+            .and_then(|id| remaps.funs.get(id));
+        if let Some(ordered_decls) = self.ordered_decls.take() {
+            self.ordered_decls = Some(
+                ordered_decls
+                    .into_iter()
+                    .filter_map(|group| remaps.remap_decl_group(group))
+                    .collect(),
+            );
+        }
+        if let Some(dependency_graph) = self.dependency_graph.take() {
+            self.dependency_graph = Some(
+                dependency_graph
+                    .into_iter()
+                    .filter(|dep| remaps.is_retained(dep.src))
+                    .filter_map(|dep| {
+                        Some(Dependency {
+                            src: remaps.get(dep.src)?,
+                            dst: remaps.get(dep.dst)?,
+                            kind: dep.kind,
+                        })
+                    })
+                    .collect(),
+            );
+        }
+        self.trait_impl_index = TraitImplIndex::new(self);
+    }
+
+    /// A copy of the crate as [TranslatedCrate::retain] would leave it. Unlike cloning the crate
+    /// then calling `retain`, this only clones the kept items, their bodies and the sources of the
+    /// files they refer to.
+    pub fn filtered(&self, mut keep: impl FnMut(AnyTransId) -> bool) -> TranslatedCrate {
+        fn copy<I: Idx, T: Clone>(
+            vector: &Vector<I, T>,
+            mut keep: impl FnMut(I) -> bool,
+        ) -> Vector<I, T> {
+            let mut copy = Vector::new();
+            for (id, x) in vector.iter_indexed_all_slots() {
+                copy.reserve_slot();
+                if let Some(x) = x
+                    && keep(id)
+                {
+                    copy.set_slot(id, x.clone());
+                }
+            }
+            copy
+        }
+        let TranslatedCrate {
+            crate_name,
+            real_crate_name,
+            id_to_file,
+            file_to_id,
+            file_id_to_content,
+            all_ids,
+            item_names,
+            reexports,
+            entry_points,
+            harnesses,
+            runtime_handlers,
+            mir_inlining,
+            type_decls,
+            fun_decls,
+            global_decls,
+            bodies,
+            trait_decls,
+            trait_impls,
+            ordered_decls,
+            dependency_graph,
+            trait_impl_index: _,
+            failed_items,
+        } = self;
+        let type_decls = copy(type_decls, |id| keep(id.into()));
+        let fun_decls = copy(fun_decls, |id| keep(id.into()));
+        let global_decls = copy(global_decls, |id| keep(id.into()));
+        let trait_decls = copy(trait_decls, |id| keep(id.into()));
+        let trait_impls = copy(trait_impls, |id| keep(id.into()));
+        let kept_bodies: HashSet<BodyId> = (fun_decls.iter().map(|decl| decl.body.ok()))
+            .chain(global_decls.iter().map(|decl| decl.body.ok()))
+            .flatten()
+            .collect();
+        let bodies = copy(bodies, |id| kept_bodies.contains(&id));
+
+        let mut files = HashSet::new();
+        let mut collect_files = visitor_enter_fn(|id: &FileId| {
+            files.insert(*id);
+        });
+        type_decls.drive(&mut collect_files);
+        fun_decls.drive(&mut collect_files);
+        global_decls.drive(&mut collect_files);
+        trait_decls.drive(&mut collect_files);
+        trait_impls.drive(&mut collect_files);
+        bodies.drive(&mut collect_files);
+        let file_id_to_content = file_id_to_content
+            .iter()
+            .filter(|(id, _)| files.contains(*id))
+            .map(|(id, content)| (*id, content.clone()))
+            .collect();
+
+        let mut krate = TranslatedCrate {
+            crate_name: crate_name.clone(),
+            real_crate_name: real_crate_name.clone(),
+            id_to_file: id_to_file.clone(),
+            file_to_id: file_to_id.clone(),
+            file_id_to_content,
+            all_ids: all_ids.clone(),
+            item_names: item_names.clone(),
+            reexports: reexports.clone(),
+            entry_points: entry_points.clone(),
+            harnesses: harnesses.clone(),
+            runtime_handlers: runtime_handlers.clone(),
+            mir_inlining: *mir_inlining,
+            type_decls,
+            fun_decls,
+            global_decls,
+            bodies,
+            trait_decls,
+            trait_impls,
+            ordered_decls: ordered_decls.clone(),
+            dependency_graph: dependency_graph.clone(),
+            trait_impl_index: TraitImplIndex::default(),
+            failed_items: failed_items.clone(),
+        };
+        krate.retain(keep);
+        krate
+    }
+
+    /// Merge the items of `other` into this crate, like a linker does with object files. The items
+    /// of `other` which this crate already contains are not copied again: we recognize them by
+    /// their [ItemMeta::dedup_hash] when they have one, and by their names otherwise. If the copy
+    /// of this crate lacks the contents of the item (e.g. the body of a function which is opaque
+    /// here) but the copy of `other` has them, the latter replaces the former. The other items of
+    /// `other` get fresh ids after those of this crate, and the references to the items of `other`
+    /// are updated accordingly.
+    ///
+    /// We keep the name and the options (e.g. [TranslatedCrate::mir_inlining]) of this crate. The
+    /// declaration groups and the dependency graph are recomputed if either crate had them.
+    pub fn link(&mut self, mut other: TranslatedCrate) {
+        let kind = |id: AnyTransId| id.variant_index_arity().0;
+        // Index the items of this crate.
+        let mut by_hash: HashMap<(u32, String), AnyTransId> = HashMap::new();
+        for item in self.all_items() {
+            if let Some(hash) = &item.item_meta().dedup_hash {
+                by_hash
+                    .entry((kind(item.id()), hash.clone()))
+                    .or_insert(item.id());
+            }
+        }
+        let fmt_ctx = self.into_fmt();
+        let mut by_name: HashMap<(u32, String), AnyTransId> = HashMap::new();
+        for (id, name) in &self.item_names {
+            by_name
+                .entry((kind(*id), name.fmt_with_ctx(&fmt_ctx)))
+                .or_insert(*id);
+        }
+
+        // Map the ids of `other`, including those of the items which are only referred to.
+        let mut collector = CollectIds::default();
+        collector
+            .ids
+            .extend(other.all_items().map(|item| item.id()));
+        collector.ids.extend(other.item_names.keys().copied());
+        collector.ids.extend(other.all_ids.iter().copied());
+        collector
+            .ids
+            .extend(other.failed_items.iter().map(|failure| failure.id));
+        other.type_decls.drive(&mut collector);
+        other.fun_decls.drive(&mut collector);
+        other.global_decls.drive(&mut collector);
+        other.trait_decls.drive(&mut collector);
+        other.trait_impls.drive(&mut collector);
+        other.bodies.drive(&mut collector);
+        for name in other.item_names.values() {
+            name.drive(&mut collector);
+        }
+        let fmt_ctx = other.into_fmt();
+        let mut remaps = Remaps::after(self);
+        // The items of `other` to move to this crate.
+        let mut moved = Vec::new();
+        for id in collector.ids {
+            let item = other.get_item(id);
+            let existing = match item.and_then(|item| item.item_meta().dedup_hash.clone()) {
+                Some(hash) => by_hash.get(&(kind(id), hash)).copied(),
+                None => other
+                    .item_name(id)
+                    .and_then(|name| by_name.get(&(kind(id), name.fmt_with_ctx(&fmt_ctx))))
+                    .copied(),
+            };
+            match existing {
+                Some(existing) => {
+                    remaps.insert_existing(id, existing);
+                    let replace = item.is_some_and(|item| has_contents(&other, item))
+                        && !self
+                            .get_item(existing)
+                            .is_some_and(|item| has_contents(self, item));
+                    if replace {
+                        moved.push(id);
+                    }
+                }
+                None => {
+                    remaps.insert(id);
+                    if item.is_some() {
+                        moved.push(id);
+                    }
+                }
+            }
+        }
+
+        // Merge the files.
+        let mut file_ids: HashMap<FileName, FileId> = self
+            .id_to_file
+            .iter_indexed()
+            .map(|(id, file)| (file.clone(), id))
+            .collect();
+        let mut files = RemapFileIds {
+            map: HashMap::new(),
+        };
+        for (old_id, file) in other.id_to_file.iter_indexed() {
+            let new_id = *file_ids.entry(file.clone()).or_insert_with(|| {
+                let new_id = self.id_to_file.push(file.clone());
+                self.file_to_id.insert(file.clone(), new_id);
+                if let Some(content) = other.file_id_to_content.remove(&old_id) {
+                    self.file_id_to_content.insert(new_id, content);
+                }
+                new_id
+            });
+            files.map.insert(old_id, new_id);
+        }
+
+        // Update the ids in `other`.
+        let mut visitor = RemapIds { remaps };
+        fn remap<T: DriveMut>(x: &mut T, ids: &mut RemapIds, files: &mut RemapFileIds) {
+            x.drive_mut(ids);
+            x.drive_mut(files);
+        }
+        remap(&mut other.type_decls, &mut visitor, &mut files);
+        remap(&mut other.fun_decls, &mut visitor, &mut files);
+        remap(&mut other.global_decls, &mut visitor, &mut files);
+        remap(&mut other.trait_decls, &mut visitor, &mut files);
+        remap(&mut other.trait_impls, &mut visitor, &mut files);
+        remap(&mut other.bodies, &mut visitor, &mut files);
+        for name in other.item_names.values_mut() {
+            remap(name, &mut visitor, &mut files);
+        }
+        for failure in &mut other.failed_items {
+            failure.span.drive_mut(&mut files);
+        }
+        let remaps = visitor.remaps;
+
+        // Move the items, with their bodies.
+        fn reserve<I: Idx, T>(vector: &mut Vector<I, T>, remap: &IdRemap<I>) {
+            while vector.next_id().index() < remap.len {
+                vector.reserve_slot();
+            }
+        }
+        reserve(&mut self.type_decls, &remaps.types);
+        reserve(&mut self.fun_decls, &remaps.funs);
+        reserve(&mut self.global_decls, &remaps.globals);
+        reserve(&mut self.trait_decls, &remaps.trait_decls);
+        reserve(&mut self.trait_impls, &remaps.trait_impls);
+        for id in moved {
+            match (id, remaps.get(id).unwrap()) {
+                (AnyTransId::Type(id), AnyTransId::Type(new_id)) => {
+                    let mut decl = other.type_decls.remove(id).unwrap();
+                    decl.def_id = new_id;
+                    self.type_decls.remove(new_id);
+                    self.type_decls.set_slot(new_id, decl);
+                }
+                (AnyTransId::Fun(id), AnyTransId::Fun(new_id)) => {
+                    let mut decl = other.fun_decls.remove(id).unwrap();
+                    decl.def_id = new_id;
+                    decl.body = decl.body.and_then(|body| {
+                        let body = other.bodies.remove(body).ok_or(Opaque)?;
+                        Ok(self.bodies.push(body))
+                    });
+                    self.fun_decls.remove(new_id);
+                    self.fun_decls.set_slot(new_id, decl);
+                }
+                (AnyTransId::Global(id), AnyTransId::Global(new_id)) => {
+                    let mut decl = other.global_decls.remove(id).unwrap();
+                    decl.def_id = new_id;
+                    decl.body = decl.body.and_then(|body| {
+                        let body = other.bodies.remove(body).ok_or(Opaque)?;
+                        Ok(self.bodies.push(body))
+                    });
+                    self.global_decls.remove(new_id);
+                    self.global_decls.set_slot(new_id, decl);
+                }
+                (AnyTransId::TraitDecl(id), AnyTransId::TraitDecl(new_id)) => {
+                    let mut decl = other.trait_decls.remove(id).unwrap();
+                    decl.def_id = new_id;
+                    self.trait_decls.remove(new_id);
+                    self.trait_decls.set_slot(new_id, decl);
+                }
+                (AnyTransId::TraitImpl(id), AnyTransId::TraitImpl(new_id)) => {
+                    let mut decl = other.trait_impls.remove(id).unwrap();
+                    decl.def_id = new_id;
+                    self.trait_impls.remove(new_id);
+                    self.trait_impls.set_slot(new_id, decl);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        // Merge the rest of the data.
+        for (id, name) in other.item_names {
+            self.item_names
+                .entry(remaps.get(id).unwrap())
+                .or_insert(name);
+        }
+        for id in other.all_ids {
+            self.all_ids.insert(remaps.get(id).unwrap());
+        }
+        for (path, id) in other.reexports {
+            let reexport = (path, remaps.get(id).unwrap());
+            if !self.reexports.contains(&reexport) {
+                self.reexports.push(reexport);
+            }
+        }
+        for id in other.entry_points {
+            let id = remaps.funs.get(id).unwrap();
+            if !self.entry_points.contains(&id) {
+                self.entry_points.push(id);
+            }
+        }
+        for id in other.harnesses {
+            let id = remaps.funs.get(id).unwrap();
+            if !self.harnesses.contains(&id) {
+                self.harnesses.push(id);
+            }
+        }
+        let handlers = &mut self.runtime_handlers;
+        let other_handlers = other.runtime_handlers;
+        handlers.global_allocator = handlers.global_allocator.or_else(|| {
+            let id = other_handlers.global_allocator?;
+            remaps.globals.get(id)
+        });
+        handlers.panic_handler = handlers.panic_handler.or_else(|| {
+            let id = other_handlers.panic_handler?;
+            remaps.funs.get(id)
+        });
+        handlers.alloc_error_handler = handlers.alloc_error_handler.or_else(|| {
+            let id = other_handlers.alloc_error_handler?;
+            remaps.funs.get(id)
+        });
+        // Only keep the failures of the items which are still missing.
+        let mut failed = HashSet::new();
+        self.failed_items = std::mem::take(&mut self.failed_items)
+            .into_iter()
+            .chain(other.failed_items.into_iter().map(|failure| ItemFailure {
+                id: remaps.get(failure.id).unwrap(),
+                ..failure
+            }))
+            .filter(|failure| self.get_item(failure.id).is_none() && failed.insert(failure.id))
+            .collect();
+
+        if self.ordered_decls.is_some() || other.ordered_decls.is_some() {
+            self.ordered_decls = Some(compute_reordered_decls(self));
+            self.reorder_bodies();
+        }
+        if self.dependency_graph.is_some() || other.dependency_graph.is_some() {
+            self.dependency_graph = Some(compute_dependency_graph(self));
+        }
+        self.trait_impl_index = TraitImplIndex::new(self);
+    }
+}
+
+impl TraitImplIndex {
+    /// Index the trait impls of the crate.
+    pub fn new(krate: &TranslatedCrate) -> Self {
+        let mut impls_of_trait: BTreeMap<TraitDeclId, Vec<TraitImplId>> = BTreeMap::new();
+        let mut impls_by_self_head: BTreeMap<_, Vec<TraitImplId>> = BTreeMap::new();
+        for timpl in krate.trait_impls.iter() {
+            let trait_id = timpl.impl_trait.trait_id;
+            impls_of_trait
+                .entry(trait_id)
+                .or_default()
+                .push(timpl.def_id);
+            // The self type is the first type argument of the trait.
+            if let Some(self_ty) = timpl.impl_trait.generics.types.get(TypeVarId::ZERO) {
+                impls_by_self_head
+                    .entry((trait_id, self_ty.head()))
+                    .or_default()
+                    .push(timpl.def_id);
+            }
+        }
+        TraitImplIndex {
+            impls_of_trait: impls_of_trait.into_iter().collect(),
+            impls_by_self_head: impls_by_self_head.into_iter().collect(),
+        }
+    }
+}
+
+impl TranslatedCrate {
+    /// The impls of the given trait, according to [TranslatedCrate::trait_impl_index].
+    pub fn impls_of_trait(&self, trait_id: TraitDeclId) -> &[TraitImplId] {
+        let impls = &self.trait_impl_index.impls_of_trait;
+        match impls.binary_search_by_key(&trait_id, |(id, _)| *id) {
+            Ok(i) => &impls[i].1,
+            Err(_) => &[],
+        }
+    }
+
+    /// The impls of the given trait whose self type has the given head, according to
+    /// [TranslatedCrate::trait_impl_index]. Use `None` to get the impls whose self type is a type
+    /// variable or an associated type; these may apply to any self type.
+    pub fn impls_for_self_head(
+        &self,
+        trait_id: TraitDeclId,
+        head: Option<TypeHead>,
+    ) -> &[TraitImplId] {
+        let impls = &self.trait_impl_index.impls_by_self_head;
+        match impls.binary_search_by_key(&(trait_id, head), |(key, _)| *key) {
+            Ok(i) => &impls[i].1,
+            Err(_) => &[],
+        }
+    }
+
+    /// The groups of [TranslatedCrate::ordered_decls] in dependency order, with their kind. This
+    /// is empty if the declarations haven't been reordered yet.
+    pub fn decl_groups(&self) -> impl Iterator<Item = (GroupKind, &DeclarationGroup)> {
+        self.ordered_decls
+            .iter()
+            .flatten()
+            .map(|group| (group.kind(), group))
+    }
+
+    /// The group of [TranslatedCrate::ordered_decls] which contains the given item.
+    pub fn group_of(&self, id: AnyTransId) -> Option<&DeclarationGroup> {
+        self.ordered_decls
+            .as_ref()?
+            .iter()
+            .find(|group| group.get_ids().contains(&id))
+    }
+
+    /// Renumber the bodies so that they come in the order of their items in
+    /// [TranslatedCrate::ordered_decls]. The bodies of the items which are not in a group come
+    /// last, in their current order.
+    pub fn reorder_bodies(&mut self) {
+        let Some(groups) = &self.ordered_decls else {
+            return;
+        };
+        let mut remap: IdRemap<BodyId> = IdRemap::new();
+        for id in groups.iter().flat_map(|group| group.get_ids()) {
+            let body = match self.get_item(id) {
+                Some(AnyTransItem::Fun(decl)) => decl.body.ok(),
+                Some(AnyTransItem::Global(decl)) => decl.body.ok(),
+                _ => None,
+            };
+            if let Some(body) = body {
+                remap.insert(body);
+            }
+        }
+        let other_bodies = (self.fun_decls.iter().map(|decl| decl.body.ok()))
+            .chain(self.global_decls.iter().map(|decl| decl.body.ok()))
+            .flatten()
+            .chain(self.bodies.iter_indices());
+        for body in other_bodies {
+            remap.insert(body);
+        }
+        remap.freeze_retained();
+
+        self.bodies = remap.compact(std::mem::take(&mut self.bodies), |_, _| ());
+        for decl in self.fun_decls.iter_mut() {
+            if let Ok(body) = &mut decl.body {
+                *body = remap.get(*body).unwrap();
+            }
+        }
+        for decl in self.global_decls.iter_mut() {
+            if let Ok(body) = &mut decl.body {
+                *body = remap.get(*body).unwrap();
+            }
+        }
+    }
+}
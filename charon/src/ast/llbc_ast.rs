@@ -8,10 +8,13 @@
 
 pub use super::llbc_ast_utils::*;
 pub use crate::ast::*;
+use crate::generate_index_type;
 use derive_visitor::{Drive, DriveMut};
 use macros::{EnumAsGetters, EnumIsA, EnumToGetters, VariantIndexArity, VariantName};
 use serde::{Deserialize, Serialize};
 
+generate_index_type!(LabelId, "Label");
+
 /// A raw statement: a statement without meta data.
 #[derive(
     Debug, Clone, EnumIsA, EnumToGetters, EnumAsGetters, Serialize, Deserialize, Drive, DriveMut,
@@ -25,6 +28,9 @@ pub enum RawStatement {
     /// Not used today because we take MIR built.
     SetDiscriminant(Place, VariantId),
     Drop(Place),
+    /// A retag, for the Stacked Borrows memory model. These are only kept with the `--keep-retags`
+    /// option.
+    Retag(RetagKind, Place),
     Assert(Assert),
     Call(Call),
     /// Panic also handles "unreachable". We keep the name of the panicking function that was
@@ -46,10 +52,37 @@ pub enum RawStatement {
     /// No-op.
     Nop,
     Switch(Switch),
+    /// The `?` operator: `dest := op?`. This is only introduced by the opt-in
+    /// [crate::transform::reconstruct_try] pass.
+    TryPropagate(TryPropagate),
     Loop(Block),
+    /// A block which can be jumped to with [RawStatement::Goto]. Those are only introduced in the
+    /// semi-structured mode (see the `--semi-structured` option), for the bodies whose
+    /// control-flow we failed to reconstruct. The body is then a sequence of labeled blocks, each
+    /// of which ends with a jump (a `goto`, a `return` or an `abort`).
+    Labeled(LabelId, Block),
+    /// Jump to the start of the labeled block with the given id.
+    Goto(LabelId),
     Error(String),
 }
 
+/// The `?` operator. This calls `Try::branch` on `op`: if the result is `Continue(v)`, `v` is
+/// stored in `dest`; if it is `Break(r)`, the function drops `residual_drops` and returns
+/// `FromResidual::from_residual(r)`.
+#[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
+pub struct TryPropagate {
+    /// The value the operator is applied to.
+    pub op: Operand,
+    /// The `Try::branch` method of the type of `op`.
+    pub branch: FnPtr,
+    /// The `FromResidual::from_residual` method of the return type of the function.
+    pub from_residual: FnPtr,
+    /// The places which are dropped before returning the residual.
+    pub residual_drops: Vec<Place>,
+    /// Where the output value is stored.
+    pub dest: Place,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
 pub struct Statement {
     pub span: Span,
@@ -57,6 +90,27 @@ pub struct Statement {
     /// Comments that precede this statement.
     // This is filled in a late pass after all the control-flow manipulation.
     pub comments_before: Vec<String>,
+    /// The borrows created and used for the last time here, if requested with
+    /// `--borrow-liveness`. This comes from the ULLBC statement or terminator this statement was
+    /// translated from; the statements introduced by the LLBC passes have none.
+    #[charon::opaque]
+    #[serde(default)]
+    pub borrow_liveness: Option<BorrowLiveness>,
+    /// The locals moved, copied and dropped here, if requested with `--move-info`. Like
+    /// [Self::borrow_liveness], this comes from ULLBC.
+    #[charon::opaque]
+    #[serde(default)]
+    pub move_info: Option<MoveInfo>,
+    /// The range of bytes this statement comes from in the original source file, if requested
+    /// with `--statement-ranges` and if the source file is available.
+    #[charon::opaque]
+    #[serde(default)]
+    pub byte_range: Option<ByteRange>,
+    /// The annotations found in [Self::comments_before], if requested with
+    /// `--comment-annotations`.
+    #[charon::opaque]
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
@@ -107,6 +161,45 @@ pub enum Switch {
     /// (whenever we find a discriminant read, we merge it with the subsequent
     /// switch into a match).
     Match(Place, Vec<(Vec<VariantId>, Block)>, Option<Block>),
+    /// A multi-level match over an ADT, e.g.:
+    /// ```text
+    /// match x {
+    ///   Some(Ok(_)) => ...,
+    ///   Some(Err(_)) => ...,
+    ///   None => ...,
+    /// }
+    /// ```
+    ///
+    /// This is only introduced by the opt-in [crate::transform::reconstruct_matches] pass, which
+    /// merges the nested [Switch::Match]es on the fields of the scrutinee. The [MatchKind] records
+    /// the surface construct the match was recognized as.
+    NestedMatch(Place, Vec<(Pattern, Block)>, Option<Block>, MatchKind),
+}
+
+/// The surface construct a [Switch::NestedMatch] was recognized as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIsA, Serialize, Deserialize, Drive, DriveMut)]
+#[charon::variants_prefix("Kind")]
+pub enum MatchKind {
+    /// A plain `match`.
+    Match,
+    /// `if let P = x { .. } else { .. }`: a single arm, and an `otherwise` branch.
+    IfLet,
+    /// `let P = x else { .. };`: a single arm, and an `otherwise` branch which diverges.
+    LetElse,
+    /// `while let P = x { .. }`: an `if let` at the end of a loop body, whose `otherwise` branch
+    /// breaks out of the loop.
+    WhileLet,
+}
+
+/// A pattern of a [Switch::NestedMatch].
+#[derive(Debug, Clone, EnumIsA, EnumAsGetters, Serialize, Deserialize, Drive, DriveMut)]
+#[charon::variants_prefix("Pat")]
+pub enum Pattern {
+    /// Matches anything (`_`).
+    Wildcard,
+    /// Matches the given variants, with sub-patterns for some of the fields (the other fields
+    /// are matched with `_`). The list of fields is only non-empty if there is a single variant.
+    Variant(Vec<VariantId>, Vec<(FieldId, Pattern)>),
 }
 
 pub type ExprBody = GExprBody<Block>;
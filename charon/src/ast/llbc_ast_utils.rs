@@ -23,6 +23,15 @@ pub fn combine_switch_targets_span(targets: &Switch) -> Span {
                 mbranches
             }
         }
+        Switch::NestedMatch(_, branches, otherwise, _) => {
+            let branches = branches.iter().map(|b| &b.1.span);
+            let mbranches = meta::combine_span_iter(branches);
+            if let Some(otherwise) = otherwise {
+                meta::combine_span(&mbranches, &otherwise.span)
+            } else {
+                mbranches
+            }
+        }
     }
 }
 
@@ -34,8 +43,11 @@ impl Switch {
             Switch::SwitchInt(_, _, targets, otherwise) => Either::Right(Either::Left(
                 targets.iter().map(|(_, tgt)| tgt).chain([otherwise]),
             )),
-            Switch::Match(_, targets, otherwise) => Either::Right(Either::Right(
+            Switch::Match(_, targets, otherwise) => Either::Right(Either::Right(Either::Left(
                 targets.iter().map(|(_, tgt)| tgt).chain(otherwise.as_ref()),
+            ))),
+            Switch::NestedMatch(_, targets, otherwise, _) => Either::Right(Either::Right(
+                Either::Right(targets.iter().map(|(_, tgt)| tgt).chain(otherwise.as_ref())),
             )),
         }
     }
@@ -47,12 +59,20 @@ impl Switch {
             Switch::SwitchInt(_, _, targets, otherwise) => Either::Right(Either::Left(
                 targets.iter_mut().map(|(_, tgt)| tgt).chain([otherwise]),
             )),
-            Switch::Match(_, targets, otherwise) => Either::Right(Either::Right(
+            Switch::Match(_, targets, otherwise) => Either::Right(Either::Right(Either::Left(
                 targets
                     .iter_mut()
                     .map(|(_, tgt)| tgt)
                     .chain(otherwise.as_mut()),
-            )),
+            ))),
+            Switch::NestedMatch(_, targets, otherwise, _) => {
+                Either::Right(Either::Right(Either::Right(
+                    targets
+                        .iter_mut()
+                        .map(|(_, tgt)| tgt)
+                        .chain(otherwise.as_mut()),
+                )))
+            }
         }
     }
 }
@@ -63,6 +83,10 @@ impl Statement {
             span,
             content,
             comments_before: vec![],
+            borrow_liveness: None,
+            move_info: None,
+            byte_range: None,
+            annotations: Vec::new(),
         }
     }
 
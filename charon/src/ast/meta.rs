@@ -62,6 +62,16 @@ impl From<RawSpan> for rustc_error_messages::MultiSpan {
     }
 }
 
+/// A range of bytes in a source file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Drive, DriveMut)]
+pub struct ByteRange {
+    pub file_id: FileId,
+    /// The (0-based) offset of the first byte.
+    pub beg: usize,
+    /// The (0-based) offset of the byte just after the range.
+    pub end: usize,
+}
+
 /// Meta information about a piece of code (block, statement, etc.)
 #[derive(
     Debug,
@@ -100,6 +110,33 @@ pub struct Span {
     pub span: RawSpan,
     /// Where the code actually comes from, in case of macro expansion/inlining/etc.
     pub generated_from_span: Option<RawSpan>,
+    /// The standard assertion macro this code was expanded from, if any. This only applies to the
+    /// code generated by the macro (e.g. the comparison and the panic of an `assert_eq!`), not to
+    /// the expressions passed to it.
+    #[drive(skip)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub macro_origin: Option<MacroOrigin>,
+}
+
+/// The standard macros whose expansions we recognize. If some code comes from nested macros
+/// (e.g. `debug_assert!` expands to `assert!`), we record the outermost one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[charon::variants_prefix("Macro")]
+pub enum MacroOrigin {
+    /// `matches!`
+    Matches,
+    /// `assert!`
+    Assert,
+    /// `assert_eq!`
+    AssertEq,
+    /// `assert_ne!`
+    AssertNe,
+    /// `debug_assert!`
+    DebugAssert,
+    /// `debug_assert_eq!`
+    DebugAssertEq,
+    /// `debug_assert_ne!`
+    DebugAssertNe,
 }
 
 #[cfg(feature = "rustc")]
@@ -153,6 +190,10 @@ pub enum Attribute {
     /// Do not translate the body of this item.
     /// Written `#[charon::opaque]`
     Opaque,
+    /// Make this function an entry point of the crate, listed in
+    /// [crate::ast::TranslatedCrate::entry_points].
+    /// Written `#[charon::root]`
+    Root,
     /// Provide a new name that consumers of the llbc can use.
     /// Written `#[charon::rename("new_name")]`
     Rename(String),
@@ -208,6 +249,74 @@ pub struct AttrInfo {
     pub public: bool,
 }
 
+/// The visibility of an item, as declared in the source.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Drive, DriveMut, EnumIsA)]
+#[charon::variants_prefix("Vis")]
+pub enum Visibility {
+    /// `pub`.
+    Public,
+    /// `pub(crate)`.
+    Crate,
+    /// Private, or restricted to a module (`pub(super)`, `pub(in path)`). Impl blocks and closures
+    /// don't have visibility modifiers; we consider them private.
+    Private,
+}
+
+/// A `#[stable(..)]` or `#[unstable(..)]` attribute. These are found in the standard library.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Drive, DriveMut)]
+pub struct Stability {
+    /// The feature the item belongs to (`feature = "..."`).
+    pub feature: String,
+    /// Whether the item is unstable, i.e. whether using it requires enabling the feature.
+    pub unstable: bool,
+}
+
+/// The attributes which influence the code generated for an item, read from the codegen
+/// information computed by rustc.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, Drive, DriveMut)]
+pub struct CodegenAttrs {
+    /// Whether the item has the `#[used]` attribute (or `#[used(linker)]`), which means it must be
+    /// kept in the object file even if it looks unused.
+    pub used: bool,
+    /// The section given by `#[link_section = "..."]`.
+    pub link_section: Option<String>,
+    /// Whether the function has the `#[cold]` attribute.
+    pub cold: bool,
+    /// Whether the function has the `#[track_caller]` attribute.
+    pub track_caller: bool,
+    /// Whether the function has the `#[naked]` attribute.
+    pub naked: bool,
+    /// The target features enabled with `#[target_feature(enable = "...")]`.
+    pub target_features: Vec<String>,
+}
+
+/// Where an item comes from, so that the items of a merged extraction can be traced back to the
+/// exact versions of the crates they come from.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, Drive, DriveMut)]
+pub struct Provenance {
+    /// The name of the crate which defines the item.
+    pub crate_name: String,
+    /// The version of this crate, when we know it: cargo gives the version of the crate being
+    /// extracted, and the version of a dependency appears in the path to its sources (e.g.
+    /// `.cargo/registry/src/<index>/smallvec-1.13.2/src/lib.rs`). This is `None` for the crates
+    /// of the standard library.
+    pub crate_version: Option<String>,
+    /// Whether the MIR of the item was available, i.e. whether we could translate its body if it
+    /// wasn't opaque. This is `false` for the functions of other crates which are neither generic
+    /// nor inlinable, and for the items which have no body (types, traits and trait impls).
+    pub body_available: bool,
+}
+
+/// A directive written in a comment, with `--comment-annotations`: `//@ requires: x > 0` gives
+/// the annotation with key `requires` and value `x > 0`. This gives tools a lightweight way to
+/// annotate the code without procedural macros.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Drive, DriveMut)]
+pub struct Annotation {
+    pub key: String,
+    /// The rest of the line, which may be empty.
+    pub value: String,
+}
+
 #[derive(
     Debug,
     Copy,
@@ -255,6 +364,29 @@ pub struct ItemMeta {
     pub attr_info: AttrInfo,
     /// `true` if the type decl is a local type decl, `false` if it comes from an external crate.
     pub is_local: bool,
+    /// The declared visibility of the item. Like [AttrInfo::public], this doesn't take
+    /// reachability into account.
+    pub visibility: Visibility,
+    /// The stability attribute of the item, if any.
+    pub stability: Option<Stability>,
+    /// Whether this item is a verification harness, i.e. a function annotated with one of the
+    /// harness attributes (`#[kani::proof]`, `#[quickcheck]`, or those given with
+    /// `--harness-attr`).
+    pub is_harness: bool,
+    /// The codegen attributes of the item (`#[used]`, `#[link_section]`, etc.).
+    pub codegen_attrs: CodegenAttrs,
+    /// The crate which defines the item, and whether its body was available.
+    pub provenance: Provenance,
+    /// For the items which Charon generates and which may be generated identically when
+    /// extracting other crates (the specialized copies of blanket impls and their methods, and the
+    /// function pointer shims of closures): a hash of the contents of the item, which doesn't
+    /// depend on the ids of the crate. When merging several extractions, the items with the same
+    /// hash are duplicates of each other and only one of them needs to be kept, like weak symbols
+    /// at link time. This is `None` for the other items.
+    pub dedup_hash: Option<String>,
+    /// The annotations found in the comments right before the item, if requested with
+    /// `--comment-annotations`. We only look for them in the local items.
+    pub annotations: Vec<Annotation>,
     /// Whether this item is considered opaque. For function and globals, this means we don't
     /// translate the body (the code); for ADTs, this means we don't translate the fields/variants.
     /// For traits and trait impls, this doesn't change anything. For modules, this means we don't
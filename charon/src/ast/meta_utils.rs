@@ -33,7 +33,39 @@ impl Loc {
     }
 }
 
+impl Loc {
+    /// Compute the byte offset of this location in the given file contents. Returns `None` if
+    /// the location is out of the file.
+    pub fn to_byte_offset(&self, contents: &str) -> Option<usize> {
+        let line_start = if self.line <= 1 {
+            0
+        } else {
+            contents
+                .match_indices('\n')
+                .nth(self.line - 2)
+                .map(|(i, _)| i + 1)?
+        };
+        let line = &contents[line_start..];
+        let col_offset = match line.char_indices().nth(self.col) {
+            Some((i, _)) => i,
+            // The location may point just after the last character.
+            None if line.chars().count() == self.col => line.len(),
+            None => return None,
+        };
+        Some(line_start + col_offset)
+    }
+}
+
 impl RawSpan {
+    /// Compute the byte range of this span in the given file contents.
+    pub fn to_byte_range(&self, contents: &str) -> Option<ByteRange> {
+        Some(ByteRange {
+            file_id: self.file_id,
+            beg: self.beg.to_byte_offset(contents)?,
+            end: self.end.to_byte_offset(contents)?,
+        })
+    }
+
     pub fn dummy() -> Self {
         RawSpan {
             file_id: FileId::from_raw(0),
@@ -67,10 +99,36 @@ impl Span {
         Span {
             span: RawSpan::dummy(),
             generated_from_span: None,
+            macro_origin: None,
         }
     }
 }
 
+impl MacroOrigin {
+    /// The macro with this name, if it is one of the macros we recognize.
+    pub fn from_macro_name(name: &str) -> Option<Self> {
+        use MacroOrigin::*;
+        Some(match name {
+            "matches" => Matches,
+            "assert" => Assert,
+            "assert_eq" => AssertEq,
+            "assert_ne" => AssertNe,
+            "debug_assert" => DebugAssert,
+            "debug_assert_eq" => DebugAssertEq,
+            "debug_assert_ne" => DebugAssertNe,
+            _ => return None,
+        })
+    }
+
+    /// Whether this is one of the `debug_assert` macros, which are only checked in debug builds.
+    pub fn is_debug_assert(&self) -> bool {
+        matches!(
+            self,
+            MacroOrigin::DebugAssert | MacroOrigin::DebugAssertEq | MacroOrigin::DebugAssertNe
+        )
+    }
+}
+
 /// Combine some span information (useful when we need to compute the
 /// span-information of, say, a sequence).
 pub fn combine_span(m0: &Span, m1: &Span) -> Span {
@@ -95,6 +153,11 @@ pub fn combine_span(m0: &Span, m1: &Span) -> Span {
         Span {
             span,
             generated_from_span: None,
+            macro_origin: if m0.macro_origin == m1.macro_origin {
+                m0.macro_origin
+            } else {
+                None
+            },
         }
     } else {
         // It happens that the spans don't come from the same file. In this
@@ -142,6 +205,8 @@ impl Attribute {
         let parsed = match attr_name {
             // `#[charon::opaque]`
             "opaque" if args.is_none() => Self::Opaque,
+            // `#[charon::root]`
+            "root" if args.is_none() => Self::Root,
             // `#[charon::rename("new_name")]`
             "rename" if let Some(attr) = args => {
                 let Some(attr) = attr
@@ -226,3 +291,29 @@ impl ItemMeta {
         name
     }
 }
+
+impl Annotation {
+    /// Parse a comment (without its leading `//`) of the form `@<key>: <value>`. The colon is
+    /// optional, so that `// @invariant i <= n` works too.
+    pub fn parse(comment: &str) -> Option<Self> {
+        let directive = comment.trim_start().strip_prefix('@')?.trim_start();
+        let key_len = directive
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(directive.len());
+        let (key, value) = directive.split_at(key_len);
+        if key.is_empty() {
+            return None;
+        }
+        let value = value.trim_start();
+        let value = value.strip_prefix(':').unwrap_or(value).trim();
+        Some(Annotation {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// Parse the annotations among the given comments.
+    pub fn parse_all<'a>(comments: impl IntoIterator<Item = &'a str>) -> Vec<Self> {
+        comments.into_iter().filter_map(Annotation::parse).collect()
+    }
+}
@@ -4,6 +4,7 @@ pub mod expressions_utils;
 pub mod gast;
 pub mod gast_utils;
 pub mod krate;
+pub mod krate_utils;
 pub mod llbc_ast;
 pub mod llbc_ast_utils;
 pub mod meta;
@@ -16,6 +17,7 @@ pub mod ullbc_ast;
 pub mod ullbc_ast_utils;
 pub mod values;
 pub mod values_utils;
+pub mod visitor;
 
 // Re-export everything except llbc/ullbc, for convenience.
 pub use crate::errors::Error;
@@ -27,3 +29,4 @@ pub use meta::*;
 pub use names::*;
 pub use types::*;
 pub use values::*;
+pub use visitor::*;
@@ -4,6 +4,8 @@ use derive_visitor::{Drive, DriveMut};
 use macros::{EnumAsGetters, EnumIsA};
 use serde::{Deserialize, Serialize};
 
+pub use super::names_utils::{demangle, mangle, mangled_file_name};
+
 generate_index_type!(Disambiguator);
 
 /// See the comments for [Name]
@@ -26,11 +28,22 @@ pub enum PathElem {
 ///   impl<T> PartialEq for List<T> { ...}
 ///   ```
 /// We distinguish the two.
+///
+/// We record the self type (and the implemented trait) of the impl blocks in the names, so that
+/// the names are self-describing, and stable across edits of unrelated impl blocks.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Drive, DriveMut)]
 #[charon::variants_prefix("ImplElem")]
 pub enum ImplElem {
+    /// An inherent impl block: its generics and its self type.
     Ty(GenericParams, Ty),
-    Trait(TraitImplId),
+    /// A trait impl block: its id, its generics and the implemented trait. The self type is the
+    /// first type argument of the trait reference.
+    Trait(TraitImplId, GenericParams, TraitDeclRef),
+    /// An implementation provided by the compiler (see [TraitRefKind::BuiltinOrAuto]): the
+    /// implemented trait. These have no declaration, hence this doesn't appear in the names of the
+    /// translated items; we use it to name the builtin impls (see
+    /// [TranslatedCrate::builtin_impl_name]).
+    Builtin(TraitDeclRef),
 }
 
 /// An item name/path
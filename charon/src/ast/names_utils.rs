@@ -56,3 +56,124 @@ impl Name {
         self.compare_with_ref_name(true, ref_name)
     }
 }
+
+/// The maximum length of the file names returned by [mangled_file_name]. Most file systems limit
+/// file names to 255 bytes; we leave some room for the extensions.
+const MAX_FILE_NAME_LEN: usize = 200;
+
+/// Escape a string so that it only contains `[A-Za-z0-9_]` and `-XX` escapes (where `XX` is the
+/// hex encoding of a byte of the UTF-8 representation of the string).
+fn escape_for_mangling(s: &str, out: &mut String) {
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'_' {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("-{byte:02X}"));
+        }
+    }
+}
+
+fn unescape_for_mangling(s: &str) -> Result<String, String> {
+    let mut bytes = Vec::new();
+    let mut iter = s.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'-' {
+            let hex: Vec<u8> = iter.by_ref().take(2).collect();
+            let byte = std::str::from_utf8(&hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| format!("invalid escape in mangled name: `{s}`"))?;
+            bytes.push(byte);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).map_err(|err| err.to_string())
+}
+
+/// Encode a name into a string which can be used as a file name: it only contains
+/// `[A-Za-z0-9_.@-]`. The encoding is reversible (see [demangle]).
+///
+/// The path elements are separated by `.`. The identifiers are escaped (see
+/// [escape_for_mangling]), and the impl blocks are encoded as `@` followed by their escaped JSON
+/// representation (which contains their generics and self type, or the id of the trait impl). The
+/// non-zero disambiguators are appended to their path element as `@<disambiguator>`.
+///
+/// The mangled names of impl blocks can be long: use [mangled_file_name] to get a file name of
+/// bounded length.
+pub fn mangle(name: &Name) -> String {
+    let mut out = String::new();
+    for (i, elem) in name.name.iter().enumerate() {
+        if i != 0 {
+            out.push('.');
+        }
+        let disambiguator = match elem {
+            PathElem::Ident(s, d) => {
+                escape_for_mangling(s, &mut out);
+                d
+            }
+            PathElem::Impl(impl_elem, d) => {
+                out.push('@');
+                escape_for_mangling(&serde_json::to_string(impl_elem).unwrap(), &mut out);
+                d
+            }
+        };
+        if !disambiguator.is_zero() {
+            out.push_str(&format!("@{disambiguator}"));
+        }
+    }
+    out
+}
+
+/// Decode a name encoded with [mangle].
+pub fn demangle(mangled: &str) -> Result<Name, String> {
+    let name = mangled
+        .split('.')
+        .map(|elem| {
+            let mut parts = elem.split('@');
+            let ident = parts.next().unwrap();
+            let impl_elem = if ident.is_empty() {
+                let json = parts
+                    .next()
+                    .ok_or_else(|| format!("invalid mangled name: `{mangled}`"))?;
+                let json = unescape_for_mangling(json)?;
+                Some(serde_json::from_str::<ImplElem>(&json).map_err(|err| err.to_string())?)
+            } else {
+                None
+            };
+            let disambiguator = match parts.next() {
+                Some(d) => Disambiguator::new(
+                    d.parse()
+                        .map_err(|_| format!("invalid disambiguator in `{mangled}`"))?,
+                ),
+                None => Disambiguator::ZERO,
+            };
+            if parts.next().is_some() {
+                return Err(format!("invalid mangled name: `{mangled}`"));
+            }
+            Ok(match impl_elem {
+                Some(impl_elem) => PathElem::Impl(impl_elem, disambiguator),
+                None => PathElem::Ident(unescape_for_mangling(ident)?, disambiguator),
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(Name { name })
+}
+
+/// A file name for the name, with the given extension. This is the mangled name (see [mangle]),
+/// unless it is too long for a file name, in which case we truncate it and append a hash of the
+/// full mangled name (so the result is not reversible anymore, but it is still unique in
+/// practice and stable across runs).
+pub fn mangled_file_name(name: &Name, extension: &str) -> String {
+    let mut mangled = mangle(name);
+    if mangled.len() > MAX_FILE_NAME_LEN {
+        // FNV-1a, which is simple and stable across platforms and compiler versions.
+        let hash = mangled.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        // The mangled names only contain ASCII characters.
+        mangled.truncate(MAX_FILE_NAME_LEN - 17);
+        mangled = format!("{mangled}-{hash:016x}");
+    }
+    format!("{mangled}.{extension}")
+}
@@ -129,6 +129,9 @@ pub enum Region {
 /// definition. Note that every path designated by [TraitInstanceId] refers
 /// to a *trait instance*, which is why the [Clause] variant may seem redundant
 /// with some of the other variants.
+///
+/// See [TraitRefKind::provenance] to know where a trait reference comes from without having to
+/// walk the paths of clauses.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Drive, DriveMut)]
 #[charon::rename("TraitInstanceId")]
 pub enum TraitRefKind {
@@ -213,8 +216,9 @@ pub enum TraitRefKind {
     #[charon::rename("Self")]
     SelfId,
 
-    /// A specific builtin trait implementation like [core::marker::Sized] or
-    /// auto trait implementation like [core::marker::Syn].
+    /// An implementation provided by the compiler: the builtin traits (e.g. [core::marker::Sized],
+    /// [core::marker::Tuple], `Copy` and `Clone` on the primitive types, the `Fn*` traits on the
+    /// function pointers and closures), and the auto traits (e.g. [core::marker::Sync]).
     BuiltinOrAuto(PolyTraitDeclRef),
 
     /// The automatically-generated implementation for `dyn Trait`.
@@ -234,6 +238,33 @@ pub struct TraitRef {
     pub trait_decl_ref: PolyTraitDeclRef,
 }
 
+/// Where the proof of a trait reference comes from (see [TraitRefKind::provenance]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraitRefProvenance<'a> {
+    /// A concrete impl block, instantiated with the given arguments.
+    Impl(TraitImplId, &'a GenericArgs),
+    /// A trait clause in scope: a clause of the current item, or the implicit `Self: Trait`
+    /// clause in a trait declaration (`None`). The proof may be a parent clause of this clause,
+    /// or a clause on one of its associated types (see [TraitRefKind::clause_path]).
+    Clause(Option<TraitClauseId>),
+    /// An implementation provided by the compiler (see [TraitRefKind::BuiltinOrAuto]).
+    Builtin,
+    /// The implementation for `dyn Trait`.
+    Dyn,
+    /// Trait resolution failed.
+    Unknown,
+}
+
+/// A step in the path from a clause in scope to a trait reference (see
+/// [TraitRefKind::clause_path]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClausePathElem<'a> {
+    /// The given parent clause of the given trait.
+    Parent(TraitDeclId, TraitClauseId),
+    /// The given clause on an associated type of the given trait.
+    AssocItem(TraitDeclId, &'a TraitItemName, TraitClauseId),
+}
+
 /// A predicate of the form `Type: Trait<Args>`.
 ///
 /// About the generics, if we write:
@@ -434,6 +465,22 @@ pub struct TypeDecl {
     pub generics: GenericParams,
     /// The type kind: enum, struct, or opaque.
     pub kind: TypeDeclKind,
+    /// Whether the type implements `Unpin`, i.e. whether its values may be moved after being
+    /// pinned. `None` for the types for which we don't compute it (e.g. `impl Trait` types).
+    pub unpin: Option<UnpinKind>,
+}
+
+/// Whether a type implements `Unpin`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIsA, Serialize, Deserialize, Drive, DriveMut)]
+pub enum UnpinKind {
+    /// The type always implements `Unpin`.
+    Unpin,
+    /// The type implements `Unpin` when its type parameters do, like most generic types
+    /// (e.g. `Option<T>`).
+    UnpinIfParamsUnpin,
+    /// The type never implements `Unpin`, e.g. because it contains a `PhantomPinned` or has an
+    /// `impl !Unpin`. Pinned values of this type stay at the same address until they are dropped.
+    NotUnpin,
 }
 
 #[derive(Debug, Clone, EnumIsA, EnumAsGetters, Serialize, Deserialize, Drive, DriveMut)]
@@ -473,6 +520,23 @@ pub struct Field {
     pub name: Option<String>,
     #[charon::rename("field_ty")]
     pub ty: Ty,
+    /// If the type of this field mentions a type of the (mutually) recursive group of the type
+    /// declaration, how it does so. Only computed with `--annotate-recursive-fields`.
+    #[drive(skip)]
+    #[charon::rename("field_recursion")]
+    pub recursion: Option<FieldRecursion>,
+}
+
+/// How the type of a field refers to the recursive group of its type declaration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[charon::variants_prefix("Rec")]
+pub enum FieldRecursion {
+    /// All the occurrences of the recursive group go through an indirection: `Box`, `Rc`, `Arc`,
+    /// `Vec`, a reference or a raw pointer.
+    Indirect,
+    /// Some occurrence of the recursive group doesn't go through a known indirection, e.g.
+    /// `Option<Self>` or `Foo<Self>` where `Foo` is a user-defined type.
+    Direct,
 }
 
 #[derive(
@@ -593,6 +657,33 @@ pub enum TypeId {
     Builtin(BuiltinTy),
 }
 
+/// The outermost type constructor of a type. We use it to index the trait impls by their self
+/// type (see [crate::ast::TraitImplIndex]).
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    Hash,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    Drive,
+    DriveMut,
+)]
+#[charon::variants_prefix("Head")]
+pub enum TypeHead {
+    Adt(TypeId),
+    Literal(LiteralTy),
+    Ref(RefKind),
+    RawPtr(RefKind),
+    Arrow,
+    DynTrait,
+    Never,
+}
+
 /// Types of primitive values. Either an integer, bool, char
 #[derive(
     Debug,
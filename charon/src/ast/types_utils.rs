@@ -185,6 +185,72 @@ impl GenericArgs {
     }
 }
 
+impl TraitRefKind {
+    /// The trait reference at the root of a path of parent clauses and clauses on associated
+    /// items. This is `self` for the other kinds.
+    pub fn root(&self) -> &TraitRefKind {
+        match self {
+            TraitRefKind::ParentClause(parent, ..) | TraitRefKind::ItemClause(parent, ..) => {
+                parent.root()
+            }
+            _ => self,
+        }
+    }
+
+    /// The path from the root (see [Self::root]) to this trait reference, starting from the root.
+    pub fn clause_path(&self) -> Vec<ClausePathElem<'_>> {
+        let mut path = Vec::new();
+        let mut current = self;
+        loop {
+            match current {
+                TraitRefKind::ParentClause(parent, trait_id, clause_id) => {
+                    path.push(ClausePathElem::Parent(*trait_id, *clause_id));
+                    current = parent;
+                }
+                TraitRefKind::ItemClause(parent, trait_id, name, clause_id) => {
+                    path.push(ClausePathElem::AssocItem(*trait_id, name, *clause_id));
+                    current = parent;
+                }
+                _ => break,
+            }
+        }
+        path.reverse();
+        path
+    }
+
+    /// Where the proof of this trait reference comes from.
+    pub fn provenance(&self) -> TraitRefProvenance<'_> {
+        match self.root() {
+            TraitRefKind::TraitImpl(impl_id, generics) => {
+                TraitRefProvenance::Impl(*impl_id, generics)
+            }
+            TraitRefKind::Clause(clause_id) => TraitRefProvenance::Clause(Some(*clause_id)),
+            TraitRefKind::SelfId => TraitRefProvenance::Clause(None),
+            TraitRefKind::BuiltinOrAuto(_) => TraitRefProvenance::Builtin,
+            TraitRefKind::Dyn(_) => TraitRefProvenance::Dyn,
+            TraitRefKind::Unknown(_) => TraitRefProvenance::Unknown,
+            TraitRefKind::ParentClause(..) | TraitRefKind::ItemClause(..) => unreachable!(),
+        }
+    }
+
+    /// Whether this is proven by a clause in scope rather than by an implementation.
+    pub fn is_from_clause(&self) -> bool {
+        matches!(self.provenance(), TraitRefProvenance::Clause(_))
+    }
+}
+
+impl TraitRef {
+    /// The trait which this reference implements.
+    pub fn trait_id(&self) -> TraitDeclId {
+        self.trait_decl_ref.skip_binder.trait_id
+    }
+
+    /// Where the proof of this trait reference comes from (see [TraitRefKind::provenance]).
+    pub fn provenance(&self) -> TraitRefProvenance<'_> {
+        self.kind.provenance()
+    }
+}
+
 impl IntegerTy {
     pub fn is_signed(&self) -> bool {
         matches!(
@@ -223,6 +289,21 @@ impl IntegerTy {
 }
 
 impl Ty {
+    /// The outermost type constructor of this type, if it is not a type variable or an
+    /// associated type.
+    pub fn head(&self) -> Option<TypeHead> {
+        Some(match self.kind() {
+            TyKind::Adt(type_id, _) => TypeHead::Adt(*type_id),
+            TyKind::Literal(lit) => TypeHead::Literal(*lit),
+            TyKind::Ref(_, _, kind) => TypeHead::Ref(*kind),
+            TyKind::RawPtr(_, kind) => TypeHead::RawPtr(*kind),
+            TyKind::Arrow(..) => TypeHead::Arrow,
+            TyKind::DynTrait(..) => TypeHead::DynTrait,
+            TyKind::Never => TypeHead::Never,
+            TyKind::TypeVar(..) | TyKind::TraitType(..) => return None,
+        })
+    }
+
     /// Return true if it is actually unit (i.e.: 0-tuple)
     pub fn is_unit(&self) -> bool {
         match self.kind() {
@@ -287,6 +368,14 @@ impl Ty {
         }
     }
 
+    /// Substitute the type and const generic variables of this type with the given arguments.
+    /// The regions and trait clauses are left untouched.
+    pub fn substitute(&self, generics: &GenericArgs) -> Ty {
+        let mut ty = self.clone();
+        ty.drive_mut(&mut Ty::visit_inside(SubstVisitor { generics }));
+        ty
+    }
+
     /// Wrap a visitor to make it visit the contents of types it encounters.
     pub fn visit_inside<V>(visitor: V) -> VisitInsideTy<V> {
         VisitInsideTy {
@@ -408,6 +497,34 @@ impl DriveMut for Ty {
     }
 }
 
+/// Replaces the type and const generic variables with the given arguments. This must be wrapped
+/// with [Ty::visit_inside] to reach the variables nested in types.
+struct SubstVisitor<'a> {
+    generics: &'a GenericArgs,
+}
+
+impl VisitorMut for SubstVisitor<'_> {
+    fn visit(&mut self, item: &mut dyn std::any::Any, event: Event) {
+        // We substitute on exit so that we don't visit the substituted arguments.
+        if !matches!(event, Event::Exit) {
+            return;
+        }
+        if let Some(ty) = item.downcast_mut::<Ty>() {
+            if let TyKind::TypeVar(id) = ty.kind()
+                && let Some(arg) = self.generics.types.get(*id)
+            {
+                *ty = arg.clone();
+            }
+        } else if let Some(cg) = item.downcast_mut::<ConstGeneric>() {
+            if let ConstGeneric::Var(id) = cg
+                && let Some(arg) = self.generics.const_generics.get(*id)
+            {
+                *cg = arg.clone();
+            }
+        }
+    }
+}
+
 pub struct VisitInsideTy<V> {
     visitor: V,
     /// If `Some`, record the effected visits and don't do them again. Only valid if the wrapped
@@ -32,6 +32,9 @@ pub enum RawStatement {
     /// We translate this to [crate::llbc_ast::RawStatement::Drop] in LLBC
     Deinit(Place),
     Drop(Place),
+    /// A retag, for the Stacked Borrows memory model. These are only kept with the `--keep-retags`
+    /// option.
+    Retag(RetagKind, Place),
     /// A built-in assert, which corresponds to runtime checks that we remove, namely: bounds
     /// checks, over/underflow checks, div/rem by zero checks, pointer alignement check.
     Assert(Assert),
@@ -45,6 +48,15 @@ pub enum RawStatement {
 pub struct Statement {
     pub span: Span,
     pub content: RawStatement,
+    /// The borrows created and used for the last time here, if requested with
+    /// `--borrow-liveness`.
+    #[charon::opaque]
+    #[serde(default)]
+    pub borrow_liveness: Option<BorrowLiveness>,
+    /// The locals moved, copied and dropped here, if requested with `--move-info`.
+    #[charon::opaque]
+    #[serde(default)]
+    pub move_info: Option<MoveInfo>,
 }
 
 #[derive(
@@ -88,6 +100,15 @@ pub enum RawTerminator {
 pub struct Terminator {
     pub span: Span,
     pub content: RawTerminator,
+    /// The borrows created and used for the last time here, if requested with
+    /// `--borrow-liveness`.
+    #[charon::opaque]
+    #[serde(default)]
+    pub borrow_liveness: Option<BorrowLiveness>,
+    /// The locals moved, copied and dropped here, if requested with `--move-info`.
+    #[charon::opaque]
+    #[serde(default)]
+    pub move_info: Option<MoveInfo>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Drive, DriveMut)]
@@ -3,8 +3,63 @@ use crate::ids::Vector;
 use crate::meta::Span;
 use crate::ullbc_ast::*;
 use derive_visitor::{visitor_enter_fn_mut, visitor_fn_mut, DriveMut, Event};
+use petgraph::algo::dominators::simple_fast;
+use petgraph::graphmap::DiGraphMap;
+use std::collections::{BTreeMap, BTreeSet};
 use take_mut::take;
 
+/// Graph information about the control-flow of a body, computed by [ExprBody::compute_cfg_info].
+#[derive(Debug, Clone)]
+pub struct CfgInfo {
+    /// The successors of every block, in the order of the targets of its terminator.
+    pub successors: Vector<BlockId, Vec<BlockId>>,
+    /// The predecessors of every block.
+    pub predecessors: Vector<BlockId, Vec<BlockId>>,
+    /// The immediate dominator of every block. This is `None` for the start block and for the
+    /// blocks which are unreachable from the start block.
+    pub immediate_dominators: Vector<BlockId, Option<BlockId>>,
+    /// The headers of the natural loops, i.e. the targets of the edges whose target dominates the
+    /// source.
+    pub loop_headers: BTreeSet<BlockId>,
+    /// The header of the innermost natural loop which contains every block, if any. The loops
+    /// which share a header are merged.
+    pub innermost_loop: Vector<BlockId, Option<BlockId>>,
+    /// The header of the loop which immediately encloses every loop, if any.
+    pub parent_loop: BTreeMap<BlockId, Option<BlockId>>,
+}
+
+impl CfgInfo {
+    /// Whether the block is reachable from the start block.
+    pub fn is_reachable(&self, block_id: BlockId) -> bool {
+        block_id == START_BLOCK_ID || self.immediate_dominators[block_id].is_some()
+    }
+
+    /// Iterate over the dominators of a block, starting with the block itself and ending with the
+    /// start block. This is empty if the block is unreachable.
+    pub fn dominators(&self, block_id: BlockId) -> impl Iterator<Item = BlockId> + '_ {
+        let first = self.is_reachable(block_id).then_some(block_id);
+        std::iter::successors(first, |bid| self.immediate_dominators[*bid])
+    }
+
+    /// Whether `dom` dominates `block_id`.
+    pub fn dominates(&self, dom: BlockId, block_id: BlockId) -> bool {
+        self.dominators(block_id).any(|bid| bid == dom)
+    }
+
+    /// Iterate over the headers of the loops which contain the block, from the innermost to the
+    /// outermost.
+    pub fn enclosing_loops(&self, block_id: BlockId) -> impl Iterator<Item = BlockId> + '_ {
+        std::iter::successors(self.innermost_loop[block_id], |header| {
+            self.parent_loop[header]
+        })
+    }
+
+    /// The loop nesting depth of the block (0 if the block is not in a loop).
+    pub fn loop_depth(&self, block_id: BlockId) -> usize {
+        self.enclosing_loops(block_id).count()
+    }
+}
+
 impl SwitchTargets {
     pub fn get_targets(&self) -> Vec<BlockId> {
         match self {
@@ -25,13 +80,23 @@ impl SwitchTargets {
 
 impl Statement {
     pub fn new(span: Span, content: RawStatement) -> Self {
-        Statement { span, content }
+        Statement {
+            span,
+            content,
+            borrow_liveness: None,
+            move_info: None,
+        }
     }
 }
 
 impl Terminator {
     pub fn new(span: Span, content: RawTerminator) -> Self {
-        Terminator { span, content }
+        Terminator {
+            span,
+            content,
+            borrow_liveness: None,
+            move_info: None,
+        }
     }
 }
 
@@ -104,6 +169,72 @@ impl BlockData {
 }
 
 impl ExprBody {
+    /// Compute the predecessors/successors, the dominators and the natural loops of the body.
+    pub fn compute_cfg_info(&self) -> CfgInfo {
+        let successors = self.body.map_ref(|block| block.targets());
+        let mut predecessors = self.body.map_ref(|_| Vec::new());
+        let mut graph: DiGraphMap<BlockId, ()> = DiGraphMap::new();
+        for block_id in self.body.iter_indices() {
+            graph.add_node(block_id);
+        }
+        for (block_id, targets) in successors.iter_indexed_values() {
+            for &target in targets {
+                predecessors[target].push(block_id);
+                graph.add_edge(block_id, target, ());
+            }
+        }
+
+        let dominators = simple_fast(&graph, START_BLOCK_ID);
+        let immediate_dominators = self
+            .body
+            .map_ref_indexed(|block_id, _| dominators.immediate_dominator(block_id));
+        let mut info = CfgInfo {
+            successors,
+            predecessors,
+            immediate_dominators,
+            loop_headers: BTreeSet::new(),
+            innermost_loop: self.body.map_ref(|_| None),
+            parent_loop: BTreeMap::new(),
+        };
+
+        // Compute the bodies of the natural loops: the header, and the blocks which can reach
+        // the source of a back edge without going through the header.
+        let mut loops: BTreeMap<BlockId, BTreeSet<BlockId>> = BTreeMap::new();
+        for (block_id, targets) in info.successors.iter_indexed_values() {
+            for &header in targets {
+                if !info.dominates(header, block_id) {
+                    continue;
+                }
+                let body = loops
+                    .entry(header)
+                    .or_insert_with(|| BTreeSet::from([header]));
+                let mut stack = vec![block_id];
+                while let Some(bid) = stack.pop() {
+                    if info.is_reachable(bid) && body.insert(bid) {
+                        stack.extend(info.predecessors[bid].iter().copied());
+                    }
+                }
+            }
+        }
+
+        // The innermost loop of a block is the smallest loop which contains it.
+        let mut loops_by_size = loops.iter().collect::<Vec<_>>();
+        loops_by_size.sort_by_key(|(_, body)| body.len());
+        for (header, body) in &loops_by_size {
+            let header = **header;
+            for bid in body.iter() {
+                info.innermost_loop[*bid].get_or_insert(header);
+            }
+            let parent = loops_by_size
+                .iter()
+                .find(|(outer, outer_body)| **outer != header && outer_body.contains(&header))
+                .map(|(outer, _)| **outer);
+            info.parent_loop.insert(header, parent);
+        }
+        info.loop_headers = loops.into_keys().collect();
+        info
+    }
+
     pub fn transform_sequences<F>(&mut self, f: &mut F)
     where
         F: FnMut(&mut Vector<VarId, Var>, &mut [Statement]) -> Vec<(usize, Vec<Statement>)>,
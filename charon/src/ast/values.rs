@@ -41,6 +41,8 @@ pub enum Literal {
     Char(char),
     ByteStr(Vec<u8>),
     Str(String),
+    /// A C string literal (`c"..."`), of type `&CStr`. The bytes include the trailing NUL.
+    CStr(Vec<u8>),
 }
 
 /// A scalar value.
@@ -80,14 +82,21 @@ pub enum ScalarValue {
 }
 
 /// This is simlar to the Scalar value above. However, instead of storing
-/// the float value itself, we store its String representation. This allows
+/// the float value itself, we store its String representation and its bit pattern. This allows
 /// to derive the Eq and Ord traits, which are not implemented for floats
 #[derive(
     Debug, PartialEq, Eq, Clone, Serialize, Deserialize, Hash, PartialOrd, Ord, Drive, DriveMut,
 )]
 pub struct FloatValue {
+    /// The value printed in decimal, e.g. `1.5`, `-0`, `inf` or `NaN`. This doesn't distinguish
+    /// the different NaNs.
     #[charon::rename("float_value")]
     pub value: String,
+    /// The bit pattern of the value, as given by `f32::to_bits` (and its equivalents for the other
+    /// float types). Unlike the string, this represents the value exactly.
+    #[charon::rename("float_bits")]
+    #[serde(with = "crate::ast::values_utils::u128_as_string")]
+    pub bits: u128,
     #[charon::rename("float_ty")]
     pub ty: FloatTy,
 }
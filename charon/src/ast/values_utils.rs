@@ -230,6 +230,7 @@ impl ScalarValue {
     pub fn to_constant(self) -> ConstantExpr {
         ConstantExpr {
             value: RawConstantExpr::Literal(Literal::Scalar(self)),
+            has_type_suffix: None,
             ty: TyKind::Literal(LiteralTy::Integer(self.get_integer_ty())).into_ty(),
         }
     }
@@ -303,3 +304,35 @@ impl<'de> Deserialize<'de> for ScalarValue {
         deserializer.deserialize_map(Visitor)
     }
 }
+
+impl FloatValue {
+    /// Whether the value is a NaN, i.e. all the bits of its exponent are set and its mantissa isn't
+    /// zero.
+    pub fn is_nan(&self) -> bool {
+        let (exponent_bits, mantissa_bits) = match self.ty {
+            FloatTy::F16 => (5, 10),
+            FloatTy::F32 => (8, 23),
+            FloatTy::F64 => (11, 52),
+            FloatTy::F128 => (15, 112),
+        };
+        let exponent = (self.bits >> mantissa_bits) & ((1 << exponent_bits) - 1);
+        let mantissa = self.bits & ((1 << mantissa_bits) - 1);
+        exponent == (1 << exponent_bits) - 1 && mantissa != 0
+    }
+}
+
+/// (De)serialize a `u128` as a decimal string, like the values of [ScalarValue]: most json
+/// libraries don't support integers this big.
+pub(crate) mod u128_as_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(v: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&v.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        use serde::de::Error;
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
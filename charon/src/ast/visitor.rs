@@ -0,0 +1,223 @@
+//! Typed visitors over the AST. The [derive_visitor] traversals give a `&dyn Any` for every node,
+//! which the visitors must downcast themselves. The [AstVisitor] and [AstVisitorMut] traits
+//! instead have one method per kind of node (`visit_ty`, `visit_rvalue`, etc.), which is called
+//! before visiting the contents of the node, and one `exit_*` method which is called after. The
+//! default methods do nothing and visit the contents, so a visitor only implements the methods
+//! for the nodes it cares about:
+//!
+//! ```ignore
+//! struct CountCalls(usize);
+//! impl AstVisitor for CountCalls {
+//!     fn visit_call(&mut self, _: &Call) -> VisitContents {
+//!         self.0 += 1;
+//!         VisitContents::Visit
+//!     }
+//! }
+//! let mut counter = CountCalls(0);
+//! counter.walk(&body);
+//! ```
+//!
+//! Contrary to the [derive_visitor] traversals, these visit the contents of the types (see
+//! [Ty::drive_inner]).
+use crate::ast::*;
+use crate::{llbc_ast, ullbc_ast};
+use derive_visitor::{Drive, DriveMut, Event, Visitor, VisitorMut};
+use std::any::Any;
+
+/// Whether to visit the contents of a node, returned by the `visit_*` methods of the typed
+/// visitors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitContents {
+    Visit,
+    /// Skip the contents of the node. The `exit_*` method of the node is still called.
+    Skip,
+}
+
+/// Generate the typed visitor traits and the functions which dispatch the untyped events of
+/// [derive_visitor] to their methods.
+macro_rules! make_typed_visitors {
+    ($($ty:ty => $visit:ident, $exit:ident;)*) => {
+        /// A visitor with one method per kind of node (see the [module docs](self)).
+        pub trait AstVisitor {
+            $(
+                #[doc = concat!("Called before visiting the contents of a `", stringify!($ty), "`.")]
+                fn $visit(&mut self, _: &$ty) -> VisitContents {
+                    VisitContents::Visit
+                }
+                #[doc = concat!("Called after visiting the contents of a `", stringify!($ty), "`.")]
+                fn $exit(&mut self, _: &$ty) {}
+            )*
+
+            /// Visit `x` and everything it contains.
+            fn walk<T: Drive + ?Sized>(&mut self, x: &T)
+            where
+                Self: Sized,
+            {
+                x.drive(&mut TypedVisitor {
+                    visitor: self,
+                    skip_depth: 0,
+                })
+            }
+        }
+
+        /// The mutable version of [AstVisitor].
+        pub trait AstVisitorMut {
+            $(
+                #[doc = concat!("Called before visiting the contents of a `", stringify!($ty), "`.")]
+                fn $visit(&mut self, _: &mut $ty) -> VisitContents {
+                    VisitContents::Visit
+                }
+                #[doc = concat!("Called after visiting the contents of a `", stringify!($ty), "`.")]
+                fn $exit(&mut self, _: &mut $ty) {}
+            )*
+
+            /// Visit `x` and everything it contains.
+            fn walk_mut<T: DriveMut + ?Sized>(&mut self, x: &mut T)
+            where
+                Self: Sized,
+            {
+                x.drive_mut(&mut TypedVisitorMut {
+                    visitor: self,
+                    skip_depth: 0,
+                })
+            }
+        }
+
+        /// Call the method of the visitor for this node, if it is of one of the visited types.
+        fn dispatch<V: AstVisitor>(visitor: &mut V, item: &dyn Any, event: Event) -> VisitContents {
+            $(
+                if let Some(x) = item.downcast_ref::<$ty>() {
+                    return match event {
+                        Event::Enter => visitor.$visit(x),
+                        Event::Exit => {
+                            visitor.$exit(x);
+                            VisitContents::Visit
+                        }
+                    };
+                }
+            )*
+            VisitContents::Visit
+        }
+
+        /// Call the method of the visitor for this node, if it is of one of the visited types.
+        fn dispatch_mut<V: AstVisitorMut>(
+            visitor: &mut V,
+            item: &mut dyn Any,
+            event: Event,
+        ) -> VisitContents {
+            $(
+                if let Some(x) = item.downcast_mut::<$ty>() {
+                    return match event {
+                        Event::Enter => visitor.$visit(x),
+                        Event::Exit => {
+                            visitor.$exit(x);
+                            VisitContents::Visit
+                        }
+                    };
+                }
+            )*
+            VisitContents::Visit
+        }
+    };
+}
+
+make_typed_visitors! {
+    // Types.
+    Ty => visit_ty, exit_ty;
+    Region => visit_region, exit_region;
+    ConstGeneric => visit_const_generic, exit_const_generic;
+    GenericArgs => visit_generic_args, exit_generic_args;
+    GenericParams => visit_generic_params, exit_generic_params;
+    TraitRef => visit_trait_ref, exit_trait_ref;
+    TraitDeclRef => visit_trait_decl_ref, exit_trait_decl_ref;
+    // Expressions.
+    Place => visit_place, exit_place;
+    Operand => visit_operand, exit_operand;
+    Rvalue => visit_rvalue, exit_rvalue;
+    ConstantExpr => visit_constant_expr, exit_constant_expr;
+    CastKind => visit_cast_kind, exit_cast_kind;
+    FnPtr => visit_fn_ptr, exit_fn_ptr;
+    Call => visit_call, exit_call;
+    // Bodies.
+    Body => visit_body, exit_body;
+    Var => visit_var, exit_var;
+    llbc_ast::Block => visit_llbc_block, exit_llbc_block;
+    llbc_ast::Statement => visit_llbc_statement, exit_llbc_statement;
+    ullbc_ast::BlockData => visit_block_data, exit_block_data;
+    ullbc_ast::Statement => visit_ullbc_statement, exit_ullbc_statement;
+    ullbc_ast::Terminator => visit_terminator, exit_terminator;
+    // Items.
+    TypeDecl => visit_type_decl, exit_type_decl;
+    FunDecl => visit_fun_decl, exit_fun_decl;
+    GlobalDecl => visit_global_decl, exit_global_decl;
+    TraitDecl => visit_trait_decl, exit_trait_decl;
+    TraitImpl => visit_trait_impl, exit_trait_impl;
+}
+
+/// Adapts an [AstVisitor] to the untyped events of [derive_visitor].
+struct TypedVisitor<'a, V> {
+    visitor: &'a mut V,
+    /// When we skip the contents of a node, the depth of the events we are ignoring. The node
+    /// itself has depth 1.
+    skip_depth: usize,
+}
+
+impl<V: AstVisitor> Visitor for TypedVisitor<'_, V> {
+    fn visit(&mut self, item: &dyn Any, event: Event) {
+        match event {
+            Event::Enter if self.skip_depth > 0 => self.skip_depth += 1,
+            Event::Enter => match dispatch(self.visitor, item, Event::Enter) {
+                VisitContents::Skip => self.skip_depth = 1,
+                VisitContents::Visit => {
+                    // The traversal doesn't enter the types by itself.
+                    if let Some(ty) = item.downcast_ref::<Ty>() {
+                        ty.drive_inner(self);
+                    }
+                }
+            },
+            Event::Exit => {
+                if self.skip_depth > 0 {
+                    self.skip_depth -= 1;
+                    if self.skip_depth > 0 {
+                        return;
+                    }
+                }
+                dispatch(self.visitor, item, Event::Exit);
+            }
+        }
+    }
+}
+
+/// Adapts an [AstVisitorMut] to the untyped events of [derive_visitor].
+struct TypedVisitorMut<'a, V> {
+    visitor: &'a mut V,
+    /// When we skip the contents of a node, the depth of the events we are ignoring. The node
+    /// itself has depth 1.
+    skip_depth: usize,
+}
+
+impl<V: AstVisitorMut> VisitorMut for TypedVisitorMut<'_, V> {
+    fn visit(&mut self, item: &mut dyn Any, event: Event) {
+        match event {
+            Event::Enter if self.skip_depth > 0 => self.skip_depth += 1,
+            Event::Enter => match dispatch_mut(self.visitor, item, Event::Enter) {
+                VisitContents::Skip => self.skip_depth = 1,
+                VisitContents::Visit => {
+                    // The traversal doesn't enter the types by itself.
+                    if let Some(ty) = item.downcast_mut::<Ty>() {
+                        ty.drive_inner_mut(self);
+                    }
+                }
+            },
+            Event::Exit => {
+                if self.skip_depth > 0 {
+                    self.skip_depth -= 1;
+                    if self.skip_depth > 0 {
+                        return;
+                    }
+                }
+                dispatch_mut(self.visitor, item, Event::Exit);
+            }
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use crate::translate::translate_crate_to_ullbc;
+use charon_lib::ast::{Span, TraitImplIndex};
 use charon_lib::export;
 use charon_lib::formatter::IntoFormatter;
+use charon_lib::import;
 use charon_lib::options;
-use charon_lib::reorder_decls::compute_reordered_decls;
-use charon_lib::transform::{LLBC_PASSES, ULLBC_PASSES};
+use charon_lib::reorder_decls::{compute_dependency_graph, compute_reordered_decls, find_cycles};
+use charon_lib::transform::ctx::TransformPass;
+use charon_lib::transform::{embed_sources, LLBC_PASSES, ULLBC_PASSES};
 use charon_lib::ullbc_to_llbc;
+use charon_lib::{tyck, validate};
 use regex::Regex;
 use rustc_driver::{Callbacks, Compilation};
 use rustc_interface::{interface::Compiler, Queries};
@@ -22,12 +26,18 @@ pub struct CharonCallbacks {
     /// The root of the toolchain.
     pub sysroot: PathBuf,
     pub error_count: usize,
+    /// Whether some errors made us lose more than bodies (see [charon_lib::errors::ErrorSummary]).
+    pub hard_errors: bool,
+    /// Whether we stopped translating because of `--max-errors`.
+    pub budget_exhausted: bool,
 }
 
 pub enum CharonFailure {
     RustcError(usize),
     Panic,
     Serialize,
+    /// Two translations of the crate gave different outputs (see `--deterministic`).
+    NotReproducible,
 }
 
 impl fmt::Display for CharonFailure {
@@ -38,6 +48,7 @@ impl fmt::Display for CharonFailure {
             }
             CharonFailure::Panic => write!(f, "Compilation panicked")?,
             CharonFailure::Serialize => write!(f, "Could not serialize output file")?,
+            CharonFailure::NotReproducible => write!(f, "The output is not reproducible")?,
         }
         Ok(())
     }
@@ -50,6 +61,8 @@ impl CharonCallbacks {
             crate_data: None,
             sysroot,
             error_count: 0,
+            hard_errors: false,
+            budget_exhausted: false,
         }
     }
 
@@ -236,7 +249,10 @@ pub fn translate(tcx: TyCtxt, internal: &mut CharonCallbacks) -> export::CrateDa
     // Run the micro-passes that clean up bodies.
     for pass in ULLBC_PASSES.iter() {
         trace!("# Starting pass {}", pass.name());
-        pass.run(&mut ctx)
+        let start = ctx.profiler.start();
+        pass.run(&mut ctx);
+        ctx.profiler
+            .record("pass", start, || pass.name().to_string());
     }
 
     let next_phase = if options.ullbc {
@@ -257,7 +273,10 @@ pub fn translate(tcx: TyCtxt, internal: &mut CharonCallbacks) -> export::CrateDa
     if !options.ullbc {
         // # Go from ULLBC to LLBC (Low-Level Borrow Calculus) by reconstructing
         // the control flow.
+        let start = ctx.profiler.start();
         ullbc_to_llbc::translate_functions(&mut ctx);
+        ctx.profiler
+            .record("phase", start, || "control-flow reconstruction".to_string());
 
         if options.print_built_llbc {
             info!("# LLBC resulting from control-flow reconstruction:\n\n{ctx}\n",);
@@ -266,7 +285,10 @@ pub fn translate(tcx: TyCtxt, internal: &mut CharonCallbacks) -> export::CrateDa
         // Run the micro-passes that clean up bodies.
         for pass in LLBC_PASSES.iter() {
             trace!("# Starting pass {}", pass.name());
-            pass.run(&mut ctx)
+            let start = ctx.profiler.start();
+            pass.run(&mut ctx);
+            ctx.profiler
+                .record("pass", start, || pass.name().to_string());
         }
 
         // # Reorder the graph of dependencies and compute the strictly
@@ -274,8 +296,25 @@ pub fn translate(tcx: TyCtxt, internal: &mut CharonCallbacks) -> export::CrateDa
         // - compute the order in which to extract the definitions
         // - find the recursive definitions
         // - group the mutually recursive definitions
-        let reordered_decls = compute_reordered_decls(&ctx);
+        let start = ctx.profiler.start();
+        let reordered_decls = compute_reordered_decls(&ctx.translated);
         ctx.translated.ordered_decls = Some(reordered_decls);
+        // Number the bodies in the same order, so they can be emitted along with their items.
+        ctx.translated.reorder_bodies();
+        // Also expose the underlying graph, for the consumers which do their own ordering.
+        ctx.translated.dependency_graph = Some(compute_dependency_graph(&ctx.translated));
+        ctx.profiler
+            .record("phase", start, || "reordering".to_string());
+        if options.report_cycles {
+            for cycle in find_cycles(&ctx.translated) {
+                let span = ctx
+                    .translated
+                    .get_item(cycle.edges[0].src)
+                    .map_or_else(Span::dummy, |item| item.item_meta().span);
+                ctx.errors
+                    .span_err_no_register(span, &cycle.report(&ctx.translated));
+            }
+        }
 
         if options.print_llbc {
             println!("# Final LLBC before serialization:\n\n{ctx}\n");
@@ -287,10 +326,49 @@ pub fn translate(tcx: TyCtxt, internal: &mut CharonCallbacks) -> export::CrateDa
         ctx.errors.report_external_deps_errors(ctx.into_fmt());
     }
 
+    ctx.compute_dedup_hashes();
+    // Merge the extractions given with `--link`, e.g. those of the standard library.
+    for path in &options.link_files {
+        match import::read_crate_data(path) {
+            Ok(crate_data) => ctx.translated.link(crate_data.translated),
+            Err(err) => {
+                let msg = format!("could not link `{}`: {err:#}", path.display());
+                ctx.errors.span_err(Span::dummy(), &msg);
+            }
+        }
+    }
+    ctx.translated.trait_impl_index = TraitImplIndex::new(&ctx.translated);
+
+    if options.check_invariants {
+        let mut errors = validate::validate_crate(&ctx.translated);
+        errors.extend(tyck::check_crate(&ctx.translated));
+        for error in errors {
+            let message = format!("Invariant violation: {}", error.message);
+            ctx.errors.span_err(error.span, &message);
+        }
+    }
+
+    // Drop the source text we don't want to embed. This must happen after all the passes, which
+    // may need it.
+    embed_sources::Transform.transform_ctx(&mut ctx);
+
     trace!("Done");
 
+    if let Some(path) = &options.profile_extraction {
+        if let Err(err) = ctx.profiler.write_chrome_trace(path) {
+            error!("Could not write the profile to {path:?}: {err}");
+        }
+    }
+
     // Update the error count
     internal.error_count = ctx.errors.error_count;
+    let summary = ctx.errors.summary();
+    internal.hard_errors = summary.has_hard_errors();
+    internal.budget_exhausted = summary.budget_exhausted;
+    if options.error_summary && ctx.errors.error_count > 0 {
+        // Not a log message: the default log level only shows errors, and the user asked for it.
+        eprint!("{}", summary.to_table(&ctx.into_fmt(), 10));
+    }
 
     export::CrateData::new(&ctx)
 }
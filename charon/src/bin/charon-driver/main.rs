@@ -8,6 +8,7 @@
 #![feature(iterator_try_collect)]
 #![feature(let_chains)]
 
+extern crate rustc_apfloat;
 extern crate rustc_ast;
 extern crate rustc_ast_pretty;
 extern crate rustc_attr;
@@ -18,8 +19,10 @@ extern crate rustc_hir;
 extern crate rustc_index;
 extern crate rustc_interface;
 extern crate rustc_middle;
+extern crate rustc_session;
 extern crate rustc_span;
 extern crate rustc_target;
+extern crate rustc_trait_selection;
 
 #[macro_use]
 extern crate charon_lib;
@@ -97,19 +100,15 @@ fn main() {
     if options.use_polonius {
         compiler_args.push("-Zpolonius".to_string());
     }
+    if options.keep_retags {
+        compiler_args.push("-Zmir-emit-retag".to_string());
+    }
 
     // Cargo calls the driver twice. The first call to the driver is with "--crate-name ___" and no
     // source file, for Cargo to retrieve some information about the crate.
     let is_dry_run = arg_values(&origin_args, "--crate-name")
         .find(|s| *s == "___")
         .is_some();
-    // When called using cargo, we tell cargo to use `charon-driver` by setting the
-    // `RUSTC_WORKSPACE_WRAPPER` env var. This uses `charon-driver` for all the crates in the
-    // workspace. We may however not want to be calling charon on all crates;
-    // `CARGO_PRIMARY_PACKAGE` tells us whether the crate was specifically selected or is a
-    // dependency.
-    let is_workspace_dependency = std::env::var("CHARON_USING_CARGO").is_ok()
-        && !std::env::var("CARGO_PRIMARY_PACKAGE").is_ok();
     // Determines if we are being invoked to build a crate for the "target" architecture, in
     // contrast to the "host" architecture. Host crates are for build scripts and proc macros and
     // still need to be built like normal; target crates need to be processed by Charon.
@@ -118,8 +117,30 @@ fn main() {
     // This matches what Miri does, which hopefully makes it reliable enough. This relies on us
     // always invoking cargo itself with `--target`, which `charon` ensures.
     let is_target = arg_values(&origin_args, "--target").next().is_some();
+    // With `--extract-dependencies`, we also translate the selected dependencies. Unlike the main
+    // crate, we must then also compile them normally, since the crates which depend on them need
+    // their metadata.
+    let is_extracted_dependency = is_target
+        && !is_dry_run
+        && arg_values(&origin_args, "--crate-name")
+            .next()
+            .is_some_and(|name| {
+                options
+                    .extract_dependencies
+                    .iter()
+                    .any(|dep| dep.replace('-', "_") == name)
+            });
+    // When called using cargo, we tell cargo to use `charon-driver` by setting the
+    // `RUSTC_WORKSPACE_WRAPPER` (or `RUSTC_WRAPPER`) env var. This uses `charon-driver` for all
+    // the crates in the workspace. We may however not want to be calling charon on all crates;
+    // `CARGO_PRIMARY_PACKAGE` tells us whether the crate was specifically selected or is a
+    // dependency.
+    let is_dependency = std::env::var("CHARON_USING_CARGO").is_ok()
+        && !std::env::var("CARGO_PRIMARY_PACKAGE").is_ok();
+    // The arguments with which to compile the dependency normally.
+    let normal_compiler_args = compiler_args.clone();
 
-    if is_dry_run || is_workspace_dependency || !is_target {
+    if is_dry_run || (is_dependency && !is_extracted_dependency) || !is_target {
         trace!("Skipping charon; running compiler normally instead.");
         // In this case we run the compiler normally.
         RunCompilerNormallyCallbacks
@@ -150,9 +171,11 @@ fn main() {
     // If such options are present, we need to update the arguments giving
     // the crate name and the source file.
 
-    // First replace the source name
+    // First replace the source name. This only concerns the main crate.
     let source_index = get_args_source_index(&compiler_args);
-    if let Some(source_index) = source_index {
+    if let Some(source_index) = source_index
+        && !is_extracted_dependency
+    {
         trace!("source ({}): {}", source_index, compiler_args[source_index]);
 
         if options.input_file.is_some() {
@@ -229,35 +252,62 @@ fn main() {
         "UnreachableEnumBranching",
         "UnreachablePropagation",
     ];
-    // Disable all these mir passes.
+    // Disable all these mir passes. We keep the inlining pass if it was requested.
     compiler_args.push(format!(
         "-Zmir-enable-passes={}",
         disabled_mir_passes
             .iter()
+            .filter(|p| **p != "Inline" || options.mir_inlining == options::MirInlining::Off)
             .map(|p| format!("-{p}"))
             .format(",")
     ));
+    compiler_args.extend(
+        options
+            .mir_inlining
+            .rustc_flags()
+            .iter()
+            .map(|flag| flag.to_string()),
+    );
 
     trace!("Compiler arguments: {:?}", compiler_args);
 
     // Call the Rust compiler with our custom callback.
-    let mut callback = CharonCallbacks::new(options, sysroot.into());
-    let mut res = callback.run_compiler(compiler_args);
+    let mut callback = CharonCallbacks::new(options, sysroot.clone().into());
+    let mut res = callback.run_compiler(compiler_args.clone());
     let CharonCallbacks {
         options,
         crate_data,
         error_count,
+        hard_errors,
+        budget_exhausted,
         ..
     } = callback;
 
+    if options.deterministic
+        && let Some(crate_data) = &crate_data
+    {
+        // Translate the crate a second time: the hash maps are seeded differently, so this catches
+        // the outputs which depend on their iteration order.
+        let mut second = CharonCallbacks::new(options.clone(), sysroot.into());
+        let _ = second.run_compiler(compiler_args);
+        let difference = match &second.crate_data {
+            Some(other) => first_difference(crate_data, other, options.export_profile),
+            None => Some("the second translation produced no output".to_string()),
+        };
+        if let Some(difference) = difference {
+            log::error!("{difference}");
+            res = res.and(Err(CharonFailure::NotReproducible));
+        }
+    }
+
     if !options.no_serialize {
         // # Final step: generate the files.
         if res.is_ok() || !options.error_on_warnings {
             // `crate_data` is set by our callbacks when there is no fatal error.
             if let Some(crate_data) = crate_data {
                 let dest_file = match options.dest_file.clone() {
-                    Some(f) => f,
-                    None => {
+                    Some(f) if !is_extracted_dependency => f,
+                    _ => {
                         let mut target_filename = options.dest_dir.clone().unwrap_or_default();
                         let crate_name = &crate_data.translated.crate_name;
                         let extension = if options.ullbc { "ullbc" } else { "llbc" };
@@ -268,13 +318,62 @@ fn main() {
                 trace!("Target file: {:?}", dest_file);
                 res = res.and(
                     crate_data
-                        .serialize_to_file(&dest_file)
+                        .serialize_to_file(&dest_file, options.export_profile)
                         .map_err(|()| CharonFailure::Serialize),
                 );
+                if options.per_module_output {
+                    let extension = if options.ullbc { "ullbc" } else { "llbc" };
+                    res = res.and(
+                        crate_data
+                            .serialize_per_module(
+                                &dest_file.with_extension("modules"),
+                                extension,
+                                options.export_profile,
+                            )
+                            .map_err(|()| CharonFailure::Serialize),
+                    );
+                }
+                if options.emit.contains(&options::EmitKind::Flat) {
+                    let program = charon_lib::lower::lower_crate(&crate_data.translated);
+                    res = res.and(
+                        program
+                            .serialize_to_file(&dest_file.with_extension("flat.json"))
+                            .map_err(|()| CharonFailure::Serialize),
+                    );
+                }
+                if options.emit.contains(&options::EmitKind::Metrics) {
+                    let metrics =
+                        charon_lib::export::metrics::CrateMetrics::compute(&crate_data.translated);
+                    res = res.and(
+                        metrics
+                            .serialize_to_file(&dest_file.with_extension("metrics.json"))
+                            .map_err(|()| CharonFailure::Serialize),
+                    );
+                }
+                if options.emit.contains(&options::EmitKind::SourceIndex) {
+                    let index = charon_lib::export::source_index::SourceIndex::compute(
+                        &crate_data.translated,
+                    );
+                    res = res.and(
+                        index
+                            .serialize_to_file(&dest_file.with_extension("source-index.json"))
+                            .map_err(|()| CharonFailure::Serialize),
+                    );
+                }
             }
         }
     }
 
+    if is_extracted_dependency {
+        trace!("Compiling the extracted dependency normally.");
+        if RunCompilerNormallyCallbacks
+            .run_compiler(normal_compiler_args)
+            .is_err()
+        {
+            std::process::exit(1);
+        }
+    }
+
     if options.error_on_warnings && matches!(res, Err(CharonFailure::Panic)) {
         // If we emitted any error, the call into rustc will panic. Hence we assume this is
         // just a normal failure.
@@ -295,9 +394,54 @@ fn main() {
             if matches!(err, CharonFailure::Panic) {
                 // This is a real panic, exit with the standard rust panic error code.
                 std::process::exit(101);
-            } else if options.error_on_warnings {
+            } else if matches!(err, CharonFailure::NotReproducible) {
                 std::process::exit(1);
+            } else if options.error_on_warnings {
+                // Distinguish the runs where the errors only made some bodies opaque.
+                let code = if error_count > 0 && !hard_errors {
+                    2
+                } else {
+                    1
+                };
+                std::process::exit(code);
             }
         }
     }
+    if budget_exhausted {
+        // We stopped translating early: the output is incomplete.
+        std::process::exit(1);
+    }
+}
+
+/// Compare the serialized outputs of two translations of the same crate, and describe the first
+/// difference if there is one.
+fn first_difference(
+    first: &charon_lib::export::CrateData,
+    second: &charon_lib::export::CrateData,
+    profile: options::ExportProfile,
+) -> Option<String> {
+    let serialize = |crate_data: &charon_lib::export::CrateData| {
+        let mut bytes = Vec::new();
+        crate_data
+            .serialize_to_writer(&mut bytes, profile)
+            .expect("could not serialize the crate");
+        bytes
+    };
+    let (first, second) = (serialize(first), serialize(second));
+    let offset = first
+        .iter()
+        .zip(&second)
+        .position(|(a, b)| a != b)
+        .or_else(|| (first.len() != second.len()).then(|| first.len().min(second.len())))?;
+    // Show some context around the difference.
+    let excerpt = |bytes: &[u8]| {
+        let start = offset.saturating_sub(40);
+        let end = (offset + 40).min(bytes.len());
+        String::from_utf8_lossy(&bytes[start..end]).into_owned()
+    };
+    Some(format!(
+        "The two translations differ at byte {offset}:\n  first:  ...{}...\n  second: ...{}...",
+        excerpt(&first),
+        excerpt(&second),
+    ))
 }
@@ -3,6 +3,7 @@
 use rustc_hir::def_id::DefId;
 use rustc_middle::mir::Body;
 use rustc_middle::ty::TyCtxt;
+use std::rc::Rc;
 
 use crate::translate::translate_ctx::MirLevel;
 
@@ -18,25 +19,28 @@ pub fn boxes_are_desugared(level: MirLevel) -> bool {
 
 /// Query the MIR for a function at a specific level. Return `None` in the case of a foreign body
 /// with no MIR available (e.g. because it is not available for inlining).
+///
+/// The body is shared, so that the hax state (which needs an `Rc`) and the translation can use it
+/// without cloning it again.
 pub fn get_mir_for_def_id_and_level(
     tcx: TyCtxt<'_>,
     def_id: DefId,
     level: MirLevel,
-) -> Option<Body<'_>> {
-    // Below: we **clone** the bodies to make sure we don't have issues with
+) -> Option<Rc<Body<'_>>> {
+    // Below: we **clone** the bodies (once) to make sure we don't have issues with
     // locked values (we had in the past).
     if let Some(local_def_id) = def_id.as_local() {
         match level {
             MirLevel::Built => {
                 let body = tcx.mir_built(local_def_id);
                 if !body.is_stolen() {
-                    return Some(body.borrow().clone());
+                    return Some(Rc::new(body.borrow().clone()));
                 }
             }
             MirLevel::Promoted => {
                 let (body, _) = tcx.mir_promoted(local_def_id);
                 if !body.is_stolen() {
-                    return Some(body.borrow().clone());
+                    return Some(Rc::new(body.borrow().clone()));
                 }
             }
             MirLevel::Optimized => {}
@@ -63,5 +67,5 @@ pub fn get_mir_for_def_id_and_level(
     } else {
         return None;
     };
-    Some(body)
+    Some(Rc::new(body))
 }
@@ -2,10 +2,12 @@
 use super::translate_ctx::*;
 use charon_lib::ast::*;
 use hax_frontend_exporter as hax;
+use rustc_hir::def_id::DefId;
 
 impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     fn translate_constant_literal_to_raw_constant_expr(
         &mut self,
+        span: Span,
         v: &hax::ConstantLiteral,
     ) -> Result<RawConstantExpr, Error> {
         let lit = match v {
@@ -42,14 +44,32 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 Literal::Scalar(scalar)
             }
             hax::ConstantLiteral::Float(value, float_type) => {
-                let value = value.clone();
-                let ty = match float_type {
-                    hax::FloatTy::F16 => FloatTy::F16,
-                    hax::FloatTy::F32 => FloatTy::F32,
-                    hax::FloatTy::F64 => FloatTy::F64,
-                    hax::FloatTy::F128 => FloatTy::F128,
+                use rustc_apfloat::ieee::{Double, Half, Quad, Single};
+                use rustc_apfloat::Float;
+                fn parse_bits<F: Float>(value: &str) -> Option<u128> {
+                    value.parse::<F>().ok().map(|f| f.to_bits())
+                }
+                // hax only gives us the decimal representation of the float, from which we
+                // recover its bits. This is exact for all the values but the NaNs, which all
+                // become the canonical NaN.
+                let (ty, bits) = match float_type {
+                    hax::FloatTy::F16 => (FloatTy::F16, parse_bits::<Half>(value)),
+                    hax::FloatTy::F32 => (FloatTy::F32, parse_bits::<Single>(value)),
+                    hax::FloatTy::F64 => (FloatTy::F64, parse_bits::<Double>(value)),
+                    hax::FloatTy::F128 => (FloatTy::F128, parse_bits::<Quad>(value)),
                 };
-                Literal::Float(FloatValue { value, ty })
+                let Some(bits) = bits else {
+                    error_or_panic!(
+                        self,
+                        span,
+                        format!("Could not parse float literal `{value}`")
+                    )
+                };
+                Literal::Float(FloatValue {
+                    value: value.clone(),
+                    bits,
+                    ty,
+                })
             }
         };
         Ok(RawConstantExpr::Literal(lit))
@@ -68,8 +88,18 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         use hax::ConstantExprKind;
         let ty = &v.ty;
         let value = match &(*v.contents) {
+            // C string literals are byte strings of type `&CStr`.
+            ConstantExprKind::Literal(hax::ConstantLiteral::ByteStr(bytes, _))
+                if self.is_cstr_ref(ty) =>
+            {
+                let mut bytes = bytes.clone();
+                if bytes.last() != Some(&0) {
+                    bytes.push(0);
+                }
+                RawConstantExpr::Literal(Literal::CStr(bytes))
+            }
             ConstantExprKind::Literal(lit) => {
-                self.translate_constant_literal_to_raw_constant_expr(lit)?
+                self.translate_constant_literal_to_raw_constant_expr(span, lit)?
             }
             ConstantExprKind::Adt { info, fields } => {
                 let fields: Vec<ConstantExpr> = fields
@@ -85,8 +115,12 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 };
                 RawConstantExpr::Adt(vid, fields)
             }
-            ConstantExprKind::Array { .. } => {
-                error_or_panic!(self, span, "array constants are not supported yet")
+            ConstantExprKind::Array { fields } => {
+                let fields: Vec<ConstantExpr> = fields
+                    .iter()
+                    .map(|f| self.translate_constant_expr_to_constant_expr(span, f))
+                    .try_collect()?;
+                RawConstantExpr::Array(fields)
             }
             ConstantExprKind::Tuple { fields } => {
                 let fields: Vec<ConstantExpr> = fields
@@ -186,7 +220,11 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         };
 
         let ty = self.translate_ty(span, ty)?;
-        Ok(ConstantExpr { value, ty })
+        Ok(ConstantExpr {
+            value,
+            ty,
+            has_type_suffix: None,
+        })
     }
 
     /// Remark: [hax::ConstantExpr] contains span information, but it is often
@@ -210,6 +248,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 Ok(ConstGeneric::Global(global_ref.id))
             }
             RawConstantExpr::Adt(..)
+            | RawConstantExpr::Array(..)
             | RawConstantExpr::TraitConst { .. }
             | RawConstantExpr::Ref(_)
             | RawConstantExpr::MutPtr(_)
@@ -231,6 +270,54 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         span: Span,
         v: &hax::Constant,
     ) -> Result<ConstantExpr, Error> {
-        self.translate_constant_expr_to_constant_expr(span, &v.const_.constant_kind)
+        let mut constant =
+            self.translate_constant_expr_to_constant_expr(span, &v.const_.constant_kind)?;
+        if let RawConstantExpr::Literal(Literal::Scalar(_)) = &constant.value {
+            constant.has_type_suffix = self.int_literal_has_type_suffix(&v.span);
+        }
+        Ok(constant)
+    }
+
+    /// Whether this is the type `&CStr`.
+    fn is_cstr_ref(&self, ty: &hax::Ty) -> bool {
+        if let hax::TyKind::Ref(_, ty, _) = ty.kind()
+            && let hax::TyKind::Adt { def_id, .. } = ty.kind()
+        {
+            let def_id = DefId::from(def_id);
+            self.t_ctx
+                .tcx
+                .is_diagnostic_item(rustc_span::sym::cstr_type, def_id)
+        } else {
+            false
+        }
+    }
+
+    /// Whether the integer literal at this span has a type suffix, like `1u32` or `-3_i8`. Returns
+    /// `None` if the span doesn't point to an integer literal, e.g. if the constant was computed by
+    /// rustc or comes from a macro expansion.
+    fn int_literal_has_type_suffix(&self, span: &hax::Span) -> Option<bool> {
+        const SUFFIXES: &[&str] = &[
+            "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+        ];
+        let span = span.rust_span_data?.span();
+        if span.is_dummy() || span.from_expansion() {
+            return None;
+        }
+        let snippet = self
+            .t_ctx
+            .tcx
+            .sess
+            .source_map()
+            .span_to_snippet(span)
+            .ok()?;
+        let literal = snippet.trim().trim_start_matches('-').trim_start();
+        let is_int_literal = literal.starts_with(|c: char| c.is_ascii_digit())
+            && literal
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !is_int_literal {
+            return None;
+        }
+        Some(SUFFIXES.iter().any(|suffix| literal.ends_with(suffix)))
     }
 }
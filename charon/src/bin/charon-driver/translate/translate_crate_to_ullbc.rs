@@ -1,9 +1,14 @@
 use super::translate_ctx::*;
 use charon_lib::ast::*;
+use charon_lib::formatter::IntoFormatter;
 use charon_lib::options::CliOpts;
+use charon_lib::pretty::FmtWithCtx;
+use charon_lib::profile::Profiler;
 use charon_lib::transform::ctx::TransformOptions;
 use charon_lib::transform::TransformCtx;
 use hax_frontend_exporter as hax;
+use itertools::Itertools;
+use rustc_hir::def::{DefKind, Res};
 use rustc_hir::def_id::DefId;
 use rustc_middle::ty::TyCtxt;
 use std::collections::{HashMap, HashSet};
@@ -15,6 +20,10 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
     fn register_local_item(&mut self, def_id: DefId) {
         use hax::FullDefKind;
         trace!("Registering {def_id:?}");
+        if !self.options.keep_main_shims && self.is_main_shim(def_id) {
+            trace!("Skipping the generated {def_id:?}");
+            return;
+        }
 
         let Ok(def) = self.hax_def(def_id) else {
             return; // Error has already been emitted
@@ -109,24 +118,201 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
         }
     }
 
+    /// Record the public paths under which the translated items are re-exported, by exploring the
+    /// public modules starting from the crate root. We do this once the items are translated so
+    /// that we know their ids.
+    fn record_reexports(&mut self) {
+        let tcx = self.tcx;
+        let crate_name = self.translated.real_crate_name.clone();
+        let root = Name {
+            name: vec![PathElem::Ident(crate_name, Disambiguator::ZERO)],
+        };
+        // The modules to explore, with their path, whether this path goes through a re-export,
+        // and the modules we went through (to avoid looping on modules which re-export their
+        // ancestors).
+        let crate_def_id = rustc_span::def_id::CRATE_DEF_ID.to_def_id();
+        let mut stack = vec![(crate_def_id, root, false, vec![crate_def_id])];
+        while let Some((mod_id, path, via_reexport, ancestors)) = stack.pop() {
+            let children = match mod_id.as_local() {
+                Some(local_id) => tcx.module_children_local(local_id),
+                None => tcx.module_children(mod_id),
+            };
+            for child in children {
+                if !child.vis.is_public() {
+                    continue;
+                }
+                let Some(def_id) = child.res.opt_def_id() else {
+                    continue;
+                };
+                let mut child_path = path.clone();
+                child_path.name.push(PathElem::Ident(
+                    child.ident.name.to_ident_string(),
+                    Disambiguator::ZERO,
+                ));
+                let via_reexport = via_reexport || !child.reexport_chain.is_empty();
+                if let Res::Def(DefKind::Mod, _) = child.res {
+                    if !ancestors.contains(&def_id) {
+                        let mut ancestors = ancestors.clone();
+                        ancestors.push(def_id);
+                        stack.push((def_id, child_path, via_reexport, ancestors));
+                    }
+                } else if via_reexport {
+                    if let Some(&id) = self.id_map.get(&def_id)
+                        && self.translated.item_names.get(&id) != Some(&child_path)
+                    {
+                        self.translated.reexports.push((child_path, id));
+                    }
+                }
+            }
+        }
+    }
+
+    /// The function where the execution of the program starts, if the crate is a binary. We don't
+    /// use `tcx.entry_fn` because it reports an error when the crate has no `main` function.
+    fn find_entry_fn(&self) -> Option<DefId> {
+        use rustc_session::config::CrateType;
+        use rustc_span::sym;
+        let tcx = self.tcx;
+        if !tcx.crate_types().contains(&CrateType::Executable) {
+            return None;
+        }
+        if let Some(def_id) = tcx
+            .hir()
+            .body_owners()
+            .find(|&id| tcx.has_attr(id, sym::start))
+        {
+            return Some(def_id.to_def_id());
+        }
+        if tcx
+            .hir()
+            .krate_attrs()
+            .iter()
+            .any(|attr| attr.has_name(sym::no_main))
+        {
+            return None;
+        }
+        let crate_def_id = rustc_span::def_id::CRATE_DEF_ID;
+        tcx.module_children_local(crate_def_id)
+            .iter()
+            .find_map(|child| match child.res {
+                Res::Def(DefKind::Fn, def_id) if child.ident.name == sym::main => Some(def_id),
+                _ => None,
+            })
+    }
+
+    /// Whether this is one of the items which the compiler generates for the test harness (with
+    /// `--test`): the `main` function which runs the tests and the constants which describe them.
+    fn is_main_shim(&self, def_id: DefId) -> bool {
+        use rustc_span::sym;
+        [sym::rustc_main, sym::rustc_test_marker]
+            .into_iter()
+            .any(|attr| self.tcx.has_attr(def_id, attr))
+    }
+
+    /// Record the entry points of the crate: the `main` or `#[start]` function, and the functions
+    /// designated with `--root` or `#[charon::root]`. We do this once the items are translated
+    /// so that we can match their names and attributes.
+    fn record_entry_points(&mut self) {
+        let mut entry_points = Vec::new();
+        if let Some(def_id) = self.find_entry_fn()
+            && let Some(AnyTransId::Fun(id)) = self.id_map.get(&def_id)
+        {
+            entry_points.push(*id);
+        }
+        for decl in self.translated.fun_decls.iter() {
+            let item_meta = &decl.item_meta;
+            let is_root = item_meta
+                .attr_info
+                .attributes
+                .iter()
+                .any(|attr| attr.is_root())
+                || self
+                    .options
+                    .roots
+                    .iter()
+                    .any(|pat| pat.matches(&self.translated, &item_meta.name));
+            if is_root && !entry_points.contains(&decl.def_id) {
+                entry_points.push(decl.def_id);
+            }
+        }
+        self.translated.entry_points = entry_points;
+    }
+
+    /// List the items which `--dependency-depth` made opaque.
+    fn report_cut_by_depth(&mut self) {
+        if self.cut_by_depth.is_empty() {
+            return;
+        }
+        let fmt_ctx = self.translated.into_fmt();
+        let names = self
+            .cut_by_depth
+            .iter()
+            .map(|name| format!("\n  {}", name.fmt_with_ctx(&fmt_ctx)))
+            .sorted()
+            .join("");
+        let msg = format!(
+            "{} items are beyond the dependency depth of {} (see `--dependency-depth`) and were \
+            translated as opaque:{names}",
+            self.cut_by_depth.len(),
+            self.options.dependency_depth.unwrap(),
+        );
+        self.errors.dcx.warn(msg);
+    }
+
+    /// List the functions marked as verification harnesses.
+    fn record_harnesses(&mut self) {
+        self.translated.harnesses = self
+            .translated
+            .fun_decls
+            .iter()
+            .filter(|decl| decl.item_meta.is_harness)
+            .map(|decl| decl.def_id)
+            .collect();
+    }
+
+    /// Record the `#[global_allocator]`, `#[panic_handler]` and `#[alloc_error_handler]` items
+    /// defined in the crate, and register them for translation.
+    fn record_runtime_handlers(&mut self) {
+        let tcx = self.tcx;
+        // `#[panic_handler]` defines a lang item.
+        if let Some(def_id) = tcx.lang_items().panic_impl()
+            && def_id.is_local()
+        {
+            let id = self.register_fun_decl_id(&None, def_id);
+            self.translated.runtime_handlers.panic_handler = Some(id);
+        }
+        // `#[global_allocator]` and `#[alloc_error_handler]` are macros which disappear after
+        // expansion: they generate functions with well-known names that call into the item the
+        // attribute was on.
+        if let Some(def_id) = find_shim_target(tcx, "__rust_alloc", |kind| {
+            matches!(kind, DefKind::Static { .. })
+        }) {
+            let id = self.register_global_decl_id(&None, def_id);
+            self.translated.runtime_handlers.global_allocator = Some(id);
+        }
+        if let Some(def_id) = find_shim_target(tcx, "__rg_oom", |kind| kind == DefKind::Fn) {
+            let id = self.register_fun_decl_id(&None, def_id);
+            self.translated.runtime_handlers.alloc_error_handler = Some(id);
+        }
+    }
+
     pub(crate) fn translate_item(&mut self, rust_id: DefId, trans_id: AnyTransId) {
         if self.errors.ignored_failed_decls.contains(&trans_id)
             || self.translated.get_item(trans_id).is_some()
         {
             return;
         }
+        let start = self.profiler.start();
         self.with_def_id(rust_id, trans_id, |mut ctx| {
             let span = ctx.def_span(rust_id);
             // Catch cycles
             let res = if ctx.translate_stack.contains(&trans_id) {
-                ctx.span_err(
-                    span,
-                    &format!(
-                        "Cycle detected while translating {rust_id:?}! Stack: {:?}",
-                        &ctx.translate_stack
-                    ),
+                let msg = format!(
+                    "Cycle detected while translating {rust_id:?}! Stack: {:?}",
+                    &ctx.translate_stack
                 );
-                Err(())
+                ctx.span_err(span, &msg);
+                Err((false, msg))
             } else {
                 ctx.translate_stack.push(trans_id);
 
@@ -138,15 +324,22 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
                 let res = match res {
                     Ok(Ok(())) => Ok(()),
                     // Translation error
-                    Ok(Err(_)) => Err(()),
+                    Ok(Err(e)) => Err((false, e.msg)),
                     // Panic
-                    Err(_) => {
+                    Err(payload) => {
                         register_error_or_panic!(
                             ctx,
                             span,
                             format!("Thread panicked when extracting item `{rust_id:?}`.")
                         );
-                        Err(())
+                        let msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+                            msg.to_string()
+                        } else if let Some(msg) = payload.downcast_ref::<String>() {
+                            msg.clone()
+                        } else {
+                            "unknown panic".to_string()
+                        };
+                        Err((true, msg))
                     }
                 };
                 // let res = ctx.translate_item_aux(rust_id, trans_id);
@@ -154,14 +347,23 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
                 res
             };
 
-            if res.is_err() {
+            if let Err((panicked, message)) = res {
                 ctx.span_err(
                     span,
                     &format!("Ignoring the following item due to a previous error: {rust_id:?}"),
                 );
                 ctx.errors.ignore_failed_decl(trans_id);
+                ctx.translated.failed_items.push(ItemFailure {
+                    id: trans_id,
+                    span,
+                    panicked,
+                    message,
+                });
             }
-        })
+        });
+        let tcx = self.tcx;
+        self.profiler
+            .record("item", start, || tcx.def_path_str(rust_id));
     }
 
     pub(crate) fn translate_item_aux(
@@ -223,6 +425,42 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
     }
 }
 
+/// Find the compiler-generated function named `shim_name` in the crate, and return the first local
+/// item its body refers to whose kind satisfies `is_target`.
+fn find_shim_target(
+    tcx: TyCtxt<'_>,
+    shim_name: &str,
+    is_target: impl Fn(DefKind) -> bool,
+) -> Option<DefId> {
+    use rustc_hir::intravisit::{self, Visitor};
+    struct FindTarget<F> {
+        is_target: F,
+        found: Option<DefId>,
+    }
+    impl<'tcx, F: Fn(DefKind) -> bool> Visitor<'tcx> for FindTarget<F> {
+        fn visit_path(&mut self, path: &rustc_hir::Path<'tcx>, _id: rustc_hir::HirId) {
+            if let Res::Def(kind, def_id) = path.res
+                && def_id.is_local()
+                && (self.is_target)(kind)
+                && self.found.is_none()
+            {
+                self.found = Some(def_id);
+            }
+            intravisit::walk_path(self, path)
+        }
+    }
+
+    let shim = tcx.hir().body_owners().find(|&id| {
+        tcx.def_kind(id) == DefKind::Fn && tcx.item_name(id.to_def_id()).as_str() == shim_name
+    })?;
+    let mut visitor = FindTarget {
+        is_target,
+        found: None,
+    };
+    visitor.visit_body(tcx.hir().body_owned_by(shim));
+    visitor.found
+}
+
 #[tracing::instrument(skip(tcx))]
 pub fn translate<'tcx, 'ctx>(
     options: &CliOpts,
@@ -258,6 +496,9 @@ pub fn translate<'tcx, 'ctx>(
         def_id: None,
         def_id_is_local: false,
         error_count: 0,
+        item_error_counts: HashMap::new(),
+        max_errors: options.max_errors,
+        budget_exhausted: false,
     };
     let translate_options = TranslateOptions::new(&mut error_ctx, options);
     let mut ctx = TranslateCtx {
@@ -269,6 +510,7 @@ pub fn translate<'tcx, 'ctx>(
         translated: TranslatedCrate {
             crate_name: requested_crate_name,
             real_crate_name,
+            mir_inlining: options.mir_inlining,
             ..TranslatedCrate::default()
         },
         id_map: Default::default(),
@@ -276,13 +518,18 @@ pub fn translate<'tcx, 'ctx>(
         items_to_translate: Default::default(),
         translate_stack: Default::default(),
         cached_names: Default::default(),
+        dependency_depths: Default::default(),
+        cut_by_depth: Default::default(),
+        profiler: Profiler::new(options.profile_extraction.is_some()),
     };
 
     // Recursively register all the items in the crate, starting from the crate root. We could
     // instead ask rustc for the plain list of all items in the crate, but we wouldn't be able to
     // skip items inside modules annotated with `#[charon::opaque]`.
+    let start = ctx.profiler.start();
     let crate_def_id = rustc_span::def_id::CRATE_DEF_ID.to_def_id();
     ctx.register_local_item(crate_def_id);
+    ctx.record_runtime_handlers();
 
     trace!(
         "Queue after we explored the crate:\n{:?}",
@@ -299,21 +546,57 @@ pub fn translate<'tcx, 'ctx>(
     // we never need to lookup a translated definition, and only use the map
     // from Rust ids to translated ids.
     while let Some((ord_id, trans_id)) = ctx.items_to_translate.pop_first() {
+        if ctx.errors.is_budget_exhausted() {
+            let msg = format!(
+                "Stopping the translation after {} errors (see `--max-errors`)",
+                ctx.errors.error_count
+            );
+            ctx.errors.span_err_no_register(Span::dummy(), &msg);
+            ctx.errors.budget_exhausted = true;
+            break;
+        }
         trace!("About to translate id: {:?}", ord_id);
         ctx.translate_item(ord_id.get_id(), trans_id);
     }
+    ctx.profiler
+        .record("phase", start, || "translation".to_string());
+
+    ctx.report_cut_by_depth();
+    ctx.record_reexports();
+    ctx.record_entry_points();
+    ctx.record_harnesses();
 
     // Return the context, dropping the hax state and rustc `tcx`.
     let transform_options = TransformOptions {
         no_code_duplication: options.no_code_duplication,
         hide_marker_traits: options.hide_marker_traits,
+        instantiate_blanket_impls: options.instantiate_blanket_impls,
         no_merge_goto_chains: options.no_merge_goto_chains,
+        reconstruct_matches: options.reconstruct_matches,
+        reconstruct_try: options.reconstruct_try,
+        extended_asserts: options.extended_asserts,
+        preserve_discriminant_reads: options.preserve_discriminant_reads,
+        simplify_constants: options.simplify_constants,
+        max_duplication: options.max_duplication,
+        semi_structured: options.semi_structured,
+        borrow_liveness: options.borrow_liveness,
+        move_info: options.move_info,
+        statement_ranges: options.statement_ranges,
+        comment_annotations: options.comment_annotations,
+        annotate_recursive_fields: options.annotate_recursive_fields,
         item_opacities: ctx.options.item_opacities,
+        embed_sources: options.embed_sources,
+        model_files: options.model_files.clone(),
+        opaque_derives: options.opaque_derives.clone(),
+        builtin_derives: options.builtin_derives.clone(),
+        devirtualize_closure_calls: options.devirtualize_closure_calls,
     };
 
     TransformCtx {
         options: transform_options,
         translated: ctx.translated,
         errors: ctx.errors,
+        generated_items: Default::default(),
+        profiler: ctx.profiler,
     }
 }
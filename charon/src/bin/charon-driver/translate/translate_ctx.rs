@@ -6,13 +6,15 @@ use charon_lib::formatter::{FmtCtx, IntoFormatter};
 use charon_lib::ids::{MapGenerator, Vector};
 use charon_lib::name_matcher::NamePattern;
 use charon_lib::options::CliOpts;
+use charon_lib::profile::Profiler;
 use charon_lib::ullbc_ast as ast;
 use hax_frontend_exporter::SInto;
 use hax_frontend_exporter::{self as hax, DefPathItem};
 use itertools::Itertools;
 use macros::VariantIndexArity;
-use rustc_hir::def_id::DefId;
+use rustc_hir::def_id::{CrateNum, DefId, LOCAL_CRATE};
 use rustc_middle::ty::TyCtxt;
+use rustc_span::hygiene::{ExpnKind, MacroKind};
 use std::borrow::Cow;
 use std::cmp::Ord;
 use std::collections::HashMap;
@@ -22,6 +24,7 @@ use std::fmt::Debug;
 use std::mem;
 use std::path::{Component, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 // Re-export to avoid having to fix imports.
 pub(crate) use charon_lib::errors::{
@@ -41,14 +44,54 @@ pub enum MirLevel {
     Optimized,
 }
 
+/// The items of the standard library which wrap `main` (see `--keep-main-shims`).
+const MAIN_SHIMS: &[&str] = &[
+    "std::rt::lang_start",
+    "std::rt::lang_start_internal",
+    "std::process::Termination",
+    "std::process::{impl std::process::Termination for _}",
+    "test::test_main_static",
+    "test::assert_test_result",
+];
+
 /// The options that control translation.
 pub struct TranslateOptions {
     /// The level at which to extract the MIR
     pub mir_level: MirLevel,
+    /// Whether to translate the debug info of the bodies.
+    pub debug_info: bool,
     /// List of patterns to assign a given opacity to. For each name, the most specific pattern that
     /// matches determines the opacity of the item. When no options are provided this is initialized
     /// to treat items in the crate as transparent and items in other crates as foreign.
     pub item_opacities: Vec<(NamePattern, ItemOpacity)>,
+    /// The patterns given with `--root`, which designate entry points of the crate.
+    pub roots: Vec<NamePattern>,
+    /// The paths of the attributes which mark a function as a verification harness.
+    pub harness_attrs: Vec<String>,
+    /// Whether to give names to the locals introduced by the compiler.
+    pub name_temporaries: bool,
+    /// Whether to translate the `Retag` statements.
+    pub keep_retags: bool,
+    /// Whether to skip the translation of all the bodies.
+    pub types_only: bool,
+    /// The patterns given with `--signatures-with-bodies-for`: only the items which match one of
+    /// these get their bodies translated.
+    pub bodies_for: Vec<NamePattern>,
+    /// The bodies with more MIR statements than this are translated as opaque.
+    pub max_body_statements: Option<usize>,
+    /// The time after which we give up on the translation of a body.
+    pub per_item_timeout: Option<Duration>,
+    /// The foreign items which are more than this number of hops away from the items of the crate
+    /// are translated as opaque.
+    pub dependency_depth: Option<usize>,
+    /// Whether to translate the operations of the `async` runtime as builtins.
+    pub async_builtins: bool,
+    /// Whether to translate the items which the compiler generates around `main`.
+    pub keep_main_shims: bool,
+    /// Whether to append the versions of the dependencies to the names of their items.
+    pub crate_versions_in_names: bool,
+    /// Whether to parse the annotations in the comments before the items.
+    pub comment_annotations: bool,
 }
 
 impl TranslateOptions {
@@ -83,6 +126,11 @@ impl TranslateOptions {
             // We always include the items from the crate.
             opacities.push(("crate".to_owned(), Transparent));
 
+            // Package names may contain dashes, but crate names use underscores.
+            for krate in options.extract_bodies_from.iter() {
+                opacities.push((krate.replace('-', "_"), Transparent));
+            }
+
             for pat in options.include.iter() {
                 opacities.push((pat.to_string(), Transparent));
             }
@@ -93,6 +141,14 @@ impl TranslateOptions {
                 opacities.push((pat.to_string(), Invisible));
             }
 
+            // The runtime functions which call `main` and report its result. They are only
+            // reachable from code generated by the compiler.
+            if !options.keep_main_shims {
+                for pat in MAIN_SHIMS {
+                    opacities.push((pat.to_string(), Invisible));
+                }
+            }
+
             // We always hide this trait.
             opacities.push((format!("core::alloc::Allocator"), Invisible));
             opacities.push((
@@ -106,9 +162,47 @@ impl TranslateOptions {
                 .collect()
         };
 
+        let roots = options
+            .roots
+            .iter()
+            .filter_map(|s| parse_pattern(s).ok())
+            .collect();
+
+        let bodies_for = options
+            .bodies_for
+            .iter()
+            .filter_map(|s| parse_pattern(s).ok())
+            .collect();
+
+        let mut harness_attrs: Vec<String> = [
+            "kani::proof",
+            // What `#[kani::proof]` expands to when compiling with kani.
+            "kanitool::proof",
+            "quickcheck",
+            "quickcheck_macros::quickcheck",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        harness_attrs.extend(options.harness_attrs.iter().cloned());
+
         TranslateOptions {
             mir_level,
+            debug_info: options.debug_info,
             item_opacities,
+            roots,
+            harness_attrs,
+            name_temporaries: options.name_temporaries,
+            keep_retags: options.keep_retags,
+            types_only: options.emit_types_only,
+            bodies_for,
+            max_body_statements: options.max_body_statements,
+            per_item_timeout: options.per_item_timeout.map(Duration::from_secs),
+            dependency_depth: options.dependency_depth,
+            async_builtins: options.async_builtins,
+            keep_main_shims: options.keep_main_shims,
+            crate_versions_in_names: options.crate_versions_in_names,
+            comment_annotations: options.comment_annotations,
         }
     }
 }
@@ -186,6 +280,14 @@ pub struct TranslateCtx<'tcx, 'ctx> {
     pub translate_stack: Vec<AnyTransId>,
     /// Cache the names to compute them only once each.
     pub cached_names: HashMap<DefId, Name>,
+    /// The smallest number of hops of foreign items between the items of the crate and each
+    /// registered item (see `--dependency-depth`). Only computed when that option is set.
+    pub dependency_depths: HashMap<DefId, usize>,
+    /// The items which we translated as opaque because they are too far from the crate.
+    pub cut_by_depth: Vec<Name>,
+    /// Records the time spent in the hax export and in the translation of each item (see
+    /// `--profile-extraction`).
+    pub profiler: Profiler,
 }
 
 /// A translation context for type/global/function bodies.
@@ -272,6 +374,15 @@ pub(crate) struct BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     /// writing the translation functions as recursive functions. We do
     /// so because we had stack overflows in the past.
     pub blocks_stack: VecDeque<hax::BasicBlock>,
+    /// The statements of the body which use a promoted constant, identified by their block and
+    /// their index in the block, with the global we translated the promoted constant to.
+    pub promoted_uses: HashMap<(usize, usize), ast::GlobalDeclRef>,
+    /// The layouts of the types of the transmutes of the body, identified like
+    /// [Self::promoted_uses].
+    pub transmute_layouts: HashMap<(usize, usize), ast::TransmuteLayouts>,
+    /// The time after which we give up on the translation of the body (see
+    /// `--per-item-timeout`).
+    pub deadline: Option<Instant>,
 }
 
 /// Translates `T` into `U` using `hax`'s `SInto` trait, catching any hax panics.
@@ -285,6 +396,18 @@ where
         .or_else(|_| error_or_panic!(err, span, format!("Hax panicked when translating `{x:?}`.")))
 }
 
+/// The outermost of the macros we recognize (see [MacroOrigin]) that this span was expanded from,
+/// if any.
+fn translate_macro_origin(span: &hax::Span) -> Option<MacroOrigin> {
+    let span = span.rust_span_data?.span();
+    span.macro_backtrace()
+        .filter_map(|expn| match expn.kind {
+            ExpnKind::Macro(MacroKind::Bang, name) => MacroOrigin::from_macro_name(name.as_str()),
+            _ => None,
+        })
+        .last()
+}
+
 impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
     pub fn continue_on_failure(&self) -> bool {
         self.errors.continue_on_failure()
@@ -327,7 +450,12 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
             DefPathItem::CrateRoot { name, .. } => {
                 // Sanity check
                 error_assert!(self, span, path_elem.disambiguator == 0);
-                Some(PathElem::Ident(name.clone(), disambiguator))
+                let name = if self.options.crate_versions_in_names {
+                    self.crate_root_ident(def.to_rust_def_id().krate)
+                } else {
+                    name.clone()
+                };
+                Some(PathElem::Ident(name, disambiguator))
             }
             // We map the three namespaces onto a single one. We can always disambiguate by looking
             // at the definition.
@@ -352,9 +480,26 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
                         ImplElem::Ty(generics, ty)
                     }
                     // Trait implementation
-                    hax::FullDefKind::TraitImpl { .. } => {
+                    hax::FullDefKind::TraitImpl { trait_pred, .. } => {
                         let impl_id = self.register_trait_impl_id(&None, def_id);
-                        ImplElem::Trait(impl_id)
+                        // We record the implemented trait (which includes the self type) so
+                        // that the name is self-describing.
+                        let mut bt_ctx = BodyTransCtx::new(def_id, None, self);
+                        let generics = bt_ctx.translate_def_generics(span, &full_def)?;
+                        let trait_ref = &trait_pred.trait_ref;
+                        let trait_id = bt_ctx.register_trait_decl_id(span, &trait_ref.def_id);
+                        let (regions, types, const_generics) =
+                            bt_ctx.translate_substs(span, None, &trait_ref.generic_args)?;
+                        let impl_trait = TraitDeclRef {
+                            trait_id,
+                            generics: GenericArgs {
+                                regions,
+                                types,
+                                const_generics,
+                                trait_refs: Vector::new(),
+                            },
+                        };
+                        ImplElem::Trait(impl_id, generics, impl_trait)
                     }
                     _ => unreachable!(),
                 };
@@ -367,6 +512,11 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
             // we should be able to extract closures in local let-bindings
             // (i.e., we shouldn't have to introduce top-level let-bindings).
             DefPathItem::Closure => Some(PathElem::Ident("closure".to_string(), disambiguator)),
+            // Anonymous constants (e.g. array lengths) and inline `const { .. }` blocks. We
+            // translate them as globals, so we need to give them a name.
+            DefPathItem::AnonConst => {
+                Some(PathElem::Ident("{constant}".to_string(), disambiguator))
+            }
             // Do nothing, functions in `extern` blocks are in the same namespace as the
             // block.
             DefPathItem::ForeignMod => None,
@@ -465,8 +615,14 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
     pub fn hax_def(&mut self, def_id: impl Into<DefId>) -> Result<Arc<hax::FullDef>, Error> {
         let def_id: DefId = def_id.into();
         let span = self.def_span(def_id);
+        let start = self.profiler.start();
         // Hax takes care of caching the translation.
-        catch_sinto(&self.hax_state, &mut self.errors, span, &def_id)
+        let def = catch_sinto(&self.hax_state, &mut self.errors, span, &def_id);
+        let tcx = self.tcx;
+        self.profiler.record("hax", start, || {
+            format!("hax::{}", tcx.def_path_str(def_id))
+        });
+        def
     }
 
     pub(crate) fn translate_attr_info(&mut self, def: &hax::FullDef) -> AttrInfo {
@@ -512,26 +668,239 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
         let span = self.translate_span_from_hax(span);
         let attr_info = self.translate_attr_info(def);
         let is_local = def.def_id.is_local;
+        let visibility = self.translate_visibility(def);
+        let stability = self
+            .tcx
+            .lookup_stability(def.rust_def_id())
+            .map(|stab| Stability {
+                feature: stab.feature.to_string(),
+                unstable: stab.level.is_unstable(),
+            });
 
         let opacity = if self.is_extern_item(def)
             || attr_info.attributes.iter().any(|attr| attr.is_opaque())
         {
             // Force opaque in these cases.
             ItemOpacity::Opaque.max(opacity)
+        } else if self.is_beyond_dependency_depth(def.rust_def_id()) && !opacity.is_invisible() {
+            self.cut_by_depth.push(name.clone());
+            ItemOpacity::Opaque.max(opacity)
         } else {
             opacity
         };
 
+        let is_fn = matches!(
+            def.kind(),
+            hax::FullDefKind::Fn { .. } | hax::FullDefKind::AssocFn { .. }
+        );
+        let is_harness = is_fn
+            && attr_info.attributes.iter().any(|attr| {
+                attr.as_unknown().is_some_and(|raw| {
+                    self.options
+                        .harness_attrs
+                        .iter()
+                        .any(|path| *path == raw.path)
+                })
+            });
+
         ItemMeta {
             name,
             span,
             source_text: def.source_text.clone(),
             attr_info,
             is_local,
+            visibility,
+            stability,
+            is_harness,
+            codegen_attrs: self.translate_codegen_attrs(def.rust_def_id()),
+            provenance: self.translate_provenance(def.rust_def_id()),
+            dedup_hash: None,
+            annotations: self.translate_item_annotations(def.rust_def_id()),
             opacity,
         }
     }
 
+    /// With `--comment-annotations`, parse the annotations in the comments right before a local
+    /// item. We skip the attributes which may come between the comments and the item.
+    fn translate_item_annotations(&self, def_id: DefId) -> Vec<Annotation> {
+        if !self.options.comment_annotations || !def_id.is_local() {
+            return Vec::new();
+        }
+        let span = self.tcx.def_span(def_id);
+        let Ok(before) = self.tcx.sess.source_map().span_to_prev_source(span) else {
+            return Vec::new();
+        };
+        let mut lines = before.lines().rev();
+        // The beginning of the line of the item must be blank (this excludes e.g. closures).
+        if !before.ends_with('\n') && lines.next().is_some_and(|line| !line.trim().is_empty()) {
+            return Vec::new();
+        }
+        let mut comments = lines
+            .map(str::trim)
+            .take_while(|line| line.starts_with("//") || line.starts_with("#["))
+            .filter_map(|line| line.strip_prefix("///").or(line.strip_prefix("//")))
+            .collect_vec();
+        comments.reverse();
+        Annotation::parse_all(comments)
+    }
+
+    fn translate_visibility(&mut self, def: &hax::FullDef) -> Visibility {
+        // Impl blocks and closures have no visibility.
+        if def.visibility.is_none() {
+            return Visibility::Private;
+        }
+        match self.tcx.visibility(def.rust_def_id()) {
+            rustc_middle::ty::Visibility::Public => Visibility::Public,
+            rustc_middle::ty::Visibility::Restricted(mod_id) if mod_id.is_crate_root() => {
+                Visibility::Crate
+            }
+            rustc_middle::ty::Visibility::Restricted(_) => Visibility::Private,
+        }
+    }
+
+    /// Read the `#[no_mangle]`, `#[export_name]` and `#[linkage]` attributes of a function or
+    /// static.
+    pub(crate) fn translate_linkage(&self, def_id: DefId) -> Linkage {
+        use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
+        // Constants have no codegen attributes, and the query panics on them.
+        if !self.tcx.def_kind(def_id).has_codegen_attrs() {
+            return Linkage::default();
+        }
+        let attrs = self.tcx.codegen_fn_attrs(def_id);
+        let no_mangle = attrs.flags.contains(CodegenFnAttrFlags::NO_MANGLE);
+        let export_name = match attrs.export_name {
+            Some(name) => Some(name.to_string()),
+            None if no_mangle => Some(self.tcx.item_name(def_id).to_string()),
+            None => None,
+        };
+        let (link_name, foreign_abi) = if self.tcx.is_foreign_item(def_id) {
+            let link_name = attrs
+                .link_name
+                .unwrap_or_else(|| self.tcx.item_name(def_id));
+            let foreign_abi = self
+                .tcx
+                .foreign_modules(def_id.krate)
+                .get(&self.tcx.parent(def_id))
+                .map(|module| module.abi.name().to_string());
+            (Some(link_name.to_string()), foreign_abi)
+        } else {
+            (None, None)
+        };
+        Linkage {
+            export_name,
+            no_mangle,
+            kind: attrs.linkage.map(|linkage| format!("{linkage:?}")),
+            link_name,
+            foreign_abi,
+        }
+    }
+
+    /// Read the `#[used]`, `#[link_section]`, `#[cold]`, `#[track_caller]`, `#[naked]` and
+    /// `#[target_feature]` attributes of an item.
+    pub(crate) fn translate_codegen_attrs(&self, def_id: DefId) -> CodegenAttrs {
+        use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
+        // Some items have no codegen attributes, and the query panics on them.
+        if !self.tcx.def_kind(def_id).has_codegen_attrs() {
+            return CodegenAttrs::default();
+        }
+        let attrs = self.tcx.codegen_fn_attrs(def_id);
+        CodegenAttrs {
+            used: attrs
+                .flags
+                .intersects(CodegenFnAttrFlags::USED | CodegenFnAttrFlags::USED_LINKER),
+            link_section: attrs.link_section.map(|section| section.to_string()),
+            cold: attrs.flags.contains(CodegenFnAttrFlags::COLD),
+            track_caller: attrs.flags.contains(CodegenFnAttrFlags::TRACK_CALLER),
+            naked: attrs.flags.contains(CodegenFnAttrFlags::NAKED),
+            target_features: attrs
+                .target_features
+                .iter()
+                .map(|feature| feature.name.to_string())
+                .collect(),
+        }
+    }
+
+    /// The first element of the names of the items of a crate with `--crate-versions-in-names`:
+    /// the name of the crate, followed by its version for the dependencies (e.g. `rand@0.8.5`).
+    /// If we don't know the version of a dependency and another crate has the same name, we use
+    /// the hash which rustc computes to tell the crates apart instead.
+    fn crate_root_ident(&self, krate: CrateNum) -> String {
+        let crate_name = self.tcx.crate_name(krate);
+        if krate == LOCAL_CRATE {
+            return crate_name.to_string();
+        }
+        if let Some(version) = self.crate_version(krate) {
+            return format!("{crate_name}@{version}");
+        }
+        let homonyms = self
+            .tcx
+            .crates(())
+            .iter()
+            .filter(|&&other| self.tcx.crate_name(other) == crate_name)
+            .count();
+        if homonyms > 1 {
+            let hash = self.tcx.stable_crate_id(krate).as_u64();
+            format!("{crate_name}@{hash:016x}")
+        } else {
+            crate_name.to_string()
+        }
+    }
+
+    /// The version of a crate, when we know it.
+    fn crate_version(&self, krate: CrateNum) -> Option<String> {
+        let crate_name = self.tcx.crate_name(krate).to_string();
+        if krate == LOCAL_CRATE {
+            // Cargo tells rustc which crate it compiles and its version.
+            std::env::var("CARGO_CRATE_NAME")
+                .is_ok_and(|name| name == crate_name)
+                .then(|| std::env::var("CARGO_PKG_VERSION").ok())
+                .flatten()
+        } else {
+            // The sources of the dependencies are in a `<name>-<version>` directory.
+            let root_span = self.tcx.def_span(krate.as_def_id());
+            match self.tcx.sess.source_map().span_to_filename(root_span) {
+                rustc_span::FileName::Real(name) => name
+                    .local_path_if_available()
+                    .components()
+                    .filter_map(|component| component.as_os_str().to_str())
+                    .filter_map(|dir| {
+                        // Package names may use dashes where crate names use underscores.
+                        let name = dir.get(..crate_name.len())?;
+                        let version = dir.get(crate_name.len()..)?.strip_prefix('-')?;
+                        (name.replace('-', "_") == crate_name).then_some(version)
+                    })
+                    .find(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+                    .map(str::to_string),
+                _ => None,
+            }
+        }
+    }
+
+    /// Find the crate which defines an item, its version, and whether the MIR of the item is
+    /// available.
+    pub(crate) fn translate_provenance(&self, def_id: DefId) -> Provenance {
+        use rustc_hir::def::DefKind;
+        let crate_name = self.tcx.crate_name(def_id.krate).to_string();
+        let crate_version = self.crate_version(def_id.krate);
+        let has_body = matches!(
+            self.tcx.def_kind(def_id),
+            DefKind::Fn
+                | DefKind::AssocFn
+                | DefKind::Closure
+                | DefKind::Const
+                | DefKind::AssocConst
+                | DefKind::AnonConst
+                | DefKind::InlineConst
+                | DefKind::Static { .. }
+        );
+        Provenance {
+            crate_name,
+            crate_version,
+            body_available: has_body
+                && (self.tcx.is_mir_available(def_id) || self.tcx.is_ctfe_mir_available(def_id)),
+        }
+    }
+
     pub fn translate_filename(&mut self, name: &hax::FileName) -> meta::FileName {
         match name {
             hax::FileName::Real(name) => {
@@ -634,6 +1003,7 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
     ) -> Span {
         // Translate the span
         let span = self.translate_raw_span(&source_info.span);
+        let macro_origin = translate_macro_origin(&source_info.span);
 
         // Lookup the top-most inlined parent scope.
         let mut parent_span = None;
@@ -648,11 +1018,13 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
             Span {
                 span: parent_span,
                 generated_from_span: Some(span),
+                macro_origin,
             }
         } else {
             Span {
                 span,
                 generated_from_span: None,
+                macro_origin,
             }
         }
     }
@@ -661,6 +1033,7 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
         Span {
             span: self.translate_raw_span(span),
             generated_from_span: None,
+            macro_origin: None,
         }
     }
 
@@ -743,6 +1116,19 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
         *opacity
     }
 
+    /// Whether to translate the body of the item with this name (see `--emit-types-only` and
+    /// `--signatures-with-bodies-for`).
+    pub(crate) fn wants_body(&self, name: &Name) -> bool {
+        let options = &self.options;
+        if !options.types_only && options.bodies_for.is_empty() {
+            return true;
+        }
+        options
+            .bodies_for
+            .iter()
+            .any(|pat| pat.matches(&self.translated, name))
+    }
+
     /// Register the fact that `id` is a dependency of `src` (if `src` is not `None`).
     pub(crate) fn register_dep_source(
         &mut self,
@@ -803,9 +1189,40 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
             }
         };
         self.register_dep_source(src, rust_id, item_id);
+        if self.options.dependency_depth.is_some() {
+            self.record_dependency_depth(src, rust_id);
+        }
         item_id
     }
 
+    /// Record the number of hops of foreign items between the items of the crate and this item:
+    /// local items are at depth 0, and a foreign item is one hop further than the item which
+    /// refers to it. We keep the shortest path.
+    fn record_dependency_depth(&mut self, src: &Option<DepSource>, def_id: DefId) {
+        let depth = if def_id.is_local() {
+            0
+        } else {
+            let src_depth = src
+                .as_ref()
+                .and_then(|src| self.reverse_id_map.get(&src.src_id))
+                .and_then(|src_id| self.dependency_depths.get(src_id))
+                .copied()
+                .unwrap_or(0);
+            src_depth + 1
+        };
+        let entry = self.dependency_depths.entry(def_id).or_insert(depth);
+        *entry = (*entry).min(depth);
+    }
+
+    /// Whether the item is further from the crate than allowed by `--dependency-depth`.
+    pub(crate) fn is_beyond_dependency_depth(&self, def_id: DefId) -> bool {
+        self.options.dependency_depth.is_some_and(|max_depth| {
+            self.dependency_depths
+                .get(&def_id)
+                .is_some_and(|depth| *depth > max_depth)
+        })
+    }
+
     pub(crate) fn register_type_decl_id(
         &mut self,
         src: &Option<DepSource>,
@@ -910,6 +1327,9 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             blocks: Default::default(),
             blocks_map: Default::default(),
             blocks_stack: Default::default(),
+            promoted_uses: Default::default(),
+            transmute_layouts: Default::default(),
+            deadline: None,
         }
     }
 
@@ -1152,6 +1572,7 @@ impl<'tcx, 'ctx, 'ctx1, 'a> IntoFormatter for &'a BodyTransCtx<'tcx, 'ctx, 'ctx1
             translated: Some(&self.t_ctx.translated),
             generics,
             locals: Some(&self.vars),
+            options: Default::default(),
         }
     }
 }
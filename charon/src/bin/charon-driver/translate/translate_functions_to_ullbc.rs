@@ -3,9 +3,11 @@
 //! us to handle, and easier to maintain - rustc's representation can evolve
 //! independently.
 
+use std::collections::{HashMap, HashSet};
 use std::mem;
 use std::panic;
 use std::rc::Rc;
+use std::time::Instant;
 
 use super::get_mir::{boxes_are_desugared, get_mir_for_def_id_and_level};
 use super::translate_ctx::*;
@@ -16,7 +18,7 @@ use charon_lib::ids::Vector;
 use charon_lib::pretty::FmtWithCtx;
 use charon_lib::ullbc_ast::*;
 use hax_frontend_exporter as hax;
-use hax_frontend_exporter::{HasMirSetter, HasOwnerIdSetter};
+use hax_frontend_exporter::{HasMirSetter, HasOwnerIdSetter, SInto};
 use itertools::Itertools;
 use rustc_hir::def_id::DefId;
 use rustc_middle::mir::START_BLOCK;
@@ -160,15 +162,64 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
     }
 }
 
+/// Generate a name for a local the user didn't name, based on why the compiler introduced it and
+/// on its index, e.g. `deref_temp_5`. We add underscores until the name doesn't clash with the
+/// name of a user variable.
+fn temporary_name(
+    rust_body: &rustc_middle::mir::Body<'_>,
+    index: usize,
+    user_names: &HashSet<&str>,
+) -> String {
+    use rustc_index::Idx;
+    use rustc_middle::mir::{ClearCrossCrate, Local, LocalInfo};
+    let prefix = if index <= rust_body.arg_count {
+        "arg"
+    } else {
+        match &rust_body.local_decls[Local::from_usize(index)].local_info {
+            ClearCrossCrate::Set(info) => match **info {
+                LocalInfo::User(..) => "binding",
+                LocalInfo::StaticRef { .. } => "static_ref",
+                LocalInfo::ConstRef { .. } => "const_ref",
+                LocalInfo::AggregateTemp => "aggregate_temp",
+                LocalInfo::BlockTailTemp(..) => "tail_temp",
+                LocalInfo::IfThenRescopeTemp { .. } => "if_then_temp",
+                LocalInfo::DerefTemp => "deref_temp",
+                LocalInfo::FakeBorrow => "fake_borrow",
+                LocalInfo::Boring => "tmp",
+            },
+            ClearCrossCrate::Clear => "tmp",
+        }
+    };
+    let mut name = format!("{prefix}_{index}");
+    while user_names.contains(name.as_str()) {
+        name.push('_');
+    }
+    name
+}
+
 impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     /// Translate a function's local variables by adding them in the environment.
-    fn translate_body_locals(&mut self, body: &hax::MirBody<()>) -> Result<(), Error> {
+    fn translate_body_locals(
+        &mut self,
+        body: &hax::MirBody<()>,
+        rust_body: &rustc_middle::mir::Body<'_>,
+    ) -> Result<(), Error> {
+        let user_names: HashSet<&str> = body
+            .local_decls
+            .raw
+            .iter()
+            .filter_map(|var| var.name.as_deref())
+            .collect();
+
         // Translate the parameters
         for (index, var) in body.local_decls.raw.iter().enumerate() {
             trace!("Translating local of index {} and type {:?}", index, var.ty);
 
             // Find the name of the variable
-            let name: Option<String> = var.name.clone();
+            let mut name: Option<String> = var.name.clone();
+            if name.is_none() && index != 0 && self.t_ctx.options.name_temporaries {
+                name = Some(temporary_name(rust_body, index, &user_names));
+            }
 
             // Translate the type
             let span = self.translate_span_from_hax(&var.source_info.span);
@@ -181,6 +232,77 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         Ok(())
     }
 
+    /// Translate the debug info of a body, if requested: its lexical scopes and its user
+    /// variables. We skip the variables which live in places we can't express with a
+    /// [DebugProjectionElem].
+    fn translate_debug_info(
+        &mut self,
+        body: &rustc_middle::mir::Body<'_>,
+    ) -> Option<BodyDebugInfo> {
+        use rustc_index::Idx;
+        if !self.t_ctx.options.debug_info {
+            return None;
+        }
+        use rustc_middle::mir::{PlaceElem, ProjectionElem, VarDebugInfoContents};
+        let translate_projection = |projection: &[PlaceElem<'_>]| {
+            projection
+                .iter()
+                .map(|elem| match elem {
+                    ProjectionElem::Deref => Some(DebugProjectionElem::Deref),
+                    ProjectionElem::Field(field, _) => {
+                        Some(DebugProjectionElem::Field(FieldId::new(field.index())))
+                    }
+                    ProjectionElem::Downcast(_, variant) => Some(DebugProjectionElem::Downcast(
+                        VariantId::new(variant.index()),
+                    )),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+        };
+
+        let mut debug_info = BodyDebugInfo::default();
+        for scope in body.source_scopes.iter() {
+            let span = scope.span.sinto(&self.hax_state);
+            debug_info.scopes.push(DebugScope {
+                span: self.translate_span_from_hax(&span),
+                parent: scope
+                    .parent_scope
+                    .map(|parent| ScopeId::new(parent.index())),
+            });
+        }
+        for var in &body.var_debug_info {
+            let value = match &var.value {
+                VarDebugInfoContents::Place(place) => {
+                    let Some(var_id) = self.vars_map.get(&place.local.index()).copied() else {
+                        continue;
+                    };
+                    let Some(projection) = translate_projection(&place.projection[..]) else {
+                        continue;
+                    };
+                    DebugValue::Place(var_id, projection)
+                }
+                VarDebugInfoContents::Const(_) => DebugValue::Const,
+            };
+            let fragment = match &var.composite {
+                None => None,
+                Some(fragment) => match translate_projection(&fragment.projection) {
+                    Some(projection) => Some(projection),
+                    None => continue,
+                },
+            };
+            let span = var.source_info.span.sinto(&self.hax_state);
+            debug_info.vars.push(VarDebugInfo {
+                name: var.name.to_string(),
+                span: self.translate_span_from_hax(&span),
+                scope: ScopeId::new(var.source_info.scope.index()),
+                fragment,
+                value,
+                argument_index: var.argument_index.map(usize::from),
+            });
+        }
+        Some(debug_info)
+    }
+
     /// Translate an expression's body (either a function or a global).
     ///
     /// The local variables should already have been translated and inserted in
@@ -195,14 +317,23 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         let id = self.translate_basic_block_id(rustc_index::Idx::new(START_BLOCK.as_usize()));
         assert!(id == START_BLOCK_ID);
 
-        // For as long as there are blocks in the stack, translate them
+        // For as long as there are blocks in the stack, translate them. We stop early if we ran
+        // out of time (see `--per-item-timeout`); the caller checks it.
         while let Some(block_id) = self.blocks_stack.pop_front() {
+            if self.is_past_deadline() {
+                break;
+            }
             self.translate_basic_block(body, block_id)?;
         }
 
         Ok(())
     }
 
+    fn is_past_deadline(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() > deadline)
+    }
+
     /// Translate a basic block id and register it, if it hasn't been done.
     fn translate_basic_block_id(&mut self, block_id: hax::BasicBlock) -> BlockId {
         match self.blocks_map.get(&block_id) {
@@ -227,11 +358,39 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
 
         // Translate the statements
         let mut statements = Vec::new();
-        for statement in &block.statements {
+        for (i, statement) in block.statements.iter().enumerate() {
             trace!("statement: {:?}", statement);
+            // Give up on the block if we ran out of time (see `--per-item-timeout`): the caller
+            // checks the deadline and discards the whole body.
+            if self.is_past_deadline() {
+                return Ok(());
+            }
 
             // Some statements might be ignored, hence the optional returned value
-            let opt_statement = self.translate_statement(body, statement)?;
+            let promoted_use = {
+                use rustc_index::Idx;
+                self.promoted_uses.get(&(block_id.index(), i)).cloned()
+            };
+            let mut opt_statement = match promoted_use {
+                Some(global_ref) => Some(self.translate_promoted_use(body, statement, global_ref)?),
+                None => self.translate_statement(body, statement)?,
+            };
+            let transmute_layouts = {
+                use rustc_index::Idx;
+                self.transmute_layouts.get(&(block_id.index(), i)).cloned()
+            };
+            if let Some(layouts) = transmute_layouts
+                && let Some(Statement {
+                    content:
+                        RawStatement::Assign(
+                            _,
+                            Rvalue::UnaryOp(UnOp::Cast(CastKind::Transmute(_, _, l)), _),
+                        ),
+                    ..
+                }) = &mut opt_statement
+            {
+                *l = layouts;
+            }
             if let Some(statement) = opt_statement {
                 statements.push(statement)
             }
@@ -514,12 +673,18 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     | hax::CastKind::PointerCoercion(hax::PointerCoercion::MutToConstPointer, ..)
                     | hax::CastKind::PointerCoercion(hax::PointerCoercion::ArrayToPointer, ..)
                     | hax::CastKind::PointerCoercion(hax::PointerCoercion::DynStar, ..)
-                    | hax::CastKind::FnPtrToPtr
-                    | hax::CastKind::PointerExposeProvenance
-                    | hax::CastKind::PointerWithExposedProvenance => Ok(Rvalue::UnaryOp(
+                    | hax::CastKind::FnPtrToPtr => Ok(Rvalue::UnaryOp(
                         UnOp::Cast(CastKind::RawPtr(src_ty, tgt_ty)),
                         operand,
                     )),
+                    hax::CastKind::PointerExposeProvenance => Ok(Rvalue::UnaryOp(
+                        UnOp::Cast(CastKind::ExposeProvenance(src_ty, tgt_ty)),
+                        operand,
+                    )),
+                    hax::CastKind::PointerWithExposedProvenance => Ok(Rvalue::UnaryOp(
+                        UnOp::Cast(CastKind::WithExposedProvenance(src_ty, tgt_ty)),
+                        operand,
+                    )),
                     hax::CastKind::PointerCoercion(
                         hax::PointerCoercion::ClosureFnPointer(_)
                         | hax::PointerCoercion::UnsafeFnPointer
@@ -530,7 +695,13 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                         operand,
                     )),
                     hax::CastKind::Transmute => Ok(Rvalue::UnaryOp(
-                        UnOp::Cast(CastKind::Transmute(src_ty, tgt_ty)),
+                        // The layouts are filled in by `translate_basic_block`, see
+                        // [BodyTransCtx::transmute_layouts].
+                        UnOp::Cast(CastKind::Transmute(
+                            src_ty,
+                            tgt_ty,
+                            TransmuteLayouts::default(),
+                        )),
                         operand,
                     )),
                     hax::CastKind::PointerCoercion(hax::PointerCoercion::Unsize, ..) => {
@@ -568,11 +739,17 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     }
                 }
             }
-            hax::Rvalue::BinaryOp(binop, (left, right)) => Ok(Rvalue::BinaryOp(
-                self.t_ctx.translate_binaryop_kind(span, *binop)?,
-                self.translate_operand(span, left)?,
-                self.translate_operand(span, right)?,
-            )),
+            hax::Rvalue::BinaryOp(binop, (left, right)) => {
+                let binop = self.t_ctx.translate_binaryop_kind(span, *binop)?;
+                let (left, lhs_ty) = self.translate_operand_with_type(span, left)?;
+                let (right, rhs_ty) = self.translate_operand_with_type(span, right)?;
+                Ok(Rvalue::BinaryOp(
+                    binop,
+                    left,
+                    right,
+                    BinOpTys { lhs_ty, rhs_ty },
+                ))
+            }
             hax::Rvalue::NullaryOp(nullop, ty) => {
                 trace!("NullOp: {:?}", nullop);
                 let ty = self.translate_ty(span, ty)?;
@@ -735,6 +912,10 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             || panic_names.iter().any(|panic| name.equals_ref_name(panic))
         {
             Ok(Some(BuiltinFun::Panic))
+        } else if self.t_ctx.options.async_builtins
+            && let Some(op) = recognize_async_builtin(&self.t_ctx.translated, &name)
+        {
+            Ok(Some(BuiltinFun::Async(op)))
         } else {
             Ok(None)
         }
@@ -768,7 +949,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         }
 
         // Translate the type parameters
-        let generics = self.translate_substs_and_trait_refs(span, None, substs, trait_refs)?;
+        let mut generics = self.translate_substs_and_trait_refs(span, None, substs, trait_refs)?;
 
         // Translate the arguments
         let args = args
@@ -799,9 +980,16 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             // is translated to:
             // `box_deref<T>`
             // (the type parameter is not `Box<T>` but `T`).
-            assert!(trait_info.is_none());
-
             let aid = builtin_fun.to_ullbc_builtin_fun();
+            // `Future::poll` is the only builtin trait method: we keep the trait reference, which
+            // gives the implementation of the future.
+            if aid == BuiltinFunId::Async(AsyncBuiltin::FuturePoll) {
+                let trait_info = trait_info.as_ref().unwrap();
+                let impl_expr = self.translate_trait_impl_expr(span, trait_info)?;
+                generics.trait_refs.push(impl_expr);
+            } else {
+                assert!(trait_info.is_none());
+            }
 
             // Note that some functions are actually traits (deref, index, etc.):
             // we assume that they are called only on a limited set of types
@@ -815,7 +1003,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             // We have to retrieve the type `Box<u32>` and check that it is of the
             // form `Box<T>` (and we generate `box_deref<u32>`).
             match aid {
-                BuiltinFunId::BoxNew => {
+                BuiltinFunId::BoxNew | BuiltinFunId::Async(_) => {
                     // Nothing to do
                 }
                 BuiltinFunId::Index { .. }
@@ -907,13 +1095,27 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 Some(RawStatement::Assert(Assert {
                     cond: op,
                     expected: true,
+                    message: Vec::new(),
                 }))
             }
             StatementKind::Intrinsic(hax::NonDivergingIntrinsic::CopyNonOverlapping(..)) => {
                 error_or_panic!(self, span, "Unsupported statement kind: CopyNonOverlapping");
             }
-            // This is for the stacked borrows memory model.
-            StatementKind::Retag(_, _) => None,
+            // This is for the stacked borrows memory model. We only keep these on demand.
+            StatementKind::Retag(kind, place) => {
+                if self.t_ctx.options.keep_retags {
+                    let kind = match kind {
+                        hax::RetagKind::FnEntry => RetagKind::FnEntry,
+                        hax::RetagKind::TwoPhase => RetagKind::TwoPhase,
+                        hax::RetagKind::Raw => RetagKind::Raw,
+                        hax::RetagKind::Default => RetagKind::Default,
+                    };
+                    let t_place = self.translate_place(span, place)?;
+                    Some(RawStatement::Retag(kind, t_place))
+                } else {
+                    None
+                }
+            }
             // There are user-provided type annotations with no semantic effect (since we get a
             // fully-typechecked MIR (TODO: this isn't quite true with opaque types, we should
             // really use promoted MIR)).
@@ -930,6 +1132,32 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         Ok(t_statement.map(|kind| Statement::new(span, kind)))
     }
 
+    /// Translate a statement `_x = const <promoted>` which uses a promoted constant, as a read of
+    /// the global we translated the promoted constant to.
+    fn translate_promoted_use(
+        &mut self,
+        body: &hax::MirBody<()>,
+        statement: &hax::Statement,
+        global_ref: GlobalDeclRef,
+    ) -> Result<Statement, Error> {
+        let span = self
+            .t_ctx
+            .translate_span_from_source_info(&body.source_scopes, &statement.source_info);
+        let hax::StatementKind::Assign((place, _)) = &*statement.kind else {
+            unreachable!()
+        };
+        let (place, ty) = self.translate_place_with_type(span, place)?;
+        let constant = ConstantExpr {
+            value: RawConstantExpr::Global(global_ref),
+            ty,
+            has_type_suffix: None,
+        };
+        Ok(Statement::new(
+            span,
+            RawStatement::Assign(place, Rvalue::Use(Operand::Const(constant))),
+        ))
+    }
+
     /// Translate a terminator
     fn translate_terminator(
         &mut self,
@@ -979,10 +1207,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 replace: _,
             } => {
                 let place = self.translate_place(span, place)?;
-                statements.push(Statement {
-                    span,
-                    content: RawStatement::Drop(place),
-                });
+                statements.push(Statement::new(span, RawStatement::Drop(place)));
                 let target = self.translate_basic_block_id(*target);
                 RawTerminator::Goto { target }
             }
@@ -1018,11 +1243,9 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 let assert = Assert {
                     cond: self.translate_operand(span, cond)?,
                     expected: *expected,
+                    message: Vec::new(),
                 };
-                statements.push(Statement {
-                    span,
-                    content: RawStatement::Assert(assert),
-                });
+                statements.push(Statement::new(span, RawStatement::Assert(assert)));
                 let target = self.translate_basic_block_id(*target);
                 RawTerminator::Goto { target }
             }
@@ -1123,7 +1346,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         // are using a local function pointer (i.e., the operand is a "move").
         let lval = self.translate_place(span, destination)?;
         let next_block = target.map(|target| self.translate_basic_block_id(target));
-        let (fn_operand, args) = match fun {
+        let (fn_operand, args, fn_ptr_sig) = match fun {
             hax::FunOperand::Id(def_id) => {
                 // Translate the function operand - should be a constant: we don't
                 // support closures for now
@@ -1150,14 +1373,26 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     SubstFunIdOrPanic::Fun(fid) => {
                         let fn_operand = FnOperand::Regular(fid.func);
                         let args = fid.args.unwrap();
-                        (fn_operand, args)
+                        (fn_operand, args, None)
                     }
                 }
             }
-            hax::FunOperand::Move(p) => {
+            hax::FunOperand::Move(fn_ptr) => {
                 // Call to a local function pointer
                 // The function
-                let p = self.translate_place(span, p)?;
+                let (p, ty) = self.translate_place_with_type(span, p)?;
+                let fn_ptr_sig = match (ty.kind(), fn_ptr.ty.kind()) {
+                    (TyKind::Arrow(regions, inputs, output), hax::TyKind::Arrow(sig)) => {
+                        Some(FnPtrSig {
+                            is_unsafe: matches!(sig.value.safety, hax::Safety::Unsafe),
+                            abi: format!("{:?}", sig.value.abi),
+                            regions: regions.clone(),
+                            inputs: inputs.clone(),
+                            output: output.clone(),
+                        })
+                    }
+                    _ => None,
+                };
 
                 // TODO: we may have a problem here because as we don't
                 // know which function is being called, we may not be
@@ -1167,18 +1402,16 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 // for instance.
                 let args = self.translate_arguments(span, args)?;
                 let fn_operand = FnOperand::Move(p);
-                (fn_operand, args)
+                (fn_operand, args, fn_ptr_sig)
             }
         };
         let call = Call {
             func: fn_operand,
             args,
             dest: lval,
+            fn_ptr_sig,
         };
-        statements.push(Statement {
-            span,
-            content: RawStatement::Call(call),
-        });
+        statements.push(Statement::new(span, RawStatement::Call(call)));
         Ok(match next_block {
             Some(target) => RawTerminator::Goto { target },
             None => RawTerminator::Abort(AbortKind::UndefinedBehavior),
@@ -1244,7 +1477,9 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     }
 
     /// Translate a function body if we can (it has MIR) and we want to (we don't translate bodies
-    /// declared opaque, and only translate non-local bodies if `extract_opaque_bodies` is set).
+    /// declared opaque, only translate non-local bodies if `extract_opaque_bodies` is set or their
+    /// crate is listed in `extract_bodies_from`, and only translate the bodies selected by
+    /// `--emit-types-only` and `--signatures-with-bodies-for`).
     fn translate_body(
         &mut self,
         def: &hax::FullDef,
@@ -1278,32 +1513,89 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             // The bodies of foreign functions are opaque by default.
             return Ok(Err(Opaque));
         }
+        if !self.t_ctx.wants_body(&item_meta.name) {
+            return Ok(Err(Opaque));
+        }
 
         // Retrieve the body
         let rust_id = def.rust_def_id();
-        let Some(body) =
+        let Some(rust_body) =
             get_mir_for_def_id_and_level(self.t_ctx.tcx, rust_id, self.t_ctx.options.mir_level)
         else {
             return Ok(Err(Opaque));
         };
 
-        // Here, we have to create a MIR state, which contains the body
-        // Yes, we have to clone, this is annoying: we end up cloning the body twice
+        // Skip the bodies which are too big (see `--max-body-statements`).
+        if let Some(max) = self.t_ctx.options.max_body_statements {
+            let num_statements: usize = rust_body
+                .basic_blocks
+                .iter()
+                .map(|block| block.statements.len())
+                .sum();
+            if num_statements > max {
+                let name = item_meta.name.fmt_with_ctx(&self.into_fmt());
+                self.t_ctx.errors.dcx.span_warn(
+                    item_meta.span,
+                    format!(
+                        "The body of `{name}` has {num_statements} statements, more than the \
+                        limit of {max} (see `--max-body-statements`); it is translated as opaque"
+                    ),
+                );
+                return Ok(Err(Opaque));
+            }
+        }
+
+        // Translate the promoted constants of the body to separate globals.
+        self.promoted_uses = self.translate_promoted_consts(rust_id, &rust_body, item_meta)?;
+        self.transmute_layouts = self.compute_transmute_layouts(rust_id, &rust_body);
+
+        // The time limit doesn't apply to the promoted constants, which we translated above.
+        self.deadline = self
+            .t_ctx
+            .options
+            .per_item_timeout
+            .map(|timeout| Instant::now() + timeout);
+
+        // Here, we have to create a MIR state, which contains the body. The body is shared, so
+        // this doesn't clone it.
         let state = self
             .hax_state
             .clone()
             .with_owner_id(rust_id)
-            .with_mir(Rc::new(body.clone()));
+            .with_mir(rust_body.clone());
         // Translate
-        let body: hax::MirBody<()> = self.t_ctx.catch_sinto(&state, item_meta.span, &body)?;
+        let start = self.t_ctx.profiler.start();
+        let body: hax::MirBody<()> = self
+            .t_ctx
+            .catch_sinto(&state, item_meta.span, &*rust_body)?;
+        let tcx = self.t_ctx.tcx;
+        self.t_ctx.profiler.record("hax", start, || {
+            format!("hax::body::{}", tcx.def_path_str(rust_id))
+        });
 
         // Initialize the local variables
         trace!("Translating the body locals");
-        self.translate_body_locals(&body)?;
+        self.translate_body_locals(&body, &rust_body)?;
+
+        // Translate the debug info, which refers to the locals
+        trace!("Translating the debug info");
+        let debug_info = self.translate_debug_info(&rust_body);
 
         // Translate the expression body
         trace!("Translating the expression body");
         self.translate_transparent_expression_body(&body)?;
+        if self.is_past_deadline() {
+            let name = item_meta.name.fmt_with_ctx(&self.into_fmt());
+            let timeout = self.t_ctx.options.per_item_timeout.unwrap().as_secs();
+            self.t_ctx.errors.dcx.span_warn(
+                item_meta.span,
+                format!(
+                    "The translation of the body of `{name}` took more than {timeout} seconds \
+                    (see `--per-item-timeout`); it is translated as opaque"
+                ),
+            );
+            return Ok(Err(Opaque));
+        }
 
         // Compute the span information
         let span = self.translate_span_from_hax(&body.span);
@@ -1323,10 +1615,225 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             arg_count,
             locals: mem::take(&mut self.vars),
             comments: self.translate_body_comments(def, span),
+            debug_info,
             body: blocks,
         })))
     }
 
+    /// With `--mir_promoted`, translate the promoted constants (the constants that rustc extracts
+    /// from a body, e.g. the `[1, 2, 3]` in `&[1, 2, 3]`) used by the given body to globals named
+    /// `<item name>::{promoted}#<index>`, which have the generics of the item. Returns the
+    /// statements of the body which use these constants (see [BodyTransCtx::promoted_uses]).
+    ///
+    /// This must be called before translating the body itself, as it uses the body translation
+    /// state.
+    fn translate_promoted_consts(
+        &mut self,
+        rust_id: DefId,
+        rust_body: &rustc_middle::mir::Body<'_>,
+        item_meta: &ItemMeta,
+    ) -> Result<HashMap<(usize, usize), GlobalDeclRef>, Error> {
+        use rustc_index::Idx;
+        use rustc_middle::mir;
+        let mut promoted_uses = HashMap::new();
+        if self.t_ctx.options.mir_level != MirLevel::Promoted {
+            return Ok(promoted_uses);
+        }
+        let Some(local_id) = rust_id.as_local() else {
+            return Ok(promoted_uses);
+        };
+        let (_, promoted) = self.t_ctx.tcx.mir_promoted(local_id);
+        if promoted.is_stolen() {
+            return Ok(promoted_uses);
+        }
+
+        // Find the statements `_x = const <promoted>`.
+        let mut uses: Vec<((usize, usize), mir::Promoted)> = Vec::new();
+        for (block_id, block) in rust_body.basic_blocks.iter_enumerated() {
+            for (i, statement) in block.statements.iter().enumerate() {
+                if let mir::StatementKind::Assign(assign) = &statement.kind
+                    && let (_, mir::Rvalue::Use(mir::Operand::Constant(constant))) = &**assign
+                    && let mir::Const::Unevaluated(uv, _) = constant.const_
+                    && uv.def == rust_id
+                    && let Some(promoted_id) = uv.promoted
+                {
+                    uses.push(((block_id.index(), i), promoted_id));
+                }
+            }
+        }
+        if uses.is_empty() {
+            return Ok(promoted_uses);
+        }
+        // Only clone the bodies of the promoted constants we use, once each.
+        let promoted_bodies: HashMap<mir::Promoted, Rc<mir::Body<'tcx>>> = {
+            let promoted = promoted.borrow();
+            uses.iter()
+                .map(|(_, promoted_id)| (*promoted_id, Rc::new(promoted[*promoted_id].clone())))
+                .collect()
+        };
+
+        // The promoted constants have the generics of the item. The bodies use erased regions.
+        let mut generics = self.generic_params.clone();
+        generics.regions = self.region_vars[0].clone();
+        let generic_args = GenericArgs {
+            regions: generics.regions.iter().map(|_| Region::Erased).collect(),
+            ..generics.identity_args()
+        };
+
+        let mut promoted_globals: HashMap<mir::Promoted, GlobalDeclId> = HashMap::new();
+        for (location, promoted_id) in uses {
+            let global_id = match promoted_globals.get(&promoted_id) {
+                Some(global_id) => *global_id,
+                None => {
+                    let global_id = self.translate_promoted_const(
+                        rust_id,
+                        &promoted_bodies[&promoted_id],
+                        promoted_id.index(),
+                        item_meta,
+                        &generics,
+                    )?;
+                    promoted_globals.insert(promoted_id, global_id);
+                    global_id
+                }
+            };
+            let global_ref = GlobalDeclRef {
+                id: global_id,
+                generics: generic_args.clone(),
+            };
+            promoted_uses.insert(location, global_ref);
+        }
+        Ok(promoted_uses)
+    }
+
+    /// Compute the layouts of the types of the transmutes of the body, which we record in the
+    /// casts (see [BodyTransCtx::transmute_layouts]). We warn about the transmutes which change
+    /// the size of the value, or whose layouts we can't compute.
+    fn compute_transmute_layouts(
+        &mut self,
+        rust_id: DefId,
+        rust_body: &rustc_middle::mir::Body<'tcx>,
+    ) -> HashMap<(usize, usize), TransmuteLayouts> {
+        use rustc_index::Idx;
+        use rustc_middle::mir;
+        let tcx = self.t_ctx.tcx;
+        let param_env = tcx.param_env(rust_id);
+        let layout_of = |ty: rustc_middle::ty::Ty<'tcx>| {
+            let layout = tcx.layout_of(param_env.and(ty)).ok()?;
+            Some(Layout {
+                size: layout.size.bytes(),
+                align: layout.align.abi.bytes(),
+                has_niche: layout.largest_niche.is_some(),
+            })
+        };
+        let mut transmute_layouts = HashMap::new();
+        for (block_id, block) in rust_body.basic_blocks.iter_enumerated() {
+            for (i, statement) in block.statements.iter().enumerate() {
+                if let mir::StatementKind::Assign(assign) = &statement.kind
+                    && let (_, mir::Rvalue::Cast(mir::CastKind::Transmute, op, tgt_ty)) = &**assign
+                {
+                    let src_ty = op.ty(rust_body, tcx);
+                    let layouts = TransmuteLayouts {
+                        src: layout_of(src_ty),
+                        tgt: layout_of(*tgt_ty),
+                    };
+                    let span = statement.source_info.span;
+                    match (&layouts.src, &layouts.tgt) {
+                        (Some(src), Some(tgt)) if src.size != tgt.size => {
+                            self.t_ctx.errors.dcx.span_warn(
+                                span,
+                                format!(
+                                "This transmute changes the size of the value from {} to {} bytes",
+                                src.size, tgt.size
+                            ),
+                            )
+                        }
+                        (Some(_), Some(_)) => {}
+                        _ => self.t_ctx.errors.dcx.span_warn(
+                            span,
+                            format!(
+                                "Could not compute the layouts of the types of this transmute \
+                                (`{src_ty}` to `{tgt_ty}`); they may depend on generic parameters"
+                            ),
+                        ),
+                    }
+                    transmute_layouts.insert((block_id.index(), i), layouts);
+                }
+            }
+        }
+        transmute_layouts
+    }
+
+    /// Translate the body of a promoted constant to a global (see
+    /// [Self::translate_promoted_consts]).
+    fn translate_promoted_const(
+        &mut self,
+        rust_id: DefId,
+        rust_body: &Rc<rustc_middle::mir::Body<'tcx>>,
+        index: usize,
+        item_meta: &ItemMeta,
+        generics: &GenericParams,
+    ) -> Result<GlobalDeclId, Error> {
+        let state = self
+            .hax_state
+            .clone()
+            .with_owner_id(rust_id)
+            .with_mir(rust_body.clone());
+        let body: hax::MirBody<()> =
+            self.t_ctx
+                .catch_sinto(&state, item_meta.span, &**rust_body)?;
+        self.translate_body_locals(&body, rust_body)?;
+        let debug_info = self.translate_debug_info(rust_body);
+        self.translate_transparent_expression_body(&body)?;
+        let span = self.translate_span_from_hax(&body.span);
+
+        // Take the body translation state, leaving it empty for the next body.
+        let blocks = mem::take(&mut self.blocks).into_values().collect();
+        let locals = mem::take(&mut self.vars);
+        self.vars_map.clear();
+        self.blocks_map = Default::default();
+        let ty = locals[VarId::ZERO].ty.clone();
+        let body = ExprBody {
+            span,
+            arg_count: 0,
+            locals,
+            comments: Vec::new(),
+            debug_info,
+            body: blocks,
+        };
+        let body_id = self.t_ctx.translated.bodies.push(Body::Unstructured(body));
+
+        let mut item_meta = item_meta.clone();
+        item_meta.span = span;
+        item_meta.source_text = None;
+        item_meta.is_harness = false;
+        item_meta.name.name.push(PathElem::Ident(
+            "{promoted}".to_string(),
+            Disambiguator::new(index),
+        ));
+        let global_id = self.t_ctx.translated.global_decls.reserve_slot();
+        let translated = &mut self.t_ctx.translated;
+        translated.all_ids.insert(global_id.into());
+        translated
+            .item_names
+            .insert(global_id.into(), item_meta.name.clone());
+        translated.global_decls.set_slot(
+            global_id,
+            GlobalDecl {
+                def_id: global_id,
+                item_meta,
+                generics: generics.clone(),
+                ty,
+                // Rustc only promotes the constants without interior mutability.
+                is_mutable: false,
+                has_interior_mutability: false,
+                kind: ItemKind::Regular,
+                linkage: Linkage::default(),
+                body: Ok(body_id),
+            },
+        );
+        Ok(global_id)
+    }
+
     /// Translate a function's signature, and initialize a body translation context
     /// at the same time - the function signature gives us the list of region and
     /// type parameters, that we put in the translation context.
@@ -1475,11 +1982,14 @@ impl BodyTransCtx<'_, '_, '_> {
             Err(Opaque)
         };
 
+        let linkage = self.t_ctx.translate_linkage(rust_id);
+
         Ok(FunDecl {
             def_id,
             item_meta,
             signature,
             kind,
+            linkage,
             body: body_id,
         })
     }
@@ -1512,10 +2022,23 @@ impl BodyTransCtx<'_, '_, '_> {
         let ty = match &def.kind {
             hax::FullDefKind::Const { ty, .. }
             | hax::FullDefKind::AssocConst { ty, .. }
-            | hax::FullDefKind::Static { ty, .. } => ty,
+            | hax::FullDefKind::Static { ty, .. } => ty.clone(),
+            // Anonymous constants and inline `const { .. }` blocks: hax doesn't give us their
+            // type, so we ask rustc directly.
+            hax::FullDefKind::AnonConst { .. } | hax::FullDefKind::InlineConst { .. } => {
+                let ty = self.t_ctx.tcx.type_of(rust_id).instantiate_identity();
+                self.t_ctx.catch_sinto(&self.hax_state, span, &ty)?
+            }
             _ => panic!("Unexpected def for constant: {def:?}"),
         };
-        let ty = self.translate_ty(span, ty)?;
+        let ty = self.translate_ty(span, &ty)?;
+
+        let tcx = self.t_ctx.tcx;
+        let is_mutable = tcx.is_mutable_static(rust_id);
+        let has_interior_mutability = !tcx
+            .type_of(rust_id)
+            .instantiate_identity()
+            .is_freeze(tcx, tcx.param_env(rust_id));
 
         // Translate its body like the body of a function. This returns `Opaque if we can't/decide
         // not to translate this body.
@@ -1528,12 +2051,17 @@ impl BodyTransCtx<'_, '_, '_> {
             Err(_) => Ok(self.t_ctx.translated.bodies.reserve_slot()),
         };
 
+        let linkage = self.t_ctx.translate_linkage(rust_id);
+
         Ok(GlobalDecl {
             def_id,
             item_meta,
             generics,
             ty,
+            is_mutable,
+            has_interior_mutability,
             kind: global_kind,
+            linkage,
             body: body_id,
         })
     }
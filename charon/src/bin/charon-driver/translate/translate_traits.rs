@@ -8,7 +8,10 @@ use charon_lib::ullbc_ast as ast;
 use hax_frontend_exporter as hax;
 use itertools::Itertools;
 use rustc_hir::def_id::DefId;
-use std::collections::HashMap;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_trait_selection::traits::vtable::{prepare_vtable_segments, VtblSegment};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::ControlFlow;
 use std::sync::Arc;
 
 /// The context in which we are translating a clause, used to generate the appropriate ids and
@@ -34,6 +37,66 @@ impl<'tcx, 'ctx> TranslateCtx<'tcx, 'ctx> {
         assert!(id.is_zero());
         Ok(TraitItemName(name.to_string()))
     }
+
+    /// Compute the slot of each method in the vtable of `dyn Trait`, following the layout used by
+    /// rustc: the common entries (drop, size and align), then the methods of the supertraits, then
+    /// the methods of the trait. Returns `None` if the trait is not dyn-compatible.
+    pub(crate) fn compute_vtable_slots(&self, trait_id: DefId) -> Option<HashMap<DefId, usize>> {
+        let tcx = self.tcx;
+        if !tcx.is_dyn_compatible(trait_id) {
+            return None;
+        }
+        let trait_ref = ty::Binder::dummy(ty::TraitRef::identity(tcx, trait_id));
+        let mut slots = HashMap::new();
+        let mut offset = 0;
+        let _: Option<()> = prepare_vtable_segments(tcx, trait_ref, |segment| {
+            match segment {
+                VtblSegment::MetadataDSA => offset += TyCtxt::COMMON_VTABLE_ENTRIES.len(),
+                VtblSegment::TraitOwnEntries {
+                    trait_ref,
+                    emit_vptr,
+                } => {
+                    let entries = tcx.own_existential_vtable_entries(trait_ref.def_id());
+                    for (i, method_id) in entries.iter().enumerate() {
+                        slots.insert(*method_id, offset + i);
+                    }
+                    offset += entries.len();
+                    // The pointer to the vtable of the supertrait, used for upcasting.
+                    if emit_vptr {
+                        offset += 1;
+                    }
+                }
+            }
+            ControlFlow::Continue(())
+        });
+        Some(slots)
+    }
+
+    /// The reasons why the trait is not dyn-compatible, as given by rustc.
+    pub(crate) fn translate_dyn_compatibility_violations(
+        &self,
+        trait_id: DefId,
+    ) -> Vec<DynCompatibilityViolation> {
+        use rustc_middle::traits::DynCompatibilityViolation as Violation;
+        self.tcx
+            .dyn_compatibility_violations(trait_id)
+            .iter()
+            .map(|violation| {
+                let item = match violation {
+                    Violation::Method(name, ..)
+                    | Violation::AssocConst(name, _)
+                    | Violation::GAT(name, _) => Some(TraitItemName(name.to_string())),
+                    Violation::SizedSelf(_)
+                    | Violation::SupertraitSelf(_)
+                    | Violation::SupertraitNonLifetimeBinder(_) => None,
+                };
+                DynCompatibilityViolation {
+                    item,
+                    reason: violation.error_msg().into_owned(),
+                }
+            })
+            .collect()
+    }
 }
 
 impl BodyTransCtx<'_, '_, '_> {
@@ -75,12 +138,14 @@ impl BodyTransCtx<'_, '_, '_> {
         // Translate the associated items
         // We do something subtle here: TODO: explain
         let mut consts = Vec::new();
-        let mut const_defaults = HashMap::new();
+        let mut const_defaults = BTreeMap::new();
         let mut types = Vec::new();
         let mut type_clauses = Vec::new();
-        let mut type_defaults = HashMap::new();
+        let mut type_defaults = BTreeMap::new();
         let mut required_methods = Vec::new();
         let mut provided_methods = Vec::new();
+        let all_vtable_slots = self.t_ctx.compute_vtable_slots(rust_id);
+        let mut vtable_slots = all_vtable_slots.as_ref().map(|_| Vec::new());
         for (item_name, hax_item, hax_def) in &items {
             let rust_item_id = DefId::from(&hax_item.def_id);
             let item_span = self.def_span(rust_item_id);
@@ -94,6 +159,14 @@ impl BodyTransCtx<'_, '_, '_> {
                         // This is a required method (no default implementation)
                         required_methods.push((item_name.clone(), fun_id));
                     }
+                    if let Some(all_vtable_slots) = &all_vtable_slots
+                        && let Some(slot) = all_vtable_slots.get(&rust_item_id)
+                    {
+                        vtable_slots
+                            .as_mut()
+                            .unwrap()
+                            .push((item_name.clone(), *slot));
+                    }
                 }
                 hax::FullDefKind::AssocConst { ty, .. } => {
                     // Check if the constant has a value (i.e., a body).
@@ -171,6 +244,10 @@ impl BodyTransCtx<'_, '_, '_> {
             type_defaults,
             required_methods,
             provided_methods,
+            vtable_slots,
+            dyn_compatibility_violations: self
+                .t_ctx
+                .translate_dyn_compatibility_violations(rust_id),
         })
     }
 
@@ -235,6 +312,13 @@ impl BodyTransCtx<'_, '_, '_> {
         let mut required_methods = Vec::new();
         let mut provided_methods = Vec::new();
         let mut type_clauses = Vec::new();
+        let mut obligations: Vec<_> = parent_trait_refs
+            .iter_indexed()
+            .map(|(id, trait_ref)| ImplObligation {
+                origin: ObligationOrigin::ParentClause(id),
+                trait_ref: trait_ref.clone(),
+            })
+            .collect();
 
         for impl_item in impl_items {
             use hax::ImplAssocItemValue::*;
@@ -284,6 +368,12 @@ impl BodyTransCtx<'_, '_, '_> {
 
                     let trait_refs =
                         self.translate_trait_impl_exprs(item_span, &impl_item.required_impl_exprs)?;
+                    obligations.extend(trait_refs.iter_indexed().map(|(id, trait_ref)| {
+                        ImplObligation {
+                            origin: ObligationOrigin::AssocTypeBound(name.clone(), id),
+                            trait_ref: trait_ref.clone(),
+                        }
+                    }));
                     type_clauses.push((name, trait_refs));
                 }
                 _ => panic!("Unexpected definition for trait item: {item_def:?}"),
@@ -312,6 +402,7 @@ impl BodyTransCtx<'_, '_, '_> {
             types,
             required_methods,
             provided_methods,
+            obligations,
         })
     }
 }
@@ -10,6 +10,7 @@ use charon_lib::pretty::FmtWithCtx;
 use core::convert::*;
 use hax::Visibility;
 use hax_frontend_exporter as hax;
+use rustc_hir::def_id::DefId;
 
 /// Small helper: we ignore some region names (when they are equal to "'_")
 fn check_region_name(s: Option<String>) -> Option<String> {
@@ -514,6 +515,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     attr_info: field_attrs,
                     name: field_name.clone(),
                     ty,
+                    recursion: None,
                 };
                 fields.push(field);
             }
@@ -642,6 +644,12 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 let parent_def = self.t_ctx.hax_def(parent)?;
                 self.push_generics_for_def(span, &parent_def, true)?;
             }
+            // Anonymous constants and inline consts can refer to the generics of their parent.
+            FullDefKind::AnonConst { .. } | FullDefKind::InlineConst { .. } => {
+                let parent = self.t_ctx.tcx.parent(def.rust_def_id());
+                let parent_def = self.t_ctx.hax_def(parent)?;
+                self.push_generics_for_def(span, &parent_def, true)?;
+            }
             _ => {}
         }
         if let Some((generics, predicates)) = def.generics() {
@@ -672,6 +680,8 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 | FullDefKind::AssocFn { .. }
                 | FullDefKind::Const { .. }
                 | FullDefKind::AssocConst { .. }
+                | FullDefKind::AnonConst { .. }
+                | FullDefKind::InlineConst { .. }
                 | FullDefKind::Static { .. } => {
                     (PredicateOrigin::WhereClauseOnFn, PredicateLocation::Base)
                 }
@@ -781,6 +791,30 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     }
 }
 
+impl TranslateCtx<'_, '_> {
+    /// Compute whether the type declared at `rust_id` implements `Unpin`. If it doesn't in its own
+    /// environment, we check again with its type parameters replaced by `()` (which is `Unpin`)
+    /// to distinguish the types which are `Unpin` depending on their parameters.
+    pub(crate) fn translate_unpin_kind(&self, rust_id: DefId) -> UnpinKind {
+        use rustc_middle::ty;
+        let tcx = self.tcx;
+        let param_env = tcx.param_env(rust_id);
+        let ty = tcx.type_of(rust_id);
+        if ty.instantiate_identity().is_unpin(tcx, param_env) {
+            return UnpinKind::Unpin;
+        }
+        let args = ty::GenericArgs::for_item(tcx, rust_id, |param, _| match param.kind {
+            ty::GenericParamDefKind::Type { .. } => tcx.types.unit.into(),
+            _ => tcx.mk_param_from_def(param),
+        });
+        if ty.instantiate(tcx, args).is_unpin(tcx, param_env) {
+            UnpinKind::UnpinIfParamsUnpin
+        } else {
+            UnpinKind::NotUnpin
+        }
+    }
+}
+
 impl BodyTransCtx<'_, '_, '_> {
     /// Translate a type definition.
     ///
@@ -822,11 +856,16 @@ impl BodyTransCtx<'_, '_, '_> {
             Ok(kind) => kind,
             Err(err) => TypeDeclKind::Error(err.msg),
         };
+        let unpin = match &def.kind {
+            hax::FullDefKind::OpaqueTy => None,
+            _ => Some(self.t_ctx.translate_unpin_kind(def.def_id.to_rust_def_id())),
+        };
         let type_def = TypeDecl {
             def_id: trans_id,
             item_meta,
             generics,
             kind,
+            unpin,
         };
 
         trace!(
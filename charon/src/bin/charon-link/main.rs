@@ -0,0 +1,50 @@
+//! Merge several `.llbc`/`.ullbc` files into one, e.g. the extractions of the crates of a project
+//! or of the standard library. Call it with `charon link`, which sets up the right toolchain paths.
+//!
+//! The items present in several files (the items of the common dependencies, and the generated
+//! items with the same [charon_lib::ast::ItemMeta::dedup_hash]) are only kept once: see
+//! [charon_lib::ast::TranslatedCrate::link].
+#![feature(rustc_private)]
+
+use anyhow::{bail, Result};
+use charon_lib::export::{BuildInfo, CrateData};
+use charon_lib::import::read_crate_data;
+use charon_lib::options::ExportProfile;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[clap(name = "charon link")]
+struct LinkOpts {
+    /// The file to write the merged crate to. The name of the merged crate is that of the first
+    /// input.
+    #[clap(long = "dest-file", short = 'o')]
+    dest_file: PathBuf,
+    /// The `.llbc` or `.ullbc` files to merge.
+    #[clap(required = true)]
+    inputs: Vec<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let opts = LinkOpts::parse();
+    let mut inputs = opts.inputs.iter();
+    let first = inputs.next().unwrap();
+    let mut krate = read_crate_data(first)?.translated;
+    for input in inputs {
+        krate.link(read_crate_data(input)?.translated);
+    }
+
+    let crate_data = CrateData {
+        charon_version: charon_lib::VERSION.to_owned(),
+        build_info: BuildInfo::current(),
+        translated: krate,
+        has_errors: false,
+    };
+    if crate_data
+        .serialize_to_file(&opts.dest_file, ExportProfile::Default)
+        .is_err()
+    {
+        bail!("could not write `{}`", opts.dest_file.display());
+    }
+    Ok(())
+}
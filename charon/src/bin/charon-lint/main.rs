@@ -0,0 +1,52 @@
+//! Report the uses of the constructs from a deny-list in a `.llbc`/`.ullbc` file, e.g. to check
+//! that a crate stays within what a verification tool supports. Call it with `charon lint`, which
+//! sets up the right toolchain paths.
+//!
+//! See [charon_lib::lint::Lint] for the constructs we can report.
+#![feature(rustc_private)]
+
+use anyhow::Result;
+use charon_lib::formatter::{GetPrintOptions, IntoFormatter};
+use charon_lib::import::read_crate_data;
+use charon_lib::lint::{lint_crate, Lint};
+use charon_lib::pretty::FmtWithCtx;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[clap(name = "charon lint")]
+struct LintOpts {
+    /// The constructs to report, separated by commas.
+    #[clap(long = "deny", value_enum, value_delimiter = ',', required = true)]
+    deny: Vec<Lint>,
+    /// The `.llbc` or `.ullbc` file to check.
+    input: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let opts = LintOpts::parse();
+    let crate_data = read_crate_data(&opts.input)?;
+    let krate = &crate_data.translated;
+
+    let violations = lint_crate(krate, &opts.deny);
+    let fmt_ctx = krate.into_fmt();
+    for violation in &violations {
+        let name = match krate.item_name(violation.item) {
+            Some(name) => name.fmt_with_ctx(&fmt_ctx),
+            None => format!("{:?}", violation.item),
+        };
+        println!(
+            "{}: [{}] in `{name}`: {}",
+            fmt_ctx.format_span(&violation.span.span),
+            violation.lint.name(),
+            violation.message
+        );
+    }
+    if violations.is_empty() {
+        eprintln!("No denied constructs found");
+        Ok(())
+    } else {
+        eprintln!("Found {} uses of denied constructs", violations.len());
+        std::process::exit(1)
+    }
+}
@@ -0,0 +1,255 @@
+//! Generate a static HTML view of a `.llbc`/`.ullbc` file. Call it with `charon report`, which
+//! sets up the right toolchain paths.
+//!
+//! The report has an index of the items, and one page per item with its pretty-printed
+//! definition. The names of the items it refers to are links to their pages, and every page lists
+//! the items which refer to it (e.g. the callers of a function).
+#![feature(rustc_private)]
+
+use anyhow::Result;
+use charon_lib::ast::*;
+use charon_lib::formatter::IntoFormatter;
+use charon_lib::import::read_crate_data;
+use charon_lib::pretty::FmtWithCtx;
+use clap::Parser;
+use derive_visitor::{Drive, Visitor};
+use itertools::Itertools;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[clap(name = "charon report")]
+struct ReportOpts {
+    /// The directory to write the report to.
+    #[clap(short = 'o', long = "output")]
+    output: PathBuf,
+    /// The `.llbc` or `.ullbc` file to generate a report for.
+    input: PathBuf,
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+pre { background: #f6f8fa; padding: 1em; overflow-x: auto; }
+a { text-decoration: none; }
+a:hover { text-decoration: underline; }
+";
+
+/// Collect the items an item refers to.
+#[derive(Default, Visitor)]
+#[visitor(
+    TypeDeclId(enter),
+    FunDeclId(enter),
+    GlobalDeclId(enter),
+    TraitImplId(enter),
+    TraitDeclId(enter),
+    Ty(enter)
+)]
+struct RefCollector {
+    refs: BTreeSet<AnyTransId>,
+}
+
+impl RefCollector {
+    fn enter_type_decl_id(&mut self, id: &TypeDeclId) {
+        self.refs.insert(AnyTransId::Type(*id));
+    }
+    fn enter_fun_decl_id(&mut self, id: &FunDeclId) {
+        self.refs.insert(AnyTransId::Fun(*id));
+    }
+    fn enter_global_decl_id(&mut self, id: &GlobalDeclId) {
+        self.refs.insert(AnyTransId::Global(*id));
+    }
+    fn enter_trait_impl_id(&mut self, id: &TraitImplId) {
+        self.refs.insert(AnyTransId::TraitImpl(*id));
+    }
+    fn enter_trait_decl_id(&mut self, id: &TraitDeclId) {
+        self.refs.insert(AnyTransId::TraitDecl(*id));
+    }
+    fn enter_ty(&mut self, ty: &Ty) {
+        // Recurse into the type, which doesn't happen by default.
+        ty.drive_inner(self);
+    }
+}
+
+fn page_name(id: AnyTransId) -> String {
+    let index = match id {
+        AnyTransId::Type(id) => id.index(),
+        AnyTransId::Fun(id) => id.index(),
+        AnyTransId::Global(id) => id.index(),
+        AnyTransId::TraitDecl(id) => id.index(),
+        AnyTransId::TraitImpl(id) => id.index(),
+    };
+    format!("{}-{index}.html", id.variant_name().to_lowercase())
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn page(title: &str, body: &str) -> String {
+    let title = escape(title);
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+        <style>{STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n"
+    )
+}
+
+struct Report<'a> {
+    krate: &'a TranslatedCrate,
+    names: BTreeMap<AnyTransId, String>,
+    /// The items every item refers to.
+    refs: BTreeMap<AnyTransId, BTreeSet<AnyTransId>>,
+    /// The items which refer to every item.
+    referrers: BTreeMap<AnyTransId, BTreeSet<AnyTransId>>,
+}
+
+impl<'a> Report<'a> {
+    fn new(krate: &'a TranslatedCrate) -> Self {
+        let fmt_ctx = krate.into_fmt();
+        let mut names = BTreeMap::new();
+        let mut refs = BTreeMap::new();
+        let mut referrers: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+        for (id, item) in krate.all_items_with_ids() {
+            names.insert(id, item.item_meta().name.fmt_with_ctx(&fmt_ctx));
+            let mut collector = RefCollector::default();
+            item.drive(&mut collector);
+            // Look into the bodies, which are stored separately.
+            let body_id = match item {
+                AnyTransItem::Fun(decl) => decl.body.ok(),
+                AnyTransItem::Global(decl) => decl.body.ok(),
+                _ => None,
+            };
+            if let Some(body) = body_id.and_then(|id| krate.bodies.get(id)) {
+                body.drive(&mut collector);
+            }
+            collector.refs.remove(&id);
+            for referred in &collector.refs {
+                referrers.entry(*referred).or_default().insert(id);
+            }
+            refs.insert(id, collector.refs);
+        }
+        Report {
+            krate,
+            names,
+            refs,
+            referrers,
+        }
+    }
+
+    fn link(&self, id: AnyTransId) -> String {
+        let name = self
+            .names
+            .get(&id)
+            .map(String::as_str)
+            .unwrap_or("<unknown>");
+        format!("<a href=\"{}\">{}</a>", page_name(id), escape(name))
+    }
+
+    /// Escape the pretty-printed item, and turn the names of the items it refers to into links.
+    fn linkify(&self, id: AnyTransId, text: &str) -> String {
+        let targets: BTreeMap<&str, AnyTransId> = self.refs[&id]
+            .iter()
+            .filter_map(|id| Some((self.names.get(id)?.as_str(), *id)))
+            .collect();
+        if targets.is_empty() {
+            return escape(text);
+        }
+        // Try the longest names first, so that we don't link a prefix of a longer name.
+        let pattern = targets
+            .keys()
+            .sorted_by_key(|name| std::cmp::Reverse(name.len()))
+            .map(|name| regex::escape(name))
+            .join("|");
+        let re = Regex::new(&pattern).unwrap();
+        // Don't link the names which are part of a longer identifier or path.
+        let is_name_char = |c: char| c.is_alphanumeric() || c == '_' || c == ':';
+        let mut out = String::new();
+        let mut last = 0;
+        for m in re.find_iter(text) {
+            if text[..m.start()].ends_with(is_name_char)
+                || text[m.end()..].starts_with(is_name_char)
+            {
+                continue;
+            }
+            out += &escape(&text[last..m.start()]);
+            let target = targets[m.as_str()];
+            let _ = write!(
+                out,
+                "<a href=\"{}\">{}</a>",
+                page_name(target),
+                escape(m.as_str())
+            );
+            last = m.end();
+        }
+        out += &escape(&text[last..]);
+        out
+    }
+
+    fn index_page(&self) -> String {
+        let mut body = format!("<h1>{}</h1>\n", escape(&self.krate.crate_name));
+        let by_kind = self.names.keys().into_group_map_by(|id| id.variant_name());
+        for (kind, title) in [
+            ("Type", "Types"),
+            ("Fun", "Functions"),
+            ("Global", "Globals"),
+            ("TraitDecl", "Traits"),
+            ("TraitImpl", "Trait impls"),
+        ] {
+            let Some(ids) = by_kind.get(kind) else {
+                continue;
+            };
+            let _ = writeln!(body, "<h2>{title}</h2>\n<ul>");
+            for id in ids.iter().sorted_by_key(|id| &self.names[**id]) {
+                let _ = writeln!(body, "<li>{}</li>", self.link(**id));
+            }
+            body += "</ul>\n";
+        }
+        page(&self.krate.crate_name, &body)
+    }
+
+    fn item_page(&self, id: AnyTransId) -> String {
+        let fmt_ctx = self.krate.into_fmt();
+        let name = &self.names[&id];
+        let mut body = format!(
+            "<p><a href=\"index.html\">index</a></p>\n<h1>{}</h1>\n",
+            escape(name)
+        );
+        let text = fmt_ctx.format_decl_id(id);
+        let _ = writeln!(body, "<pre>{}</pre>", self.linkify(id, &text));
+        for (title, ids) in [
+            ("Refers to", self.refs.get(&id)),
+            ("Referred to by", self.referrers.get(&id)),
+        ] {
+            let Some(ids) = ids.filter(|ids| !ids.is_empty()) else {
+                continue;
+            };
+            let _ = writeln!(body, "<h2>{title}</h2>\n<ul>");
+            for id in ids {
+                let _ = writeln!(body, "<li>{}</li>", self.link(*id));
+            }
+            body += "</ul>\n";
+        }
+        page(name, &body)
+    }
+}
+
+fn main() -> Result<()> {
+    let opts = ReportOpts::parse();
+    let crate_data = read_crate_data(&opts.input)?;
+    let report = Report::new(&crate_data.translated);
+
+    std::fs::create_dir_all(&opts.output)?;
+    std::fs::write(opts.output.join("index.html"), report.index_page())?;
+    for id in report.names.keys() {
+        std::fs::write(opts.output.join(page_name(*id)), report.item_page(*id))?;
+    }
+    eprintln!(
+        "Generated the report: {}",
+        opts.output.join("index.html").display()
+    );
+    Ok(())
+}
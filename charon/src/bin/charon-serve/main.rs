@@ -0,0 +1,255 @@
+//! A long-running server which keeps the translation of a crate up to date and answers queries
+//! about it. Call it with `charon serve`, which sets up the right toolchain paths.
+//!
+//! The server watches the workspace in the current directory (by polling the modification times
+//! of the Rust sources and of the cargo manifests), and re-runs charon whenever something
+//! changed. Cargo takes care of not rebuilding the dependencies, so only the crate itself gets
+//! re-extracted.
+//!
+//! The queries are JSON-RPC 2.0 requests, sent over a TCP socket, one request per line. The
+//! supported methods are:
+//! - `status`: returns `{ "generation": <n>, "extraction_error": <msg?> }`, where the generation
+//!   is incremented on every successful extraction;
+//! - `items`: returns the list of the items, as `{ "id": <id>, "name": <name> }` objects;
+//! - `item`: takes `{ "name": <name> }` and returns the pretty-printed items with that name;
+//! - `reextract`: re-runs charon immediately and returns the new status.
+#![feature(rustc_private)]
+
+use anyhow::{bail, Context, Result};
+use charon_lib::export::CrateData;
+use charon_lib::formatter::IntoFormatter;
+use charon_lib::import::read_crate_data;
+use charon_lib::pretty::FmtWithCtx;
+use clap::Parser;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+#[derive(Parser)]
+#[clap(name = "charon serve")]
+struct ServeOpts {
+    /// The address to listen on.
+    #[clap(long = "listen", default_value = "127.0.0.1:7878")]
+    listen: String,
+    /// How often to check the workspace for changes, in milliseconds.
+    #[clap(long = "poll-interval", default_value_t = 500)]
+    poll_interval: u64,
+    /// The arguments to pass to charon (e.g. `-- --lib --extract-opaque-bodies`).
+    #[clap(last = true)]
+    charon_args: Vec<String>,
+}
+
+/// The latest translation of the crate.
+#[derive(Default)]
+struct State {
+    /// Incremented on every successful extraction.
+    generation: usize,
+    krate: Option<CrateData>,
+    /// The error of the last extraction, if it failed.
+    extraction_error: Option<String>,
+}
+
+struct Server {
+    opts: ServeOpts,
+    /// The file charon writes the translated crate to.
+    dest_file: PathBuf,
+    state: Mutex<State>,
+    /// Serializes the extractions.
+    extraction_lock: Mutex<()>,
+}
+
+impl Server {
+    /// Run charon on the workspace and load the result.
+    fn extract(&self) {
+        let _guard = self.extraction_lock.lock().unwrap();
+        let result = self.run_charon();
+        let mut state = self.state.lock().unwrap();
+        match result {
+            Ok(krate) => {
+                state.generation += 1;
+                state.krate = Some(krate);
+                state.extraction_error = None;
+            }
+            // We keep the previous translation around.
+            Err(err) => state.extraction_error = Some(format!("{err:#}")),
+        }
+    }
+
+    fn run_charon(&self) -> Result<CrateData> {
+        let charon = std::env::current_exe()?.with_file_name("charon");
+        let status = Command::new(charon)
+            .args(&self.opts.charon_args)
+            .arg("--dest-file")
+            .arg(&self.dest_file)
+            .status()
+            .context("could not run charon")?;
+        if !status.success() {
+            bail!("charon exited with {status}");
+        }
+        read_crate_data(&self.dest_file)
+    }
+
+    fn status(&self) -> Value {
+        let state = self.state.lock().unwrap();
+        json!({
+            "generation": state.generation,
+            "extraction_error": state.extraction_error,
+        })
+    }
+
+    /// Answer a JSON-RPC request.
+    fn handle_request(&self, method: &str, params: &Value) -> Result<Value> {
+        match method {
+            "status" => Ok(self.status()),
+            "reextract" => {
+                self.extract();
+                Ok(self.status())
+            }
+            "items" | "item" => {
+                let state = self.state.lock().unwrap();
+                let Some(krate) = &state.krate else {
+                    bail!("the crate hasn't been extracted yet")
+                };
+                let translated = &krate.translated;
+                let fmt_ctx = translated.into_fmt();
+                let items = translated
+                    .all_items_with_ids()
+                    .map(|(id, item)| (id, item.item_meta().name.fmt_with_ctx(&fmt_ctx)));
+                if method == "items" {
+                    Ok(items
+                        .map(|(id, name)| json!({ "id": id, "name": name }))
+                        .collect())
+                } else {
+                    let Some(name) = params.get("name").and_then(Value::as_str) else {
+                        bail!("missing parameter `name`")
+                    };
+                    Ok(items
+                        .filter(|(_, item_name)| item_name == name)
+                        .map(|(id, _)| Value::String(fmt_ctx.format_decl_id(id)))
+                        .collect())
+                }
+            }
+            _ => bail!("unknown method `{method}`"),
+        }
+    }
+
+    /// Answer the requests of a client, one per line.
+    fn handle_client(&self, stream: TcpStream) -> Result<()> {
+        let mut writer = stream.try_clone()?;
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<Value>(&line) {
+                Ok(request) => {
+                    let id = request.get("id").cloned().unwrap_or(Value::Null);
+                    let method = request.get("method").and_then(Value::as_str);
+                    let params = request.get("params").cloned().unwrap_or(Value::Null);
+                    match method.map(|method| self.handle_request(method, &params)) {
+                        Some(Ok(result)) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                        Some(Err(err)) => json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": { "code": -32000, "message": format!("{err:#}") },
+                        }),
+                        None => json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": { "code": -32600, "message": "invalid request" },
+                        }),
+                    }
+                }
+                Err(err) => json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": { "code": -32700, "message": err.to_string() },
+                }),
+            };
+            writeln!(writer, "{response}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Collect the modification times of the files whose changes should trigger a re-extraction.
+fn collect_mtimes(dir: &Path, mtimes: &mut BTreeMap<PathBuf, SystemTime>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if file_name != "target" && !file_name.starts_with('.') {
+                collect_mtimes(&path, mtimes);
+            }
+        } else if file_name.ends_with(".rs")
+            || file_name == "Cargo.toml"
+            || file_name == "Cargo.lock"
+            || file_name == "Charon.toml"
+        {
+            if let Ok(mtime) = metadata.modified() {
+                mtimes.insert(path, mtime);
+            }
+        }
+    }
+}
+
+/// Re-extract the crate whenever the workspace changes.
+fn watch(server: &Server) {
+    let mut mtimes = BTreeMap::new();
+    collect_mtimes(Path::new("."), &mut mtimes);
+    loop {
+        std::thread::sleep(Duration::from_millis(server.opts.poll_interval));
+        let mut new_mtimes = BTreeMap::new();
+        collect_mtimes(Path::new("."), &mut new_mtimes);
+        if new_mtimes != mtimes {
+            mtimes = new_mtimes;
+            server.extract();
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let opts = ServeOpts::parse();
+    // The directory is removed when `main` returns.
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("charon-serve-")
+        .tempdir()
+        .context("could not create a temporary directory")?;
+    let listener = TcpListener::bind(&opts.listen)
+        .with_context(|| format!("could not listen on {}", opts.listen))?;
+    let server = Arc::new(Server {
+        opts,
+        dest_file: tmp_dir.path().join("crate.llbc"),
+        state: Default::default(),
+        extraction_lock: Mutex::new(()),
+    });
+
+    server.extract();
+    {
+        let server = server.clone();
+        std::thread::spawn(move || watch(&server));
+    }
+    eprintln!("Listening on {}", server.opts.listen);
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let server = server.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = server.handle_client(stream) {
+                eprintln!("Error while answering a client: {err:#}");
+            }
+        });
+    }
+    Ok(())
+}
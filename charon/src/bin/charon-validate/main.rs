@@ -0,0 +1,46 @@
+//! Check the invariants of a `.llbc`/`.ullbc` file, e.g. one produced by another tool or modified
+//! by hand. Call it with `charon validate`, which sets up the right toolchain paths.
+//!
+//! See [charon_lib::validate] for the list of invariants we check. We also type-check the bodies
+//! with [charon_lib::tyck].
+#![feature(rustc_private)]
+
+use anyhow::Result;
+use charon_lib::formatter::IntoFormatter;
+use charon_lib::import::read_crate_data;
+use charon_lib::pretty::FmtWithCtx;
+use charon_lib::tyck;
+use charon_lib::validate::validate_crate;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[clap(name = "charon validate")]
+struct ValidateOpts {
+    /// The `.llbc` or `.ullbc` file to check.
+    input: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let opts = ValidateOpts::parse();
+    let crate_data = read_crate_data(&opts.input)?;
+    let krate = &crate_data.translated;
+
+    let mut errors = validate_crate(krate);
+    errors.extend(tyck::check_crate(krate));
+    let fmt_ctx = krate.into_fmt();
+    for error in &errors {
+        let name = match krate.item_name(error.item) {
+            Some(name) => name.fmt_with_ctx(&fmt_ctx),
+            None => format!("{:?}", error.item),
+        };
+        println!("error in `{name}`: {}", error.message);
+    }
+    if errors.is_empty() {
+        eprintln!("No invariant violations found");
+        Ok(())
+    } else {
+        eprintln!("Found {} invariant violations", errors.len());
+        std::process::exit(1)
+    }
+}
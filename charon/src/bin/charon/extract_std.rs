@@ -0,0 +1,87 @@
+//! `charon extract-std`: extract the crates of the standard library (`core` and `alloc` by
+//! default) from their sources in the sysroot, similarly to how `-Zbuild-std` builds them. This
+//! only needs to be done once per toolchain: the generated files can then be reused across
+//! extractions. The items they contain have the same names as the standard library items found in
+//! the other extractions.
+use crate::options::CliOpts;
+use anyhow::{bail, Context};
+use clap::Parser;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[clap(name = "charon extract-std")]
+pub struct ExtractStdOpts {
+    /// The directory in which to write the `<crate>.llbc` files.
+    #[clap(long = "dest", default_value = "std-llbc")]
+    pub dest_dir: PathBuf,
+    /// Extract ULLBC instead of LLBC.
+    #[clap(long = "ullbc")]
+    pub ullbc: bool,
+    /// Extra flags to pass to rustc.
+    #[clap(long = "rustc-flag")]
+    pub rustc_args: Vec<String>,
+    /// The crates to extract. Their files can then be merged into other extractions with `--link`
+    /// (or with `charon link`), to get the bodies of the items of the standard library.
+    #[clap(default_values_t = ["core".to_owned(), "alloc".to_owned()])]
+    pub crates: Vec<String>,
+}
+
+impl ExtractStdOpts {
+    /// The options to extract one of the crates of the standard library, given the sysroot of the
+    /// toolchain.
+    pub fn charon_options(&self, sysroot: &Path, krate: &str) -> anyhow::Result<CliOpts> {
+        let crate_dir = sysroot.join("lib/rustlib/src/rust/library").join(krate);
+        let manifest = crate_dir.join("Cargo.toml");
+        if !manifest.exists() {
+            bail!(
+                "Could not find the sources of `{krate}` (expected `{}`). \
+                You can install them with `rustup component add rust-src`.",
+                manifest.display()
+            )
+        }
+        let source = crate_dir.join(lib_path(&manifest)?);
+        let mut rustc_args = vec![
+            "--crate-type=lib".to_owned(),
+            "--edition=2021".to_owned(),
+            // This is how the standard library is built: the items without stability attributes
+            // are considered unstable.
+            "-Zforce-unstable-if-unmarked".to_owned(),
+        ];
+        rustc_args.extend(self.rustc_args.iter().cloned());
+        Ok(CliOpts {
+            ullbc: self.ullbc,
+            no_cargo: true,
+            // Like cargo, we name the crate after its directory with `-` replaced by `_`.
+            crate_name: Some(krate.replace('-', "_")),
+            input_file: Some(source),
+            dest_dir: Some(self.dest_dir.clone()),
+            rustc_args,
+            ..CliOpts::default()
+        })
+    }
+}
+
+/// The part of a `Cargo.toml` we need.
+#[derive(Deserialize)]
+struct Manifest {
+    lib: Option<LibTarget>,
+}
+
+#[derive(Deserialize)]
+struct LibTarget {
+    path: Option<PathBuf>,
+}
+
+/// The root file of the library of a crate, relative to the directory of the crate: the `[lib]
+/// path` of its manifest, `src/lib.rs` by default. E.g. `rustc-std-workspace-core` uses `lib.rs`.
+fn lib_path(manifest: &Path) -> anyhow::Result<PathBuf> {
+    let contents = std::fs::read_to_string(manifest)
+        .with_context(|| format!("could not read `{}`", manifest.display()))?;
+    let manifest: Manifest = toml::from_str(&contents)
+        .with_context(|| format!("could not parse `{}`", manifest.display()))?;
+    Ok(manifest
+        .lib
+        .and_then(|lib| lib.path)
+        .unwrap_or_else(|| PathBuf::from("src/lib.rs")))
+}
@@ -33,6 +33,7 @@
 // We must not link with the `charon_lib` crate because that would make `charon` need to
 // dynamically link to `librustc_driver.so` etc. The `charon` binary must be runnable _before_
 // setting up the correct toolchain paths.
+mod extract_std;
 #[path = "../../logger.rs"]
 mod logger;
 #[path = "../../options.rs"]
@@ -46,7 +47,7 @@ use serde::Deserialize;
 use std::env;
 use std::ffi::OsStr;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, ExitStatus};
 
 // Store the toolchain details directly in the binary.
 static PINNED_TOOLCHAIN: &str = include_str!("../../../rust-toolchain");
@@ -136,6 +137,15 @@ fn in_toolchain(program: impl AsRef<OsStr>) -> anyhow::Result<Command> {
     Ok(cmd)
 }
 
+/// The path of one of the binaries installed alongside the driver.
+fn tool_path(name: &str) -> PathBuf {
+    driver_path().with_file_name(if cfg!(windows) {
+        format!("{name}.exe")
+    } else {
+        name.to_owned()
+    })
+}
+
 fn driver_cmd() -> anyhow::Result<Command> {
     // We need `in_toolchain` to get the right library paths.
     let mut cmd = in_toolchain(driver_path())?;
@@ -144,15 +154,8 @@ fn driver_cmd() -> anyhow::Result<Command> {
     Ok(cmd)
 }
 
-pub fn main() -> anyhow::Result<()> {
-    // Initialize the logger
-    logger::initialize_logger();
-
-    // Parse the command-line
-    let mut options = CliOpts::parse();
-    trace!("Arguments: {:?}", std::env::args());
-    options.validate();
-
+/// Check that we can find the toolchain, and return the host target triple.
+fn host_triple() -> anyhow::Result<String> {
     // FIXME: when using rustup, ensure the toolchain has the right components installed.
     let use_rustup = which::which("rustup").is_ok();
     // This is set by the nix develop environment and the nix builder; in both cases the toolchain
@@ -172,31 +175,181 @@ pub fn main() -> anyhow::Result<()> {
         rustc_version::VersionMeta::for_command(driver_cmd()?).unwrap_or_else(|err| {
             panic!("failed to determine underlying rustc version of Charon:\n{err:?}",)
         });
-    let host = &rustc_version.host;
+    Ok(rustc_version.host)
+}
 
-    let exit_status = if options.no_cargo {
-        if !options.cargo_args.is_empty() {
-            bail!("Option `--cargo-arg` is not compatible with `--no-cargo`")
+/// The rustflags set by the user, plus `flag`, in the format of `CARGO_ENCODED_RUSTFLAGS`. Like
+/// cargo, we take the first non-empty flags among `CARGO_ENCODED_RUSTFLAGS`, `RUSTFLAGS`, the
+/// `target.<host>.rustflags` and the `build.rustflags` of the cargo config files: setting
+/// `CARGO_ENCODED_RUSTFLAGS` makes cargo ignore the config files, so we must forward them. The
+/// `target.<cfg>.rustflags` aren't supported.
+fn rustflags_with(flag: &str, host: &str) -> String {
+    let mut flags: Vec<String> = match env::var("CARGO_ENCODED_RUSTFLAGS") {
+        Ok(flags) if !flags.is_empty() => flags.split('\x1f').map(str::to_owned).collect(),
+        _ => match env::var("RUSTFLAGS") {
+            Ok(flags) if !flags.trim().is_empty() => {
+                flags.split_whitespace().map(str::to_owned).collect()
+            }
+            _ => config_rustflags(&format!("target.{host}.rustflags"))
+                .or_else(|| config_rustflags("build.rustflags"))
+                .unwrap_or_default(),
+        },
+    };
+    flags.push(flag.to_owned());
+    flags.join("\x1f")
+}
+
+/// The rustflags set in the cargo config files under the given key, if any. This uses the
+/// unstable `cargo config` command, which our nightly toolchain provides.
+fn config_rustflags(key: &str) -> Option<Vec<String>> {
+    /// The flags are either a string of space-separated flags or an array of flags.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flags {
+        String(String),
+        Array(Vec<String>),
+    }
+    let output = in_toolchain("cargo")
+        .ok()?
+        .args([
+            "-Zunstable-options",
+            "config",
+            "get",
+            "--format=json-value",
+            key,
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        // The key isn't set.
+        return None;
+    }
+    let flags: Vec<String> = match serde_json::from_slice(&output.stdout).ok()? {
+        Flags::String(flags) => flags.split_whitespace().map(str::to_owned).collect(),
+        Flags::Array(flags) => flags,
+    };
+    (!flags.is_empty()).then_some(flags)
+}
+
+/// The target directory cargo would use for the workspace, as given by `cargo metadata` (this
+/// takes `CARGO_TARGET_DIR` and the cargo config files into account).
+fn default_target_dir(options: &CliOpts) -> anyhow::Result<PathBuf> {
+    #[derive(Deserialize)]
+    struct Metadata {
+        target_directory: PathBuf,
+    }
+    let mut cmd = in_toolchain("cargo")?;
+    cmd.args(["metadata", "--no-deps", "--format-version=1"]);
+    // Forward the arguments which select the workspace.
+    let mut cargo_args = options.cargo_args.iter();
+    while let Some(arg) = cargo_args.next() {
+        if arg.starts_with("--manifest-path=") {
+            cmd.arg(arg);
+        } else if arg == "--manifest-path" {
+            cmd.arg(arg);
+            cmd.args(cargo_args.next());
         }
+    }
+    let output = cmd.output()?;
+    if !output.status.success() {
+        bail!(
+            "`cargo metadata` failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+    }
+    let metadata: Metadata = serde_json::from_slice(&output.stdout)?;
+    Ok(metadata.target_directory)
+}
 
-        // Run just the driver.
-        let mut cmd = driver_cmd()?;
+/// Run the driver directly on a source file, without going through cargo.
+fn run_driver(options: &CliOpts, host: &str) -> anyhow::Result<ExitStatus> {
+    let mut cmd = driver_cmd()?;
 
-        cmd.env(CHARON_ARGS, serde_json::to_string(&options).unwrap());
+    cmd.env(CHARON_ARGS, serde_json::to_string(options).unwrap());
 
-        // Make sure the build target is explicitly set. This is needed to detect which crates are
-        // proc-macro/build-script in `charon-driver`.
-        cmd.arg("--target");
-        cmd.arg(host);
+    // Make sure the build target is explicitly set. This is needed to detect which crates are
+    // proc-macro/build-script in `charon-driver`.
+    cmd.arg("--target");
+    cmd.arg(host);
+
+    if let Some(input_file) = &options.input_file {
+        cmd.arg(input_file);
+    }
+
+    Ok(cmd
+        .spawn()
+        .expect("could not run charon-driver")
+        .wait()
+        .expect("failed to wait for charon-driver?"))
+}
 
-        if let Some(input_file) = &options.input_file {
-            cmd.arg(input_file);
+/// `charon extract-std [args]`: extract the crates of the standard library from their sources.
+fn extract_std() -> anyhow::Result<()> {
+    let opts = extract_std::ExtractStdOpts::parse_from(env::args().skip(1));
+    let host = host_triple()?;
+    let output = in_toolchain("rustc")?.arg("--print=sysroot").output()?;
+    let sysroot = PathBuf::from(String::from_utf8(output.stdout)?.trim());
+
+    let mut failed = Vec::new();
+    for krate in &opts.crates {
+        let options = opts.charon_options(&sysroot, krate)?;
+        options.validate();
+        if !run_driver(&options, &host)?.success() {
+            failed.push(krate.as_str());
         }
+    }
+    if !failed.is_empty() {
+        // The files are still generated if there were errors in some of the items.
+        bail!(
+            "Errors occurred while extracting: {}. See the messages above.",
+            failed.join(", ")
+        )
+    }
+    Ok(())
+}
 
-        cmd.spawn()
-            .expect("could not run charon-driver")
+pub fn main() -> anyhow::Result<()> {
+    // Initialize the logger
+    logger::initialize_logger();
+
+    // `charon serve [args]` runs the server, `charon report [args]` generates an HTML report,
+    // `charon validate [args]` checks the invariants of a file, `charon lint [args]` reports the
+    // uses of denied constructs in a file and `charon link [args]` merges several files. They need
+    // the toolchain paths like the driver.
+    if let Some(tool @ ("serve" | "report" | "validate" | "lint" | "link")) =
+        env::args().nth(1).as_deref()
+    {
+        let tool = format!("charon-{tool}");
+        let exit_status = in_toolchain(tool_path(&tool))?
+            .args(env::args().skip(2))
+            .spawn()
+            .unwrap_or_else(|_| panic!("could not run {tool}"))
             .wait()
-            .expect("failed to wait for charon-driver?")
+            .unwrap_or_else(|_| panic!("failed to wait for {tool}?"));
+        std::process::exit(exit_status.code().unwrap_or(-1));
+    }
+    // `charon extract-std [args]` extracts the standard library crates (see `extract_std.rs`).
+    if env::args().nth(1).as_deref() == Some("extract-std") {
+        return extract_std();
+    }
+
+    // Parse the command-line
+    let mut options = CliOpts::parse();
+    trace!("Arguments: {:?}", std::env::args());
+    options.validate();
+
+    let host = &host_triple()?;
+
+    let exit_status = if options.no_cargo {
+        if !options.cargo_args.is_empty() {
+            bail!("Option `--cargo-arg` is not compatible with `--no-cargo`")
+        }
+        if !options.extract_dependencies.is_empty() {
+            bail!("Option `--extract-dependencies` is not compatible with `--no-cargo`")
+        }
+
+        // Run just the driver.
+        run_driver(&options, host)?
     } else {
         if let Some(toml) = toml_config::read_toml() {
             options = toml.apply(options);
@@ -207,7 +360,13 @@ pub fn main() -> anyhow::Result<()> {
         // Tell cargo to use the driver for all the crates in the workspace. There's no option for
         // "run only on the selected crate" so the driver might be called on a crate dependency
         // within the workspace. The driver will detect that case and run rustc normally then.
-        cmd.env("RUSTC_WORKSPACE_WRAPPER", driver_path());
+        // The dependencies we extract may be outside of the workspace, in which case we need the
+        // driver to be called on all the crates.
+        if options.extract_dependencies.is_empty() {
+            cmd.env("RUSTC_WORKSPACE_WRAPPER", driver_path());
+        } else {
+            cmd.env("RUSTC_WRAPPER", driver_path());
+        }
         // Tell the driver that we're being called by cargo.
         cmd.env("CHARON_USING_CARGO", "1");
         // Make sure we don't inherit this variable from the outside. Cargo sets this itself.
@@ -220,11 +379,49 @@ pub fn main() -> anyhow::Result<()> {
         let cargo_subcommand = "rustc";
         cmd.arg(cargo_subcommand);
 
+        // Compile like `cargo check`: the dependencies are only analyzed (rustc emits their
+        // metadata but no code) and nothing is linked, since the driver stops after the analysis
+        // of the target crate anyway. We leave the profile alone if the user chose one.
+        let user_chose_profile = options
+            .cargo_args
+            .iter()
+            .any(|arg| arg == "--release" || arg.starts_with("--profile"));
+        if !user_chose_profile {
+            cmd.arg("--profile=check");
+            // Without code generation, rustc doesn't store the MIR of the functions in the
+            // metadata of the dependencies; ask for it if we may extract their bodies.
+            if options.extract_opaque_bodies
+                || !options.extract_bodies_from.is_empty()
+                || !options.include.is_empty()
+            {
+                cmd.env(
+                    "CARGO_ENCODED_RUSTFLAGS",
+                    rustflags_with("-Zalways-encode-mir", host),
+                );
+            }
+        }
+
         // Make sure the build target is explicitly set. This is needed to detect which crates are
         // proc-macro/build-script in `charon-driver`.
         cmd.arg("--target");
         cmd.arg(host);
 
+        // Use a separate target directory: since we change the compiler with
+        // `RUSTC_WORKSPACE_WRAPPER`, sharing the artifacts with the normal builds would make cargo
+        // rebuild the workspace each time one alternates between `cargo build` and `charon`.
+        let user_chose_target_dir = options
+            .cargo_args
+            .iter()
+            .any(|arg| arg.starts_with("--target-dir"));
+        if !user_chose_target_dir {
+            let target_dir = match &options.target_dir {
+                Some(target_dir) => target_dir.clone(),
+                None => default_target_dir(&options)?.join("charon"),
+            };
+            cmd.arg("--target-dir");
+            cmd.arg(target_dir);
+        }
+
         if options.lib {
             cmd.arg("--lib");
         }
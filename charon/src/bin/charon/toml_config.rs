@@ -2,7 +2,12 @@
 use serde::Deserialize;
 use std::path::PathBuf;
 
-use crate::{options::CliOpts, trace};
+use crate::{
+    options::{
+        CliOpts, ConstantSimplification, EmitKind, ExportProfile, MirInlining, SourceEmbedding,
+    },
+    trace,
+};
 
 /// The struct used to define the options available in `Charon.toml` files.
 #[derive(Debug, Deserialize)]
@@ -26,19 +31,107 @@ pub struct CharonTomlConfig {
     #[serde(default)]
     pub mir_optimized: bool,
     #[serde(default)]
+    pub mir_inlining: Option<MirInlining>,
+    #[serde(default)]
     pub polonius: bool,
     #[serde(default)]
     pub no_code_duplication: bool,
     #[serde(default)]
     pub extract_opaque_bodies: bool,
     #[serde(default)]
+    pub extract_bodies_from: Vec<String>,
+    #[serde(default)]
+    pub dependency_depth: Option<usize>,
+    #[serde(default)]
     pub include: Vec<String>,
     #[serde(default)]
     pub opaque: Vec<String>,
     #[serde(default)]
     pub exclude: Vec<String>,
     #[serde(default)]
+    pub roots: Vec<String>,
+    #[serde(default)]
+    pub harness_attrs: Vec<String>,
+    #[serde(default)]
     pub no_merge_goto_chains: bool,
+    #[serde(default)]
+    pub reconstruct_matches: bool,
+    #[serde(default)]
+    pub reconstruct_try: bool,
+    #[serde(default)]
+    pub extended_asserts: bool,
+    #[serde(default)]
+    pub preserve_discriminant_reads: bool,
+    #[serde(default)]
+    pub simplify_constants: Option<ConstantSimplification>,
+    #[serde(default)]
+    pub instantiate_blanket_impls: bool,
+    #[serde(default)]
+    pub max_duplication: Option<usize>,
+    #[serde(default)]
+    pub semi_structured: bool,
+    #[serde(default)]
+    pub borrow_liveness: bool,
+    #[serde(default)]
+    pub move_info: bool,
+    #[serde(default)]
+    pub debug_info: bool,
+    #[serde(default)]
+    pub statement_ranges: bool,
+    #[serde(default)]
+    pub comment_annotations: bool,
+    #[serde(default)]
+    pub annotate_recursive_fields: bool,
+    #[serde(default)]
+    pub name_temporaries: bool,
+    #[serde(default)]
+    pub keep_retags: bool,
+    #[serde(default)]
+    pub emit_types_only: bool,
+    #[serde(default)]
+    pub bodies_for: Vec<String>,
+    #[serde(default)]
+    pub max_body_statements: Option<usize>,
+    #[serde(default)]
+    pub per_item_timeout: Option<u64>,
+    #[serde(default)]
+    pub max_errors: Option<usize>,
+    #[serde(default)]
+    pub error_summary: bool,
+    #[serde(default)]
+    pub export_profile: Option<ExportProfile>,
+    #[serde(default)]
+    pub emit: Vec<EmitKind>,
+    #[serde(default)]
+    pub per_module_output: bool,
+    #[serde(default)]
+    pub report_cycles: bool,
+    #[serde(default)]
+    pub deterministic: bool,
+    #[serde(default)]
+    pub profile_extraction: Option<PathBuf>,
+    #[serde(default)]
+    pub embed_sources: Option<SourceEmbedding>,
+    #[serde(default)]
+    pub model_files: Vec<PathBuf>,
+    #[serde(default)]
+    pub link_files: Vec<PathBuf>,
+    #[serde(default)]
+    pub opaque_derives: Vec<String>,
+    #[serde(default)]
+    pub builtin_derives: Vec<String>,
+    #[serde(default)]
+    pub devirtualize_closure_calls: bool,
+    #[serde(default)]
+    pub async_builtins: bool,
+    #[serde(default)]
+    pub target_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub extract_dependencies: Vec<String>,
+    #[serde(default)]
+    pub keep_main_shims: bool,
+    #[serde(default)]
+    pub crate_versions_in_names: bool,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -58,11 +151,77 @@ impl TomlConfig {
         config.use_polonius |= self.charon.polonius;
         config.no_code_duplication |= self.charon.no_code_duplication;
         config.extract_opaque_bodies |= self.charon.extract_opaque_bodies;
+        config
+            .extract_bodies_from
+            .extend(self.charon.extract_bodies_from);
+        config.dependency_depth = config.dependency_depth.or(self.charon.dependency_depth);
         config.include.extend(self.charon.include);
         config.opaque.extend(self.charon.opaque);
         config.exclude.extend(self.charon.exclude);
+        config.roots.extend(self.charon.roots);
+        config.harness_attrs.extend(self.charon.harness_attrs);
         config.rustc_args.extend(self.rustc.flags);
         config.no_merge_goto_chains |= self.charon.no_merge_goto_chains;
+        config.reconstruct_matches |= self.charon.reconstruct_matches;
+        config.reconstruct_try |= self.charon.reconstruct_try;
+        config.extended_asserts |= self.charon.extended_asserts;
+        config.preserve_discriminant_reads |= self.charon.preserve_discriminant_reads;
+        config.instantiate_blanket_impls |= self.charon.instantiate_blanket_impls;
+        config.max_duplication = config.max_duplication.or(self.charon.max_duplication);
+        config.semi_structured |= self.charon.semi_structured;
+        config.borrow_liveness |= self.charon.borrow_liveness;
+        config.move_info |= self.charon.move_info;
+        config.debug_info |= self.charon.debug_info;
+        config.statement_ranges |= self.charon.statement_ranges;
+        config.comment_annotations |= self.charon.comment_annotations;
+        config.annotate_recursive_fields |= self.charon.annotate_recursive_fields;
+        config.name_temporaries |= self.charon.name_temporaries;
+        config.keep_retags |= self.charon.keep_retags;
+        config.emit_types_only |= self.charon.emit_types_only;
+        config.bodies_for.extend(self.charon.bodies_for);
+        config.max_body_statements = config
+            .max_body_statements
+            .or(self.charon.max_body_statements);
+        config.per_item_timeout = config.per_item_timeout.or(self.charon.per_item_timeout);
+        config.max_errors = config.max_errors.or(self.charon.max_errors);
+        config.error_summary |= self.charon.error_summary;
+        if config.mir_inlining == MirInlining::Off {
+            if let Some(mir_inlining) = self.charon.mir_inlining {
+                config.mir_inlining = mir_inlining;
+            }
+        }
+        if config.simplify_constants == ConstantSimplification::All {
+            if let Some(simplify_constants) = self.charon.simplify_constants {
+                config.simplify_constants = simplify_constants;
+            }
+        }
+        if config.export_profile == ExportProfile::Default {
+            if let Some(profile) = self.charon.export_profile {
+                config.export_profile = profile;
+            }
+        }
+        config.emit.extend(self.charon.emit);
+        config.per_module_output |= self.charon.per_module_output;
+        config.report_cycles |= self.charon.report_cycles;
+        config.deterministic |= self.charon.deterministic;
+        config.profile_extraction = config.profile_extraction.or(self.charon.profile_extraction);
+        if config.embed_sources == SourceEmbedding::All {
+            if let Some(embed_sources) = self.charon.embed_sources {
+                config.embed_sources = embed_sources;
+            }
+        }
+        config.model_files.extend(self.charon.model_files);
+        config.link_files.extend(self.charon.link_files);
+        config.opaque_derives.extend(self.charon.opaque_derives);
+        config.builtin_derives.extend(self.charon.builtin_derives);
+        config.devirtualize_closure_calls |= self.charon.devirtualize_closure_calls;
+        config.async_builtins |= self.charon.async_builtins;
+        config.target_dir = config.target_dir.or(self.charon.target_dir);
+        config
+            .extract_dependencies
+            .extend(self.charon.extract_dependencies);
+        config.keep_main_shims |= self.charon.keep_main_shims;
+        config.crate_versions_in_names |= self.charon.crate_versions_in_names;
         config
     }
 }
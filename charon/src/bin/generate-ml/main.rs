@@ -153,12 +153,28 @@ impl<'a> GenerateCtx<'a> {
             }
             name_to_type.insert(long_name, ty);
 
+            // We skip the opaque fields and variants: the types which only appear there are not
+            // needed on the OCaml side.
             let mut contained = HashSet::new();
-            ty.drive(&mut Ty::visit_inside(visitor_enter_fn(
-                |id: &TypeDeclId| {
-                    contained.insert(*id);
-                },
-            )));
+            let mut visitor = Ty::visit_inside(visitor_enter_fn(|id: &TypeDeclId| {
+                contained.insert(*id);
+            }));
+            match &ty.kind {
+                TypeDeclKind::Struct(fields) | TypeDeclKind::Union(fields) => {
+                    for field in fields.iter().filter(|f| !f.is_opaque()) {
+                        field.drive(&mut visitor);
+                    }
+                }
+                TypeDeclKind::Enum(variants) => {
+                    for variant in variants.iter().filter(|v| !v.is_opaque()) {
+                        for field in variant.fields.iter().filter(|f| !f.is_opaque()) {
+                            field.drive(&mut visitor);
+                        }
+                    }
+                }
+                _ => ty.drive(&mut visitor),
+            }
+            drop(visitor);
             type_tree.insert(ty.def_id, contained);
         }
         let contains_raw_span = {
@@ -1107,6 +1123,32 @@ fn generate_ml(
                 "#,
             ),
         ),
+        // Hand-written because `macro_origin` is omitted when it is `None`.
+        (
+            "Span",
+            indoc!(
+                r#"
+                | `Assoc [ ("span", span); ("generated_from_span", generated_from_span) ] ->
+                    let* span = raw_span_of_json id_to_file span in
+                    let* generated_from_span =
+                      option_of_json (raw_span_of_json id_to_file) generated_from_span
+                    in
+                    Ok ({ span; generated_from_span; macro_origin = None } : span)
+                | `Assoc
+                    [
+                      ("span", span);
+                      ("generated_from_span", generated_from_span);
+                      ("macro_origin", macro_origin);
+                    ] ->
+                    let* span = raw_span_of_json id_to_file span in
+                    let* generated_from_span =
+                      option_of_json (raw_span_of_json id_to_file) generated_from_span
+                    in
+                    let* macro_origin = option_of_json macro_origin_of_json macro_origin in
+                    Ok ({ span; generated_from_span; macro_origin } : span)
+                "#
+            ),
+        ),
         // Hand-written because the rust version is an enum with custom (de)serialization
         // functions.
         (
@@ -1265,8 +1307,10 @@ fn generate_ml(
                     ],
                 })), &[
                     "FnOperand",
+                    "FnPtrSig",
                     "Call",
                     "Assert",
+                    "RetagKind",
                 ]),
                 (GenerationKind::TypeDecl(None), &[
                     "ParamsInfo",
@@ -1274,11 +1318,20 @@ fn generate_ml(
                     "ClosureInfo",
                     "FunSig",
                     "ItemKind",
+                    "Linkage",
+                    "RuntimeHandlers",
+                    "TypeHead",
+                    "TraitImplIndex",
                     "GExprBody",
+                    "DynCompatibilityViolation",
                     "TraitDecl",
                     "TraitImpl",
+                    "ImplObligation",
+                    "ObligationOrigin",
                     "GDeclarationGroup",
                     "DeclarationGroup",
+                    "DependencyKind",
+                    "Dependency",
                 ]),
                 (GenerationKind::TypeDecl(None), &["Var", "AnyTransId", "FunDeclId"]),
             ]),
@@ -1303,13 +1356,17 @@ fn generate_ml(
                     ],
                 })), &[
                     "BuiltinIndexOp",
+                    "AsyncBuiltin",
                     "BuiltinFunId",
                     "BorrowKind",
                     "BinOp",
+                    "BinOpTys",
                     "FieldProjKind",
                     "ProjectionElem",
                     "Projection",
                     "Place",
+                    "Layout",
+                    "TransmuteLayouts",
                     "CastKind",
                     "UnOp",
                     "NullOp",
@@ -1333,10 +1390,16 @@ fn generate_ml(
                     "FileName",
                     "RawSpan",
                     "Span",
+                    "MacroOrigin",
                     "InlineAttr",
                     "Attribute",
                     "RawAttribute",
                     "AttrInfo",
+                    "Visibility",
+                    "Stability",
+                    "CodegenAttrs",
+                    "Provenance",
+                    "Annotation",
                 ]),
             ]),
         },
@@ -1422,8 +1485,10 @@ fn generate_ml(
                     "Name",
                     "ItemMeta",
                     "Field",
+                    "FieldRecursion",
                     "Variant",
                     "TypeDeclKind",
+                    "UnpinKind",
                     "TypeDecl",
                 ]),
             ]),
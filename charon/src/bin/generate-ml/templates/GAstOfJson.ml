@@ -241,16 +241,34 @@ and gcrate_of_json
     (body_of_json : id_to_file_map -> json -> ('body gexpr_body, string) result)
     (js : json) : (('body, 'body gexpr_body option) gcrate, string) result =
   match js with
-  | `Assoc [ ("charon_version", charon_version); ("translated", translated) ] ->
+  | `Assoc
+      [
+        ("charon_version", charon_version);
+        ("build_info", build_info);
+        ("translated", translated);
+      ] ->
       (* Ensure the version is the one we support. *)
       let* charon_version = string_of_json charon_version in
       if
         not (String.equal charon_version CharonVersion.supported_charon_version)
       then
+        (* The exact versions of charon and rustc which produced the file. *)
+        let build_info =
+          let or_unknown default = function
+            | `String s -> s
+            | _ -> default
+          in
+          match build_info with
+          | `Assoc [ ("charon_commit", commit); ("rustc_version", rustc) ] ->
+              "commit " ^ or_unknown "unknown" commit ^ ", "
+              ^ or_unknown "unknown rustc" rustc
+          | _ -> "unknown build"
+        in
         Error
           ("Incompatible version of charon: this program supports llbc emitted \
             by charon v" ^ CharonVersion.supported_charon_version
          ^ " but attempted to read a file emitted by charon v" ^ charon_version
-         ^ ".")
+         ^ " (" ^ build_info
+         ^ "). Regenerate the file with the same version of charon.")
       else gtranslated_crate_of_json body_of_json translated
   | _ -> combine_error_msgs js __FUNCTION__ (Error "")
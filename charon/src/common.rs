@@ -114,8 +114,8 @@ pub mod hash_consing {
     use derive_visitor::{Drive, DriveMut, Event, Visitor, VisitorMut};
     use itertools::Either;
     use serde::{Deserialize, Serialize};
-    use std::collections::HashMap;
-    use std::hash::Hash;
+    use std::collections::hash_map::{DefaultHasher, HashMap};
+    use std::hash::{Hash, Hasher};
     use std::sync::{Arc, LazyLock, RwLock};
 
     /// Hash-consed data structure: a reference-counted wrapper that guarantees that two equal
@@ -138,52 +138,164 @@ pub mod hash_consing {
             Self::intern(Either::Left(inner))
         }
 
-        /// Clones if needed to get mutable access to the inner value.
+        /// Gives mutable access to a copy of the inner value, and re-interns it if it changed.
+        /// Many traversals through `DriveMut` don't change anything, so we compare the values
+        /// rather than going through the intern table every time. Comparing is cheap in the
+        /// common case: the unchanged sub-values are still shared, hence compared by address.
         pub fn with_inner_mut<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R {
-            let kind = Arc::make_mut(&mut self.0);
-            let ret = f(kind);
-            // Re-establish sharing, crucial for the hashing function to be correct.
-            *self = Self::intern(Either::Right(self.0.clone()));
+            let mut inner = self.inner().clone();
+            let ret = f(&mut inner);
+            if inner != *self.inner() {
+                // Re-establish sharing, crucial for the hashing function to be correct.
+                *self = Self::intern(Either::Left(inner));
+            }
             ret
         }
 
         /// Deduplicate the valuess by hashing them. This deduplication is crucial for the hashing
         /// function to be correct. This is the only function allowed to create `Self` values.
         fn intern(inner: Either<T, Arc<T>>) -> Self {
-            struct InternMapper;
-            impl Mapper for InternMapper {
-                type Value<T: Mappable> = HashMap<T, Arc<T>>;
+            let value = inner.as_ref().either(|x| x, |x| x.as_ref());
+            let shard = InternTable::<T>::get().shard(value);
+            if let Some(arc) = shard.read().unwrap().get(value) {
+                return Self(arc.clone());
+            }
+            // We clone the value here in the slow path, which makes it possible to avoid an
+            // allocation in the fast path.
+            let raw_val: T = value.clone();
+            let arc: Arc<T> = inner.either(Arc::new, |x| x);
+            // Another thread may have interned the same value since we released the read lock, in
+            // which case we must use its copy.
+            let arc = shard.write().unwrap().entry(raw_val).or_insert(arc).clone();
+            Self(arc)
+        }
+    }
+
+    /// The number of shards of each intern table. Each shard has its own lock, so that the
+    /// threads which intern different values rarely wait for each other.
+    const SHARDS: usize = 16;
+
+    /// The interned values of type `T`, split in shards according to their hash.
+    struct InternTable<T> {
+        shards: [RwLock<HashMap<T, Arc<T>>>; SHARDS],
+    }
+
+    struct InternMapper;
+    impl Mapper for InternMapper {
+        type Value<T: Mappable> = &'static InternTable<T>;
+    }
+
+    /// The intern tables of all the types, which we never free. We also keep them in a list to
+    /// implement [clear_unused] and [stats], which don't know the types.
+    struct Registry {
+        tables: TypeMap<InternMapper>,
+        all: Vec<&'static dyn AnyInternTable>,
+    }
+
+    static REGISTRY: LazyLock<RwLock<Registry>> = LazyLock::new(|| {
+        RwLock::new(Registry {
+            tables: Default::default(),
+            all: Vec::new(),
+        })
+    });
+
+    impl<T: Hash + Eq + Mappable> InternTable<T> {
+        /// The intern table of type `T`.
+        fn get() -> &'static Self {
+            if let Some(table) = REGISTRY.read().unwrap().tables.get::<T>() {
+                return *table;
+            }
+            let mut registry = REGISTRY.write().unwrap();
+            // Another thread may have created the table since we released the read lock.
+            if let Some(table) = registry.tables.get::<T>() {
+                return *table;
+            }
+            let table: &'static Self = Box::leak(Box::new(InternTable {
+                shards: Default::default(),
+            }));
+            registry.tables.insert::<T>(table);
+            registry.all.push(table);
+            table
+        }
+
+        fn shard(&self, value: &T) -> &RwLock<HashMap<T, Arc<T>>> {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            &self.shards[hasher.finish() as usize % SHARDS]
+        }
+    }
+
+    /// The operations on the intern tables which don't depend on their type.
+    trait AnyInternTable: Send + Sync {
+        fn clear_unused(&self) -> usize;
+        fn stats(&self) -> InternStats;
+    }
+
+    impl<T: Hash + Eq + Mappable> AnyInternTable for InternTable<T> {
+        fn clear_unused(&self) -> usize {
+            let mut removed = 0;
+            for shard in &self.shards {
+                let mut shard = shard.write().unwrap();
+                let len = shard.len();
+                // The table holds one reference; nobody else can get one while we hold the lock.
+                shard.retain(|_, arc| Arc::strong_count(arc) > 1);
+                removed += len - shard.len();
             }
-            static INTERNED: LazyLock<RwLock<TypeMap<InternMapper>>> =
-                LazyLock::new(|| Default::default());
+            removed
+        }
 
-            if INTERNED.read().unwrap().get::<T>().is_none() {
-                INTERNED.write().unwrap().insert::<T>(Default::default());
+        fn stats(&self) -> InternStats {
+            let mut stats = InternStats {
+                type_name: std::any::type_name::<T>(),
+                values: 0,
+                unused: 0,
+            };
+            for shard in &self.shards {
+                let shard = shard.read().unwrap();
+                stats.values += shard.len();
+                stats.unused += shard
+                    .values()
+                    .filter(|arc| Arc::strong_count(arc) == 1)
+                    .count();
             }
-            let read_guard = INTERNED.read().unwrap();
-            if let Some(inner) = (*read_guard)
-                .get::<T>()
-                .unwrap()
-                .get(inner.as_ref().either(|x| x, |x| x.as_ref()))
-            {
-                Self(inner.clone())
-            } else {
-                drop(read_guard);
-                // We clone the value here in the slow path, which makes it possible to avoid an
-                // allocation in the fast path.
-                let raw_val: T = inner.as_ref().either(T::clone, |x| x.as_ref().clone());
-                let arc: Arc<T> = inner.either(Arc::new, |x| x);
-                INTERNED
-                    .write()
-                    .unwrap()
-                    .get_mut::<T>()
-                    .unwrap()
-                    .insert(raw_val, arc.clone());
-                Self(arc)
+            stats
+        }
+    }
+
+    /// The statistics of the intern table of a type (see [stats]).
+    #[derive(Debug, Clone)]
+    pub struct InternStats {
+        pub type_name: &'static str,
+        /// The number of interned values.
+        pub values: usize,
+        /// The number of interned values which are not used anymore, i.e. which [clear_unused]
+        /// would remove.
+        pub unused: usize,
+    }
+
+    /// Remove from the intern tables the values which are not used anymore, and return how many
+    /// we removed. The values still in use must stay in the tables for the hash-consing to be
+    /// correct, so this is the way to free memory in long-running programs which translate
+    /// several crates.
+    pub fn clear_unused() -> usize {
+        let tables = REGISTRY.read().unwrap().all.clone();
+        let mut total = 0;
+        // Removing a value may make the values it contains unused, possibly in other tables.
+        loop {
+            let removed: usize = tables.iter().map(|table| table.clear_unused()).sum();
+            if removed == 0 {
+                return total;
             }
+            total += removed;
         }
     }
 
+    /// The statistics of the intern tables, e.g. to monitor their memory usage.
+    pub fn stats() -> Vec<InternStats> {
+        let tables = REGISTRY.read().unwrap().all.clone();
+        tables.iter().map(|table| table.stats()).collect()
+    }
+
     /// Hash the pointer; this is only correct if two identical values of `Self` are guaranteed to
     /// point to the same memory location, which we carefully enforce above.
     impl<T> std::hash::Hash for HashConsed<T> {
@@ -203,7 +315,8 @@ pub mod hash_consing {
         }
     }
 
-    /// Note: this explores the full tree mutably by cloning and re-hashing afterwards.
+    /// Note: this explores the full tree mutably by cloning, and re-hashes the values which changed
+    /// (see [HashConsed::with_inner_mut]).
     impl<T> DriveMut for HashConsed<T>
     where
         T: DriveMut + Hash + PartialEq + Eq + Clone + Mappable,
@@ -278,15 +391,25 @@ pub mod visitor_event {
 // This is the amount of bytes that need to be left on the stack before increasing the size. It
 // must be at least as large as the stack required by any code that does not call
 // `ensure_sufficient_stack`.
+#[cfg(not(target_arch = "wasm32"))]
 const RED_ZONE: usize = 100 * 1024; // 100k
 
 // Only the first stack that is pushed, grows exponentially (2^n * STACK_PER_RECURSION) from then
 // on. Values taken from rustc.
+#[cfg(not(target_arch = "wasm32"))]
 const STACK_PER_RECURSION: usize = 1024 * 1024; // 1MB
 
 /// Grows the stack on demand to prevent stack overflow. Call this in strategic locations to "break
 /// up" recursive calls. E.g. most statement visitors can benefit from this.
 #[inline]
+#[cfg(not(target_arch = "wasm32"))]
 pub fn ensure_sufficient_stack<R>(f: impl FnOnce() -> R) -> R {
     stacker::maybe_grow(RED_ZONE, STACK_PER_RECURSION, f)
 }
+
+/// `stacker` doesn't support wasm, so we can't grow the stack there.
+#[inline]
+#[cfg(target_arch = "wasm32")]
+pub fn ensure_sufficient_stack<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
@@ -1,7 +1,7 @@
 //! Utilities to generate error reports about the external dependencies.
 use crate::ast::{AnyTransId, Span};
 use std::cmp::{Ord, PartialOrd};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// Common error used during the translation.
 #[derive(Debug)]
@@ -88,6 +88,74 @@ pub struct ErrorCtx<'ctx> {
     pub def_id_is_local: bool,
     /// The number of errors encountered so far.
     pub error_count: usize,
+    /// The number of errors encountered while translating each item.
+    pub item_error_counts: HashMap<AnyTransId, usize>,
+    /// The number of errors after which we stop translating (see `--max-errors`).
+    pub max_errors: Option<usize>,
+    /// Whether we stopped translating because we reached `max_errors`.
+    pub budget_exhausted: bool,
+}
+
+/// The categories of errors, by how much of the crate they cost (see [ErrorCtx::summary]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorCategory {
+    /// Errors in an item which we still translated: only (parts of) its body may be missing.
+    Body,
+    /// Errors because of which we dropped the item.
+    Item,
+    /// Errors which don't belong to a specific item.
+    Other,
+}
+
+impl ErrorCategory {
+    /// Whether the errors of this category made us lose more than bodies.
+    pub fn is_hard(self) -> bool {
+        !matches!(self, ErrorCategory::Body)
+    }
+}
+
+/// A summary of the errors of a translation.
+#[derive(Debug, Default)]
+pub struct ErrorSummary {
+    /// The number of errors in each category.
+    pub counts: BTreeMap<ErrorCategory, usize>,
+    /// The items with errors, with their number of errors, by decreasing number of errors.
+    pub items: Vec<(AnyTransId, ErrorCategory, usize)>,
+    /// Whether we stopped translating because there were too many errors.
+    pub budget_exhausted: bool,
+}
+
+impl ErrorSummary {
+    /// Whether we lost more than bodies because of the errors.
+    pub fn has_hard_errors(&self) -> bool {
+        self.budget_exhausted || self.counts.keys().any(|category| category.is_hard())
+    }
+
+    /// Format the summary as a table, listing at most `max_items` items.
+    pub fn to_table(&self, f: &crate::formatter::FmtCtx<'_>, max_items: usize) -> String {
+        use crate::formatter::Formatter;
+        use std::fmt::Write;
+
+        let mut out = String::from("Error summary:\n");
+        for (category, count) in &self.counts {
+            let _ = writeln!(
+                out,
+                "  {:<8} {count}",
+                format!("{category:?}").to_lowercase()
+            );
+        }
+        if self.budget_exhausted {
+            let _ = writeln!(out, "  (stopped early: too many errors)");
+        }
+        if !self.items.is_empty() {
+            let _ = writeln!(out, "Items with the most errors:");
+            for (id, category, count) in self.items.iter().take(max_items) {
+                let category = format!("{category:?}").to_lowercase();
+                let _ = writeln!(out, "  {count:>4} ({category}) {}", f.format_object(*id));
+            }
+        }
+        out
+    }
 }
 
 impl ErrorCtx<'_> {
@@ -97,6 +165,10 @@ impl ErrorCtx<'_> {
     pub(crate) fn has_errors(&self) -> bool {
         self.error_count > 0
     }
+    /// Whether we reached the maximum number of errors (see `--max-errors`).
+    pub fn is_budget_exhausted(&self) -> bool {
+        self.max_errors.is_some_and(|max| self.error_count >= max)
+    }
 
     /// Report an error without registering anything.
     #[cfg(feature = "rustc")]
@@ -126,6 +198,9 @@ impl ErrorCtx<'_> {
     pub fn span_err(&mut self, span: Span, msg: &str) {
         self.span_err_no_register(span, msg);
         self.error_count += 1;
+        if let Some(id) = self.def_id {
+            *self.item_error_counts.entry(id).or_default() += 1;
+        }
         if let Some(id) = self.def_id
             && !self.def_id_is_local
         {
@@ -136,6 +211,31 @@ impl ErrorCtx<'_> {
     pub fn ignore_failed_decl(&mut self, id: AnyTransId) {
         self.ignored_failed_decls.insert(id);
     }
+
+    /// Summarize the errors by category and by item.
+    pub fn summary(&self) -> ErrorSummary {
+        let mut summary = ErrorSummary {
+            budget_exhausted: self.budget_exhausted,
+            ..ErrorSummary::default()
+        };
+        for (&id, &count) in &self.item_error_counts {
+            let category = if self.ignored_failed_decls.contains(&id) {
+                ErrorCategory::Item
+            } else {
+                ErrorCategory::Body
+            };
+            *summary.counts.entry(category).or_default() += count;
+            summary.items.push((id, category, count));
+        }
+        let other = self.error_count - self.item_error_counts.values().sum::<usize>();
+        if other > 0 {
+            summary.counts.insert(ErrorCategory::Other, other);
+        }
+        summary
+            .items
+            .sort_by_key(|(id, _, count)| (std::cmp::Reverse(*count), *id));
+        summary
+    }
 }
 
 impl ErrorCtx<'_> {
@@ -1,9 +1,16 @@
 use crate::ast::*;
+use crate::options::ExportProfile;
 use crate::transform::TransformCtx;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::fs::File;
+use std::io::Write;
 use std::path::Path;
 
+pub mod metrics;
+pub mod ocaml;
+pub mod source_index;
+pub mod split;
+
 /// The data of a generic crate. We serialize this to pass it to `charon-ml`, so this must be as
 /// stable as possible. This is used for both ULLBC and LLBC.
 #[derive(Serialize, Deserialize)]
@@ -13,24 +20,73 @@ pub struct CrateData {
     /// trying to read an incompatible version (for now we compare versions for equality).
     #[serde(deserialize_with = "ensure_version")]
     pub charon_version: String,
+    /// The exact versions of charon and rustc which produced the file, to help diagnose the
+    /// mismatches (see [crate::import]).
+    pub build_info: BuildInfo,
     pub translated: TranslatedCrate,
     #[serde(skip)]
     /// If there were errors, this contains only a partial description of the input crate.
     pub has_errors: bool,
 }
 
+/// The versions of the tools which produced a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// The git commit charon was built from, if it was built from a git checkout.
+    pub charon_commit: Option<String>,
+    /// The version of rustc used to compile the crate, as printed by `rustc --version`.
+    pub rustc_version: Option<String>,
+}
+
+impl BuildInfo {
+    /// The versions of the current build of charon.
+    pub fn current() -> Self {
+        BuildInfo {
+            charon_commit: option_env!("CHARON_COMMIT").map(str::to_owned),
+            rustc_version: option_env!("CHARON_RUSTC_VERSION").map(str::to_owned),
+        }
+    }
+}
+
+impl std::fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let commit = self.charon_commit.as_deref().unwrap_or("unknown");
+        let rustc = self.rustc_version.as_deref().unwrap_or("unknown rustc");
+        write!(f, "commit {commit}, {rustc}")
+    }
+}
+
 impl CrateData {
     pub fn new(ctx: &TransformCtx) -> Self {
         CrateData {
             charon_version: crate::VERSION.to_owned(),
+            build_info: BuildInfo::current(),
             translated: ctx.translated.clone(),
             has_errors: ctx.has_errors(),
         }
     }
 
-    /// Export the translated definitions to a JSON file.
+    /// Serialize the translated definitions to JSON, with the shape given by `profile`.
+    pub fn serialize_to_writer(
+        &self,
+        writer: impl Write,
+        profile: ExportProfile,
+    ) -> serde_json::Result<()> {
+        match profile {
+            ExportProfile::Default => serde_json::to_writer(writer, self),
+            ExportProfile::Ocaml => {
+                ocaml::to_value(self).and_then(|value| serde_json::to_writer(writer, &value))
+            }
+        }
+    }
+
+    /// Export the translated definitions to a JSON file, with the shape given by `profile`.
     #[allow(clippy::result_unit_err)]
-    pub fn serialize_to_file(&self, target_filename: &Path) -> Result<(), ()> {
+    pub fn serialize_to_file(
+        &self,
+        target_filename: &Path,
+        profile: ExportProfile,
+    ) -> Result<(), ()> {
         // Create the directory, if necessary (note that if the target directory
         // is not specified, there is no need to create it: otherwise we
         // couldn't have read the input file in the first place).
@@ -49,7 +105,7 @@ impl CrateData {
             return Err(());
         };
         // Write to the file.
-        match serde_json::to_writer(&outfile, self) {
+        match self.serialize_to_writer(&outfile, profile) {
             Ok(()) => {}
             Err(err) => {
                 error!("Could not write to `{target_filename:?}`: {err:?}");
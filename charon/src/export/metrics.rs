@@ -0,0 +1,125 @@
+//! Complexity metrics of the functions of a crate (see `--emit metrics`), which give an idea of
+//! the effort needed to verify them.
+use crate::ast::*;
+use crate::formatter::IntoFormatter;
+use crate::llbc_ast;
+use crate::pretty::FmtWithCtx;
+use crate::ullbc_ast;
+use derive_visitor::{visitor_enter_fn, Drive};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::Path;
+
+/// The metrics of the functions of a crate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateMetrics {
+    pub functions: Vec<FunMetrics>,
+}
+
+/// The metrics of a function.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunMetrics {
+    pub id: FunDeclId,
+    pub name: String,
+    /// The number of type and const generic parameters.
+    pub generic_arity: usize,
+    /// `None` if the function has no body (e.g. because it is opaque).
+    pub body: Option<BodyMetrics>,
+}
+
+/// The metrics of the body of a function.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct BodyMetrics {
+    /// The number of blocks: the basic blocks in ULLBC, the nested blocks (the branches of the
+    /// `if`s and `match`es, the bodies of the loops, etc.) in LLBC.
+    pub blocks: usize,
+    /// The number of statements. In LLBC this includes the statements which contain other
+    /// statements, like `loop`s and `match`es.
+    pub statements: usize,
+    /// The number of loops. In ULLBC these are the natural loops of the control-flow graph.
+    pub loops: usize,
+    /// The maximum number of branches of a switch.
+    pub max_switch_fan_out: usize,
+}
+
+impl BodyMetrics {
+    fn of_unstructured(body: &ullbc_ast::ExprBody) -> Self {
+        let mut metrics = BodyMetrics {
+            blocks: body.body.len(),
+            loops: body.compute_cfg_info().loop_headers.len(),
+            ..Default::default()
+        };
+        for block in body.body.iter() {
+            metrics.statements += block.statements.len();
+            if let ullbc_ast::RawTerminator::Switch { targets, .. } = &block.terminator.content {
+                let fan_out = targets.get_targets().len();
+                metrics.max_switch_fan_out = metrics.max_switch_fan_out.max(fan_out);
+            }
+        }
+        metrics
+    }
+
+    fn of_structured(body: &llbc_ast::ExprBody) -> Self {
+        let mut metrics = BodyMetrics::default();
+        body.body
+            .drive(&mut visitor_enter_fn(|_: &llbc_ast::Block| {
+                metrics.blocks += 1
+            }));
+        body.body
+            .drive(&mut visitor_enter_fn(|st: &llbc_ast::Statement| {
+                metrics.statements += 1;
+                match &st.content {
+                    llbc_ast::RawStatement::Loop(_) => metrics.loops += 1,
+                    llbc_ast::RawStatement::Switch(switch) => {
+                        let fan_out = switch.iter_targets().count();
+                        metrics.max_switch_fan_out = metrics.max_switch_fan_out.max(fan_out);
+                    }
+                    _ => {}
+                }
+            }));
+        metrics
+    }
+}
+
+impl CrateMetrics {
+    pub fn compute(krate: &TranslatedCrate) -> Self {
+        let fmt_ctx = krate.into_fmt();
+        let functions = krate
+            .fun_decls
+            .iter()
+            .map(|decl| {
+                let body = decl
+                    .body
+                    .ok()
+                    .and_then(|body_id| krate.bodies.get(body_id))
+                    .map(|body| match body {
+                        Body::Unstructured(body) => BodyMetrics::of_unstructured(body),
+                        Body::Structured(body) => BodyMetrics::of_structured(body),
+                    });
+                let generics = &decl.signature.generics;
+                FunMetrics {
+                    id: decl.def_id,
+                    name: decl.item_meta.name.fmt_with_ctx(&fmt_ctx),
+                    generic_arity: generics.types.len() + generics.const_generics.len(),
+                    body,
+                }
+            })
+            .collect();
+        CrateMetrics { functions }
+    }
+
+    /// Export the metrics to a JSON file.
+    #[allow(clippy::result_unit_err)]
+    pub fn serialize_to_file(&self, target_filename: &Path) -> Result<(), ()> {
+        let std::io::Result::Ok(outfile) = File::create(target_filename) else {
+            error!("Could not open: {:?}", target_filename);
+            return Err(());
+        };
+        if let Err(err) = serde_json::to_writer(&outfile, self) {
+            error!("Could not write to `{target_filename:?}`: {err:?}");
+            return Err(());
+        }
+        info!("Generated the file: {}", target_filename.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+//! An index of the items by source location (see `--emit source-index`), so that editors can map
+//! a cursor location to the corresponding extracted item without parsing names.
+use crate::ast::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+
+/// The items of a crate, grouped by the file in which they are defined.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceIndex {
+    pub files: Vec<FileIndex>,
+}
+
+/// The items defined in a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileIndex {
+    pub file: FileName,
+    /// The items, sorted by the beginning of their span. The spans of nested items (e.g. the
+    /// methods of an impl block) are included in the spans of the items which contain them.
+    pub items: Vec<IndexEntry>,
+}
+
+/// The source range of an item.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub item: AnyTransId,
+    pub beg: Loc,
+    pub end: Loc,
+}
+
+impl SourceIndex {
+    pub fn compute(krate: &TranslatedCrate) -> Self {
+        let mut files: BTreeMap<FileId, Vec<IndexEntry>> = BTreeMap::new();
+        for (id, item) in krate.all_items_with_ids() {
+            let span = item.item_meta().span.span;
+            // Skip the dummy spans and the spans which don't come from a real file.
+            if span.beg.line == 0
+                || matches!(
+                    krate.id_to_file.get(span.file_id),
+                    None | Some(FileName::NotReal(_))
+                )
+            {
+                continue;
+            }
+            files.entry(span.file_id).or_default().push(IndexEntry {
+                item: id,
+                beg: span.beg,
+                end: span.end,
+            });
+        }
+        let files = files
+            .into_iter()
+            .map(|(file_id, mut items)| {
+                // Sort the enclosing items before the items they contain.
+                items.sort_by(|a, b| a.beg.cmp(&b.beg).then(b.end.cmp(&a.end)));
+                FileIndex {
+                    file: krate.id_to_file[file_id].clone(),
+                    items,
+                }
+            })
+            .collect();
+        SourceIndex { files }
+    }
+
+    /// The items whose span contains the given (1-based) line of the file, innermost first.
+    pub fn items_at(&self, file: &FileName, line: usize) -> Vec<AnyTransId> {
+        let Some(file_index) = self.files.iter().find(|f| &f.file == file) else {
+            return Vec::new();
+        };
+        file_index
+            .items
+            .iter()
+            .rev()
+            .filter(|entry| entry.beg.line <= line && line <= entry.end.line)
+            .map(|entry| entry.item)
+            .collect()
+    }
+
+    /// Export the index to a JSON file.
+    #[allow(clippy::result_unit_err)]
+    pub fn serialize_to_file(&self, target_filename: &Path) -> Result<(), ()> {
+        let std::io::Result::Ok(outfile) = File::create(target_filename) else {
+            error!("Could not open: {:?}", target_filename);
+            return Err(());
+        };
+        if let Err(err) = serde_json::to_writer(&outfile, self) {
+            error!("Could not write to `{target_filename:?}`: {err:?}");
+            return Err(());
+        }
+        info!("Generated the file: {}", target_filename.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+//! Split a crate into one file per module, for `--per-module-output`.
+//!
+//! Each module file is a complete crate containing the items defined in that module; the items
+//! of other modules it refers to are kept as empty slots with their names, like items which
+//! failed to translate (see [TranslatedCrate::retain]). The index file lists the modules, their
+//! files and the items each module refers to in the other modules.
+use crate::ast::*;
+use crate::export::CrateData;
+use crate::formatter::IntoFormatter;
+use crate::options::ExportProfile;
+use crate::pretty::FmtWithCtx;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::path::Path;
+
+/// The index of a split crate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleIndex {
+    pub charon_version: String,
+    pub crate_name: String,
+    pub modules: Vec<ModuleEntry>,
+}
+
+/// A module of a split crate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleEntry {
+    /// The path of the module, e.g. `crate_name::foo::bar`.
+    pub module: String,
+    /// The file which contains the items of this module, relative to the index file.
+    pub file: String,
+    /// The names of the items defined in this module.
+    pub items: Vec<String>,
+    /// The names of the items of other modules which this module refers to, grouped by module.
+    pub references: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Compute the module which defines an item. We approximate it from the name of the item: this is
+/// the prefix of the name up to its first `impl` block, or the name without its last component.
+/// Items of traits and trait impls belong to the module of their trait or impl. Since names don't
+/// tell functions and modules apart, the items defined inside a function body get a module of
+/// their own, named after the function: e.g. `crate_name::foo::f` for `crate_name::foo::f::Inner`.
+pub fn item_module(krate: &TranslatedCrate, id: AnyTransId) -> Option<Name> {
+    let kind = match krate.get_item(id) {
+        Some(AnyTransItem::Fun(decl)) => Some(&decl.kind),
+        Some(AnyTransItem::Global(decl)) => Some(&decl.kind),
+        _ => None,
+    };
+    match kind {
+        Some(ItemKind::TraitDecl { trait_id, .. }) => {
+            return item_module(krate, (*trait_id).into())
+        }
+        Some(ItemKind::TraitImpl { impl_id, .. }) => return item_module(krate, (*impl_id).into()),
+        _ => {}
+    }
+    let name = &krate.item_name(id)?.name;
+    let len = match name.iter().position(|elem| elem.is_impl()) {
+        Some(i) => i,
+        None => name.len().saturating_sub(1),
+    };
+    Some(Name {
+        name: name[..len].to_vec(),
+    })
+}
+
+/// The name of the file for the given module.
+fn module_file_name(module: &str, extension: &str) -> String {
+    format!("{}.{extension}", module.replace("::", "-"))
+}
+
+impl CrateData {
+    /// Split the crate by module and write one file per module in `target_dir`, with the given
+    /// extension, along with an `index.json` file (see [ModuleIndex]).
+    #[allow(clippy::result_unit_err)]
+    pub fn serialize_per_module(
+        &self,
+        target_dir: &Path,
+        extension: &str,
+        profile: ExportProfile,
+    ) -> Result<(), ()> {
+        if std::fs::create_dir_all(target_dir).is_err() {
+            error!("Could not create the directory: {:?}", target_dir);
+            return Err(());
+        }
+        let krate = &self.translated;
+        let fmt_ctx = krate.into_fmt();
+
+        // Group the items by module.
+        let module_of: HashMap<AnyTransId, String> = krate
+            .item_names
+            .keys()
+            .filter_map(|id| Some((*id, item_module(krate, *id)?.fmt_with_ctx(&fmt_ctx))))
+            .collect();
+        let mut modules: BTreeMap<&str, Vec<AnyTransId>> = BTreeMap::new();
+        for id in &krate.all_ids {
+            if krate.get_item(*id).is_some()
+                && let Some(module) = module_of.get(id)
+            {
+                modules.entry(module).or_default().push(*id);
+            }
+        }
+
+        let mut index = ModuleIndex {
+            charon_version: self.charon_version.clone(),
+            crate_name: krate.crate_name.clone(),
+            modules: Vec::new(),
+        };
+        for (module, ids) in modules {
+            let mut references: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+            for id in &ids {
+                for referred in krate.item_references(*id) {
+                    if let Some(referred_module) = module_of.get(&referred)
+                        && referred_module != module
+                        && let Some(name) = krate.item_name(referred)
+                    {
+                        references
+                            .entry(referred_module.clone())
+                            .or_default()
+                            .insert(name.fmt_with_ctx(&fmt_ctx));
+                    }
+                }
+            }
+            let items = ids
+                .iter()
+                .filter_map(|id| Some(krate.item_name(*id)?.fmt_with_ctx(&fmt_ctx)))
+                .collect();
+
+            let translated =
+                krate.filtered(|id| module_of.get(&id).map(String::as_str) == Some(module));
+            let module_data = CrateData {
+                charon_version: self.charon_version.clone(),
+                build_info: self.build_info.clone(),
+                translated,
+                has_errors: self.has_errors,
+            };
+            let file = module_file_name(module, extension);
+            module_data.serialize_to_file(&target_dir.join(&file), profile)?;
+
+            index.modules.push(ModuleEntry {
+                module: module.to_owned(),
+                file,
+                items,
+                references,
+            });
+        }
+
+        let index_path = target_dir.join("index.json");
+        let std::io::Result::Ok(outfile) = File::create(&index_path) else {
+            error!("Could not open: {:?}", index_path);
+            return Err(());
+        };
+        if let Err(err) = serde_json::to_writer_pretty(&outfile, &index) {
+            error!("Could not write to `{index_path:?}`: {err:?}");
+            return Err(());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+//! Read the files produced by charon. We check that a file was produced by the version of charon
+//! this library comes from before parsing it, so that a mismatch is reported with the versions of
+//! charon and rustc involved instead of as an obscure parsing error.
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context};
+use serde::Deserialize;
+
+use crate::export::{BuildInfo, CrateData};
+
+/// The part of a file which tells which versions produced it. `build_info` is optional so that we
+/// can report the mismatches with the files of older versions.
+#[derive(Deserialize)]
+struct Header {
+    charon_version: String,
+    #[serde(default)]
+    build_info: Option<BuildInfo>,
+}
+
+/// Check that a file produced by charon `version` can be read by this version of charon. For now
+/// we compare the versions for equality.
+pub fn check_compatibility(version: &str, build_info: Option<&BuildInfo>) -> anyhow::Result<()> {
+    if version == crate::VERSION {
+        return Ok(());
+    }
+    let produced_by = match build_info {
+        Some(build_info) => format!("charon v{version} ({build_info})"),
+        None => format!("charon v{version}"),
+    };
+    bail!(
+        "incompatible version of charon: this program reads the files produced by charon v{} \
+        ({}), but the file was produced by {produced_by}. Regenerate the file with the same \
+        version of charon.",
+        crate::VERSION,
+        BuildInfo::current(),
+    )
+}
+
+/// Parse the contents of a `.llbc` or `.ullbc` file.
+pub fn crate_data_from_slice(bytes: &[u8]) -> anyhow::Result<CrateData> {
+    let header: Header = serde_json::from_slice(bytes)
+        .map_err(|err| anyhow!("this is not a file produced by charon: {err}"))?;
+    check_compatibility(&header.charon_version, header.build_info.as_ref())?;
+    Ok(serde_json::from_slice(bytes)?)
+}
+
+/// Read a `.llbc` or `.ullbc` file.
+pub fn read_crate_data(path: &Path) -> anyhow::Result<CrateData> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("could not open `{}`", path.display()))?;
+    crate_data_from_slice(&bytes).with_context(|| format!("could not parse `{}`", path.display()))
+}
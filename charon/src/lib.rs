@@ -43,10 +43,18 @@ pub mod ast;
 pub mod common;
 pub mod errors;
 pub mod export;
+pub mod import;
+pub mod lint;
+pub mod lower;
 pub mod name_matcher;
 pub mod options;
 pub mod pretty;
+pub mod profile;
+#[cfg(all(feature = "test-harness", not(target_arch = "wasm32")))]
+pub mod test_harness;
 pub mod transform;
+pub mod tyck;
+pub mod validate;
 
 // Re-export all the ast modules so we can keep the old import structure.
 pub use ast::{builtins, expressions, gast, llbc_ast, meta, names, types, ullbc_ast, values};
@@ -54,4 +62,4 @@ pub use pretty::formatter;
 pub use transform::{graphs, reorder_decls, ullbc_to_llbc};
 
 /// The version of the crate, as defined in `Cargo.toml`.
-const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,322 @@
+//! Report the uses of the constructs which the tools downstream of charon can't verify, from a
+//! list chosen by the user (see [Lint]). This runs on external files with `charon lint`.
+use crate::ast::*;
+use crate::formatter::{FmtCtx, IntoFormatter};
+use crate::llbc_ast;
+use crate::name_matcher::NamePattern;
+use crate::pretty::FmtWithCtx;
+use crate::ullbc_ast;
+use petgraph::algo::tarjan_scc;
+use petgraph::graphmap::DiGraphMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A kind of construct to report.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    clap::ValueEnum,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Lint {
+    /// The raw pointer types, and the creation of raw pointers.
+    RawPointers,
+    /// The transmutations, i.e. the calls to `transmute` and the transmute casts.
+    Transmute,
+    /// The types with interior mutability: `UnsafeCell`, `Cell`, `RefCell`, `OnceCell` and the
+    /// atomic types.
+    InteriorMutability,
+    /// The (mutually) recursive functions. This needs the declaration groups, which we only
+    /// compute for LLBC.
+    Recursion,
+    /// The loops. We don't try to tell whether a loop terminates, hence we report all of them.
+    UnboundedLoops,
+}
+
+impl Lint {
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::RawPointers => "raw-pointers",
+            Lint::Transmute => "transmute",
+            Lint::InteriorMutability => "interior-mutability",
+            Lint::Recursion => "recursion",
+            Lint::UnboundedLoops => "unbounded-loops",
+        }
+    }
+}
+
+/// A use of a denied construct.
+#[derive(Debug, Clone)]
+pub struct LintViolation {
+    pub lint: Lint,
+    /// The item in which we found the construct.
+    pub item: AnyTransId,
+    /// The span of the statement which uses the construct, or of the item if the construct is not
+    /// inside a statement (e.g. in the signature of a function).
+    pub span: Span,
+    pub message: String,
+}
+
+/// The functions which transmute their argument.
+const TRANSMUTE_FUNS: &[&str] = &[
+    "core::intrinsics::transmute",
+    "core::intrinsics::transmute_unchecked",
+    "core::mem::transmute_copy",
+];
+
+/// The types with interior mutability.
+const INTERIOR_MUTABILITY_TYPES: &[&str] = &[
+    "core::cell::UnsafeCell",
+    "core::cell::Cell",
+    "core::cell::RefCell",
+    "core::cell::OnceCell",
+    "core::sync::atomic",
+];
+
+/// Report the uses of the given constructs in the crate.
+pub fn lint_crate(krate: &TranslatedCrate, deny: &[Lint]) -> Vec<LintViolation> {
+    let deny: HashSet<Lint> = deny.iter().copied().collect();
+    let parse = |patterns: &[&str]| -> Vec<NamePattern> {
+        patterns
+            .iter()
+            .map(|pat| NamePattern::parse(pat).unwrap())
+            .collect()
+    };
+    let transmute_funs = parse(TRANSMUTE_FUNS);
+    let interior_mutability_types = parse(INTERIOR_MUTABILITY_TYPES);
+    let fmt_ctx = krate.into_fmt();
+
+    let mut violations = Vec::new();
+    for (id, item) in krate.all_items_with_ids() {
+        let span = item.item_meta().span;
+        let mut linter = ItemLinter {
+            krate,
+            fmt_ctx: &fmt_ctx,
+            deny: &deny,
+            transmute_funs: &transmute_funs,
+            interior_mutability_types: &interior_mutability_types,
+            spans: vec![span],
+            found: Vec::new(),
+        };
+        linter.walk(&item);
+        let body_id = match item {
+            AnyTransItem::Fun(decl) => decl.body.ok(),
+            AnyTransItem::Global(decl) => decl.body.ok(),
+            _ => None,
+        };
+        if let Some(body) = body_id.and_then(|body_id| krate.bodies.get(body_id)) {
+            linter.walk(body);
+            if let Some(body) = body.as_unstructured()
+                && deny.contains(&Lint::UnboundedLoops)
+            {
+                linter.find_cfg_loops(body);
+            }
+        }
+        violations.extend(
+            linter
+                .found
+                .into_iter()
+                .map(|(lint, span, message)| LintViolation {
+                    lint,
+                    item: id,
+                    span,
+                    message,
+                }),
+        );
+    }
+
+    if deny.contains(&Lint::Recursion)
+        && let Some(groups) = &krate.ordered_decls
+    {
+        for group in groups {
+            if !group.is_rec() {
+                continue;
+            }
+            let funs: Vec<FunDeclId> = group
+                .get_ids()
+                .into_iter()
+                .filter_map(|id| id.as_fun().copied())
+                .collect();
+            for &fun_id in &funs {
+                let Some(decl) = krate.fun_decls.get(fun_id) else {
+                    continue;
+                };
+                let others = funs
+                    .iter()
+                    .filter(|&&other| other != fun_id)
+                    .filter_map(|&other| krate.item_name(other))
+                    .map(|name| format!("`{}`", name.fmt_with_ctx(&fmt_ctx)))
+                    .collect::<Vec<_>>();
+                let message = if others.is_empty() {
+                    "recursive function".to_string()
+                } else {
+                    format!("function mutually recursive with {}", others.join(", "))
+                };
+                violations.push(LintViolation {
+                    lint: Lint::Recursion,
+                    item: fun_id.into(),
+                    span: decl.item_meta.span,
+                    message,
+                });
+            }
+        }
+    }
+    violations
+}
+
+/// Finds the denied constructs in an item.
+struct ItemLinter<'a> {
+    krate: &'a TranslatedCrate,
+    fmt_ctx: &'a FmtCtx<'a>,
+    deny: &'a HashSet<Lint>,
+    transmute_funs: &'a [NamePattern],
+    interior_mutability_types: &'a [NamePattern],
+    /// The spans of the statements we are in. The first one is the span of the item.
+    spans: Vec<Span>,
+    found: Vec<(Lint, Span, String)>,
+}
+
+impl ItemLinter<'_> {
+    fn report(&mut self, lint: Lint, message: String) {
+        if !self.deny.contains(&lint) {
+            return;
+        }
+        let span = *self.spans.last().unwrap();
+        // A statement may use the same construct several times.
+        if !self
+            .found
+            .iter()
+            .any(|(l, s, m)| *l == lint && *s == span && *m == message)
+        {
+            self.found.push((lint, span, message));
+        }
+    }
+
+    fn check_ty(&mut self, ty: &Ty) {
+        match ty.kind() {
+            TyKind::RawPtr(..) => {
+                let ty = ty.fmt_with_ctx(self.fmt_ctx);
+                self.report(Lint::RawPointers, format!("raw pointer type `{ty}`"));
+            }
+            TyKind::Adt(TypeId::Adt(type_id), _) => {
+                if let Some(name) = self.krate.item_name(*type_id)
+                    && self
+                        .interior_mutability_types
+                        .iter()
+                        .any(|pat| pat.matches(self.krate, name))
+                {
+                    let name = name.fmt_with_ctx(self.fmt_ctx);
+                    self.report(
+                        Lint::InteriorMutability,
+                        format!("type `{name}` with interior mutability"),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_fn_ptr(&mut self, fn_ptr: &FnPtr) {
+        if let FunIdOrTraitMethodRef::Fun(FunId::Regular(fun_id)) = &fn_ptr.func
+            && let Some(name) = self.krate.item_name(*fun_id)
+            && self
+                .transmute_funs
+                .iter()
+                .any(|pat| pat.matches(self.krate, name))
+        {
+            let name = name.fmt_with_ctx(self.fmt_ctx);
+            self.report(Lint::Transmute, format!("call to `{name}`"));
+        }
+    }
+
+    fn check_cast(&mut self, cast: &CastKind) {
+        if let CastKind::Transmute(src, tgt, _) = cast {
+            let src = src.fmt_with_ctx(self.fmt_ctx);
+            let tgt = tgt.fmt_with_ctx(self.fmt_ctx);
+            self.report(
+                Lint::Transmute,
+                format!("transmutation of `{src}` into `{tgt}`"),
+            );
+        }
+    }
+
+    /// Report the loops of a CFG, i.e. its strongly connected components with a cycle. We report
+    /// each loop at the terminator of its first block.
+    fn find_cfg_loops(&mut self, body: &ullbc_ast::ExprBody) {
+        let mut graph: DiGraphMap<ullbc_ast::BlockId, ()> = DiGraphMap::new();
+        for (block_id, block) in body.body.iter_indexed() {
+            graph.add_node(block_id);
+            for target in block.targets() {
+                graph.add_edge(block_id, target, ());
+            }
+        }
+        for scc in tarjan_scc(&graph) {
+            let head = *scc.iter().min().unwrap();
+            if scc.len() == 1 && !graph.contains_edge(head, head) {
+                continue;
+            }
+            self.spans.push(body.body[head].terminator.span);
+            self.report(Lint::UnboundedLoops, "loop".to_string());
+            self.spans.pop();
+        }
+    }
+}
+
+impl AstVisitor for ItemLinter<'_> {
+    fn visit_ty(&mut self, ty: &Ty) -> VisitContents {
+        self.check_ty(ty);
+        VisitContents::Visit
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &Rvalue) -> VisitContents {
+        if let Rvalue::RawPtr(..) = rvalue {
+            self.report(Lint::RawPointers, "creation of a raw pointer".to_string());
+        }
+        VisitContents::Visit
+    }
+
+    fn visit_cast_kind(&mut self, cast: &CastKind) -> VisitContents {
+        self.check_cast(cast);
+        VisitContents::Visit
+    }
+
+    fn visit_fn_ptr(&mut self, fn_ptr: &FnPtr) -> VisitContents {
+        self.check_fn_ptr(fn_ptr);
+        VisitContents::Visit
+    }
+
+    // We track the spans of the statements to report the constructs at the statements which use
+    // them.
+    fn visit_llbc_statement(&mut self, st: &llbc_ast::Statement) -> VisitContents {
+        self.spans.push(st.span);
+        if let llbc_ast::RawStatement::Loop(_) = st.content {
+            self.report(Lint::UnboundedLoops, "loop".to_string());
+        }
+        VisitContents::Visit
+    }
+    fn exit_llbc_statement(&mut self, _: &llbc_ast::Statement) {
+        self.spans.pop();
+    }
+    fn visit_ullbc_statement(&mut self, st: &ullbc_ast::Statement) -> VisitContents {
+        self.spans.push(st.span);
+        VisitContents::Visit
+    }
+    fn exit_ullbc_statement(&mut self, _: &ullbc_ast::Statement) {
+        self.spans.pop();
+    }
+    fn visit_terminator(&mut self, terminator: &ullbc_ast::Terminator) -> VisitContents {
+        self.spans.push(terminator.span);
+        VisitContents::Visit
+    }
+    fn exit_terminator(&mut self, _: &ullbc_ast::Terminator) {
+        self.spans.pop();
+    }
+}
@@ -1,6 +1,13 @@
+#[cfg(not(target_arch = "wasm32"))]
 extern crate env_logger;
 
+/// Initialize the logger. This does nothing on wasm, where the logging libraries we use are not
+/// available.
+#[cfg(target_arch = "wasm32")]
+pub fn initialize_logger() {}
+
 /// Initialize the logger.
+#[cfg(not(target_arch = "wasm32"))]
 pub fn initialize_logger() {
     {
         // Initialize the logger only once (useful when running the driver in tests).
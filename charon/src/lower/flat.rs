@@ -0,0 +1,151 @@
+//! The flat imperative IR produced by [crate::lower]. It has no generics and no traits, and all
+//! the memory accesses are explicit:
+//! - the locals whose address is taken live in memory, and are accessed through [Expr::Load] and
+//!   [Stmt::Store] on their address ([Expr::AddrOf]); the other locals are plain variables;
+//! - references, raw pointers and boxes are all addresses ([Ty::Ptr]);
+//! - boxes are allocated with [Stmt::Alloc] and freed with [Stmt::Free];
+//! - structs and tuples are values, accessed with [Expr::Field] and updated with
+//!   [Expr::SetField].
+use crate::ast::*;
+use crate::ids::Vector;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::Path;
+
+/// A lowered crate.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Program {
+    pub structs: Vec<Struct>,
+    pub functions: Vec<Function>,
+    /// The items we could not lower, with the reason why.
+    pub unsupported: Vec<Unsupported>,
+}
+
+impl Program {
+    /// Export the program to a JSON file.
+    #[allow(clippy::result_unit_err)]
+    pub fn serialize_to_file(&self, target_filename: &Path) -> Result<(), ()> {
+        let std::io::Result::Ok(outfile) = File::create(target_filename) else {
+            error!("Could not open: {:?}", target_filename);
+            return Err(());
+        };
+        if let Err(err) = serde_json::to_writer(&outfile, self) {
+            error!("Could not write to `{target_filename:?}`: {err:?}");
+            return Err(());
+        }
+        if !self.unsupported.is_empty() {
+            info!(
+                "{} items could not be lowered to the flat IR (see the `unsupported` field)",
+                self.unsupported.len()
+            );
+        }
+        info!("Generated the file: {}", target_filename.display());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Unsupported {
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Ty {
+    Bool,
+    Char,
+    Int(IntegerTy),
+    /// An address: a reference, a raw pointer or a box.
+    Ptr,
+    /// A tuple. The unit type is the empty tuple.
+    Tuple(Vec<Ty>),
+    /// A struct, referred to by name (see [Program::structs]).
+    Struct(String),
+}
+
+/// A struct, whose values are tuples of its fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Struct {
+    pub name: String,
+    pub fields: Vec<Ty>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Function {
+    pub name: String,
+    pub inputs: Vec<Ty>,
+    pub output: Ty,
+    /// `None` if the function is opaque.
+    pub body: Option<FunctionBody>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionBody {
+    /// The locals. The local `0` is the return value and the locals `1..=arg_count` are the
+    /// parameters, as in LLBC. Lowering may introduce temporaries after the original locals.
+    pub locals: Vector<VarId, Local>,
+    pub arg_count: usize,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Local {
+    pub name: Option<String>,
+    pub ty: Ty,
+    /// Whether the address of the local is taken, in which case it lives in memory and is only
+    /// accessed through its address.
+    pub in_memory: bool,
+}
+
+/// A side-effect-free expression.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Expr {
+    /// The value of a local which doesn't live in memory.
+    Var(VarId),
+    /// The address of a local which lives in memory.
+    AddrOf(VarId),
+    Literal(Literal),
+    /// Read the memory at the given address.
+    Load(Box<Expr>),
+    /// The address of a field, given the address of a struct or a tuple.
+    FieldAddr(Box<Expr>, usize),
+    /// A field of a struct or a tuple.
+    Field(Box<Expr>, usize),
+    /// A copy of a struct or a tuple with an updated field.
+    SetField(Box<Expr>, usize, Box<Expr>),
+    /// A struct or a tuple.
+    Tuple(Vec<Expr>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    /// A cast between scalars.
+    Cast(LiteralTy, Box<Expr>),
+    /// The checked operations return a pair of the result and of an overflow flag.
+    BinOp(BinOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Stmt {
+    Assign(VarId, Expr),
+    /// Write a value at the given address.
+    Store(Expr, Expr),
+    /// Allocate a memory cell initialized with the given value, and store its address in the
+    /// local.
+    Alloc(VarId, Expr),
+    /// Free the memory cell at the given address.
+    Free(Expr),
+    Call {
+        dest: VarId,
+        func: String,
+        args: Vec<Expr>,
+    },
+    /// Abort if the condition is false.
+    Assert(Expr),
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    Loop(Vec<Stmt>),
+    /// Break to the outer loops, as in LLBC ([crate::llbc_ast::RawStatement::Break]).
+    Break(usize),
+    /// Continue to the outer loops, as in LLBC ([crate::llbc_ast::RawStatement::Continue]).
+    Continue(usize),
+    Return,
+    Abort,
+}
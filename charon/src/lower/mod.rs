@@ -0,0 +1,426 @@
+//! Lower the LLBC of a crate to a flat imperative IR (see [flat]), meant to be translated directly
+//! to SMT-LIB or Boogie. This is experimental, and is used by the `--emit flat` option.
+//!
+//! We don't monomorphize: we only lower the non-generic structs and functions, and the calls to
+//! non-generic functions. The items we can't lower are listed in [flat::Program::unsupported].
+//! In particular, we don't support enums, arrays, slices, trait method calls and globals yet.
+//! User-defined `Drop` impls are ignored: we only lower the drops of boxes (to
+//! [flat::Stmt::Free]).
+pub mod flat;
+
+use crate::ast::*;
+use crate::formatter::{FmtCtx, IntoFormatter};
+use crate::ids::Vector;
+use crate::llbc_ast::{Block, RawStatement, Switch};
+use crate::pretty::FmtWithCtx;
+use derive_visitor::{visitor_enter_fn, Drive};
+use flat::{Expr, Stmt};
+
+type Result<T> = std::result::Result<T, String>;
+
+/// Lower a crate. The bodies must be structured (i.e. this doesn't work in `--ullbc` mode).
+pub fn lower_crate(krate: &TranslatedCrate) -> flat::Program {
+    let fmt_ctx = krate.into_fmt();
+    let ctx = LowerCtx {
+        krate,
+        fmt_ctx: &fmt_ctx,
+    };
+    let mut program = flat::Program::default();
+    for decl in krate.type_decls.iter() {
+        let name = ctx.name(&decl.item_meta.name);
+        match ctx.lower_type_decl(decl) {
+            Ok(fields) => program.structs.push(flat::Struct { name, fields }),
+            Err(reason) => program.unsupported.push(flat::Unsupported { name, reason }),
+        }
+    }
+    for decl in krate.fun_decls.iter() {
+        match ctx.lower_fun_decl(decl) {
+            Ok(fun) => program.functions.push(fun),
+            Err(reason) => program.unsupported.push(flat::Unsupported {
+                name: ctx.name(&decl.item_meta.name),
+                reason,
+            }),
+        }
+    }
+    program
+}
+
+fn check_no_generics(generics: &GenericParams) -> Result<()> {
+    // The regions are erased.
+    if generics.types.is_empty()
+        && generics.const_generics.is_empty()
+        && generics.trait_clauses.is_empty()
+    {
+        Ok(())
+    } else {
+        Err("generic items are not supported".to_owned())
+    }
+}
+
+fn check_no_generic_args(generics: &GenericArgs) -> Result<()> {
+    if generics.types.is_empty()
+        && generics.const_generics.is_empty()
+        && generics.trait_refs.is_empty()
+    {
+        Ok(())
+    } else {
+        Err("generic instantiations are not supported".to_owned())
+    }
+}
+
+struct LowerCtx<'a> {
+    krate: &'a TranslatedCrate,
+    fmt_ctx: &'a FmtCtx<'a>,
+}
+
+impl LowerCtx<'_> {
+    fn name(&self, name: &Name) -> String {
+        name.fmt_with_ctx(self.fmt_ctx)
+    }
+
+    fn lower_ty(&self, ty: &Ty) -> Result<flat::Ty> {
+        match ty.kind() {
+            TyKind::Literal(LiteralTy::Bool) => Ok(flat::Ty::Bool),
+            TyKind::Literal(LiteralTy::Char) => Ok(flat::Ty::Char),
+            TyKind::Literal(LiteralTy::Integer(int_ty)) => Ok(flat::Ty::Int(*int_ty)),
+            TyKind::Ref(..) | TyKind::RawPtr(..) => Ok(flat::Ty::Ptr),
+            TyKind::Adt(TypeId::Builtin(BuiltinTy::Box), _) => Ok(flat::Ty::Ptr),
+            TyKind::Adt(TypeId::Tuple, generics) => Ok(flat::Ty::Tuple(
+                generics
+                    .types
+                    .iter()
+                    .map(|ty| self.lower_ty(ty))
+                    .collect::<Result<_>>()?,
+            )),
+            TyKind::Adt(TypeId::Adt(id), generics) => {
+                check_no_generic_args(generics)?;
+                match self.krate.type_decls.get(*id) {
+                    Some(decl) if decl.kind.is_struct() => {
+                        Ok(flat::Ty::Struct(self.name(&decl.item_meta.name)))
+                    }
+                    _ => Err(format!(
+                        "unsupported type: {}",
+                        ty.fmt_with_ctx(self.fmt_ctx)
+                    )),
+                }
+            }
+            TyKind::Never => Ok(flat::Ty::Tuple(Vec::new())),
+            _ => Err(format!(
+                "unsupported type: {}",
+                ty.fmt_with_ctx(self.fmt_ctx)
+            )),
+        }
+    }
+
+    fn lower_type_decl(&self, decl: &TypeDecl) -> Result<Vec<flat::Ty>> {
+        check_no_generics(&decl.generics)?;
+        match &decl.kind {
+            TypeDeclKind::Struct(fields) => fields.iter().map(|f| self.lower_ty(&f.ty)).collect(),
+            _ => Err("only structs are supported".to_owned()),
+        }
+    }
+
+    fn lower_fun_decl(&self, decl: &FunDecl) -> Result<flat::Function> {
+        let sig = &decl.signature;
+        check_no_generics(&sig.generics)?;
+        let inputs = sig
+            .inputs
+            .iter()
+            .map(|ty| self.lower_ty(ty))
+            .collect::<Result<_>>()?;
+        let output = self.lower_ty(&sig.output)?;
+        let body = match decl.body {
+            Ok(body_id) => match self.krate.bodies.get(body_id) {
+                Some(Body::Structured(body)) => Some(self.lower_body(body)?),
+                Some(Body::Unstructured(_)) => {
+                    return Err("unstructured bodies are not supported".to_owned())
+                }
+                None => return Err("the body failed to translate".to_owned()),
+            },
+            Err(Opaque) => None,
+        };
+        Ok(flat::Function {
+            name: self.name(&decl.item_meta.name),
+            inputs,
+            output,
+            body,
+        })
+    }
+
+    fn lower_body(&self, body: &llbc_ast::ExprBody) -> Result<flat::FunctionBody> {
+        // The locals whose address is taken live in memory.
+        let mut in_memory = body.locals.map_ref(|_| false);
+        body.body.drive(&mut visitor_enter_fn(|rv: &Rvalue| {
+            if let Rvalue::Ref(p, _) | Rvalue::RawPtr(p, _) = rv
+                && !p.projection.contains(&ProjectionElem::Deref)
+            {
+                in_memory[p.var_id] = true;
+            }
+        }));
+        let locals = body
+            .locals
+            .iter_indexed()
+            .map(|(id, var)| {
+                Ok(flat::Local {
+                    name: var.name.clone(),
+                    ty: self.lower_ty(&var.ty)?,
+                    in_memory: in_memory[id],
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut body_ctx = BodyCtx {
+            ctx: self,
+            llbc_locals: &body.locals,
+            locals: locals.into_iter().collect(),
+        };
+        let stmts = body_ctx.lower_block(&body.body)?;
+        Ok(flat::FunctionBody {
+            locals: body_ctx.locals,
+            arg_count: body.arg_count,
+            body: stmts,
+        })
+    }
+}
+
+/// A place, lowered either to a value or to the address of the memory it lives in.
+enum Loc {
+    Value(Expr),
+    Addr(Expr),
+}
+
+struct BodyCtx<'a> {
+    ctx: &'a LowerCtx<'a>,
+    llbc_locals: &'a Vector<VarId, Var>,
+    locals: Vector<VarId, flat::Local>,
+}
+
+impl BodyCtx<'_> {
+    fn field_index(kind: &FieldProjKind, field_id: FieldId) -> Result<usize> {
+        match kind {
+            FieldProjKind::Adt(_, None) | FieldProjKind::Tuple(_) => Ok(field_id.index()),
+            FieldProjKind::Adt(_, Some(_)) => Err("enums are not supported".to_owned()),
+            FieldProjKind::ClosureState => Err("closures are not supported".to_owned()),
+        }
+    }
+
+    fn lower_place(&self, place: &Place) -> Result<Loc> {
+        let mut loc = if self.locals[place.var_id].in_memory {
+            Loc::Addr(Expr::AddrOf(place.var_id))
+        } else {
+            Loc::Value(Expr::Var(place.var_id))
+        };
+        for elem in &place.projection {
+            loc = match (elem, loc) {
+                (ProjectionElem::Deref, Loc::Value(e)) => Loc::Addr(e),
+                (ProjectionElem::Deref, Loc::Addr(a)) => Loc::Addr(Expr::Load(Box::new(a))),
+                (ProjectionElem::Field(kind, field_id), loc) => {
+                    let i = Self::field_index(kind, *field_id)?;
+                    match loc {
+                        Loc::Value(e) => Loc::Value(Expr::Field(Box::new(e), i)),
+                        Loc::Addr(a) => Loc::Addr(Expr::FieldAddr(Box::new(a), i)),
+                    }
+                }
+                _ => return Err("array and slice projections are not supported".to_owned()),
+            };
+        }
+        Ok(loc)
+    }
+
+    fn read_place(&self, place: &Place) -> Result<Expr> {
+        Ok(match self.lower_place(place)? {
+            Loc::Value(e) => e,
+            Loc::Addr(a) => Expr::Load(Box::new(a)),
+        })
+    }
+
+    fn write_place(&self, place: &Place, value: Expr) -> Result<Stmt> {
+        if let Loc::Addr(a) = self.lower_place(place)? {
+            return Ok(Stmt::Store(a, value));
+        }
+        // The place is a field path in a local which doesn't live in memory: we update the
+        // local functionally, from the innermost field outwards.
+        let mut value = value;
+        for depth in (0..place.projection.len()).rev() {
+            let ProjectionElem::Field(kind, field_id) = &place.projection[depth] else {
+                unreachable!()
+            };
+            let i = Self::field_index(kind, *field_id)?;
+            let prefix = Place {
+                var_id: place.var_id,
+                projection: place.projection[..depth].to_vec(),
+            };
+            value = Expr::SetField(Box::new(self.read_place(&prefix)?), i, Box::new(value));
+        }
+        Ok(Stmt::Assign(place.var_id, value))
+    }
+
+    fn lower_operand(&self, op: &Operand) -> Result<Expr> {
+        match op {
+            Operand::Copy(p) | Operand::Move(p) => self.read_place(p),
+            Operand::Const(c) => match &c.value {
+                RawConstantExpr::Literal(lit) => Ok(Expr::Literal(lit.clone())),
+                _ => Err("non-literal constants are not supported".to_owned()),
+            },
+        }
+    }
+
+    fn lower_rvalue(&self, rv: &Rvalue) -> Result<Expr> {
+        let op = |op| self.lower_operand(op).map(Box::new);
+        match rv {
+            Rvalue::Use(o) => self.lower_operand(o),
+            Rvalue::Ref(p, _) | Rvalue::RawPtr(p, _) => match self.lower_place(p)? {
+                Loc::Addr(a) => Ok(a),
+                // We put in memory the locals whose address is taken.
+                Loc::Value(_) => unreachable!(),
+            },
+            Rvalue::BinaryOp(binop, o1, o2, _) => Ok(Expr::BinOp(*binop, op(o1)?, op(o2)?)),
+            Rvalue::UnaryOp(UnOp::Not, o) => Ok(Expr::Not(op(o)?)),
+            Rvalue::UnaryOp(UnOp::Neg, o) => Ok(Expr::Neg(op(o)?)),
+            Rvalue::UnaryOp(UnOp::Cast(CastKind::Scalar(_, tgt)), o) => {
+                Ok(Expr::Cast(*tgt, op(o)?))
+            }
+            Rvalue::Aggregate(
+                AggregateKind::Adt(TypeId::Tuple, ..)
+                | AggregateKind::Adt(TypeId::Adt(_), None, ..),
+                ops,
+            ) => Ok(Expr::Tuple(
+                ops.iter()
+                    .map(|o| self.lower_operand(o))
+                    .collect::<Result<_>>()?,
+            )),
+            _ => Err(format!(
+                "unsupported rvalue: {}",
+                rv.fmt_with_ctx(self.ctx.fmt_ctx)
+            )),
+        }
+    }
+
+    /// The local a call writes to. Calls can only write to locals which don't live in memory,
+    /// hence we may need a temporary, which we store afterwards.
+    fn call_dest(&mut self, dest: &Place, post: &mut Vec<Stmt>) -> Result<VarId> {
+        if !dest.projection.is_empty() {
+            return Err("calls writing to projections are not supported".to_owned());
+        }
+        let local = &self.locals[dest.var_id];
+        if !local.in_memory {
+            return Ok(dest.var_id);
+        }
+        let tmp = self.locals.push(flat::Local {
+            name: None,
+            ty: local.ty.clone(),
+            in_memory: false,
+        });
+        post.push(Stmt::Store(Expr::AddrOf(dest.var_id), Expr::Var(tmp)));
+        Ok(tmp)
+    }
+
+    fn lower_call(&mut self, call: &Call, stmts: &mut Vec<Stmt>) -> Result<()> {
+        let args = call
+            .args
+            .iter()
+            .map(|o| self.lower_operand(o))
+            .collect::<Result<Vec<_>>>()?;
+        let FnOperand::Regular(fn_ptr) = &call.func else {
+            return Err("calls to function pointers are not supported".to_owned());
+        };
+        let mut post = Vec::new();
+        let dest = self.call_dest(&call.dest, &mut post)?;
+        match &fn_ptr.func {
+            FunIdOrTraitMethodRef::Fun(FunId::Regular(id)) => {
+                check_no_generic_args(&fn_ptr.generics)?;
+                let Some(decl) = self.ctx.krate.fun_decls.get(*id) else {
+                    return Err("call to a function which failed to translate".to_owned());
+                };
+                stmts.push(Stmt::Call {
+                    dest,
+                    func: self.ctx.name(&decl.item_meta.name),
+                    args,
+                });
+            }
+            FunIdOrTraitMethodRef::Fun(FunId::Builtin(BuiltinFunId::BoxNew)) => {
+                let [value] = <[_; 1]>::try_from(args).unwrap();
+                stmts.push(Stmt::Alloc(dest, value));
+            }
+            FunIdOrTraitMethodRef::Fun(FunId::Builtin(_)) => {
+                return Err("array, slice and async operations are not supported".to_owned())
+            }
+            FunIdOrTraitMethodRef::Trait(..) => {
+                return Err("trait method calls are not supported".to_owned())
+            }
+        }
+        stmts.extend(post);
+        Ok(())
+    }
+
+    fn lower_block(&mut self, block: &Block) -> Result<Vec<Stmt>> {
+        let mut stmts = Vec::new();
+        for st in &block.statements {
+            match &st.content {
+                RawStatement::Assign(p, rv) => {
+                    let value = self.lower_rvalue(rv)?;
+                    stmts.push(self.write_place(p, value)?);
+                }
+                RawStatement::Call(call) => self.lower_call(call, &mut stmts)?,
+                RawStatement::Drop(p) => {
+                    let is_box = p.projection.is_empty() && self.llbc_locals[p.var_id].ty.is_box();
+                    if is_box {
+                        stmts.push(Stmt::Free(self.read_place(p)?));
+                    }
+                }
+                RawStatement::Assert(assert) => {
+                    let cond = self.lower_operand(&assert.cond)?;
+                    stmts.push(Stmt::Assert(if assert.expected {
+                        cond
+                    } else {
+                        Expr::Not(Box::new(cond))
+                    }));
+                }
+                RawStatement::Abort(_) => stmts.push(Stmt::Abort),
+                RawStatement::Return => stmts.push(Stmt::Return),
+                RawStatement::Break(i) => stmts.push(Stmt::Break(*i)),
+                RawStatement::Continue(i) => stmts.push(Stmt::Continue(*i)),
+                RawStatement::Nop | RawStatement::FakeRead(_) | RawStatement::Retag(..) => {}
+                RawStatement::Loop(body) => stmts.push(Stmt::Loop(self.lower_block(body)?)),
+                RawStatement::Switch(Switch::If(cond, then_block, else_block)) => {
+                    let cond = self.lower_operand(cond)?;
+                    stmts.push(Stmt::If(
+                        cond,
+                        self.lower_block(then_block)?,
+                        self.lower_block(else_block)?,
+                    ));
+                }
+                RawStatement::Switch(Switch::SwitchInt(scrut, _, targets, otherwise)) => {
+                    let scrut = self.lower_operand(scrut)?;
+                    // Turn the switch into a chain of `if`s, starting from the last branch.
+                    let mut chain = self.lower_block(otherwise)?;
+                    for (values, block) in targets.iter().rev() {
+                        let cond = values
+                            .iter()
+                            .map(|v| {
+                                Expr::BinOp(
+                                    BinOp::Eq,
+                                    Box::new(scrut.clone()),
+                                    Box::new(Expr::Literal(Literal::Scalar(*v))),
+                                )
+                            })
+                            .reduce(|c1, c2| Expr::BinOp(BinOp::BitOr, Box::new(c1), Box::new(c2)))
+                            .unwrap();
+                        chain = vec![Stmt::If(cond, self.lower_block(block)?, chain)];
+                    }
+                    stmts.extend(chain);
+                }
+                RawStatement::Switch(_) | RawStatement::SetDiscriminant(..) => {
+                    return Err("enums are not supported".to_owned())
+                }
+                RawStatement::Labeled(..) | RawStatement::Goto(_) => {
+                    return Err("`goto`s are not supported".to_owned())
+                }
+                RawStatement::TryPropagate(_) => {
+                    return Err("the `?` operator is not supported".to_owned())
+                }
+                RawStatement::Error(msg) => return Err(msg.clone()),
+            }
+        }
+        Ok(stmts)
+    }
+}
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::ast::*;
 
-mod parser;
+pub(crate) mod parser;
 
 pub use Pattern as NamePattern;
 
@@ -52,6 +52,12 @@ impl Pattern {
         self.matches_with_generics(ctx, name, &GenericArgs::empty())
     }
 
+    /// Whether the pattern matches the name of the item or one of the public paths under which it
+    /// is re-exported.
+    pub fn matches_item(&self, ctx: &TranslatedCrate, id: impl Into<AnyTransId>) -> bool {
+        ctx.item_paths(id).any(|name| self.matches(ctx, name))
+    }
+
     pub fn matches_with_generics(
         &self,
         ctx: &TranslatedCrate,
@@ -80,6 +86,18 @@ impl Pattern {
         true
     }
 
+    /// Whether the pattern matches the impl which proves this trait reference: the name of the
+    /// impl block, or the name of the builtin impl (see [TranslatedCrate::builtin_impl_name]).
+    pub fn matches_trait_ref(&self, ctx: &TranslatedCrate, tref: &TraitRef) -> bool {
+        match &tref.kind {
+            TraitRefKind::TraitImpl(impl_id, _) => self.matches_item(ctx, *impl_id),
+            TraitRefKind::BuiltinOrAuto(impl_trait) => ctx
+                .builtin_impl_name(&impl_trait.skip_binder)
+                .is_some_and(|name| self.matches(ctx, &name)),
+            _ => false,
+        }
+    }
+
     pub fn matches_ty(&self, ctx: &TranslatedCrate, ty: &Ty) -> bool {
         if let [PatElem::Glob] = self.elems.as_slice() {
             return true;
@@ -166,22 +184,30 @@ impl PatElem {
                 PathElem::Ident(ident, _),
             ) => {
                 // `crate` is a special keyword that referes to the current crate.
-                let same_ident =
-                    pat_ident == ident || (pat_ident == "crate" && ident == &ctx.real_crate_name);
+                // With `--crate-versions-in-names`, the crate roots look like `rand@0.8.5`. A pattern
+                // without version matches all the versions.
+                let same_ident = pat_ident == ident
+                    || (pat_ident == "crate" && ident == &ctx.real_crate_name)
+                    || ident
+                        .split_once('@')
+                        .is_some_and(|(crate_name, _)| crate_name == pat_ident);
                 same_ident && (generics.is_empty() || PatTy::matches_generics(ctx, generics, args))
             }
             (PatElem::Impl(_pat), PathElem::Impl(ImplElem::Ty(_, _ty), _)) => {
                 // TODO
                 false
             }
-            (PatElem::Impl(pat), PathElem::Impl(ImplElem::Trait(impl_id), _)) => {
-                let Some(timpl) = ctx.trait_impls.get(*impl_id) else {
-                    return false;
-                };
-                let Some(trait_name) = ctx.item_name(timpl.impl_trait.trait_id) else {
+            (
+                PatElem::Impl(pat),
+                PathElem::Impl(
+                    ImplElem::Trait(_, _, impl_trait) | ImplElem::Builtin(impl_trait),
+                    _,
+                ),
+            ) => {
+                let Some(trait_name) = ctx.item_name(impl_trait.trait_id) else {
                     return false;
                 };
-                pat.matches_with_generics(ctx, trait_name, &timpl.impl_trait.generics)
+                pat.matches_with_generics(ctx, trait_name, &impl_trait.generics)
             }
             _ => false,
         }
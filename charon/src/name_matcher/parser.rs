@@ -51,7 +51,7 @@ fn parse_pattern_complete(i: &str) -> Result<Pattern, ErrorTree<String>> {
         .map_err(|e: ErrorTree<_>| e.map_locations(|s: &str| s.to_string()))
 }
 
-fn parse_pattern(i: &str) -> ParseResult<'_, Pattern> {
+pub(crate) fn parse_pattern(i: &str) -> ParseResult<'_, Pattern> {
     separated_list0(tag("::").followed_by(multispace0), parse_pat_elem)
         .map(|elems| Pattern { elems })
         .parse(i)
@@ -40,6 +40,18 @@ pub struct CliOpts {
     #[clap(long = "mir_optimized")]
     #[serde(default)]
     pub mir_optimized: bool,
+    #[clap(
+        long = "mir-inlining",
+        value_enum,
+        default_value_t,
+        help = indoc!("
+            Control the inlining performed by rustc on the optimized MIR (see `--mir_optimized`).
+            `off` disables it, `default` uses the heuristics of rustc, and `aggressive` raises
+            the size thresholds under which functions get inlined. The chosen setting is recorded
+            in the output.
+    "))]
+    #[serde(default)]
+    pub mir_inlining: MirInlining,
     /// Provide a custom name for the compiled crate (ignore the name computed
     /// by Cargo)
     #[clap(long = "crate")]
@@ -115,6 +127,29 @@ pub struct CliOpts {
     #[clap(long = "extract-opaque-bodies")]
     #[serde(default)]
     pub extract_opaque_bodies: bool,
+    /// Like `--extract-opaque-bodies`, but only for the items of the given crates.
+    #[clap(
+        long = "extract-bodies-from",
+        value_delimiter = ',',
+        help = indoc!("
+            Extract the bodies of the items of these crates, like `--extract-opaque-bodies` does
+            for all the crates. Takes a comma-separated list of crate names, e.g.
+            `--extract-bodies-from smallvec,arrayvec`. The `--opaque` and `--exclude` options still
+            apply to the items of these crates.
+        ")
+    )]
+    #[serde(default)]
+    pub extract_bodies_from: Vec<String>,
+    #[clap(
+        long = "dependency-depth",
+        help = indoc!("
+            Translate as opaque the foreign items which are more than this number of hops away from
+            the items of the crate, and list them in a warning. With `--dependency-depth 1`, the
+            items used by the crate are translated as usual, but the items they use in turn are
+            opaque. This bounds the size of the extraction when extracting foreign bodies.
+    "))]
+    #[serde(default)]
+    pub dependency_depth: Option<usize>,
     /// Whitelist of items to translate. These use the name-matcher syntax.
     #[clap(
         long = "include",
@@ -154,11 +189,39 @@ pub struct CliOpts {
     )]
     #[serde(default)]
     pub exclude: Vec<String>,
+    /// Functions to list as entry points of the crate. These use the name-matcher syntax.
+    #[clap(
+        long = "root",
+        help = indoc!("
+            Functions to list as entry points of the crate, in addition to `main`, `#[start]`
+            functions and functions annotated with `#[charon::root]`. These use the name-matcher
+            syntax, see the doc of `--include`. Only the translated functions are considered.
+    "))]
+    #[serde(default)]
+    pub roots: Vec<String>,
+    /// Additional attributes which mark functions as verification harnesses.
+    #[clap(
+        long = "harness-attr",
+        help = indoc!("
+            Mark the functions which have this attribute as verification harnesses, in addition to
+            `#[kani::proof]` and `#[quickcheck]`. The attribute is given by its path as written in
+            the source, e.g. `my_tool::check`.
+    "))]
+    #[serde(default)]
+    pub harness_attrs: Vec<String>,
     /// Whether to hide the `Sized`, `Sync`, `Send` and `Unpin` marker traits anywhere they show
     /// up.
     #[clap(long = "hide-marker-traits")]
     #[serde(default)]
     pub hide_marker_traits: bool,
+    #[clap(
+        long = "instantiate-blanket-impls",
+        help = indoc!("
+            Replace the uses of blanket impls (e.g. `impl<T: Foo> Bar for T`) at concrete types by
+            uses of specialized copies of these impls, which are added to the crate.
+    "))]
+    #[serde(default)]
+    pub instantiate_blanket_impls: bool,
     /// Do not run cargo; instead, run the driver directly.
     // FIXME: use a subcommand instead, when we update clap to support flattening.
     #[clap(long = "no-cargo")]
@@ -172,6 +235,28 @@ pub struct CliOpts {
     #[clap(long = "cargo-arg")]
     #[serde(default)]
     pub cargo_args: Vec<String>,
+    #[clap(
+        long = "target-dir",
+        value_parser,
+        help = indoc!("
+            The directory in which cargo puts the build artifacts. Defaults to the `charon`
+            subdirectory of the target directory of the workspace (e.g. `target/charon`), so that
+            the builds of charon and the normal builds don't invalidate each other.
+    "))]
+    #[serde(default)]
+    pub target_dir: Option<PathBuf>,
+    #[clap(
+        long = "extract-dependencies",
+        value_delimiter = ',',
+        help = indoc!("
+            Also translate these dependencies of the crate, during the same cargo invocation.
+            Takes a comma-separated list of crate names. Each crate gets its own output file in the
+            destination directory, named after the crate (`--dest-file` only applies to the main
+            crate). Incompatible with `--no-cargo`.
+        ")
+    )]
+    #[serde(default)]
+    pub extract_dependencies: Vec<String>,
     /// Panic on the first error. This is useful for debugging.
     #[clap(long = "abort-on-error")]
     #[serde(default)]
@@ -210,6 +295,25 @@ pub struct CliOpts {
     )]
     #[serde(default)]
     pub print_llbc: bool,
+    #[clap(
+        long = "check-invariants",
+        help = indoc!("
+            Check the invariants of the crate after the micro-passes (e.g. that the referenced
+            items and blocks exist, that the generic arguments match the parameters of the items,
+            and that the bodies are well-typed), and report the violations as errors.
+    "))]
+    #[serde(default)]
+    pub check_invariants: bool,
+    #[clap(
+        long = "report-cycles",
+        help = indoc!("
+            Report the groups of mutually recursive declarations, with a cycle which goes through
+            each of them, as warnings. The groups which mix declarations of different kinds (e.g. a
+            type which is recursive through a trait impl) are reported as such. This only applies
+            to LLBC.
+    "))]
+    #[serde(default)]
+    pub report_cycles: bool,
     #[clap(
         long = "no-merge-goto-chains",
         help = indoc!("
@@ -217,6 +321,423 @@ pub struct CliOpts {
     "))]
     #[serde(default)]
     pub no_merge_goto_chains: bool,
+    #[clap(
+        long = "reconstruct-matches",
+        help = indoc!("
+            Merge the nested matches produced by the desugaring of nested patterns into
+            multi-level matches (e.g. `match x { Some(Ok(_)) => .., Some(Err(_)) => .., None => .. }`).
+    "))]
+    #[serde(default)]
+    pub reconstruct_matches: bool,
+    #[clap(
+        long = "reconstruct-try",
+        help = indoc!("
+            Collapse the desugaring of the `?` operator (a call to `Try::branch` followed by a
+            match on the resulting `ControlFlow`, whose `Break` branch returns the residual) into a
+            single `dest := op?` statement. This only applies to LLBC.
+    "))]
+    #[serde(default)]
+    pub reconstruct_try: bool,
+    #[clap(
+        long = "extended-asserts",
+        help = indoc!("
+            Also turn into asserts the `if`s whose `else` branch panics, and those whose panicking
+            branch computes the panic message in temporaries first (the constant parts of the
+            message are kept on the assert). Warn about the `if`s which have a panicking branch
+            but don't have one of these shapes. By default, we only recognize the
+            `if c { panic } else { .. }` shape. This only applies to LLBC.
+    "))]
+    #[serde(default)]
+    pub extended_asserts: bool,
+    #[clap(
+        long = "preserve-discriminant-reads",
+        help = indoc!("
+            Keep the discriminant reads (`_x = discriminant(_y)`) whose value is used otherwise
+            than by a switch, e.g. when the discriminant is stored or compared. By default, we
+            replace them with matches on the enum value.
+    "))]
+    #[serde(default)]
+    pub preserve_discriminant_reads: bool,
+    #[clap(
+        long = "simplify-constants",
+        value_enum,
+        default_value_t,
+        help = indoc!("
+            Control how far the constant expressions are turned into statements. `all` introduces
+            intermediate statements for the constant ADTs, arrays, references and globals, so that
+            the operands only contain literals. `globals` only does so for the globals (and the
+            constants which contain them), and `none` keeps the constant expressions as they are.
+    "))]
+    #[serde(default)]
+    pub simplify_constants: ConstantSimplification,
+    #[clap(
+        long = "max-duplication",
+        help = indoc!("
+            Bound the number of statements that the control-flow reconstruction may duplicate in
+            a body. When the reconstruction of a body exceeds this bound, we translate it as a
+            state machine (`loop { switch state { .. } }`) instead, which never duplicates code, and
+            report the function in a warning.
+    "))]
+    #[serde(default)]
+    pub max_duplication: Option<usize>,
+    #[clap(
+        long = "semi-structured",
+        help = indoc!("
+            When we fail to reconstruct the control-flow of a body, translate it to labeled blocks
+            and `goto`s instead of a state machine. This is meant for the consumers which can
+            handle a few `goto`s.
+    "))]
+    #[serde(default)]
+    pub semi_structured: bool,
+    #[clap(
+        long = "borrow-liveness",
+        help = indoc!("
+            Compute, for every borrow, the statements where it is used for the last time. The
+            borrows are numbered per body, and every statement records the borrow it creates and
+            the borrows it uses for the last time.
+    "))]
+    #[serde(default)]
+    pub borrow_liveness: bool,
+    #[clap(
+        long = "move-info",
+        help = indoc!("
+            Compute, for every statement, the locals it moves, copies and drops, and store them on
+            the statement.
+    "))]
+    #[serde(default)]
+    pub move_info: bool,
+    #[clap(
+        long = "debug-info",
+        help = indoc!("
+            Translate the debug info of the bodies: their lexical scopes and their user variables,
+            with the places they live in. This lets tools name the source variables when they
+            report on a body.
+    "))]
+    #[serde(default)]
+    pub debug_info: bool,
+    #[clap(
+        long = "statement-ranges",
+        help = indoc!("
+            Record, for every statement of the LLBC bodies, the range of bytes it comes from in the
+            original source file.
+    "))]
+    #[serde(default)]
+    pub statement_ranges: bool,
+    #[clap(
+        long = "comment-annotations",
+        help = indoc!("
+            Parse the comments of the form `//@ <key>: <value>` (e.g. `//@ requires: x > 0`) into
+            annotations: those right before a local item go in its `annotations`, and those in a
+            body go in the `annotations` of the statement which follows them. The annotations in
+            bodies are only recorded in LLBC.
+    "))]
+    #[serde(default)]
+    pub comment_annotations: bool,
+    #[clap(
+        long = "annotate-recursive-fields",
+        help = indoc!("
+            Annotate the fields of the (mutually) recursive types which mention a type of their own
+            recursive group, recording whether the recursion goes through an indirection (`Box`,
+            `Rc`, `Arc`, `Vec`, a reference or a raw pointer) or not.
+    "))]
+    #[serde(default)]
+    pub annotate_recursive_fields: bool,
+    #[clap(
+        long = "name-temporaries",
+        help = indoc!("
+            Give names to the locals introduced by the compiler, based on where they come from and
+            on their index (e.g. `tmp_3`, `deref_temp_5`), so that the pretty-printed bodies are
+            easier to read.
+    "))]
+    #[serde(default)]
+    pub name_temporaries: bool,
+    #[clap(
+        long = "keep-retags",
+        help = indoc!("
+            Keep the `Retag` statements of the Stacked Borrows memory model, which rustc only emits
+            in the optimized MIR (see `--mir_optimized`). Without this option they are removed.
+    "))]
+    #[serde(default)]
+    pub keep_retags: bool,
+    #[clap(
+        long = "emit-types-only",
+        help = indoc!("
+            Don't translate the bodies of the functions and globals: only emit the types, the
+            signatures, the trait declarations and the trait impls. This is much faster, for tools
+            which only need the shape of the API of the crate.
+    "))]
+    #[serde(default)]
+    pub emit_types_only: bool,
+    #[clap(
+        long = "signatures-with-bodies-for",
+        help = indoc!("
+            Only translate the bodies of the items which match this pattern (this uses the
+            name-matcher syntax, see the doc of `--include`), and the signatures of the other
+            items. Can be combined with `--emit-types-only`, in which case the bodies of the
+            matching items are translated anyway.
+    "))]
+    #[serde(default)]
+    pub bodies_for: Vec<String>,
+    #[clap(
+        long = "max-body-statements",
+        help = indoc!("
+            Translate the bodies which have more than this number of MIR statements as opaque, and
+            report them in a warning. This avoids ballooning the output with giant generated bodies
+            (e.g. big match tables).
+    "))]
+    #[serde(default)]
+    pub max_body_statements: Option<usize>,
+    #[clap(
+        long = "per-item-timeout",
+        help = indoc!("
+            Give up on the translation of a body after this number of seconds, translate it as
+            opaque and report it in a warning. The time is checked before the translation of each
+            statement, so a single step which hangs is not interrupted; in particular, the
+            conversion of the MIR body which precedes the translation counts towards the limit but
+            can't be interrupted. The transformation passes which follow aren't counted.
+    "))]
+    #[serde(default)]
+    pub per_item_timeout: Option<u64>,
+    #[clap(
+        long = "max-errors",
+        help = indoc!("
+            Stop translating new items once this number of errors has been reported. The items
+            translated so far are still serialized, and the exit code is `1`.
+    "))]
+    #[serde(default)]
+    pub max_errors: Option<usize>,
+    #[clap(
+        long = "error-summary",
+        help = indoc!("
+            Print a summary of the errors at the end: their number in each category, and the items
+            with the most errors. The categories are `body` (errors in an item which we still
+            translated, possibly with an opaque body), `item` (errors because of which we dropped
+            an item) and `other`. Whether or not this option is set, with `--error-on-warnings` we
+            exit with code `2` when all the errors are `body` errors, and `1` otherwise.
+    "))]
+    #[serde(default)]
+    pub error_summary: bool,
+    #[clap(
+        long = "export-profile",
+        value_enum,
+        default_value_t,
+        help = indoc!("
+            The shape of the serialized (U)LLBC. `ocaml` follows the conventions of
+            `ppx_deriving_yojson` (variants as `[\"Variant\", args..]` arrays, maps as arrays of
+            pairs), so that OCaml consumers can derive their deserializers.
+    "))]
+    #[serde(default)]
+    pub export_profile: ExportProfile,
+    #[clap(
+        long = "emit",
+        value_enum,
+        help = indoc!("
+            Emit additional outputs next to the (U)LLBC file. `flat` (experimental) lowers the
+            non-generic functions to a flat imperative IR meant to be translated to SMT-LIB or
+            Boogie, in a `.flat.json` file (see `charon_lib::lower`). `metrics` computes complexity
+            metrics of the functions (number of blocks, statements and loops, maximum fan-out of
+            the switches, generic arity), in a `.metrics.json` file. `source-index` maps the
+            source files and line ranges to the ids of the items defined there, in a
+            `.source-index.json` file, so that editors can find the item at the cursor.
+    "))]
+    #[serde(default)]
+    pub emit: Vec<EmitKind>,
+    #[clap(
+        long = "per-module-output",
+        help = indoc!("
+            In addition to the (U)LLBC file, split the crate by module and emit one file per
+            module, in a directory named like the (U)LLBC file with the `.modules` extension.
+            The directory also contains an `index.json` file which lists the modules with their
+            items and the items they refer to in other modules.
+    "))]
+    #[serde(default)]
+    pub per_module_output: bool,
+    #[clap(
+        long = "deterministic",
+        help = indoc!("
+            Check that the output is reproducible: translate the crate a second time in the same
+            process and fail if the two serialized outputs differ. This doubles the translation
+            time, and the diagnostics are emitted twice.
+    "))]
+    #[serde(default)]
+    pub deterministic: bool,
+    #[clap(
+        long = "profile-extraction",
+        value_parser,
+        help = indoc!("
+            Write a profile of the extraction to this file, in the Chrome trace event format: the
+            time spent in the hax export, in the translation of each item and in each pass. The
+            file can be opened with `chrome://tracing`, Perfetto or speedscope.
+    "))]
+    #[serde(default)]
+    pub profile_extraction: Option<PathBuf>,
+    #[clap(
+        long = "embed-sources",
+        value_enum,
+        default_value_t,
+        help = indoc!("
+            Which source text to embed in the output (in `file_id_to_content`). `referenced` only
+            keeps the lines which some span of the crate refers to (the other lines are emptied,
+            so the line numbers stay valid), and `none` drops the sources.
+    "))]
+    #[serde(default)]
+    pub embed_sources: SourceEmbedding,
+    #[clap(
+        long = "model-file",
+        help = indoc!("
+            A file of models for the opaque functions. A model is a body which replaces the
+            missing body of the opaque functions whose name matches a pattern, if it agrees with
+            their signature. The file is either a `.llbc` file, whose functions with a body are
+            models for the functions with the same name, or a text file of `fn <pattern> { <body>
+            }` entries, where the bodies are written in the syntax of `--print-llbc`. Can be
+            repeated. Not available with `--ullbc`.
+    "))]
+    #[serde(default)]
+    pub model_files: Vec<PathBuf>,
+    #[clap(
+        long = "link",
+        help = indoc!("
+            Merge the given `.llbc` (or `.ullbc`, with `--ullbc`) file into the output, e.g. a
+            crate of the standard library extracted with `charon extract-std`. The items of the
+            file replace the opaque items with the same name, and the items present in both are
+            only kept once (see `charon link`). Can be repeated.
+    "))]
+    #[serde(default)]
+    pub link_files: Vec<PathBuf>,
+    #[clap(
+        long = "opaque-derive",
+        help = indoc!("
+            Don't keep the bodies of the methods of the trait impls generated by this derive, e.g.
+            `Debug`. The derive is given by the name of the trait.
+    "))]
+    #[serde(default)]
+    pub opaque_derives: Vec<String>,
+    #[clap(
+        long = "builtin-derive",
+        help = indoc!("
+            Remove the trait impls generated by this derive, e.g. `Clone` or `PartialEq`, and treat
+            them as builtin impls: the trait references which used them become `BuiltinOrAuto`
+            references. The derive is given by the name of the trait.
+    "))]
+    #[serde(default)]
+    pub builtin_derives: Vec<String>,
+    #[clap(
+        long = "devirtualize-closure-calls",
+        help = indoc!("
+            Replace the calls to `Fn::call`, `FnMut::call_mut` and `FnOnce::call_once` on a known
+            closure with direct calls to the function of the closure, which takes the state of the
+            closure as first argument.
+    "))]
+    #[serde(default)]
+    pub devirtualize_closure_calls: bool,
+    #[clap(
+        long = "async-builtins",
+        help = indoc!("
+            Translate the calls to `Future::poll` and to the methods of `Pin`, `Context` and
+            `Waker` as builtin functions (see `AsyncBuiltin`) instead of calls to the functions of
+            the standard library.
+    "))]
+    #[serde(default)]
+    pub async_builtins: bool,
+    #[clap(
+        long = "keep-main-shims",
+        help = indoc!("
+            Translate the items which the compiler generates around `main`, which we skip by
+            default: the `lang_start` functions of the runtime, the `Termination` trait which
+            reports the result of `main`, and the `main` function and the test descriptions of the
+            test harness (with `--test`).
+    "))]
+    #[serde(default)]
+    pub keep_main_shims: bool,
+    #[clap(
+        long = "crate-versions-in-names",
+        help = indoc!("
+            Append the versions of the dependencies to the first element of the names of their
+            items (e.g. `rand@0.8.5::Rng`), so that the items of two versions of the same crate
+            don't get the same names. When we don't know the version of a crate and another crate
+            has the same name, we append a hash of the crate instead. The name patterns still match
+            these names: `rand::Rng` matches the items of all the versions of `rand`.
+    "))]
+    #[serde(default)]
+    pub crate_versions_in_names: bool,
+}
+
+/// The additional outputs (see `--emit`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmitKind {
+    /// The flat imperative IR of `charon_lib::lower`.
+    Flat,
+    /// The complexity metrics of `charon_lib::export::metrics`.
+    Metrics,
+    /// The index of the items by source location of `charon_lib::export::source_index`.
+    #[serde(rename = "source-index")]
+    SourceIndex,
+}
+
+/// The MIR inlining setting (see `--mir-inlining`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MirInlining {
+    /// No MIR inlining.
+    #[default]
+    Off,
+    /// The default heuristics of rustc.
+    Default,
+    /// Inline bigger functions than the default heuristics.
+    Aggressive,
+}
+
+impl MirInlining {
+    /// The flags to pass to rustc for this setting.
+    pub fn rustc_flags(self) -> &'static [&'static str] {
+        match self {
+            MirInlining::Off => &["-Zinline-mir=no"],
+            MirInlining::Default => &["-Zinline-mir=yes"],
+            MirInlining::Aggressive => &[
+                "-Zinline-mir=yes",
+                "-Zinline-mir-threshold=1000",
+                "-Zinline-mir-hint-threshold=1000",
+            ],
+        }
+    }
+}
+
+/// How far the constant expressions are turned into statements (see `--simplify-constants`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConstantSimplification {
+    /// Only leave literals (and const generic variables) in the constant operands.
+    #[default]
+    All,
+    /// Only introduce statements for the globals and the constants which contain them.
+    Globals,
+    /// Keep the constant expressions as they are.
+    None,
+}
+
+/// The shape of the serialized (U)LLBC.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportProfile {
+    /// The default serde representation, used by `charon-ml`.
+    #[default]
+    Default,
+    /// The conventions of `ppx_deriving_yojson` (see `export::ocaml`).
+    Ocaml,
+}
+
+/// Which source text to embed in the output (see `--embed-sources`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceEmbedding {
+    /// The full text of every file.
+    #[default]
+    All,
+    /// Only the lines which some span refers to.
+    Referenced,
+    /// No source text.
+    None,
 }
 
 impl CliOpts {
@@ -231,5 +752,10 @@ impl CliOpts {
             !self.mir_promoted || !self.mir_optimized,
             "Can't use --mir_promoted and --mir_optimized at the same time"
         );
+
+        assert!(
+            !self.ullbc || self.model_files.is_empty(),
+            "Can't use --model-file with --ullbc: the models are LLBC bodies"
+        );
     }
 }
@@ -69,8 +69,13 @@ impl<C: AstFormatter> FmtWithCtx<C> for AnyTransItem<'_> {
 
 impl<C: AstFormatter> FmtWithCtx<C> for Assert {
     fn fmt_with_ctx(&self, ctx: &C) -> String {
+        let message: String = self
+            .message
+            .iter()
+            .map(|op| format!(", {}", op.fmt_with_ctx(ctx)))
+            .collect();
         format!(
-            "assert({} == {})",
+            "assert({} == {}{message})",
             self.cond.fmt_with_ctx(ctx),
             self.expected,
         )
@@ -98,6 +103,12 @@ impl<C: AstFormatter> FmtWithCtx<C> for BlockData {
 
         // Format the statements
         for statement in &self.statements {
+            if ctx.print_options().show_spans {
+                out.push(format!(
+                    "{tab}// {}\n",
+                    ctx.format_span(&statement.span.span)
+                ));
+            }
             out.push(format!("{}{};\n", tab, statement.fmt_with_ctx(ctx)).to_string());
         }
 
@@ -132,6 +143,20 @@ impl<C: AstFormatter> FmtWithCtx<C> for CastKind {
             CastKind::FnPtr(src, tgt) | CastKind::RawPtr(src, tgt) => {
                 format!("cast<{}, {}>", src.fmt_with_ctx(ctx), tgt.fmt_with_ctx(ctx))
             }
+            CastKind::ExposeProvenance(src, tgt) => {
+                format!(
+                    "expose_provenance<{}, {}>",
+                    src.fmt_with_ctx(ctx),
+                    tgt.fmt_with_ctx(ctx)
+                )
+            }
+            CastKind::WithExposedProvenance(src, tgt) => {
+                format!(
+                    "with_exposed_provenance<{}, {}>",
+                    src.fmt_with_ctx(ctx),
+                    tgt.fmt_with_ctx(ctx)
+                )
+            }
             CastKind::Unsize(src, tgt) => {
                 format!(
                     "unsize_cast<{}, {}>",
@@ -139,7 +164,7 @@ impl<C: AstFormatter> FmtWithCtx<C> for CastKind {
                     tgt.fmt_with_ctx(ctx)
                 )
             }
-            CastKind::Transmute(src, tgt) => {
+            CastKind::Transmute(src, tgt, _) => {
                 format!(
                     "transmute<{}, {}>",
                     src.fmt_with_ctx(ctx),
@@ -304,6 +329,9 @@ impl GenericArgs {
     where
         C: AstFormatter,
     {
+        if ctx.print_options().hide_generics {
+            return String::new();
+        }
         let mut params = Vec::new();
         let GenericArgs {
             regions,
@@ -833,6 +861,10 @@ impl<C: AstFormatter> FmtWithCtx<C> for RawConstantExpr {
                 let values: Vec<String> = values.iter().map(|v| v.fmt_with_ctx(ctx)).collect();
                 format!("ConstAdt {} [{}]", variant_id, values.join(", "))
             }
+            RawConstantExpr::Array(values) => {
+                let values: Vec<String> = values.iter().map(|v| v.fmt_with_ctx(ctx)).collect();
+                format!("ConstArray [{}]", values.join(", "))
+            }
             RawConstantExpr::Global(global_ref) => global_ref.fmt_with_ctx(ctx),
             RawConstantExpr::TraitConst(trait_ref, name) => {
                 format!("{}::{name}", trait_ref.fmt_with_ctx(ctx),)
@@ -914,7 +946,7 @@ impl<C: AstFormatter> FmtWithCtx<C> for Rvalue {
                 format!("{ptr_kind}{}", place.fmt_with_ctx(ctx))
             }
 
-            Rvalue::BinaryOp(binop, x, y) => {
+            Rvalue::BinaryOp(binop, x, y, _) => {
                 format!("{} {} {}", x.fmt_with_ctx(ctx), binop, y.fmt_with_ctx(ctx))
             }
             Rvalue::UnaryOp(unop, x) => {
@@ -1018,6 +1050,9 @@ impl<C: AstFormatter> FmtWithCtx<C> for ullbc::Statement {
             RawStatement::StorageDead(vid) => format!("@storage_dead({})", vid.to_pretty_string()),
             RawStatement::Deinit(place) => format!("@deinit({})", place.fmt_with_ctx(ctx)),
             RawStatement::Drop(place) => format!("drop {}", place.fmt_with_ctx(ctx)),
+            RawStatement::Retag(kind, place) => {
+                format!("@retag[{kind}]({})", place.fmt_with_ctx(ctx))
+            }
             RawStatement::Assert(assert) => format!("{}", assert.fmt_with_ctx(ctx)),
             RawStatement::Nop => format!("nop"),
             RawStatement::Error(s) => format!("@Error({})", s),
@@ -1025,6 +1060,38 @@ impl<C: AstFormatter> FmtWithCtx<C> for ullbc::Statement {
     }
 }
 
+impl<C: AstFormatter> FmtWithCtx<C> for llbc::MatchKind {
+    fn fmt_with_ctx(&self, _ctx: &C) -> String {
+        match self {
+            llbc::MatchKind::Match => "match",
+            llbc::MatchKind::IfLet => "if let match",
+            llbc::MatchKind::LetElse => "let else match",
+            llbc::MatchKind::WhileLet => "while let match",
+        }
+        .to_string()
+    }
+}
+
+impl<C: AstFormatter> FmtWithCtx<C> for llbc::Pattern {
+    fn fmt_with_ctx(&self, ctx: &C) -> String {
+        match self {
+            llbc::Pattern::Wildcard => "_".to_string(),
+            llbc::Pattern::Variant(variants, fields) => {
+                let variants = variants.iter().format(" | ");
+                if fields.is_empty() {
+                    variants.to_string()
+                } else {
+                    let fields = fields
+                        .iter()
+                        .map(|(field_id, pat)| format!("{field_id}: {}", pat.fmt_with_ctx(ctx)))
+                        .format(", ");
+                    format!("{variants} {{ {fields} }}")
+                }
+            }
+        }
+    }
+}
+
 impl<C: AstFormatter> FmtWithCtx<C> for llbc::Statement {
     fn fmt_with_ctx(&self, ctx: &C) -> String {
         // By default use a tab.
@@ -1034,8 +1101,14 @@ impl<C: AstFormatter> FmtWithCtx<C> for llbc::Statement {
     fn fmt_with_ctx_and_indent(&self, tab: &str, ctx: &C) -> String {
         use llbc::RawStatement;
         let mut out = String::new();
-        for line in &self.comments_before {
-            let _ = writeln!(&mut out, "{tab}// {line}");
+        let options = ctx.print_options();
+        if options.inline_comments {
+            for line in &self.comments_before {
+                let _ = writeln!(&mut out, "{tab}// {line}");
+            }
+        }
+        if options.show_spans {
+            let _ = writeln!(&mut out, "{tab}// {}", ctx.format_span(&self.span.span));
         }
         let _ = match &self.content {
             RawStatement::Assign(place, rvalue) => write!(
@@ -1058,6 +1131,9 @@ impl<C: AstFormatter> FmtWithCtx<C> for llbc::Statement {
             RawStatement::Drop(place) => {
                 write!(&mut out, "{}drop {}", tab, place.fmt_with_ctx(ctx))
             }
+            RawStatement::Retag(kind, place) => {
+                write!(&mut out, "{tab}@retag[{kind}]({})", place.fmt_with_ctx(ctx))
+            }
             RawStatement::Assert(assert) => {
                 write!(&mut out, "{}{}", tab, assert.fmt_with_ctx(ctx),)
             }
@@ -1139,7 +1215,41 @@ impl<C: AstFormatter> FmtWithCtx<C> for llbc::Statement {
                         maps.iter().format(""),
                     )
                 }
+                Switch::NestedMatch(discr, maps, otherwise, kind) => {
+                    let inner_tab1 = format!("{tab}{TAB_INCR}");
+                    let inner_tab2 = format!("{inner_tab1}{TAB_INCR}");
+                    let mut maps: Vec<String> = maps
+                        .iter()
+                        .map(|(pat, st)| {
+                            format!(
+                                "{inner_tab1}{} => {{\n{}{inner_tab1}}},\n",
+                                pat.fmt_with_ctx(ctx),
+                                st.fmt_with_ctx_and_indent(&inner_tab2, ctx),
+                            )
+                        })
+                        .collect();
+                    if let Some(otherwise) = otherwise {
+                        maps.push(format!(
+                            "{inner_tab1}_ => {{\n{}{inner_tab1}}},\n",
+                            otherwise.fmt_with_ctx_and_indent(&inner_tab2, ctx),
+                        ));
+                    };
+
+                    write!(
+                        &mut out,
+                        "{tab}{} {} {{\n{}{tab}}}",
+                        kind.fmt_with_ctx(ctx),
+                        discr.fmt_with_ctx(ctx),
+                        maps.iter().format(""),
+                    )
+                }
             },
+            RawStatement::TryPropagate(try_propagate) => write!(
+                &mut out,
+                "{tab}{} := {}?",
+                try_propagate.dest.fmt_with_ctx(ctx),
+                try_propagate.op.fmt_with_ctx(ctx),
+            ),
             RawStatement::Loop(body) => {
                 let inner_tab = format!("{tab}{TAB_INCR}");
                 write!(
@@ -1148,6 +1258,15 @@ impl<C: AstFormatter> FmtWithCtx<C> for llbc::Statement {
                     body.fmt_with_ctx_and_indent(&inner_tab, ctx),
                 )
             }
+            RawStatement::Labeled(label, body) => {
+                let inner_tab = format!("{tab}{TAB_INCR}");
+                write!(
+                    &mut out,
+                    "{tab}'label{label}: {{\n{}{tab}}}",
+                    body.fmt_with_ctx_and_indent(&inner_tab, ctx),
+                )
+            }
+            RawStatement::Goto(label) => write!(&mut out, "{tab}goto 'label{label}"),
             RawStatement::Error(s) => write!(&mut out, "{tab}@ERROR({})", s),
         };
         out
@@ -1595,6 +1714,7 @@ impl std::fmt::Display for BuiltinFunId {
                 let mutability = mutability.variant_name();
                 &format!("{ty}{op}{mutability}")
             }
+            BuiltinFunId::Async(op) => op.variant_name(),
         };
         f.write_str(name)
     }
@@ -1645,6 +1765,11 @@ impl std::fmt::Display for Literal {
             Literal::Char(v) => write!(f, "{v}"),
             Literal::Str(v) => write!(f, "\"{v}\""),
             Literal::ByteStr(v) => write!(f, "{v:?}"),
+            Literal::CStr(v) => {
+                // Don't print the trailing NUL.
+                let v = v.strip_suffix(&[0]).unwrap_or(v);
+                write!(f, "c\"{}\"", v.escape_ascii())
+            }
         }
     }
 }
@@ -1699,6 +1824,17 @@ impl std::fmt::Display for Region {
     }
 }
 
+impl std::fmt::Display for RetagKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            RetagKind::FnEntry => write!(f, "fn_entry"),
+            RetagKind::TwoPhase => write!(f, "two_phase"),
+            RetagKind::Raw => write!(f, "raw"),
+            RetagKind::Default => write!(f, "default"),
+        }
+    }
+}
+
 impl std::fmt::Display for Rvalue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
         write!(f, "{}", self.fmt_with_ctx(&FmtCtx::new()))
@@ -1726,7 +1862,12 @@ impl std::fmt::Display for ScalarValue {
 
 impl std::fmt::Display for FloatValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-        let v = &self.value;
+        // The decimal representation doesn't distinguish the NaNs: we print their bits instead.
+        let v = if self.is_nan() {
+            format!("NaN(0x{:x})", self.bits)
+        } else {
+            self.value.clone()
+        };
         match self.ty {
             FloatTy::F16 => write!(f, "{v} : f16"),
             FloatTy::F32 => write!(f, "{v} : f32"),
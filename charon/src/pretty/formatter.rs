@@ -1,6 +1,8 @@
 use std::borrow::Cow;
 use std::collections::VecDeque;
 
+use itertools::Itertools;
+
 use crate::ast::*;
 use crate::common::TAB_INCR;
 use crate::gast;
@@ -82,6 +84,7 @@ impl<'a, 'b> SetGenerics<'a> for FmtCtx<'b> {
             translated: self.translated.as_deref(),
             generics: [Cow::Borrowed(generics)].into(),
             locals: self.locals.as_deref(),
+            options: self.options,
         }
     }
 }
@@ -102,6 +105,7 @@ impl<'a, 'b> SetLocals<'a> for FmtCtx<'b> {
             translated: self.translated.as_deref(),
             generics: self.generics.clone(),
             locals: Some(locals),
+            options: self.options,
         }
     }
 }
@@ -126,6 +130,7 @@ impl<'a, 'b> PushBoundRegions<'a> for FmtCtx<'b> {
             translated: self.translated.as_deref(),
             generics,
             locals: self.locals.as_deref(),
+            options: self.options,
         }
     }
 }
@@ -150,7 +155,97 @@ pub trait AstFormatter = Formatter<TypeVarId>
     + for<'a> Formatter<&'a llbc_ast::Block>
     + for<'a> SetGenerics<'a>
     + for<'a> SetLocals<'a>
-    + for<'a> PushBoundRegions<'a>;
+    + for<'a> PushBoundRegions<'a>
+    + GetPrintOptions;
+
+/// The options which control the pretty-printing (see [render_item]). The default options give
+/// the output of `--print-llbc`.
+#[derive(Debug, Clone, Copy)]
+pub struct PrintOptions {
+    /// Print the ids of the items next to their names, e.g. `foo@Fun3`.
+    pub show_ids: bool,
+    /// Print the source location of the items and statements in comments.
+    pub show_spans: bool,
+    /// The number of spaces per indentation level.
+    pub tab_width: usize,
+    /// Don't print the generic arguments of the types and functions.
+    pub hide_generics: bool,
+    /// Print the source comments attached to the statements (see
+    /// [llbc_ast::Statement::comments_before]).
+    pub inline_comments: bool,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        PrintOptions {
+            show_ids: false,
+            show_spans: false,
+            tab_width: TAB_INCR.len(),
+            hide_generics: false,
+            inline_comments: true,
+        }
+    }
+}
+
+/// Access the [PrintOptions] of a formatter.
+pub trait GetPrintOptions {
+    fn print_options(&self) -> PrintOptions;
+
+    /// Format a span as `file:line:col`, if the formatter knows the file names.
+    fn format_span(&self, _span: &RawSpan) -> String {
+        String::new()
+    }
+}
+
+impl<'a> GetPrintOptions for FmtCtx<'a> {
+    fn print_options(&self) -> PrintOptions {
+        self.options
+    }
+
+    fn format_span(&self, span: &RawSpan) -> String {
+        let file = match self
+            .translated
+            .and_then(|translated| translated.id_to_file.get(span.file_id))
+        {
+            Some(FileName::Virtual(path) | FileName::Local(path)) => path.display().to_string(),
+            Some(FileName::NotReal(name)) => name.clone(),
+            None => span.file_id.to_pretty_string(),
+        };
+        format!("{file}:{}:{}", span.beg.line, span.beg.col)
+    }
+}
+
+/// Render an item of the crate the way `--print-llbc` does, with the given options. This is the
+/// entry point for the consumers which display items, e.g. in a UI.
+pub fn render_item(krate: &TranslatedCrate, id: AnyTransId, options: &PrintOptions) -> String {
+    let ctx = FmtCtx {
+        translated: Some(krate),
+        options: *options,
+        ..FmtCtx::default()
+    };
+    let mut out = String::new();
+    if options.show_spans {
+        if let Some(item) = krate.get_item(id) {
+            out.push_str(&format!(
+                "// {}\n",
+                ctx.format_span(&item.item_meta().span.span)
+            ));
+        }
+    }
+    out.push_str(&ctx.format_decl_id(id));
+    if options.tab_width == TAB_INCR.len() {
+        return out;
+    }
+    // Re-indent: the printers indent with `TAB_INCR`.
+    let indent = " ".repeat(options.tab_width);
+    out.lines()
+        .map(|line| {
+            let trimmed = line.trim_start_matches(TAB_INCR);
+            let levels = (line.len() - trimmed.len()) / TAB_INCR.len();
+            format!("{}{trimmed}", indent.repeat(levels))
+        })
+        .join("\n")
+}
 
 /// For formatting.
 ///
@@ -169,6 +264,7 @@ pub struct FmtCtx<'a> {
     /// work, we keep the innermost parameters at the start of the vector.
     pub generics: VecDeque<Cow<'a, GenericParams>>,
     pub locals: Option<&'a Vector<VarId, ast::Var>>,
+    pub options: PrintOptions,
 }
 
 impl<'a> FmtCtx<'a> {
@@ -258,6 +354,7 @@ impl<'a> Formatter<AnyTransId> for FmtCtx<'a> {
             .and_then(|translated| translated.item_name(id))
         {
             None => id.to_string(),
+            Some(name) if self.options.show_ids => format!("{}{id}", name.fmt_with_ctx(self)),
             Some(name) => name.fmt_with_ctx(self),
         }
     }
@@ -335,27 +432,28 @@ impl<'a> Formatter<&ImplElem> for FmtCtx<'a> {
                 // Just printing the generics (not the predicates)
                 ty.fmt_with_ctx(&self.set_generics(generics))
             }
-            ImplElem::Trait(impl_id) => {
-                match &self.translated {
-                    None => format!("impl#{impl_id}"),
-                    Some(translated) => match translated.trait_impls.get(*impl_id) {
-                        None => format!("impl#{impl_id}"),
-                        Some(timpl) => {
-                            // We need to put the first type parameter aside: it is
-                            // the type for which we implement the trait.
-                            // This is not very clean because it's hard to move the
-                            // first element out of a vector...
-                            let ctx = &self.set_generics(&timpl.generics);
-                            let TraitDeclRef { trait_id, generics } = &timpl.impl_trait;
-                            let (ty, generics) = generics.pop_first_type_arg();
-                            let tr = TraitDeclRef {
-                                trait_id: *trait_id,
-                                generics,
-                            };
-                            format!("impl {} for {}", tr.fmt_with_ctx(ctx), ty.fmt_with_ctx(ctx))
-                        }
-                    },
-                }
+            ImplElem::Trait(_, generics, impl_trait) => {
+                // We need to put the first type parameter aside: it is
+                // the type for which we implement the trait.
+                let ctx = &self.set_generics(generics);
+                let (ty, generics) = impl_trait.generics.pop_first_type_arg();
+                let tr = TraitDeclRef {
+                    trait_id: impl_trait.trait_id,
+                    generics,
+                };
+                format!("impl {} for {}", tr.fmt_with_ctx(ctx), ty.fmt_with_ctx(ctx))
+            }
+            ImplElem::Builtin(impl_trait) => {
+                let (ty, generics) = impl_trait.generics.pop_first_type_arg();
+                let tr = TraitDeclRef {
+                    trait_id: impl_trait.trait_id,
+                    generics,
+                };
+                format!(
+                    "impl {} for {}",
+                    tr.fmt_with_ctx(self),
+                    ty.fmt_with_ctx(self)
+                )
             }
         };
 
@@ -1,4 +1,5 @@
 //! This module contains functions to pretty-print charon types.
 pub mod fmt_with_ctx;
 pub mod formatter;
+pub mod parser;
 pub use fmt_with_ctx::FmtWithCtx;
@@ -0,0 +1,665 @@
+//! A parser for the pretty-printed LLBC bodies, i.e. the inverse of the printing of an
+//! [llbc_ast::ExprBody] (see `--print-llbc`). This allows writing LLBC by hand, e.g. for test
+//! inputs or for the models of the opaque functions. The entry point is [parse_body].
+//!
+//! We only parse a well-defined subset of the syntax, which covers the bodies of simple monomorphic
+//! functions:
+//! - types: the literal types, `!`, the tuples, the references (`&'_ (T)`, `&'_ mut (T)`) and the
+//!   ADTs of the crate without generic arguments;
+//! - places: the locals, the dereferences and the field projections;
+//! - rvalues: the operands (`copy`, `move` and the integer and boolean constants), the binary and
+//!   unary operations, the borrows, the tuples and `@discriminant`;
+//! - statements: the assignments, the calls to the functions of the crate without generic
+//!   arguments, `drop`, `@fake_read`, `assert`, `panic`, `undefined_behavior`, `return`, `break`,
+//!   `continue`, `nop`, `if`, `switch` and `loop`.
+//!
+//! A file of models (see [parse_models]) is a sequence of `fn <name pattern> { <body> }`.
+//!
+//! The spans of the parsed body are dummy spans. The whole-line comments before a statement are
+//! stored in its [llbc_ast::Statement::comments_before].
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    character::complete::{char, digit1, multispace0, not_line_ending, satisfy},
+    combinator::{map_res, not, opt, peek, recognize, value},
+    multi::{many0, separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated},
+    Parser,
+};
+use nom_supreme::{error::ErrorTree, final_parser::Location, ParserExt};
+
+use crate::ast::*;
+use crate::ids::Vector;
+use crate::llbc_ast::{Block, ExprBody, RawStatement, Statement, Switch};
+use crate::name_matcher::{parser::parse_pattern, NamePattern};
+use crate::pretty::formatter::IntoFormatter;
+use crate::pretty::FmtWithCtx;
+
+type ParseResult<'a, T> = nom::IResult<&'a str, T, ErrorTree<&'a str>>;
+
+/// Parse a pretty-printed LLBC body: the declarations of the locals followed by the statements,
+/// optionally surrounded by braces. The names of the types and functions are resolved in `krate`.
+/// The number of arguments is given by the `// arg #i` comments of the declarations, as printed.
+pub fn parse_body(krate: &TranslatedCrate, text: &str) -> Result<ExprBody, ErrorTree<Location>> {
+    let mut parser = BodyParser::new(krate);
+    nom_supreme::final_parser::final_parser(|i| parser.parse_body(i))(text)
+}
+
+/// Parse a file of models: a sequence of `fn <name pattern> { <body> }`, where the pattern uses
+/// the name-matcher syntax (see `--include`) and the body is parsed like in [parse_body].
+pub fn parse_models(
+    krate: &TranslatedCrate,
+    text: &str,
+) -> Result<Vec<(NamePattern, ExprBody)>, ErrorTree<Location>> {
+    let mut parser = BodyParser::new(krate);
+    nom_supreme::final_parser::final_parser(|i| parser.parse_models(i))(text)
+}
+
+/// Skip the whitespace.
+fn ws(i: &str) -> ParseResult<'_, &str> {
+    multispace0(i)
+}
+
+/// A token, followed by whitespace.
+fn token<'a>(t: &'static str) -> impl FnMut(&'a str) -> ParseResult<'a, &'a str> {
+    terminated(tag(t), multispace0)
+}
+
+/// A keyword, which must not be followed by an identifier character (e.g. `return` doesn't
+/// match the beginning of `return_value@3`).
+fn keyword<'a>(kw: &'static str) -> impl FnMut(&'a str) -> ParseResult<'a, &'a str> {
+    terminated(
+        terminated(
+            tag(kw),
+            not(peek(satisfy(|c: char| {
+                c.is_alphanumeric() || c == '_' || c == '@'
+            }))),
+        ),
+        multispace0,
+    )
+}
+
+fn ident(i: &str) -> ParseResult<'_, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || c == '_')(i)
+}
+
+/// A path like `core::option::Option`.
+fn path(i: &str) -> ParseResult<'_, &str> {
+    recognize(separated_list1(tag("::"), ident))(i)
+}
+
+fn number<T: FromStr>(i: &str) -> ParseResult<'_, T> {
+    map_res(digit1, str::parse).terminated(multispace0).parse(i)
+}
+
+/// A `// comment` line, without the `//`.
+fn comment(i: &str) -> ParseResult<'_, String> {
+    preceded(tag("//"), not_line_ending)
+        .map(|s: &str| s.strip_prefix(' ').unwrap_or(s).to_string())
+        .terminated(multispace0)
+        .parse(i)
+}
+
+fn integer_ty(name: &str) -> Option<IntegerTy> {
+    Some(match name {
+        "isize" => IntegerTy::Isize,
+        "i8" => IntegerTy::I8,
+        "i16" => IntegerTy::I16,
+        "i32" => IntegerTy::I32,
+        "i64" => IntegerTy::I64,
+        "i128" => IntegerTy::I128,
+        "usize" => IntegerTy::Usize,
+        "u8" => IntegerTy::U8,
+        "u16" => IntegerTy::U16,
+        "u32" => IntegerTy::U32,
+        "u64" => IntegerTy::U64,
+        "u128" => IntegerTy::U128,
+        _ => return None,
+    })
+}
+
+fn literal_ty(name: &str) -> Option<LiteralTy> {
+    Some(match name {
+        "bool" => LiteralTy::Bool,
+        "char" => LiteralTy::Char,
+        "f16" => LiteralTy::Float(FloatTy::F16),
+        "f32" => LiteralTy::Float(FloatTy::F32),
+        "f64" => LiteralTy::Float(FloatTy::F64),
+        "f128" => LiteralTy::Float(FloatTy::F128),
+        _ => LiteralTy::Integer(integer_ty(name)?),
+    })
+}
+
+/// An integer value, printed as `value : ty`.
+fn scalar_value(i: &str) -> ParseResult<'_, ScalarValue> {
+    map_res(
+        pair(
+            recognize(pair(opt(char('-')), digit1)).terminated(multispace0),
+            preceded(token(":"), ident.terminated(multispace0)),
+        ),
+        |(value, ty): (&str, &str)| {
+            let ty =
+                integer_ty(ty).ok_or_else(|| anyhow::anyhow!("`{ty}` is not an integer type"))?;
+            let value = match value.parse::<i128>() {
+                Ok(v) if ty.is_signed() => ScalarValue::from_int(ty, v),
+                _ => ScalarValue::from_uint(ty, value.parse()?),
+            };
+            value.map_err(|err| anyhow::anyhow!("invalid `{ty}` constant: {err:?}"))
+        },
+    )(i)
+}
+
+fn bool_value(i: &str) -> ParseResult<'_, bool> {
+    alt((
+        keyword("true").map(|_| true),
+        keyword("false").map(|_| false),
+    ))(i)
+}
+
+fn binop(i: &str) -> ParseResult<'_, BinOp> {
+    use BinOp::*;
+    alt((
+        alt((
+            value(CheckedAdd, tag("checked.+")),
+            value(CheckedSub, tag("checked.-")),
+            value(CheckedMul, tag("checked.*")),
+            value(Eq, tag("==")),
+            value(Ne, tag("!=")),
+            value(Le, tag("<=")),
+            value(Ge, tag(">=")),
+            value(Shl, tag("<<")),
+            value(Shr, tag(">>")),
+        )),
+        alt((
+            value(Lt, tag("<")),
+            value(Gt, tag(">")),
+            value(Add, tag("+")),
+            value(Sub, tag("-")),
+            value(Mul, tag("*")),
+            value(Div, tag("/")),
+            value(Rem, tag("%")),
+            value(BitAnd, tag("&")),
+            value(BitOr, tag("|")),
+            value(BitXor, tag("^")),
+        )),
+    ))
+    .terminated(multispace0)
+    .parse(i)
+}
+
+struct BodyParser<'a> {
+    krate: &'a TranslatedCrate,
+    /// The items of the crate, by printed name.
+    items: HashMap<String, AnyTransId>,
+    /// The locals declared so far.
+    locals: Vector<VarId, Var>,
+}
+
+impl<'a> BodyParser<'a> {
+    fn new(krate: &'a TranslatedCrate) -> Self {
+        let fmt_ctx = krate.into_fmt();
+        let items = krate
+            .item_names
+            .iter()
+            .map(|(id, name)| (name.fmt_with_ctx(&fmt_ctx), *id))
+            .collect();
+        BodyParser {
+            krate,
+            items,
+            locals: Vector::new(),
+        }
+    }
+
+    fn parse_body<'i>(&mut self, i: &'i str) -> ParseResult<'i, ExprBody> {
+        let (i, braced) = preceded(ws, opt(token("{")))(i)?;
+        let mut i = i;
+        let mut arg_count = 0;
+        loop {
+            match self.parse_local(i) {
+                Ok((rest, (var, is_arg))) => {
+                    arg_count += is_arg as usize;
+                    self.locals.push(var);
+                    i = rest;
+                }
+                Err(nom::Err::Error(_)) => break,
+                Err(err) => return Err(err),
+            }
+        }
+        let (i, body) = self.parse_block_contents(i)?;
+        let (i, _) = if braced.is_some() {
+            token("}")(i)?
+        } else {
+            (i, "")
+        };
+        let body = ExprBody {
+            span: Span::dummy(),
+            arg_count,
+            locals: std::mem::take(&mut self.locals),
+            comments: Vec::new(),
+            debug_info: None,
+            body,
+        };
+        Ok((i, body))
+    }
+
+    fn parse_models<'i>(&mut self, i: &'i str) -> ParseResult<'i, Vec<(NamePattern, ExprBody)>> {
+        let (mut i, _) = preceded(ws, many0(comment))(i)?;
+        let mut models = Vec::new();
+        while !i.is_empty() {
+            let (rest, pattern) = preceded(keyword("fn"), parse_pattern.terminated(multispace0))
+                .cut()
+                .parse(i)?;
+            let (rest, body) = preceded(peek(tag("{")), |i| self.parse_body(i))
+                .cut()
+                .parse(rest)?;
+            let (rest, _) = many0(comment)(rest)?;
+            models.push((pattern, body));
+            i = rest;
+        }
+        Ok((i, models))
+    }
+
+    /// `let name@N: ty; // comment`. Also returns whether the local is an argument.
+    fn parse_local<'i>(&self, i: &'i str) -> ParseResult<'i, (Var, bool)> {
+        // The locals must be declared in order.
+        let index = self.locals.len();
+        let (i, name) = preceded(
+            keyword("let"),
+            map_res(
+                pair(opt(ident), preceded(char('@'), number::<usize>)),
+                |(name, id)| {
+                    if id == index {
+                        Ok(name)
+                    } else {
+                        Err(anyhow::anyhow!(
+                            "expected the declaration of local @{index}"
+                        ))
+                    }
+                },
+            )
+            .cut(),
+        )(i)?;
+        let (i, ty) = delimited(token(":"), |i| self.parse_ty(i), token(";"))(i)?;
+        let (i, comment) = opt(comment)(i)?;
+        let is_arg = comment.is_some_and(|c| c.starts_with("arg #"));
+        let var = Var {
+            index: VarId::new(index),
+            name: name.map(str::to_string),
+            ty,
+        };
+        Ok((i, (var, is_arg)))
+    }
+
+    fn parse_ty<'i>(&self, i: &'i str) -> ParseResult<'i, Ty> {
+        let region = alt((
+            tag("'_").map(|_| Region::Erased),
+            tag("'static").map(|_| Region::Static),
+        ))
+        .terminated(multispace0);
+        let reference = preceded(
+            char('&'),
+            pair(
+                region.and(opt(keyword("mut"))),
+                delimited(token("("), |i| self.parse_ty(i), token(")")),
+            ),
+        )
+        .map(|((region, mutability), ty)| {
+            let kind = if mutability.is_some() {
+                RefKind::Mut
+            } else {
+                RefKind::Shared
+            };
+            TyKind::Ref(region, ty, kind).into_ty()
+        });
+        let tuple = delimited(
+            token("("),
+            separated_list0(token(","), |i| self.parse_ty(i)),
+            token(")"),
+        )
+        .map(|tys| {
+            let generics = GenericArgs::new_from_types(tys.into_iter().collect());
+            TyKind::Adt(TypeId::Tuple, generics).into_ty()
+        });
+        let never = token("!").map(|_| TyKind::Never.into_ty());
+        let named = map_res(path.terminated(multispace0), |name| {
+            if let Some(ty) = literal_ty(name) {
+                return Ok(TyKind::Literal(ty).into_ty());
+            }
+            match self.items.get(name) {
+                Some(AnyTransId::Type(id)) => {
+                    Ok(TyKind::Adt(TypeId::Adt(*id), GenericArgs::empty()).into_ty())
+                }
+                _ => Err(anyhow::anyhow!("unknown type `{name}`")),
+            }
+        });
+        alt((reference, tuple, never, named))(i)
+    }
+
+    fn place_ty(&self, place: &Place) -> Option<Ty> {
+        let mut ty = self.locals.get(place.var_id)?.ty.clone();
+        for elem in &place.projection {
+            ty = elem.project_ty(self.krate, &ty).ok()??;
+        }
+        Some(ty)
+    }
+
+    /// Resolve the field `field` (a name or an index) of a place of type `ty`.
+    fn field_projection(
+        &self,
+        ty: &Ty,
+        variant: Option<VariantId>,
+        field: &str,
+    ) -> anyhow::Result<ProjectionElem> {
+        let by_index = field.parse::<usize>().ok().map(FieldId::new);
+        match ty.kind() {
+            TyKind::Adt(TypeId::Tuple, generics) => {
+                let field_id = by_index.ok_or_else(|| anyhow::anyhow!("invalid tuple field"))?;
+                let kind = FieldProjKind::Tuple(generics.types.len());
+                Ok(ProjectionElem::Field(kind, field_id))
+            }
+            TyKind::Adt(TypeId::Adt(id), _) => {
+                let decl = self
+                    .krate
+                    .type_decls
+                    .get(*id)
+                    .ok_or_else(|| anyhow::anyhow!("missing type declaration"))?;
+                let fields = match (&decl.kind, variant) {
+                    (TypeDeclKind::Struct(fields) | TypeDeclKind::Union(fields), None) => fields,
+                    (TypeDeclKind::Enum(variants), Some(variant)) => variants
+                        .get(variant)
+                        .map(|v| &v.fields)
+                        .ok_or_else(|| anyhow::anyhow!("invalid variant"))?,
+                    _ => anyhow::bail!("invalid field projection"),
+                };
+                let field_id = fields
+                    .iter_indexed()
+                    .find(|(_, f)| f.name.as_deref() == Some(field))
+                    .map(|(id, _)| id)
+                    .or(by_index)
+                    .ok_or_else(|| anyhow::anyhow!("unknown field `{field}`"))?;
+                Ok(ProjectionElem::Field(
+                    FieldProjKind::Adt(*id, variant),
+                    field_id,
+                ))
+            }
+            _ => anyhow::bail!("field projection on a non-ADT type"),
+        }
+    }
+
+    fn parse_var<'i>(&self, i: &'i str) -> ParseResult<'i, VarId> {
+        map_res(
+            preceded(opt(ident), preceded(char('@'), number::<usize>)),
+            |id| {
+                let id = VarId::new(id);
+                if self.locals.get(id).is_some() {
+                    Ok(id)
+                } else {
+                    Err(anyhow::anyhow!(
+                        "undeclared local {}",
+                        id.to_pretty_string()
+                    ))
+                }
+            },
+        )(i)
+    }
+
+    fn parse_place<'i>(&self, i: &'i str) -> ParseResult<'i, Place> {
+        let deref = delimited(token("*("), |i| self.parse_place(i), token(")")).map(|mut p| {
+            p.projection.push(ProjectionElem::Deref);
+            p
+        });
+        let variant = preceded(
+            pair(keyword("as"), keyword("variant")),
+            preceded(char('@'), number::<usize>).map(VariantId::new),
+        );
+        let field = map_res(
+            pair(
+                delimited(
+                    token("("),
+                    pair(|i| self.parse_place(i), opt(variant)),
+                    token(")"),
+                ),
+                preceded(char('.'), ident.terminated(multispace0)),
+            ),
+            |((mut place, variant), field)| {
+                let ty = self
+                    .place_ty(&place)
+                    .ok_or_else(|| anyhow::anyhow!("unknown type for field projection"))?;
+                let elem = self.field_projection(&ty, variant, field)?;
+                place.projection.push(elem);
+                anyhow::Ok(place)
+            },
+        );
+        let var = (|i| self.parse_var(i))
+            .terminated(multispace0)
+            .map(Place::new);
+        alt((deref, field, var))(i)
+    }
+
+    fn parse_constant<'i>(&self, i: &'i str) -> ParseResult<'i, ConstantExpr> {
+        let boolean = bool_value.map(|b| ConstantExpr {
+            value: RawConstantExpr::Literal(Literal::Bool(b)),
+            ty: TyKind::Literal(LiteralTy::Bool).into_ty(),
+            has_type_suffix: None,
+        });
+        alt((boolean, scalar_value.map(ScalarValue::to_constant)))(i)
+    }
+
+    fn parse_operand<'i>(&self, i: &'i str) -> ParseResult<'i, Operand> {
+        let place = |i: &'i str| delimited(token("("), |i| self.parse_place(i), token(")"))(i);
+        alt((
+            preceded(keyword("copy"), place).map(Operand::Copy),
+            preceded(keyword("move"), place).map(Operand::Move),
+            preceded(
+                keyword("const"),
+                delimited(token("("), |i| self.parse_constant(i), token(")")),
+            )
+            .map(Operand::Const),
+        ))(i)
+    }
+
+    fn operand_ty(&self, op: &Operand) -> Option<Ty> {
+        match op {
+            Operand::Copy(p) | Operand::Move(p) => self.place_ty(p),
+            Operand::Const(c) => Some(c.ty.clone()),
+        }
+    }
+
+    fn parse_rvalue<'i>(&self, i: &'i str) -> ParseResult<'i, Rvalue> {
+        let borrow = alt((
+            preceded(keyword("&mut"), |i| self.parse_place(i))
+                .map(|p| Rvalue::Ref(p, BorrowKind::Mut)),
+            preceded(token("&"), |i| self.parse_place(i))
+                .map(|p| Rvalue::Ref(p, BorrowKind::Shared)),
+        ));
+        let discriminant = map_res(
+            delimited(token("@discriminant("), |i| self.parse_place(i), token(")")),
+            |p| match self.place_ty(&p).as_ref().map(Ty::kind) {
+                Some(TyKind::Adt(TypeId::Adt(id), _)) => Ok(Rvalue::Discriminant(p, *id)),
+                _ => Err(anyhow::anyhow!("discriminant of a non-ADT place")),
+            },
+        );
+        let unop = pair(
+            alt((
+                token("-(").map(|_| UnOp::Neg),
+                token("~(").map(|_| UnOp::Not),
+            )),
+            terminated(|i| self.parse_operand(i), token(")")),
+        )
+        .map(|(op, x)| Rvalue::UnaryOp(op, x));
+        let tuple = map_res(
+            delimited(
+                token("("),
+                separated_list0(token(","), |i| self.parse_operand(i)),
+                token(")"),
+            ),
+            |ops| {
+                let tys = ops
+                    .iter()
+                    .map(|op| self.operand_ty(op))
+                    .collect::<Option<Vector<TypeVarId, Ty>>>()
+                    .ok_or_else(|| anyhow::anyhow!("unknown type in tuple"))?;
+                let kind =
+                    AggregateKind::Adt(TypeId::Tuple, None, None, GenericArgs::new_from_types(tys));
+                anyhow::Ok(Rvalue::Aggregate(kind, ops))
+            },
+        );
+        let operation = map_res(
+            pair(
+                |i| self.parse_operand(i),
+                opt(pair(binop, |i| self.parse_operand(i))),
+            ),
+            |(x, rest)| match rest {
+                None => Ok(Rvalue::Use(x)),
+                Some((op, y)) => {
+                    let (Some(lhs_ty), Some(rhs_ty)) = (self.operand_ty(&x), self.operand_ty(&y))
+                    else {
+                        return Err(anyhow::anyhow!("unknown operand type"));
+                    };
+                    Ok(Rvalue::BinaryOp(op, x, y, BinOpTys { lhs_ty, rhs_ty }))
+                }
+            },
+        );
+        alt((borrow, discriminant, unop, tuple, operation))(i)
+    }
+
+    /// `f(args)`, where `f` is a function of the crate.
+    fn parse_call<'i>(&self, dest: Place, i: &'i str) -> ParseResult<'i, RawStatement> {
+        let func = map_res(terminated(path, char('(')), |name| {
+            match self.items.get(name) {
+                Some(AnyTransId::Fun(id)) => Ok(*id),
+                _ => Err(anyhow::anyhow!("unknown function `{name}`")),
+            }
+        })
+        .terminated(multispace0);
+        let (i, (id, args)) = pair(
+            func,
+            terminated(
+                separated_list0(token(","), |i| self.parse_operand(i)),
+                token(")"),
+            ),
+        )(i)?;
+        let call = Call {
+            func: FnOperand::Regular(FnPtr {
+                func: FunIdOrTraitMethodRef::Fun(FunId::Regular(id)),
+                generics: GenericArgs::empty(),
+            }),
+            args,
+            dest,
+            fn_ptr_sig: None,
+        };
+        Ok((i, RawStatement::Call(call)))
+    }
+
+    fn parse_assign<'i>(&self, i: &'i str) -> ParseResult<'i, RawStatement> {
+        let (i, dest) = terminated(|i| self.parse_place(i), token(":="))(i)?;
+        let rvalue = |i: &'i str| self.parse_rvalue(i);
+        alt((
+            |i| self.parse_call(dest.clone(), i),
+            rvalue.map(|rv| RawStatement::Assign(dest.clone(), rv)),
+        ))(i)
+    }
+
+    fn parse_braced_block<'i>(&self, i: &'i str) -> ParseResult<'i, Block> {
+        delimited(token("{"), |i| self.parse_block_contents(i), token("}"))(i)
+    }
+
+    fn parse_switch<'i>(&self, i: &'i str) -> ParseResult<'i, Switch> {
+        let if_then_else = pair(
+            preceded(keyword("if"), |i| self.parse_operand(i)),
+            pair(
+                |i| self.parse_braced_block(i),
+                preceded(keyword("else"), |i| self.parse_braced_block(i)),
+            ),
+        )
+        .map(|(cond, (then_block, else_block))| Switch::If(cond, then_block, else_block));
+        let branch = pair(
+            separated_list1(token("|"), scalar_value),
+            preceded(token("=>"), |i| self.parse_braced_block(i)),
+        )
+        .terminated(token(","));
+        let otherwise = delimited(
+            pair(token("_"), token("=>")),
+            |i| self.parse_braced_block(i),
+            token(","),
+        );
+        let switch_int = map_res(
+            pair(
+                preceded(keyword("switch"), |i| self.parse_operand(i)),
+                delimited(token("{"), pair(many0(branch), otherwise), token("}")),
+            ),
+            |(discr, (branches, otherwise))| {
+                let ty = match self.operand_ty(&discr).as_ref().map(Ty::kind) {
+                    Some(TyKind::Literal(LiteralTy::Integer(ty))) => *ty,
+                    _ => anyhow::bail!("switch on a non-integer operand"),
+                };
+                Ok(Switch::SwitchInt(discr, ty, branches, otherwise))
+            },
+        );
+        alt((if_then_else, switch_int))(i)
+    }
+
+    fn parse_statement<'i>(&self, i: &'i str) -> ParseResult<'i, Statement> {
+        let place = |i: &'i str| self.parse_place(i);
+        let (i, comments_before) = many0(comment)(i)?;
+        let (i, content) = alt((
+            alt((
+                value(RawStatement::Return, keyword("return")),
+                value(RawStatement::Nop, keyword("nop")),
+                value(
+                    RawStatement::Abort(AbortKind::UndefinedBehavior),
+                    keyword("undefined_behavior"),
+                ),
+                preceded(keyword("break"), number).map(RawStatement::Break),
+                preceded(keyword("continue"), number).map(RawStatement::Continue),
+                preceded(keyword("drop"), place).map(RawStatement::Drop),
+                delimited(token("@fake_read("), place, token(")")).map(RawStatement::FakeRead),
+            )),
+            delimited(token("panic("), path.terminated(multispace0), token(")")).map(
+                |name: &str| {
+                    let name = name
+                        .split("::")
+                        .map(|elem| PathElem::Ident(elem.to_string(), Disambiguator::ZERO))
+                        .collect();
+                    RawStatement::Abort(AbortKind::Panic(Name { name }))
+                },
+            ),
+            delimited(
+                token("assert("),
+                pair(
+                    pair(|i| self.parse_operand(i), preceded(token("=="), bool_value)),
+                    many0(preceded(token(","), |i| self.parse_operand(i))),
+                ),
+                token(")"),
+            )
+            .map(|((cond, expected), message)| {
+                RawStatement::Assert(Assert {
+                    cond,
+                    expected,
+                    message,
+                })
+            }),
+            preceded(keyword("loop"), |i| self.parse_braced_block(i)).map(RawStatement::Loop),
+            (|i| self.parse_switch(i)).map(RawStatement::Switch),
+            |i| self.parse_assign(i),
+        ))(i)?;
+        let mut st = Statement::new(Span::dummy(), content);
+        st.comments_before = comments_before;
+        Ok((i, st))
+    }
+
+    /// A sequence of statements, up to the closing brace or the end of the input.
+    fn parse_block_contents<'i>(&self, i: &'i str) -> ParseResult<'i, Block> {
+        let (i, statements) = preceded(ws, many0(|i| self.parse_statement(i)))(i)?;
+        // Skip the comments after the last statement.
+        let (i, _) = many0(comment)(i)?;
+        let block = Block {
+            span: Span::dummy(),
+            statements,
+        };
+        Ok((i, block))
+    }
+}
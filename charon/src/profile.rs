@@ -0,0 +1,92 @@
+//! A lightweight profiler for the extraction (see `--profile-extraction`). It records how long the
+//! hax export, the translation of each item and each pass take, and writes them in the Chrome
+//! trace event format, which `chrome://tracing`, Perfetto and speedscope can display as a flame
+//! graph.
+//!
+//! The events are recorded with their start time and duration, so the nesting (e.g. the hax export
+//! of an item inside the translation of that item) is inferred by the viewers.
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// A complete event ("X" phase) of the Chrome trace event format.
+#[derive(Debug, Serialize)]
+struct Event {
+    name: String,
+    cat: &'static str,
+    ph: &'static str,
+    /// Start of the event, in microseconds since the start of the profiling.
+    ts: u64,
+    /// Duration of the event, in microseconds.
+    dur: u64,
+    pid: u32,
+    tid: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct Trace<'a> {
+    #[serde(rename = "traceEvents")]
+    trace_events: &'a [Event],
+}
+
+/// Records the events when profiling is enabled, and does nothing otherwise.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    /// `None` when profiling is disabled.
+    state: Option<(Instant, Vec<Event>)>,
+}
+
+impl Profiler {
+    pub fn new(enabled: bool) -> Self {
+        Profiler {
+            state: enabled.then(|| (Instant::now(), Vec::new())),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state.is_some()
+    }
+
+    /// The start time of an event, to pass to [Self::record] once the event is over. This is
+    /// `None` when profiling is disabled, so that we don't query the clock needlessly.
+    pub fn start(&self) -> Option<Instant> {
+        self.state.as_ref().map(|_| Instant::now())
+    }
+
+    /// Record an event of category `cat` which started at `start` and ends now. The name is only
+    /// computed if profiling is enabled.
+    pub fn record(
+        &mut self,
+        cat: &'static str,
+        start: Option<Instant>,
+        name: impl FnOnce() -> String,
+    ) {
+        let (Some((origin, events)), Some(start)) = (&mut self.state, start) else {
+            return;
+        };
+        events.push(Event {
+            name: name(),
+            cat,
+            ph: "X",
+            ts: start.saturating_duration_since(*origin).as_micros() as u64,
+            dur: start.elapsed().as_micros() as u64,
+            pid: std::process::id(),
+            tid: 0,
+        });
+    }
+
+    /// Write the recorded events to `path`, in the Chrome trace event format.
+    pub fn write_chrome_trace(&self, path: &Path) -> std::io::Result<()> {
+        let events = self.state.as_ref().map_or(&[][..], |(_, events)| events);
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(
+            &mut writer,
+            &Trace {
+                trace_events: events,
+            },
+        )?;
+        writer.flush()
+    }
+}
@@ -0,0 +1,349 @@
+//! A harness for golden tests of charon: we extract a `.rs` file, and compare the pretty-printed
+//! LLBC (or the errors, for negative tests) with the contents of the `.out` file next to it. This
+//! is what runs the ui tests of charon; downstream projects can use it to pin the behavior of
+//! charon on their own code snippets.
+//!
+//! The tests run the `charon` executable, whose path must be provided. Files can start with
+//! special comments that affect the test behavior: see [HELP_STRING].
+use crate::ast::TranslatedCrate;
+use crate::import::read_crate_data;
+use crate::logger;
+use anyhow::{anyhow, bail, Context};
+use indoc::indoc;
+use snapbox::filter::Filter;
+use std::fmt::Display;
+use std::fs::{read_to_string, File};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub static HELP_STRING: &str = indoc!(
+    "Options are:
+    - `//@ output=pretty-llbc`: record the pretty-printed llbc (default);
+    - `//@ known-failure`: a test that is expected to fail.
+    - `//@ exit-code=<code>`: for a `known-failure` test, the exit code with which charon must fail.
+    - `//@ known-panic`: a test that is expected to panic.
+    - `//@ skip`: skip the test.
+
+    Other comments can be used to control the behavior of charon:
+    - `//@ charon-args=<charon cli options>`
+    - `//@ rustc-args=<rustc cli options>`
+    - `//@ no-check-output`: don't store the output in a file; useful if the output is unstable or
+         differs between debug and release mode.
+    - `//@ aux-crate=<file path>`: compile this file as a crate dependency.
+    "
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestKind {
+    PrettyLlbc,
+    KnownFailure,
+    KnownPanic,
+    Skip,
+}
+
+/// The options of a test, specified with special comments at the start of the file.
+#[derive(Debug, Clone)]
+pub struct MagicComments {
+    pub test_kind: TestKind,
+    /// The options with which to run charon.
+    pub charon_opts: Vec<String>,
+    /// The options to pass to rustc.
+    pub rustc_opts: Vec<String>,
+    /// Whether we should store the test output in a file and check it.
+    pub check_output: bool,
+    /// A list of paths to files that must be compiled as dependencies for this test.
+    pub auxiliary_crates: Vec<PathBuf>,
+    /// The exit code with which a `known-failure` test must fail, if specified.
+    pub exit_code: Option<i32>,
+}
+
+impl MagicComments {
+    pub fn parse(input_path: &Path) -> anyhow::Result<Self> {
+        let mut comments = MagicComments {
+            test_kind: TestKind::PrettyLlbc,
+            charon_opts: Vec::new(),
+            rustc_opts: Vec::new(),
+            check_output: true,
+            auxiliary_crates: Vec::new(),
+            exit_code: None,
+        };
+        for line in read_to_string(input_path)?.lines() {
+            let Some(line) = line.strip_prefix("//@") else {
+                break;
+            };
+            let line = line.trim();
+            if line == "known-panic" {
+                comments.test_kind = TestKind::KnownPanic;
+            } else if line == "known-failure" {
+                comments.test_kind = TestKind::KnownFailure;
+            } else if line == "output=pretty-llbc" {
+                comments.test_kind = TestKind::PrettyLlbc;
+            } else if line == "skip" {
+                comments.test_kind = TestKind::Skip;
+            } else if line == "no-check-output" {
+                comments.check_output = false;
+            } else if let Some(charon_opts) = line.strip_prefix("charon-args=") {
+                comments
+                    .charon_opts
+                    .extend(charon_opts.split_whitespace().map(|s| s.to_string()));
+            } else if let Some(rustc_opts) = line.strip_prefix("rustc-args=") {
+                comments
+                    .rustc_opts
+                    .extend(rustc_opts.split_whitespace().map(|s| s.to_string()));
+            } else if let Some(code) = line.strip_prefix("exit-code=") {
+                comments.exit_code = Some(code.trim().parse().with_context(|| {
+                    format!("While processing file {}", input_path.to_string_lossy())
+                })?);
+            } else if let Some(crate_path) = line.strip_prefix("aux-crate=") {
+                let crate_path: PathBuf = crate_path.into();
+                let crate_path = input_path.parent().unwrap().join(crate_path);
+                comments.auxiliary_crates.push(crate_path)
+            } else {
+                return Err(
+                    anyhow!("Unknown magic comment: `{line}`. {HELP_STRING}").context(format!(
+                        "While processing file {}",
+                        input_path.to_string_lossy()
+                    )),
+                );
+            }
+        }
+        Ok(comments)
+    }
+}
+
+/// What to do with the output of a test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Check that the output matches the expected output.
+    Verify,
+    /// Overwrite the expected output with the output.
+    Overwrite,
+}
+
+/// Depending on `action`, either check that the contents of `path` matches `output`, or overwrite
+/// the file with the given output.
+pub fn compare_or_overwrite(action: Action, output: String, path: &Path) -> anyhow::Result<()> {
+    let actual = snapbox::Data::text(output);
+    let actual = snapbox::filter::FilterNewlines.filter(actual);
+    match action {
+        Action::Verify => expect_file_contents(path, actual)?,
+        Action::Overwrite => actual.write_to_path(path)?,
+    }
+    Ok(())
+}
+
+/// Compare the file contents with the provided string and error with a diff if they differ.
+fn expect_file_contents(path: &Path, actual: snapbox::Data) -> anyhow::Result<()> {
+    let expected = snapbox::Data::read_from(path, Some(snapbox::data::DataFormat::Text));
+    let expected = snapbox::filter::FilterNewlines.filter(expected);
+
+    if expected != actual {
+        let mut buf = String::new();
+        snapbox::report::write_diff(
+            &mut buf,
+            &expected,
+            &actual,
+            Some(&path.display()),
+            Some(&"charon output"),
+            Default::default(),
+        )
+        .map_err(|e| anyhow!(e.to_string()))?;
+        bail!("{buf}")
+    } else {
+        Ok(())
+    }
+}
+
+fn path_to_crate_name(path: &Path) -> Option<String> {
+    Some(
+        path.file_name()?
+            .to_str()?
+            .strip_suffix(".rs")?
+            .replace(['-'], "_"),
+    )
+}
+
+/// A golden test: the extraction of `input_path`, whose output is compared with the contents of
+/// `expected`.
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    pub input_path: PathBuf,
+    pub expected: PathBuf,
+    pub magic_comments: MagicComments,
+}
+
+impl TestCase {
+    /// The test for the given `.rs` file. The expected output is stored in the `.out` file with
+    /// the same name.
+    pub fn new(input_path: PathBuf) -> anyhow::Result<Self> {
+        let expected = input_path.with_extension("out");
+        let magic_comments = MagicComments::parse(&input_path)?;
+        Ok(TestCase {
+            input_path,
+            expected,
+            magic_comments,
+        })
+    }
+
+    /// Whether the test is marked with `//@ skip`.
+    pub fn is_ignored(&self) -> bool {
+        matches!(self.magic_comments.test_kind, TestKind::Skip)
+    }
+
+    /// Run the test with the given `charon` executable.
+    pub fn run(&self, charon: &Path, action: Action) -> anyhow::Result<()> {
+        // Dependencies
+        // Vec of (crate name, path to crate.rs, path to libcrate.rlib).
+        let deps: Vec<(String, PathBuf, String)> = self
+            .magic_comments
+            .auxiliary_crates
+            .iter()
+            .cloned()
+            .map(|path| {
+                let crate_name = path_to_crate_name(&path).unwrap();
+                let rlib_file_name = format!("lib{crate_name}.rlib"); // yep it must start with "lib"
+                let rlib_path = path.parent().unwrap().join(rlib_file_name);
+                let rlib_path = rlib_path.to_str().unwrap().to_owned();
+                (crate_name, path, rlib_path)
+            })
+            .collect();
+        for (crate_name, rs_path, rlib_path) in deps.iter() {
+            let output = Command::new("rustc")
+                .arg("--crate-type=rlib")
+                .arg(format!("--crate-name={crate_name}"))
+                .arg("-o")
+                .arg(rlib_path)
+                .arg(rs_path)
+                .output()?;
+            if !output.status.success() {
+                bail!(
+                    "Compilation of `{}` failed: {}",
+                    rs_path.display(),
+                    String::from_utf8_lossy(&output.stderr)
+                )
+            }
+        }
+
+        // Run Charon.
+        let mut cmd = Command::new(charon);
+        cmd.arg("--no-cargo");
+
+        cmd.arg("--error-on-warnings");
+        cmd.arg("--print-llbc");
+        cmd.arg("--crate=test_crate");
+        cmd.arg("--input");
+        cmd.arg(&self.input_path);
+        cmd.arg("--dest-file");
+        cmd.arg(self.input_path.with_extension("llbc"));
+
+        cmd.arg("--rustc-flag=--edition=2021");
+        for (crate_name, _, rlib_path) in deps {
+            cmd.arg(format!("--rustc-flag=--extern={crate_name}={rlib_path}"));
+        }
+        for arg in &self.magic_comments.rustc_opts {
+            cmd.arg(format!("--rustc-flag={arg}"));
+        }
+        for arg in &self.magic_comments.charon_opts {
+            cmd.arg(arg);
+        }
+
+        let output = cmd
+            .output()
+            .with_context(|| format!("could not run `{}`", charon.display()))?;
+        let stderr = String::from_utf8(output.stderr.clone())?;
+        let stdout = String::from_utf8(output.stdout.clone())?;
+
+        let test_output = match self.magic_comments.test_kind {
+            TestKind::KnownPanic => {
+                if output.status.code() != Some(101) {
+                    let status = if output.status.success() {
+                        "succeeded"
+                    } else {
+                        "errored"
+                    };
+                    bail!("Compilation was expected to panic but instead {status}: {stderr}");
+                }
+                stderr
+            }
+            TestKind::KnownFailure => {
+                if output.status.success() || output.status.code() == Some(101) {
+                    let status = if output.status.success() {
+                        "succeeded"
+                    } else {
+                        "panicked"
+                    };
+                    bail!("Compilation was expected to fail but instead {status}: {stderr}");
+                }
+                if let Some(code) = self.magic_comments.exit_code
+                    && output.status.code() != Some(code)
+                {
+                    bail!(
+                        "Compilation was expected to fail with exit code {code} but instead \
+                        exited with {}: {stderr}",
+                        output.status
+                    );
+                }
+                stderr
+            }
+            TestKind::PrettyLlbc => {
+                if !output.status.success() {
+                    bail!("Compilation failed: {stderr}")
+                }
+                stdout
+            }
+            TestKind::Skip => return Ok(()),
+        };
+        if self.magic_comments.check_output {
+            compare_or_overwrite(action, test_output, &self.expected)?;
+        } else {
+            // Remove the `out` file if there's one from a previous run.
+            if self.expected.exists() {
+                std::fs::remove_file(&self.expected)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Given a string that contains rust code, this calls the given `charon` executable on it and
+/// returns the result.
+pub fn translate_rust_text(
+    charon: &Path,
+    code: impl Display,
+    extra_args: &[&str],
+) -> anyhow::Result<TranslatedCrate> {
+    // Initialize the logger
+    logger::initialize_logger();
+
+    // Write the code to a temporary file.
+    use std::io::Write;
+    let tmp_dir = tempfile::TempDir::new()?;
+    let input_path = tmp_dir.path().join("test_crate.rs");
+    {
+        let mut tmp_file = File::create(&input_path)?;
+        write!(tmp_file, "{}", code)?;
+        drop(tmp_file);
+    }
+
+    // Call charon
+    let output_path = tmp_dir.path().join("test_crate.llbc");
+    let output = Command::new(charon)
+        .arg("--no-cargo")
+        .arg("--rustc-flag=--edition=2021")
+        .arg("--input")
+        .arg(input_path)
+        .arg("--dest-file")
+        .arg(&output_path)
+        .args(extra_args)
+        .output()
+        .with_context(|| format!("could not run `{}`", charon.display()))?;
+    if !output.status.success() {
+        bail!("Charon failed: {}", String::from_utf8_lossy(&output.stderr))
+    }
+
+    // Extract the computed crate data.
+    let crate_data = read_crate_data(&output_path)?;
+
+    Ok(crate_data.translated)
+}
@@ -0,0 +1,162 @@
+//! # Micro-pass: find the groups of (mutually) recursive types, and annotate the fields of their
+//! types which mention a type of the group with whether the recursion goes through an indirection
+//! or not (see [FieldRecursion]). Backends which generate inductive datatypes need this to know
+//! which occurrences they must wrap or can translate as is. This is opt-in (see the
+//! `--annotate-recursive-fields` option).
+use std::collections::HashSet;
+
+use derive_visitor::{visitor_enter_fn, Drive};
+use petgraph::algo::tarjan_scc;
+use petgraph::graphmap::DiGraphMap;
+
+use crate::ast::*;
+use crate::name_matcher::NamePattern;
+use crate::transform::TransformCtx;
+
+use super::ctx::TransformPass;
+
+/// The library types which store their type arguments behind a pointer.
+const INDIRECTIONS: &[&str] = &[
+    "alloc::rc::Rc",
+    "alloc::rc::Weak",
+    "alloc::sync::Arc",
+    "alloc::sync::Weak",
+    "alloc::vec::Vec",
+];
+
+struct Classifier<'a> {
+    krate: &'a TranslatedCrate,
+    indirections: Vec<NamePattern>,
+    /// The types of the recursive group.
+    group: HashSet<TypeDeclId>,
+}
+
+impl Classifier<'_> {
+    fn is_indirection(&self, id: TypeDeclId) -> bool {
+        self.krate.item_name(id).is_some_and(|name| {
+            self.indirections
+                .iter()
+                .any(|p| p.matches(self.krate, name))
+        })
+    }
+
+    /// How the type mentions the group, if it does. `behind_ptr` tells whether we are already
+    /// behind an indirection.
+    fn classify(&self, ty: &Ty, behind_ptr: bool) -> Option<FieldRecursion> {
+        let this_level = if behind_ptr {
+            FieldRecursion::Indirect
+        } else {
+            FieldRecursion::Direct
+        };
+        match ty.kind() {
+            TyKind::Adt(TypeId::Adt(id), generics) => {
+                let here = self.group.contains(id).then_some(this_level);
+                let behind_ptr = behind_ptr || self.is_indirection(*id);
+                let in_args = generics
+                    .types
+                    .iter()
+                    .filter_map(|ty| self.classify(ty, behind_ptr))
+                    .max();
+                here.max(in_args)
+            }
+            TyKind::Adt(TypeId::Builtin(BuiltinTy::Box), generics) => generics
+                .types
+                .iter()
+                .filter_map(|ty| self.classify(ty, true))
+                .max(),
+            TyKind::Adt(_, generics) => generics
+                .types
+                .iter()
+                .filter_map(|ty| self.classify(ty, behind_ptr))
+                .max(),
+            TyKind::Ref(_, ty, _) | TyKind::RawPtr(ty, _) => self.classify(ty, true),
+            // Function pointers don't store values of their argument and return types.
+            TyKind::Arrow(..) => None,
+            TyKind::TypeVar(..)
+            | TyKind::Literal(..)
+            | TyKind::Never
+            | TyKind::TraitType(..)
+            | TyKind::DynTrait(..) => None,
+        }
+    }
+}
+
+/// The types mentioned in the definition of a type.
+fn type_dependencies(decl: &TypeDecl) -> HashSet<TypeDeclId> {
+    let mut deps = HashSet::new();
+    decl.kind.drive(&mut visitor_enter_fn(|id: &TypeDeclId| {
+        deps.insert(*id);
+    }));
+    deps
+}
+
+pub struct Transform;
+impl TransformPass for Transform {
+    fn transform_ctx(&self, ctx: &mut TransformCtx<'_>) {
+        if !ctx.options.annotate_recursive_fields {
+            return;
+        }
+
+        // Compute the groups of recursive types.
+        let mut graph: DiGraphMap<TypeDeclId, ()> = DiGraphMap::new();
+        for decl in ctx.translated.type_decls.iter() {
+            graph.add_node(decl.def_id);
+            for dep in type_dependencies(decl) {
+                if ctx.translated.type_decls.get(dep).is_some() {
+                    graph.add_edge(decl.def_id, dep, ());
+                }
+            }
+        }
+        let groups: Vec<Vec<TypeDeclId>> = tarjan_scc(&graph)
+            .into_iter()
+            .filter(|scc| scc.len() > 1 || graph.contains_edge(scc[0], scc[0]))
+            .collect();
+
+        let indirections: Vec<NamePattern> = INDIRECTIONS
+            .iter()
+            .map(|s| NamePattern::parse(s).unwrap())
+            .collect();
+        for group in groups {
+            let classifier = Classifier {
+                krate: &ctx.translated,
+                indirections: indirections.clone(),
+                group: group.iter().copied().collect(),
+            };
+            let mut annotations: Vec<(TypeDeclId, Option<VariantId>, FieldId, FieldRecursion)> =
+                Vec::new();
+            for &id in &group {
+                let decl = &ctx.translated.type_decls[id];
+                let variants: Vec<(Option<VariantId>, &Vector<FieldId, Field>)> = match &decl.kind {
+                    TypeDeclKind::Struct(fields) | TypeDeclKind::Union(fields) => {
+                        vec![(None, fields)]
+                    }
+                    TypeDeclKind::Enum(variants) => variants
+                        .iter_indexed_values()
+                        .map(|(variant_id, variant)| (Some(variant_id), &variant.fields))
+                        .collect(),
+                    TypeDeclKind::Opaque | TypeDeclKind::Alias(..) | TypeDeclKind::Error(..) => {
+                        continue
+                    }
+                };
+                for (variant_id, fields) in variants {
+                    for (field_id, field) in fields.iter_indexed_values() {
+                        if let Some(rec) = classifier.classify(&field.ty, false) {
+                            annotations.push((id, variant_id, field_id, rec));
+                        }
+                    }
+                }
+            }
+
+            for (id, variant_id, field_id, rec) in annotations {
+                let fields = match (&mut ctx.translated.type_decls[id].kind, variant_id) {
+                    (TypeDeclKind::Enum(variants), Some(variant_id)) => {
+                        &mut variants[variant_id].fields
+                    }
+                    (TypeDeclKind::Struct(fields) | TypeDeclKind::Union(fields), None) => fields,
+                    _ => unreachable!(),
+                };
+                fields[field_id].recursion = Some(rec);
+            }
+        }
+    }
+}
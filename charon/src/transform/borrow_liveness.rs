@@ -0,0 +1,266 @@
+//! # Micro-pass: compute, for every borrow (i.e. every [Rvalue::Ref]), the statements where it is
+//! used for the last time. This is opt-in (see the `--borrow-liveness` option). The result is
+//! stored on the statements and terminators (see [BorrowLiveness]), so that it survives the
+//! control-flow reconstruction.
+//!
+//! This is an approximation of the NLL liveness of the borrows, in two steps:
+//! - a forward analysis computes, at every location, the set of borrows that every local may
+//!   contain (a local contains the borrows of the operands it was computed from);
+//! - a backward analysis computes the set of borrows which may be used later at every location.
+//!   A use of a borrow is a last use if the borrow is not live after it.
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use derive_visitor::{visitor_enter_fn, Drive};
+
+use crate::ids::Vector;
+use crate::transform::TransformCtx;
+use crate::ullbc_ast::*;
+
+use super::ctx::UllbcPass;
+
+/// A program point in a body: the statement with the given index in the given block. The
+/// terminator of a block has index `statements.len()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Location {
+    block_id: BlockId,
+    statement_index: usize,
+}
+
+/// The borrows which may be contained in every local.
+type LocalBorrows = BTreeMap<VarId, BTreeSet<BorrowId>>;
+
+/// The locals read by a statement or a terminator, except the destination of an assignment
+/// (unless it goes through a projection).
+fn read_locals(st: &impl Drive, dest: Option<&Place>) -> HashSet<VarId> {
+    let mut locals = HashSet::new();
+    st.drive(&mut visitor_enter_fn(|p: &Place| {
+        if !dest.is_some_and(|dest| std::ptr::eq(dest, p) && dest.projection.is_empty()) {
+            locals.insert(p.var_id);
+        }
+    }));
+    locals
+}
+
+/// The destination of a statement, if any.
+fn statement_dest(st: &Statement) -> Option<&Place> {
+    match &st.content {
+        RawStatement::Assign(dest, _) => Some(dest),
+        RawStatement::Call(call) => Some(&call.dest),
+        _ => None,
+    }
+}
+
+/// The borrows used by a statement or a terminator, given the borrows contained in the locals.
+fn used_borrows(read: &HashSet<VarId>, state: &LocalBorrows) -> BTreeSet<BorrowId> {
+    read.iter()
+        .filter_map(|var_id| state.get(var_id))
+        .flatten()
+        .copied()
+        .collect()
+}
+
+struct Analysis<'a> {
+    body: &'a ExprBody,
+    /// The borrow created at every location, numbered in the order of the locations.
+    borrows: BTreeMap<Location, BorrowId>,
+}
+
+impl Analysis<'_> {
+    /// Update the borrows contained in the locals after a statement.
+    fn transfer(&self, loc: Location, st: &Statement, state: &mut LocalBorrows) {
+        let dest = statement_dest(st);
+        let read = read_locals(st, dest);
+        match dest {
+            Some(dest) => {
+                let mut new = used_borrows(&read, state);
+                new.extend(self.borrows.get(&loc));
+                let old = state.entry(dest.var_id).or_default();
+                if dest.projection.is_empty() {
+                    *old = new;
+                } else {
+                    old.extend(new);
+                }
+            }
+            None => {
+                if let RawStatement::StorageDead(var_id) = &st.content {
+                    state.remove(var_id);
+                }
+            }
+        }
+    }
+
+    /// Compute the borrows contained in the locals at the start of every block.
+    fn forward(&self, cfg: &CfgInfo) -> Vector<BlockId, LocalBorrows> {
+        let mut entry_states = self.body.body.map_ref(|_| LocalBorrows::new());
+        let mut worklist: BTreeSet<BlockId> = self.body.body.iter_indices().collect();
+        while let Some(block_id) = worklist.pop_first() {
+            let block = &self.body.body[block_id];
+            let mut state = entry_states[block_id].clone();
+            for (statement_index, st) in block.statements.iter().enumerate() {
+                let loc = Location {
+                    block_id,
+                    statement_index,
+                };
+                self.transfer(loc, st, &mut state);
+            }
+            for &succ in &cfg.successors[block_id] {
+                let succ_state = &mut entry_states[succ];
+                let mut changed = false;
+                for (var_id, borrows) in &state {
+                    let succ_borrows = succ_state.entry(*var_id).or_default();
+                    for borrow in borrows {
+                        changed |= succ_borrows.insert(*borrow);
+                    }
+                }
+                if changed {
+                    worklist.insert(succ);
+                }
+            }
+        }
+        entry_states
+    }
+
+    /// Compute the borrows used at every location.
+    fn uses(
+        &self,
+        entry_states: &Vector<BlockId, LocalBorrows>,
+    ) -> BTreeMap<Location, BTreeSet<BorrowId>> {
+        let mut uses = BTreeMap::new();
+        for (block_id, block) in self.body.body.iter_indexed_values() {
+            let mut state = entry_states[block_id].clone();
+            for (statement_index, st) in block.statements.iter().enumerate() {
+                let loc = Location {
+                    block_id,
+                    statement_index,
+                };
+                let read = read_locals(st, statement_dest(st));
+                uses.insert(loc, used_borrows(&read, &state));
+                self.transfer(loc, st, &mut state);
+            }
+            let loc = Location {
+                block_id,
+                statement_index: block.statements.len(),
+            };
+            let read = read_locals(&block.terminator, None);
+            uses.insert(loc, used_borrows(&read, &state));
+        }
+        uses
+    }
+
+    /// Compute the last uses of every borrow.
+    fn last_uses(
+        &self,
+        cfg: &CfgInfo,
+        uses: &BTreeMap<Location, BTreeSet<BorrowId>>,
+    ) -> BTreeMap<BorrowId, BTreeSet<Location>> {
+        // Walk a block backwards, starting from the borrows live at its end. Returns the borrows
+        // live at its start and calls `on_last_use` on the last uses.
+        let walk_block =
+            |block_id: BlockId,
+             mut live: BTreeSet<BorrowId>,
+             on_last_use: &mut dyn FnMut(BorrowId, Location)| {
+                let len = self.body.body[block_id].statements.len();
+                for statement_index in (0..=len).rev() {
+                    let loc = Location {
+                        block_id,
+                        statement_index,
+                    };
+                    // The borrow created here is not live before.
+                    if let Some(borrow) = self.borrows.get(&loc) {
+                        live.remove(borrow);
+                    }
+                    for borrow in &uses[&loc] {
+                        if live.insert(*borrow) {
+                            on_last_use(*borrow, loc);
+                        }
+                    }
+                }
+                live
+            };
+
+        let mut exit_states = self.body.body.map_ref(|_| BTreeSet::new());
+        let mut worklist: BTreeSet<BlockId> = self.body.body.iter_indices().collect();
+        while let Some(block_id) = worklist.pop_last() {
+            let live = walk_block(block_id, exit_states[block_id].clone(), &mut |_, _| {});
+            for &pred in &cfg.predecessors[block_id] {
+                let pred_live = &mut exit_states[pred];
+                let len = pred_live.len();
+                pred_live.extend(live.iter().copied());
+                if pred_live.len() != len {
+                    worklist.insert(pred);
+                }
+            }
+        }
+
+        let mut last_uses: BTreeMap<BorrowId, BTreeSet<Location>> = BTreeMap::new();
+        for block_id in self.body.body.iter_indices() {
+            walk_block(
+                block_id,
+                exit_states[block_id].clone(),
+                &mut |borrow, loc| {
+                    last_uses.entry(borrow).or_default().insert(loc);
+                },
+            );
+        }
+        last_uses
+    }
+}
+
+pub struct Transform;
+impl UllbcPass for Transform {
+    fn transform_body(&self, ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
+        if !ctx.options.borrow_liveness {
+            return;
+        }
+
+        // Collect the borrows.
+        let mut borrows = BTreeMap::new();
+        for (block_id, block) in b.body.iter_indexed_values() {
+            for (statement_index, st) in block.statements.iter().enumerate() {
+                if let RawStatement::Assign(_, Rvalue::Ref(..)) = &st.content {
+                    let loc = Location {
+                        block_id,
+                        statement_index,
+                    };
+                    let id = BorrowId::new(borrows.len());
+                    borrows.insert(loc, id);
+                }
+            }
+        }
+
+        let analysis = Analysis { body: b, borrows };
+        let cfg = b.compute_cfg_info();
+        let entry_states = analysis.forward(&cfg);
+        let uses = analysis.uses(&entry_states);
+        let last_uses = analysis.last_uses(&cfg, &uses);
+        let borrows = analysis.borrows;
+
+        // Record the facts on the statements and the terminators.
+        let mut last_uses_at: BTreeMap<Location, Vec<BorrowId>> = BTreeMap::new();
+        for (borrow, locs) in last_uses {
+            for loc in locs {
+                last_uses_at.entry(loc).or_default().push(borrow);
+            }
+        }
+        let block_ids: Vec<_> = b.body.iter_indices().collect();
+        for block_id in block_ids {
+            let block = &mut b.body[block_id];
+            let len = block.statements.len();
+            for statement_index in 0..=len {
+                let loc = Location {
+                    block_id,
+                    statement_index,
+                };
+                let liveness = Some(BorrowLiveness {
+                    created: borrows.get(&loc).copied(),
+                    last_uses: last_uses_at.remove(&loc).unwrap_or_default(),
+                });
+                if statement_index == len {
+                    block.terminator.borrow_liveness = liveness;
+                } else {
+                    block.statements[statement_index].borrow_liveness = liveness;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! # Micro-pass: compute, for every statement, the locals it moves, copies and drops. This is
+//! opt-in (see the `--move-info` option). The result is stored on the statements and terminators
+//! (see [MoveInfo]), so that it survives the control-flow reconstruction.
+use derive_visitor::{visitor_enter_fn, Drive};
+
+use crate::transform::TransformCtx;
+use crate::ullbc_ast::*;
+
+use super::ctx::UllbcPass;
+
+/// The moves and copies of the operands of a statement or a terminator, and the local it drops.
+fn compute_move_info(st: &impl Drive, dropped: Option<&Place>) -> MoveInfo {
+    let mut info = MoveInfo::default();
+    st.drive(&mut visitor_enter_fn(|op: &Operand| match op {
+        Operand::Move(p) => info.moved.push(p.var_id),
+        Operand::Copy(p) => info.copied.push(p.var_id),
+        Operand::Const(_) => {}
+    }));
+    info.dropped.extend(dropped.map(|p| p.var_id));
+    info
+}
+
+pub struct Transform;
+impl UllbcPass for Transform {
+    fn transform_body(&self, ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
+        if !ctx.options.move_info {
+            return;
+        }
+
+        for block in b.body.iter_mut() {
+            for st in &mut block.statements {
+                let dropped = match &st.content {
+                    RawStatement::Drop(p) => Some(p),
+                    _ => None,
+                };
+                let info = compute_move_info(&*st, dropped);
+                st.move_info = Some(info);
+            }
+            let info = compute_move_info(&block.terminator, None);
+            block.terminator.move_info = Some(info);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! # Micro-pass: record the byte range of every statement in the original source file. This is
+//! opt-in (see the `--statement-ranges` option).
+use derive_visitor::{visitor_enter_fn_mut, DriveMut};
+
+use crate::llbc_ast::*;
+use crate::transform::TransformCtx;
+
+use super::ctx::LlbcPass;
+
+pub struct Transform;
+impl LlbcPass for Transform {
+    fn transform_body(&self, ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
+        if !ctx.options.statement_ranges {
+            return;
+        }
+        let files = &ctx.translated.file_id_to_content;
+        b.body
+            .drive_mut(&mut visitor_enter_fn_mut(|st: &mut Statement| {
+                let span = st.span.span;
+                st.byte_range = files
+                    .get(&span.file_id)
+                    .and_then(|contents| span.to_byte_range(contents));
+            }));
+    }
+}
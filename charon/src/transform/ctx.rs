@@ -4,9 +4,13 @@ use crate::formatter::{FmtCtx, IntoFormatter};
 use crate::ids::Vector;
 use crate::llbc_ast;
 use crate::name_matcher::NamePattern;
+use crate::options::{ConstantSimplification, SourceEmbedding};
 use crate::pretty::FmtWithCtx;
+use crate::profile::Profiler;
 use crate::ullbc_ast;
+use std::collections::HashSet;
 use std::fmt;
+use std::path::PathBuf;
 
 /// The options that control transformation.
 pub struct TransformOptions {
@@ -17,11 +21,49 @@ pub struct TransformOptions {
     /// Whether to hide the `Sized`, `Sync`, `Send` and `Unpin` marker traits anywhere they show
     /// up.
     pub hide_marker_traits: bool,
+    /// Replace the uses of blanket impls at concrete types by uses of specialized impls.
+    pub instantiate_blanket_impls: bool,
     /// Do not merge the chains of gotos.
     pub no_merge_goto_chains: bool,
+    /// Merge the nested matches into multi-level matches.
+    pub reconstruct_matches: bool,
+    /// Collapse the desugaring of the `?` operator.
+    pub reconstruct_try: bool,
+    /// Recognize more shapes of asserts, and warn about the assert-like `if`s we can't recognize.
+    pub extended_asserts: bool,
+    /// Keep the discriminant reads whose value is used otherwise than by a switch.
+    pub preserve_discriminant_reads: bool,
+    /// How far the constant expressions are turned into statements.
+    pub simplify_constants: ConstantSimplification,
+    /// Fall back to a state machine when the control-flow reconstruction of a body duplicates
+    /// more than this number of statements.
+    pub max_duplication: Option<usize>,
+    /// Fall back to labeled blocks and `goto`s instead of a state machine when we fail to
+    /// reconstruct the control-flow of a body.
+    pub semi_structured: bool,
+    /// Compute the liveness of the borrows.
+    pub borrow_liveness: bool,
+    /// Compute where the locals are moved, copied and dropped.
+    pub move_info: bool,
+    /// Record the byte ranges of the statements.
+    pub statement_ranges: bool,
+    /// Parse the annotations in the comments of the bodies.
+    pub comment_annotations: bool,
+    /// Annotate the fields through which the types are recursive.
+    pub annotate_recursive_fields: bool,
     /// List of patterns to assign a given opacity to. Same as the corresponding `TranslateOptions`
     /// field.
     pub item_opacities: Vec<(NamePattern, ItemOpacity)>,
+    /// Which source text to embed in the output.
+    pub embed_sources: SourceEmbedding,
+    /// The files of models for the opaque functions.
+    pub model_files: Vec<PathBuf>,
+    /// The derives whose generated methods lose their bodies.
+    pub opaque_derives: Vec<String>,
+    /// The derives whose generated impls are replaced by builtin impls.
+    pub builtin_derives: Vec<String>,
+    /// Whether to replace the calls to the `Fn*` traits on known closures with direct calls.
+    pub devirtualize_closure_calls: bool,
 }
 
 /// Simpler context used for rustc-independent code transformation. This only depends on rustc for
@@ -33,6 +75,11 @@ pub struct TransformCtx<'ctx> {
     pub translated: TranslatedCrate,
     /// Context for tracking and reporting errors.
     pub errors: ErrorCtx<'ctx>,
+    /// The items generated by the passes which may be generated identically when extracting
+    /// other crates. We compute their [ItemMeta::dedup_hash] at the end of the translation.
+    pub generated_items: HashSet<AnyTransId>,
+    /// Records the time spent in the translation and in each pass (see `--profile-extraction`).
+    pub profiler: Profiler,
 }
 
 /// A pass that modifies ullbc bodies.
@@ -251,6 +298,37 @@ impl<'ctx> TransformCtx<'ctx> {
         ret
     }
 
+    /// Compute the [ItemMeta::dedup_hash] of the [Self::generated_items] (see
+    /// [AnyTransItem::stable_hash]). This must be done once the items are in their final form.
+    pub fn compute_dedup_hashes(&mut self) {
+        let hashes: Vec<(AnyTransId, String)> = self
+            .generated_items
+            .iter()
+            .filter_map(|id| {
+                let item = self.translated.get_item(*id)?;
+                let hash = item.stable_hash(&self.translated);
+                Some((*id, format!("{hash:016x}")))
+            })
+            .collect();
+        let krate = &mut self.translated;
+        for (id, hash) in hashes {
+            let item_meta = match id {
+                AnyTransId::Type(id) => krate.type_decls.get_mut(id).map(|d| &mut d.item_meta),
+                AnyTransId::Fun(id) => krate.fun_decls.get_mut(id).map(|d| &mut d.item_meta),
+                AnyTransId::Global(id) => krate.global_decls.get_mut(id).map(|d| &mut d.item_meta),
+                AnyTransId::TraitDecl(id) => {
+                    krate.trait_decls.get_mut(id).map(|d| &mut d.item_meta)
+                }
+                AnyTransId::TraitImpl(id) => {
+                    krate.trait_impls.get_mut(id).map(|d| &mut d.item_meta)
+                }
+            };
+            if let Some(item_meta) = item_meta {
+                item_meta.dedup_hash = Some(hash);
+            }
+        }
+    }
+
     /// Mutably iterate over the bodies.
     // FIXME: this does not set `with_def_id` to track error sources. That would require having a
     // way to go from the body back to its parent declaration.
@@ -0,0 +1,243 @@
+//! # Micro-pass: replace the calls to `Fn::call`, `FnMut::call_mut` and `FnOnce::call_once` on a
+//! known closure with direct calls to the function of the closure (opt-in, see
+//! `--devirtualize-closure-calls`). After [crate::transform::update_closure_signatures], the
+//! function of a closure takes the state of the closure (by reference, unless it is a `FnOnce`
+//! closure) and the tuple of the arguments, like the trait methods, so the arguments of the call
+//! don't change:
+//! ```text
+//! @4 := {closure} {move (@5), move (@6)}
+//! f@3 := &@4
+//! @7 := &*(f@3)
+//! @0 := core::ops::function::Fn<fn(u32) -> u32, (u32)>::call(move (@7), move (@8))
+//! ```
+//! becomes:
+//! ```text
+//! @0 := closure<'_>(move (@7), move (@8))
+//! ```
+//! We only recognize the closures built in the same body, in locals which are assigned once, and
+//! the called method must be the one of the kind of the closure (e.g. we don't devirtualize
+//! `FnOnce::call_once` on a `Fn` closure, as the closure expects a reference to its state).
+use std::collections::HashMap;
+
+use crate::name_matcher::NamePattern;
+use crate::transform::TransformCtx;
+use crate::ullbc_ast::*;
+
+use super::ctx::TransformPass;
+
+/// The local which holds the closure value, and whether a local holds this value or a reference
+/// to it.
+#[derive(Clone, Copy)]
+struct ClosureRef {
+    closure_var: VarId,
+    is_ref: bool,
+}
+
+/// Find the locals which hold (a reference to) a closure built in the body, and the closures
+/// built in the body.
+fn find_closure_locals(
+    body: &ExprBody,
+) -> (
+    HashMap<VarId, ClosureRef>,
+    HashMap<VarId, (FunDeclId, GenericArgs)>,
+) {
+    // We only track the locals which are assigned once.
+    let mut num_assignments: HashMap<VarId, usize> = HashMap::new();
+    for block in body.body.iter() {
+        for st in &block.statements {
+            let dest = match &st.content {
+                RawStatement::Assign(place, _) => place,
+                RawStatement::Call(call) => &call.dest,
+                _ => continue,
+            };
+            *num_assignments.entry(dest.var_id).or_default() += 1;
+        }
+    }
+    let num_assignments = &num_assignments;
+    let assignments = move || {
+        body.body
+            .iter()
+            .flat_map(|block| &block.statements)
+            .filter_map(move |st| match &st.content {
+                RawStatement::Assign(place, rvalue)
+                    if place.projection.is_empty() && num_assignments[&place.var_id] == 1 =>
+                {
+                    Some((place.var_id, rvalue))
+                }
+                _ => None,
+            })
+    };
+
+    let mut closures = HashMap::new();
+    let mut locals = HashMap::new();
+    for (var, rvalue) in assignments() {
+        if let Rvalue::Aggregate(AggregateKind::Closure(fun_id, generics), _) = rvalue {
+            closures.insert(var, (*fun_id, generics.clone()));
+            let closure_ref = ClosureRef {
+                closure_var: var,
+                is_ref: false,
+            };
+            locals.insert(var, closure_ref);
+        }
+    }
+    // Follow the moves and borrows until we reach a fixpoint.
+    loop {
+        let mut changed = false;
+        for (var, rvalue) in assignments() {
+            if locals.contains_key(&var) {
+                continue;
+            }
+            let source = match rvalue {
+                Rvalue::Use(Operand::Move(place) | Operand::Copy(place))
+                    if place.projection.is_empty() =>
+                {
+                    locals.get(&place.var_id).copied()
+                }
+                // `&closure`
+                Rvalue::Ref(place, _) if place.projection.is_empty() => locals
+                    .get(&place.var_id)
+                    .filter(|closure_ref| !closure_ref.is_ref)
+                    .map(|closure_ref| ClosureRef {
+                        is_ref: true,
+                        ..*closure_ref
+                    }),
+                // `&*ref_to_closure`
+                Rvalue::Ref(place, _)
+                    if matches!(place.projection.as_slice(), [ProjectionElem::Deref]) =>
+                {
+                    locals
+                        .get(&place.var_id)
+                        .filter(|closure_ref| closure_ref.is_ref)
+                        .copied()
+                }
+                _ => None,
+            };
+            if let Some(closure_ref) = source {
+                locals.insert(var, closure_ref);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    (locals, closures)
+}
+
+/// The `Fn*` traits: the name of the trait, the name of its method, whether the method takes the
+/// state by reference, and the kind of the closures which implement it with their function.
+const FN_TRAITS: [(&str, &str, bool, ClosureKind); 3] = [
+    ("core::ops::function::Fn", "call", true, ClosureKind::Fn),
+    (
+        "core::ops::function::FnMut",
+        "call_mut",
+        true,
+        ClosureKind::FnMut,
+    ),
+    (
+        "core::ops::function::FnOnce",
+        "call_once",
+        false,
+        ClosureKind::FnOnce,
+    ),
+];
+
+/// Replace the calls to the `Fn*` traits on the closures built in this body.
+fn devirtualize_calls(
+    krate: &TranslatedCrate,
+    fn_traits: &[(NamePattern, &str, bool, ClosureKind)],
+    closure_sigs: &HashMap<FunDeclId, (ClosureKind, usize)>,
+    body: &mut ExprBody,
+) {
+    let (locals, closures) = find_closure_locals(body);
+    if closures.is_empty() {
+        return;
+    }
+    for block in body.body.iter_mut() {
+        for st in &mut block.statements {
+            let RawStatement::Call(call) = &mut st.content else {
+                continue;
+            };
+            let FnOperand::Regular(FnPtr {
+                func: FunIdOrTraitMethodRef::Trait(tref, method, _),
+                ..
+            }) = &call.func
+            else {
+                continue;
+            };
+            let Some(Operand::Move(state) | Operand::Copy(state)) = call.args.first() else {
+                continue;
+            };
+            let Some(closure_ref) = locals.get(&state.var_id) else {
+                continue;
+            };
+            if !state.projection.is_empty() {
+                continue;
+            }
+            let (fun_id, generics) = &closures[&closure_ref.closure_var];
+            let Some((closure_kind, num_regions)) = closure_sigs.get(fun_id) else {
+                continue;
+            };
+            let Some(trait_name) = krate.item_name(tref.trait_decl_ref.skip_binder.trait_id) else {
+                continue;
+            };
+            let matches = fn_traits.iter().any(|(pattern, m, is_ref, kind)| {
+                pattern.matches(krate, trait_name)
+                    && method.0 == *m
+                    && closure_ref.is_ref == *is_ref
+                    && closure_kind == kind
+            });
+            if !matches {
+                continue;
+            }
+            // We erase the regions, like for the other function pointers.
+            call.func = FnOperand::Regular(FnPtr {
+                func: FunIdOrTraitMethodRef::Fun(FunId::Regular(*fun_id)),
+                generics: GenericArgs {
+                    regions: (0..*num_regions).map(|_| Region::Erased).collect(),
+                    ..generics.clone()
+                },
+            });
+        }
+    }
+}
+
+pub struct Transform;
+impl TransformPass for Transform {
+    fn transform_ctx(&self, ctx: &mut TransformCtx<'_>) {
+        if !ctx.options.devirtualize_closure_calls {
+            return;
+        }
+        let fn_traits: Vec<_> = FN_TRAITS
+            .iter()
+            .map(|(pattern, method, is_ref, kind)| {
+                (
+                    NamePattern::parse(pattern).unwrap(),
+                    *method,
+                    *is_ref,
+                    *kind,
+                )
+            })
+            .collect();
+        // The kind and number of region parameters of the closures.
+        let closure_sigs: HashMap<FunDeclId, (ClosureKind, usize)> = ctx
+            .translated
+            .fun_decls
+            .iter()
+            .filter_map(|decl| {
+                let info = decl.signature.closure_info.as_ref()?;
+                Some((
+                    decl.def_id,
+                    (info.kind, decl.signature.generics.regions.len()),
+                ))
+            })
+            .collect();
+        ctx.with_mut_bodies(|ctx, bodies| {
+            for body in bodies.iter_mut() {
+                if let Some(body) = body.as_unstructured_mut() {
+                    devirtualize_calls(&ctx.translated, &fn_traits, &closure_sigs, body);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,63 @@
+//! # Micro-pass: drop the source text we don't want to embed in the output (see the
+//! `--embed-sources` option). With `referenced`, we only keep the lines which some span of the
+//! crate refers to; the other lines are emptied (but not removed) so that the line numbers of the
+//! spans stay valid. This must run after all the passes which read the sources.
+use std::collections::HashMap;
+
+use derive_visitor::{visitor_enter_fn, Drive};
+
+use crate::ast::*;
+use crate::options::SourceEmbedding;
+use crate::transform::TransformCtx;
+
+use super::ctx::TransformPass;
+
+/// Keep only the given (1-based) lines of `contents`, emptying the others.
+fn keep_lines(contents: &str, lines: &[bool]) -> String {
+    contents
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(i, line)| {
+            if lines.get(i + 1).copied().unwrap_or(false) {
+                line
+            } else if line.ends_with('\n') {
+                "\n"
+            } else {
+                ""
+            }
+        })
+        .collect()
+}
+
+pub struct Transform;
+impl TransformPass for Transform {
+    fn transform_ctx(&self, ctx: &mut TransformCtx<'_>) {
+        match ctx.options.embed_sources {
+            SourceEmbedding::All => {}
+            SourceEmbedding::None => ctx.translated.file_id_to_content.clear(),
+            SourceEmbedding::Referenced => {
+                // For each file, which lines are referenced.
+                let mut referenced: HashMap<FileId, Vec<bool>> = HashMap::new();
+                ctx.translated
+                    .drive(&mut visitor_enter_fn(|span: &RawSpan| {
+                        if span.beg.line > span.end.line {
+                            return;
+                        }
+                        let lines = referenced.entry(span.file_id).or_default();
+                        if lines.len() <= span.end.line {
+                            lines.resize(span.end.line + 1, false);
+                        }
+                        for line in &mut lines[span.beg.line..=span.end.line] {
+                            *line = true;
+                        }
+                    }));
+                ctx.translated
+                    .file_id_to_content
+                    .retain(|id, _| referenced.contains_key(id));
+                for (id, contents) in ctx.translated.file_id_to_content.iter_mut() {
+                    *contents = keep_lines(contents, &referenced[id]);
+                }
+            }
+        }
+    }
+}
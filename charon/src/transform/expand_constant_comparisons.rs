@@ -0,0 +1,137 @@
+//! # Micro-pass: turn the comparisons with constant structs into field-wise comparisons.
+//!
+//! Matching on a constant of ADT type (e.g. `match p { ORIGIN => .. }`) or comparing a value with
+//! such a constant may lead to a call to the `PartialEq::eq` method of the type with a constant
+//! operand, e.g. in the optimized MIR where the constants are propagated. When this method is the
+//! one generated by `#[derive(PartialEq)]`, the comparison is structural, hence we replace the
+//! call with the comparisons of the fields:
+//! ```text
+//! b := <Point as PartialEq>::eq(move p, const &ConstAdt None [0, 0])
+//! ~~>
+//! b0 := (*p).x == const 0;
+//! b1 := (*p).y == const 0;
+//! b := move b0 & move b1
+//! ```
+//! We only do so for the structs whose fields are all literals (integers, booleans and
+//! characters): their equality is the builtin one. The calls to the impls removed by
+//! `--builtin-derive PartialEq` are left as is.
+use crate::transform::TransformCtx;
+use crate::ullbc_ast::*;
+
+use super::ctx::UllbcPass;
+use super::simplify_derives::{derived_trait_name, is_automatically_derived};
+
+/// If the call is a call to a derived `PartialEq::eq` with a constant struct argument, return the
+/// other argument (a reference to the compared value), the struct and the fields of the constant.
+fn as_constant_comparison<'a>(
+    krate: &TranslatedCrate,
+    call: &'a Call,
+) -> Option<(&'a Place, TypeDeclId, &'a [ConstantExpr])> {
+    let FnOperand::Regular(FnPtr {
+        func: FunIdOrTraitMethodRef::Trait(trait_ref, method_name, _),
+        ..
+    }) = &call.func
+    else {
+        return None;
+    };
+    let TraitRefKind::TraitImpl(impl_id, _) = &trait_ref.kind else {
+        return None;
+    };
+    let timpl = krate.trait_impls.get(*impl_id)?;
+    if method_name.0 != "eq"
+        || !is_automatically_derived(timpl)
+        || derived_trait_name(krate, timpl) != Some("PartialEq")
+    {
+        return None;
+    }
+    let (place, cst) = match call.args.as_slice() {
+        [Operand::Move(place) | Operand::Copy(place), Operand::Const(cst)]
+        | [Operand::Const(cst), Operand::Move(place) | Operand::Copy(place)] => (place, cst),
+        _ => return None,
+    };
+    let RawConstantExpr::Ref(pointee) = &cst.value else {
+        return None;
+    };
+    let RawConstantExpr::Adt(None, fields) = &pointee.value else {
+        return None;
+    };
+    let TyKind::Adt(TypeId::Adt(type_id), _) = pointee.ty.kind() else {
+        return None;
+    };
+    let is_struct = krate
+        .type_decls
+        .get(*type_id)
+        .is_some_and(|decl| decl.kind.is_struct());
+    let fields_are_literals = fields.iter().all(|field| {
+        matches!(
+            field.value,
+            RawConstantExpr::Literal(Literal::Scalar(_) | Literal::Bool(_) | Literal::Char(_))
+        )
+    });
+    (is_struct && fields_are_literals).then_some((place, *type_id, fields.as_slice()))
+}
+
+pub struct Transform;
+impl UllbcPass for Transform {
+    fn transform_body(&self, ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
+        let krate = &ctx.translated;
+        b.transform_sequences(&mut |locals, seq| {
+            let RawStatement::Call(call) = &seq[0].content else {
+                return Vec::new();
+            };
+            let Some((place, type_id, fields)) = as_constant_comparison(krate, call) else {
+                return Vec::new();
+            };
+            let span = seq[0].span;
+            let dest = call.dest.clone();
+            let bool_ty = TyKind::Literal(LiteralTy::Bool).into_ty();
+            let bool_tys = BinOpTys {
+                lhs_ty: bool_ty.clone(),
+                rhs_ty: bool_ty.clone(),
+            };
+
+            // Compare the fields one by one.
+            let mut comparisons = fields.iter().enumerate().map(|(i, field)| {
+                let mut field_place = place.clone();
+                field_place.projection.push(ProjectionElem::Deref);
+                field_place.projection.push(ProjectionElem::Field(
+                    FieldProjKind::Adt(type_id, None),
+                    FieldId::new(i),
+                ));
+                let tys = BinOpTys {
+                    lhs_ty: field.ty.clone(),
+                    rhs_ty: field.ty.clone(),
+                };
+                Rvalue::BinaryOp(
+                    BinOp::Eq,
+                    Operand::Copy(field_place),
+                    Operand::Const(field.clone()),
+                    tys,
+                )
+            });
+            // Combine the comparisons, storing the intermediate results in fresh locals.
+            let mut new_var = make_locals_generator(locals);
+            let mut nst = Vec::new();
+            let rvalue = match comparisons.next() {
+                None => Rvalue::Use(Operand::Const(ConstantExpr {
+                    value: RawConstantExpr::Literal(Literal::Bool(true)),
+                    ty: bool_ty.clone(),
+                    has_type_suffix: None,
+                })),
+                Some(first) => comparisons.fold(first, |acc, comparison| {
+                    let [acc, comparison] = [acc, comparison].map(|rvalue| {
+                        let var_id = new_var(bool_ty.clone());
+                        nst.push(Statement::new(
+                            span,
+                            RawStatement::Assign(Place::new(var_id), rvalue),
+                        ));
+                        Operand::Move(Place::new(var_id))
+                    });
+                    Rvalue::BinaryOp(BinOp::BitAnd, acc, comparison, bool_tys.clone())
+                }),
+            };
+            seq[0].content = RawStatement::Assign(dest, rvalue);
+            vec![(0, nst)]
+        });
+    }
+}
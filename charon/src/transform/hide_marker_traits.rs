@@ -63,6 +63,11 @@ impl Visitor {
                 trait_refs.remove(<_ as Idx>::from_usize(i));
             }
         }
+        timpl.obligations.retain(|obligation| {
+            !self
+                .exclude
+                .contains(&obligation.trait_ref.trait_decl_ref.skip_binder.trait_id)
+        });
     }
 }
 
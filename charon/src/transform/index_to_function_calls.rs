@@ -137,10 +137,18 @@ impl<'a> Visitor<'a> {
                 );
                 self.statements.push(Statement::new(self.span, kind));
                 // `index_var = len(p) - last_arg`
-                let index_var = self.fresh_var(None, usize_ty);
+                let index_var = self.fresh_var(None, usize_ty.clone());
                 let kind = RawStatement::Assign(
                     Place::new(index_var),
-                    Rvalue::BinaryOp(BinOp::Sub, Operand::Copy(Place::new(len_var)), last_arg),
+                    Rvalue::BinaryOp(
+                        BinOp::Sub,
+                        Operand::Copy(Place::new(len_var)),
+                        last_arg,
+                        BinOpTys {
+                            lhs_ty: usize_ty.clone(),
+                            rhs_ty: usize_ty,
+                        },
+                    ),
                 );
                 self.statements.push(Statement::new(self.span, kind));
                 args.push(Operand::Copy(Place::new(index_var)));
@@ -156,6 +164,7 @@ impl<'a> Visitor<'a> {
                     func: indexing_function,
                     args,
                     dest: Place::new(output_var),
+                    fn_ptr_sig: None,
                 };
                 let kind = RawStatement::Call(index_call);
                 self.statements.push(Statement::new(self.span, kind));
@@ -312,21 +321,21 @@ impl LlbcPass for Transform {
             use llbc_ast::Switch::*;
             use RawStatement::*;
             match &mut st.content {
-                Loop(..) => {}
+                Loop(..) | Labeled(..) => {}
                 Switch(If(op, ..) | SwitchInt(op, ..)) => op.drive_mut(&mut visitor),
-                Switch(Match(place, ..)) => {
+                Switch(Match(place, ..) | NestedMatch(place, ..)) => {
                     visitor.place_mutability_stack.push(false); // Unsure why we do this
                     place.drive_mut(&mut visitor)
                 }
-                Abort(..) | Return | Break(..) | Continue(..) | Nop | Error(..) | Assert(..)
-                | Call(..) => {
+                Abort(..) | Return | Break(..) | Continue(..) | Goto(..) | Nop | Error(..)
+                | Assert(..) | Call(..) | TryPropagate(..) => {
                     st.drive_mut(&mut visitor);
                 }
                 FakeRead(place) => {
                     visitor.place_mutability_stack.push(false);
                     place.drive_mut(&mut visitor);
                 }
-                Assign(..) | SetDiscriminant(..) | Drop(..) => {
+                Assign(..) | SetDiscriminant(..) | Drop(..) | Retag(..) => {
                     visitor.place_mutability_stack.push(true);
                     st.drive_mut(&mut visitor);
                 }
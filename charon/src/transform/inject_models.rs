@@ -0,0 +1,123 @@
+//! # Micro-pass: give a body to the opaque functions for which the user provided a model (see the
+//! `--model-file` option). A model is an LLBC body together with a name pattern; it replaces the
+//! missing body of every opaque function whose name matches the pattern, provided the types of its
+//! return value and arguments match the signature of the function.
+//!
+//! The models are read either from a text file in the pretty-printed syntax (see
+//! [crate::pretty::parser::parse_models]), or from a `.llbc` file, in which case every function
+//! with a body is a model for the functions of the same name. In both cases the bodies are parsed
+//! in the context of the current crate, which resolves the names of the types and functions they
+//! mention.
+use std::path::Path;
+
+use crate::ast::*;
+use crate::formatter::IntoFormatter;
+use crate::import::read_crate_data;
+use crate::llbc_ast::ExprBody;
+use crate::name_matcher::NamePattern;
+use crate::pretty::parser::{parse_body, parse_models};
+use crate::pretty::FmtWithCtx;
+use crate::transform::TransformCtx;
+use crate::tyck::compatible;
+
+use super::ctx::TransformPass;
+
+/// Read the models of a file.
+fn load_models(
+    krate: &TranslatedCrate,
+    path: &Path,
+) -> anyhow::Result<Vec<(NamePattern, ExprBody)>> {
+    if path.extension().is_some_and(|ext| ext == "llbc") {
+        let models = read_crate_data(path)?;
+        let models = &models.translated;
+        let fmt_ctx = models.into_fmt();
+        models
+            .fun_decls
+            .iter()
+            .filter_map(|decl| {
+                let body = models
+                    .bodies
+                    .get(*decl.body.as_ref().ok()?)?
+                    .as_structured()?;
+                Some((decl, body))
+            })
+            .map(|(decl, body)| {
+                let name = decl.item_meta.name.fmt_with_ctx(&fmt_ctx);
+                let pattern = NamePattern::parse(&name)
+                    .map_err(|err| anyhow::anyhow!("invalid name `{name}`: {err}"))?;
+                let body = parse_body(krate, &body.fmt_with_ctx(&fmt_ctx)).map_err(|err| {
+                    anyhow::anyhow!("could not parse the model of `{name}`: {err}")
+                })?;
+                Ok((pattern, body))
+            })
+            .collect()
+    } else {
+        let text = std::fs::read_to_string(path)?;
+        parse_models(krate, &text).map_err(|err| anyhow::anyhow!("{err}"))
+    }
+}
+
+/// Check that the body has the return type and argument types of the signature.
+fn check_signature(sig: &FunSig, body: &ExprBody) -> Result<(), String> {
+    if body.arg_count != sig.inputs.len() {
+        return Err(format!(
+            "expected {} arguments, found {}",
+            sig.inputs.len(),
+            body.arg_count
+        ));
+    }
+    let expected = std::iter::once(&sig.output).chain(&sig.inputs);
+    for (var, ty) in body.locals.iter().zip(expected) {
+        if !compatible(ty, &var.ty) {
+            return Err(format!(
+                "wrong type for local {}",
+                var.index.to_pretty_string()
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub struct Transform;
+impl TransformPass for Transform {
+    fn transform_ctx(&self, ctx: &mut TransformCtx<'_>) {
+        for path in ctx.options.model_files.clone() {
+            let models = match load_models(&ctx.translated, &path) {
+                Ok(models) => models,
+                Err(err) => {
+                    let msg = format!("could not load the models of `{}`: {err}", path.display());
+                    ctx.span_err(Span::dummy(), &msg);
+                    continue;
+                }
+            };
+            for (pattern, body) in models {
+                let targets: Vec<FunDeclId> = ctx
+                    .translated
+                    .fun_decls
+                    .iter()
+                    .filter(|decl| decl.body.is_err())
+                    .filter(|decl| pattern.matches(&ctx.translated, &decl.item_meta.name))
+                    .map(|decl| decl.def_id)
+                    .collect();
+                if targets.is_empty() {
+                    let msg = format!("the model `{pattern}` matches no opaque function");
+                    ctx.span_err(Span::dummy(), &msg);
+                }
+                for id in targets {
+                    let decl = &ctx.translated.fun_decls[id];
+                    if let Err(err) = check_signature(&decl.signature, &body) {
+                        let span = decl.item_meta.span;
+                        let msg = format!(
+                            "the model `{pattern}` doesn't match the signature of `{}`: {err}",
+                            decl.item_meta.name.fmt_with_ctx(&ctx.into_fmt())
+                        );
+                        ctx.span_err(span, &msg);
+                        continue;
+                    }
+                    let body_id = ctx.translated.bodies.push(Body::Structured(body.clone()));
+                    ctx.translated.fun_decls[id].body = Ok(body_id);
+                }
+            }
+        }
+    }
+}
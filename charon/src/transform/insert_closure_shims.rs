@@ -0,0 +1,321 @@
+//! # Micro-pass: a closure which doesn't capture anything can be coerced to a function pointer
+//! (`ClosureFnPointer` coercion in rustc). We translate this coercion as a `CastKind::FnPtr`
+//! applied to the closure value, which only makes sense with special knowledge of closures. This
+//! pass generates, for each such closure, a shim function which builds the (empty) state of the
+//! closure and calls it, and makes the cast operate on (a constant pointer to) this shim instead:
+//! ```text
+//! @3 := {closure} {}
+//! f@2 := cast<fn(u32) -> u32, fn(u32) -> u32>(move (@3))
+//! ```
+//! becomes:
+//! ```text
+//! @3 := {closure} {}
+//! f@2 := cast<fn(u32) -> u32, fn(u32) -> u32>(const (closure::fn_ptr_shim))
+//! ```
+//! with:
+//! ```text
+//! fn closure::fn_ptr_shim(@1: u32) -> u32 {
+//!     @2 := ()
+//!     @3 := &@2
+//!     @4 := (move (@1))
+//!     @0 := closure(move (@3), move (@4))
+//!     return
+//! }
+//! ```
+//! This must happen after [crate::transform::update_closure_signatures], as we build the shim
+//! from the signature of the closure.
+use std::collections::{BTreeSet, HashMap};
+
+use derive_visitor::{DriveMut, VisitorMut};
+
+use crate::ids::Vector;
+use crate::ullbc_ast::*;
+
+use super::{ctx::TransformPass, TransformCtx};
+
+/// Erase the regions bound by the signature, as the types in bodies use erased regions. The
+/// regions bound inside function pointer types are left untouched.
+#[derive(Default, VisitorMut)]
+#[visitor(Region(exit), Ty(enter, exit))]
+struct EraseSigRegions {
+    // The number of region groups we dived into.
+    depth: usize,
+}
+
+impl EraseSigRegions {
+    fn exit_region(&mut self, r: &mut Region) {
+        if let Region::BVar(db, _) = r
+            && db.index == self.depth
+        {
+            *r = Region::Erased;
+        }
+    }
+
+    fn enter_ty(&mut self, ty: &mut Ty) {
+        if let TyKind::Arrow(..) = ty.kind() {
+            self.depth += 1;
+        }
+    }
+
+    fn exit_ty(&mut self, ty: &mut Ty) {
+        if let TyKind::Arrow(..) = ty.kind() {
+            self.depth -= 1;
+        }
+    }
+}
+
+fn erase_sig_regions(mut ty: Ty) -> Ty {
+    ty.drive_mut(&mut Ty::visit_inside(EraseSigRegions::default()));
+    ty
+}
+
+/// Find the locals of the body which are assigned a closure without captures.
+fn closure_locals(body: &ExprBody) -> HashMap<VarId, (FunDeclId, GenericArgs)> {
+    let mut closures = HashMap::new();
+    for block in body.body.iter() {
+        for st in &block.statements {
+            if let RawStatement::Assign(
+                place,
+                Rvalue::Aggregate(AggregateKind::Closure(fun_id, generics), ops),
+            ) = &st.content
+                && place.projection.is_empty()
+                && ops.is_empty()
+            {
+                closures.insert(place.var_id, (*fun_id, generics.clone()));
+            }
+        }
+    }
+    closures
+}
+
+/// If this is a cast of a closure to a function pointer, return the operand of the cast and the
+/// closure.
+fn closure_cast<'a>(
+    st: &'a mut Statement,
+    closures: &HashMap<VarId, (FunDeclId, GenericArgs)>,
+) -> Option<(&'a mut Operand, Ty, FunDeclId, GenericArgs)> {
+    if let RawStatement::Assign(_, Rvalue::UnaryOp(UnOp::Cast(CastKind::FnPtr(src_ty, _)), op)) =
+        &mut st.content
+        && let Operand::Move(place) | Operand::Copy(place) = &*op
+        && place.projection.is_empty()
+        && let Some((fun_id, generics)) = closures.get(&place.var_id)
+    {
+        let src_ty = src_ty.clone();
+        Some((op, src_ty, *fun_id, generics.clone()))
+    } else {
+        None
+    }
+}
+
+/// Build the shim for the given closure. Returns `None` if the closure wasn't translated or
+/// doesn't have the expected shape.
+fn make_shim(krate: &mut TranslatedCrate, closure_id: FunDeclId) -> Option<FunDeclId> {
+    let closure = krate.fun_decls.get(closure_id)?;
+    let info = closure.signature.closure_info.as_ref()?;
+    let [state_ty, args_ty] = closure.signature.inputs.as_slice() else {
+        return None;
+    };
+    let TyKind::Adt(TypeId::Tuple, args_generics) = args_ty.kind() else {
+        return None;
+    };
+    if !info.state.is_empty() {
+        return None;
+    }
+    let span = closure.item_meta.span;
+    let generics = closure.signature.generics.clone();
+    let kind = info.kind;
+    let state_ty = state_ty.clone();
+    let args_ty = args_ty.clone();
+    let arg_tys = args_generics.types.clone();
+    let inputs: Vec<Ty> = arg_tys.iter().cloned().collect();
+    let output = closure.signature.output.clone();
+
+    let mut item_meta = closure.item_meta.clone();
+    item_meta.name.name.push(PathElem::Ident(
+        "fn_ptr_shim".to_string(),
+        Disambiguator::ZERO,
+    ));
+    item_meta.source_text = None;
+    item_meta.is_harness = false;
+    item_meta.attr_info.attributes.clear();
+    item_meta.attr_info.rename = None;
+
+    // The locals: the return value, the arguments, the state, the reference to the state (unless
+    // the closure is `FnOnce`) and the tuple of the arguments.
+    let mut locals = Vector::new();
+    let mut new_var = |ty: Ty| {
+        let ty = erase_sig_regions(ty);
+        locals.push_with(|index| Var {
+            index,
+            name: None,
+            ty,
+        })
+    };
+    let ret_var = new_var(output.clone());
+    let arg_vars: Vec<VarId> = arg_tys.iter().map(|ty| new_var(ty.clone())).collect();
+    let state_var = new_var(Ty::mk_unit());
+    let state_ref_var = match kind {
+        ClosureKind::FnOnce => None,
+        ClosureKind::Fn | ClosureKind::FnMut => Some(new_var(state_ty)),
+    };
+    let args_var = new_var(args_ty);
+
+    let mk_st = |content| Statement::new(span, content);
+    let mut statements = vec![mk_st(RawStatement::Assign(
+        Place::new(state_var),
+        Rvalue::Aggregate(
+            AggregateKind::Adt(TypeId::Tuple, None, None, GenericArgs::empty()),
+            Vec::new(),
+        ),
+    ))];
+    let state_arg = match state_ref_var {
+        Some(state_ref_var) => {
+            let borrow_kind = match kind {
+                ClosureKind::Fn => BorrowKind::Shared,
+                _ => BorrowKind::Mut,
+            };
+            statements.push(mk_st(RawStatement::Assign(
+                Place::new(state_ref_var),
+                Rvalue::Ref(Place::new(state_var), borrow_kind),
+            )));
+            state_ref_var
+        }
+        None => state_var,
+    };
+    statements.push(mk_st(RawStatement::Assign(
+        Place::new(args_var),
+        Rvalue::Aggregate(
+            AggregateKind::Adt(
+                TypeId::Tuple,
+                None,
+                None,
+                GenericArgs::new_from_types(arg_tys.map(erase_sig_regions)),
+            ),
+            arg_vars
+                .iter()
+                .map(|var| Operand::Move(Place::new(*var)))
+                .collect(),
+        ),
+    )));
+    statements.push(mk_st(RawStatement::Call(Call {
+        func: FnOperand::Regular(FnPtr {
+            func: FunIdOrTraitMethodRef::Fun(FunId::Regular(closure_id)),
+            generics: GenericArgs {
+                regions: generics.regions.iter().map(|_| Region::Erased).collect(),
+                ..generics.identity_args()
+            },
+        }),
+        args: vec![
+            Operand::Move(Place::new(state_arg)),
+            Operand::Move(Place::new(args_var)),
+        ],
+        dest: Place::new(ret_var),
+        fn_ptr_sig: None,
+    })));
+    let block = BlockData {
+        statements,
+        terminator: Terminator::new(span, RawTerminator::Return),
+    };
+
+    let body = ExprBody {
+        span,
+        arg_count: arg_vars.len(),
+        locals,
+        comments: Vec::new(),
+        debug_info: None,
+        body: [block].into_iter().collect(),
+    };
+    let body_id = krate.bodies.push(Body::Unstructured(body));
+
+    let signature = FunSig {
+        is_unsafe: false,
+        is_closure: false,
+        closure_info: None,
+        generics,
+        parent_params_info: None,
+        inputs,
+        output,
+    };
+    let shim_id = krate.fun_decls.push_with(|def_id| FunDecl {
+        def_id,
+        item_meta,
+        signature,
+        kind: ItemKind::Regular,
+        linkage: Linkage::default(),
+        body: Ok(body_id),
+    });
+    let name = krate.fun_decls[shim_id].item_meta.name.clone();
+    krate.all_ids.insert(shim_id.into());
+    krate.item_names.insert(shim_id.into(), name);
+    Some(shim_id)
+}
+
+pub struct Transform;
+impl TransformPass for Transform {
+    fn transform_ctx(&self, ctx: &mut TransformCtx<'_>) {
+        let krate = &mut ctx.translated;
+
+        // Find the closures which are cast to function pointers. We use an ordered set so that the
+        // ids of the shims don't depend on the iteration order of a hash set.
+        let mut cast_closures: BTreeSet<FunDeclId> = BTreeSet::new();
+        for body in krate.bodies.iter_mut() {
+            let Some(body) = body.as_unstructured_mut() else {
+                continue;
+            };
+            let closures = closure_locals(body);
+            if closures.is_empty() {
+                continue;
+            }
+            for block in body.body.iter_mut() {
+                for st in &mut block.statements {
+                    if let Some((_, _, fun_id, _)) = closure_cast(st, &closures) {
+                        cast_closures.insert(fun_id);
+                    }
+                }
+            }
+        }
+
+        // Generate the shims.
+        let shims: HashMap<FunDeclId, FunDeclId> = cast_closures
+            .into_iter()
+            .filter_map(|closure_id| Some((closure_id, make_shim(krate, closure_id)?)))
+            .collect();
+        ctx.generated_items
+            .extend(shims.values().map(|shim_id| AnyTransId::from(*shim_id)));
+
+        // Make the casts refer to the shims.
+        for body in krate.bodies.iter_mut() {
+            let Some(body) = body.as_unstructured_mut() else {
+                continue;
+            };
+            let closures = closure_locals(body);
+            if closures.is_empty() {
+                continue;
+            }
+            for block in body.body.iter_mut() {
+                for st in &mut block.statements {
+                    if let Some((op, src_ty, fun_id, generics)) = closure_cast(st, &closures)
+                        && let Some(shim_id) = shims.get(&fun_id)
+                    {
+                        // The shim has the generics of the closure. We erase the regions, like
+                        // for the other function pointers.
+                        let num_regions =
+                            krate.fun_decls[*shim_id].signature.generics.regions.len();
+                        let generics = GenericArgs {
+                            regions: (0..num_regions).map(|_| Region::Erased).collect(),
+                            ..generics
+                        };
+                        *op = Operand::Const(ConstantExpr {
+                            value: RawConstantExpr::FnPtr(FnPtr {
+                                func: FunIdOrTraitMethodRef::Fun(FunId::Regular(*shim_id)),
+                                generics,
+                            }),
+                            ty: src_ty,
+                            has_type_suffix: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
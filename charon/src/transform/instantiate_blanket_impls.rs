@@ -0,0 +1,357 @@
+//! With `--instantiate-blanket-impls`: replace the uses of blanket impls (impls whose self type is
+//! a type parameter, like `impl<T: Foo> Bar for T`) at concrete types by uses of specialized
+//! copies of these impls. E.g. a use of `impl<T: Foo> Bar for T` with `T := u32` and a trait
+//! reference for `u32: Foo` becomes a use of a new `impl Bar for u32`, whose methods are
+//! specialized too. This spares the consumers which work on monomorphic code from resolving the
+//! chains of impl expressions which go through blanket impls.
+//!
+//! The specialized items keep the region parameters of the original ones. The type, const
+//! generic and trait clause parameters of the impl are removed from the generics of the
+//! specialized items, leaving empty slots (like in [crate::transform::hide_marker_traits]), so
+//! that the ids of the remaining parameters of the methods don't change.
+use std::collections::{HashMap, HashSet};
+
+use derive_visitor::{Drive, DriveMut, Visitor, VisitorMut};
+use hashlink::LinkedHashSet;
+use index_vec::Idx;
+
+use crate::ast::*;
+use crate::ids::Vector;
+
+use super::{ctx::TransformPass, TransformCtx};
+
+/// We stop instantiating after this many rounds, to avoid looping on polymorphic recursion.
+const MAX_ROUNDS: usize = 100;
+
+/// A blanket impl applied to some generic arguments. The regions are not part of the key since
+/// we keep the region parameters in the specialized impls.
+type InstanceKey = (TraitImplId, GenericArgs);
+
+fn instance_key(impl_id: TraitImplId, args: &GenericArgs) -> InstanceKey {
+    let args = GenericArgs {
+        regions: Vector::new(),
+        ..args.clone()
+    };
+    (impl_id, args)
+}
+
+/// Looks for generic parameters, i.e. for things which are not concrete. We conservatively treat
+/// all the bound regions as generic, even those which are bound inside the arguments.
+#[derive(Default, Visitor)]
+#[visitor(Ty(enter), ConstGeneric(enter), TraitRefKind(enter), Region(enter))]
+struct FindGenerics {
+    found: bool,
+}
+
+impl FindGenerics {
+    fn enter_ty(&mut self, ty: &Ty) {
+        if let TyKind::TypeVar(_) = ty.kind() {
+            self.found = true;
+        }
+        ty.drive_inner(self);
+    }
+    fn enter_const_generic(&mut self, cg: &ConstGeneric) {
+        if let ConstGeneric::Var(_) = cg {
+            self.found = true;
+        }
+    }
+    fn enter_trait_ref_kind(&mut self, kind: &TraitRefKind) {
+        if matches!(
+            kind,
+            TraitRefKind::Clause(..)
+                | TraitRefKind::ParentClause(..)
+                | TraitRefKind::ItemClause(..)
+                | TraitRefKind::SelfId
+                | TraitRefKind::Unknown(..)
+        ) {
+            self.found = true;
+        }
+    }
+    fn enter_region(&mut self, region: &Region) {
+        if let Region::BVar(..) = region {
+            self.found = true;
+        }
+    }
+}
+
+fn is_concrete(args: &GenericArgs) -> bool {
+    let mut visitor = FindGenerics::default();
+    args.types.drive(&mut visitor);
+    args.const_generics.drive(&mut visitor);
+    args.trait_refs.drive(&mut visitor);
+    !visitor.found
+}
+
+/// Collects the uses of blanket impls at concrete arguments.
+#[derive(Visitor)]
+#[visitor(TraitRefKind(enter), Ty(enter))]
+struct CollectInstances<'a> {
+    blanket_impls: &'a HashSet<TraitImplId>,
+    /// In the order in which we encountered them, so that the ids we give them are reproducible.
+    instances: LinkedHashSet<InstanceKey>,
+}
+
+impl CollectInstances<'_> {
+    fn enter_trait_ref_kind(&mut self, kind: &TraitRefKind) {
+        if let TraitRefKind::TraitImpl(impl_id, args) = kind
+            && self.blanket_impls.contains(impl_id)
+            && is_concrete(args)
+        {
+            self.instances.insert(instance_key(*impl_id, args));
+        }
+    }
+    fn enter_ty(&mut self, ty: &Ty) {
+        ty.drive_inner(self);
+    }
+}
+
+/// Replaces the uses of blanket impls with the uses of their specialized versions.
+#[derive(VisitorMut)]
+#[visitor(TraitRefKind(exit))]
+struct ReplaceInstances {
+    instances: HashMap<InstanceKey, TraitImplId>,
+}
+
+impl ReplaceInstances {
+    fn exit_trait_ref_kind(&mut self, kind: &mut TraitRefKind) {
+        if let TraitRefKind::TraitImpl(impl_id, args) = kind
+            && let Some(new_id) = self.instances.get(&instance_key(*impl_id, args))
+        {
+            let args = GenericArgs {
+                regions: std::mem::take(&mut args.regions),
+                ..GenericArgs::empty()
+            };
+            *kind = TraitRefKind::TraitImpl(*new_id, args);
+        }
+    }
+}
+
+/// Replaces the type, const generic and trait clause parameters of an impl with the given
+/// arguments. This must be wrapped with [Ty::visit_inside] to reach the parameters nested in
+/// types.
+struct Instantiate<'a> {
+    args: &'a GenericArgs,
+}
+
+impl derive_visitor::VisitorMut for Instantiate<'_> {
+    fn visit(&mut self, item: &mut dyn std::any::Any, event: derive_visitor::Event) {
+        // We substitute on exit so that we don't visit the substituted arguments.
+        if !matches!(event, derive_visitor::Event::Exit) {
+            return;
+        }
+        if let Some(ty) = item.downcast_mut::<Ty>() {
+            if let TyKind::TypeVar(id) = ty.kind()
+                && let Some(arg) = self.args.types.get(*id)
+            {
+                *ty = arg.clone();
+            }
+        } else if let Some(cg) = item.downcast_mut::<ConstGeneric>() {
+            if let ConstGeneric::Var(id) = cg
+                && let Some(arg) = self.args.const_generics.get(*id)
+            {
+                *cg = arg.clone();
+            }
+        } else if let Some(kind) = item.downcast_mut::<TraitRefKind>() {
+            if let TraitRefKind::Clause(id) = kind
+                && let Some(arg) = self.args.trait_refs.get(*id)
+            {
+                *kind = arg.kind.clone();
+            }
+        }
+    }
+}
+
+/// Remove the parameters of the impl from the given generics, leaving empty slots.
+fn remove_impl_params(generics: &mut GenericParams, impl_generics: &GenericParams) {
+    for id in impl_generics.types.iter_indices() {
+        if id.index() < generics.types.next_id().index() {
+            generics.types.remove(id);
+        }
+    }
+    for id in impl_generics.const_generics.iter_indices() {
+        if id.index() < generics.const_generics.next_id().index() {
+            generics.const_generics.remove(id);
+        }
+    }
+    for id in impl_generics.trait_clauses.iter_indices() {
+        if id.index() < generics.trait_clauses.next_id().index() {
+            generics.trait_clauses.remove(id);
+        }
+    }
+}
+
+/// Update the path element of `name` which refers to the impl `old_id`.
+fn rename_impl(
+    name: &mut Name,
+    old_id: TraitImplId,
+    new_id: TraitImplId,
+    generics: &GenericParams,
+) {
+    for elem in name.name.iter_mut() {
+        if let PathElem::Impl(ImplElem::Trait(impl_id, impl_generics, _), _) = elem
+            && *impl_id == old_id
+        {
+            *impl_id = new_id;
+            *impl_generics = generics.clone();
+        }
+    }
+}
+
+/// Build the specialized version of a method of a blanket impl. Returns `None` if the method
+/// wasn't translated.
+fn instantiate_method(
+    krate: &mut TranslatedCrate,
+    fun_id: FunDeclId,
+    blanket_impl: &TraitImpl,
+    new_impl_id: TraitImplId,
+    args: &GenericArgs,
+) -> Option<FunDeclId> {
+    let mut decl = krate.fun_decls.get(fun_id)?.clone();
+    let body = decl.body.ok().and_then(|id| krate.bodies.get(id)).cloned();
+
+    let mut visitor = Ty::visit_inside(Instantiate { args });
+    decl.drive_mut(&mut visitor);
+    remove_impl_params(&mut decl.signature.generics, &blanket_impl.generics);
+    rename_impl(
+        &mut decl.item_meta.name,
+        blanket_impl.def_id,
+        new_impl_id,
+        &GenericParams::empty(),
+    );
+    if let ItemKind::TraitImpl { impl_id, .. } = &mut decl.kind {
+        *impl_id = new_impl_id;
+    }
+    if let Some(mut body) = body {
+        body.drive_mut(&mut visitor);
+        decl.body = Ok(krate.bodies.push(body));
+    }
+
+    let new_id = krate.fun_decls.push_with(|id| {
+        decl.def_id = id;
+        decl
+    });
+    let decl = &krate.fun_decls[new_id];
+    krate.all_ids.insert(new_id.into());
+    krate
+        .item_names
+        .insert(new_id.into(), decl.item_meta.name.clone());
+    Some(new_id)
+}
+
+/// Build the specialized version of a blanket impl, in the reserved slot `new_id`. The ids of the
+/// new items are added to `generated_items`.
+fn instantiate_impl(
+    krate: &mut TranslatedCrate,
+    blanket_id: TraitImplId,
+    args: &GenericArgs,
+    new_id: TraitImplId,
+    generated_items: &mut HashSet<AnyTransId>,
+) {
+    let Some(blanket_impl) = krate.trait_impls.get(blanket_id).cloned() else {
+        return;
+    };
+    let mut timpl = blanket_impl.clone();
+    timpl.def_id = new_id;
+    timpl.drive_mut(&mut Ty::visit_inside(Instantiate { args }));
+    remove_impl_params(&mut timpl.generics, &blanket_impl.generics);
+    let generics = timpl.generics.clone();
+    rename_impl(&mut timpl.item_meta.name, blanket_id, new_id, &generics);
+
+    for (_, fun_id) in timpl
+        .required_methods
+        .iter_mut()
+        .chain(timpl.provided_methods.iter_mut())
+    {
+        if let Some(new_fun_id) = instantiate_method(krate, *fun_id, &blanket_impl, new_id, args) {
+            *fun_id = new_fun_id;
+            generated_items.insert(new_fun_id.into());
+        }
+    }
+
+    generated_items.insert(new_id.into());
+    krate.all_ids.insert(new_id.into());
+    krate
+        .item_names
+        .insert(new_id.into(), timpl.item_meta.name.clone());
+    krate.trait_impls.set_slot(new_id, timpl);
+}
+
+pub struct Transform;
+impl TransformPass for Transform {
+    fn transform_ctx(&self, ctx: &mut TransformCtx<'_>) {
+        if !ctx.options.instantiate_blanket_impls {
+            return;
+        }
+        let krate = &mut ctx.translated;
+        let blanket_impls: HashSet<TraitImplId> = krate
+            .trait_impls
+            .iter()
+            .filter(|timpl| {
+                timpl
+                    .impl_trait
+                    .generics
+                    .types
+                    .get(TypeVarId::ZERO)
+                    .is_some_and(|self_ty| self_ty.kind().is_type_var())
+            })
+            .map(|timpl| timpl.def_id)
+            .collect();
+        if blanket_impls.is_empty() {
+            return;
+        }
+
+        let mut instances: HashMap<InstanceKey, TraitImplId> = HashMap::new();
+        for _ in 0..MAX_ROUNDS {
+            // Find the new instances, including in the items we created in the previous round.
+            let mut collector = CollectInstances {
+                blanket_impls: &blanket_impls,
+                instances: LinkedHashSet::new(),
+            };
+            krate.type_decls.drive(&mut collector);
+            krate.fun_decls.drive(&mut collector);
+            krate.global_decls.drive(&mut collector);
+            krate.trait_decls.drive(&mut collector);
+            krate.trait_impls.drive(&mut collector);
+            krate.bodies.drive(&mut collector);
+            let new_instances: Vec<InstanceKey> = collector
+                .instances
+                .into_iter()
+                .filter(|key| !instances.contains_key(key))
+                .collect();
+            if new_instances.is_empty() {
+                return;
+            }
+
+            // Reserve their ids, then replace their uses.
+            for key in &new_instances {
+                let new_id = krate.trait_impls.reserve_slot();
+                instances.insert(key.clone(), new_id);
+            }
+            let mut visitor = ReplaceInstances { instances };
+            krate.type_decls.drive_mut(&mut visitor);
+            krate.fun_decls.drive_mut(&mut visitor);
+            krate.global_decls.drive_mut(&mut visitor);
+            krate.trait_decls.drive_mut(&mut visitor);
+            krate.trait_impls.drive_mut(&mut visitor);
+            krate.bodies.drive_mut(&mut visitor);
+            instances = visitor.instances;
+
+            // Build the specialized impls. Their contents are updated in the next round.
+            for key in new_instances {
+                let (blanket_id, args) = &key;
+                instantiate_impl(
+                    krate,
+                    *blanket_id,
+                    args,
+                    instances[&key],
+                    &mut ctx.generated_items,
+                );
+            }
+        }
+        let msg = format!(
+            "Stopped instantiating the blanket impls after {MAX_ROUNDS} rounds; this may be \
+            caused by polymorphic recursion"
+        );
+        ctx.errors.span_err(Span::dummy(), &msg);
+    }
+}
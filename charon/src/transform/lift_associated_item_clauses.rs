@@ -35,6 +35,18 @@ impl TransformPass for Transform {
 
         // Move the item-local trait refs to match what we did in the trait declarations.
         for timpl in ctx.translated.trait_impls.iter_mut() {
+            // The obligations on associated types now refer to parent clauses.
+            if let Some(id_maps) = trait_item_clause_ids.get(timpl.impl_trait.trait_id) {
+                for obligation in &mut timpl.obligations {
+                    if let ObligationOrigin::AssocTypeBound(name, clause_id) =
+                        &mut obligation.origin
+                        && let Some(new_id) =
+                            id_maps.get(&*name).and_then(|ids| ids.get(*clause_id))
+                    {
+                        *clause_id = *new_id;
+                    }
+                }
+            }
             for (_, refs) in mem::take(&mut timpl.type_clauses) {
                 for trait_ref in refs {
                     // Note: this assumes that we listed the types in the same order as in the trait
@@ -1,17 +1,29 @@
+pub mod annotate_recursive_fields;
+pub mod borrow_liveness;
 pub mod check_generics;
+pub mod compute_move_info;
+pub mod compute_statement_ranges;
 pub mod ctx;
+pub mod devirtualize_closure_calls;
+pub mod embed_sources;
+pub mod expand_constant_comparisons;
 pub mod filter_invisible_trait_impls;
 pub mod graphs;
 pub mod hide_marker_traits;
 pub mod index_to_function_calls;
+pub mod inject_models;
 pub mod inline_local_panic_functions;
 pub mod insert_assign_return_unit;
+pub mod insert_closure_shims;
+pub mod instantiate_blanket_impls;
 pub mod lift_associated_item_clauses;
 pub mod merge_goto_chains;
 pub mod ops_to_function_calls;
 pub mod prettify_cfg;
 pub mod reconstruct_asserts;
 pub mod reconstruct_boxes;
+pub mod reconstruct_matches;
+pub mod reconstruct_try;
 pub mod recover_body_comments;
 pub mod remove_arithmetic_overflow_checks;
 pub mod remove_drop_never;
@@ -21,6 +33,7 @@ pub mod remove_read_discriminant;
 pub mod remove_unused_locals;
 pub mod reorder_decls;
 pub mod simplify_constants;
+pub mod simplify_derives;
 pub mod ullbc_to_llbc;
 pub mod update_block_indices;
 pub mod update_closure_signatures;
@@ -37,6 +50,13 @@ pub static ULLBC_PASSES: &[Pass] = &[
     // # Micro-pass: filter the trait impls that were marked invisible since we couldn't filter
     // them out earlier.
     NonBody(&filter_invisible_trait_impls::Transform),
+    // # Micro-pass: drop the bodies of the derived impls, or replace these impls with builtin ones
+    // (see `--opaque-derive` and `--builtin-derive`). This comes early to spare the work of the
+    // next passes on the removed bodies.
+    NonBody(&simplify_derives::Transform),
+    // # Micro-pass: replace the uses of blanket impls at concrete types by uses of specialized
+    // impls (if the option `--instantiate-blanket-impls` is set).
+    NonBody(&instantiate_blanket_impls::Transform),
     // # Micro-pass: merge single-origin gotos into their parent. This drastically reduces the
     // graph size of the CFG.
     UnstructuredBody(&merge_goto_chains::Transform),
@@ -54,13 +74,23 @@ pub static ULLBC_PASSES: &[Pass] = &[
     // it must happen before passes that insert statements like [simplify_constants].
     // **WARNING**: this pass works across calls, hence must happen after `merge_goto_chains`,
     UnstructuredBody(&reconstruct_boxes::Transform),
+    // # Micro-pass: turn the calls to derived `PartialEq::eq` with a constant struct into
+    // field-wise comparisons. This must happen before [simplify_constants], which desugars the
+    // constant structs.
+    UnstructuredBody(&expand_constant_comparisons::Transform),
     // # Micro-pass: desugar the constants to other values/operands as much
-    // as possible.
+    // as possible (as configured by `--simplify-constants`).
     UnstructuredBody(&simplify_constants::Transform),
     // # Micro-pass: the first local variable of closures is the
     // closure itself. This is not consistent with the closure signature,
     // which ignores this first variable. This micro-pass updates this.
     UnstructuredBody(&update_closure_signatures::Transform),
+    // # Micro-pass: make the casts of closures to function pointers refer to shim functions which
+    // call the closures. This must happen after [update_closure_signatures].
+    NonBody(&insert_closure_shims::Transform),
+    // # Micro-pass: replace the calls to the `Fn*` traits on known closures with direct calls to
+    // the closures (opt-in). This must happen after [update_closure_signatures].
+    NonBody(&devirtualize_closure_calls::Transform),
     // # Micro-pass: remove the dynamic checks we couldn't remove in [`remove_dynamic_checks`].
     // **WARNING**: this pass uses the fact that the dynamic checks
     // introduced by Rustc use a special "assert" construct. Because of
@@ -71,6 +101,16 @@ pub static ULLBC_PASSES: &[Pass] = &[
     UnstructuredBody(&ops_to_function_calls::Transform),
     // # Micro-pass: make sure the block ids used in the ULLBC are consecutive
     UnstructuredBody(&update_block_indices::Transform),
+    // # Micro-pass: compute the liveness of the borrows (opt-in). The results are attached to the
+    // statements and describe their operands, hence this must come after the passes which add or
+    // rewrite statements and operands (e.g. [simplify_constants], [ops_to_function_calls]).
+    UnstructuredBody(&borrow_liveness::Transform),
+    // # Micro-pass: compute where the locals are moved, copied and dropped (opt-in). Like
+    // [borrow_liveness], this records the operands of the statements, hence must come after the
+    // passes which change them.
+    UnstructuredBody(&compute_move_info::Transform),
+    // # Micro-pass: annotate the fields through which the types are recursive (opt-in).
+    NonBody(&annotate_recursive_fields::Transform),
 ];
 
 pub static LLBC_PASSES: &[Pass] = &[
@@ -83,10 +123,17 @@ pub static LLBC_PASSES: &[Pass] = &[
     // calls.
     // (introduces: ArrayIndexShared, ArrayIndexMut, etc.)
     StructuredBody(&index_to_function_calls::Transform),
-    // # Micro-pass: Remove the discriminant reads (merge them with the switches)
+    // # Micro-pass: Remove the discriminant reads (merge them with the switches). With
+    // `--preserve-discriminant-reads`, the reads whose value is used elsewhere are kept.
     StructuredBody(&remove_read_discriminant::Transform),
+    // # Micro-pass: merge the nested matches into multi-level matches (opt-in).
+    StructuredBody(&reconstruct_matches::Transform),
     // Cleanup the cfg.
     StructuredBody(&prettify_cfg::Transform),
+    // # Micro-pass: collapse the desugaring of the `?` operator (opt-in). This must happen after
+    // [remove_read_discriminant], which introduces the matches on the `ControlFlow` values, and
+    // before [remove_unused_locals], which cleans up the locals this leaves unused.
+    StructuredBody(&reconstruct_try::Transform),
     // # Micro-pass: add the missing assignments to the return value.
     // When the function return type is unit, the generated MIR doesn't
     // set the return value to `()`. This can be a concern: in the case
@@ -107,6 +154,13 @@ pub static LLBC_PASSES: &[Pass] = &[
     // statements. This must be last after all the statement-affecting passes to avoid losing
     // comments.
     StructuredBody(&recover_body_comments::Transform),
+    // # Micro-pass: record the byte ranges of the statements (opt-in). This comes after all the
+    // statement-affecting passes, so that the statements they introduce get a range too.
+    StructuredBody(&compute_statement_ranges::Transform),
+    // # Micro-pass: give their models (see `--model-file`) as bodies to the opaque functions. This
+    // comes after the passes which transform the bodies since the models are already in their
+    // final form.
+    NonBody(&inject_models::Transform),
     // Check that all supplied generic types match the corresponding generic parameters.
     NonBody(&check_generics::Check),
 ];
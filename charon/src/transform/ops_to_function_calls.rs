@@ -29,6 +29,7 @@ fn transform_st(s: &mut Statement) {
                 func,
                 args: vec![op.clone()],
                 dest: p.clone(),
+                fn_ptr_sig: None,
             });
         }
         // Transform the array aggregates to function calls
@@ -48,6 +49,7 @@ fn transform_st(s: &mut Statement) {
                 func,
                 args: vec![op.clone()],
                 dest: p.clone(),
+                fn_ptr_sig: None,
             });
         }
         _ => {}
@@ -2,41 +2,211 @@
 //! (for the binops for example). The `assert!` introduced by the user
 //! introduce `if ... then { panic!(...) } else { ...}`.
 //! This pass introduces `assert` instead in order to make the code shorter.
+//!
+//! The exact shape of those `if`s depends on the rustc version and on the MIR optimizations: the
+//! panicking branch can be either branch, and it may compute the panic message before panicking.
+//! By default we only recognize the `if c { panic } else { .. }` shape. With `--extended-asserts`,
+//! we match the `if`s against all the shapes listed in [ASSERT_PATTERNS], and the `if`s which
+//! have a branch ending with a panic but which don't match any pattern are reported in a warning,
+//! so that the users know which asserts were left as is.
 
+use std::collections::HashMap;
 use std::mem;
 
+use derive_visitor::{visitor_enter_fn, Drive};
+
+use crate::ids::Vector;
 use crate::llbc_ast::*;
 use crate::transform::TransformCtx;
 
 use super::ctx::LlbcPass;
 
-fn transform_st(st: &mut Statement) -> Vec<Statement> {
-    if let RawStatement::Switch(Switch::If(_, then_block, _)) = &mut st.content
-        && let Some(first) = then_block.statements.first_mut()
-        && first.content.is_abort()
-    {
-        // Replace the `if` with a `nop`.
-        let (op, then_block, else_block) = mem::replace(&mut st.content, RawStatement::Nop)
-            .to_switch()
-            .unwrap()
-            .to_if()
-            .unwrap();
-        let assert = Statement::new(
-            then_block.span,
-            RawStatement::Assert(Assert {
-                cond: op,
-                expected: false,
-            }),
-        );
-        [assert].into_iter().chain(else_block.statements).collect()
-    } else {
-        Vec::new()
+/// A shape of `if` which we turn into an assert.
+struct AssertPattern {
+    /// Whether the panicking branch is the `then` branch (in which case the assert expects the
+    /// condition to be `false`) or the `else` branch (in which case it expects `true`).
+    panics_in_then: bool,
+    /// Whether this shape is only recognized with `--extended-asserts`.
+    extended: bool,
+    /// Recognizes the panicking branch, and returns the operands of the panic message.
+    matches_branch: fn(&FreshTemporaries, &Block) -> Option<Vec<Operand>>,
+}
+
+static ASSERT_PATTERNS: &[AssertPattern] = &[
+    // `if c { panic } else { .. }`: this is what rustc emits for `assert!(c)` (where `c` is the
+    // negation of the user condition) and for the dynamic checks.
+    AssertPattern {
+        panics_in_then: true,
+        extended: false,
+        matches_branch: panics,
+    },
+    // `if c { .. } else { panic }`: newer rustc versions and MIR inlining may flip the branches.
+    AssertPattern {
+        panics_in_then: false,
+        extended: true,
+        matches_branch: panics,
+    },
+    // `if c { msg := ..; panic } else { .. }`: the branch computes the panic message, e.g. for
+    // `assert!(c, "message")`.
+    AssertPattern {
+        panics_in_then: true,
+        extended: true,
+        matches_branch: computes_message_then_panics,
+    },
+    AssertPattern {
+        panics_in_then: false,
+        extended: true,
+        matches_branch: computes_message_then_panics,
+    },
+];
+
+/// Counts the occurrences of the locals of a body, to find the temporaries which are only used in
+/// a given block.
+struct FreshTemporaries<'a> {
+    locals: &'a Vector<VarId, Var>,
+    arg_count: usize,
+    occurrences: HashMap<VarId, usize>,
+}
+
+impl<'a> FreshTemporaries<'a> {
+    fn new(locals: &'a Vector<VarId, Var>, arg_count: usize, body: &Block) -> Self {
+        FreshTemporaries {
+            locals,
+            arg_count,
+            occurrences: count_occurrences(body),
+        }
+    }
+
+    /// Whether `place` is an anonymous local (i.e. neither a user variable nor an argument) which
+    /// only appears in the block whose occurrences are `block_occurrences`.
+    fn is_fresh_in(&self, block_occurrences: &HashMap<VarId, usize>, place: &Place) -> bool {
+        let var_id = place.var_id;
+        place.projection.is_empty()
+            && var_id.index() > self.arg_count
+            && self.locals[var_id].name.is_none()
+            && self.occurrences.get(&var_id) == block_occurrences.get(&var_id)
     }
 }
 
+fn count_occurrences(x: &impl Drive) -> HashMap<VarId, usize> {
+    let mut occurrences = HashMap::new();
+    x.drive(&mut visitor_enter_fn(|vid: &VarId| {
+        *occurrences.entry(*vid).or_default() += 1;
+    }));
+    occurrences
+}
+
+/// Whether the block starts with a panic.
+fn panics(_: &FreshTemporaries, block: &Block) -> Option<Vec<Operand>> {
+    let first = block.statements.first()?;
+    first.content.is_abort().then(Vec::new)
+}
+
+/// Whether the block assigns some fresh temporaries (to compute a panic message) then panics.
+/// Returns the constant operands of those assignments.
+fn computes_message_then_panics(temps: &FreshTemporaries, block: &Block) -> Option<Vec<Operand>> {
+    let (last, prefix) = block.statements.split_last()?;
+    if prefix.is_empty() || !last.content.is_abort() {
+        return None;
+    }
+    let block_occurrences = count_occurrences(block);
+    let mut message = Vec::new();
+    for st in prefix {
+        let dest = match &st.content {
+            RawStatement::Assign(dest, _) => dest,
+            RawStatement::Call(call) => &call.dest,
+            RawStatement::Drop(dest) => dest,
+            _ => return None,
+        };
+        if !temps.is_fresh_in(&block_occurrences, dest) {
+            return None;
+        }
+        st.content.drive(&mut visitor_enter_fn(|op: &Operand| {
+            if let Operand::Const(_) = op {
+                message.push(op.clone());
+            }
+        }));
+    }
+    Some(message)
+}
+
+/// Whether the block ends with a panic. The `if`s with such a branch are assert-like.
+fn ends_with_panic(block: &Block) -> bool {
+    block
+        .statements
+        .last()
+        .is_some_and(|st| matches!(st.content, RawStatement::Abort(AbortKind::Panic(_))))
+}
+
+fn transform_st(
+    extended: bool,
+    temps: &FreshTemporaries,
+    st: &mut Statement,
+    unrecognized: &mut Vec<Span>,
+) -> Vec<Statement> {
+    let RawStatement::Switch(Switch::If(_, then_block, else_block)) = &st.content else {
+        return Vec::new();
+    };
+    let Some((pattern, message)) = ASSERT_PATTERNS
+        .iter()
+        .filter(|pattern| extended || !pattern.extended)
+        .find_map(|pattern| {
+            let branch = if pattern.panics_in_then {
+                then_block
+            } else {
+                else_block
+            };
+            Some((pattern, (pattern.matches_branch)(temps, branch)?))
+        })
+    else {
+        if extended && (ends_with_panic(then_block) || ends_with_panic(else_block)) {
+            unrecognized.push(st.span);
+        }
+        return Vec::new();
+    };
+
+    // Replace the `if` with a `nop`.
+    let (op, then_block, else_block) = mem::replace(&mut st.content, RawStatement::Nop)
+        .to_switch()
+        .unwrap()
+        .to_if()
+        .unwrap();
+    let (panicking_block, continuation) = if pattern.panics_in_then {
+        (then_block, else_block)
+    } else {
+        (else_block, then_block)
+    };
+    let assert = Statement::new(
+        panicking_block.span,
+        RawStatement::Assert(Assert {
+            cond: op,
+            expected: !pattern.panics_in_then,
+            message,
+        }),
+    );
+    [assert]
+        .into_iter()
+        .chain(continuation.statements)
+        .collect()
+}
+
 pub struct Transform;
 impl LlbcPass for Transform {
-    fn transform_body(&self, _ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
-        b.body.transform(&mut transform_st);
+    fn transform_body(&self, ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
+        let extended = ctx.options.extended_asserts;
+        // The occurrences are computed before any `if` is removed: removing an `if` can only
+        // remove occurrences, hence this can only make us miss some fresh temporaries.
+        let temps = FreshTemporaries::new(&b.locals, b.arg_count, &b.body);
+        let mut unrecognized = Vec::new();
+        b.body.transform(&mut |st: &mut Statement| {
+            transform_st(extended, &temps, st, &mut unrecognized)
+        });
+        for span in unrecognized {
+            ctx.errors.span_err_no_register(
+                span,
+                "This `if` looks like an assert (one of its branches panics) but doesn't match any \
+                known assert shape: it was not turned into an assert",
+            );
+        }
     }
 }
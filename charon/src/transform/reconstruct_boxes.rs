@@ -80,10 +80,10 @@ impl Transform {
                                 let name = locals[var_id].name.clone();
                                 let ty = generics.types[0].clone();
                                 let var = locals.push_with(|index| Var { index, name, ty });
-                                let st = Statement {
-                                    span: seq[real_i].span,
-                                    content: RawStatement::Assign(Place::new(var), val),
-                                };
+                                let st = Statement::new(
+                                    seq[real_i].span,
+                                    RawStatement::Assign(Place::new(var), val),
+                                );
                                 to_insert.push((real_i, vec![st]));
                                 Operand::Move(Place::new(var))
                             }
@@ -97,6 +97,7 @@ impl Transform {
                             }),
                             args: vec![val],
                             dest,
+                            fn_ptr_sig: None,
                         });
                         return to_insert;
                     }
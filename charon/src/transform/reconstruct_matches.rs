@@ -0,0 +1,276 @@
+//! # Micro-pass: merge nested matches into multi-level matches.
+//!
+//! Rustc decomposes a match over nested patterns into a tree of matches over the discriminants
+//! of the successive sub-places. For instance:
+//! ```text
+//! match x {
+//!   Some(Ok(_)) => A,
+//!   Some(Err(_)) => B,
+//!   None => C,
+//! }
+//! ```
+//! becomes:
+//! ```text
+//! match x {
+//!   0 => { C },
+//!   1 => {
+//!     match (x as variant @1).0 {
+//!       0 => { A },
+//!       1 => { B },
+//!     }
+//!   },
+//! }
+//! ```
+//! This pass recognizes the arms whose body is exactly a match on a field of the scrutinee, and
+//! merges them into a single [Switch::NestedMatch] with one arm per leaf.
+//!
+//! We also recognize the shapes produced by `if let`, `let ... else` and `while let`, and record
+//! them in the [MatchKind] of the match. This pass is opt-in (see the `--reconstruct-matches`
+//! option).
+use crate::llbc_ast::*;
+use crate::transform::TransformCtx;
+
+use super::ctx::LlbcPass;
+
+pub struct Transform;
+
+impl Transform {
+    /// Convert a match into a list of arms with patterns.
+    fn into_pattern_arms(
+        switch: Switch,
+    ) -> Result<(Place, Vec<(Pattern, Block)>, Option<Block>), Switch> {
+        match switch {
+            Switch::Match(p, arms, otherwise) => {
+                let arms = arms
+                    .into_iter()
+                    .map(|(variants, block)| (Pattern::Variant(variants, Vec::new()), block))
+                    .collect();
+                Ok((p, arms, otherwise))
+            }
+            Switch::NestedMatch(p, arms, otherwise, _) => Ok((p, arms, otherwise)),
+            switch => Err(switch),
+        }
+    }
+
+    /// If `block` consists only of a match on a field of `(scrutinee as variant_id)`, return the
+    /// id of that field.
+    fn inner_match_field(
+        scrutinee: &Place,
+        variants: &[VariantId],
+        block: &Block,
+    ) -> Option<FieldId> {
+        let [variant_id] = variants else {
+            return None;
+        };
+        let mut statements = block.statements.iter().filter(|st| !st.content.is_nop());
+        let st = statements.next()?;
+        if statements.next().is_some() {
+            return None;
+        }
+        let RawStatement::Switch(Switch::Match(p, ..) | Switch::NestedMatch(p, ..)) = &st.content
+        else {
+            return None;
+        };
+        if p.var_id != scrutinee.var_id {
+            return None;
+        }
+        let (last, prefix) = p.projection.split_last()?;
+        if prefix != scrutinee.projection.as_slice() {
+            return None;
+        }
+        match last {
+            ProjectionElem::Field(FieldProjKind::Adt(_, Some(v)), field_id) if v == variant_id => {
+                Some(*field_id)
+            }
+            _ => None,
+        }
+    }
+
+    /// Merge the arms that consist of a match on a field of the scrutinee.
+    fn merge_nested_matches(st: &mut Statement) {
+        // We work bottom-up, so the outer match is always a [Switch::Match] (the inner ones may
+        // already have been merged).
+        let RawStatement::Switch(switch @ Switch::Match(..)) = &mut st.content else {
+            return;
+        };
+        let Switch::Match(p, arms, _) = &*switch else {
+            unreachable!()
+        };
+        if !arms
+            .iter()
+            .any(|(variants, block)| Self::inner_match_field(p, variants, block).is_some())
+        {
+            return;
+        }
+
+        take_mut::take(switch, |switch| {
+            let (p, arms, otherwise) = Self::into_pattern_arms(switch).unwrap();
+            let mut new_arms = Vec::new();
+            for (pat, block) in arms {
+                let Pattern::Variant(variants, _) = &pat else {
+                    unreachable!()
+                };
+                let Some(field_id) = Self::inner_match_field(&p, variants, &block) else {
+                    new_arms.push((pat, block));
+                    continue;
+                };
+                let variants = variants.clone();
+                // Extract the inner match: this is the only non-nop statement of the block.
+                let inner = block
+                    .statements
+                    .into_iter()
+                    .find(|st| !st.content.is_nop())
+                    .unwrap();
+                let RawStatement::Switch(inner) = inner.content else {
+                    unreachable!()
+                };
+                let (_, inner_arms, inner_otherwise) = Self::into_pattern_arms(inner).unwrap();
+                let wrap = |sub_pat| Pattern::Variant(variants.clone(), vec![(field_id, sub_pat)]);
+                for (sub_pat, sub_block) in inner_arms {
+                    new_arms.push((wrap(sub_pat), sub_block));
+                }
+                if let Some(inner_otherwise) = inner_otherwise {
+                    new_arms.push((wrap(Pattern::Wildcard), inner_otherwise));
+                }
+            }
+            Switch::NestedMatch(p, new_arms, otherwise, MatchKind::Match)
+        });
+    }
+
+    /// Whether this block never falls through, i.e. every path through it ends with a `return`,
+    /// an `abort`, or a jump out of the block. This is what `let ... else` requires of its `else`
+    /// branch.
+    fn diverges(block: &Block) -> bool {
+        let Some(last) = block
+            .statements
+            .iter()
+            .rev()
+            .find(|st| !st.content.is_nop())
+        else {
+            return false;
+        };
+        match &last.content {
+            RawStatement::Return
+            | RawStatement::Abort(_)
+            | RawStatement::Break(_)
+            | RawStatement::Continue(_)
+            | RawStatement::Goto(_) => true,
+            // A switch diverges if all its branches do. A `match` without `otherwise` is
+            // exhaustive, hence `iter_targets` gives all the branches.
+            RawStatement::Switch(switch) => switch.iter_targets().all(Self::diverges),
+            // A loop diverges if nothing breaks out of it.
+            RawStatement::Loop(body) => !Self::breaks_out(body, 0),
+            _ => false,
+        }
+    }
+
+    /// Whether this block, which is the body of a loop nested `depth` loops deep inside the
+    /// loop we are interested in, contains a `break` to that loop.
+    fn breaks_out(block: &Block, depth: usize) -> bool {
+        block.statements.iter().any(|st| match &st.content {
+            RawStatement::Break(i) => *i == depth,
+            RawStatement::Loop(body) => Self::breaks_out(body, depth + 1),
+            RawStatement::Switch(switch) => {
+                switch.iter_targets().any(|b| Self::breaks_out(b, depth))
+            }
+            RawStatement::Labeled(_, body) => Self::breaks_out(body, depth),
+            _ => false,
+        })
+    }
+
+    /// Recognize the shapes produced by `if let` and `let ... else`, i.e. matches with a single
+    /// arm and an `otherwise` branch.
+    fn annotate_if_let(st: &mut Statement) {
+        let RawStatement::Switch(switch @ (Switch::Match(..) | Switch::NestedMatch(..))) =
+            &mut st.content
+        else {
+            return;
+        };
+        let (num_arms, otherwise) = match &*switch {
+            Switch::Match(_, arms, otherwise) => (arms.len(), otherwise),
+            Switch::NestedMatch(_, arms, otherwise, _) => (arms.len(), otherwise),
+            _ => unreachable!(),
+        };
+        let Some(otherwise) = otherwise else {
+            return;
+        };
+        if num_arms != 1 {
+            return;
+        }
+        // `let ... else` requires the `else` branch to diverge; otherwise this can only come from
+        // an `if let` (or from a `match` with a single non-wildcard arm, which we can't tell
+        // apart).
+        let kind = if Self::diverges(otherwise) {
+            MatchKind::LetElse
+        } else {
+            MatchKind::IfLet
+        };
+        take_mut::take(switch, |switch| {
+            let (p, arms, otherwise) = Self::into_pattern_arms(switch).unwrap();
+            Switch::NestedMatch(p, arms, otherwise, kind)
+        });
+    }
+
+    /// Recognize the shape produced by `while let`: a loop whose body ends with an `if let`
+    /// which breaks out of the loop in its `otherwise` branch.
+    fn annotate_while_let(st: &mut Statement) {
+        let RawStatement::Loop(body) = &mut st.content else {
+            return;
+        };
+        let mut statements = body
+            .statements
+            .iter_mut()
+            .rev()
+            .filter(|st| !st.content.is_nop());
+        let Some(last) = statements.next() else {
+            return;
+        };
+        // The statements which evaluate the scrutinee must not have control-flow.
+        if statements.any(|st| st.content.is_switch() || st.content.is_loop()) {
+            return;
+        }
+        if let RawStatement::Switch(Switch::NestedMatch(
+            _,
+            arms,
+            Some(otherwise),
+            kind @ (MatchKind::IfLet | MatchKind::LetElse),
+        )) = &mut last.content
+            && arms.len() == 1
+            && Self::only_breaks(otherwise)
+        {
+            *kind = MatchKind::WhileLet;
+        }
+    }
+
+    /// Whether this block only drops values and breaks out of the current loop.
+    fn only_breaks(block: &Block) -> bool {
+        let mut statements = block
+            .statements
+            .iter()
+            .rev()
+            .filter(|st| !st.content.is_nop());
+        matches!(
+            statements.next(),
+            Some(Statement {
+                content: RawStatement::Break(0),
+                ..
+            })
+        ) && statements.all(|st| st.content.is_drop())
+    }
+
+    fn update_statement(st: &mut Statement) {
+        Self::merge_nested_matches(st);
+        Self::annotate_if_let(st);
+        Self::annotate_while_let(st);
+    }
+}
+
+impl LlbcPass for Transform {
+    fn transform_body(&self, ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
+        if !ctx.options.reconstruct_matches {
+            return;
+        }
+        // This is bottom-up, hence the inner matches get merged first.
+        b.body.visit_statements(&mut Transform::update_statement);
+    }
+}
@@ -0,0 +1,218 @@
+//! # Micro-pass: collapse the desugaring of the `?` operator.
+//!
+//! Rustc desugars `x?` to a call to `Try::branch` followed by a match on the resulting
+//! `ControlFlow`:
+//! ```text
+//! @3 := core::ops::try_trait::Try<...>::branch(move (@4))
+//! match @3 {
+//!   ControlFlow::Continue => {
+//!     x@5 := move ((@3 as variant @0).0)
+//!     ...
+//!   },
+//!   ControlFlow::Break => {
+//!     @6 := move ((@3 as variant @1).0)
+//!     @0 := core::ops::try_trait::FromResidual<...>::from_residual(move (@6))
+//!     return
+//!   },
+//! }
+//! ```
+//! This pass recognizes this shape and replaces it with a [RawStatement::TryPropagate]:
+//! ```text
+//! x@5 := move (@4)?
+//! ...
+//! ```
+//! This pass is opt-in (see the `--reconstruct-try` option).
+use derive_visitor::{visitor_fn_mut, DriveMut, Event};
+
+use crate::llbc_ast::*;
+use crate::name_matcher::NamePattern;
+use crate::transform::TransformCtx;
+
+use super::ctx::LlbcPass;
+
+struct Matcher<'a> {
+    krate: &'a TranslatedCrate,
+    try_trait: NamePattern,
+    from_residual_trait: NamePattern,
+}
+
+impl Matcher<'_> {
+    /// If the function is the given method of a trait which matches the pattern, return it.
+    fn trait_method(
+        &self,
+        func: &FnOperand,
+        trait_pat: &NamePattern,
+        method: &str,
+    ) -> Option<FnPtr> {
+        let FnOperand::Regular(
+            fn_ptr @ FnPtr {
+                func: FunIdOrTraitMethodRef::Trait(trait_ref, method_name, _),
+                ..
+            },
+        ) = func
+        else {
+            return None;
+        };
+        let trait_id = trait_ref.trait_decl_ref.skip_binder.trait_id;
+        (method_name.0 == method && trait_pat.matches_item(self.krate, trait_id))
+            .then(|| fn_ptr.clone())
+    }
+
+    /// Whether the operand moves or copies `(scrutinee as variant).0`, where `variant` has the
+    /// given name.
+    fn is_variant_field(&self, op: &Operand, scrutinee: &Place, variant: &str) -> bool {
+        let (Operand::Move(p) | Operand::Copy(p)) = op else {
+            return false;
+        };
+        let [ProjectionElem::Field(FieldProjKind::Adt(type_id, Some(variant_id)), field_id)] =
+            p.projection.as_slice()
+        else {
+            return false;
+        };
+        p.var_id == scrutinee.var_id
+            && scrutinee.projection.is_empty()
+            && field_id.index() == 0
+            && self
+                .krate
+                .type_decls
+                .get(*type_id)
+                .and_then(|decl| decl.kind.as_enum())
+                .and_then(|variants| variants.get(*variant_id))
+                .is_some_and(|v| v.name == variant)
+    }
+
+    /// Recognize the `Continue` branch: it starts by moving the output value out of the
+    /// `ControlFlow`. Returns the destination of the output value and the rest of the branch.
+    fn continue_branch(&self, scrutinee: &Place, block: &Block) -> Option<(Place, Vec<Statement>)> {
+        let mut statements = block.statements.iter().skip_while(|st| st.content.is_nop());
+        let RawStatement::Assign(dest, Rvalue::Use(op)) = &statements.next()?.content else {
+            return None;
+        };
+        self.is_variant_field(op, scrutinee, "Continue")
+            .then(|| (dest.clone(), statements.cloned().collect()))
+    }
+
+    /// Recognize the `Break` branch: it moves the residual out of the `ControlFlow`, converts it
+    /// with `from_residual` into the return value, drops some places and returns. Returns the
+    /// `from_residual` function and the dropped places.
+    fn break_branch(&self, scrutinee: &Place, block: &Block) -> Option<(FnPtr, Vec<Place>)> {
+        let mut statements = block.statements.iter().filter(|st| !st.content.is_nop());
+        let RawStatement::Assign(residual, Rvalue::Use(op)) = &statements.next()?.content else {
+            return None;
+        };
+        if !self.is_variant_field(op, scrutinee, "Break") || !residual.projection.is_empty() {
+            return None;
+        }
+        let RawStatement::Call(call) = &statements.next()?.content else {
+            return None;
+        };
+        let from_residual =
+            self.trait_method(&call.func, &self.from_residual_trait, "from_residual")?;
+        let [Operand::Move(arg)] = call.args.as_slice() else {
+            return None;
+        };
+        if arg != residual || call.dest != Place::new(VarId::ZERO) {
+            return None;
+        }
+        let mut drops = Vec::new();
+        loop {
+            match &statements.next()?.content {
+                RawStatement::Drop(p) => drops.push(p.clone()),
+                RawStatement::Return => break,
+                _ => return None,
+            }
+        }
+        if statements.next().is_some() {
+            return None;
+        }
+        Some((from_residual, drops))
+    }
+
+    /// If the statements starting at `i` are the desugaring of `?`, return the index of the
+    /// match, the `TryPropagate` statement and the statements of the `Continue` branch.
+    fn try_propagate(
+        &self,
+        statements: &[Statement],
+        i: usize,
+    ) -> Option<(usize, Statement, Vec<Statement>)> {
+        let RawStatement::Call(call) = &statements[i].content else {
+            return None;
+        };
+        let branch = self.trait_method(&call.func, &self.try_trait, "branch")?;
+        let [op] = call.args.as_slice() else {
+            return None;
+        };
+        let scrutinee = &call.dest;
+        let (j, st) = statements
+            .iter()
+            .enumerate()
+            .skip(i + 1)
+            .find(|(_, st)| !st.content.is_nop())?;
+        let RawStatement::Switch(Switch::Match(p, arms, None)) = &st.content else {
+            return None;
+        };
+        if p != scrutinee {
+            return None;
+        }
+        let [(_, first), (_, second)] = arms.as_slice() else {
+            return None;
+        };
+        let ((dest, rest), (from_residual, residual_drops)) =
+            if let Some(cont) = self.continue_branch(scrutinee, first) {
+                (cont, self.break_branch(scrutinee, second)?)
+            } else {
+                (
+                    self.continue_branch(scrutinee, second)?,
+                    self.break_branch(scrutinee, first)?,
+                )
+            };
+        let try_st = Statement::new(
+            statements[i].span,
+            RawStatement::TryPropagate(TryPropagate {
+                op: op.clone(),
+                branch,
+                from_residual,
+                residual_drops,
+                dest,
+            }),
+        );
+        Some((j, try_st, rest))
+    }
+
+    fn transform_block(&self, block: &mut Block) {
+        let mut i = 0;
+        while i < block.statements.len() {
+            if let Some((j, try_st, rest)) = self.try_propagate(&block.statements, i) {
+                let rest_len = rest.len();
+                block
+                    .statements
+                    .splice(i..=j, [try_st].into_iter().chain(rest));
+                i += 1 + rest_len;
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+pub struct Transform;
+impl LlbcPass for Transform {
+    fn transform_body(&self, ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
+        if !ctx.options.reconstruct_try {
+            return;
+        }
+        let matcher = Matcher {
+            krate: &ctx.translated,
+            try_trait: NamePattern::parse("core::ops::try_trait::Try").unwrap(),
+            from_residual_trait: NamePattern::parse("core::ops::try_trait::FromResidual").unwrap(),
+        };
+        // We transform the inner blocks first, so that the nested `?`s in the `Continue` branches
+        // are handled before we move them.
+        b.body
+            .drive_mut(&mut visitor_fn_mut(|block: &mut Block, e: Event| {
+                if matches!(e, Event::Exit) {
+                    matcher.transform_block(block)
+                }
+            }));
+    }
+}
@@ -1,4 +1,5 @@
-//! Take all the comments found in the original body and assign them to statements.
+//! Take all the comments found in the original body and assign them to statements, and parse the
+//! annotations they contain (see `--comment-annotations`).
 
 use derive_visitor::{visitor_enter_fn_mut, DriveMut};
 
@@ -9,21 +10,41 @@ use super::ctx::LlbcPass;
 
 pub struct Transform;
 impl LlbcPass for Transform {
-    fn transform_body(&self, _ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
+    fn transform_body(&self, ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
         // Constraints in the ideal case:
         // - each comment should be assigned to exactly one statement;
         // - the order of comments in the source should refine the partial order of control flow;
         // - a comment should come before the statement it was applied to.
 
-        // This is a pretty simple heuristic which is good enough for now.
+        // This is a pretty simple heuristic which is good enough for now: we give each comment to
+        // the first statement (in control-flow order) which starts on the line of the comment or
+        // after. We compare the lines of statements of the body only: the statements whose span
+        // is in another file (e.g. because they were inlined from another function) or outside
+        // the body (e.g. because they come from a macro) can't tell where the comments go.
+        let body_span = b.span.span;
+        let in_body = |span: &RawSpan| {
+            span.file_id == body_span.file_id
+                && body_span.beg.line <= span.beg.line
+                && span.beg.line <= body_span.end.line
+        };
         let mut comments: Vec<(usize, Vec<String>)> = b.comments.clone();
+        let comment_annotations = ctx.options.comment_annotations;
         b.body
             .drive_mut(&mut visitor_enter_fn_mut(|st: &mut Statement| {
+                if !in_body(&st.span.span) {
+                    return;
+                }
                 let st_line = st.span.span.beg.line;
                 st.comments_before = comments
                     .extract_if(|(i, _)| *i <= st_line)
                     .flat_map(|(_, comments)| comments)
                     .collect();
+                // With `--comment-annotations`, parse the annotations in the comments we just
+                // attached.
+                if comment_annotations {
+                    st.annotations =
+                        Annotation::parse_all(st.comments_before.iter().map(String::as_str));
+                }
             }));
     }
 }
@@ -30,6 +30,7 @@ impl Transform {
                         op @ (BinOp::CheckedAdd | BinOp::CheckedSub | BinOp::CheckedMul),
                         _,
                         _,
+                        _,
                     ),
                 ),
             ..
@@ -38,6 +39,7 @@ impl Transform {
                 RawStatement::Assert(Assert {
                     cond: Operand::Move(assert_cond),
                     expected: false,
+                    ..
                 }),
             ..
         }, Statement {
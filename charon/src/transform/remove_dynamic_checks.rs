@@ -26,7 +26,7 @@ fn remove_dynamic_checks(_ctx: &mut TransformCtx, statements: &mut [Statement])
             content:
                 RawStatement::Assign(
                     is_in_bounds,
-                    Rvalue::BinaryOp(BinOp::Lt, _, Operand::Copy(lt_op2)),
+                    Rvalue::BinaryOp(BinOp::Lt, _, Operand::Copy(lt_op2), _),
                 ),
             ..
         }, Statement {
@@ -34,6 +34,7 @@ fn remove_dynamic_checks(_ctx: &mut TransformCtx, statements: &mut [Statement])
                 RawStatement::Assert(Assert {
                     cond: Operand::Move(cond),
                     expected,
+                    ..
                 }),
             ..
         }, rest @ ..]
@@ -47,13 +48,14 @@ fn remove_dynamic_checks(_ctx: &mut TransformCtx, statements: &mut [Statement])
         //   assert(move b == false)
         [Statement {
             content:
-                RawStatement::Assign(is_zero, Rvalue::BinaryOp(BinOp::Eq, _, Operand::Const(_zero))),
+                RawStatement::Assign(is_zero, Rvalue::BinaryOp(BinOp::Eq, _, Operand::Const(_zero), _)),
             ..
         }, Statement {
             content:
                 RawStatement::Assert(Assert {
                     cond: Operand::Move(cond),
                     expected,
+                    ..
                 }),
             ..
         }, rest @ ..]
@@ -68,16 +70,21 @@ fn remove_dynamic_checks(_ctx: &mut TransformCtx, statements: &mut [Statement])
         //   has_overflow := move (is_neg_1) & move (is_min)
         //   assert(move has_overflow == false)
         [Statement {
-            content: RawStatement::Assign(is_neg_1, Rvalue::BinaryOp(BinOp::Eq, _y_op, _minus_1)),
+            content: RawStatement::Assign(is_neg_1, Rvalue::BinaryOp(BinOp::Eq, _y_op, _minus_1, _)),
             ..
         }, Statement {
-            content: RawStatement::Assign(is_min, Rvalue::BinaryOp(BinOp::Eq, _x_op, _int_min)),
+            content: RawStatement::Assign(is_min, Rvalue::BinaryOp(BinOp::Eq, _x_op, _int_min, _)),
             ..
         }, Statement {
             content:
                 RawStatement::Assign(
                     has_overflow,
-                    Rvalue::BinaryOp(BinOp::BitAnd, Operand::Move(and_op1), Operand::Move(and_op2)),
+                    Rvalue::BinaryOp(
+                        BinOp::BitAnd,
+                        Operand::Move(and_op1),
+                        Operand::Move(and_op2),
+                        _,
+                    ),
                 ),
             ..
         }, Statement {
@@ -85,6 +92,7 @@ fn remove_dynamic_checks(_ctx: &mut TransformCtx, statements: &mut [Statement])
                 RawStatement::Assert(Assert {
                     cond: Operand::Move(cond),
                     expected,
+                    ..
                 }),
             ..
         }, rest @ ..]
@@ -107,7 +115,7 @@ fn remove_dynamic_checks(_ctx: &mut TransformCtx, statements: &mut [Statement])
             content:
                 RawStatement::Assign(
                     has_overflow,
-                    Rvalue::BinaryOp(BinOp::Lt, Operand::Move(lt_op2), Operand::Const(..)),
+                    Rvalue::BinaryOp(BinOp::Lt, Operand::Move(lt_op2), Operand::Const(..), _),
                 ),
             ..
         }, Statement {
@@ -115,6 +123,7 @@ fn remove_dynamic_checks(_ctx: &mut TransformCtx, statements: &mut [Statement])
                 RawStatement::Assert(Assert {
                     cond: Operand::Move(cond),
                     expected,
+                    ..
                 }),
             ..
         }, rest @ ..]
@@ -129,7 +138,7 @@ fn remove_dynamic_checks(_ctx: &mut TransformCtx, statements: &mut [Statement])
             content:
                 RawStatement::Assign(
                     has_overflow,
-                    Rvalue::BinaryOp(BinOp::Lt, Operand::Const(..), Operand::Const(..)),
+                    Rvalue::BinaryOp(BinOp::Lt, Operand::Const(..), Operand::Const(..), _),
                 ),
             ..
         }, Statement {
@@ -137,6 +146,7 @@ fn remove_dynamic_checks(_ctx: &mut TransformCtx, statements: &mut [Statement])
                 RawStatement::Assert(Assert {
                     cond: Operand::Move(cond),
                     expected,
+                    ..
                 }),
             ..
         }, rest @ ..]
@@ -162,6 +172,7 @@ fn remove_dynamic_checks(_ctx: &mut TransformCtx, statements: &mut [Statement])
                 RawStatement::Assert(Assert {
                     cond: Operand::Move(cond),
                     expected,
+                    ..
                 }),
             ..
         }, ..]
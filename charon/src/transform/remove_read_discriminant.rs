@@ -1,15 +1,18 @@
-//! The MIR code often contains variables with type `Never`, and we want to get
-//! rid of those. We proceed in two steps. First, we remove the instructions
-//! `drop(v)` where `v` has type `Never` (it can happen - this module does the
-//! filtering). Then, we filter the unused variables ([crate::remove_unused_locals]).
+//! The MIR code reads the discriminant of an enum with `_x = discriminant(_y)`, then switches on
+//! `_x` with a `SwitchInt`. We merge the two into a `match _y { .. }` on the variants. A
+//! discriminant read which is not directly followed by a switch on its value is replaced with a
+//! match which assigns the value of the discriminant.
+//!
+//! With `--preserve-discriminant-reads`, we keep the `Rvalue::Discriminant` reads whose value is
+//! used otherwise than by the switch (e.g. stored, compared or hashed). The switches on them are
+//! still turned into matches.
 
 use crate::errors::register_error_or_panic;
 use crate::formatter::IntoFormatter;
 use crate::llbc_ast::*;
 use crate::pretty::FmtWithCtx;
 use crate::transform::TransformCtx;
-use derive_visitor::visitor_enter_fn_mut;
-use derive_visitor::DriveMut;
+use derive_visitor::{visitor_enter_fn, visitor_enter_fn_mut, Drive, DriveMut};
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
 
@@ -17,7 +20,12 @@ use super::ctx::LlbcPass;
 
 pub struct Transform;
 impl Transform {
-    fn update_block(ctx: &mut TransformCtx<'_>, block: &mut Block) {
+    fn update_block(
+        ctx: &mut TransformCtx<'_>,
+        place_uses: &HashMap<VarId, usize>,
+        block: &mut Block,
+    ) {
+        let preserve = ctx.options.preserve_discriminant_reads;
         // Iterate through the statements.
         for i in 0..block.statements.len() {
             let suffix = &mut block.statements[i..];
@@ -76,13 +84,17 @@ impl Transform {
                     return;
                 };
 
-                // We look for a `SwitchInt` just after the discriminant read.
+                // We look for a `SwitchInt` on the discriminant just after the discriminant read.
                 match rest {
                     [Statement {
                         content:
-                            RawStatement::Switch(switch @ Switch::SwitchInt(Operand::Move(_), ..)),
+                            RawStatement::Switch(
+                                switch @ Switch::SwitchInt(Operand::Move(switch_p), ..),
+                            ),
                         ..
-                    }, ..] => {
+                    }, ..]
+                        if switch_p.projection.is_empty() && switch_p.var_id == dest.var_id =>
+                    {
                         // Convert between discriminants and variant indices. Remark: the discriminant can
                         // be of any *signed* integer type (`isize`, `i8`, etc.).
                         let discr_to_id: HashMap<ScalarValue, VariantId> = variants
@@ -129,8 +141,15 @@ impl Transform {
                             // Replace the old switch with a match.
                             Switch::Match(p.clone(), targets, otherwise)
                         });
-                        // `Nop` the discriminant read.
-                        block.statements[i].content = RawStatement::Nop;
+                        // `Nop` the discriminant read, unless its value is used elsewhere and we
+                        // want to preserve it. The read and the switch account for two uses.
+                        let used_elsewhere = place_uses.get(&dest.var_id).copied().unwrap_or(0) > 2;
+                        if !(preserve && used_elsewhere) {
+                            block.statements[i].content = RawStatement::Nop;
+                        }
+                    }
+                    _ if preserve => {
+                        // Keep the discriminant read as is.
                     }
                     _ => {
                         // The discriminant read is not followed by a `SwitchInt`. This can happen
@@ -159,9 +178,14 @@ impl Transform {
 
 impl LlbcPass for Transform {
     fn transform_body(&self, ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
+        // Count the occurrences of each local in the places of the body.
+        let mut place_uses: HashMap<VarId, usize> = HashMap::new();
+        b.body.drive(&mut visitor_enter_fn(|p: &Place| {
+            *place_uses.entry(p.var_id).or_default() += 1;
+        }));
         b.body
             .drive_mut(&mut visitor_enter_fn_mut(|block: &mut Block| {
-                Transform::update_block(ctx, block);
+                Transform::update_block(ctx, &place_uses, block);
             }));
     }
 }
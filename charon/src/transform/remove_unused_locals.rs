@@ -2,6 +2,7 @@
 //! never used in the function bodies.  This is useful to remove the locals with
 //! type `Never`. We actually check that there are no such local variables
 //! remaining afterwards.
+use crate::gast::DebugValue;
 use crate::llbc_ast::ExprBody;
 use crate::transform::TransformCtx;
 use crate::ullbc_ast::Var;
@@ -40,5 +41,15 @@ impl LlbcPass for Transform {
             .drive_mut(&mut visitor_enter_fn_mut(|vid: &mut VarId| {
                 *vid = *vids_map.get(vid).unwrap();
             }));
+        // Same in the debug info, where we drop the user variables whose local was removed.
+        if let Some(debug_info) = &mut b.debug_info {
+            debug_info.vars.retain(|var| match var.value {
+                DebugValue::Place(vid, _) => vids_map.contains_key(&vid),
+                DebugValue::Const => true,
+            });
+            debug_info.drive_mut(&mut visitor_enter_fn_mut(|vid: &mut VarId| {
+                *vid = *vids_map.get(vid).unwrap();
+            }));
+        }
     }
 }
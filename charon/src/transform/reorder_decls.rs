@@ -1,7 +1,7 @@
 use crate::common::*;
 use crate::formatter::{AstFormatter, IntoFormatter};
 use crate::graphs::*;
-use crate::transform::TransformCtx;
+use crate::pretty::FmtWithCtx;
 use crate::ullbc_ast::*;
 use derive_visitor::{Drive, Visitor};
 use hashlink::{LinkedHashMap, LinkedHashSet};
@@ -9,6 +9,7 @@ use macros::{EnumAsGetters, EnumIsA, VariantIndexArity, VariantName};
 use petgraph::algo::tarjan_scc;
 use petgraph::graphmap::DiGraphMap;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::{Debug, Display, Error};
 use std::vec::Vec;
 
@@ -45,7 +46,22 @@ pub enum DeclarationGroup {
     Mixed(GDeclarationGroup<AnyTransId>),
 }
 
+/// Whether a group of declarations is recursive, see [DeclarationGroup::kind].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GroupKind {
+    /// A single declaration which doesn't refer to itself.
+    NonRec,
+    /// One or several mutually recursive declarations.
+    Rec,
+}
+
 impl<Id: Copy> GDeclarationGroup<Id> {
+    pub fn kind(&self) -> GroupKind {
+        match self {
+            GDeclarationGroup::NonRec(_) => GroupKind::NonRec,
+            GDeclarationGroup::Rec(_) => GroupKind::Rec,
+        }
+    }
     pub fn get_ids(&self) -> &[Id] {
         use GDeclarationGroup::*;
         match self {
@@ -75,6 +91,27 @@ impl<Id: Copy> GDeclarationGroup<Id> {
 }
 
 impl DeclarationGroup {
+    /// Whether the declarations of this group are (mutually) recursive.
+    pub fn is_rec(&self) -> bool {
+        use DeclarationGroup::*;
+        match self {
+            Type(gr) => gr.is_rec(),
+            Fun(gr) => gr.is_rec(),
+            Global(gr) => gr.is_rec(),
+            TraitDecl(gr) => gr.is_rec(),
+            TraitImpl(gr) => gr.is_rec(),
+            Mixed(gr) => gr.is_rec(),
+        }
+    }
+
+    pub fn kind(&self) -> GroupKind {
+        if self.is_rec() {
+            GroupKind::Rec
+        } else {
+            GroupKind::NonRec
+        }
+    }
+
     pub fn get_ids(&self) -> Vec<AnyTransId> {
         use DeclarationGroup::*;
         match self {
@@ -88,6 +125,139 @@ impl DeclarationGroup {
     }
 }
 
+/// The kind of a dependency between two declarations, given by the kind of the declaration which is
+/// depended upon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[charon::variants_prefix("Dep")]
+pub enum DependencyKind {
+    /// A call to (or a reference to) a function.
+    Call,
+    /// A use of a type.
+    TypeUse,
+    /// A reference to a trait declaration or to a trait impl.
+    TraitRef,
+    /// A use of a global.
+    GlobalUse,
+}
+
+/// An edge of the dependency graph of the declarations: `src` depends on `dst`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dependency {
+    pub src: AnyTransId,
+    pub dst: AnyTransId,
+    pub kind: DependencyKind,
+}
+
+impl DependencyKind {
+    fn of_dependee(id: AnyTransId) -> Self {
+        match id {
+            AnyTransId::Fun(_) => DependencyKind::Call,
+            AnyTransId::Type(_) => DependencyKind::TypeUse,
+            AnyTransId::TraitDecl(_) | AnyTransId::TraitImpl(_) => DependencyKind::TraitRef,
+            AnyTransId::Global(_) => DependencyKind::GlobalUse,
+        }
+    }
+}
+
+impl Display for DependencyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), Error> {
+        let kind = match self {
+            DependencyKind::Call => "call",
+            DependencyKind::TypeUse => "type use",
+            DependencyKind::TraitRef => "trait reference",
+            DependencyKind::GlobalUse => "global use",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+/// A cycle of dependencies in a group of mutually recursive declarations (see `--report-cycles`).
+#[derive(Debug, Clone)]
+pub struct Cycle {
+    /// The edges of the cycle: each edge starts where the previous one ends, and the last edge
+    /// ends where the first one starts.
+    pub edges: Vec<Dependency>,
+    /// Whether the group mixes declarations of different kinds, e.g. a type and a trait impl.
+    pub is_mixed: bool,
+}
+
+impl Cycle {
+    /// A readable description of the cycle, which names its edges.
+    pub fn report(&self, krate: &TranslatedCrate) -> String {
+        let fmt_ctx = krate.into_fmt();
+        let name = |id: AnyTransId| match krate.item_name(id) {
+            Some(name) => name.fmt_with_ctx(&fmt_ctx),
+            None => format!("{id:?}"),
+        };
+        let header = if self.is_mixed {
+            "Found a group of mutually recursive declarations of different kinds, with the cycle:"
+        } else {
+            "Found a group of mutually recursive declarations, with the cycle:"
+        };
+        let edges = self
+            .edges
+            .iter()
+            .map(|dep| {
+                format!(
+                    "\n  `{}` -> `{}` ({})",
+                    name(dep.src),
+                    name(dep.dst),
+                    dep.kind
+                )
+            })
+            .collect::<String>();
+        format!("{header}{edges}")
+    }
+}
+
+/// Find a cycle in each recursive group of [TranslatedCrate::ordered_decls], following the edges
+/// of [TranslatedCrate::dependency_graph]. We report the shortest cycle which goes through the
+/// first declaration of the group.
+pub fn find_cycles(krate: &TranslatedCrate) -> Vec<Cycle> {
+    let (Some(groups), Some(deps)) = (&krate.ordered_decls, &krate.dependency_graph) else {
+        return Vec::new();
+    };
+    let mut successors: HashMap<AnyTransId, Vec<&Dependency>> = HashMap::new();
+    for dep in deps {
+        successors.entry(dep.src).or_default().push(dep);
+    }
+    groups
+        .iter()
+        .filter(|group| group.is_rec())
+        .filter_map(|group| {
+            let ids = group.get_ids();
+            let members: HashSet<AnyTransId> = ids.iter().copied().collect();
+            let start = *ids.first()?;
+            // Breadth-first search of the shortest path from `start` back to itself.
+            let mut preds: HashMap<AnyTransId, &Dependency> = HashMap::new();
+            let mut queue = VecDeque::from([start]);
+            let mut last_edge = None;
+            'search: while let Some(id) = queue.pop_front() {
+                for dep in successors.get(&id).into_iter().flatten() {
+                    if dep.dst == start {
+                        last_edge = Some(*dep);
+                        break 'search;
+                    }
+                    if members.contains(&dep.dst) && !preds.contains_key(&dep.dst) {
+                        preds.insert(dep.dst, *dep);
+                        queue.push_back(dep.dst);
+                    }
+                }
+            }
+            let mut edges = vec![last_edge?.clone()];
+            while edges.last().unwrap().src != start {
+                let pred = preds[&edges.last().unwrap().src];
+                edges.push(pred.clone());
+            }
+            edges.reverse();
+            Some(Cycle {
+                edges,
+                is_mixed: group.is_mixed(),
+            })
+        })
+        .collect()
+}
+
 #[derive(Clone, Copy)]
 pub struct DeclInfo {
     pub is_transparent: bool,
@@ -129,8 +299,8 @@ impl Display for DeclarationGroup {
     BodyId(enter),
     Ty(enter)
 )]
-pub struct Deps<'tcx, 'ctx> {
-    ctx: &'tcx TransformCtx<'ctx>,
+pub struct Deps<'a> {
+    krate: &'a TranslatedCrate,
     dgraph: DiGraphMap<AnyTransId, ()>,
     // Want to make sure we remember the order of insertion
     graph: LinkedHashMap<AnyTransId, LinkedHashSet<AnyTransId>>,
@@ -180,10 +350,10 @@ pub struct Deps<'tcx, 'ctx> {
     impl_trait_id: Option<TraitImplId>,
 }
 
-impl<'tcx, 'ctx> Deps<'tcx, 'ctx> {
-    fn new(ctx: &'tcx TransformCtx<'ctx>) -> Self {
+impl<'a> Deps<'a> {
+    fn new(krate: &'a TranslatedCrate) -> Self {
         Deps {
-            ctx,
+            krate,
             dgraph: DiGraphMap::new(),
             graph: LinkedHashMap::new(),
             current_id: None,
@@ -191,7 +361,7 @@ impl<'tcx, 'ctx> Deps<'tcx, 'ctx> {
         }
     }
 
-    fn set_current_id(&mut self, ctx: &TransformCtx, id: AnyTransId) {
+    fn set_current_id(&mut self, krate: &TranslatedCrate, id: AnyTransId) {
         self.insert_node(id);
         self.current_id = Some(id);
 
@@ -206,7 +376,7 @@ impl<'tcx, 'ctx> Deps<'tcx, 'ctx> {
                 // Lookup the function declaration.
                 //
                 // The declaration may not be present if we encountered errors.
-                if let Some(decl) = ctx.translated.fun_decls.get(id) {
+                if let Some(decl) = krate.fun_decls.get(id) {
                     if let ItemKind::TraitImpl { impl_id, .. } = &decl.kind {
                         // Register the trait decl id
                         self.impl_trait_id = Some(*impl_id)
@@ -240,7 +410,7 @@ impl<'tcx, 'ctx> Deps<'tcx, 'ctx> {
     }
 }
 
-impl Deps<'_, '_> {
+impl Deps<'_> {
     fn enter_type_decl_id(&mut self, id: &TypeDeclId) {
         let id = AnyTransId::Type(*id);
         self.insert_edge(id);
@@ -277,7 +447,7 @@ impl Deps<'_, '_> {
     }
 
     fn enter_body_id(&mut self, id: &BodyId) {
-        if let Some(body) = self.ctx.translated.bodies.get(*id) {
+        if let Some(body) = self.krate.bodies.get(*id) {
             body.drive(self);
         }
     }
@@ -289,9 +459,9 @@ impl Deps<'_, '_> {
 }
 
 impl AnyTransId {
-    fn fmt_with_ctx(&self, ctx: &TransformCtx) -> String {
+    fn fmt_with_ctx(&self, krate: &TranslatedCrate) -> String {
         use AnyTransId::*;
-        let ctx = ctx.into_fmt();
+        let ctx = krate.into_fmt();
         match self {
             Type(id) => ctx.format_object(*id),
             Fun(id) => ctx.format_object(*id),
@@ -302,29 +472,29 @@ impl AnyTransId {
     }
 }
 
-impl Deps<'_, '_> {
-    fn fmt_with_ctx(&self, ctx: &TransformCtx) -> String {
+impl Deps<'_> {
+    fn fmt_with_ctx(&self, krate: &TranslatedCrate) -> String {
         self.dgraph
             .nodes()
             .map(|node| {
                 let edges = self
                     .dgraph
                     .edges(node)
-                    .map(|e| format!("\n  {}", e.1.fmt_with_ctx(ctx)))
+                    .map(|e| format!("\n  {}", e.1.fmt_with_ctx(krate)))
                     .collect::<Vec<String>>()
                     .join(",");
 
-                format!("{} -> [{}\n]", node.fmt_with_ctx(ctx), edges)
+                format!("{} -> [{}\n]", node.fmt_with_ctx(krate), edges)
             })
             .collect::<Vec<String>>()
             .join(",\n")
     }
 }
 
-fn compute_declarations_graph<'tcx, 'ctx>(ctx: &'tcx TransformCtx<'ctx>) -> Deps<'tcx, 'ctx> {
-    let mut graph = Deps::new(ctx);
-    for (id, item) in ctx.translated.all_items_with_ids() {
-        graph.set_current_id(ctx, id);
+fn compute_declarations_graph(krate: &TranslatedCrate) -> Deps<'_> {
+    let mut graph = Deps::new(krate);
+    for (id, item) in krate.all_items_with_ids() {
+        graph.set_current_id(krate, id);
         match item {
             AnyTransItem::Type(d) => {
                 d.drive(&mut graph);
@@ -353,6 +523,8 @@ fn compute_declarations_graph<'tcx, 'ctx>(ctx: &'tcx TransformCtx<'ctx>) -> Deps
                     type_clauses,
                     required_methods,
                     provided_methods,
+                    vtable_slots: _,
+                    dyn_compatibility_violations: _,
                 } = d;
                 // Visit the traits referenced in the generics
                 generics.drive(&mut graph);
@@ -384,7 +556,7 @@ fn compute_declarations_graph<'tcx, 'ctx>(ctx: &'tcx TransformCtx<'ctx>) -> Deps
                     //   fn f(x : Trait::X);
                     // }
                     // ```
-                    if let Some(decl) = ctx.translated.fun_decls.get(id) {
+                    if let Some(decl) = krate.fun_decls.get(id) {
                         decl.signature.drive(&mut graph);
                     }
                 }
@@ -402,6 +574,8 @@ fn compute_declarations_graph<'tcx, 'ctx>(ctx: &'tcx TransformCtx<'ctx>) -> Deps
                     type_clauses,
                     required_methods,
                     provided_methods,
+                    // These are the same trait refs as the parent trait refs and type clauses.
+                    obligations: _,
                 } = d;
                 impl_trait.drive(&mut graph);
                 generics.drive(&mut graph);
@@ -419,8 +593,8 @@ fn compute_declarations_graph<'tcx, 'ctx>(ctx: &'tcx TransformCtx<'ctx>) -> Deps
 }
 
 fn group_declarations_from_scc(
-    _ctx: &TransformCtx,
-    graph: Deps<'_, '_>,
+    _krate: &TranslatedCrate,
+    graph: Deps<'_>,
     reordered_sccs: SCCs<AnyTransId>,
 ) -> DeclarationsGroups {
     let reordered_sccs = &reordered_sccs.sccs;
@@ -484,12 +658,12 @@ fn group_declarations_from_scc(
     reordered_decls
 }
 
-pub fn compute_reordered_decls(ctx: &TransformCtx) -> DeclarationsGroups {
+pub fn compute_reordered_decls(krate: &TranslatedCrate) -> DeclarationsGroups {
     trace!();
 
     // Step 1: explore the declarations to build the graph
-    let graph = compute_declarations_graph(ctx);
-    trace!("Graph:\n{}\n", graph.fmt_with_ctx(ctx));
+    let graph = compute_declarations_graph(krate);
+    trace!("Graph:\n{}\n", graph.fmt_with_ctx(krate));
 
     // Step 2: Apply Tarjan's SCC (Strongly Connected Components) algorithm
     let sccs = tarjan_scc(&graph.dgraph);
@@ -506,17 +680,35 @@ pub fn compute_reordered_decls(ctx: &TransformCtx) -> DeclarationsGroups {
         .keys()
         .copied()
         // Don't list ids that weren't translated.
-        .filter(|id| ctx.translated.get_item(*id).is_some())
+        .filter(|id| krate.get_item(*id).is_some())
         .collect();
     let reordered_sccs = reorder_sccs::<AnyTransId>(get_id_dependencies, &all_ids, &sccs);
 
     // Finally, generate the list of declarations
-    let reordered_decls = group_declarations_from_scc(ctx, graph, reordered_sccs);
+    let reordered_decls = group_declarations_from_scc(krate, graph, reordered_sccs);
 
     trace!("{:?}", reordered_decls);
     reordered_decls
 }
 
+/// Compute the dependency graph of the declarations, from which [compute_reordered_decls] computes
+/// the order of the declarations. Like for the ordering, we ignore the dependencies of the methods
+/// of a trait impl to this impl, and those of the methods of a trait declaration to this trait.
+pub fn compute_dependency_graph(krate: &TranslatedCrate) -> Vec<Dependency> {
+    let graph = compute_declarations_graph(krate);
+    graph
+        .graph
+        .iter()
+        .flat_map(|(src, dsts)| {
+            dsts.iter().map(|dst| Dependency {
+                src: *src,
+                dst: *dst,
+                kind: DependencyKind::of_dependee(*dst),
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -9,7 +9,13 @@
 //! A small remark about the intermediate statements we introduce for the globals:
 //! we do so because, when evaluating the code in "concrete" mode, it allows to
 //! handle the globals like function calls.
+//!
+//! With `--simplify-constants globals`, we only introduce statements for the globals, the
+//! references to globals and the constants which contain them (e.g. a constant ADT with a global
+//! field), and keep the other constant expressions as they are. With
+//! `--simplify-constants none`, this pass does nothing.
 
+use crate::options::ConstantSimplification;
 use crate::transform::TransformCtx;
 use crate::ullbc_ast::*;
 
@@ -21,12 +27,18 @@ use super::ctx::UllbcPass;
 ///
 /// Goes fom e.g. `f(T::A(x, y))` to `let a = T::A(x, y); f(a)`.
 /// The function is recursively called on the aggregate fields (e.g. here x and y).
+///
+/// If `only_globals` is set, we leave the constants which don't contain a global untouched.
 fn transform_constant_expr<F: FnMut(Ty) -> VarId>(
     span: &Span,
     nst: &mut Vec<Statement>,
     val: ConstantExpr,
+    only_globals: bool,
     make_new_var: &mut F,
 ) -> Operand {
+    if only_globals && !contains_global(&val) {
+        return Operand::Const(val);
+    }
     match val.value {
         RawConstantExpr::Literal(_)
         | RawConstantExpr::Var(_)
@@ -66,7 +78,7 @@ fn transform_constant_expr<F: FnMut(Ty) -> VarId>(
                 _ => {
                     // Recurse on the borrowed value
                     let bval_ty = bval.ty.clone();
-                    let bval = transform_constant_expr(span, nst, bval, make_new_var);
+                    let bval = transform_constant_expr(span, nst, bval, only_globals, make_new_var);
 
                     // Introduce an intermediate statement to evaluate the referenced value
                     let bvar_id = make_new_var(bval_ty);
@@ -107,7 +119,7 @@ fn transform_constant_expr<F: FnMut(Ty) -> VarId>(
                 _ => {
                     // Recurse on the borrowed value
                     let bval_ty = bval.ty.clone();
-                    let bval = transform_constant_expr(span, nst, bval, make_new_var);
+                    let bval = transform_constant_expr(span, nst, bval, only_globals, make_new_var);
 
                     // Introduce an intermediate statement to evaluate the referenced value
                     let bvar_id = make_new_var(bval_ty);
@@ -133,7 +145,7 @@ fn transform_constant_expr<F: FnMut(Ty) -> VarId>(
             // Recurse on the fields
             let fields = fields
                 .into_iter()
-                .map(|f| transform_constant_expr(span, nst, f, make_new_var))
+                .map(|f| transform_constant_expr(span, nst, f, only_globals, make_new_var))
                 .collect();
 
             // Introduce an intermediate assignment for the aggregated ADT
@@ -148,22 +160,64 @@ fn transform_constant_expr<F: FnMut(Ty) -> VarId>(
                 RawStatement::Assign(Place::new(var_id), rval),
             ));
 
+            // Return the new operand
+            Operand::Move(Place::new(var_id))
+        }
+        RawConstantExpr::Array(elems) => {
+            // Recurse on the elements
+            let elems = elems
+                .into_iter()
+                .map(|e| transform_constant_expr(span, nst, e, only_globals, make_new_var))
+                .collect();
+
+            // Introduce an intermediate assignment for the array
+            let rval = {
+                let (_, generics) = val.ty.kind().as_adt().unwrap();
+                let aggregate_kind = AggregateKind::Array(
+                    generics.types[0].clone(),
+                    generics.const_generics[0].clone(),
+                );
+                Rvalue::Aggregate(aggregate_kind, elems)
+            };
+            let var_id = make_new_var(val.ty);
+            nst.push(Statement::new(
+                *span,
+                RawStatement::Assign(Place::new(var_id), rval),
+            ));
+
             // Return the new operand
             Operand::Move(Place::new(var_id))
         }
     }
 }
 
+/// Whether the constant is a global or contains one (e.g. a reference to a global, or an ADT
+/// with a global field).
+fn contains_global(val: &ConstantExpr) -> bool {
+    match &val.value {
+        RawConstantExpr::Global(_) => true,
+        RawConstantExpr::Ref(bval) | RawConstantExpr::MutPtr(bval) => contains_global(bval),
+        RawConstantExpr::Adt(_, fields) | RawConstantExpr::Array(fields) => {
+            fields.iter().any(contains_global)
+        }
+        RawConstantExpr::Literal(_)
+        | RawConstantExpr::TraitConst(..)
+        | RawConstantExpr::Var(_)
+        | RawConstantExpr::FnPtr(_) => false,
+    }
+}
+
 fn transform_operand<F: FnMut(Ty) -> VarId>(
     span: &Span,
     nst: &mut Vec<Statement>,
     op: &mut Operand,
+    only_globals: bool,
     f: &mut F,
 ) {
     // Transform the constant operands (otherwise do nothing)
     take_mut::take(op, |op| {
         if let Operand::Const(val) = op {
-            transform_constant_expr(span, nst, val, f)
+            transform_constant_expr(span, nst, val, only_globals, f)
         } else {
             op
         }
@@ -172,10 +226,86 @@ fn transform_operand<F: FnMut(Ty) -> VarId>(
 
 pub struct Transform;
 impl UllbcPass for Transform {
-    fn transform_body(&self, _ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
+    fn transform_body(&self, ctx: &mut TransformCtx<'_>, b: &mut ExprBody) {
+        let only_globals = match ctx.options.simplify_constants {
+            ConstantSimplification::All => false,
+            ConstantSimplification::Globals => true,
+            ConstantSimplification::None => return,
+        };
         let mut f = make_locals_generator(&mut b.locals);
         body_transform_operands(&mut b.body, &mut |span, nst, op| {
-            transform_operand(span, nst, op, &mut f)
+            transform_operand(span, nst, op, only_globals, &mut f)
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::Vector;
+
+    fn mk_const(value: RawConstantExpr, ty: Ty) -> ConstantExpr {
+        ConstantExpr {
+            value,
+            ty,
+            has_type_suffix: None,
+        }
+    }
+
+    /// In `globals` mode, a global nested in a constant ADT is turned into a statement, while the
+    /// other fields stay constants.
+    #[test]
+    fn nested_global() {
+        let u8_ty = TyKind::Literal(LiteralTy::Integer(IntegerTy::U8)).into_ty();
+        let adt_ty = TyKind::Adt(TypeId::Adt(TypeDeclId::ZERO), GenericArgs::empty()).into_ty();
+        let global = mk_const(
+            RawConstantExpr::Global(GlobalDeclRef {
+                id: GlobalDeclId::ZERO,
+                generics: GenericArgs::empty(),
+            }),
+            u8_ty.clone(),
+        );
+        let literal = mk_const(
+            RawConstantExpr::Literal(Literal::Scalar(ScalarValue::U8(0))),
+            u8_ty,
+        );
+        let adt = mk_const(
+            RawConstantExpr::Adt(None, vec![global, literal.clone()]),
+            adt_ty,
+        );
+
+        let mut locals = Vector::new();
+        let mut nst = Vec::new();
+        let op = transform_constant_expr(
+            &Span::dummy(),
+            &mut nst,
+            adt,
+            true,
+            &mut make_locals_generator(&mut locals),
+        );
+
+        // `_0 := global; _1 := Adt(move _0, const 0); move _1`
+        assert_eq!(nst.len(), 2);
+        assert!(matches!(
+            &nst[0].content,
+            RawStatement::Assign(_, Rvalue::Global(global_ref)) if global_ref.id == GlobalDeclId::ZERO
+        ));
+        let RawStatement::Assign(_, Rvalue::Aggregate(_, fields)) = &nst[1].content else {
+            panic!("expected an aggregate, found {:?}", nst[1].content)
+        };
+        assert!(matches!(fields[0], Operand::Move(_)));
+        assert_eq!(fields[1], Operand::Const(literal.clone()));
+        assert!(matches!(op, Operand::Move(_)));
+
+        // The constants without globals are left untouched.
+        let op = transform_constant_expr(
+            &Span::dummy(),
+            &mut nst,
+            literal.clone(),
+            true,
+            &mut make_locals_generator(&mut locals),
+        );
+        assert_eq!(op, Operand::Const(literal));
+        assert_eq!(nst.len(), 2);
+    }
+}
@@ -0,0 +1,115 @@
+//! # Micro-pass: simplify the trait impls generated by `#[derive(..)]`, which are recognized by
+//! their `#[automatically_derived]` attribute. Their methods make up a large part of the output
+//! and are rarely of interest:
+//! - with `--opaque-derive Debug`, the methods of the derived `Debug` impls lose their bodies;
+//! - with `--builtin-derive Clone`, the derived `Clone` impls are removed altogether, and the
+//!   trait references which used them become [TraitRefKind::BuiltinOrAuto], like the references to
+//!   the impls which rustc provides (e.g. `Clone` for tuples). The method calls refer to the
+//!   methods of the trait declaration so they stay valid.
+//!
+//! The derives are given by the name of the trait, e.g. `PartialEq`.
+use std::collections::HashSet;
+
+use derive_visitor::{visitor_enter_fn_mut, DriveMut};
+
+use crate::ast::*;
+use crate::transform::TransformCtx;
+
+use super::ctx::TransformPass;
+
+pub(crate) fn is_automatically_derived(timpl: &TraitImpl) -> bool {
+    timpl
+        .item_meta
+        .attr_info
+        .attributes
+        .iter()
+        .any(|attr| matches!(attr, Attribute::Unknown(raw) if raw.path == "automatically_derived"))
+}
+
+/// The last component of the name of the implemented trait, e.g. `Clone` for
+/// `core::clone::Clone`.
+pub(crate) fn derived_trait_name<'a>(
+    krate: &'a TranslatedCrate,
+    timpl: &TraitImpl,
+) -> Option<&'a str> {
+    match krate.item_name(timpl.impl_trait.trait_id)?.name.last()? {
+        PathElem::Ident(name, _) => Some(name),
+        PathElem::Impl(..) => None,
+    }
+}
+
+pub struct Transform;
+impl TransformPass for Transform {
+    fn transform_ctx(&self, ctx: &mut TransformCtx<'_>) {
+        if ctx.options.opaque_derives.is_empty() && ctx.options.builtin_derives.is_empty() {
+            return;
+        }
+        let mut opaque_impls = Vec::new();
+        let mut builtin_impls = HashSet::new();
+        for timpl in ctx.translated.trait_impls.iter() {
+            if !is_automatically_derived(timpl) {
+                continue;
+            }
+            let Some(name) = derived_trait_name(&ctx.translated, timpl) else {
+                continue;
+            };
+            if ctx.options.builtin_derives.iter().any(|d| d == name) {
+                builtin_impls.insert(timpl.def_id);
+            } else if ctx.options.opaque_derives.iter().any(|d| d == name) {
+                opaque_impls.push(timpl.def_id);
+            }
+        }
+
+        let krate = &mut ctx.translated;
+        for impl_id in opaque_impls {
+            let timpl = &krate.trait_impls[impl_id];
+            let methods: Vec<FunDeclId> = timpl
+                .required_methods
+                .iter()
+                .chain(&timpl.provided_methods)
+                .map(|(_, id)| *id)
+                .collect();
+            for fun_id in methods {
+                if let Some(fun) = krate.fun_decls.get_mut(fun_id)
+                    && let Ok(body_id) = fun.body
+                {
+                    fun.body = Err(Opaque);
+                    fun.item_meta.opacity = ItemOpacity::Opaque;
+                    krate.bodies.remove(body_id);
+                }
+            }
+        }
+
+        if builtin_impls.is_empty() {
+            return;
+        }
+        let mut visitor =
+            Ty::visit_inside_stateless(visitor_enter_fn_mut(|tref: &mut TraitRef| {
+                if let TraitRefKind::TraitImpl(impl_id, _) = &tref.kind
+                    && builtin_impls.contains(impl_id)
+                {
+                    tref.kind = TraitRefKind::BuiltinOrAuto(tref.trait_decl_ref.clone());
+                }
+            }));
+        krate.type_decls.drive_mut(&mut visitor);
+        krate.fun_decls.drive_mut(&mut visitor);
+        krate.global_decls.drive_mut(&mut visitor);
+        krate.trait_decls.drive_mut(&mut visitor);
+        krate.trait_impls.drive_mut(&mut visitor);
+        krate.bodies.drive_mut(&mut visitor);
+        for impl_id in builtin_impls {
+            let Some(timpl) = krate.trait_impls.remove(impl_id) else {
+                continue;
+            };
+            krate.item_names.remove(&AnyTransId::TraitImpl(impl_id));
+            for (_, fun_id) in timpl.required_methods.iter().chain(&timpl.provided_methods) {
+                if let Some(fun) = krate.fun_decls.remove(*fun_id) {
+                    krate.item_names.remove(&AnyTransId::Fun(*fun_id));
+                    if let Ok(body_id) = fun.body {
+                        krate.bodies.remove(body_id);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -21,12 +21,15 @@
 //! many nodes and edges).
 
 use crate::common::ensure_sufficient_stack;
-use crate::expressions::Place;
+use crate::expressions::{Operand, Place, Rvalue};
 use crate::formatter::{Formatter, IntoFormatter};
 use crate::gast;
 use crate::llbc_ast as tgt;
 use crate::meta::{combine_span, Span};
+use crate::pretty::FmtWithCtx;
+use crate::transform::ctx::TransformOptions;
 use crate::transform::TransformCtx;
+use crate::types::{IntegerTy, LiteralTy, TyKind};
 use crate::ullbc_ast::{self as src};
 use crate::values as v;
 use hashlink::linked_hash_map::LinkedHashMap;
@@ -51,6 +54,10 @@ struct BlockInfo<'a> {
     body: &'a src::ExprBody,
     exits_info: &'a ExitInfo,
     explored: &'a mut HashSet<src::BlockId>,
+    /// The number of statements (including terminators) we translated more than once.
+    duplicated_size: usize,
+    /// Give up as soon as `duplicated_size` exceeds this (see `--max-duplication`).
+    max_duplication: Option<usize>,
 }
 
 /// This structure contains various information about a function's CFG.
@@ -1355,28 +1362,23 @@ fn translate_child_block(
     switch_exit_blocks: &HashSet<src::BlockId>,
     parent_span: Span,
     child_id: src::BlockId,
-) -> Option<tgt::Block> {
+) -> Result<Option<tgt::Block>, ReconstructionFailure> {
     // Check if this is a backward call
     match get_goto_kind(info.exits_info, parent_loops, switch_exit_blocks, child_id) {
         GotoKind::Break(index) => {
             let st = tgt::RawStatement::Break(index);
-            Some(tgt::Statement::new(parent_span, st).into_block())
+            Ok(Some(tgt::Statement::new(parent_span, st).into_block()))
         }
         GotoKind::Continue(index) => {
             let st = tgt::RawStatement::Continue(index);
-            Some(tgt::Statement::new(parent_span, st).into_block())
+            Ok(Some(tgt::Statement::new(parent_span, st).into_block()))
         }
         // If we are going to an exit block we simply ignore the goto
-        GotoKind::ExitBlock => None,
+        GotoKind::ExitBlock => Ok(None),
         GotoKind::Goto => {
             // "Standard" goto: just recursively translate
             ensure_sufficient_stack(|| {
-                Some(translate_block(
-                    info,
-                    parent_loops,
-                    switch_exit_blocks,
-                    child_id,
-                ))
+                translate_block(info, parent_loops, switch_exit_blocks, child_id).map(Some)
             })
         }
     }
@@ -1386,9 +1388,9 @@ fn opt_block_unwrap_or_nop(span: Span, opt_block: Option<tgt::Block>) -> tgt::Bl
     opt_block.unwrap_or_else(|| tgt::Statement::new(span, tgt::RawStatement::Nop).into_block())
 }
 
-fn translate_statement(st: &src::Statement) -> Option<tgt::Statement> {
-    let src_span = st.span;
-    let st = match st.content.clone() {
+fn translate_statement(src_st: &src::Statement) -> Option<tgt::Statement> {
+    let src_span = src_st.span;
+    let st = match src_st.content.clone() {
         src::RawStatement::Assign(place, rvalue) => tgt::RawStatement::Assign(place, rvalue),
         src::RawStatement::Call(s) => tgt::RawStatement::Call(s),
         src::RawStatement::FakeRead(place) => tgt::RawStatement::FakeRead(place),
@@ -1400,11 +1402,28 @@ fn translate_statement(st: &src::Statement) -> Option<tgt::Statement> {
         // We translate a deinit as a drop
         src::RawStatement::Deinit(place) => tgt::RawStatement::Drop(place),
         src::RawStatement::Drop(place) => tgt::RawStatement::Drop(place),
+        src::RawStatement::Retag(kind, place) => tgt::RawStatement::Retag(kind, place),
         src::RawStatement::Assert(assert) => tgt::RawStatement::Assert(assert),
         src::RawStatement::Nop => tgt::RawStatement::Nop,
         src::RawStatement::Error(s) => tgt::RawStatement::Error(s),
     };
-    Some(tgt::Statement::new(src_span, st))
+    let mut tgt_st = tgt::Statement::new(src_span, st);
+    tgt_st.borrow_liveness = src_st.borrow_liveness.clone();
+    tgt_st.move_info = src_st.move_info.clone();
+    Some(tgt_st)
+}
+
+/// Create the statement a terminator is translated to. It keeps the facts computed on the
+/// terminator by the ULLBC passes.
+fn translate_terminator_statement(
+    terminator: &src::Terminator,
+    span: Span,
+    st: tgt::RawStatement,
+) -> tgt::Statement {
+    let mut tgt_st = tgt::Statement::new(span, st);
+    tgt_st.borrow_liveness = terminator.borrow_liveness.clone();
+    tgt_st.move_info = terminator.move_info.clone();
+    tgt_st
 }
 
 fn translate_terminator(
@@ -1412,15 +1431,19 @@ fn translate_terminator(
     parent_loops: &Vec<src::BlockId>,
     switch_exit_blocks: &HashSet<src::BlockId>,
     terminator: &src::Terminator,
-) -> tgt::Block {
+) -> Result<tgt::Block, ReconstructionFailure> {
     let src_span = terminator.span;
 
-    match &terminator.content {
-        src::RawTerminator::Abort(kind) => {
-            tgt::Statement::new(src_span, tgt::RawStatement::Abort(kind.clone())).into_block()
-        }
+    let block = match &terminator.content {
+        src::RawTerminator::Abort(kind) => translate_terminator_statement(
+            terminator,
+            src_span,
+            tgt::RawStatement::Abort(kind.clone()),
+        )
+        .into_block(),
         src::RawTerminator::Return => {
-            tgt::Statement::new(src_span, tgt::RawStatement::Return).into_block()
+            translate_terminator_statement(terminator, src_span, tgt::RawStatement::Return)
+                .into_block()
         }
         src::RawTerminator::Goto { target } => {
             let block = translate_child_block(
@@ -1429,7 +1452,7 @@ fn translate_terminator(
                 switch_exit_blocks,
                 terminator.span,
                 *target,
-            );
+            )?;
             let block = opt_block_unwrap_or_nop(terminator.span, block);
             block
         }
@@ -1444,7 +1467,7 @@ fn translate_terminator(
                         switch_exit_blocks,
                         terminator.span,
                         *then_tgt,
-                    );
+                    )?;
                     // We use the terminator span information in case then
                     // then statement is `None`
                     let then_block = opt_block_unwrap_or_nop(terminator.span, then_block);
@@ -1454,7 +1477,7 @@ fn translate_terminator(
                         switch_exit_blocks,
                         terminator.span,
                         *else_tgt,
-                    );
+                    )?;
                     let else_block = opt_block_unwrap_or_nop(terminator.span, else_block);
 
                     // Translate
@@ -1502,7 +1525,7 @@ fn translate_terminator(
                                 switch_exit_blocks,
                                 terminator.span,
                                 *bid,
-                            );
+                            )?;
                             // We use the terminator span information in case then
                             // then statement is `None`
                             let block = opt_block_unwrap_or_nop(terminator.span, block);
@@ -1518,7 +1541,7 @@ fn translate_terminator(
                         switch_exit_blocks,
                         terminator.span,
                         *otherwise,
-                    );
+                    )?;
                     // We use the terminator span information in case then
                     // then statement is `None`
                     let otherwise_block = opt_block_unwrap_or_nop(terminator.span, otherwise_block);
@@ -1532,9 +1555,10 @@ fn translate_terminator(
             let span = tgt::combine_switch_targets_span(&switch);
             let span = combine_span(&src_span, &span);
             let st = tgt::RawStatement::Switch(switch);
-            tgt::Statement::new(span, st).into_block()
+            translate_terminator_statement(terminator, span, st).into_block()
         }
-    }
+    };
+    Ok(block)
 }
 
 /// Return `true` if whatever the path we take, evaluating the statement
@@ -1552,17 +1576,22 @@ fn is_terminal_explore(num_loops: usize, st: &tgt::Statement) -> bool {
         | tgt::RawStatement::FakeRead(_)
         | tgt::RawStatement::SetDiscriminant(_, _)
         | tgt::RawStatement::Drop(_)
+        | tgt::RawStatement::Retag(..)
         | tgt::RawStatement::Assert(_)
         | tgt::RawStatement::Call(_)
+        | tgt::RawStatement::TryPropagate(_)
         | tgt::RawStatement::Nop
         | tgt::RawStatement::Error(_) => false,
-        tgt::RawStatement::Abort(..) | tgt::RawStatement::Return => true,
+        tgt::RawStatement::Abort(..) | tgt::RawStatement::Return | tgt::RawStatement::Goto(_) => {
+            true
+        }
         tgt::RawStatement::Break(index) => *index >= num_loops,
         tgt::RawStatement::Continue(_index) => true,
         tgt::RawStatement::Switch(switch) => switch
             .iter_targets()
             .all(|tgt_st| is_terminal_explore_block(num_loops, tgt_st)),
         tgt::RawStatement::Loop(loop_st) => is_terminal_explore_block(num_loops + 1, loop_st),
+        tgt::RawStatement::Labeled(_, block) => is_terminal_explore_block(num_loops, block),
     }
 }
 fn is_terminal_explore_block(num_loops: usize, block: &tgt::Block) -> bool {
@@ -1581,7 +1610,7 @@ fn translate_block(
     parent_loops: &Vec<src::BlockId>,
     switch_exit_blocks: &HashSet<src::BlockId>,
     block_id: src::BlockId,
-) -> tgt::Block {
+) -> Result<tgt::Block, ReconstructionFailure> {
     // If the user activated this check: check that we didn't already translate
     // this block, and insert the block id in the set of already translated blocks.
     trace!(
@@ -1593,9 +1622,18 @@ fn translate_block(
     if info.no_code_duplication {
         assert!(!info.explored.contains(&block_id));
     }
-    info.explored.insert(block_id);
-
     let block = info.body.body.get(block_id).unwrap();
+    if !info.explored.insert(block_id) {
+        info.duplicated_size += block.statements.len() + 1;
+        // Give up right away: the reconstruction may duplicate an exponential amount of code.
+        if let Some(max_duplication) = info.max_duplication
+            && info.duplicated_size > max_duplication
+        {
+            return Err(ReconstructionFailure::TooMuchDuplication(
+                info.duplicated_size,
+            ));
+        }
+    }
 
     // Check if we enter a loop: if so, update parent_loops and the current_exit_block
     let is_loop = info.cfg.loop_entries.contains(&block_id);
@@ -1640,7 +1678,7 @@ fn translate_block(
     // (if it is an exit).
 
     let terminator =
-        translate_terminator(info, nparent_loops, &nswitch_exit_blocks, &block.terminator);
+        translate_terminator(info, nparent_loops, &nswitch_exit_blocks, &block.terminator)?;
 
     // Translate the statements inside the block
     let statements = block
@@ -1674,19 +1712,209 @@ fn translate_block(
     if let Some(exit_block_id) = next_block {
         let next_block = ensure_sufficient_stack(|| {
             translate_block(info, parent_loops, switch_exit_blocks, exit_block_id)
-        });
+        })?;
         block = block.merge(next_block);
     }
 
-    block
+    Ok(block)
+}
+
+/// Check whether the CFG is irreducible, i.e. whether it contains a loop with several entry
+/// points. In a reducible CFG, the target of every backward edge dominates its source.
+fn cfg_is_irreducible(body: &src::ExprBody, cfg: &CfgInfo) -> bool {
+    let cfg_info = body.compute_cfg_info();
+    cfg.backward_edges
+        .iter()
+        .any(|(src, tgt)| !cfg_info.dominates(*tgt, *src))
+}
+
+/// Translate a block of a body whose control-flow we couldn't reconstruct. The jumps to other
+/// blocks are translated with `jump`.
+fn translate_block_with_jumps(
+    block: &src::BlockData,
+    jump: impl Fn(Span, src::BlockId) -> tgt::Block,
+) -> tgt::Block {
+    let mut statements = block
+        .statements
+        .iter()
+        .filter_map(translate_statement)
+        .collect_vec();
+    let span = block.terminator.span;
+    let terminator = match &block.terminator.content {
+        src::RawTerminator::Abort(kind) => translate_terminator_statement(
+            &block.terminator,
+            span,
+            tgt::RawStatement::Abort(kind.clone()),
+        )
+        .into_block(),
+        src::RawTerminator::Return => {
+            translate_terminator_statement(&block.terminator, span, tgt::RawStatement::Return)
+                .into_block()
+        }
+        src::RawTerminator::Goto { target } => jump(span, *target),
+        src::RawTerminator::Switch { discr, targets } => {
+            let switch = match targets {
+                src::SwitchTargets::If(then_tgt, else_tgt) => {
+                    tgt::Switch::If(discr.clone(), jump(span, *then_tgt), jump(span, *else_tgt))
+                }
+                src::SwitchTargets::SwitchInt(int_ty, targets, otherwise) => {
+                    tgt::Switch::SwitchInt(
+                        discr.clone(),
+                        *int_ty,
+                        targets
+                            .iter()
+                            .map(|(v, bid)| (vec![*v], jump(span, *bid)))
+                            .collect(),
+                        jump(span, *otherwise),
+                    )
+                }
+            };
+            translate_terminator_statement(
+                &block.terminator,
+                span,
+                tgt::RawStatement::Switch(switch),
+            )
+            .into_block()
+        }
+    };
+    statements.extend(terminator.statements);
+    tgt::Block::from_seq(statements).unwrap()
 }
 
-fn translate_body_aux(no_code_duplication: bool, src_body: &src::ExprBody) -> tgt::ExprBody {
+/// Fallback for the CFGs which we can't turn into structured control-flow. We translate the body
+/// to a state machine:
+/// ```text
+/// state := 0;
+/// loop {
+///     switch state {
+///         0 => { <statements of bb0>; state := <target>; continue 0 },
+///         1 => { <statements of bb1>; return },
+///         ...
+///     }
+/// }
+/// ```
+fn translate_body_as_state_machine(src_body: &src::ExprBody) -> tgt::ExprBody {
+    let span = src_body.span;
+    let mut locals = src_body.locals.clone();
+    let usize_ty = TyKind::Literal(LiteralTy::Integer(IntegerTy::Usize)).into_ty();
+    let state = Place::new(locals.push_with(|index| gast::Var {
+        index,
+        name: Some("state".to_string()),
+        ty: usize_ty,
+    }));
+    let state_value = |bid: src::BlockId| v::ScalarValue::Usize(bid.index() as u64);
+    let set_state = |span: Span, bid: src::BlockId| {
+        let value = Rvalue::Use(Operand::Const(state_value(bid).to_constant()));
+        tgt::Statement::new(span, tgt::RawStatement::Assign(state.clone(), value))
+    };
+    // Jump to the given block: we update the state and go back to the start of the loop.
+    let goto = |span: Span, bid: src::BlockId| tgt::Block {
+        span,
+        statements: vec![
+            set_state(span, bid),
+            tgt::Statement::new(span, tgt::RawStatement::Continue(0)),
+        ],
+    };
+
+    let arms = src_body
+        .body
+        .iter_indexed_values()
+        .map(|(bid, block)| {
+            (
+                vec![state_value(bid)],
+                translate_block_with_jumps(block, goto),
+            )
+        })
+        .collect_vec();
+
+    // The state is always a valid block id.
+    let otherwise = tgt::Statement::new(
+        span,
+        tgt::RawStatement::Abort(gast::AbortKind::UndefinedBehavior),
+    )
+    .into_block();
+    let dispatch = tgt::Switch::SwitchInt(
+        Operand::Copy(state.clone()),
+        IntegerTy::Usize,
+        arms,
+        otherwise,
+    );
+    let loop_body = tgt::Statement::new(span, tgt::RawStatement::Switch(dispatch)).into_block();
+    let body = tgt::Block {
+        span,
+        statements: vec![
+            set_state(span, src::BlockId::ZERO),
+            tgt::Statement::new(span, tgt::RawStatement::Loop(loop_body)),
+        ],
+    };
+
+    tgt::ExprBody {
+        span,
+        arg_count: src_body.arg_count,
+        locals,
+        comments: src_body.comments.clone(),
+        debug_info: src_body.debug_info.clone(),
+        body,
+    }
+}
+
+/// Fallback for the CFGs which we can't turn into structured control-flow, in semi-structured
+/// mode. We keep the blocks as labeled blocks, and translate the jumps to `goto`s:
+/// ```text
+/// 'label0: { <statements of bb0>; goto 'label<target> }
+/// 'label1: { <statements of bb1>; return }
+/// ...
+/// ```
+/// The body starts with the first labeled block.
+fn translate_body_with_gotos(src_body: &src::ExprBody) -> tgt::ExprBody {
+    let label = |bid: src::BlockId| tgt::LabelId::new(bid.index());
+    let goto = |span: Span, bid: src::BlockId| {
+        tgt::Statement::new(span, tgt::RawStatement::Goto(label(bid))).into_block()
+    };
+    let statements = src_body
+        .body
+        .iter_indexed_values()
+        .map(|(bid, block)| {
+            let block = translate_block_with_jumps(block, goto);
+            tgt::Statement::new(block.span, tgt::RawStatement::Labeled(label(bid), block))
+        })
+        .collect_vec();
+
+    tgt::ExprBody {
+        span: src_body.span,
+        arg_count: src_body.arg_count,
+        locals: src_body.locals.clone(),
+        comments: src_body.comments.clone(),
+        debug_info: src_body.debug_info.clone(),
+        body: tgt::Block::from_seq(statements).unwrap(),
+    }
+}
+
+/// The reasons why we may give up on reconstructing the control-flow of a body.
+enum ReconstructionFailure {
+    /// The CFG is irreducible.
+    Irreducible,
+    /// The reconstruction duplicated more statements (the given number) than allowed by
+    /// `--max-duplication`.
+    TooMuchDuplication(usize),
+}
+
+/// Reconstruct the control-flow of the body.
+fn translate_body_aux(
+    no_code_duplication: bool,
+    max_duplication: Option<usize>,
+    src_body: &src::ExprBody,
+) -> Result<tgt::ExprBody, ReconstructionFailure> {
     // Explore the function body to create the control-flow graph without backward
     // edges, and identify the loop entries (which are destinations of backward edges).
     let cfg_info = build_cfg_info(src_body);
     trace!("cfg_info: {:?}", cfg_info);
 
+    // The reconstruction below assumes that the loops have a single entry point.
+    if cfg_is_irreducible(src_body, &cfg_info) {
+        return Err(ReconstructionFailure::Irreducible);
+    }
+
     // Find the exit block for all the loops and switches, if such an exit point
     // exists.
     let exits_info = compute_loop_switch_exits(&cfg_info);
@@ -1704,38 +1932,102 @@ fn translate_body_aux(no_code_duplication: bool, src_body: &src::ExprBody) -> tg
         body: src_body,
         exits_info: &exits_info,
         explored: &mut explored,
+        duplicated_size: 0,
+        max_duplication,
     };
-    let tgt_body = translate_block(&mut info, &Vec::new(), &HashSet::new(), src::BlockId::ZERO);
+    let tgt_body = translate_block(&mut info, &Vec::new(), &HashSet::new(), src::BlockId::ZERO)?;
 
     // Sanity: check that we translated all the blocks
     for (bid, _) in src_body.body.iter_indexed_values() {
         assert!(explored.contains(&bid));
     }
 
-    tgt::ExprBody {
+    Ok(tgt::ExprBody {
         span: src_body.span,
         arg_count: src_body.arg_count,
         locals: src_body.locals.clone(),
         comments: src_body.comments.clone(),
+        debug_info: src_body.debug_info.clone(),
         body: tgt_body,
-    }
+    })
 }
 
-fn translate_body(no_code_duplication: bool, body: &mut gast::Body) {
+/// If we fail to reconstruct the control-flow, we fall back to a state machine (or to labeled
+/// blocks and `goto`s in semi-structured mode) and return the reason of the failure.
+fn translate_body(
+    options: &TransformOptions,
+    body: &mut gast::Body,
+) -> Result<(), ReconstructionFailure> {
     use gast::Body::{Structured, Unstructured};
     let Unstructured(src_body) = body else {
         panic!("Called `ullbc_to_llbc` on an already restructured body")
     };
     trace!("About to translate to ullbc: {:?}", src_body.span);
-    let tgt_body = translate_body_aux(no_code_duplication, src_body);
+    let (tgt_body, result) = match translate_body_aux(
+        options.no_code_duplication,
+        options.max_duplication,
+        src_body,
+    ) {
+        Ok(tgt_body) => (tgt_body, Ok(())),
+        Err(failure) if options.semi_structured => {
+            (translate_body_with_gotos(src_body), Err(failure))
+        }
+        Err(failure) => (translate_body_as_state_machine(src_body), Err(failure)),
+    };
     *body = Structured(tgt_body);
+    result
 }
 
 /// Translate the functions by reconstructing the control-flow.
 pub fn translate_functions(ctx: &mut TransformCtx) {
     // Translate the bodies one at a time.
-    for body in &mut ctx.translated.bodies {
-        translate_body(ctx.options.no_code_duplication, body);
+    let mut failures = HashMap::new();
+    for body_id in ctx.translated.bodies.iter_indices().collect_vec() {
+        let body = &mut ctx.translated.bodies[body_id];
+        if let Err(failure) = translate_body(&ctx.options, body) {
+            failures.insert(body_id, failure);
+        }
+    }
+
+    // Warn about the items whose control-flow we couldn't reconstruct.
+    if !failures.is_empty() {
+        let fmt_ctx = ctx.into_fmt();
+        let items = ctx
+            .translated
+            .fun_decls
+            .iter()
+            .map(|decl| (decl.body, &decl.item_meta))
+            .chain(
+                ctx.translated
+                    .global_decls
+                    .iter()
+                    .map(|decl| (decl.body, &decl.item_meta)),
+            );
+        for (body, item_meta) in items {
+            let Ok(body_id) = body else { continue };
+            let Some(failure) = failures.get(&body_id) else {
+                continue;
+            };
+            let name = item_meta.name.fmt_with_ctx(&fmt_ctx);
+            let reason = match failure {
+                ReconstructionFailure::Irreducible => {
+                    format!("The control-flow of `{name}` is irreducible")
+                }
+                ReconstructionFailure::TooMuchDuplication(size) => format!(
+                    "Reconstructing the control-flow of `{name}` duplicates at least {size} \
+                    statements, which is more than the maximum allowed by `--max-duplication` \
+                    ({})",
+                    ctx.options.max_duplication.unwrap()
+                ),
+            };
+            let fallback = if ctx.options.semi_structured {
+                "labeled blocks and `goto`s"
+            } else {
+                "a state machine (`loop { switch state { .. } }`)"
+            };
+            let msg = format!("{reason}: it was translated as {fallback} instead.");
+            ctx.errors.span_err_no_register(item_meta.span, &msg);
+        }
     }
 
     // Print the functions
@@ -1756,3 +2048,141 @@ pub fn translate_functions(ctx: &mut TransformCtx) {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::*;
+    use crate::ids::Vector;
+
+    /// Build a body with a boolean argument, whose blocks have the given terminators. The
+    /// switches branch on the argument.
+    fn mk_body(terminators: Vec<src::RawTerminator>) -> src::ExprBody {
+        let mut locals = Vector::new();
+        for name in [None, Some("b".to_string())] {
+            locals.push_with(|index| Var {
+                index,
+                name,
+                ty: TyKind::Literal(LiteralTy::Bool).into_ty(),
+            });
+        }
+        let body = terminators
+            .into_iter()
+            .map(|content| src::BlockData {
+                statements: Vec::new(),
+                terminator: src::Terminator::new(Span::dummy(), content),
+            })
+            .collect();
+        src::ExprBody {
+            span: Span::dummy(),
+            arg_count: 1,
+            locals,
+            comments: Vec::new(),
+            debug_info: None,
+            body,
+        }
+    }
+
+    fn goto(target: usize) -> src::RawTerminator {
+        src::RawTerminator::Goto {
+            target: src::BlockId::new(target),
+        }
+    }
+
+    fn branch(then_tgt: usize, else_tgt: usize) -> src::RawTerminator {
+        src::RawTerminator::Switch {
+            discr: Operand::Copy(Place::new(VarId::new(1))),
+            targets: src::SwitchTargets::If(
+                src::BlockId::new(then_tgt),
+                src::BlockId::new(else_tgt),
+            ),
+        }
+    }
+
+    /// A loop with two entry points: `bb1` and `bb2` can both be reached from `bb0`.
+    fn irreducible_body() -> src::ExprBody {
+        mk_body(vec![
+            branch(1, 2),
+            branch(2, 3),
+            goto(1),
+            src::RawTerminator::Return,
+        ])
+    }
+
+    #[test]
+    fn irreducible_cfg_as_state_machine() {
+        let body = irreducible_body();
+        assert!(matches!(
+            translate_body_aux(false, None, &body),
+            Err(ReconstructionFailure::Irreducible)
+        ));
+
+        // `state := 0; loop { switch state { 0 => .., 1 => .., 2 => .., 3 => .. } }`
+        let tgt_body = translate_body_as_state_machine(&body);
+        let [init, tgt::Statement {
+            content: tgt::RawStatement::Loop(loop_body),
+            ..
+        }] = tgt_body.body.statements.as_slice()
+        else {
+            panic!("unexpected body: {:?}", tgt_body.body)
+        };
+        assert!(init.content.is_assign());
+        let [tgt::Statement {
+            content: tgt::RawStatement::Switch(tgt::Switch::SwitchInt(_, _, arms, _)),
+            ..
+        }] = loop_body.statements.as_slice()
+        else {
+            panic!("unexpected loop body: {loop_body:?}")
+        };
+        assert_eq!(arms.len(), body.body.len());
+        // The new local holds the state.
+        assert_eq!(tgt_body.locals.len(), body.locals.len() + 1);
+    }
+
+    #[test]
+    fn max_duplication() {
+        // The outer `if` (`bb0`) exits to `bb5`. The inner `if` (`bb1`) can reach that exit, hence
+        // doesn't get an exit of its own: `bb4` is translated in both its branches.
+        let body = mk_body(vec![
+            branch(1, 5),
+            branch(2, 3),
+            goto(4),
+            goto(4),
+            goto(5),
+            src::RawTerminator::Return,
+        ]);
+        assert!(translate_body_aux(false, None, &body).is_ok());
+        assert!(translate_body_aux(false, Some(1), &body).is_ok());
+        assert!(matches!(
+            translate_body_aux(false, Some(0), &body),
+            Err(ReconstructionFailure::TooMuchDuplication(1))
+        ));
+    }
+
+    #[test]
+    fn irreducible_cfg_with_gotos() {
+        let body = irreducible_body();
+        // One labeled block per basic block, which ends with a jump.
+        let tgt_body = translate_body_with_gotos(&body);
+        let statements = &tgt_body.body.statements;
+        assert_eq!(statements.len(), body.body.len());
+        for (i, st) in statements.iter().enumerate() {
+            let tgt::RawStatement::Labeled(label, block) = &st.content else {
+                panic!("unexpected statement: {st:?}")
+            };
+            assert_eq!(label.index(), i);
+            assert!(is_terminal(block));
+        }
+        // `bb2` jumps back to `bb1`.
+        let tgt::RawStatement::Labeled(_, block) = &statements[2].content else {
+            unreachable!()
+        };
+        assert!(matches!(
+            block.statements.as_slice(),
+            [tgt::Statement {
+                content: tgt::RawStatement::Goto(label),
+                ..
+            }] if label.index() == 1
+        ));
+    }
+}
@@ -0,0 +1,387 @@
+//! Type-check the bodies of a translated crate: the values assigned to places must have the types
+//! of these places, the arguments of calls must match the signatures of the called functions (after
+//! substitution of their generics), the conditions must be booleans, etc.
+//!
+//! We compare types modulo regions. We don't check the types involving trait associated types,
+//! because we don't normalize them. The structural invariants, e.g. that the referenced locals
+//! exist, are checked by [crate::validate]: we skip what doesn't satisfy them.
+use crate::ast::*;
+use crate::formatter::{FmtCtx, IntoFormatter};
+use crate::ids::Vector;
+use crate::pretty::FmtWithCtx;
+use crate::validate::ValidationError;
+use crate::{llbc_ast, ullbc_ast};
+use derive_visitor::{visitor_enter_fn, visitor_enter_fn_mut, Drive, DriveMut};
+
+/// Type-check all the bodies of the crate. Returns the errors we found.
+pub fn check_crate(krate: &TranslatedCrate) -> Vec<ValidationError> {
+    let fmt_ctx = krate.into_fmt();
+    let mut errors = Vec::new();
+    for (id, item) in krate.all_items_with_ids() {
+        let body_id = match item {
+            AnyTransItem::Fun(decl) => decl.body.ok(),
+            AnyTransItem::Global(decl) => decl.body.ok(),
+            _ => None,
+        };
+        let Some(body) = body_id.and_then(|body_id| krate.bodies.get(body_id)) else {
+            continue;
+        };
+        let locals = match body {
+            Body::Unstructured(body) => &body.locals,
+            Body::Structured(body) => &body.locals,
+        };
+        let mut ctx = TyCtx {
+            krate,
+            fmt_ctx: &fmt_ctx,
+            locals,
+            item: id,
+            errors: &mut errors,
+        };
+        match body {
+            Body::Unstructured(body) => {
+                for block in body.body.iter() {
+                    for st in &block.statements {
+                        ctx.check_ullbc_statement(st);
+                    }
+                    ctx.check_terminator(&block.terminator);
+                }
+            }
+            Body::Structured(body) => {
+                body.body
+                    .drive(&mut visitor_enter_fn(|st: &llbc_ast::Statement| {
+                        ctx.check_llbc_statement(st)
+                    }));
+            }
+        }
+    }
+    errors
+}
+
+/// Compare two types modulo regions. `Never` is compatible with every type, and we accept the
+/// types that contain associated types since we can't normalize them.
+pub(crate) fn compatible(expected: &Ty, actual: &Ty) -> bool {
+    fn erase_regions(ty: &Ty) -> Ty {
+        let mut ty = ty.clone();
+        ty.drive_mut(&mut Ty::visit_inside(visitor_enter_fn_mut(
+            |r: &mut Region| *r = Region::Erased,
+        )));
+        ty
+    }
+    fn has_trait_type(ty: &Ty) -> bool {
+        let mut found = false;
+        ty.drive(&mut Ty::visit_inside(visitor_enter_fn(|ty: &Ty| {
+            found |= ty.kind().is_trait_type()
+        })));
+        found
+    }
+    expected.kind().is_never()
+        || actual.kind().is_never()
+        || has_trait_type(expected)
+        || has_trait_type(actual)
+        || erase_regions(expected) == erase_regions(actual)
+}
+
+fn mk_array_ty(ty: Ty, len: ConstGeneric) -> Ty {
+    let args = GenericArgs::new(
+        Vector::new(),
+        [ty].into_iter().collect(),
+        [len].into_iter().collect(),
+        Vector::new(),
+    );
+    TyKind::Adt(TypeId::Builtin(BuiltinTy::Array), args).into_ty()
+}
+
+fn mk_usize_ty() -> Ty {
+    TyKind::Literal(LiteralTy::Integer(IntegerTy::Usize)).into_ty()
+}
+
+fn mk_bool_ty() -> Ty {
+    TyKind::Literal(LiteralTy::Bool).into_ty()
+}
+
+struct TyCtx<'a> {
+    krate: &'a TranslatedCrate,
+    fmt_ctx: &'a FmtCtx<'a>,
+    locals: &'a Vector<VarId, Var>,
+    item: AnyTransId,
+    errors: &'a mut Vec<ValidationError>,
+}
+
+impl TyCtx<'_> {
+    fn error(&mut self, span: Span, message: String) {
+        self.errors.push(ValidationError {
+            item: self.item,
+            span,
+            message,
+        })
+    }
+
+    fn fmt_ty(&self, ty: &Ty) -> String {
+        ty.fmt_with_ctx(self.fmt_ctx)
+    }
+
+    fn expect_ty(&mut self, span: Span, what: &str, expected: &Ty, actual: Option<Ty>) {
+        if let Some(actual) = actual
+            && !compatible(expected, &actual)
+        {
+            let message = format!(
+                "{what} has type `{}` but `{}` was expected",
+                self.fmt_ty(&actual),
+                self.fmt_ty(expected)
+            );
+            self.error(span, message)
+        }
+    }
+
+    /// The type of a place, if it can be computed.
+    fn place_ty(&self, place: &Place) -> Option<Ty> {
+        let mut ty = self.locals.get(place.var_id)?.ty.clone();
+        for elem in &place.projection {
+            ty = elem.project_ty(self.krate, &ty).ok()??;
+        }
+        Some(ty)
+    }
+
+    fn operand_ty(&self, op: &Operand) -> Option<Ty> {
+        match op {
+            Operand::Copy(place) | Operand::Move(place) => self.place_ty(place),
+            Operand::Const(c) => Some(c.ty.clone()),
+        }
+    }
+
+    /// Check the operands of an rvalue, and compute its type if possible.
+    fn rvalue_ty(&mut self, span: Span, rv: &Rvalue) -> Option<Ty> {
+        Some(match rv {
+            Rvalue::Use(op) => self.operand_ty(op)?,
+            Rvalue::Ref(place, kind) => {
+                let kind = match kind {
+                    BorrowKind::Mut | BorrowKind::TwoPhaseMut => RefKind::Mut,
+                    BorrowKind::Shared | BorrowKind::Shallow => RefKind::Shared,
+                };
+                TyKind::Ref(Region::Erased, self.place_ty(place)?, kind).into_ty()
+            }
+            Rvalue::RawPtr(place, kind) => TyKind::RawPtr(self.place_ty(place)?, *kind).into_ty(),
+            Rvalue::BinaryOp(op, lhs, rhs, tys) => {
+                let lhs_ty = self.operand_ty(lhs);
+                let rhs_ty = self.operand_ty(rhs);
+                self.expect_ty(span, "the left operand", &tys.lhs_ty, lhs_ty);
+                self.expect_ty(span, "the right operand", &tys.rhs_ty, rhs_ty);
+                // The operands of the shifts may have different types.
+                if !matches!(op, BinOp::Shl | BinOp::Shr) {
+                    self.expect_ty(
+                        span,
+                        "the right operand",
+                        &tys.lhs_ty,
+                        Some(tys.rhs_ty.clone()),
+                    );
+                }
+                match op {
+                    BinOp::Eq | BinOp::Lt | BinOp::Le | BinOp::Ne | BinOp::Ge | BinOp::Gt => {
+                        mk_bool_ty()
+                    }
+                    BinOp::CheckedAdd | BinOp::CheckedSub | BinOp::CheckedMul => {
+                        let types = [tys.lhs_ty.clone(), mk_bool_ty()].into_iter().collect();
+                        TyKind::Adt(TypeId::Tuple, GenericArgs::new_from_types(types)).into_ty()
+                    }
+                    _ => tys.lhs_ty.clone(),
+                }
+            }
+            Rvalue::UnaryOp(op, operand) => {
+                let operand_ty = self.operand_ty(operand);
+                match op {
+                    UnOp::Not | UnOp::Neg => operand_ty?,
+                    UnOp::Cast(kind) => {
+                        let (src, tgt) = match kind {
+                            CastKind::Scalar(src, tgt) => (
+                                TyKind::Literal(*src).into_ty(),
+                                TyKind::Literal(*tgt).into_ty(),
+                            ),
+                            CastKind::RawPtr(src, tgt)
+                            | CastKind::ExposeProvenance(src, tgt)
+                            | CastKind::WithExposedProvenance(src, tgt)
+                            | CastKind::FnPtr(src, tgt)
+                            | CastKind::Unsize(src, tgt)
+                            | CastKind::Transmute(src, tgt, _) => (src.clone(), tgt.clone()),
+                        };
+                        self.expect_ty(span, "the cast operand", &src, operand_ty);
+                        tgt
+                    }
+                }
+            }
+            Rvalue::NullaryOp(NullOp::UbChecks, _) => mk_bool_ty(),
+            Rvalue::NullaryOp(..) | Rvalue::Len(..) => mk_usize_ty(),
+            // The type of the discriminant depends on the `repr` of the enum.
+            Rvalue::Discriminant(..) => return None,
+            Rvalue::Aggregate(kind, ops) => self.aggregate_ty(span, kind, ops)?,
+            Rvalue::Global(global_ref) | Rvalue::GlobalRef(global_ref, _) => {
+                let decl = self.krate.global_decls.get(global_ref.id)?;
+                let ty = decl.ty.substitute(&global_ref.generics);
+                match rv {
+                    Rvalue::GlobalRef(_, kind) => TyKind::Ref(Region::Erased, ty, *kind).into_ty(),
+                    _ => ty,
+                }
+            }
+            Rvalue::Repeat(op, ty, len) => {
+                let op_ty = self.operand_ty(op);
+                self.expect_ty(span, "the repeated operand", ty, op_ty);
+                mk_array_ty(ty.clone(), len.clone())
+            }
+            Rvalue::ShallowInitBox(_, ty) => {
+                let args = GenericArgs::new_from_types([ty.clone()].into_iter().collect());
+                TyKind::Adt(TypeId::Builtin(BuiltinTy::Box), args).into_ty()
+            }
+        })
+    }
+
+    fn aggregate_ty(&mut self, span: Span, kind: &AggregateKind, ops: &[Operand]) -> Option<Ty> {
+        Some(match kind {
+            AggregateKind::Adt(TypeId::Tuple, _, _, args) => {
+                if args.types.len() != ops.len() {
+                    self.error(
+                        span,
+                        "wrong number of fields in a tuple aggregate".to_owned(),
+                    );
+                }
+                TyKind::Adt(TypeId::Tuple, args.clone()).into_ty()
+            }
+            AggregateKind::Adt(type_id @ TypeId::Adt(id), variant, field, args) => {
+                let fields: Vec<&Field> =
+                    match (&self.krate.type_decls.get(*id)?.kind, variant, field) {
+                        (TypeDeclKind::Struct(fields), None, None) => fields.iter().collect(),
+                        (TypeDeclKind::Enum(variants), Some(variant), None) => {
+                            variants.get(*variant)?.fields.iter().collect()
+                        }
+                        (TypeDeclKind::Union(fields), None, Some(field)) => {
+                            vec![fields.get(*field)?]
+                        }
+                        _ => return None,
+                    };
+                if fields.len() != ops.len() {
+                    self.error(span, "wrong number of fields in an aggregate".to_owned());
+                }
+                for (field, op) in fields.into_iter().zip(ops) {
+                    let op_ty = self.operand_ty(op);
+                    self.expect_ty(
+                        span,
+                        "the aggregated field",
+                        &field.ty.substitute(args),
+                        op_ty,
+                    );
+                }
+                TyKind::Adt(*type_id, args.clone()).into_ty()
+            }
+            AggregateKind::Adt(TypeId::Builtin(_), ..) | AggregateKind::Closure(..) => return None,
+            AggregateKind::Array(ty, len) => {
+                for op in ops {
+                    let op_ty = self.operand_ty(op);
+                    self.expect_ty(span, "the array element", ty, op_ty);
+                }
+                mk_array_ty(ty.clone(), len.clone())
+            }
+        })
+    }
+
+    fn check_assign(&mut self, span: Span, place: &Place, rv: &Rvalue) {
+        let rv_ty = self.rvalue_ty(span, rv);
+        if let Some(place_ty) = self.place_ty(place) {
+            self.expect_ty(span, "the assigned value", &place_ty, rv_ty);
+        }
+    }
+
+    fn check_call(&mut self, span: Span, call: &Call) {
+        let FnOperand::Regular(fn_ptr) = &call.func else {
+            return;
+        };
+        let id = match &fn_ptr.func {
+            FunIdOrTraitMethodRef::Fun(FunId::Regular(id)) => *id,
+            FunIdOrTraitMethodRef::Trait(..) | FunIdOrTraitMethodRef::Fun(FunId::Builtin(_)) => {
+                // TODO: the generics of trait method calls only contain the method generics.
+                return;
+            }
+        };
+        let Some(decl) = self.krate.fun_decls.get(id) else {
+            return;
+        };
+        let sig = &decl.signature;
+        if !fn_ptr.generics.matches(&sig.generics) {
+            // Reported by the validator.
+            return;
+        }
+        if sig.inputs.len() != call.args.len() {
+            let message = format!(
+                "`{}` takes {} arguments but {} were supplied",
+                decl.item_meta.name.fmt_with_ctx(self.fmt_ctx),
+                sig.inputs.len(),
+                call.args.len()
+            );
+            self.error(span, message);
+        }
+        for (i, (input, arg)) in sig.inputs.iter().zip(&call.args).enumerate() {
+            let arg_ty = self.operand_ty(arg);
+            let what = format!("argument {i}");
+            self.expect_ty(span, &what, &input.substitute(&fn_ptr.generics), arg_ty);
+        }
+        if let Some(dest_ty) = self.place_ty(&call.dest) {
+            let output = sig.output.substitute(&fn_ptr.generics);
+            self.expect_ty(span, "the call destination", &dest_ty, Some(output));
+        }
+    }
+
+    fn check_bool(&mut self, span: Span, what: &str, op: &Operand) {
+        let ty = self.operand_ty(op);
+        self.expect_ty(span, what, &mk_bool_ty(), ty);
+    }
+
+    fn check_int(&mut self, span: Span, int_ty: IntegerTy, op: &Operand) {
+        let ty = self.operand_ty(op);
+        let expected = TyKind::Literal(LiteralTy::Integer(int_ty)).into_ty();
+        self.expect_ty(span, "the switch discriminant", &expected, ty);
+    }
+
+    fn check_ullbc_statement(&mut self, st: &ullbc_ast::Statement) {
+        use ullbc_ast::RawStatement;
+        let span = st.span;
+        match &st.content {
+            RawStatement::Assign(place, rv) => self.check_assign(span, place, rv),
+            RawStatement::Call(call) => self.check_call(span, call),
+            RawStatement::Assert(assert) => self.check_bool(span, "the assertion", &assert.cond),
+            _ => {}
+        }
+    }
+
+    fn check_terminator(&mut self, terminator: &ullbc_ast::Terminator) {
+        use ullbc_ast::{RawTerminator, SwitchTargets};
+        let span = terminator.span;
+        if let RawTerminator::Switch { discr, targets } = &terminator.content {
+            match targets {
+                SwitchTargets::If(..) => self.check_bool(span, "the condition", discr),
+                SwitchTargets::SwitchInt(int_ty, ..) => self.check_int(span, *int_ty, discr),
+            }
+        }
+    }
+
+    fn check_llbc_statement(&mut self, st: &llbc_ast::Statement) {
+        use llbc_ast::{RawStatement, Switch};
+        let span = st.span;
+        match &st.content {
+            RawStatement::Assign(place, rv) => self.check_assign(span, place, rv),
+            RawStatement::Call(call) => self.check_call(span, call),
+            RawStatement::Assert(assert) => self.check_bool(span, "the assertion", &assert.cond),
+            RawStatement::Switch(Switch::If(cond, ..)) => {
+                self.check_bool(span, "the condition", cond)
+            }
+            RawStatement::Switch(Switch::SwitchInt(discr, int_ty, ..)) => {
+                self.check_int(span, *int_ty, discr)
+            }
+            RawStatement::Switch(Switch::Match(place, ..)) => {
+                if let Some(ty) = self.place_ty(place)
+                    && !matches!(ty.kind(), TyKind::Adt(TypeId::Adt(_), _))
+                {
+                    let message = format!("matching on the non-ADT type `{}`", self.fmt_ty(&ty));
+                    self.error(span, message);
+                }
+            }
+            _ => {}
+        }
+    }
+}
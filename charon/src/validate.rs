@@ -0,0 +1,301 @@
+//! Check the invariants of a translated crate: every referenced item exists, the bodies are
+//! well-formed (their block targets and local variables exist, their place projections are
+//! well-typed, their `break`s and `continue`s refer to enclosing loops), and the generic arguments
+//! match the parameters of the items they are applied to.
+//!
+//! This runs after the passes with `--check-invariants`, and on external files with
+//! `charon validate`.
+use crate::ast::*;
+use crate::formatter::{FmtCtx, IntoFormatter};
+use crate::ids::Vector;
+use crate::llbc_ast;
+use crate::pretty::FmtWithCtx;
+use crate::ullbc_ast::{self, BlockId};
+use derive_visitor::{visitor_enter_fn, Drive, Visitor};
+use std::collections::HashSet;
+
+/// An invariant violation.
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    /// The item in which we found the error.
+    pub item: AnyTransId,
+    pub span: Span,
+    pub message: String,
+}
+
+/// Check the invariants of the crate. Returns the errors we found.
+pub fn validate_crate(krate: &TranslatedCrate) -> Vec<ValidationError> {
+    let fmt_ctx = krate.into_fmt();
+    let mut errors = Vec::new();
+    for (id, item) in krate.all_items_with_ids() {
+        let mut visitor = ItemValidator {
+            krate,
+            fmt_ctx: &fmt_ctx,
+            errors: Vec::new(),
+        };
+        if item.id() != id {
+            visitor.error(format!("the item is registered with id {id:?}"));
+        }
+        item.drive(&mut visitor);
+        let body_id = match item {
+            AnyTransItem::Fun(decl) => decl.body.ok(),
+            AnyTransItem::Global(decl) => decl.body.ok(),
+            _ => None,
+        };
+        if let Some(body_id) = body_id {
+            if body_id >= krate.bodies.next_id() {
+                visitor.error(format!("the body {body_id:?} doesn't exist"));
+            } else if let Some(body) = krate.bodies.get(body_id) {
+                // Empty body slots correspond to bodies that failed to translate.
+                body.drive(&mut visitor);
+                visitor.validate_body(body);
+            }
+        }
+        let span = item.item_meta().span;
+        errors.extend(visitor.errors.into_iter().map(|message| ValidationError {
+            item: id,
+            span,
+            message,
+        }));
+    }
+    errors
+}
+
+/// Checks the ids and generics found in an item.
+#[derive(Visitor)]
+#[visitor(
+    TypeDeclId(enter),
+    FunDeclId(enter),
+    GlobalDeclId(enter),
+    TraitImplId(enter),
+    TraitDeclId(enter),
+    AggregateKind(enter),
+    FnPtr(enter),
+    GlobalDeclRef(enter),
+    TraitDeclRef(enter),
+    TraitRefKind(enter),
+    Ty(enter)
+)]
+struct ItemValidator<'a> {
+    krate: &'a TranslatedCrate,
+    fmt_ctx: &'a FmtCtx<'a>,
+    errors: Vec<String>,
+}
+
+impl ItemValidator<'_> {
+    fn error(&mut self, message: impl Into<String>) {
+        self.errors.push(message.into())
+    }
+
+    /// Items that failed to translate don't have a declaration, but they still have a name.
+    fn check_id(&mut self, id: AnyTransId) {
+        if self.krate.get_item(id).is_none() && self.krate.item_name(id).is_none() {
+            self.error(format!("reference to the non-existing item {id:?}"))
+        }
+    }
+
+    fn check_generics(&mut self, args: &GenericArgs, id: impl Into<AnyTransId>) {
+        let id = id.into();
+        if let Some(item) = self.krate.get_item(id)
+            && !args.matches(item.generic_params())
+        {
+            let name = item.item_meta().name.fmt_with_ctx(self.fmt_ctx);
+            let params = item.generic_params();
+            self.error(format!(
+                "mismatched generics for `{name}`: expected {} regions, {} types, {} const \
+                generics and {} trait refs, got {}, {}, {} and {}",
+                params.regions.len(),
+                params.types.len(),
+                params.const_generics.len(),
+                params.trait_clauses.len(),
+                args.regions.len(),
+                args.types.len(),
+                args.const_generics.len(),
+                args.trait_refs.len(),
+            ))
+        }
+    }
+
+    fn check_type_id_generics(&mut self, args: &GenericArgs, type_id: &TypeId) {
+        match type_id {
+            TypeId::Adt(id) => self.check_generics(args, *id),
+            TypeId::Tuple => {
+                if !(args.regions.is_empty()
+                    && args.const_generics.is_empty()
+                    && args.trait_refs.is_empty())
+                {
+                    self.error("the generics of a tuple should only be types")
+                }
+            }
+            TypeId::Builtin(..) => {}
+        }
+    }
+
+    fn validate_body(&mut self, body: &Body) {
+        match body {
+            Body::Unstructured(body) => {
+                self.validate_locals(&body.locals, body.arg_count, &body.body);
+                self.validate_blocks(&body.body);
+            }
+            Body::Structured(body) => {
+                self.validate_locals(&body.locals, body.arg_count, &body.body);
+                let mut labels = HashSet::new();
+                body.body
+                    .drive(&mut visitor_enter_fn(|st: &llbc_ast::RawStatement| {
+                        if let llbc_ast::RawStatement::Labeled(label, _) = st {
+                            labels.insert(*label);
+                        }
+                    }));
+                self.validate_block(&body.body, 0, &labels);
+            }
+        }
+    }
+
+    /// Check that the locals are consistently numbered, and that the places of the body only
+    /// refer to existing locals with well-typed projections.
+    fn validate_locals<T: Drive>(
+        &mut self,
+        locals: &Vector<VarId, Var>,
+        arg_count: usize,
+        body: &T,
+    ) {
+        for (id, var) in locals.iter_indexed() {
+            if var.index != id {
+                self.error(format!("the local {id:?} has index {:?}", var.index))
+            }
+        }
+        if arg_count >= locals.len() {
+            self.error(format!(
+                "the body has {} locals, which is too few for {arg_count} arguments",
+                locals.len()
+            ))
+        }
+        let mut errors = Vec::new();
+        body.drive(&mut visitor_enter_fn(|id: &VarId| {
+            if locals.get(*id).is_none() {
+                errors.push(format!("reference to the non-existing local {id:?}"))
+            }
+        }));
+        body.drive(&mut visitor_enter_fn(|place: &Place| {
+            let Some(var) = locals.get(place.var_id) else {
+                return;
+            };
+            let mut ty = var.ty.clone();
+            for elem in &place.projection {
+                match elem.project_ty(self.krate, &ty) {
+                    Ok(Some(elem_ty)) => ty = elem_ty,
+                    Ok(None) => break,
+                    Err(msg) => {
+                        let place = place.fmt_with_ctx(self.fmt_ctx);
+                        errors.push(format!("ill-typed place `{place}`: {msg}"));
+                        break;
+                    }
+                }
+            }
+        }));
+        self.errors.extend(errors);
+    }
+
+    fn validate_blocks(&mut self, blocks: &Vector<BlockId, ullbc_ast::BlockData>) {
+        if blocks.get(BlockId::ZERO).is_none() {
+            self.error("the body has no start block")
+        }
+        for (block_id, block) in blocks.iter_indexed() {
+            for target in block.targets() {
+                if blocks.get(target).is_none() {
+                    self.error(format!(
+                        "the block {block_id:?} jumps to the non-existing block {target:?}"
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Check that the `break`s and `continue`s refer to enclosing loops, and that the `goto`s
+    /// refer to existing labels. `depth` is the number of loops enclosing this block.
+    fn validate_block(
+        &mut self,
+        block: &llbc_ast::Block,
+        depth: usize,
+        labels: &HashSet<llbc_ast::LabelId>,
+    ) {
+        use llbc_ast::RawStatement;
+        for st in &block.statements {
+            match &st.content {
+                RawStatement::Break(i) | RawStatement::Continue(i) if *i >= depth => {
+                    self.error(format!(
+                        "`{}` refers to loop {i} but only {depth} loops enclose it",
+                        if st.content.is_break() {
+                            "break"
+                        } else {
+                            "continue"
+                        }
+                    ))
+                }
+                RawStatement::Goto(label) if !labels.contains(label) => {
+                    self.error(format!("`goto` to the non-existing label {label:?}"))
+                }
+                RawStatement::Loop(body) => self.validate_block(body, depth + 1, labels),
+                RawStatement::Labeled(_, body) => self.validate_block(body, depth, labels),
+                RawStatement::Switch(switch) => {
+                    for target in switch.iter_targets() {
+                        self.validate_block(target, depth, labels)
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// Visitor functions
+impl ItemValidator<'_> {
+    fn enter_type_decl_id(&mut self, id: &TypeDeclId) {
+        self.check_id(AnyTransId::Type(*id));
+    }
+    fn enter_fun_decl_id(&mut self, id: &FunDeclId) {
+        self.check_id(AnyTransId::Fun(*id));
+    }
+    fn enter_global_decl_id(&mut self, id: &GlobalDeclId) {
+        self.check_id(AnyTransId::Global(*id));
+    }
+    fn enter_trait_impl_id(&mut self, id: &TraitImplId) {
+        self.check_id(AnyTransId::TraitImpl(*id));
+    }
+    fn enter_trait_decl_id(&mut self, id: &TraitDeclId) {
+        self.check_id(AnyTransId::TraitDecl(*id));
+    }
+
+    fn enter_aggregate_kind(&mut self, agg: &AggregateKind) {
+        match agg {
+            AggregateKind::Adt(type_id, _, _, args) => self.check_type_id_generics(args, type_id),
+            AggregateKind::Closure(id, args) => self.check_generics(args, *id),
+            AggregateKind::Array(..) => {}
+        }
+    }
+    fn enter_fn_ptr(&mut self, fn_ptr: &FnPtr) {
+        match &fn_ptr.func {
+            FunIdOrTraitMethodRef::Fun(FunId::Regular(id))
+            | FunIdOrTraitMethodRef::Trait(_, _, id) => self.check_generics(&fn_ptr.generics, *id),
+            FunIdOrTraitMethodRef::Fun(FunId::Builtin(..)) => {}
+        }
+    }
+    fn enter_global_decl_ref(&mut self, global_ref: &GlobalDeclRef) {
+        self.check_generics(&global_ref.generics, global_ref.id);
+    }
+    fn enter_trait_decl_ref(&mut self, tref: &TraitDeclRef) {
+        self.check_generics(&tref.generics, tref.trait_id);
+    }
+    fn enter_trait_ref_kind(&mut self, kind: &TraitRefKind) {
+        if let TraitRefKind::TraitImpl(id, args) = kind {
+            self.check_generics(args, *id)
+        }
+    }
+    fn enter_ty(&mut self, ty: &Ty) {
+        if let TyKind::Adt(type_id, args) = ty.kind() {
+            self.check_type_id_generics(args, type_id);
+        }
+        // Recurse into the type, which doesn't happen by default.
+        ty.drive_inner(self);
+    }
+}
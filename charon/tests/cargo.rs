@@ -4,7 +4,11 @@
 use anyhow::bail;
 use assert_cmd::prelude::CommandCargoExt;
 use libtest_mimic::Trial;
-use std::{error::Error, path::PathBuf, process::Command};
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 use util::{compare_or_overwrite, Action};
 
@@ -17,8 +21,12 @@ struct Case {
     dir: PathBuf,
     /// Path of the output pretty-llbc file.
     expected: PathBuf,
+    /// Whether to compare the pretty-llbc output with the `expected` file.
+    check_output: bool,
     /// Extra arguments to pass to charon.
     charon_args: Vec<String>,
+    /// Extra checks to run on the directory once charon succeeded.
+    check_dir: Option<fn(&Path) -> anyhow::Result<()>>,
 }
 
 fn perform_test(test_case: &Case, action: Action) -> anyhow::Result<()> {
@@ -41,7 +49,12 @@ fn perform_test(test_case: &Case, action: Action) -> anyhow::Result<()> {
 
     if output.status.success() {
         let stdout = String::from_utf8(output.stdout.clone())?;
-        compare_or_overwrite(action, stdout, &test_case.expected)?;
+        if test_case.check_output {
+            compare_or_overwrite(action, stdout, &test_case.expected)?;
+        }
+        if let Some(check_dir) = test_case.check_dir {
+            check_dir(&test_case.dir)?;
+        }
     } else {
         let stderr = String::from_utf8(output.stderr.clone())?;
         bail!("Compilation failed: {stderr}")
@@ -50,6 +63,60 @@ fn perform_test(test_case: &Case, action: Action) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// The files of `dir` whose name starts with `prefix` and ends with `suffix`.
+fn files_like(dir: &Path, prefix: &str, suffix: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap().to_string_lossy();
+        if name.starts_with(prefix) && name.ends_with(suffix) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// We build like `cargo check`: the dependencies only get their metadata, not their code.
+fn check_profile(dir: &Path) -> anyhow::Result<()> {
+    let deps = dir.join("target/charon/debug/deps");
+    if files_like(&deps, "libtake_mut-", ".rmeta")?.is_empty() {
+        bail!("The metadata of the dependency was not generated")
+    }
+    let rlibs = files_like(&deps, "libtake_mut-", ".rlib")?;
+    if !rlibs.is_empty() {
+        bail!("The dependency was compiled to {rlibs:?}")
+    }
+    Ok(())
+}
+
+/// The dependency is translated to its own file, with the body of its functions.
+fn check_extract_dependencies(dir: &Path) -> anyhow::Result<()> {
+    let path = dir.join("target/llbc/take_mut.llbc");
+    let translated = charon_lib::import::read_crate_data(&path)?.translated;
+    if translated.crate_name != "take_mut" {
+        bail!("Unexpected crate name: {}", translated.crate_name)
+    }
+    let has_bodies = translated
+        .fun_decls
+        .iter()
+        .any(|decl| decl.item_meta.is_local && decl.body.is_ok());
+    if !has_bodies {
+        bail!("The functions of the dependency were not translated")
+    }
+    Ok(())
+}
+
+/// We build in the directory given with `--target-dir`, not in the default `target/charon`.
+fn check_target_dir(dir: &Path) -> anyhow::Result<()> {
+    if !dir.join("target/custom/debug").is_dir() {
+        bail!("Nothing was built in the target directory")
+    }
+    if dir.join("target/charon").exists() {
+        bail!("The default target directory was used")
+    }
+    Ok(())
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let action = if std::env::var("IN_CI").as_deref() == Ok("1") {
         Action::Verify
@@ -58,26 +125,63 @@ fn main() -> Result<(), Box<dyn Error>> {
     };
 
     let root: PathBuf = PathBuf::from(TESTS_DIR).canonicalize()?;
-    let mktest = |name: &str, dir: PathBuf, charon_args: &[String]| {
+    let mktest = |name: &str,
+                  dir: PathBuf,
+                  charon_args: &[String],
+                  check_output: bool,
+                  check_dir: Option<fn(&Path) -> anyhow::Result<()>>| {
         let charon_args = charon_args.to_vec();
         let expected = root.join(format!("{name}.out"));
         Trial::test(name, move || {
             let case = Case {
                 dir,
                 expected,
+                check_output,
                 charon_args,
+                check_dir,
             };
             perform_test(&case, action).map_err(|err| err.into())
         })
     };
     let tests = vec![
-        mktest("build-script", root.join("build-script"), &[]),
+        mktest("build-script", root.join("build-script"), &[], true, None),
+        mktest(
+            "check-profile",
+            root.join("check-profile"),
+            &[],
+            true,
+            Some(check_profile),
+        ),
         mktest(
             "dependencies",
             root.join("dependencies"),
             &["--cargo-arg=--features=test_feature".to_owned()],
+            true,
+            None,
+        ),
+        mktest(
+            "extract-dependencies",
+            root.join("extract-dependencies"),
+            &[
+                "--extract-dependencies=take_mut".to_owned(),
+                format!(
+                    "--dest-dir={}",
+                    root.join("extract-dependencies/target/llbc").display()
+                ),
+            ],
+            // The dependency is printed too, interleaved with the main crate as cargo sees fit.
+            false,
+            Some(check_extract_dependencies),
+        ),
+        mktest(
+            "target-dir",
+            root.join("target-dir"),
+            // Inside `target` so that `cargo clean` removes it.
+            &["--target-dir=target/custom".to_owned()],
+            true,
+            Some(check_target_dir),
         ),
-        mktest("toml", root.join("toml"), &[]),
+        mktest("toml", root.join("toml"), &[], true, None),
         mktest(
             "workspace",
             root.join("workspace"),
@@ -85,6 +189,8 @@ fn main() -> Result<(), Box<dyn Error>> {
                 "--cargo-arg=--package=crate2".to_owned(),
                 "--extract-opaque-bodies".to_owned(),
             ],
+            true,
+            None,
         ),
     ];
 
@@ -0,0 +1,10 @@
+//! This tests the translation of a dependency along with the main crate.
+fn silly_incr(x: &mut u32) {
+    take_mut::take(x, |y| y + 1);
+}
+
+fn main() {
+    let mut x = 0;
+    silly_incr(&mut x);
+    assert_eq!(x, 1);
+}
@@ -0,0 +1,2 @@
+//! This tests that `charon` builds in the target directory given with `--target-dir`.
+fn main() {}
@@ -1,5 +1,6 @@
 #![feature(rustc_private)]
 use charon_lib::ast::{AnyTransItem, TranslatedCrate};
+use derive_visitor::Drive;
 use itertools::Itertools;
 use std::collections::HashMap;
 
@@ -11,6 +12,13 @@ fn translate(code: impl std::fmt::Display) -> anyhow::Result<TranslatedCrate> {
     util::translate_rust_text(code)
 }
 
+fn translate_with_args(
+    code: impl std::fmt::Display,
+    args: &[&str],
+) -> anyhow::Result<TranslatedCrate> {
+    util::translate_rust_text_with_args(code, args)
+}
+
 /// `Name` is a complex datastructure; to inspect it we serialize it a little bit.
 fn repr_name(crate_data: &TranslatedCrate, n: &Name) -> String {
     n.name
@@ -18,14 +26,15 @@ fn repr_name(crate_data: &TranslatedCrate, n: &Name) -> String {
         .map(|path_elem| match path_elem {
             PathElem::Ident(i, _) => i.clone(),
             PathElem::Impl(elem, _) => match elem {
-                ImplElem::Trait(impl_id) => match crate_data.trait_impls.get(*impl_id) {
-                    None => format!("<trait impl#{impl_id}>"),
-                    Some(timpl) => {
-                        let trait_name = trait_name(crate_data, timpl.impl_trait.trait_id);
-                        format!("<impl for {trait_name}>")
-                    }
-                },
+                ImplElem::Trait(_, _, impl_trait) => {
+                    let trait_name = trait_name(crate_data, impl_trait.trait_id);
+                    format!("<impl for {trait_name}>")
+                }
                 ImplElem::Ty(..) => "<inherent impl>".to_string(),
+                ImplElem::Builtin(impl_trait) => {
+                    let trait_name = trait_name(crate_data, impl_trait.trait_id);
+                    format!("<builtin impl for {trait_name}>")
+                }
             },
         })
         .join("::")
@@ -388,6 +397,50 @@ fn visibility() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn visibility_and_stability() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        pub struct Pub;
+        pub(crate) struct Crate;
+        struct Priv;
+        mod private {
+            pub(super) struct Super;
+        }
+        fn foo() -> Option<u32> {
+            let x: Option<u32> = None;
+            x.as_ref().copied()
+        }
+        "#,
+    )?;
+    let visibilities: Vec<_> = crate_data
+        .type_decls
+        .iter()
+        .filter(|decl| decl.item_meta.is_local)
+        .map(|decl| decl.item_meta.visibility)
+        .collect();
+    assert_eq!(
+        visibilities,
+        vec![
+            Visibility::Public,
+            Visibility::Crate,
+            Visibility::Private,
+            Visibility::Private
+        ]
+    );
+    for decl in &crate_data.type_decls {
+        assert_eq!(decl.item_meta.stability.is_some(), !decl.item_meta.is_local);
+    }
+    let option = crate_data
+        .type_decls
+        .iter()
+        .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == "core::option::Option")
+        .unwrap();
+    let stability = option.item_meta.stability.as_ref().unwrap();
+    assert!(!stability.unstable);
+    Ok(())
+}
+
 #[test]
 fn discriminants() -> anyhow::Result<()> {
     let crate_data = translate(
@@ -615,6 +668,53 @@ fn declaration_groups() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn declaration_group_kinds() -> anyhow::Result<()> {
+    use charon_lib::reorder_decls::GroupKind;
+    let crate_data = translate(
+        r#"
+        fn even(n: u32) -> bool {
+            if n == 0 { true } else { odd(n - 1) }
+        }
+        fn odd(n: u32) -> bool {
+            if n == 0 { false } else { even(n - 1) }
+        }
+        fn main() {
+            let _ = even(2);
+        }
+        "#,
+    )?;
+    let fun_id = |name: &str| {
+        crate_data
+            .fun_decls
+            .iter()
+            .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == name)
+            .unwrap()
+            .def_id
+    };
+    let even = AnyTransId::Fun(fun_id("test_crate::even"));
+    let main = AnyTransId::Fun(fun_id("test_crate::main"));
+
+    let kinds: Vec<GroupKind> = crate_data.decl_groups().map(|(kind, _)| kind).collect();
+    assert_eq!(kinds, vec![GroupKind::Rec, GroupKind::NonRec]);
+    assert_eq!(crate_data.group_of(even).unwrap().get_ids().len(), 2);
+    assert_eq!(crate_data.group_of(main).unwrap().kind(), GroupKind::NonRec);
+
+    // The bodies are numbered in the order of the declarations.
+    let body_ids: Vec<BodyId> = crate_data
+        .decl_groups()
+        .flat_map(|(_, group)| group.get_ids())
+        .filter_map(|id| match id {
+            AnyTransId::Fun(id) => crate_data.fun_decls[id].body.ok(),
+            _ => None,
+        })
+        .collect();
+    let expected: Vec<BodyId> = crate_data.bodies.iter_indices().collect();
+    assert_eq!(body_ids, expected);
+
+    Ok(())
+}
+
 #[test]
 fn source_text() -> anyhow::Result<()> {
     let crate_data = translate(
@@ -653,3 +753,3389 @@ fn source_text() -> anyhow::Result<()> {
     assert_eq!(sources[5], "impl Trait for () {}");
     Ok(())
 }
+
+#[test]
+fn inline_const() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        fn foo() -> u32 {
+            const { 1 + 2 }
+        }
+        "#,
+    )?;
+
+    let globals = crate_data
+        .global_decls
+        .iter()
+        .map(|g| repr_name(&crate_data, &g.item_meta.name))
+        .collect_vec();
+    assert_eq!(globals, vec!["test_crate::foo::{constant}"]);
+    let global = crate_data.global_decls.iter().next().unwrap();
+    assert!(global.ty.kind().is_literal());
+    Ok(())
+}
+
+#[test]
+fn reconstruct_matches() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        fn foo(x: Option<Result<u32, u32>>) -> u32 {
+            match x {
+                Some(Ok(_)) => 0,
+                Some(Err(_)) => 1,
+                None => 2,
+            }
+        }
+        "#,
+        &["--reconstruct-matches"],
+    )?;
+
+    let body_id = crate_data.fun_decls.iter().next().unwrap().body.unwrap();
+    let body = crate_data.bodies[body_id].as_structured().unwrap();
+    let mut nested_matches = Vec::new();
+    body.body.drive(&mut derive_visitor::visitor_enter_fn(
+        |st: &llbc_ast::Switch| {
+            if let llbc_ast::Switch::NestedMatch(_, arms, otherwise, _) = st {
+                nested_matches.push((arms.len(), otherwise.is_some()));
+            }
+        },
+    ));
+    assert_eq!(nested_matches, vec![(3, false)]);
+    Ok(())
+}
+
+#[test]
+fn preserve_discriminant_reads() -> anyhow::Result<()> {
+    let code = r#"
+        enum E { A, B }
+        fn foo(e: E) -> isize {
+            e as isize
+        }
+    "#;
+    let count_discriminant_reads = |crate_data: &TranslatedCrate| {
+        let body_id = crate_data.fun_decls.iter().next().unwrap().body.unwrap();
+        let body = crate_data.bodies[body_id].as_structured().unwrap();
+        let mut count = 0;
+        body.body
+            .drive(&mut derive_visitor::visitor_enter_fn(|rv: &Rvalue| {
+                if let Rvalue::Discriminant(..) = rv {
+                    count += 1;
+                }
+            }));
+        count
+    };
+
+    let crate_data = translate(code)?;
+    assert_eq!(count_discriminant_reads(&crate_data), 0);
+    let crate_data = translate_with_args(code, &["--preserve-discriminant-reads"])?;
+    assert_eq!(count_discriminant_reads(&crate_data), 1);
+    Ok(())
+}
+
+#[test]
+fn reconstruct_let_shapes() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        fn if_let(x: Option<u32>) -> u32 {
+            if let Some(y) = x { y } else { 0 }
+        }
+        fn let_else(x: Option<u32>) -> u32 {
+            let Some(y) = x else { return 0 };
+            y
+        }
+        fn if_let_with_statements(x: Option<u32>) -> u32 {
+            if let Some(y) = x {
+                y
+            } else {
+                let z = 1;
+                z
+            }
+        }
+        fn let_else_panic(x: Option<u32>) -> u32 {
+            let Some(y) = x else { panic!() };
+            y
+        }
+        fn let_else_branches(x: Option<u32>, b: bool) -> u32 {
+            let Some(y) = x else {
+                if b {
+                    return 1;
+                } else {
+                    return 2;
+                }
+            };
+            y
+        }
+        fn while_let(mut x: Option<u32>) -> u32 {
+            let mut n = 0;
+            while let Some(y) = x {
+                n = y;
+                x = None;
+            }
+            n
+        }
+        "#,
+        &["--reconstruct-matches"],
+    )?;
+
+    let kinds = crate_data
+        .fun_decls
+        .iter()
+        .filter(|decl| decl.item_meta.is_local)
+        .map(|decl| {
+            let body = crate_data.bodies[decl.body.unwrap()]
+                .as_structured()
+                .unwrap();
+            let mut kinds = Vec::new();
+            body.body.drive(&mut derive_visitor::visitor_enter_fn(
+                |st: &llbc_ast::Switch| {
+                    if let llbc_ast::Switch::NestedMatch(.., kind) = st {
+                        kinds.push(*kind);
+                    }
+                },
+            ));
+            kinds
+        })
+        .collect_vec();
+    assert_eq!(
+        kinds,
+        vec![
+            vec![llbc_ast::MatchKind::IfLet],
+            vec![llbc_ast::MatchKind::LetElse],
+            // The `else` branch doesn't diverge: this can't be a `let ... else`.
+            vec![llbc_ast::MatchKind::IfLet],
+            vec![llbc_ast::MatchKind::LetElse],
+            vec![llbc_ast::MatchKind::LetElse],
+            vec![llbc_ast::MatchKind::WhileLet],
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn cfg_info() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        fn nested_loops(n: u32) -> u32 {
+            let mut sum = 0;
+            let mut i = 0;
+            while i < n {
+                let mut j = 0;
+                while j < i {
+                    sum += j;
+                    j += 1;
+                }
+                i += 1;
+            }
+            sum
+        }
+        "#,
+        &["--ullbc"],
+    )?;
+
+    let decl = crate_data.fun_decls.iter().next().unwrap();
+    let body = crate_data.bodies[decl.body.unwrap()]
+        .as_unstructured()
+        .unwrap();
+    let cfg = body.compute_cfg_info();
+
+    // The predecessors and successors are consistent.
+    for (bid, succs) in cfg.successors.iter_indexed_values() {
+        for succ in succs {
+            assert!(cfg.predecessors[*succ].contains(&bid));
+        }
+    }
+    // The start block dominates everything.
+    for bid in body.body.iter_indices() {
+        assert!(cfg.dominates(ullbc_ast::START_BLOCK_ID, bid));
+    }
+    // We have two loops, one nested in the other.
+    assert_eq!(cfg.loop_headers.len(), 2);
+    let (outer, inner) = cfg
+        .loop_headers
+        .iter()
+        .copied()
+        .partition::<Vec<_>, _>(|header| cfg.parent_loop[header].is_none());
+    let ([outer], [inner]) = (&outer[..], &inner[..]) else {
+        panic!("expected one loop nested in the other")
+    };
+    let (outer, inner) = (*outer, *inner);
+    assert_eq!(cfg.parent_loop[&inner], Some(outer));
+    assert_eq!(cfg.loop_depth(inner), 2);
+    assert!(cfg.dominates(outer, inner));
+    Ok(())
+}
+
+#[test]
+fn borrow_liveness() -> anyhow::Result<()> {
+    let code = r#"
+        fn f(x: u32) -> u32 {
+            let y = &x;
+            let z = *y + 1;
+            z
+        }
+        "#;
+    // The borrow `&x` is last used when reading `*y`, after it is created. The facts are listed
+    // in the order of the statements.
+    let check = |facts: Vec<BorrowLiveness>| {
+        let created = facts
+            .iter()
+            .positions(|f| f.created.is_some())
+            .collect_vec();
+        let last_uses = facts
+            .iter()
+            .positions(|f| !f.last_uses.is_empty())
+            .collect_vec();
+        let ([created], [last_use]) = (created.as_slice(), last_uses.as_slice()) else {
+            panic!("expected a single borrow with a single last use, got {facts:?}")
+        };
+        assert_eq!(
+            facts[*last_use].last_uses,
+            facts[*created].created.into_iter().collect_vec()
+        );
+        assert!(last_use > created);
+    };
+
+    let crate_data = translate_with_args(code, &["--ullbc", "--borrow-liveness"])?;
+    let decl = crate_data.fun_decls.iter().next().unwrap();
+    let body = crate_data.bodies[decl.body.unwrap()]
+        .as_unstructured()
+        .unwrap();
+    let facts = body
+        .body
+        .iter()
+        .flat_map(|block| {
+            let statements = block.statements.iter().map(|st| &st.borrow_liveness);
+            statements.chain([&block.terminator.borrow_liveness])
+        })
+        .map(|facts| facts.clone().unwrap())
+        .collect_vec();
+    check(facts);
+
+    // The facts are kept by the control-flow reconstruction.
+    let crate_data = translate_with_args(code, &["--borrow-liveness"])?;
+    let decl = crate_data.fun_decls.iter().next().unwrap();
+    let body = crate_data.bodies[decl.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    let mut facts = vec![];
+    body.body.drive(&mut derive_visitor::visitor_enter_fn(
+        |st: &llbc_ast::Statement| facts.extend(st.borrow_liveness.clone()),
+    ));
+    check(facts);
+    Ok(())
+}
+
+#[test]
+fn move_info() -> anyhow::Result<()> {
+    let code = r#"
+        fn f(x: String, n: u32) -> (String, u32) {
+            (x, n)
+        }
+        "#;
+    // `x` is moved and `n` is copied.
+    let check = |locals: &charon_lib::ids::Vector<VarId, Var>, infos: Vec<MoveInfo>| {
+        let local = |name: &str| {
+            locals
+                .iter()
+                .find(|var| var.name.as_deref() == Some(name))
+                .unwrap()
+                .index
+        };
+        let (x, n) = (local("x"), local("n"));
+        assert!(infos.iter().any(|info| info.moved.contains(&x)));
+        assert!(!infos.iter().any(|info| info.copied.contains(&x)));
+        assert!(!infos.iter().any(|info| info.moved.contains(&n)));
+        assert!(infos.iter().any(|info| info.copied.contains(&n)));
+    };
+
+    let crate_data = translate_with_args(code, &["--ullbc", "--move-info"])?;
+    let decl = crate_data.fun_decls.iter().next().unwrap();
+    let body = crate_data.bodies[decl.body.unwrap()]
+        .as_unstructured()
+        .unwrap();
+    let infos = body
+        .body
+        .iter()
+        .flat_map(|block| {
+            let statements = block.statements.iter().map(|st| &st.move_info);
+            statements.chain([&block.terminator.move_info])
+        })
+        .map(|info| info.clone().unwrap())
+        .collect_vec();
+    check(&body.locals, infos);
+
+    // The facts are kept by the control-flow reconstruction.
+    let crate_data = translate_with_args(code, &["--move-info"])?;
+    let decl = crate_data.fun_decls.iter().next().unwrap();
+    let body = crate_data.bodies[decl.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    let mut infos = vec![];
+    body.body.drive(&mut derive_visitor::visitor_enter_fn(
+        |st: &llbc_ast::Statement| infos.extend(st.move_info.clone()),
+    ));
+    check(&body.locals, infos);
+    Ok(())
+}
+
+#[test]
+fn debug_info() -> anyhow::Result<()> {
+    let code = r#"
+        fn f(p: (u32, u32)) -> u32 {
+            let (a, b) = p;
+            {
+                let c = a;
+                c + b
+            }
+        }
+        "#;
+
+    // The debug info is opt-in.
+    let crate_data = translate(code)?;
+    let decl = crate_data.fun_decls.iter().next().unwrap();
+    let body = crate_data.bodies[decl.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    assert!(body.debug_info.is_none());
+
+    let crate_data = translate_with_args(code, &["--debug-info"])?;
+    let decl = crate_data.fun_decls.iter().next().unwrap();
+    let body = crate_data.bodies[decl.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    let debug_info = body.debug_info.as_ref().unwrap();
+    let var = |name: &str| debug_info.vars.iter().find(|var| var.name == name).unwrap();
+    assert_eq!(var("p").argument_index, Some(1));
+    assert_eq!(var("a").argument_index, None);
+    // `c` is declared in a scope nested in the scope of `a`.
+    let mut scope = Some(var("c").scope);
+    let mut ancestors = Vec::new();
+    while let Some(id) = scope {
+        ancestors.push(id);
+        scope = debug_info.scopes[id].parent;
+    }
+    assert!(ancestors[1..].contains(&var("a").scope));
+    Ok(())
+}
+
+#[test]
+fn debug_info_unused_locals() -> anyhow::Result<()> {
+    // The overflow check of the addition uses a local which we remove, which shifts the ids of
+    // the locals after it.
+    let code = r#"
+        fn f(x: u32) -> u32 {
+            let y = x + 1;
+            let z = y;
+            z
+        }
+        "#;
+    let crate_data = translate_with_args(code, &["--debug-info"])?;
+    let decl = crate_data.fun_decls.iter().next().unwrap();
+    let body = crate_data.bodies[decl.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    let debug_info = body.debug_info.as_ref().unwrap();
+    for name in ["x", "y", "z"] {
+        let var = debug_info.vars.iter().find(|var| var.name == name).unwrap();
+        let DebugValue::Place(var_id, projection) = &var.value else {
+            panic!("unexpected value for `{name}`: {:?}", var.value)
+        };
+        assert!(projection.is_empty());
+        assert_eq!(body.locals[*var_id].name.as_deref(), Some(name));
+    }
+    Ok(())
+}
+
+#[test]
+fn statement_ranges() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        fn f(x: u32) -> u32 {
+            let y = x.wrapping_add(1);
+            y
+        }
+        "#,
+        &["--statement-ranges"],
+    )?;
+
+    let decl = crate_data.fun_decls.iter().next().unwrap();
+    let body = crate_data.bodies[decl.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    let mut texts = vec![];
+    body.body.drive(&mut derive_visitor::visitor_enter_fn(
+        |st: &llbc_ast::Statement| {
+            let range = st.byte_range.as_ref().unwrap();
+            let text = &crate_data.file_id_to_content[&range.file_id][range.beg..range.end];
+            texts.push((st.content.is_call(), text));
+        },
+    ));
+    assert!(
+        texts.contains(&(true, "x.wrapping_add(1)")),
+        "unexpected ranges: {texts:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn devirtualize_closure_calls() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        fn apply<F: Fn(u32) -> u32>(f: F, x: u32) -> u32 {
+            f(x)
+        }
+        fn add(x: u32, y: u32) -> u32 {
+            let add = |z| x + y + z;
+            add(1)
+        }
+        fn take(v: Vec<u32>) -> Vec<u32> {
+            let f = move || v;
+            f()
+        }
+        "#,
+        &["--devirtualize-closure-calls"],
+    )?;
+    let callees = |name: &str| {
+        let decl = crate_data
+            .fun_decls
+            .iter()
+            .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == name)
+            .unwrap();
+        let body = crate_data.bodies[decl.body.unwrap()]
+            .as_structured()
+            .unwrap();
+        let mut callees = Vec::new();
+        body.body.drive(&mut derive_visitor::visitor_enter_fn(
+            |call: &llbc_ast::Call| {
+                if let FnOperand::Regular(fn_ptr) = &call.func {
+                    callees.push(fn_ptr.func.clone());
+                }
+            },
+        ));
+        callees
+    };
+    let is_closure_call = |func: &FunIdOrTraitMethodRef| match func {
+        FunIdOrTraitMethodRef::Fun(FunId::Regular(id)) => {
+            crate_data.fun_decls[*id].signature.is_closure
+        }
+        _ => false,
+    };
+
+    // The closures built in the body are called directly.
+    assert!(callees("test_crate::add").iter().any(is_closure_call));
+    assert!(callees("test_crate::take").iter().any(is_closure_call));
+    // The closure given as argument is unknown.
+    let apply_callees = callees("test_crate::apply");
+    assert!(!apply_callees.iter().any(is_closure_call));
+    assert!(matches!(
+        apply_callees.as_slice(),
+        [FunIdOrTraitMethodRef::Trait(..)]
+    ));
+    Ok(())
+}
+
+#[test]
+fn async_builtins() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        use std::future::Future;
+        use std::pin::Pin;
+        use std::task::{Context, Poll, Waker};
+        struct Ready(u32);
+        impl Future for Ready {
+            type Output = u32;
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<u32> {
+                cx.waker().wake_by_ref();
+                Poll::Ready(self.get_mut().0)
+            }
+        }
+        fn run(fut: &mut Ready, waker: &Waker) -> Poll<u32> {
+            let mut cx = Context::from_waker(waker);
+            Pin::new(fut).poll(&mut cx)
+        }
+        "#,
+        &["--async-builtins"],
+    )?;
+    let builtin_callees = |name: &str| {
+        let decl = crate_data
+            .fun_decls
+            .iter()
+            .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == name)
+            .unwrap();
+        let body = crate_data.bodies[decl.body.unwrap()]
+            .as_structured()
+            .unwrap();
+        let mut callees = Vec::new();
+        body.body.drive(&mut derive_visitor::visitor_enter_fn(
+            |call: &llbc_ast::Call| {
+                if let FnOperand::Regular(FnPtr {
+                    func: FunIdOrTraitMethodRef::Fun(FunId::Builtin(BuiltinFunId::Async(op))),
+                    generics,
+                }) = &call.func
+                {
+                    callees.push((*op, generics.trait_refs.len()));
+                }
+            },
+        ));
+        callees
+    };
+
+    use AsyncBuiltin::*;
+    assert_eq!(
+        builtin_callees("test_crate::run"),
+        vec![(ContextFromWaker, 0), (PinNew, 0), (FuturePoll, 1)]
+    );
+    assert_eq!(
+        builtin_callees("test_crate::<impl for Future>::poll"),
+        vec![(ContextWaker, 0), (WakerWakeByRef, 0), (PinGetMut, 0)]
+    );
+    Ok(())
+}
+
+#[test]
+fn unpin_and_pin_projections() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        use std::future::Future;
+        use std::marker::PhantomPinned;
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
+        struct Plain(u32);
+        struct Wrapper<T>(T);
+        struct Pinned(PhantomPinned);
+        struct Map<F> {
+            inner: F,
+        }
+        impl<F: Future> Future for Map<F> {
+            type Output = F::Output;
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+                let inner = unsafe { self.map_unchecked_mut(|this| &mut this.inner) };
+                inner.poll(cx)
+            }
+        }
+        "#,
+        &["--async-builtins"],
+    )?;
+    let unpin = |name: &str| {
+        crate_data
+            .type_decls
+            .iter()
+            .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == name)
+            .unwrap()
+            .unpin
+    };
+    assert_eq!(unpin("test_crate::Plain"), Some(UnpinKind::Unpin));
+    assert_eq!(
+        unpin("test_crate::Wrapper"),
+        Some(UnpinKind::UnpinIfParamsUnpin)
+    );
+    assert_eq!(unpin("test_crate::Pinned"), Some(UnpinKind::NotUnpin));
+
+    let poll = crate_data
+        .fun_decls
+        .iter()
+        .find(|decl| {
+            repr_name(&crate_data, &decl.item_meta.name) == "test_crate::<impl for Future>::poll"
+        })
+        .unwrap();
+    let body = crate_data.bodies[poll.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    let mut builtins = Vec::new();
+    body.body.drive(&mut derive_visitor::visitor_enter_fn(
+        |call: &llbc_ast::Call| {
+            if let FnOperand::Regular(FnPtr {
+                func: FunIdOrTraitMethodRef::Fun(FunId::Builtin(BuiltinFunId::Async(op))),
+                ..
+            }) = &call.func
+            {
+                builtins.push(*op);
+            }
+        },
+    ));
+    assert_eq!(
+        builtins,
+        vec![AsyncBuiltin::PinMapUncheckedMut, AsyncBuiltin::FuturePoll]
+    );
+    Ok(())
+}
+
+#[test]
+fn version_mismatch() -> anyhow::Result<()> {
+    use charon_lib::export::{BuildInfo, CrateData};
+    use charon_lib::import::crate_data_from_slice;
+    let translated = translate("fn foo() {}")?;
+    let mut crate_data = CrateData {
+        charon_version: env!("CARGO_PKG_VERSION").to_owned(),
+        build_info: BuildInfo::current(),
+        translated,
+        has_errors: false,
+    };
+    let bytes = serde_json::to_vec(&crate_data)?;
+    assert!(crate_data_from_slice(&bytes).is_ok());
+
+    crate_data.charon_version = "0.0.1".to_owned();
+    crate_data.build_info = BuildInfo {
+        charon_commit: Some("0123abcd".to_owned()),
+        rustc_version: Some("rustc 1.0.0".to_owned()),
+    };
+    let bytes = serde_json::to_vec(&crate_data)?;
+    let err = crate_data_from_slice(&bytes).unwrap_err().to_string();
+    assert!(err.contains(&format!("charon v{}", env!("CARGO_PKG_VERSION"))));
+    assert!(err.contains("charon v0.0.1"));
+    assert!(err.contains("commit 0123abcd, rustc 1.0.0"));
+    Ok(())
+}
+
+#[test]
+fn ocaml_export_profile() -> anyhow::Result<()> {
+    use charon_lib::export::ocaml;
+    use serde_json::json;
+    assert_eq!(ocaml::to_value(&RefKind::Mut)?, json!(["Mut"]));
+    assert_eq!(
+        ocaml::to_value(&Literal::Bool(true))?,
+        json!(["Bool", true])
+    );
+
+    let crate_data = translate("fn f(x: Option<u32>) -> u32 { x.unwrap_or(0) }")?;
+    let value = ocaml::to_value(&crate_data)?;
+    let decl = &value["fun_decls"][0];
+    assert_eq!(decl["kind"], json!(["Regular"]));
+    Ok(())
+}
+
+#[test]
+fn lower_to_flat_ir() -> anyhow::Result<()> {
+    use charon_lib::lower;
+    let crate_data = translate(
+        r#"
+        struct Point { x: u32, y: u32 }
+        fn incr(p: &mut Point) {
+            p.x = p.x.wrapping_add(1);
+        }
+        fn f() -> u32 {
+            let mut p = Point { x: 0, y: 1 };
+            incr(&mut p);
+            p.y
+        }
+        fn id<T>(x: T) -> T {
+            x
+        }
+        "#,
+    )?;
+    let program = lower::lower_crate(&crate_data);
+    assert!(program
+        .structs
+        .iter()
+        .any(|s| s.name == "test_crate::Point"));
+    let f = program
+        .functions
+        .iter()
+        .find(|f| f.name == "test_crate::f")
+        .unwrap();
+    // `p` lives in memory because we borrow it.
+    let body = f.body.as_ref().unwrap();
+    assert!(body
+        .locals
+        .iter()
+        .any(|local| local.name.as_deref() == Some("p") && local.in_memory));
+    assert!(program
+        .unsupported
+        .iter()
+        .any(|item| item.name == "test_crate::id"));
+    Ok(())
+}
+
+#[test]
+fn mangle_names() -> anyhow::Result<()> {
+    use charon_lib::names::{demangle, mangle, mangled_file_name};
+    let crate_data = translate(
+        r#"
+        struct Foo<T>(T);
+        impl<T> Foo<T> {
+            fn new(x: T) -> Self { Foo(x) }
+        }
+        impl Foo<u32> {
+            fn get(&self) -> u32 { self.0 }
+        }
+        impl<T: Clone> Clone for Foo<T> {
+            fn clone(&self) -> Self { Foo(self.0.clone()) }
+        }
+        "#,
+    )?;
+    for (_, item) in crate_data.all_items_with_ids() {
+        let name = &item.item_meta().name;
+        let mangled = mangle(name);
+        assert!(
+            mangled
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_.@-".contains(c)),
+            "unexpected character in `{mangled}`"
+        );
+        assert_eq!(&demangle(&mangled).unwrap(), name);
+        assert!(mangled_file_name(name, "llbc").len() <= 255);
+    }
+    Ok(())
+}
+
+#[test]
+fn reexports() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        mod private {
+            pub struct Hidden;
+        }
+        pub mod ser {
+            pub trait Serialize {}
+        }
+        pub use private::Hidden;
+        pub use ser::Serialize;
+        pub use ser as serialization;
+        "#,
+    )?;
+    let serialize = crate_data.trait_decls.iter().next().unwrap().def_id;
+    let mut paths: Vec<_> = crate_data
+        .item_paths(serialize)
+        .map(|name| repr_name(&crate_data, name))
+        .collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![
+            "test_crate::Serialize",
+            "test_crate::ser::Serialize",
+            "test_crate::serialization::Serialize",
+        ]
+    );
+    let hidden = crate_data.type_decls.iter().next().unwrap().def_id;
+    let paths: Vec<_> = crate_data
+        .item_paths(hidden)
+        .map(|name| repr_name(&crate_data, name))
+        .collect();
+    assert_eq!(
+        paths,
+        vec!["test_crate::private::Hidden", "test_crate::Hidden"]
+    );
+
+    let pattern = charon_lib::name_matcher::NamePattern::parse("test_crate::Hidden").unwrap();
+    assert!(!pattern.matches(&crate_data, &crate_data.type_decls[hidden].item_meta.name));
+    assert!(pattern.matches_item(&crate_data, hidden));
+    Ok(())
+}
+
+#[test]
+fn retain_items() -> anyhow::Result<()> {
+    let mut crate_data = translate(
+        r#"
+        struct Foo;
+        fn unused() {}
+        fn foo() -> Foo { Foo }
+        fn bar() -> Foo { foo() }
+        "#,
+    )?;
+    let unused = crate_data
+        .fun_decls
+        .iter()
+        .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == "test_crate::unused")
+        .unwrap()
+        .def_id;
+    let num_bodies = crate_data.bodies.len();
+    crate_data.retain(|id| id != AnyTransId::Fun(unused));
+
+    // The vectors are compacted and the ids updated.
+    assert_eq!(crate_data.fun_decls.len(), 2);
+    assert_eq!(crate_data.bodies.len(), num_bodies - 1);
+    assert!(crate_data.fun_decls.iter_all_slots().all(Option::is_some));
+    for (id, decl) in crate_data.fun_decls.iter_indexed() {
+        assert_eq!(decl.def_id, id);
+        assert!(crate_data.bodies.get(decl.body.unwrap()).is_some());
+    }
+    for group in crate_data.ordered_decls.as_ref().unwrap() {
+        for id in group.get_ids() {
+            assert!(crate_data.get_item(id).is_some());
+        }
+    }
+    // The call to `foo` in `bar` still refers to `foo`.
+    let output = crate_data.to_string();
+    assert!(!output.contains("unused"));
+    assert!(output.contains(":= test_crate::foo()"), "{output}");
+    Ok(())
+}
+
+#[test]
+fn failed_items() -> anyhow::Result<()> {
+    let mut crate_data = translate(
+        r#"
+        trait LendingIterator {
+            type Item<'a> where Self: 'a;
+        }
+        fn foo() {}
+        fn bar() {}
+        "#,
+    )?;
+    // The trait fails to translate, the other items are there.
+    assert_eq!(crate_data.failed_items.len(), 1);
+    let failure = &crate_data.failed_items[0];
+    assert!(matches!(failure.id, AnyTransId::TraitDecl(_)));
+    assert!(!failure.panicked);
+    assert!(
+        failure.message.contains("Generic associated types"),
+        "{}",
+        failure.message
+    );
+    assert!(crate_data.get_item(failure.id).is_none());
+    let items = items_by_name(&crate_data);
+    assert!(items.contains_key("test_crate::foo"));
+    assert!(items.contains_key("test_crate::bar"));
+
+    // The failures are remapped along with the items.
+    let foo = crate_data
+        .fun_decls
+        .iter()
+        .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == "test_crate::foo")
+        .unwrap()
+        .def_id;
+    crate_data.retain(|id| id != AnyTransId::Fun(foo));
+    assert_eq!(crate_data.failed_items.len(), 1);
+    let failed_id = crate_data.failed_items[0].id;
+    assert!(crate_data.get_item(failed_id).is_none());
+    let name = &crate_data.item_names[&failed_id];
+    assert_eq!(repr_name(&crate_data, name), "test_crate::LendingIterator");
+
+    // The failures which are neither kept nor referred to are removed.
+    crate_data.retain(|id| matches!(id, AnyTransId::Fun(_)));
+    assert!(crate_data.failed_items.is_empty());
+    Ok(())
+}
+
+#[test]
+fn validate() -> anyhow::Result<()> {
+    use charon_lib::validate::validate_crate;
+    use derive_visitor::{visitor_enter_fn_mut, DriveMut};
+    let mut crate_data = translate(
+        r#"
+        struct Wrapper<T>(T);
+        fn unwrap<T>(x: Wrapper<T>) -> T { x.0 }
+        fn id(x: u32) -> u32 { let y = unwrap(Wrapper(x)); y }
+        "#,
+    )?;
+    let errors = validate_crate(&crate_data);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    // Dereference all the places of the bodies: this is ill-typed since they don't contain
+    // pointers.
+    for body in crate_data.bodies.iter_mut() {
+        let body = body.as_structured_mut().unwrap();
+        body.body
+            .drive_mut(&mut visitor_enter_fn_mut(|place: &mut Place| {
+                place.projection.push(ProjectionElem::Deref)
+            }));
+    }
+    let errors = validate_crate(&crate_data);
+    assert!(!errors.is_empty());
+    assert!(
+        errors
+            .iter()
+            .all(|e| e.message.starts_with("ill-typed place")),
+        "{errors:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn type_check() -> anyhow::Result<()> {
+    let mut crate_data = translate(
+        r#"
+        struct Pair<T> { x: T, y: u32 }
+        fn first<T>(p: Pair<T>) -> T { p.x }
+        fn call(b: bool) -> u32 {
+            let p = Pair { x: 1u32, y: 2 };
+            if b { first(p) } else { p.y }
+        }
+        "#,
+    )?;
+    let errors = charon_lib::tyck::check_crate(&crate_data);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    // Change the type of the return value of `call`.
+    let call = crate_data
+        .fun_decls
+        .iter()
+        .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == "test_crate::call")
+        .unwrap();
+    let body_id = call.body.unwrap();
+    let body = crate_data.bodies[body_id].as_structured_mut().unwrap();
+    body.locals[VarId::ZERO].ty = TyKind::Literal(LiteralTy::Bool).into_ty();
+    let errors = charon_lib::tyck::check_crate(&crate_data);
+    assert!(!errors.is_empty());
+    assert!(
+        errors
+            .iter()
+            .all(|e| e.message.ends_with("but `bool` was expected")),
+        "{errors:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn linkage() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        #[no_mangle]
+        pub extern "C" fn exported() {}
+        #[export_name = "renamed"]
+        pub fn named() {}
+        pub fn regular() {}
+        #[no_mangle]
+        pub static FOO: u32 = 0;
+        pub const BAR: u32 = 0;
+        "#,
+    )?;
+    let fun_linkage = |name: &str| {
+        crate_data
+            .fun_decls
+            .iter()
+            .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == name)
+            .unwrap()
+            .linkage
+            .clone()
+    };
+    let global_linkage = |name: &str| {
+        crate_data
+            .global_decls
+            .iter()
+            .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == name)
+            .unwrap()
+            .linkage
+            .clone()
+    };
+    let exported = fun_linkage("test_crate::exported");
+    assert_eq!(exported.export_name.as_deref(), Some("exported"));
+    assert!(exported.no_mangle);
+    let named = fun_linkage("test_crate::named");
+    assert_eq!(named.export_name.as_deref(), Some("renamed"));
+    assert!(!named.no_mangle);
+    assert_eq!(fun_linkage("test_crate::regular"), Linkage::default());
+    assert_eq!(
+        global_linkage("test_crate::FOO").export_name.as_deref(),
+        Some("FOO")
+    );
+    assert_eq!(global_linkage("test_crate::BAR"), Linkage::default());
+    Ok(())
+}
+
+#[test]
+fn extern_statics() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        extern "C" {
+            static FOO: u32;
+            #[link_name = "real_bar"]
+            static mut BAR: u8;
+        }
+        "#,
+    )?;
+    let global = |name: &str| {
+        crate_data
+            .global_decls
+            .iter()
+            .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == name)
+            .unwrap()
+    };
+    let foo = global("test_crate::FOO");
+    assert!(foo.body.is_err());
+    assert!(!foo.is_mutable);
+    assert_eq!(foo.linkage.link_name.as_deref(), Some("FOO"));
+    assert_eq!(foo.linkage.foreign_abi.as_deref(), Some("C"));
+    let bar = global("test_crate::BAR");
+    assert!(bar.body.is_err());
+    assert!(bar.is_mutable);
+    assert_eq!(bar.linkage.link_name.as_deref(), Some("real_bar"));
+    assert_eq!(bar.linkage.foreign_abi.as_deref(), Some("C"));
+    Ok(())
+}
+
+#[test]
+fn runtime_handlers() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        #![no_std]
+        #![feature(alloc_error_handler)]
+        use core::alloc::{GlobalAlloc, Layout};
+        struct NoAlloc;
+        unsafe impl GlobalAlloc for NoAlloc {
+            unsafe fn alloc(&self, _: Layout) -> *mut u8 {
+                core::ptr::null_mut()
+            }
+            unsafe fn dealloc(&self, _: *mut u8, _: Layout) {}
+        }
+        #[global_allocator]
+        static ALLOCATOR: NoAlloc = NoAlloc;
+        #[panic_handler]
+        fn panic(_: &core::panic::PanicInfo) -> ! {
+            loop {}
+        }
+        #[alloc_error_handler]
+        fn oom(_: Layout) -> ! {
+            loop {}
+        }
+        "#,
+    )?;
+    let handlers = &crate_data.runtime_handlers;
+    let global_allocator = handlers.global_allocator.unwrap();
+    assert_eq!(
+        repr_name(&crate_data, crate_data.item_name(global_allocator).unwrap()),
+        "test_crate::ALLOCATOR"
+    );
+    let panic_handler = handlers.panic_handler.unwrap();
+    assert_eq!(
+        repr_name(&crate_data, crate_data.item_name(panic_handler).unwrap()),
+        "test_crate::panic"
+    );
+    let alloc_error_handler = handlers.alloc_error_handler.unwrap();
+    assert_eq!(
+        repr_name(
+            &crate_data,
+            crate_data.item_name(alloc_error_handler).unwrap()
+        ),
+        "test_crate::oom"
+    );
+
+    let crate_data = translate("fn foo() {}")?;
+    assert!(crate_data.runtime_handlers.global_allocator.is_none());
+    assert!(crate_data.runtime_handlers.panic_handler.is_none());
+    Ok(())
+}
+
+#[test]
+fn entry_points() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        #![feature(register_tool)]
+        #![register_tool(charon)]
+        fn helper() {}
+        #[charon::root]
+        fn annotated() {}
+        mod harness {
+            pub fn check() {}
+            pub fn other() {}
+        }
+        fn main() {
+            helper();
+            harness::check();
+            harness::other();
+            annotated();
+        }
+        "#,
+        &["--root", "crate::harness::check"],
+    )?;
+    let entry_points = crate_data
+        .entry_points
+        .iter()
+        .map(|id| repr_name(&crate_data, crate_data.item_name(*id).unwrap()))
+        .collect_vec();
+    assert_eq!(
+        entry_points,
+        vec![
+            "test_crate::main",
+            "test_crate::annotated",
+            "test_crate::harness::check"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn harnesses() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        #![feature(register_tool)]
+        #![register_tool(kani)]
+        #![register_tool(my_tool)]
+        #[kani::proof]
+        fn proof() {}
+        #[my_tool::check]
+        fn custom() {}
+        fn not_a_harness() {}
+        "#,
+        &["--harness-attr", "my_tool::check"],
+    )?;
+    let harnesses = crate_data
+        .harnesses
+        .iter()
+        .map(|id| repr_name(&crate_data, crate_data.item_name(*id).unwrap()))
+        .collect_vec();
+    assert_eq!(harnesses, vec!["test_crate::proof", "test_crate::custom"]);
+    let not_a_harness = crate_data
+        .fun_decls
+        .iter()
+        .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == "test_crate::not_a_harness")
+        .unwrap();
+    assert!(!not_a_harness.item_meta.is_harness);
+    Ok(())
+}
+
+#[test]
+fn per_module_output() -> anyhow::Result<()> {
+    use charon_lib::export::split::{item_module, ModuleIndex};
+    use charon_lib::export::{BuildInfo, CrateData};
+    use charon_lib::options::ExportProfile;
+    let crate_data = translate(
+        r#"
+        mod foo {
+            pub struct Foo;
+            pub trait Trait {
+                fn method(&self);
+            }
+            impl Trait for Foo {
+                fn method(&self) {}
+            }
+        }
+        mod bar {
+            pub fn use_foo(x: &super::foo::Foo) {
+                use super::foo::Trait;
+                x.method()
+            }
+        }
+        "#,
+    )?;
+    let module_of = |name: &str| {
+        let (id, _) = crate_data
+            .item_names
+            .iter()
+            .find(|(_, n)| repr_name(&crate_data, n) == name)
+            .unwrap();
+        repr_name(&crate_data, &item_module(&crate_data, *id).unwrap())
+    };
+    assert_eq!(module_of("test_crate::foo::Foo"), "test_crate::foo");
+    assert_eq!(
+        module_of("test_crate::foo::Trait::method"),
+        "test_crate::foo"
+    );
+    assert_eq!(
+        module_of("test_crate::foo::<impl for Trait>::method"),
+        "test_crate::foo"
+    );
+    assert_eq!(module_of("test_crate::bar::use_foo"), "test_crate::bar");
+
+    let crate_data = CrateData {
+        charon_version: env!("CARGO_PKG_VERSION").to_owned(),
+        build_info: BuildInfo::current(),
+        translated: crate_data,
+        has_errors: false,
+    };
+    let dir = tempfile::TempDir::new()?;
+    crate_data
+        .serialize_per_module(dir.path(), "llbc", ExportProfile::Default)
+        .unwrap();
+    let index: ModuleIndex =
+        serde_json::from_reader(std::fs::File::open(dir.path().join("index.json"))?)?;
+    let bar = index
+        .modules
+        .iter()
+        .find(|m| m.module == "test_crate::bar")
+        .unwrap();
+    assert_eq!(bar.items, vec!["test_crate::bar::use_foo"]);
+    assert!(bar.references["test_crate::foo"].contains("test_crate::foo::Foo"));
+    let bar_data: CrateData =
+        serde_json::from_reader(std::fs::File::open(dir.path().join(&bar.file))?)?;
+    assert_eq!(bar_data.translated.fun_decls.iter().count(), 1);
+    Ok(())
+}
+
+#[test]
+fn trait_impl_index() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        trait Trait {}
+        struct Foo;
+        impl Trait for Foo {}
+        impl Trait for u32 {}
+        impl<T> Trait for Option<T> {}
+        impl<T> Trait for &T {}
+        trait Other {}
+        impl<T> Other for T {}
+        "#,
+    )?;
+    let trait_id = |name: &str| {
+        crate_data
+            .trait_decls
+            .iter()
+            .find(|d| repr_name(&crate_data, &d.item_meta.name) == name)
+            .unwrap()
+            .def_id
+    };
+    let self_ty = |impl_id: &TraitImplId| {
+        crate_data.trait_impls[*impl_id].impl_trait.generics.types[TypeVarId::ZERO].clone()
+    };
+    let trait_id_ = trait_id("test_crate::Trait");
+    assert_eq!(crate_data.impls_of_trait(trait_id_).len(), 4);
+    let foo_id = crate_data
+        .type_decls
+        .iter()
+        .find(|d| repr_name(&crate_data, &d.item_meta.name) == "test_crate::Foo")
+        .unwrap()
+        .def_id;
+    let foo_impls =
+        crate_data.impls_for_self_head(trait_id_, Some(TypeHead::Adt(TypeId::Adt(foo_id))));
+    assert_eq!(foo_impls.len(), 1);
+    assert_eq!(
+        self_ty(&foo_impls[0]).head(),
+        Some(TypeHead::Adt(TypeId::Adt(foo_id)))
+    );
+    let u32_impls = crate_data.impls_for_self_head(
+        trait_id_,
+        Some(TypeHead::Literal(LiteralTy::Integer(IntegerTy::U32))),
+    );
+    assert_eq!(u32_impls.len(), 1);
+    let ref_impls = crate_data.impls_for_self_head(trait_id_, Some(TypeHead::Ref(RefKind::Shared)));
+    assert_eq!(ref_impls.len(), 1);
+
+    let other_id = trait_id("test_crate::Other");
+    assert_eq!(crate_data.impls_of_trait(other_id).len(), 1);
+    assert_eq!(crate_data.impls_for_self_head(other_id, None).len(), 1);
+    Ok(())
+}
+
+#[test]
+fn instantiate_blanket_impls() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        trait Foo {
+            fn foo(&self) -> u32;
+        }
+        impl Foo for u32 {
+            fn foo(&self) -> u32 {
+                *self
+            }
+        }
+        trait Bar {
+            fn bar(&self) -> u32;
+        }
+        impl<T: Foo> Bar for T {
+            fn bar(&self) -> u32 {
+                self.foo() + 1
+            }
+        }
+        fn use_bar(x: u32) -> u32 {
+            x.bar()
+        }
+        "#,
+        &["--instantiate-blanket-impls"],
+    )?;
+    let bar_id = crate_data
+        .trait_decls
+        .iter()
+        .find(|d| repr_name(&crate_data, &d.item_meta.name) == "test_crate::Bar")
+        .unwrap()
+        .def_id;
+    let u32_impls = crate_data.impls_for_self_head(
+        bar_id,
+        Some(TypeHead::Literal(LiteralTy::Integer(IntegerTy::U32))),
+    );
+    assert_eq!(u32_impls.len(), 1);
+    let timpl = &crate_data.trait_impls[u32_impls[0]];
+    assert!(timpl.generics.types.is_empty());
+    assert!(timpl.generics.trait_clauses.is_empty());
+
+    // The method is specialized too, and `use_bar` calls it through the specialized impl.
+    let (_, bar_method) = timpl.required_methods[0];
+    let bar_method = &crate_data.fun_decls[bar_method];
+    assert!(bar_method.signature.generics.types.is_empty());
+    let use_bar = crate_data
+        .fun_decls
+        .iter()
+        .find(|d| repr_name(&crate_data, &d.item_meta.name) == "test_crate::use_bar")
+        .unwrap();
+    let body = &crate_data.bodies[use_bar.body.unwrap()];
+    let mut impl_ids = Vec::new();
+    body.drive(&mut derive_visitor::visitor_enter_fn(
+        |kind: &TraitRefKind| {
+            if let TraitRefKind::TraitImpl(impl_id, _) = kind {
+                impl_ids.push(*impl_id);
+            }
+        },
+    ));
+    assert_eq!(impl_ids, vec![timpl.def_id]);
+    Ok(())
+}
+
+#[test]
+fn fn_ptr_call_sig() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        fn call(f: unsafe extern "C" fn(u32, bool) -> u64) -> u64 {
+            unsafe { f(0, true) }
+        }
+        "#,
+    )?;
+    let body_id = crate_data.fun_decls.iter().next().unwrap().body.unwrap();
+    let body = crate_data.bodies[body_id].as_structured().unwrap();
+    let mut sigs = Vec::new();
+    body.body
+        .drive(&mut derive_visitor::visitor_enter_fn(|call: &Call| {
+            if let FnOperand::Move(_) = call.func {
+                sigs.push(call.fn_ptr_sig.clone().unwrap());
+            }
+        }));
+    let [sig] = sigs.as_slice() else {
+        panic!("expected one call to a function pointer")
+    };
+    assert!(sig.is_unsafe);
+    assert!(sig.abi.starts_with('C'));
+    assert_eq!(sig.inputs.len(), 2);
+    assert_eq!(sig.inputs[1].kind(), &TyKind::Literal(LiteralTy::Bool));
+    assert_eq!(
+        sig.output.kind(),
+        &TyKind::Literal(LiteralTy::Integer(IntegerTy::U64))
+    );
+    Ok(())
+}
+
+#[test]
+fn mir_inlining() -> anyhow::Result<()> {
+    let code = r#"
+        #[inline(always)]
+        fn helper(x: u32) -> u32 {
+            x
+        }
+        fn foo(x: u32) -> u32 {
+            helper(x)
+        }
+    "#;
+    let crate_data = translate_with_args(code, &["--mir_optimized"])?;
+    assert_eq!(
+        crate_data.mir_inlining,
+        charon_lib::options::MirInlining::Off
+    );
+    let foo = crate_data
+        .fun_decls
+        .iter()
+        .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == "test_crate::foo")
+        .unwrap();
+    let body = crate_data.bodies[foo.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    let mut calls = 0;
+    body.body
+        .drive(&mut derive_visitor::visitor_enter_fn(|_: &Call| calls += 1));
+    assert_eq!(calls, 1);
+
+    let crate_data =
+        translate_with_args(code, &["--mir_optimized", "--mir-inlining", "aggressive"])?;
+    assert_eq!(
+        crate_data.mir_inlining,
+        charon_lib::options::MirInlining::Aggressive
+    );
+    Ok(())
+}
+
+#[test]
+fn promoted_consts() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        fn foo() -> &'static [u32; 3] {
+            &[1, 2, 3]
+        }
+        "#,
+        &["--mir_promoted"],
+    )?;
+    let promoted = crate_data
+        .global_decls
+        .iter()
+        .map(|global| repr_name(&crate_data, &global.item_meta.name))
+        .collect_vec();
+    assert_eq!(promoted, vec!["test_crate::foo::{promoted}"]);
+    let promoted_id = crate_data.global_decls.iter().next().unwrap().def_id;
+
+    let foo = crate_data.fun_decls.iter().next().unwrap();
+    let body = crate_data.bodies[foo.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    let mut uses = 0;
+    body.body.drive(&mut derive_visitor::visitor_enter_fn(
+        |global: &GlobalDeclRef| {
+            if global.id == promoted_id {
+                uses += 1;
+            }
+        },
+    ));
+    assert_eq!(uses, 1);
+    Ok(())
+}
+
+#[test]
+fn static_mutability() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        use std::sync::atomic::AtomicU32;
+        const C: u32 = 0;
+        static S: u32 = 0;
+        static mut M: u32 = 0;
+        static A: AtomicU32 = AtomicU32::new(0);
+        "#,
+    )?;
+    let flags = crate_data
+        .global_decls
+        .iter()
+        .map(|global| {
+            (
+                repr_name(&crate_data, &global.item_meta.name),
+                global.is_mutable,
+                global.has_interior_mutability,
+            )
+        })
+        .filter(|(name, ..)| name.starts_with("test_crate::"))
+        .collect_vec();
+    assert_eq!(
+        flags,
+        vec![
+            ("test_crate::C".to_string(), false, false),
+            ("test_crate::S".to_string(), false, false),
+            ("test_crate::M".to_string(), true, false),
+            ("test_crate::A".to_string(), false, true),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn extract_bodies_from() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        fn is_some(x: Option<u32>) -> bool {
+            x.is_some()
+        }
+        fn new_vec() -> Vec<u32> {
+            Vec::new()
+        }
+        "#,
+        &["--extract-bodies-from", "core"],
+    )?;
+    let has_body = |name: &str| {
+        crate_data
+            .fun_decls
+            .iter()
+            .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == name)
+            .unwrap()
+            .body
+            .is_ok()
+    };
+    assert!(has_body("test_crate::is_some"));
+    assert!(has_body("core::option::<inherent impl>::is_some"));
+    assert!(!has_body("alloc::vec::<inherent impl>::new"));
+    Ok(())
+}
+
+#[test]
+fn dependency_depth() -> anyhow::Result<()> {
+    let code = r#"
+        fn is_some(x: Option<u32>) -> bool {
+            x.is_some()
+        }
+    "#;
+    let is_some_has_body = |crate_data: &TranslatedCrate| {
+        crate_data
+            .fun_decls
+            .iter()
+            .find(|decl| {
+                repr_name(crate_data, &decl.item_meta.name)
+                    == "core::option::<inherent impl>::is_some"
+            })
+            .unwrap()
+            .body
+            .is_ok()
+    };
+
+    let crate_data = translate_with_args(
+        code,
+        &["--extract-bodies-from", "core", "--dependency-depth", "1"],
+    )?;
+    assert!(is_some_has_body(&crate_data));
+    let crate_data = translate_with_args(
+        code,
+        &["--extract-bodies-from", "core", "--dependency-depth", "0"],
+    )?;
+    assert!(!is_some_has_body(&crate_data));
+    Ok(())
+}
+
+#[test]
+fn provenance() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        struct Foo;
+        fn foo(x: Option<u32>) -> bool {
+            x.is_some()
+        }
+        "#,
+    )?;
+    let provenance = |name: &str| {
+        crate_data
+            .all_items()
+            .find(|item| repr_name(&crate_data, &item.item_meta().name) == name)
+            .unwrap()
+            .item_meta()
+            .provenance
+            .clone()
+    };
+
+    let foo = provenance("test_crate::foo");
+    assert_eq!(foo.crate_name, "test_crate");
+    assert!(foo.body_available);
+    assert!(!provenance("test_crate::Foo").body_available);
+    let option = provenance("core::option::Option");
+    assert_eq!(option.crate_name, "core");
+    assert_eq!(option.crate_version, None);
+    // `is_some` is inlinable, so its MIR is in the rlib of `core`.
+    assert!(provenance("core::option::<inherent impl>::is_some").body_available);
+    Ok(())
+}
+
+#[test]
+fn codegen_attrs() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        #[used]
+        #[link_section = ".vectors"]
+        static VECTORS: [u32; 2] = [0, 0];
+        #[cold]
+        #[track_caller]
+        fn cold() {}
+        #[target_feature(enable = "avx2")]
+        unsafe fn with_avx2() {}
+        fn regular() {}
+        "#,
+    )?;
+    let attrs = |name: &str| {
+        crate_data
+            .all_items()
+            .find(|item| repr_name(&crate_data, &item.item_meta().name) == name)
+            .unwrap()
+            .item_meta()
+            .codegen_attrs
+            .clone()
+    };
+
+    let vectors = attrs("test_crate::VECTORS");
+    assert!(vectors.used);
+    assert_eq!(vectors.link_section.as_deref(), Some(".vectors"));
+    let cold = attrs("test_crate::cold");
+    assert!(cold.cold);
+    assert!(cold.track_caller);
+    assert!(!cold.used);
+    assert!(attrs("test_crate::with_avx2")
+        .target_features
+        .contains(&"avx2".to_string()));
+    assert_eq!(attrs("test_crate::regular"), CodegenAttrs::default());
+    Ok(())
+}
+
+#[test]
+fn float_bits() -> anyhow::Result<()> {
+    // In optimized MIR, the uses of `f64::INFINITY` and `f32::NAN` are evaluated to literals.
+    let crate_data = translate_with_args(
+        r#"
+        fn half() -> f32 { 0.5 }
+        fn neg_zero() -> f64 { -0.0 }
+        fn inf() -> f64 { f64::INFINITY }
+        fn nan() -> f32 { f32::NAN }
+        "#,
+        &["--mir_optimized"],
+    )?;
+    let float = |name: &str| {
+        let fun = crate_data
+            .fun_decls
+            .iter()
+            .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == name)
+            .unwrap();
+        let body = crate_data.bodies[fun.body.unwrap()]
+            .as_structured()
+            .unwrap();
+        let mut floats = Vec::new();
+        body.body
+            .drive(&mut derive_visitor::visitor_enter_fn(|v: &FloatValue| {
+                floats.push(v.clone())
+            }));
+        floats.pop().unwrap()
+    };
+    assert_eq!(float("test_crate::half").bits, 0.5f32.to_bits() as u128);
+    assert_eq!(
+        float("test_crate::neg_zero").bits,
+        (-0.0f64).to_bits() as u128
+    );
+    assert_eq!(
+        float("test_crate::inf").bits,
+        f64::INFINITY.to_bits() as u128
+    );
+    let nan = float("test_crate::nan");
+    assert!(nan.is_nan());
+    assert_eq!(nan.bits, f32::NAN.to_bits() as u128);
+    Ok(())
+}
+
+#[test]
+fn binop_tys() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        fn shift(x: u128, y: u32) -> u128 { x << y }
+        fn add(x: i128, y: i128) -> i128 { x + y }
+        "#,
+    )?;
+    let mut binops = Vec::new();
+    for fun in &crate_data.fun_decls {
+        if !repr_name(&crate_data, &fun.item_meta.name).starts_with("test_crate::") {
+            continue;
+        }
+        let body = crate_data.bodies[fun.body.unwrap()]
+            .as_structured()
+            .unwrap();
+        body.body
+            .drive(&mut derive_visitor::visitor_enter_fn(|rv: &Rvalue| {
+                if let Rvalue::BinaryOp(op, _, _, tys) = rv {
+                    binops.push((*op, tys.clone()));
+                }
+            }));
+    }
+    let int_ty = |ty| TyKind::Literal(LiteralTy::Integer(ty)).into_ty();
+    let shl_tys = BinOpTys {
+        lhs_ty: int_ty(IntegerTy::U128),
+        rhs_ty: int_ty(IntegerTy::U32),
+    };
+    assert!(binops.contains(&(BinOp::Shl, shl_tys)));
+    let add_tys = BinOpTys {
+        lhs_ty: int_ty(IntegerTy::I128),
+        rhs_ty: int_ty(IntegerTy::I128),
+    };
+    assert!(binops.contains(&(BinOp::Add, add_tys)));
+    Ok(())
+}
+
+#[test]
+fn literal_type_suffix() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        fn f() -> (u32, u64) { (1u32, 2) }
+        "#,
+    )?;
+    let fun = &crate_data.fun_decls[0];
+    let body = crate_data.bodies[fun.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    let mut suffixes = Vec::new();
+    body.body
+        .drive(&mut derive_visitor::visitor_enter_fn(|c: &ConstantExpr| {
+            if let RawConstantExpr::Literal(Literal::Scalar(v)) = &c.value {
+                suffixes.push((v.to_bits(), c.has_type_suffix));
+            }
+        }));
+    assert_eq!(suffixes, vec![(1, Some(true)), (2, Some(false))]);
+    Ok(())
+}
+
+#[test]
+fn byte_and_c_strings() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        use std::ffi::CStr;
+        static BYTES: &[u8] = b"hi";
+        static C: &CStr = c"hi";
+        fn cstr() -> &'static CStr { c"a\nb" }
+        "#,
+    )?;
+    let mut literals = Vec::new();
+    for body in crate_data.bodies.iter() {
+        body.drive(&mut derive_visitor::visitor_enter_fn(|lit: &Literal| {
+            literals.push(lit.clone())
+        }));
+    }
+    assert!(literals.contains(&Literal::ByteStr(b"hi".to_vec())));
+    assert!(literals.contains(&Literal::CStr(b"hi\0".to_vec())));
+    assert!(literals.contains(&Literal::CStr(b"a\nb\0".to_vec())));
+    assert_eq!(Literal::CStr(b"a\nb\0".to_vec()).to_string(), r#"c"a\nb""#);
+    Ok(())
+}
+
+#[test]
+fn pattern_constants() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        #[derive(PartialEq, Eq)]
+        struct Point { x: u8, y: u8 }
+        const ORIGIN: Point = Point { x: 0, y: 0 };
+        const KEY: &[u8] = &[1, 2];
+        fn is_origin(p: Point) -> bool { matches!(p, ORIGIN) }
+        fn is_key(k: &[u8]) -> bool { matches!(k, KEY) }
+        "#,
+    )?;
+    // The constant patterns are compared field by field (resp. element by element): collect the
+    // values the bodies switch on.
+    let switch_values = |name: &str| {
+        let fun = crate_data
+            .fun_decls
+            .iter()
+            .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == name)
+            .unwrap();
+        let body = crate_data.bodies[fun.body.unwrap()]
+            .as_structured()
+            .unwrap();
+        let mut values = Vec::new();
+        body.body.drive(&mut derive_visitor::visitor_enter_fn(
+            |switch: &llbc_ast::Switch| {
+                if let llbc_ast::Switch::SwitchInt(_, _, targets, _) = switch {
+                    values.extend(
+                        targets
+                            .iter()
+                            .flat_map(|(target_values, _)| target_values.clone()),
+                    );
+                }
+            },
+        ));
+        values
+    };
+    assert_eq!(
+        switch_values("test_crate::is_origin"),
+        vec![ScalarValue::U8(0), ScalarValue::U8(0)]
+    );
+    let key_values = switch_values("test_crate::is_key");
+    assert!(key_values.contains(&ScalarValue::U8(1)));
+    assert!(key_values.contains(&ScalarValue::U8(2)));
+    Ok(())
+}
+
+#[test]
+fn constant_comparisons() -> anyhow::Result<()> {
+    // In the optimized MIR, the constant is propagated to the call to `PartialEq::eq`, which we
+    // turn into the comparisons of the fields.
+    let crate_data = translate_with_args(
+        r#"
+        #[derive(PartialEq, Eq)]
+        struct Point { x: u8, y: u8 }
+        const ORIGIN: Point = Point { x: 0, y: 0 };
+        fn is_origin(p: &Point) -> bool { *p == ORIGIN }
+        "#,
+        &["--mir_optimized"],
+    )?;
+    let fun = crate_data
+        .fun_decls
+        .iter()
+        .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == "test_crate::is_origin")
+        .unwrap();
+    let body = crate_data.bodies[fun.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    let mut calls = 0;
+    let mut field_comparisons = 0;
+    body.body.drive(&mut derive_visitor::visitor_enter_fn(
+        |st: &llbc_ast::Statement| match &st.content {
+            llbc_ast::RawStatement::Call(_) => calls += 1,
+            llbc_ast::RawStatement::Assign(
+                _,
+                Rvalue::BinaryOp(BinOp::Eq, Operand::Copy(place), Operand::Const(_), _),
+            ) if matches!(place.projection.last(), Some(ProjectionElem::Field(..))) => {
+                field_comparisons += 1
+            }
+            _ => {}
+        },
+    ));
+    assert_eq!(calls, 0);
+    assert_eq!(field_comparisons, 2);
+    Ok(())
+}
+
+#[test]
+fn simplify_constants() -> anyhow::Result<()> {
+    let code = r#"
+        struct Point { x: u8, y: u8 }
+        const ORIGIN: Point = Point { x: 0, y: 0 };
+        fn origin() -> Point { ORIGIN }
+        fn unit() {}
+        "#;
+    // Collect the kinds of the constants found in the operands of the bodies.
+    let constant_kinds = |crate_data: &TranslatedCrate| {
+        let mut kinds = std::collections::HashSet::new();
+        for fun in &crate_data.fun_decls {
+            if !repr_name(crate_data, &fun.item_meta.name).starts_with("test_crate::") {
+                continue;
+            }
+            let body = crate_data.bodies[fun.body.unwrap()]
+                .as_structured()
+                .unwrap();
+            body.body
+                .drive(&mut derive_visitor::visitor_enter_fn(|op: &Operand| {
+                    if let Operand::Const(cv) = op {
+                        kinds.insert(cv.value.variant_name());
+                    }
+                }));
+        }
+        kinds
+    };
+
+    let crate_data = translate(code)?;
+    let kinds = constant_kinds(&crate_data);
+    assert!(!kinds.contains("Global"));
+    assert!(!kinds.contains("Adt"));
+
+    let crate_data = translate_with_args(code, &["--simplify-constants", "globals"])?;
+    let kinds = constant_kinds(&crate_data);
+    assert!(!kinds.contains("Global"));
+    assert!(kinds.contains("Adt"));
+
+    let crate_data = translate_with_args(code, &["--simplify-constants", "none"])?;
+    let kinds = constant_kinds(&crate_data);
+    assert!(kinds.contains("Global"));
+    assert!(kinds.contains("Adt"));
+    Ok(())
+}
+
+#[test]
+fn dedup_hashes() -> anyhow::Result<()> {
+    let code = r#"
+        trait Foo {
+            fn foo(&self) -> u32;
+        }
+        impl Foo for u32 {
+            fn foo(&self) -> u32 {
+                *self
+            }
+        }
+        trait Bar {
+            fn bar(&self) -> u32;
+        }
+        impl<T: Foo> Bar for T {
+            fn bar(&self) -> u32 {
+                self.foo() + 1
+            }
+        }
+        fn use_bar(x: u32) -> u32 {
+            x.bar()
+        }
+        "#;
+    // The hashes of the generated items: the specialized impl and its method.
+    let generated_hashes = |crate_data: &TranslatedCrate| {
+        let mut hashes = Vec::new();
+        for item in crate_data.all_items() {
+            let name = repr_name(crate_data, &item.item_meta().name);
+            if let Some(hash) = &item.item_meta().dedup_hash {
+                hashes.push((name, hash.clone()));
+            }
+        }
+        hashes.sort();
+        hashes
+    };
+
+    let crate_data = translate_with_args(code, &["--instantiate-blanket-impls"])?;
+    let hashes = generated_hashes(&crate_data);
+    assert_eq!(hashes.len(), 2, "{hashes:?}");
+
+    // Adding unrelated items changes the ids but not the hashes.
+    let other_code = format!("struct Unrelated;\nfn unrelated() {{}}\n{code}");
+    let other_crate_data = translate_with_args(other_code, &["--instantiate-blanket-impls"])?;
+    assert_eq!(generated_hashes(&other_crate_data), hashes);
+    Ok(())
+}
+
+#[test]
+fn link_crates() -> anyhow::Result<()> {
+    use charon_lib::validate::validate_crate;
+    let code = r#"
+        trait Foo {
+            fn foo(&self) -> u32;
+        }
+        impl Foo for u32 {
+            fn foo(&self) -> u32 {
+                *self
+            }
+        }
+        trait Bar {
+            fn bar(&self) -> u32;
+        }
+        impl<T: Foo> Bar for T {
+            fn bar(&self) -> u32 {
+                self.foo() + 1
+            }
+        }
+        fn use_bar(x: u32) -> u32 {
+            x.bar()
+        }
+        "#;
+    let args = &["--instantiate-blanket-impls"];
+    let mut crate_data = translate_with_args(code, args)?;
+    let other_code =
+        format!("struct Unrelated;\nfn unrelated(x: u32) -> u32 {{ x.bar() }}\n{code}");
+    let other_crate_data = translate_with_args(other_code, args)?;
+    let num_items = other_crate_data.all_items().count();
+    crate_data.link(other_crate_data);
+
+    // The shared items, including the generated ones, are only kept once.
+    assert_eq!(crate_data.all_items().count(), num_items);
+    let hashes: Vec<_> = crate_data
+        .all_items()
+        .filter_map(|item| item.item_meta().dedup_hash.clone())
+        .collect();
+    assert_eq!(hashes.len(), 2, "{hashes:?}");
+    let names: Vec<_> = crate_data
+        .all_items()
+        .map(|item| repr_name(&crate_data, &item.item_meta().name))
+        .collect();
+    assert!(names.contains(&"test_crate::unrelated".to_owned()));
+    assert!(names.contains(&"test_crate::Unrelated".to_owned()));
+    // The references are to the items of the merged crate.
+    let errors = validate_crate(&crate_data);
+    assert!(errors.is_empty(), "{errors:?}");
+    Ok(())
+}
+
+#[test]
+fn stable_hash() -> anyhow::Result<()> {
+    let code = r#"
+        struct Point { x: u32, y: u32 }
+        fn norm(p: &Point) -> u32 { p.x + p.y }
+        fn double_norm(p: &Point) -> u32 { 2 * norm(p) }
+        "#;
+    let hashes = |crate_data: &TranslatedCrate| {
+        crate_data
+            .all_items()
+            .filter(|item| {
+                repr_name(crate_data, &item.item_meta().name).starts_with("test_crate::")
+            })
+            .map(|item| {
+                (
+                    repr_name(crate_data, &item.item_meta().name),
+                    item.stable_hash(crate_data),
+                )
+            })
+            .collect::<HashMap<_, _>>()
+    };
+    let crate_data = translate(code)?;
+    let reference = hashes(&crate_data);
+    assert_eq!(reference.len(), 3);
+
+    // Adding unrelated items changes the ids and the spans but not the hashes.
+    let shifted = format!("struct Unrelated;\nfn unrelated() {{}}\n\n{code}");
+    let crate_data = translate(shifted)?;
+    let shifted = hashes(&crate_data);
+    for (name, hash) in &reference {
+        assert_eq!(shifted[name], *hash, "the hash of {name} changed");
+    }
+
+    // Changing a body changes the hash of that item only.
+    let changed = code.replace("2 * norm(p)", "3 * norm(p)");
+    let crate_data = translate(changed)?;
+    let changed = hashes(&crate_data);
+    for (name, hash) in &reference {
+        if name == "test_crate::double_norm" {
+            assert_ne!(changed[name], *hash);
+        } else {
+            assert_eq!(changed[name], *hash, "the hash of {name} changed");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn dependency_graph() -> anyhow::Result<()> {
+    use charon_lib::reorder_decls::DependencyKind;
+    let crate_data = translate(
+        r#"
+        struct Point { x: u32 }
+        static ORIGIN: Point = Point { x: 0 };
+        trait Norm { fn norm(&self) -> u32; }
+        impl Norm for Point { fn norm(&self) -> u32 { self.x } }
+        fn get_x(p: &Point) -> u32 { p.x }
+        fn origin_x() -> u32 { get_x(&ORIGIN) + ORIGIN.norm() }
+        "#,
+    )?;
+    let name = |id: AnyTransId| repr_name(&crate_data, crate_data.item_name(id).unwrap());
+    let deps: Vec<(String, String, DependencyKind)> = crate_data
+        .dependency_graph
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|dep| (name(dep.src), name(dep.dst), dep.kind))
+        .collect();
+    let has_dep = |src: &str, dst: &str, kind| {
+        deps.contains(&(
+            format!("test_crate::{src}"),
+            format!("test_crate::{dst}"),
+            kind,
+        ))
+    };
+    assert!(has_dep("origin_x", "get_x", DependencyKind::Call));
+    assert!(has_dep("origin_x", "ORIGIN", DependencyKind::GlobalUse));
+    assert!(has_dep("get_x", "Point", DependencyKind::TypeUse));
+    assert!(has_dep("<impl for Norm>", "Norm", DependencyKind::TraitRef));
+    Ok(())
+}
+
+#[test]
+fn cycle_reports() -> anyhow::Result<()> {
+    use charon_lib::reorder_decls::{find_cycles, DependencyKind};
+    let crate_data = translate(
+        r#"
+        enum List { Nil, Cons(u32, Box<List>) }
+        fn is_even(n: u32) -> bool { if n == 0 { true } else { is_odd(n - 1) } }
+        fn is_odd(n: u32) -> bool { if n == 0 { false } else { is_even(n - 1) } }
+        fn not_recursive() {}
+        "#,
+    )?;
+    let name = |id: AnyTransId| repr_name(&crate_data, crate_data.item_name(id).unwrap());
+    let cycles: Vec<Vec<(String, String, DependencyKind)>> = find_cycles(&crate_data)
+        .into_iter()
+        .map(|cycle| {
+            assert!(!cycle.is_mixed);
+            assert!(cycle.report(&crate_data).contains("->"));
+            cycle
+                .edges
+                .iter()
+                .map(|dep| (name(dep.src), name(dep.dst), dep.kind))
+                .collect()
+        })
+        .filter(|edges: &Vec<_>| edges[0].0.starts_with("test_crate::"))
+        .collect();
+    assert_eq!(cycles.len(), 2, "{cycles:?}");
+    let list = "test_crate::List".to_string();
+    assert!(cycles.contains(&vec![(list.clone(), list, DependencyKind::TypeUse)]));
+    let (even, odd) = (
+        "test_crate::is_even".to_string(),
+        "test_crate::is_odd".to_string(),
+    );
+    assert!(cycles.iter().any(|edges| {
+        edges.len() == 2
+            && edges
+                .iter()
+                .all(|(_, _, kind)| *kind == DependencyKind::Call)
+            && edges
+                .iter()
+                .any(|(src, dst, _)| *src == even && *dst == odd)
+            && edges
+                .iter()
+                .any(|(src, dst, _)| *src == odd && *dst == even)
+    }));
+    Ok(())
+}
+
+#[test]
+fn recursive_field_annotations() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        struct Wrapper<T>(T);
+        struct Tree { children: Vec<Tree>, parent: Option<&'static Tree>, label: u32 }
+        enum Expr { Lit(u32), Neg(Box<Expr>), Block(Wrapper<Stmt>) }
+        struct Stmt { expr: Option<Box<Expr>> }
+        struct NotRecursive { x: Wrapper<u32> }
+        "#,
+        &["--annotate-recursive-fields"],
+    )?;
+    let fields: HashMap<String, Option<FieldRecursion>> = crate_data
+        .type_decls
+        .iter()
+        .flat_map(|decl| {
+            let name = repr_name(&crate_data, &decl.item_meta.name);
+            let fields: Vec<&Field> = match &decl.kind {
+                TypeDeclKind::Struct(fields) => fields.iter().collect(),
+                TypeDeclKind::Enum(variants) => {
+                    variants.iter().flat_map(|v| v.fields.iter()).collect()
+                }
+                _ => Vec::new(),
+            };
+            fields
+                .into_iter()
+                .enumerate()
+                .map(move |(i, field)| (format!("{name}.{i}"), field.recursion))
+        })
+        .filter(|(name, _)| name.starts_with("test_crate::"))
+        .collect();
+    use FieldRecursion::*;
+    let expected = [
+        ("test_crate::Wrapper.0", None),
+        ("test_crate::Tree.0", Some(Indirect)),
+        ("test_crate::Tree.1", Some(Indirect)),
+        ("test_crate::Tree.2", None),
+        ("test_crate::Expr.0", None),
+        ("test_crate::Expr.1", Some(Indirect)),
+        ("test_crate::Expr.2", Some(Direct)),
+        ("test_crate::Stmt.0", Some(Indirect)),
+        ("test_crate::NotRecursive.0", None),
+    ];
+    assert_eq!(fields.len(), expected.len(), "{fields:?}");
+    for (name, recursion) in expected {
+        assert_eq!(fields[name], recursion, "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn vtable_slots() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        trait Super { fn a(&self); fn b(&self) {} }
+        trait Sub: Super { fn c(&self); fn d(&self) where Self: Sized; fn e(&self); }
+        trait NotDynCompatible { fn new() -> Self; }
+        "#,
+    )?;
+    let slots: HashMap<String, Option<Vec<(String, usize)>>> = crate_data
+        .trait_decls
+        .iter()
+        .map(|d| {
+            let slots = d.vtable_slots.as_ref().map(|slots| {
+                slots
+                    .iter()
+                    .map(|(name, slot)| (name.0.clone(), *slot))
+                    .collect()
+            });
+            (repr_name(&crate_data, &d.item_meta.name), slots)
+        })
+        .collect();
+    let slot = |name: &str, slot: usize| (name.to_string(), slot);
+    assert_eq!(
+        slots["test_crate::Super"],
+        Some(vec![slot("a", 3), slot("b", 4)])
+    );
+    assert_eq!(
+        slots["test_crate::Sub"],
+        Some(vec![slot("c", 5), slot("e", 6)])
+    );
+    assert_eq!(slots["test_crate::NotDynCompatible"], None);
+    Ok(())
+}
+
+#[test]
+fn dyn_compatibility_violations() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        trait DynCompatible { fn a(&self); fn new() -> Self where Self: Sized; }
+        trait Constructor { fn new() -> Self; }
+        trait Generic { fn map<T>(&self, x: T) -> T; }
+        trait Sub: Generic {}
+        "#,
+    )?;
+    let violations: HashMap<String, Vec<Option<String>>> = crate_data
+        .trait_decls
+        .iter()
+        .map(|d| {
+            let items = d
+                .dyn_compatibility_violations
+                .iter()
+                .map(|v| v.item.as_ref().map(|item| item.0.clone()))
+                .collect();
+            (repr_name(&crate_data, &d.item_meta.name), items)
+        })
+        .collect();
+    let item = |name: &str| Some(name.to_string());
+    assert_eq!(violations["test_crate::DynCompatible"], vec![]);
+    assert_eq!(violations["test_crate::Constructor"], vec![item("new")]);
+    assert_eq!(violations["test_crate::Generic"], vec![item("map")]);
+    // The violations of the supertraits are reported too.
+    assert_eq!(violations["test_crate::Sub"], vec![item("map")]);
+
+    let generic = crate_data
+        .trait_decls
+        .iter()
+        .find(|d| repr_name(&crate_data, &d.item_meta.name) == "test_crate::Generic")
+        .unwrap();
+    assert!(generic.vtable_slots.is_none());
+    assert!(generic.dyn_compatibility_violations[0]
+        .reason
+        .contains("`map`"));
+    Ok(())
+}
+
+#[test]
+fn reconstruct_try() -> anyhow::Result<()> {
+    let code = r#"
+        fn result(x: Result<u32, ()>) -> Result<u32, ()> {
+            let y = x?;
+            Ok(y)
+        }
+        fn option(x: Option<u32>, y: Option<u32>) -> Option<(u32, u32)> {
+            Some((x?, y?))
+        }
+        "#;
+    let count_try = |crate_data: &TranslatedCrate| -> HashMap<String, usize> {
+        crate_data
+            .fun_decls
+            .iter()
+            .filter_map(|decl| {
+                let name = repr_name(crate_data, &decl.item_meta.name);
+                if !name.starts_with("test_crate::") {
+                    return None;
+                }
+                let body = crate_data.bodies[decl.body.unwrap()]
+                    .as_structured()
+                    .unwrap();
+                let mut count = 0;
+                body.body.drive(&mut derive_visitor::visitor_enter_fn(
+                    |st: &llbc_ast::RawStatement| {
+                        if let llbc_ast::RawStatement::TryPropagate(_) = st {
+                            count += 1;
+                        }
+                    },
+                ));
+                Some((name, count))
+            })
+            .collect()
+    };
+
+    let crate_data = translate(code)?;
+    let counts = count_try(&crate_data);
+    assert_eq!(counts["test_crate::result"], 0);
+    assert_eq!(counts["test_crate::option"], 0);
+
+    let crate_data = translate_with_args(code, &["--reconstruct-try"])?;
+    let counts = count_try(&crate_data);
+    assert_eq!(counts["test_crate::result"], 1);
+    assert_eq!(counts["test_crate::option"], 2);
+    Ok(())
+}
+
+#[test]
+fn macro_origins() -> anyhow::Result<()> {
+    let crate_data = translate(
+        r#"
+        fn foo(x: u32, y: Option<u32>) -> bool {
+            debug_assert!(x > 0);
+            assert_eq!(x, 1);
+            matches!(y, Some(0))
+        }
+        "#,
+    )?;
+    let body_id = crate_data.fun_decls.iter().next().unwrap().body.unwrap();
+    let body = crate_data.bodies[body_id].as_structured().unwrap();
+    let mut origins = Vec::new();
+    body.body.drive(&mut derive_visitor::visitor_enter_fn(
+        |st: &llbc_ast::Statement| {
+            if let Some(origin) = st.span.macro_origin {
+                if !origins.contains(&origin) {
+                    origins.push(origin);
+                }
+            }
+        },
+    ));
+    assert_eq!(
+        origins,
+        vec![
+            MacroOrigin::DebugAssert,
+            MacroOrigin::AssertEq,
+            MacroOrigin::Matches
+        ]
+    );
+    assert!(origins[0].is_debug_assert());
+    Ok(())
+}
+
+#[test]
+fn temporary_names() -> anyhow::Result<()> {
+    let code = r#"
+        fn foo(x: &Option<u32>, tmp_3: u32) -> u32 {
+            match *x {
+                Some(y) => y + tmp_3,
+                None => 0,
+            }
+        }
+        "#;
+    let locals_of = |crate_data: &TranslatedCrate| {
+        let body_id = crate_data.fun_decls.iter().next().unwrap().body.unwrap();
+        let body = crate_data.bodies[body_id].as_structured().unwrap();
+        body.locals
+            .iter()
+            .map(|var| var.name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    // By default, the temporaries have no names.
+    let crate_data = translate(code)?;
+    assert!(locals_of(&crate_data)
+        .iter()
+        .skip(1)
+        .any(|name| name.is_none()));
+
+    let crate_data = translate_with_args(code, &["--name-temporaries"])?;
+    let names = locals_of(&crate_data);
+    // The return value is still unnamed, every other local has a name.
+    assert_eq!(names[0], None);
+    let names: Vec<String> = names
+        .into_iter()
+        .skip(1)
+        .map(|name| name.unwrap())
+        .collect();
+    assert_eq!(names[0], "x");
+    assert_eq!(names[1], "tmp_3");
+    assert!(names
+        .iter()
+        .any(|name| name.starts_with("tmp_") && name != "tmp_3"));
+    // The generated names don't clash with the names of the user variables.
+    assert_eq!(names.iter().filter(|name| *name == "tmp_3").count(), 1);
+    Ok(())
+}
+
+#[test]
+fn keep_retags() -> anyhow::Result<()> {
+    let code = r#"
+        fn foo(x: &mut u32) -> &mut u32 {
+            *x += 1;
+            x
+        }
+        "#;
+    let retag_kinds = |crate_data: &TranslatedCrate| {
+        let body_id = crate_data.fun_decls.iter().next().unwrap().body.unwrap();
+        let body = crate_data.bodies[body_id].as_structured().unwrap();
+        let mut kinds = Vec::new();
+        body.body.drive(&mut derive_visitor::visitor_enter_fn(
+            |st: &llbc_ast::Statement| {
+                if let llbc_ast::RawStatement::Retag(kind, _) = &st.content {
+                    kinds.push(*kind);
+                }
+            },
+        ));
+        kinds
+    };
+
+    // By default, the retags are removed.
+    let crate_data = translate_with_args(code, &["--mir_optimized"])?;
+    assert!(retag_kinds(&crate_data).is_empty());
+
+    let crate_data = translate_with_args(code, &["--mir_optimized", "--keep-retags"])?;
+    assert!(retag_kinds(&crate_data).contains(&RetagKind::FnEntry));
+    Ok(())
+}
+
+#[test]
+fn transmute_layouts() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        fn foo(x: u32) -> [u8; 4] {
+            unsafe { std::mem::transmute(x) }
+        }
+        fn bar(x: char) -> u32 {
+            unsafe { std::mem::transmute(x) }
+        }
+        "#,
+        &["--mir_optimized"],
+    )?;
+    let mut layouts = Vec::new();
+    for fun in crate_data.fun_decls.iter() {
+        let body = crate_data.bodies[fun.body.unwrap()]
+            .as_structured()
+            .unwrap();
+        body.body
+            .drive(&mut derive_visitor::visitor_enter_fn(|kind: &CastKind| {
+                if let CastKind::Transmute(_, _, l) = kind {
+                    layouts.push(l.clone());
+                }
+            }));
+    }
+    let layout = |size, align, has_niche| {
+        Some(Layout {
+            size,
+            align,
+            has_niche,
+        })
+    };
+    assert_eq!(
+        layouts,
+        vec![
+            TransmuteLayouts {
+                src: layout(4, 4, false),
+                tgt: layout(4, 1, false),
+            },
+            TransmuteLayouts {
+                src: layout(4, 4, true),
+                tgt: layout(4, 4, false),
+            },
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn emit_types_only() -> anyhow::Result<()> {
+    let code = r#"
+        struct Pair(u32, u32);
+        trait Swap {
+            fn swap(&mut self);
+        }
+        impl Swap for Pair {
+            fn swap(&mut self) {
+                std::mem::swap(&mut self.0, &mut self.1)
+            }
+        }
+        const ZERO: u32 = 0;
+        "#;
+    let has_mem_swap = |crate_data: &TranslatedCrate| {
+        crate_data
+            .fun_decls
+            .iter()
+            .any(|f| repr_name(crate_data, &f.item_meta.name) == "core::mem::swap")
+    };
+
+    let crate_data = translate(code)?;
+    assert!(has_mem_swap(&crate_data));
+
+    let crate_data = translate_with_args(code, &["--emit-types-only"])?;
+    // The items are there, but without their bodies.
+    assert!(crate_data.type_decls.iter().next().is_some());
+    assert!(crate_data.trait_decls.iter().next().is_some());
+    assert_eq!(crate_data.trait_impls.iter().count(), 1);
+    assert_eq!(crate_data.global_decls.iter().count(), 1);
+    assert!(crate_data.fun_decls.iter().all(|f| f.body.is_err()));
+    assert!(crate_data.global_decls.iter().all(|g| g.body.is_err()));
+    assert!(crate_data.bodies.iter().next().is_none());
+    // We don't explore the items which are only used in bodies.
+    assert!(!has_mem_swap(&crate_data));
+    Ok(())
+}
+
+#[test]
+fn signatures_with_bodies_for() -> anyhow::Result<()> {
+    let code = r#"
+        mod analyzed {
+            pub fn foo() -> u32 {
+                super::bar() + 1
+            }
+        }
+        fn bar() -> u32 {
+            42
+        }
+        "#;
+    let has_body = |crate_data: &TranslatedCrate| {
+        crate_data
+            .fun_decls
+            .iter()
+            .map(|f| (repr_name(crate_data, &f.item_meta.name), f.body.is_ok()))
+            .collect::<HashMap<_, _>>()
+    };
+
+    let crate_data = translate_with_args(
+        code,
+        &["--signatures-with-bodies-for", "test_crate::analyzed"],
+    )?;
+    let bodies = has_body(&crate_data);
+    assert_eq!(bodies["test_crate::analyzed::foo"], true);
+    assert_eq!(bodies["test_crate::bar"], false);
+
+    // With `--emit-types-only`, the matching items still get their bodies.
+    let crate_data = translate_with_args(
+        code,
+        &[
+            "--emit-types-only",
+            "--signatures-with-bodies-for",
+            "test_crate::bar",
+        ],
+    )?;
+    let bodies = has_body(&crate_data);
+    assert_eq!(bodies["test_crate::analyzed::foo"], false);
+    assert_eq!(bodies["test_crate::bar"], true);
+    Ok(())
+}
+
+#[test]
+fn max_body_statements() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        fn small() -> u32 {
+            0
+        }
+        fn big(x: u32) -> u32 {
+            match x {
+                0 => 1,
+                1 => 2,
+                2 => 3,
+                3 => 4,
+                4 => 5,
+                5 => 6,
+                6 => 7,
+                7 => 8,
+                _ => 0,
+            }
+        }
+        "#,
+        &["--max-body-statements", "5"],
+    )?;
+    let bodies: HashMap<String, bool> = crate_data
+        .fun_decls
+        .iter()
+        .map(|f| (repr_name(&crate_data, &f.item_meta.name), f.body.is_ok()))
+        .collect();
+    assert_eq!(bodies["test_crate::small"], true);
+    assert_eq!(bodies["test_crate::big"], false);
+    Ok(())
+}
+
+#[test]
+fn deterministic() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        r#"
+        trait Trait {
+            const A: u32 = 0;
+            const B: u32 = 1;
+            type T = u32;
+        }
+        fn foo() -> fn(u32) -> u32 {
+            let f: fn(u32) -> u32 = |x| x + 1;
+            f
+        }
+        fn bar() -> fn(u32) -> u32 {
+            |x| x + 2
+        }
+        "#,
+        &["--deterministic"],
+    )?;
+    let ids: Vec<AnyTransId> = crate_data.item_names.keys().copied().collect();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    let trait_decl = crate_data
+        .trait_decls
+        .iter()
+        .find(|d| repr_name(&crate_data, &d.item_meta.name) == "test_crate::Trait")
+        .unwrap();
+    let defaults: Vec<&str> = trait_decl
+        .const_defaults
+        .keys()
+        .map(|name| name.0.as_str())
+        .collect();
+    assert_eq!(defaults, ["A", "B"]);
+    Ok(())
+}
+
+#[test]
+fn profile_extraction() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let profile = dir.path().join("profile.json");
+    translate_with_args(
+        r#"
+        fn foo() -> u32 {
+            42
+        }
+        "#,
+        &["--profile-extraction", profile.to_str().unwrap()],
+    )?;
+    let trace: serde_json::Value = serde_json::from_reader(std::fs::File::open(&profile)?)?;
+    let events = trace["traceEvents"].as_array().unwrap();
+    let has_event = |cat: &str, name: &str| {
+        events
+            .iter()
+            .any(|e| e["cat"] == cat && e["name"].as_str().unwrap().contains(name))
+    };
+    assert!(has_event("item", "foo"));
+    assert!(has_event("hax", "foo"));
+    assert!(has_event("phase", "translation"));
+    assert!(has_event("pass", ""));
+    Ok(())
+}
+
+#[test]
+fn embed_sources() -> anyhow::Result<()> {
+    let code = r#"
+        fn foo() -> u32 {
+            42
+        }
+        // A comment which no span refers to.
+        "#;
+    let crate_data = translate_with_args(code, &["--embed-sources", "referenced"])?;
+    let contents = crate_data.file_id_to_content.values().next().unwrap();
+    assert!(contents.contains("fn foo"));
+    assert!(!contents.contains("A comment"));
+    // The line numbers are preserved.
+    assert_eq!(contents.split('\n').count(), code.split('\n').count());
+
+    let crate_data = translate_with_args(code, &["--embed-sources", "none"])?;
+    assert!(crate_data.file_id_to_content.is_empty());
+    Ok(())
+}
+
+#[test]
+fn render_item() -> anyhow::Result<()> {
+    use charon_lib::pretty::formatter::{render_item, PrintOptions};
+    let crate_data = translate(
+        "
+        fn foo<T: Clone>(x: &T) -> T {
+            // Clone it.
+            x.clone()
+        }
+        ",
+    )?;
+    let id = AnyTransId::Fun(crate_data.fun_decls.iter().next().unwrap().def_id);
+    // The default options give the usual output.
+    let default = render_item(&crate_data, id, &PrintOptions::default());
+    assert_eq!(default, crate_data.into_fmt().format_decl_id(id));
+    assert!(default.contains("// Clone it."));
+    assert!(default.contains("<T>"));
+
+    let options = PrintOptions {
+        show_ids: true,
+        show_spans: true,
+        tab_width: 2,
+        hide_generics: true,
+        inline_comments: false,
+    };
+    let rendered = render_item(&crate_data, id, &options);
+    assert!(!rendered.contains("// Clone it."));
+    assert!(!rendered.contains("<T>"));
+    assert!(rendered.contains("foo@Fun0"));
+    assert!(rendered.starts_with("// "));
+    assert!(rendered
+        .lines()
+        .any(|line| line.starts_with("  ") && !line.starts_with("    ")));
+    Ok(())
+}
+
+#[test]
+fn parse_llbc_body() -> anyhow::Result<()> {
+    use charon_lib::pretty::parser::parse_body;
+    use charon_lib::pretty::FmtWithCtx;
+    let crate_data = translate(
+        "
+        fn sum(max: u32) -> u32 {
+            let mut i = 0;
+            let mut s = 0;
+            // Add them all.
+            while i < max {
+                s += i;
+                i += 1;
+            }
+            s
+        }
+        fn choose(b: bool, x: (u32, u32)) -> u32 {
+            if b { x.0 } else { sum(x.1) }
+        }
+        ",
+    )?;
+    let fmt_ctx = crate_data.into_fmt();
+    for fun in &crate_data.fun_decls {
+        let body = crate_data.bodies[*fun.body.as_ref().unwrap()]
+            .as_structured()
+            .unwrap();
+        let printed = body.fmt_with_ctx(&fmt_ctx);
+        let parsed = parse_body(&crate_data, &printed).map_err(|e| anyhow::anyhow!("{e}"))?;
+        assert_eq!(parsed.arg_count, body.arg_count);
+        assert_eq!(parsed.fmt_with_ctx(&fmt_ctx), printed);
+    }
+
+    // Errors are reported with their location.
+    let err = parse_body(&crate_data, "let @0: u32; // return\n\n@0 := foo()").unwrap_err();
+    assert!(err.to_string().contains("line 3"));
+    Ok(())
+}
+
+#[test]
+fn model_file() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let models = dir.path().join("models.txt");
+    std::fs::write(
+        &models,
+        "
+        // The model of `secret`.
+        fn crate::secret {
+            let @0: u32; // return
+            let x@1: u32; // arg #1
+
+            @0 := copy (x@1) + const (1 : u32)
+            return
+        }
+        ",
+    )?;
+    let crate_data = translate_with_args(
+        "
+        fn secret(x: u32) -> u32 {
+            x * 2
+        }
+        fn other(b: bool) -> bool {
+            !b
+        }
+        ",
+        &[
+            "--opaque",
+            "crate::secret",
+            "--opaque",
+            "crate::other",
+            "--model-file",
+            models.to_str().unwrap(),
+        ],
+    )?;
+    let fun = |name: &str| {
+        crate_data
+            .fun_decls
+            .iter()
+            .find(|f| repr_name(&crate_data, &f.item_meta.name) == name)
+            .unwrap()
+    };
+    let body = crate_data.bodies[*fun("test_crate::secret").body.as_ref().unwrap()]
+        .as_structured()
+        .unwrap();
+    assert_eq!(body.arg_count, 1);
+    assert!(fun("test_crate::other").body.is_err());
+
+    // A model whose signature doesn't match is rejected.
+    let result = translate_with_args(
+        "
+        fn secret(x: bool) -> bool {
+            x
+        }
+        ",
+        &[
+            "--opaque",
+            "crate::secret",
+            "--model-file",
+            models.to_str().unwrap(),
+            "--error-on-warnings",
+        ],
+    );
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn simplify_derives() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        "
+        #[derive(Debug, Clone, PartialEq)]
+        struct Foo(u32);
+        fn dup(x: &Foo) -> Foo {
+            x.clone()
+        }
+        ",
+        &["--opaque-derive", "Debug", "--builtin-derive", "Clone"],
+    )?;
+    let impl_of = |name: &str| {
+        crate_data
+            .trait_impls
+            .iter()
+            .find(|timpl| trait_name(&crate_data, timpl.impl_trait.trait_id) == name)
+    };
+    // The `Clone` impl is replaced by a builtin impl.
+    assert!(impl_of("Clone").is_none());
+    let mut builtin_clone = false;
+    crate_data.drive(&mut derive_visitor::visitor_enter_fn(|tref: &TraitRef| {
+        if let TraitRefKind::BuiltinOrAuto(decl_ref) = &tref.kind {
+            builtin_clone |= trait_name(&crate_data, decl_ref.skip_binder.trait_id) == "Clone";
+        }
+    }));
+    assert!(builtin_clone);
+    // The `Debug` methods are opaque.
+    let debug = impl_of("Debug").unwrap();
+    for (_, fun_id) in &debug.required_methods {
+        assert!(crate_data.fun_decls[*fun_id].body.is_err());
+    }
+    // The `PartialEq` methods are kept.
+    let eq = impl_of("PartialEq").unwrap();
+    assert!(eq
+        .required_methods
+        .iter()
+        .all(|(_, fun_id)| crate_data.fun_decls[*fun_id].body.is_ok()));
+    Ok(())
+}
+
+#[test]
+fn main_shims() -> anyhow::Result<()> {
+    let code = "
+        fn add(x: u32, y: u32) -> u32 {
+            x + y
+        }
+        #[test]
+        fn test_add() {
+            assert_eq!(add(1, 2), 3);
+        }
+        ";
+    let names = |crate_data: &TranslatedCrate| {
+        crate_data
+            .item_names
+            .values()
+            .map(|name| repr_name(crate_data, name))
+            .collect_vec()
+    };
+    let global_names = |crate_data: &TranslatedCrate| {
+        crate_data
+            .global_decls
+            .iter()
+            .map(|decl| repr_name(crate_data, &decl.item_meta.name))
+            .collect_vec()
+    };
+    // By default we skip the `main` of the test harness and the description of the test.
+    let crate_data = translate_with_args(code, &["--rustc-flag=--test"])?;
+    let skipped = names(&crate_data);
+    assert!(!global_names(&crate_data).contains(&"test_crate::test_add".to_owned()));
+    assert!(!skipped.contains(&"test_crate::main".to_owned()));
+    assert!(skipped.contains(&"test_crate::test_add".to_owned()));
+
+    let crate_data = translate_with_args(code, &["--rustc-flag=--test", "--keep-main-shims"])?;
+    let kept = names(&crate_data);
+    assert!(kept.contains(&"test_crate::main".to_owned()));
+    assert!(global_names(&crate_data).contains(&"test_crate::test_add".to_owned()));
+    Ok(())
+}
+
+#[test]
+fn impl_obligations() -> anyhow::Result<()> {
+    let crate_data = translate(
+        "
+        trait Foo: Clone {
+            type Item: Copy;
+        }
+        #[derive(Clone)]
+        struct Wrapper<T>(T);
+        impl<T: Copy> Foo for Wrapper<T> {
+            type Item = T;
+        }
+        ",
+    )?;
+    let tdecl = crate_data
+        .trait_decls
+        .iter()
+        .find(|tdecl| trait_name(&crate_data, tdecl.def_id) == "Foo")
+        .unwrap();
+    let timpl = crate_data
+        .trait_impls
+        .iter()
+        .find(|timpl| timpl.impl_trait.trait_id == tdecl.def_id)
+        .unwrap();
+    // There is one obligation per clause of the trait, and they refer to the lifted clauses.
+    assert_eq!(timpl.obligations.len(), tdecl.parent_clauses.len());
+    let mut names = vec![];
+    for obligation in &timpl.obligations {
+        let clause_id = match &obligation.origin {
+            ObligationOrigin::ParentClause(id) => *id,
+            ObligationOrigin::AssocTypeBound(name, id) => {
+                assert_eq!(name.0, "Item");
+                *id
+            }
+        };
+        let trait_id = obligation.trait_ref.trait_decl_ref.skip_binder.trait_id;
+        assert_eq!(
+            tdecl.parent_clauses[clause_id].trait_.skip_binder.trait_id,
+            trait_id
+        );
+        names.push(trait_name(&crate_data, trait_id).to_owned());
+    }
+    assert!(names.contains(&"Clone".to_owned()));
+    // The `Item: Copy` obligation is proven by the clause of the impl.
+    let copy = timpl
+        .obligations
+        .iter()
+        .find(|obligation| {
+            trait_name(
+                &crate_data,
+                obligation.trait_ref.trait_decl_ref.skip_binder.trait_id,
+            ) == "Copy"
+        })
+        .unwrap();
+    assert!(matches!(copy.origin, ObligationOrigin::AssocTypeBound(..)));
+    assert!(matches!(copy.trait_ref.kind, TraitRefKind::Clause(_)));
+    Ok(())
+}
+
+#[test]
+fn trait_ref_provenance() -> anyhow::Result<()> {
+    let crate_data = translate(
+        "
+        trait Foo: Clone {}
+        fn from_clause<T: Foo>(x: &T) -> T {
+            x.clone()
+        }
+        fn from_impl(x: &Option<u32>) -> Option<u32> {
+            x.clone()
+        }
+        fn from_builtin() -> usize {
+            core::mem::size_of::<u8>()
+        }
+        ",
+    )?;
+    let trait_refs_in = |name: &str| {
+        let fun = crate_data
+            .fun_decls
+            .iter()
+            .find(|f| repr_name(&crate_data, &f.item_meta.name) == format!("test_crate::{name}"))
+            .unwrap();
+        let mut trait_refs = vec![];
+        fun.body
+            .as_ref()
+            .unwrap()
+            .drive(&mut derive_visitor::visitor_enter_fn(|tref: &TraitRef| {
+                trait_refs.push(tref.clone())
+            }));
+        trait_refs
+    };
+
+    let clone = trait_refs_in("from_clause")
+        .into_iter()
+        .find(|tref| trait_name(&crate_data, tref.trait_id()) == "Clone")
+        .unwrap();
+    assert!(matches!(
+        clone.provenance(),
+        TraitRefProvenance::Clause(Some(_))
+    ));
+    assert!(clone.kind.is_from_clause());
+    assert!(matches!(
+        clone.kind.clause_path().as_slice(),
+        [ClausePathElem::Parent(..)]
+    ));
+
+    let clone = trait_refs_in("from_impl")
+        .into_iter()
+        .find(|tref| trait_name(&crate_data, tref.trait_id()) == "Clone")
+        .unwrap();
+    assert!(matches!(clone.provenance(), TraitRefProvenance::Impl(..)));
+    assert!(clone.kind.clause_path().is_empty());
+
+    let sized = trait_refs_in("from_builtin")
+        .into_iter()
+        .find(|tref| trait_name(&crate_data, tref.trait_id()) == "Sized")
+        .unwrap();
+    assert_eq!(sized.provenance(), TraitRefProvenance::Builtin);
+    Ok(())
+}
+
+#[test]
+fn builtin_impl_names() -> anyhow::Result<()> {
+    use charon_lib::formatter::IntoFormatter;
+    use charon_lib::name_matcher::NamePattern;
+    use charon_lib::pretty::FmtWithCtx;
+    let crate_data = translate(
+        "
+        fn size() -> usize {
+            core::mem::size_of::<Option<bool>>()
+        }
+        ",
+    )?;
+    let mut sized = None;
+    crate_data.drive(&mut derive_visitor::visitor_enter_fn(|tref: &TraitRef| {
+        if matches!(tref.kind, TraitRefKind::BuiltinOrAuto(_))
+            && trait_name(&crate_data, tref.trait_id()) == "Sized"
+        {
+            sized = Some(tref.clone());
+        }
+    }));
+    let sized = sized.unwrap();
+    let TraitRefKind::BuiltinOrAuto(impl_trait) = &sized.kind else {
+        panic!()
+    };
+    let name = crate_data
+        .builtin_impl_name(&impl_trait.skip_binder)
+        .unwrap();
+    assert_eq!(
+        repr_name(&crate_data, &name),
+        "core::marker::<builtin impl for Sized>"
+    );
+    assert_eq!(
+        name.fmt_with_ctx(&crate_data.into_fmt()),
+        "core::marker::{impl core::marker::Sized for core::option::Option<bool>}"
+    );
+    // Builtin impls can be matched like the other impls.
+    let pattern = NamePattern::parse(
+        "core::marker::{impl core::marker::Sized for core::option::Option<bool>}",
+    )
+    .unwrap();
+    assert!(pattern.matches_trait_ref(&crate_data, &sized));
+    let pattern = NamePattern::parse(
+        "core::marker::{impl core::marker::Sized for core::result::Result<_, _>}",
+    )
+    .unwrap();
+    assert!(!pattern.matches_trait_ref(&crate_data, &sized));
+    Ok(())
+}
+
+#[test]
+fn lint() -> anyhow::Result<()> {
+    use charon_lib::lint::{lint_crate, Lint};
+    let crate_data = translate(
+        "
+        use std::cell::Cell;
+        fn raw(x: &u32) -> *const u32 {
+            x as *const u32
+        }
+        fn cell(c: &Cell<u32>) -> u32 {
+            c.get()
+        }
+        fn to_bits(x: f32) -> u32 {
+            unsafe { std::mem::transmute(x) }
+        }
+        fn fact(n: u32) -> u32 {
+            if n == 0 { 1 } else { n * fact(n - 1) }
+        }
+        fn count(mut n: u32) -> u32 {
+            let mut i = 0;
+            while n > 0 {
+                n /= 2;
+                i += 1;
+            }
+            i
+        }
+        ",
+    )?;
+    let lints_in = |deny: &[Lint], name: &str| {
+        lint_crate(&crate_data, deny)
+            .into_iter()
+            .filter(|violation| {
+                crate_data
+                    .item_name(violation.item)
+                    .is_some_and(|n| repr_name(&crate_data, n) == format!("test_crate::{name}"))
+            })
+            .map(|violation| violation.lint)
+            .collect_vec()
+    };
+    let all = [
+        Lint::RawPointers,
+        Lint::Transmute,
+        Lint::InteriorMutability,
+        Lint::Recursion,
+        Lint::UnboundedLoops,
+    ];
+    assert!(lints_in(&all, "raw").contains(&Lint::RawPointers));
+    assert!(lints_in(&all, "cell").contains(&Lint::InteriorMutability));
+    assert!(lints_in(&all, "to_bits").contains(&Lint::Transmute));
+    assert!(lints_in(&all, "fact").contains(&Lint::Recursion));
+    assert!(lints_in(&all, "count").contains(&Lint::UnboundedLoops));
+    // We only report the denied constructs.
+    assert!(lints_in(&[Lint::Transmute], "raw").is_empty());
+    assert!(lints_in(&all, "count")
+        .iter()
+        .all(|l| *l == Lint::UnboundedLoops));
+    Ok(())
+}
+
+#[test]
+fn function_metrics() -> anyhow::Result<()> {
+    use charon_lib::export::metrics::CrateMetrics;
+    let crate_data = translate(
+        "
+        fn classify<T>(x: u32, _y: T) -> u32 {
+            let mut n = 0;
+            for i in 0..x {
+                n += match i % 4 {
+                    0 => 1,
+                    1 => 2,
+                    2 => 3,
+                    _ => 4,
+                };
+            }
+            n
+        }
+        ",
+    )?;
+    let metrics = CrateMetrics::compute(&crate_data);
+    let classify = metrics
+        .functions
+        .iter()
+        .find(|f| f.name == "test_crate::classify")
+        .unwrap();
+    assert_eq!(classify.generic_arity, 1);
+    let body = classify.body.as_ref().unwrap();
+    assert_eq!(body.loops, 1);
+    assert_eq!(body.max_switch_fan_out, 4);
+    assert!(body.statements > 0 && body.blocks > 1);
+    Ok(())
+}
+
+#[test]
+fn crate_versions_in_names() -> anyhow::Result<()> {
+    use charon_lib::name_matcher::NamePattern;
+    let crate_data = translate_with_args("fn foo() {}", &["--crate-versions-in-names"])?;
+    // We don't append a version to the name of the crate we extract.
+    let names = crate_data
+        .fun_decls
+        .iter()
+        .map(|decl| repr_name(&crate_data, &decl.item_meta.name))
+        .collect_vec();
+    assert!(names.contains(&"test_crate::foo".to_string()));
+
+    // The patterns without versions match all the versions of a crate.
+    let name = |root: &str| Name {
+        name: vec![
+            PathElem::Ident(root.to_string(), Disambiguator::ZERO),
+            PathElem::Ident("Rng".to_string(), Disambiguator::ZERO),
+        ],
+    };
+    let pattern = NamePattern::parse("rand::Rng").unwrap();
+    assert!(pattern.matches(&crate_data, &name("rand@0.7.3")));
+    assert!(pattern.matches(&crate_data, &name("rand@0.8.5")));
+    assert!(!pattern.matches(&crate_data, &name("rand_core@0.6.4")));
+    Ok(())
+}
+
+#[test]
+fn source_index() -> anyhow::Result<()> {
+    use charon_lib::export::source_index::SourceIndex;
+    let crate_data = translate(
+        "
+        struct Counter(u32);
+        impl Counter {
+            fn incr(&mut self) {
+                self.0 += 1;
+            }
+        }
+        fn other() {}
+        ",
+    )?;
+    let index = SourceIndex::compute(&crate_data);
+    let incr = crate_data
+        .fun_decls
+        .iter()
+        .find(|decl| repr_name(&crate_data, &decl.item_meta.name).ends_with("::incr"))
+        .unwrap();
+    let span = incr.item_meta.span.span;
+    let file = &crate_data.id_to_file[span.file_id];
+    let items_at = |line: usize| {
+        index
+            .items_at(file, line)
+            .into_iter()
+            .map(|id| repr_name(&crate_data, crate_data.item_name(id).unwrap()))
+            .collect_vec()
+    };
+    // The innermost item comes first.
+    let at_incr = items_at(span.beg.line + 1);
+    assert_eq!(at_incr[0], repr_name(&crate_data, &incr.item_meta.name));
+    assert!(!at_incr.contains(&"test_crate::other".to_string()));
+    assert_eq!(items_at(span.end.line + 3), vec!["test_crate::other"]);
+    assert!(items_at(1).is_empty());
+    Ok(())
+}
+
+#[test]
+fn statement_comments() -> anyhow::Result<()> {
+    let crate_data = translate(
+        "
+        fn sum(n: u32) -> u32 {
+            let mut s = 0;
+            let mut i = 0;
+            while i < n {
+                // @invariant s <= i * i
+                s += i;
+                i += 1;
+            }
+            // Return the sum
+            s
+        }
+        ",
+    )?;
+    let decl = crate_data
+        .fun_decls
+        .iter()
+        .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == "test_crate::sum")
+        .unwrap();
+    let body = crate_data.bodies[decl.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    let comments_in = |block: &llbc_ast::Block| {
+        let mut comments = Vec::new();
+        block.drive(&mut derive_visitor::visitor_enter_fn(
+            |st: &llbc_ast::Statement| comments.extend(st.comments_before.iter().cloned()),
+        ));
+        comments
+    };
+    // Each comment is attached to exactly one statement.
+    assert_eq!(
+        comments_in(&body.body),
+        vec!["@invariant s <= i * i", "Return the sum"]
+    );
+    // The annotation ends up inside the loop, next to the statement it precedes.
+    let mut loop_comments = Vec::new();
+    body.body.drive(&mut derive_visitor::visitor_enter_fn(
+        |st: &llbc_ast::RawStatement| {
+            if let llbc_ast::RawStatement::Loop(block) = st {
+                loop_comments.extend(comments_in(block));
+            }
+        },
+    ));
+    assert_eq!(loop_comments, vec!["@invariant s <= i * i"]);
+    Ok(())
+}
+
+#[test]
+fn comment_annotations() -> anyhow::Result<()> {
+    let crate_data = translate_with_args(
+        "
+        // Increment a number.
+        //@ requires: x < 100
+        //@ ensures: result == x + 1
+        #[inline(never)]
+        fn incr(x: u32) -> u32 {
+            //@ assert: x < 100
+            x + 1
+        }
+        fn plain() {}
+        ",
+        &["--comment-annotations"],
+    )?;
+    let annotation = |key: &str, value: &str| Annotation {
+        key: key.to_string(),
+        value: value.to_string(),
+    };
+    let find_fun = |name: &str| {
+        crate_data
+            .fun_decls
+            .iter()
+            .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == name)
+            .unwrap()
+    };
+    let incr = find_fun("test_crate::incr");
+    assert_eq!(
+        incr.item_meta.annotations,
+        vec![
+            annotation("requires", "x < 100"),
+            annotation("ensures", "result == x + 1")
+        ]
+    );
+    let body = crate_data.bodies[incr.body.unwrap()]
+        .as_structured()
+        .unwrap();
+    let mut annotations = Vec::new();
+    body.body.drive(&mut derive_visitor::visitor_enter_fn(
+        |st: &llbc_ast::Statement| {
+            if !st.annotations.is_empty() {
+                annotations.push(st.annotations.clone());
+            }
+        },
+    ));
+    assert_eq!(annotations, vec![vec![annotation("assert", "x < 100")]]);
+    assert!(find_fun("test_crate::plain")
+        .item_meta
+        .annotations
+        .is_empty());
+
+    // The colon is optional, and the other comments are not annotations.
+    assert_eq!(
+        Annotation::parse("@invariant i <= n"),
+        Some(annotation("invariant", "i <= n"))
+    );
+    assert_eq!(
+        Annotation::parse(" @ decreases: n - i"),
+        Some(annotation("decreases", "n - i"))
+    );
+    assert_eq!(Annotation::parse("Increment a number."), None);
+    assert_eq!(Annotation::parse("@: no key"), None);
+    Ok(())
+}
+
+#[test]
+fn typed_visitor() -> anyhow::Result<()> {
+    let crate_data = translate(
+        "
+        fn f(_: Option<u32>) {}
+        fn g(n: u32) {
+            f(None);
+            for _ in 0..n {
+                f(Some(n));
+            }
+        }
+        ",
+    )?;
+    /// Count the calls to `f`, optionally skipping the loops, and the uses of `u32`.
+    struct Counter {
+        skip_loops: bool,
+        calls: usize,
+        u32s: usize,
+        depth: isize,
+    }
+    impl AstVisitor for Counter {
+        fn visit_call(&mut self, call: &Call) -> VisitContents {
+            if matches!(
+                &call.func,
+                FnOperand::Regular(FnPtr {
+                    func: FunIdOrTraitMethodRef::Fun(FunId::Regular(_)),
+                    ..
+                })
+            ) {
+                self.calls += 1;
+            }
+            VisitContents::Visit
+        }
+        fn visit_ty(&mut self, ty: &Ty) -> VisitContents {
+            if matches!(
+                ty.kind(),
+                TyKind::Literal(LiteralTy::Integer(IntegerTy::U32))
+            ) {
+                self.u32s += 1;
+            }
+            VisitContents::Visit
+        }
+        fn visit_llbc_statement(&mut self, st: &llbc_ast::Statement) -> VisitContents {
+            self.depth += 1;
+            if self.skip_loops && matches!(st.content, llbc_ast::RawStatement::Loop(_)) {
+                VisitContents::Skip
+            } else {
+                VisitContents::Visit
+            }
+        }
+        fn exit_llbc_statement(&mut self, _: &llbc_ast::Statement) {
+            self.depth -= 1;
+        }
+    }
+    let g = crate_data
+        .fun_decls
+        .iter()
+        .find(|decl| repr_name(&crate_data, &decl.item_meta.name) == "test_crate::g")
+        .unwrap();
+    let body = &crate_data.bodies[g.body.unwrap()];
+    let count = |skip_loops: bool| {
+        let mut counter = Counter {
+            skip_loops,
+            calls: 0,
+            u32s: 0,
+            depth: 0,
+        };
+        counter.walk(body);
+        // The `exit_*` methods are called even for the skipped nodes.
+        assert_eq!(counter.depth, 0);
+        counter
+    };
+    let all = count(false);
+    let outside_loops = count(true);
+    assert!(all.calls > outside_loops.calls);
+    assert!(outside_loops.calls >= 1);
+    // The visitor enters the types, e.g. the `u32` in `Option<u32>`.
+    assert!(all.u32s > 0);
+    Ok(())
+}
+
+#[test]
+fn hash_consed_drive_mut() {
+    use derive_visitor::{visitor_enter_fn_mut, DriveMut};
+    use std::collections::HashSet;
+    let bool_ty = TyKind::Literal(LiteralTy::Bool).into_ty();
+    let u32_ty = TyKind::Literal(LiteralTy::Integer(IntegerTy::U32)).into_ty();
+    let ref_to = |ty: &Ty| TyKind::Ref(Region::Erased, ty.clone(), RefKind::Shared).into_ty();
+    // The hash of a type is the address of its interned value, so this checks that the types
+    // stay interned.
+    let interned: HashSet<Ty> = [ref_to(&bool_ty), ref_to(&u32_ty)].into_iter().collect();
+
+    // A traversal which doesn't change anything.
+    let mut ty = ref_to(&bool_ty);
+    ty.drive_mut(&mut Ty::visit_inside(visitor_enter_fn_mut(|_: &mut Ty| {})));
+    assert!(interned.contains(&ty));
+
+    // A traversal which changes a nested type.
+    ty.drive_mut(&mut Ty::visit_inside(visitor_enter_fn_mut(
+        |ty: &mut Ty| {
+            if *ty == bool_ty {
+                *ty = u32_ty.clone();
+            }
+        },
+    )));
+    assert_eq!(ty, ref_to(&u32_ty));
+    assert!(interned.contains(&ty));
+}
+
+#[test]
+fn intern_tables() {
+    use charon_lib::common::hash_consing;
+    use std::collections::HashSet;
+    let nested = |depth: usize| {
+        let mut ty = TyKind::Literal(LiteralTy::Integer(IntegerTy::U128)).into_ty();
+        for _ in 0..depth {
+            ty = TyKind::Ref(Region::Erased, ty, RefKind::Mut).into_ty();
+        }
+        ty
+    };
+    let used = nested(9);
+    // This one is only referenced by the intern table once we drop it.
+    drop(nested(10));
+    let stats = hash_consing::stats();
+    assert!(stats
+        .iter()
+        .any(|s| s.type_name.ends_with("TyKind") && s.values > 0));
+
+    hash_consing::clear_unused();
+    // The values in use are still interned: building them again gives the same values (the hash
+    // of a type is the address of its interned value).
+    let interned: HashSet<Ty> = [used].into_iter().collect();
+    assert!(interned.contains(&nested(9)));
+}
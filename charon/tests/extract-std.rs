@@ -0,0 +1,23 @@
+#![feature(rustc_private)]
+//! Test of `charon extract-std`. This needs the sources of the standard library (the `rust-src`
+//! component of the toolchain).
+use assert_cmd::prelude::CommandCargoExt;
+use charon_lib::import::read_crate_data;
+use std::process::Command;
+
+#[test]
+fn extract_std() -> anyhow::Result<()> {
+    let dest = tempfile::TempDir::new()?;
+    // We extract a tiny crate of the standard library (it only re-exports `core`) to keep the test
+    // fast; `core` and `alloc` go through the same path.
+    let status = Command::cargo_bin("charon")?
+        .arg("extract-std")
+        .arg("--dest")
+        .arg(dest.path())
+        .arg("rustc-std-workspace-core")
+        .status()?;
+    assert!(status.success());
+    let crate_data = read_crate_data(&dest.path().join("rustc_std_workspace_core.llbc"))?;
+    assert_eq!(crate_data.translated.crate_name, "rustc_std_workspace_core");
+    Ok(())
+}
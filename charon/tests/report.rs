@@ -0,0 +1,61 @@
+#![feature(rustc_private)]
+//! Test of `charon report`: we generate the report of a small crate and check its links.
+use assert_cmd::prelude::CommandCargoExt;
+use std::process::Command;
+
+#[test]
+fn report() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let source = dir.path().join("test_crate.rs");
+    let llbc = dir.path().join("test_crate.llbc");
+    let output = dir.path().join("report");
+    std::fs::write(&source, "fn bar() {}\npub fn foo() {\n    bar()\n}\n")?;
+    let status = Command::cargo_bin("charon")?
+        .arg("--no-cargo")
+        .arg("--input")
+        .arg(&source)
+        .arg("--crate=test_crate")
+        .arg("--dest-file")
+        .arg(&llbc)
+        .status()?;
+    assert!(status.success());
+    let status = Command::cargo_bin("charon")?
+        .arg("report")
+        .arg("--output")
+        .arg(&output)
+        .arg(&llbc)
+        .status()?;
+    assert!(status.success());
+
+    let index = std::fs::read_to_string(output.join("index.html"))?;
+    let pages: Vec<(String, String)> = ["fun-0.html", "fun-1.html"]
+        .into_iter()
+        .map(|page| {
+            let link = format!("<a href=\"{page}\">");
+            let contents = std::fs::read_to_string(output.join(page)).unwrap();
+            (link, contents)
+        })
+        .collect();
+    for (link, _) in &pages {
+        assert!(index.contains(link.as_str()), "{index}");
+    }
+    let page_of = |name: &str| {
+        pages
+            .iter()
+            .find(|(_, contents)| contents.contains(&format!("<h1>{name}</h1>")))
+            .unwrap()
+    };
+    let (foo_link, foo) = page_of("test_crate::foo");
+    let (bar_link, bar) = page_of("test_crate::bar");
+    // The call to `bar` links to its page, and `bar` lists `foo` as a caller.
+    assert!(
+        foo.contains(&format!("{bar_link}test_crate::bar</a>")),
+        "{foo}"
+    );
+    assert!(bar.contains("Referred to by"), "{bar}");
+    assert!(
+        bar.contains(&format!("{foo_link}test_crate::foo</a>")),
+        "{bar}"
+    );
+    Ok(())
+}
@@ -0,0 +1,91 @@
+#![feature(rustc_private)]
+//! Test of `charon serve`: we start the server on a small crate and query it.
+use anyhow::Context;
+use assert_cmd::prelude::CommandCargoExt;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command};
+use std::time::Duration;
+
+/// Kills the server when the test ends, whether it succeeded or not.
+struct KillOnDrop(Child);
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Connect to the server, waiting for it to start.
+fn connect(addr: &str) -> anyhow::Result<TcpStream> {
+    for _ in 0..100 {
+        if let Ok(stream) = TcpStream::connect(addr) {
+            return Ok(stream);
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    TcpStream::connect(addr).with_context(|| format!("could not connect to {addr}"))
+}
+
+#[test]
+fn serve() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    std::fs::write(
+        dir.path().join("test_crate.rs"),
+        "pub fn foo() -> u32 { 42 }\n",
+    )?;
+    // Find a free port.
+    let addr = TcpListener::bind("127.0.0.1:0")?.local_addr()?.to_string();
+
+    let server = Command::cargo_bin("charon")?
+        .current_dir(dir.path())
+        .arg("serve")
+        .arg("--listen")
+        .arg(&addr)
+        .arg("--")
+        .arg("--no-cargo")
+        .arg("--input=test_crate.rs")
+        .arg("--crate=test_crate")
+        .spawn()?;
+    let _server = KillOnDrop(server);
+
+    let stream = connect(&addr)?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut id = 0;
+    let mut request = |method: &str, params: Value| -> anyhow::Result<Value> {
+        id += 1;
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        writeln!(writer, "{request}")?;
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let response: Value = serde_json::from_str(&line)?;
+        assert_eq!(response["id"], id);
+        Ok(response)
+    };
+
+    // The requests are answered once the first extraction is done.
+    let status = request("status", Value::Null)?;
+    assert_eq!(status["result"]["generation"], 1, "{status}");
+    assert_eq!(status["result"]["extraction_error"], Value::Null);
+
+    let items = request("items", Value::Null)?;
+    let names: Vec<&str> = items["result"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["test_crate::foo"]);
+
+    let item = request("item", json!({ "name": "test_crate::foo" }))?;
+    let printed = item["result"][0].as_str().unwrap();
+    assert!(printed.contains("fn test_crate::foo()"), "{printed}");
+    assert!(printed.contains("const (42 : u32)"), "{printed}");
+
+    let error = request("item", Value::Null)?;
+    assert_eq!(error["error"]["message"], "missing parameter `name`");
+    Ok(())
+}
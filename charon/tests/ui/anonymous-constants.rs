@@ -0,0 +1,17 @@
+//! Anonymous constants and inline `const` blocks are translated as globals.
+#![feature(inline_const_pat)]
+
+pub fn inline_const() -> u32 {
+    const { 42 }
+}
+
+pub fn inline_const_pattern(x: u32) -> u32 {
+    match x {
+        const { 42 } => 0,
+        _ => 1,
+    }
+}
+
+pub fn array_length() -> [u8; 1 + 2] {
+    [0; 1 + 2]
+}
@@ -0,0 +1,12 @@
+//@ known-failure
+//@ exit-code=2
+//@ charon-args=--error-summary
+//! When the errors only made some bodies opaque, we exit with code 2.
+const DISGUISED_INT: *const () = 42 as _;
+
+fn bar() {
+    match 43 as *const () {
+        DISGUISED_INT => {}
+        _ => {}
+    }
+}
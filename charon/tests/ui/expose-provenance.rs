@@ -0,0 +1,9 @@
+//! The casts between raw pointers and integers expose the provenance of the pointer, or pick up a
+//! previously exposed provenance.
+pub fn expose(x: &u8) -> usize {
+    x as *const u8 as usize
+}
+
+pub fn with_exposed(addr: usize) -> *const u8 {
+    addr as *const u8
+}
@@ -0,0 +1,12 @@
+//@ charon-args=--extended-asserts
+//! Tests for the assert shapes which are only recognized with `--extended-asserts`.
+
+// The panicking branch is the `else` branch.
+fn flipped() {
+    assert!(false);
+}
+
+// The panicking branch computes the panic message before panicking.
+fn with_message() {
+    assert!(false, "assert failed");
+}
@@ -0,0 +1,14 @@
+//! The names of the trait impls record the implemented trait and the self type.
+struct Foo<T>(T);
+
+trait Trait<U> {
+    fn method(&self);
+}
+
+impl Trait<u8> for Foo<u32> {
+    fn method(&self) {}
+}
+
+impl<T> Trait<T> for Foo<bool> {
+    fn method(&self) {}
+}
@@ -0,0 +1,19 @@
+//@ known-failure
+//@ exit-code=1
+//@ charon-args=--max-errors=1 --error-summary
+//! We stop translating after `--max-errors` errors.
+const DISGUISED_INT: *const () = 42 as _;
+
+fn bar() {
+    match 43 as *const () {
+        DISGUISED_INT => {}
+        _ => {}
+    }
+}
+
+fn baz() {
+    match 44 as *const () {
+        DISGUISED_INT => {}
+        _ => {}
+    }
+}
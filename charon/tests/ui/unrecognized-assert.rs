@@ -0,0 +1,7 @@
+//@ known-failure
+//@ charon-args=--extended-asserts --error-on-warnings
+//! The panicking branch of this assert drops temporaries which are used outside of it, hence it
+//! doesn't match any of the assert shapes and we report it.
+fn same_len(s: &[u32], s2: &[u32]) {
+    assert!(s.len() == s2.len());
+}